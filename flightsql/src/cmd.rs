@@ -1,16 +1,21 @@
 //! IOx FlightSQL Command structures
 
-use std::fmt::Display;
+use std::{fmt::Display, io::Cursor};
 
+use arrow::{
+    ipc::{reader::StreamReader, writer::StreamWriter},
+    record_batch::RecordBatch,
+};
 use arrow_flight::sql::{
     ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest, Any,
     CommandGetCatalogs, CommandGetCrossReference, CommandGetDbSchemas, CommandGetExportedKeys,
     CommandGetImportedKeys, CommandGetPrimaryKeys, CommandGetSqlInfo, CommandGetTableTypes,
     CommandGetTables, CommandGetXdbcTypeInfo, CommandPreparedStatementQuery, CommandStatementQuery,
+    ProstMessageExt,
 };
 use bytes::Bytes;
 use prost::Message;
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 
 use crate::error::*;
 
@@ -21,11 +26,36 @@ use crate::error::*;
 pub struct PreparedStatementHandle {
     /// The raw SQL query text
     query: String,
+    /// A single row containing the values of the query parameters,
+    /// if they have been bound with a `DoPut` request
+    params: Option<RecordBatch>,
+}
+
+/// The encoded form of a [`PreparedStatementHandle`]
+#[derive(Clone, PartialEq, Message)]
+struct EncodedPreparedStatementHandle {
+    #[prost(string, tag = "1")]
+    query: String,
+    /// The parameter values, encoded as an Arrow IPC stream, or empty
+    /// if no parameters have been bound
+    #[prost(bytes = "bytes", tag = "2")]
+    params: Bytes,
 }
 
 impl PreparedStatementHandle {
     pub fn new(query: String) -> Self {
-        Self { query }
+        Self {
+            query,
+            params: None,
+        }
+    }
+
+    /// Binds the parameter values in `params`, replacing any that were previously bound
+    pub fn with_params(self, params: RecordBatch) -> Self {
+        Self {
+            params: Some(params),
+            ..self
+        }
     }
 
     /// return the query
@@ -33,16 +63,43 @@ impl PreparedStatementHandle {
         self.query.as_ref()
     }
 
+    /// return the bound parameter values, if any
+    pub fn params(&self) -> Option<&RecordBatch> {
+        self.params.as_ref()
+    }
+
     fn try_decode(handle: Bytes) -> Result<Self> {
-        // Note: in IOx  handles are the entire decoded query
-        // It will likely need to get more sophisticated as part of
-        // https://github.com/influxdata/influxdb_iox/issues/6699
-        let query = String::from_utf8(handle.to_vec()).context(InvalidHandleSnafu)?;
-        Ok(Self { query })
+        let EncodedPreparedStatementHandle { query, params } =
+            Message::decode(handle).context(InvalidHandleSnafu)?;
+
+        let params = if params.is_empty() {
+            None
+        } else {
+            let mut reader = StreamReader::try_new(Cursor::new(params), None)?;
+            Some(reader.next().transpose()?.context(InvalidParametersSnafu {
+                description: "missing parameter values",
+            })?)
+        };
+
+        Ok(Self { query, params })
     }
 
-    fn encode(self) -> Bytes {
-        Bytes::from(self.query.into_bytes())
+    /// Encode the handle, to be passed back to the client
+    pub fn try_encode(self) -> Result<Bytes> {
+        let params = match self.params {
+            Some(params) => {
+                let mut writer = StreamWriter::try_new(Vec::new(), &params.schema())?;
+                writer.write(&params)?;
+                writer.into_inner()?.into()
+            }
+            None => Bytes::new(),
+        };
+
+        let encoded = EncodedPreparedStatementHandle {
+            query: self.query,
+            params,
+        };
+        Ok(encoded.encode_to_vec().into())
     }
 }
 
@@ -52,13 +109,73 @@ impl Display for PreparedStatementHandle {
     }
 }
 
-/// Encode a PreparedStatementHandle as Bytes
-impl From<PreparedStatementHandle> for Bytes {
-    fn from(value: PreparedStatementHandle) -> Self {
-        Self::from(value.query.into_bytes())
+/// Request to ingest the record batches sent with `DoPut` into a
+/// table.
+///
+/// Defined by the FlightSQL protocol, but not yet by the version of
+/// `arrow-flight` in use.
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandStatementIngest {
+    /// What to do if the table does or does not exist
+    #[prost(message, optional, tag = "1")]
+    pub table_definition_options: Option<TableDefinitionOptions>,
+    /// The table to ingest into
+    #[prost(string, tag = "2")]
+    pub table: String,
+    /// The schema of the table, if not the default
+    #[prost(string, optional, tag = "3")]
+    pub schema: Option<String>,
+    /// The catalog of the table, if not the default
+    #[prost(string, optional, tag = "4")]
+    pub catalog: Option<String>,
+    /// Ingest into a temporary table
+    #[prost(bool, tag = "5")]
+    pub temporary: bool,
+    /// The transaction to ingest in, if any
+    #[prost(string, optional, tag = "6")]
+    pub transaction_id: Option<String>,
+    /// Backend specific options
+    #[prost(map = "string, string", tag = "1000")]
+    pub options: std::collections::HashMap<String, String>,
+}
+
+/// Options for [`CommandStatementIngest`] when the table does or does
+/// not exist.
+#[derive(Clone, Copy, PartialEq, Message)]
+pub struct TableDefinitionOptions {
+    /// `0`: unspecified, `1`: create the table, `2`: fail
+    #[prost(int32, tag = "1")]
+    pub if_not_exist: i32,
+    /// `0`: unspecified, `1`: fail, `2`: append, `3`: replace the table
+    #[prost(int32, tag = "2")]
+    pub if_exists: i32,
+}
+
+impl ProstMessageExt for CommandStatementIngest {
+    fn type_url() -> &'static str {
+        "type.googleapis.com/arrow.flight.protocol.sql.CommandStatementIngest"
+    }
+
+    fn as_any(&self) -> Any {
+        Any {
+            type_url: Self::type_url().to_string(),
+            value: self.encode_to_vec().into(),
+        }
     }
 }
 
+/// The `app_metadata` of the [`arrow_flight::PutResult`] returned for
+/// each batch ingested with `DoPut`.
+///
+/// Defined by the FlightSQL protocol, but not yet by the version of
+/// `arrow-flight` in use.
+#[derive(Clone, Copy, PartialEq, Message)]
+pub struct DoPutUpdateResult {
+    /// The number of records written
+    #[prost(int64, tag = "1")]
+    pub record_count: i64,
+}
+
 /// Decoded / validated FlightSQL command messages
 ///
 /// Handles encoding/decoding prost::Any messages back
@@ -103,6 +220,8 @@ pub enum FlightSQLCommand {
     ActionCreatePreparedStatementRequest(ActionCreatePreparedStatementRequest),
     /// Close a prepared statement
     ActionClosePreparedStatementRequest(PreparedStatementHandle),
+    /// Ingest record batches into a table
+    CommandStatementIngest(CommandStatementIngest),
 }
 
 impl Display for FlightSQLCommand {
@@ -243,6 +362,9 @@ impl Display for FlightSQLCommand {
             Self::ActionClosePreparedStatementRequest(h) => {
                 write!(f, "ActionClosePreparedStatementRequest{h}")
             }
+            Self::CommandStatementIngest(CommandStatementIngest { table, .. }) => {
+                write!(f, "CommandStatementIngest(table={table})")
+            }
         }
     }
 }
@@ -293,6 +415,8 @@ impl FlightSQLCommand {
             } = decoded_cmd;
             let handle = PreparedStatementHandle::try_decode(prepared_statement_handle)?;
             Ok(Self::ActionClosePreparedStatementRequest(handle))
+        } else if let Some(decoded_cmd) = Any::unpack::<CommandStatementIngest>(&msg)? {
+            Ok(Self::CommandStatementIngest(decoded_cmd))
         } else {
             UnsupportedMessageTypeSnafu {
                 description: &msg.type_url,
@@ -306,7 +430,7 @@ impl FlightSQLCommand {
         let msg = match self {
             Self::CommandStatementQuery(cmd) => Any::pack(&cmd),
             Self::CommandPreparedStatementQuery(handle) => {
-                let prepared_statement_handle = handle.encode();
+                let prepared_statement_handle = handle.try_encode()?;
                 let cmd = CommandPreparedStatementQuery {
                     prepared_statement_handle,
                 };
@@ -324,12 +448,49 @@ impl FlightSQLCommand {
             Self::CommandGetXdbcTypeInfo(cmd) => Any::pack(&cmd),
             Self::ActionCreatePreparedStatementRequest(cmd) => Any::pack(&cmd),
             Self::ActionClosePreparedStatementRequest(handle) => {
-                let prepared_statement_handle = handle.encode();
+                let prepared_statement_handle = handle.try_encode()?;
                 Any::pack(&ActionClosePreparedStatementRequest {
                     prepared_statement_handle,
                 })
             }
+            Self::CommandStatementIngest(cmd) => Any::pack(&cmd),
         }?;
         Ok(msg.encode_to_vec().into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int64Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn prepared_statement_handle_round_trip() {
+        let handle = PreparedStatementHandle::new("SELECT * FROM cpu WHERE host = $1".into());
+        let cmd = FlightSQLCommand::CommandPreparedStatementQuery(handle.clone());
+        let decoded = FlightSQLCommand::try_decode(cmd.clone().try_encode().unwrap()).unwrap();
+        assert_eq!(decoded, cmd);
+
+        let params = RecordBatch::try_from_iter([
+            ("$1", Arc::new(StringArray::from(vec!["server01"])) as _),
+            ("$2", Arc::new(Int64Array::from(vec![42])) as _),
+        ])
+        .unwrap();
+        let cmd = FlightSQLCommand::CommandPreparedStatementQuery(handle.with_params(params));
+        let decoded = FlightSQLCommand::try_decode(cmd.clone().try_encode().unwrap()).unwrap();
+        assert_eq!(decoded, cmd);
+    }
+
+    #[test]
+    fn prepared_statement_handle_invalid() {
+        let cmd = CommandPreparedStatementQuery {
+            prepared_statement_handle: Bytes::from_static(b"\xff\xff"),
+        };
+        let msg: Bytes = Any::pack(&cmd).unwrap().encode_to_vec().into();
+        let err = FlightSQLCommand::try_decode(msg).unwrap_err();
+        assert!(matches!(err, Error::InvalidHandle { .. }), "{err}");
+    }
+}
@@ -1,6 +1,4 @@
 //! FlightSQL errors
-use std::string::FromUtf8Error;
-
 use arrow::error::ArrowError;
 use arrow_flight::error::FlightError;
 use datafusion::error::DataFusionError;
@@ -16,8 +14,11 @@ pub enum Error {
     #[snafu(context(false))]
     Decode { source: DecodeError },
 
-    #[snafu(display("Invalid PreparedStatement handle (invalid UTF-8:) {}", source))]
-    InvalidHandle { source: FromUtf8Error },
+    #[snafu(display("Invalid PreparedStatement handle: {}", source))]
+    InvalidHandle { source: DecodeError },
+
+    #[snafu(display("Invalid PreparedStatement parameters: {}", description))]
+    InvalidParameters { description: String },
 
     #[snafu(display("{}", source))]
     #[snafu(context(false))]
@@ -23,6 +23,9 @@ mod planner;
 mod sql_info;
 mod xdbc_type_info;
 
-pub use cmd::{FlightSQLCommand, PreparedStatementHandle};
+pub use cmd::{
+    CommandStatementIngest, DoPutUpdateResult, FlightSQLCommand, PreparedStatementHandle,
+    TableDefinitionOptions,
+};
 pub use error::{Error, Result};
 pub use planner::FlightSQLPlanner;
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use arrow::{
     array::{ArrayRef, StringArray},
+    compute::concat_batches,
     datatypes::{DataType, Field, Schema, SchemaRef},
     error::ArrowError,
     ipc::writer::IpcWriteOptions,
@@ -22,6 +23,7 @@ use bytes::Bytes;
 use datafusion::{
     logical_expr::{LogicalPlan, TableType},
     physical_plan::ExecutionPlan,
+    scalar::ScalarValue,
     sql::TableReference,
 };
 use iox_query::{exec::IOxSessionContext, QueryNamespace};
@@ -54,9 +56,9 @@ impl FlightSQLPlanner {
             FlightSQLCommand::CommandStatementQuery(CommandStatementQuery { query, .. }) => {
                 get_schema_for_query(&query, ctx).await
             }
-            FlightSQLCommand::CommandPreparedStatementQuery(handle) => {
-                get_schema_for_query(handle.query(), ctx).await
-            }
+            FlightSQLCommand::CommandPreparedStatementQuery(handle) => Ok(get_schema_for_plan(
+                plan_prepared_statement(&handle, ctx).await?,
+            )),
             FlightSQLCommand::CommandGetSqlInfo(CommandGetSqlInfo { .. }) => {
                 Ok(iox_sql_info_data().schema())
             }
@@ -82,7 +84,8 @@ impl FlightSQLPlanner {
                 Ok(Arc::clone(&GET_XDBC_TYPE_INFO_SCHEMA))
             }
             FlightSQLCommand::ActionCreatePreparedStatementRequest(_)
-            | FlightSQLCommand::ActionClosePreparedStatementRequest(_) => ProtocolSnafu {
+            | FlightSQLCommand::ActionClosePreparedStatementRequest(_)
+            | FlightSQLCommand::CommandStatementIngest(_) => ProtocolSnafu {
                 cmd: format!("{cmd:?}"),
                 method: "GetFlightInfo",
             }
@@ -108,7 +111,8 @@ impl FlightSQLPlanner {
             FlightSQLCommand::CommandPreparedStatementQuery(handle) => {
                 let query = handle.query();
                 debug!(%query, "Planning FlightSQL prepared query");
-                Ok(ctx.sql_to_physical_plan(query).await?)
+                let plan = plan_prepared_statement(&handle, ctx).await?;
+                Ok(ctx.create_physical_plan(&plan).await?)
             }
             FlightSQLCommand::CommandGetSqlInfo(cmd) => {
                 debug!(?cmd, "Planning GetSqlInfo query");
@@ -223,7 +227,8 @@ impl FlightSQLPlanner {
                 Ok(ctx.create_physical_plan(&plan).await?)
             }
             FlightSQLCommand::ActionClosePreparedStatementRequest(_)
-            | FlightSQLCommand::ActionCreatePreparedStatementRequest(_) => ProtocolSnafu {
+            | FlightSQLCommand::ActionCreatePreparedStatementRequest(_)
+            | FlightSQLCommand::CommandStatementIngest(_) => ProtocolSnafu {
                 cmd: format!("{cmd:?}"),
                 method: "DoGet",
             }
@@ -249,18 +254,17 @@ impl FlightSQLPlanner {
             ) => {
                 debug!(%query, "Creating prepared statement");
 
-                // todo run the planner here and actually figure out parameter schemas
-                // see https://github.com/apache/arrow-datafusion/pull/4701
-                let parameter_schema = vec![];
+                let plan = ctx.sql_to_logical_plan(&query).await?;
+                let parameter_schema = encode_parameter_schema(&plan)?;
 
-                let dataset_schema = get_schema_for_query(&query, ctx).await?;
+                let dataset_schema = get_schema_for_plan(plan);
                 let dataset_schema = encode_schema(dataset_schema.as_ref())?;
                 let handle = PreparedStatementHandle::new(query);
 
                 let result = ActionCreatePreparedStatementResult {
-                    prepared_statement_handle: Bytes::from(handle),
+                    prepared_statement_handle: handle.try_encode()?,
                     dataset_schema,
-                    parameter_schema: Bytes::from(parameter_schema),
+                    parameter_schema,
                 };
 
                 let msg = Any::pack(&result)?;
@@ -280,6 +284,111 @@ impl FlightSQLPlanner {
             .fail(),
         }
     }
+
+    /// Handles the `DoPut` request `cmd`, where `data` holds the record
+    /// batches sent by the client, and returns bytes for the
+    /// `app_metadata` of the [`arrow_flight::PutResult`].
+    ///
+    /// A `CommandPreparedStatementQuery` binds the single row in `data`
+    /// as the values of the parameters of the prepared statement, and
+    /// returns a `DoPutPreparedStatementResult` with the updated handle,
+    /// which the client uses for subsequent requests.
+    pub async fn do_put(
+        namespace_name: impl Into<String> + Send,
+        _database: Arc<dyn QueryNamespace>,
+        cmd: FlightSQLCommand,
+        data: Vec<RecordBatch>,
+        _ctx: &IOxSessionContext,
+    ) -> Result<Bytes> {
+        let namespace_name = namespace_name.into();
+        debug!(%namespace_name, %cmd, "Handling flightsql do_put");
+
+        match cmd {
+            FlightSQLCommand::CommandPreparedStatementQuery(handle) => {
+                let query = handle.query();
+                debug!(%query, "Binding prepared statement parameters");
+
+                let num_rows = data.iter().map(|b| b.num_rows()).sum::<usize>();
+                if num_rows != 1 {
+                    return InvalidParametersSnafu {
+                        description: format!(
+                            "expected a single row of parameter values, got {num_rows}"
+                        ),
+                    }
+                    .fail();
+                }
+                let schema = data[0].schema();
+                let params = concat_batches(&schema, &data)?;
+
+                let result = DoPutPreparedStatementResult {
+                    prepared_statement_handle: Some(handle.with_params(params).try_encode()?),
+                };
+                Ok(result.encode_to_vec().into())
+            }
+            _ => ProtocolSnafu {
+                cmd: format!("{cmd:?}"),
+                method: "DoPut",
+            }
+            .fail(),
+        }
+    }
+}
+
+/// The result of binding the parameters of a prepared statement with
+/// `DoPut`, returned in the `app_metadata` of the `PutResult`.
+///
+/// Defined by the FlightSQL protocol, but not yet by the version of
+/// `arrow-flight` in use.
+#[derive(Clone, PartialEq, Message)]
+struct DoPutPreparedStatementResult {
+    /// The updated handle, to be used in place of the original one
+    #[prost(bytes = "bytes", optional, tag = "1")]
+    prepared_statement_handle: Option<Bytes>,
+}
+
+/// Return the `LogicalPlan` for the prepared statement `handle`, with
+/// the placeholders, such as `$1`, replaced by any bound parameter values
+async fn plan_prepared_statement(
+    handle: &PreparedStatementHandle,
+    ctx: &IOxSessionContext,
+) -> Result<LogicalPlan> {
+    let plan = ctx.sql_to_logical_plan(handle.query()).await?;
+
+    let Some(params) = handle.params() else {
+        return Ok(plan);
+    };
+
+    let param_values = params
+        .columns()
+        .iter()
+        .map(|array| ScalarValue::try_from_array(array, 0))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(plan.with_param_values(param_values)?)
+}
+
+/// Return the IPC encoded schema of the placeholders in `plan`, ordered
+/// by their position (`$1`, `$2`, ...), or empty bytes if there are none
+fn encode_parameter_schema(plan: &LogicalPlan) -> Result<Bytes> {
+    let mut parameters = plan.get_parameter_types()?.into_iter().collect::<Vec<_>>();
+    if parameters.is_empty() {
+        return Ok(Bytes::new());
+    }
+
+    parameters.sort_by_cached_key(|(id, _)| {
+        (
+            id.trim_start_matches('$')
+                .parse::<usize>()
+                .unwrap_or(usize::MAX),
+            id.clone(),
+        )
+    });
+
+    let fields = parameters
+        .into_iter()
+        .map(|(id, data_type)| Field::new(id, data_type.unwrap_or(DataType::Null), true))
+        .collect::<Vec<_>>();
+
+    encode_schema(&Schema::new(fields))
 }
 
 /// Return the schema for the specified query
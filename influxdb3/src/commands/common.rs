@@ -0,0 +1,38 @@
+//! Options shared by the commands that talk to a running InfluxDB 3.0 server
+
+/// The default URL of the InfluxDB 3.0 server the client commands connect to.
+pub const DEFAULT_HOST_URL: &str = "http://127.0.0.1:8181";
+
+#[derive(Debug, clap::Parser)]
+pub struct InfluxDb3Config {
+    /// The host URL of the running InfluxDB 3.0 server
+    #[clap(
+    short = 'H',
+    long = "host",
+    env = "INFLUXDB3_HOST_URL",
+    default_value = DEFAULT_HOST_URL,
+    action,
+    )]
+    pub host_url: String,
+
+    /// The name of the database to operate on
+    #[clap(short = 'd', long = "dbname", env = "INFLUXDB3_DATABASE_NAME", action)]
+    pub database_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn host_short_flag_does_not_conflict_with_help() {
+        InfluxDb3Config::command().debug_assert();
+
+        let config =
+            InfluxDb3Config::try_parse_from(["test", "-H", "http://localhost:9999", "-d", "foo"])
+                .unwrap();
+        assert_eq!(config.host_url, "http://localhost:9999");
+        assert_eq!(config.database_name, "foo");
+    }
+}
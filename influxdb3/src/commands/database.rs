@@ -0,0 +1,51 @@
+//! Commands for managing databases on a running InfluxDB 3.0 server
+
+use super::common::InfluxDb3Config;
+use influxdb3_client::Client;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("client error: {0}")]
+    Client(#[from] influxdb3_client::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Parser)]
+enum Command {
+    /// Create a new, empty database
+    Create(InfluxDb3Config),
+
+    /// Delete a database and all of its data
+    Delete(InfluxDb3Config),
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    match config.command {
+        Command::Create(InfluxDb3Config {
+            host_url,
+            database_name,
+        }) => {
+            let client = Client::new(host_url)?;
+            client.api_v3_configure_db_create(&database_name).await?;
+            println!("Database {database_name:?} created successfully");
+        }
+        Command::Delete(InfluxDb3Config {
+            host_url,
+            database_name,
+        }) => {
+            let client = Client::new(host_url)?;
+            client.api_v3_configure_db_delete(&database_name).await?;
+            println!("Database {database_name:?} deleted successfully");
+        }
+    }
+
+    Ok(())
+}
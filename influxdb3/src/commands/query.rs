@@ -0,0 +1,98 @@
+//! Run a SQL or InfluxQL query against a running InfluxDB 3.0 server
+
+use super::common::InfluxDb3Config;
+use influxdb3_client::{Client, QueryLanguage};
+use influxdb_iox_client::format::QueryOutputFormat;
+use std::io::Write;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("client error: {0}")]
+    Client(#[from] influxdb3_client::Error),
+
+    #[error("error writing query results: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Sql,
+    InfluxQl,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sql" => Ok(Self::Sql),
+            "influxql" => Ok(Self::InfluxQl),
+            _ => Err(format!(
+                "Unknown query language: {s}. Expected one of 'sql' or 'influxql'"
+            )),
+        }
+    }
+}
+
+impl From<Language> for QueryLanguage {
+    fn from(language: Language) -> Self {
+        match language {
+            Language::Sql => Self::Sql,
+            Language::InfluxQl => Self::InfluxQl,
+        }
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(flatten)]
+    influxdb3_config: InfluxDb3Config,
+
+    /// The query language used to write the query, either `sql` or `influxql`
+    #[clap(short = 'l', long = "lang", default_value = "sql", action)]
+    language: Language,
+
+    /// The format in which to output the query results, one of `pretty`, `csv` or `json`
+    #[clap(long = "format", default_value = "pretty", action)]
+    output_format: QueryOutputFormat,
+
+    /// The query to run
+    #[clap(required = true, action)]
+    query: Vec<String>,
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    let Config {
+        influxdb3_config:
+            InfluxDb3Config {
+                host_url,
+                database_name,
+            },
+        language,
+        output_format,
+        query,
+    } = config;
+
+    let client = Client::new(host_url)?;
+    let query = query.join(" ");
+    let results = client
+        .api_v3_query(
+            &database_name,
+            &query,
+            language.into(),
+            output_format.to_string(),
+        )
+        .await?;
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(&results)?;
+    if !results.ends_with(b"\n") {
+        writeln!(stdout)?;
+    }
+
+    Ok(())
+}
@@ -7,11 +7,16 @@ use clap_blocks::{
     object_store::{make_object_store, ObjectStoreConfig},
     socket_addr::SocketAddr,
 };
-use influxdb3_server::{query_executor::QueryExecutorImpl, serve, CommonServerState, Server};
+use influxdb3_server::{
+    continuous_query::ContinuousQueryScheduler, query_executor::QueryExecutorImpl, serve,
+    tls::TlsConfig, wait_for_signal, CommonServerState, Server,
+};
 use influxdb3_write::persister::PersisterImpl;
 use influxdb3_write::wal::WalImpl;
 use influxdb3_write::write_buffer::WriteBufferImpl;
+use influxdb3_write::Bufferer;
 use iox_query::exec::{Executor, ExecutorConfig};
+use iox_time::{SystemProvider, TimeProvider};
 use ioxd_common::reexport::trace_http::ctx::TraceHeaderParser;
 use object_store::DynObjectStore;
 use observability_deps::tracing::*;
@@ -22,6 +27,7 @@ use std::{
     num::NonZeroUsize,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use thiserror::Error;
 use tokio_util::sync::CancellationToken;
@@ -48,6 +54,9 @@ pub enum Error {
 
     #[error("Wal error: {0}")]
     Wal(#[from] influxdb3_write::wal::Error),
+
+    #[error("Error loading the persisted catalog and segments: {0}")]
+    LoadPersistedState(influxdb3_write::Error),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -63,6 +72,37 @@ pub struct Config {
     )]
     pub max_http_request_size: usize,
 
+    /// Maximum time to spend persisting the open segment when shutting down.
+    ///
+    /// Data that has not been persisted when this deadline passes is only recoverable from the
+    /// WAL, if one is configured.
+    #[clap(
+        long = "shutdown-timeout",
+        env = "INFLUXDB3_SHUTDOWN_TIMEOUT",
+        default_value = "30s",
+        value_parser = humantime::parse_duration,
+    )]
+    pub shutdown_timeout: Duration,
+
+    /// How long after the end of each interval to run continuous queries, to allow for late
+    /// arriving data.
+    #[clap(
+        long = "continuous-query-lag",
+        env = "INFLUXDB3_CONTINUOUS_QUERY_LAG",
+        default_value = "0s",
+        value_parser = humantime::parse_duration,
+    )]
+    pub continuous_query_lag: Duration,
+
+    /// How often to check for continuous queries that are due to run.
+    #[clap(
+        long = "continuous-query-check-interval",
+        env = "INFLUXDB3_CONTINUOUS_QUERY_CHECK_INTERVAL",
+        default_value = "1s",
+        value_parser = parse_check_interval,
+    )]
+    pub continuous_query_check_interval: Duration,
+
     #[clap(flatten)]
     object_store_config: ObjectStoreConfig,
 
@@ -81,6 +121,38 @@ pub struct Config {
     )]
     pub http_bind_address: SocketAddr,
 
+    /// Path to a PEM encoded certificate chain used to serve the API over TLS.
+    ///
+    /// Requires `--tls-key`. Send `SIGHUP` to the server to reload the certificate and key.
+    #[clap(
+        long = "tls-cert",
+        env = "INFLUXDB3_TLS_CERT",
+        requires = "tls_key",
+        action
+    )]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded private key for `--tls-cert`.
+    #[clap(
+        long = "tls-key",
+        env = "INFLUXDB3_TLS_KEY",
+        requires = "tls_cert",
+        action
+    )]
+    pub tls_key: Option<PathBuf>,
+
+    /// Path to a PEM encoded set of CA certificates used to verify client certificates.
+    ///
+    /// If set, clients must present a certificate signed by one of these CAs. Requires
+    /// `--tls-cert`.
+    #[clap(
+        long = "tls-client-ca",
+        env = "INFLUXDB3_TLS_CLIENT_CA",
+        requires = "tls_cert",
+        action
+    )]
+    pub tls_client_ca: Option<PathBuf>,
+
     /// Size of the RAM cache used to store data in bytes.
     ///
     /// Can be given as absolute value or in percentage of the total available memory (e.g. `10%`).
@@ -226,40 +298,94 @@ pub async fn command(config: Config) -> Result<()> {
         )
         .with_jaeger_debug_name(config.tracing_config.traces_jaeger_debug_name);
 
+    let tls_config = config
+        .tls_cert
+        .zip(config.tls_key)
+        .map(|(cert_path, key_path)| TlsConfig {
+            cert_path,
+            key_path,
+            client_ca_path: config.tls_client_ca,
+        });
+
+    let time_provider: Arc<dyn TimeProvider> = Arc::new(SystemProvider::new());
     let common_state = CommonServerState::new(
         Arc::clone(&metrics),
         trace_exporter,
         trace_header_parser,
         *config.http_bind_address,
+        tls_config,
+        Arc::clone(&time_provider),
     );
-    let catalog = Arc::new(influxdb3_write::catalog::Catalog::new());
+    let persister = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+
+    // Continue from the catalog persisted with the last segment, which includes the state of
+    // continuous queries, so that they neither skip nor repeat intervals across restarts.
     let wal: Option<Arc<WalImpl>> = config
         .wal_directory
         .map(|dir| WalImpl::new(dir).map(Arc::new))
         .transpose()?;
-    let write_buffer = Arc::new(WriteBufferImpl::new(Arc::clone(&catalog), wal));
-    let query_executor = QueryExecutorImpl::new(
-        catalog,
+    let write_buffer = Arc::new(
+        WriteBufferImpl::load(
+            Arc::clone(&persister) as _,
+            parquet_store.object_store_url(),
+            wal,
+            Arc::clone(&time_provider),
+        )
+        .await
+        .map_err(Error::LoadPersistedState)?,
+    );
+    let query_executor = Arc::new(QueryExecutorImpl::new(
+        write_buffer.catalog(),
         Arc::clone(&write_buffer),
         Arc::clone(&exec),
         Arc::clone(&metrics),
         Arc::new(config.datafusion_config),
         10,
+        Arc::clone(&time_provider),
+    ));
+
+    let continuous_queries = ContinuousQueryScheduler::new(
+        Arc::clone(&write_buffer),
+        Arc::clone(&query_executor),
+        time_provider,
+        config.continuous_query_lag,
+        config.continuous_query_check_interval,
     );
 
-    let persister = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
     let server = Server::new(
         common_state,
         persister,
         Arc::clone(&write_buffer),
-        Arc::new(query_executor),
+        query_executor,
         config.max_http_request_size,
-    );
+        config.shutdown_timeout,
+    )
+    .with_continuous_queries(continuous_queries);
+
+    let signal_shutdown = frontend_shutdown.clone();
+    tokio::spawn(async move {
+        wait_for_signal().await;
+        info!("shutting down, no longer accepting requests");
+        signal_shutdown.cancel();
+    });
+
     serve(server, frontend_shutdown).await?;
+    info!("shutdown complete");
 
     Ok(())
 }
 
+/// Parse the continuous query check interval, which must be greater than zero.
+fn parse_check_interval(
+    s: &str,
+) -> Result<Duration, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let interval = humantime::parse_duration(s)?;
+    if interval.is_zero() {
+        return Err("the check interval must be greater than zero".into());
+    }
+    Ok(interval)
+}
+
 fn parse_datafusion_config(
     s: &str,
 ) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -0,0 +1,196 @@
+//! Write line protocol from a file to a running InfluxDB 3.0 server
+
+use super::common::InfluxDb3Config;
+use clap_blocks::memory_size::MemorySize;
+use flate2::{write::GzEncoder, Compression};
+use influxdb3_client::Client;
+use observability_deps::tracing::debug;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("error reading file {path:?}: {source}")]
+    ReadingFile { path: PathBuf, source: io::Error },
+
+    #[error("error compressing line protocol: {0}")]
+    Gzip(io::Error),
+
+    #[error("client error: {0}")]
+    Client(#[from] influxdb3_client::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(flatten)]
+    influxdb3_config: InfluxDb3Config,
+
+    /// Maximum size of line protocol to send in a single request, before compression.
+    ///
+    /// Large files are streamed to the server in chunks of at most this size, split on line
+    /// boundaries. A single line that is larger than this is sent in a request of its own.
+    #[clap(
+    long = "max-request-size",
+    env = "INFLUXDB3_WRITE_MAX_REQUEST_SIZE",
+    default_value = "1048576", // 1 MiB
+    action,
+    )]
+    max_request_size: MemorySize,
+
+    /// Send the line protocol uncompressed rather than gzip compressed
+    #[clap(long = "no-gzip", action)]
+    no_gzip: bool,
+
+    /// The file containing line protocol to write
+    #[clap(value_name = "FILE", action)]
+    file_path: PathBuf,
+}
+
+pub async fn command(config: Config) -> Result<()> {
+    let Config {
+        influxdb3_config:
+            InfluxDb3Config {
+                host_url,
+                database_name,
+            },
+        max_request_size,
+        no_gzip,
+        file_path,
+    } = config;
+
+    let client = Client::new(host_url)?;
+    let file = File::open(&file_path).map_err(|source| Error::ReadingFile {
+        path: file_path.clone(),
+        source,
+    })?;
+    let mut chunks = LineChunker::new(BufReader::new(file), max_request_size.bytes());
+
+    let mut total_bytes = 0;
+    let mut requests = 0;
+    while let Some(chunk) = chunks.next_chunk().map_err(|source| Error::ReadingFile {
+        path: file_path.clone(),
+        source,
+    })? {
+        total_bytes += chunk.len();
+        requests += 1;
+        debug!(bytes = chunk.len(), request = requests, "writing chunk");
+
+        if no_gzip {
+            client.api_v3_write_lp(&database_name, chunk, false).await?;
+        } else {
+            let body = gzip(chunk.as_bytes()).map_err(Error::Gzip)?;
+            client.api_v3_write_lp(&database_name, body, true).await?;
+        }
+    }
+
+    println!("Wrote {total_bytes} bytes of line protocol in {requests} request(s)");
+
+    Ok(())
+}
+
+fn gzip(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(data.len() / 4), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Reads line protocol from `reader` in chunks of roughly `max_chunk_size` bytes, only ever
+/// splitting between complete lines so that no line is sent across two requests.
+#[derive(Debug)]
+struct LineChunker<R> {
+    reader: R,
+    max_chunk_size: usize,
+    /// Data read past the end of the previous chunk that has not been sent yet
+    remainder: String,
+    eof: bool,
+}
+
+impl<R: BufRead> LineChunker<R> {
+    fn new(reader: R, max_chunk_size: usize) -> Self {
+        Self {
+            reader,
+            max_chunk_size,
+            remainder: String::new(),
+            eof: false,
+        }
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Option<String>> {
+        let mut buf = std::mem::take(&mut self.remainder);
+        while !self.eof {
+            if self.reader.read_line(&mut buf)? == 0 {
+                self.eof = true;
+                break;
+            }
+            if buf.len() < self.max_chunk_size {
+                continue;
+            }
+
+            // A newline may be inside a quoted string field, so use the line protocol splitter
+            // to find where the last, possibly incomplete, line starts and hold it back for the
+            // next chunk. If that is the start of the buffer there is no complete line yet.
+            let last_line_start = influxdb_line_protocol::split_lines(&buf)
+                .last()
+                .map(|line| line.as_ptr() as usize - buf.as_ptr() as usize)
+                .unwrap_or_default();
+            if last_line_start > 0 {
+                self.remainder = buf.split_off(last_line_start);
+                break;
+            }
+        }
+
+        if self.eof && buf.trim().is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(input: &str, max_chunk_size: usize) -> Vec<String> {
+        let mut chunker = LineChunker::new(input.as_bytes(), max_chunk_size);
+        let mut chunks = vec![];
+        while let Some(chunk) = chunker.next_chunk().unwrap() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    #[test]
+    fn single_chunk() {
+        let lp = "cpu usage=1 1\ncpu usage=2 2\n";
+        assert_eq!(chunks(lp, 1024), vec![lp.to_string()]);
+    }
+
+    #[test]
+    fn splits_on_line_boundaries() {
+        let lp = "cpu usage=1 1\ncpu usage=2 2\ncpu usage=3 3\n";
+        let chunks = chunks(lp, 20);
+        assert!(chunks.len() > 1, "{chunks:?}");
+        assert_eq!(chunks.concat(), lp);
+        for chunk in &chunks {
+            assert!(chunk.ends_with('\n'), "{chunk:?}");
+        }
+    }
+
+    #[test]
+    fn does_not_split_quoted_newlines() {
+        let lp = "log msg=\"line one\nline two\" 1\nlog msg=\"other\" 2\n";
+        let chunks = chunks(lp, 16);
+        assert_eq!(chunks.concat(), lp);
+        assert!(
+            chunks.iter().any(|c| c.contains("line one\nline two")),
+            "{chunks:?}"
+        );
+    }
+}
@@ -25,7 +25,11 @@ use trogging::{
 };
 
 mod commands {
+    pub(crate) mod common;
+    pub mod database;
+    pub mod query;
     pub mod serve;
+    pub mod write;
 }
 
 #[cfg(all(not(feature = "heappy"), feature = "jemalloc_replacing_malloc"))]
@@ -71,6 +75,16 @@ Examples:
 
     # Run InfluxDB 3.0 Edge with full debug logging specified with LOG_FILTER
     LOG_FILTER=debug influxdb3 serve
+
+    # Write line protocol from a file to the database "mydb"
+    influxdb3 write --dbname mydb data.lp
+
+    # Query the database "mydb" and output the results as CSV
+    influxdb3 query --dbname mydb --format csv "SELECT * FROM cpu"
+
+    # Create or delete the database "mydb"
+    influxdb3 database create --dbname mydb
+    influxdb3 database delete --dbname mydb
 "#
 )]
 struct Config {
@@ -82,6 +96,15 @@ struct Config {
 enum Command {
     /// Run the InfluxDB 3.0 server
     Serve(commands::serve::Config),
+
+    /// Write line protocol from a file to a running InfluxDB 3.0 server
+    Write(commands::write::Config),
+
+    /// Run a SQL or InfluxQL query against a running InfluxDB 3.0 server
+    Query(commands::query::Config),
+
+    /// Create and delete databases on a running InfluxDB 3.0 server
+    Database(commands::database::Config),
 }
 
 fn main() -> Result<(), std::io::Error> {
@@ -114,6 +137,24 @@ fn main() -> Result<(), std::io::Error> {
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
+            Some(Command::Write(config)) => {
+                if let Err(e) = commands::write::command(config).await {
+                    eprintln!("Write command failed: {e}");
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
+            Some(Command::Query(config)) => {
+                if let Err(e) = commands::query::command(config).await {
+                    eprintln!("Query command failed: {e}");
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
+            Some(Command::Database(config)) => {
+                if let Err(e) = commands::database::command(config).await {
+                    eprintln!("Database command failed: {e}");
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
         }
     });
 
@@ -0,0 +1,250 @@
+//! An HTTP client for the InfluxDB 3.0 Edge API.
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::bare_urls,
+    rust_2018_idioms,
+    missing_debug_implementations,
+    unreachable_pub
+)]
+#![warn(
+    missing_docs,
+    clippy::todo,
+    clippy::dbg_macro,
+    clippy::clone_on_ref_ptr,
+    // See https://github.com/influxdata/influxdb_iox/pull/1671
+    clippy::future_not_send,
+    unused_crate_dependencies
+)]
+
+// Workaround for "unused crate" lint false positives.
+use workspace_hack as _;
+
+use bytes::Bytes;
+use reqwest::{header::CONTENT_ENCODING, Method, StatusCode, Url};
+use thiserror::Error;
+
+/// The errors returned by the [`Client`]
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The base URL could not be parsed
+    #[error("invalid server URL '{url}': {source}")]
+    InvalidUrl {
+        /// The URL that was provided
+        url: String,
+        /// The underlying parse error
+        source: url::ParseError,
+    },
+
+    /// The request could not be sent or the response could not be read
+    #[error("error communicating with the server: {0}")]
+    Reqwest(#[from] reqwest::Error),
+
+    /// The server responded with a non-success status code
+    #[error("server responded with error [{code}]: {message}")]
+    Api {
+        /// The HTTP status code of the response
+        code: StatusCode,
+        /// The body of the response
+        message: String,
+    },
+}
+
+/// Result type for the [`Client`]
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The language of a query sent with [`Client::api_v3_query`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryLanguage {
+    /// SQL, sent to `/api/v3/query_sql`
+    Sql,
+    /// InfluxQL, sent to `/api/v3/query_influxql`
+    InfluxQl,
+}
+
+impl QueryLanguage {
+    fn path(&self) -> &'static str {
+        match self {
+            Self::Sql => "api/v3/query_sql",
+            Self::InfluxQl => "api/v3/query_influxql",
+        }
+    }
+}
+
+/// A client for the InfluxDB 3.0 Edge HTTP API.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), influxdb3_client::Error> {
+/// use influxdb3_client::{Client, QueryLanguage};
+///
+/// let client = Client::new("http://127.0.0.1:8181")?;
+/// client
+///     .api_v3_write_lp("foo", "cpu,host=a usage=0.5", false)
+///     .await?;
+/// let csv = client
+///     .api_v3_query("foo", "SELECT * FROM cpu", QueryLanguage::Sql, "csv")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: Url,
+    http_client: reqwest::Client,
+}
+
+impl Client {
+    /// Creates a new client for the server at `base_url`, e.g. `http://127.0.0.1:8181`
+    pub fn new(base_url: impl AsRef<str>) -> Result<Self> {
+        let base_url = base_url.as_ref();
+        let base_url = Url::parse(base_url).map_err(|source| Error::InvalidUrl {
+            url: base_url.to_string(),
+            source,
+        })?;
+
+        Ok(Self {
+            base_url,
+            http_client: reqwest::Client::new(),
+        })
+    }
+
+    /// Writes the line protocol in `body` to `db`. If `gzipped` is true the body must already be
+    /// gzip compressed and is sent with `Content-Encoding: gzip`.
+    pub async fn api_v3_write_lp(
+        &self,
+        db: impl AsRef<str> + Send,
+        body: impl Into<Bytes> + Send,
+        gzipped: bool,
+    ) -> Result<()> {
+        let mut request = self
+            .request(Method::POST, "api/v3/write_lp")
+            .query(&[("db", db.as_ref())])
+            .body(body.into());
+        if gzipped {
+            request = request.header(CONTENT_ENCODING, "gzip");
+        }
+
+        check_response(request.send().await?).await?;
+
+        Ok(())
+    }
+
+    /// Runs `query` against `db` and returns the results rendered by the server in `format`,
+    /// which is one of `pretty`, `csv` or `json`.
+    pub async fn api_v3_query(
+        &self,
+        db: impl AsRef<str> + Send,
+        query: impl AsRef<str> + Send,
+        language: QueryLanguage,
+        format: impl AsRef<str> + Send,
+    ) -> Result<Bytes> {
+        let response = self
+            .request(Method::GET, language.path())
+            .query(&[
+                ("db", db.as_ref()),
+                ("q", query.as_ref()),
+                ("format", format.as_ref()),
+            ])
+            .send()
+            .await?;
+
+        Ok(check_response(response).await?.bytes().await?)
+    }
+
+    /// Creates a new, empty database.
+    pub async fn api_v3_configure_db_create(&self, db: impl AsRef<str> + Send) -> Result<()> {
+        let response = self
+            .request(Method::POST, "api/v3/configure/database")
+            .query(&[("db", db.as_ref())])
+            .send()
+            .await?;
+        check_response(response).await?;
+
+        Ok(())
+    }
+
+    /// Deletes a database and all of its data.
+    pub async fn api_v3_configure_db_delete(&self, db: impl AsRef<str> + Send) -> Result<()> {
+        let response = self
+            .request(Method::DELETE, "api/v3/configure/database")
+            .query(&[("db", db.as_ref())])
+            .send()
+            .await?;
+        check_response(response).await?;
+
+        Ok(())
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let url = self
+            .base_url
+            .join(path)
+            .expect("API paths are valid relative URLs");
+        self.http_client.request(method, url)
+    }
+}
+
+/// Turns a non-success response into an [`Error::Api`]
+async fn check_response(response: reqwest::Response) -> Result<reqwest::Response> {
+    let code = response.status();
+    if code.is_success() {
+        return Ok(response);
+    }
+
+    let message = response.text().await?;
+    Err(Error::Api { code, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    #[tokio::test]
+    async fn write_lp() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v3/write_lp")
+            .match_query(Matcher::UrlEncoded("db".into(), "foo".into()))
+            .match_header("content-encoding", "gzip")
+            .match_body("cpu usage=1")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let client = Client::new(server.url()).unwrap();
+        client
+            .api_v3_write_lp("foo", "cpu usage=1", true)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn query_error() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/query_influxql")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("db".into(), "foo".into()),
+                Matcher::UrlEncoded("q".into(), "SHOW MEASUREMENTS".into()),
+                Matcher::UrlEncoded("format".into(), "csv".into()),
+            ]))
+            .with_status(404)
+            .with_body("database not found foo")
+            .create_async()
+            .await;
+
+        let client = Client::new(server.url()).unwrap();
+        let err = client
+            .api_v3_query("foo", "SHOW MEASUREMENTS", QueryLanguage::InfluxQl, "csv")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Api { code: StatusCode::NOT_FOUND, ref message } if message == "database not found foo"),
+            "unexpected error: {err}"
+        );
+
+        mock.assert_async().await;
+    }
+}
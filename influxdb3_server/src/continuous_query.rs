@@ -0,0 +1,491 @@
+//! Continuous queries, which periodically run an InfluxQL `SELECT ... INTO` statement to
+//! downsample recent data into another measurement.
+use crate::{QueryExecutor, QueryKind};
+use arrow::array::{DictionaryArray, StringArray};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema as ArrowSchema};
+use arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
+use futures::TryStreamExt;
+use generated_types::influxdata::iox::querier::v1::InfluxQlMetadata;
+use influxdb3_write::catalog::ContinuousQueryDefinition;
+use influxdb3_write::WriteBuffer;
+use influxdb_influxql_parser::continuous_query::CreateContinuousQueryStatement;
+use influxdb_influxql_parser::expression::{
+    ConditionalBinary, ConditionalExpression, ConditionalOperator, Expr, VarRef,
+};
+use influxdb_influxql_parser::literal::Literal;
+use influxdb_influxql_parser::parse_statements;
+use influxdb_influxql_parser::select::SelectStatement;
+use influxdb_influxql_parser::statement::Statement;
+use iox_time::TimeProvider;
+use observability_deps::tracing::{debug, error, info};
+use schema::{INFLUXQL_MEASUREMENT_COLUMN_NAME, INFLUXQL_METADATA_KEY, TIME_COLUMN_NAME};
+use service_common::planner::StatementParams;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+
+/// Returns the catalog definition of the continuous query created by `stmt`.
+///
+/// The query must write its results with an `INTO` clause and group them by a fixed
+/// `GROUP BY time()` interval. If not specified by a `RESAMPLE` clause, the query runs
+/// once per interval and each run covers a single interval.
+pub(crate) fn definition_from_statement(
+    stmt: &CreateContinuousQueryStatement,
+) -> Result<ContinuousQueryDefinition, DataFusionError> {
+    if stmt.source.into.is_none() {
+        return Err(DataFusionError::Plan(
+            "continuous query must contain an INTO clause".to_owned(),
+        ));
+    }
+
+    let time_dimension = stmt
+        .source
+        .group_by
+        .as_ref()
+        .and_then(|group_by| group_by.time_dimension())
+        .ok_or_else(|| {
+            DataFusionError::Plan(
+                "continuous query must contain a GROUP BY time() clause".to_owned(),
+            )
+        })?;
+    if time_dimension.offset.is_some() {
+        return Err(DataFusionError::NotImplemented(
+            "GROUP BY time() offset in continuous queries".to_owned(),
+        ));
+    }
+    let interval_ns = match &time_dimension.interval {
+        Expr::Literal(Literal::Duration(d)) if **d > 0 => **d,
+        _ => {
+            return Err(DataFusionError::Plan(
+                "continuous query GROUP BY time() interval must be a positive duration".to_owned(),
+            ))
+        }
+    };
+
+    let every_ns = stmt.resample_every.map_or(interval_ns, |d| *d);
+    let for_ns = stmt.resample_for.map_or(interval_ns, |d| *d);
+    if every_ns <= 0 {
+        return Err(DataFusionError::Plan(
+            "continuous query RESAMPLE EVERY duration must be positive".to_owned(),
+        ));
+    }
+    if for_ns < interval_ns {
+        return Err(DataFusionError::Plan(
+            "continuous query RESAMPLE FOR duration must be >= GROUP BY time() interval".to_owned(),
+        ));
+    }
+
+    Ok(ContinuousQueryDefinition {
+        name: stmt.name.as_str().to_owned(),
+        database: stmt.database.as_str().to_owned(),
+        query: stmt.to_string(),
+        interval_ns,
+        every_ns,
+        for_ns,
+        last_run_ns: None,
+    })
+}
+
+/// Returns the result of a `SHOW CONTINUOUS QUERIES` statement, which lists the name
+/// and statement of each of the `queries`, grouped by database.
+pub(crate) fn show_continuous_queries_result(
+    queries: &[ContinuousQueryDefinition],
+) -> Result<RecordBatch, DataFusionError> {
+    let metadata = InfluxQlMetadata {
+        measurement_column_index: 0,
+        tag_key_columns: vec![],
+    };
+    let metadata =
+        serde_json::to_string(&metadata).map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+    let schema = ArrowSchema::new(vec![
+        Field::new(
+            INFLUXQL_MEASUREMENT_COLUMN_NAME,
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+            false,
+        ),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("query", DataType::Utf8, false),
+    ])
+    .with_metadata(HashMap::from([(
+        INFLUXQL_METADATA_KEY.to_owned(),
+        metadata,
+    )]));
+
+    Ok(RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(DictionaryArray::<Int32Type>::from_iter(
+                queries.iter().map(|cq| cq.database.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                queries.iter().map(|cq| cq.name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                queries.iter().map(|cq| cq.query.as_str()),
+            )),
+        ],
+    )?)
+}
+
+/// A run of a continuous query, covering the time range `[start_ns, end_ns)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ContinuousQueryRun {
+    /// The time of the run, aligned to the `RESAMPLE EVERY` duration of the query.
+    run_ns: i64,
+    start_ns: i64,
+    end_ns: i64,
+}
+
+/// Returns the earliest run of `cq` that is due at `now_ns`, if any.
+///
+/// A run is due once per `RESAMPLE EVERY` duration, `lag_ns` after the boundary, and covers
+/// the `RESAMPLE FOR` duration of complete `GROUP BY time()` intervals before the boundary.
+/// If runs were missed, for example while the server was stopped, the run after the last one
+/// is due, so that missed runs are caught up one `RESAMPLE EVERY` duration at a time.
+fn due_run(cq: &ContinuousQueryDefinition, now_ns: i64, lag_ns: i64) -> Option<ContinuousQueryRun> {
+    let align = |t: i64, d: i64| t.div_euclid(d) * d;
+
+    let latest_run_ns = align(now_ns - lag_ns, cq.every_ns);
+    let run_ns = match cq.last_run_ns {
+        Some(last) if last >= latest_run_ns => return None,
+        Some(last) => align(last, cq.every_ns) + cq.every_ns,
+        None => latest_run_ns,
+    };
+
+    let end_ns = align(run_ns, cq.interval_ns);
+    let start_ns = align(end_ns - cq.for_ns, cq.interval_ns);
+
+    Some(ContinuousQueryRun {
+        run_ns,
+        start_ns,
+        end_ns,
+    })
+}
+
+/// Returns the `SELECT ... INTO` statement of `cq`, restricted to the time range of `run`.
+fn run_statement(
+    cq: &ContinuousQueryDefinition,
+    run: ContinuousQueryRun,
+) -> Result<SelectStatement, DataFusionError> {
+    let mut statements = parse_statements(&cq.query).map_err(|e| {
+        DataFusionError::Internal(format!("invalid continuous query {}: {e}", cq.name))
+    })?;
+    let Some(Statement::CreateContinuousQuery(create)) = statements.pop() else {
+        return Err(DataFusionError::Internal(format!(
+            "invalid continuous query {}: expected CREATE CONTINUOUS QUERY statement",
+            cq.name
+        )));
+    };
+
+    let time = |op, ns| {
+        ConditionalExpression::Binary(ConditionalBinary {
+            lhs: Box::new(ConditionalExpression::Expr(Box::new(Expr::VarRef(
+                VarRef {
+                    name: TIME_COLUMN_NAME.into(),
+                    data_type: None,
+                },
+            )))),
+            op,
+            rhs: Box::new(ConditionalExpression::Expr(Box::new(Expr::Literal(
+                Literal::Integer(ns),
+            )))),
+        })
+    };
+    let and = |lhs, rhs| {
+        ConditionalExpression::Binary(ConditionalBinary {
+            lhs: Box::new(lhs),
+            op: ConditionalOperator::And,
+            rhs: Box::new(rhs),
+        })
+    };
+
+    let mut select = create.source;
+    let range = and(
+        time(ConditionalOperator::GtEq, run.start_ns),
+        time(ConditionalOperator::Lt, run.end_ns),
+    );
+    let condition = match select.condition.take() {
+        Some(condition) => and(
+            ConditionalExpression::Grouped(Box::new((*condition).clone())),
+            range,
+        ),
+        None => range,
+    };
+    select.condition = Some(condition.into());
+
+    Ok(*select)
+}
+
+/// Runs the continuous queries in the catalog of the write buffer as they become due, and
+/// records the last run of each in the catalog, which the write buffer persists.
+#[derive(Debug)]
+pub struct ContinuousQueryScheduler<W, Q> {
+    write_buffer: Arc<W>,
+    query_executor: Arc<Q>,
+    time_provider: Arc<dyn TimeProvider>,
+    lag: Duration,
+    check_interval: Duration,
+}
+
+impl<W: WriteBuffer, Q: QueryExecutor> ContinuousQueryScheduler<W, Q> {
+    /// Create a scheduler that checks for due queries every `check_interval` and runs each
+    /// query `lag` after its interval boundary, to allow for late arriving data.
+    pub fn new(
+        write_buffer: Arc<W>,
+        query_executor: Arc<Q>,
+        time_provider: Arc<dyn TimeProvider>,
+        lag: Duration,
+        check_interval: Duration,
+    ) -> Self {
+        Self {
+            write_buffer,
+            query_executor,
+            time_provider,
+            lag,
+            check_interval,
+        }
+    }
+
+    /// Run due continuous queries until `shutdown` is cancelled.
+    pub async fn run(self, shutdown: CancellationToken) {
+        let mut interval = tokio::time::interval(self.check_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => self.run_due_queries().await,
+            }
+        }
+
+        info!("continuous query scheduler stopped");
+    }
+
+    /// Run each continuous query that is due, including any runs that were missed. A query
+    /// that fails is retried, over the same time range, the next time this is called.
+    pub async fn run_due_queries(&self) {
+        let now_ns = self.time_provider.now().timestamp_nanos();
+        let lag_ns = self.lag.as_nanos() as i64;
+
+        for mut cq in self.write_buffer.catalog().continuous_queries() {
+            while let Some(run) = due_run(&cq, now_ns, lag_ns) {
+                if let Err(e) = self.run_query(&cq, run).await {
+                    error!(%e, name = %cq.name, db = %cq.database, "continuous query failed");
+                    break;
+                }
+
+                match self
+                    .write_buffer
+                    .set_continuous_query_last_run(&cq.database, &cq.name, run.run_ns)
+                    .await
+                {
+                    Ok(()) => {}
+                    Err(influxdb3_write::Error::Catalog(e)) => {
+                        // The query was dropped while it was running
+                        debug!(%e, name = %cq.name, "not recording continuous query run");
+                        break;
+                    }
+                    Err(e) => {
+                        error!(%e, name = %cq.name, db = %cq.database, "error persisting continuous query run");
+                    }
+                }
+                cq.last_run_ns = Some(run.run_ns);
+            }
+        }
+    }
+
+    async fn run_query(
+        &self,
+        cq: &ContinuousQueryDefinition,
+        run: ContinuousQueryRun,
+    ) -> crate::Result<()> {
+        let select = run_statement(cq, run)?;
+        debug!(name = %cq.name, db = %cq.database, %select, "running continuous query");
+
+        let _: Vec<_> = self
+            .query_executor
+            .query(
+                &cq.database,
+                &select.to_string(),
+                StatementParams::default(),
+                QueryKind::InfluxQl,
+                None,
+                None,
+            )
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000_000_000;
+    const HOUR: i64 = 60 * MINUTE;
+
+    fn definition(query: &str) -> ContinuousQueryDefinition {
+        let mut statements = parse_statements(query).unwrap();
+        let Some(Statement::CreateContinuousQuery(create)) = statements.pop() else {
+            panic!("expected CREATE CONTINUOUS QUERY");
+        };
+        definition_from_statement(&create).unwrap()
+    }
+
+    #[test]
+    fn test_definition_from_statement() {
+        let cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END",
+        );
+        assert_eq!(cq.name, "cq");
+        assert_eq!(cq.database, "foo");
+        assert_eq!((cq.interval_ns, cq.every_ns, cq.for_ns), (HOUR, HOUR, HOUR));
+
+        let cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo RESAMPLE EVERY 30m FOR 2h BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END",
+        );
+        assert_eq!(
+            (cq.interval_ns, cq.every_ns, cq.for_ns),
+            (HOUR, 30 * MINUTE, 2 * HOUR)
+        );
+
+        let err = |query: &str| {
+            let mut statements = parse_statements(query).unwrap();
+            let Some(Statement::CreateContinuousQuery(create)) = statements.pop() else {
+                panic!("expected CREATE CONTINUOUS QUERY");
+            };
+            definition_from_statement(&create).unwrap_err().to_string()
+        };
+        assert_eq!(
+            err("CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) FROM cpu GROUP BY time(1h) END"),
+            "Error during planning: continuous query must contain an INTO clause"
+        );
+        assert_eq!(
+            err("CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO bar FROM cpu END"),
+            "Error during planning: continuous query must contain a GROUP BY time() clause"
+        );
+        assert_eq!(
+            err("CREATE CONTINUOUS QUERY cq ON foo RESAMPLE FOR 30m BEGIN SELECT mean(usage) INTO bar FROM cpu GROUP BY time(1h) END"),
+            "Error during planning: continuous query RESAMPLE FOR duration must be >= GROUP BY time() interval"
+        );
+    }
+
+    #[test]
+    fn test_due_run() {
+        let mut cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END",
+        );
+
+        // The first run covers the last complete interval
+        let now = 10 * HOUR + 5 * MINUTE;
+        assert_eq!(
+            due_run(&cq, now, 0),
+            Some(ContinuousQueryRun {
+                run_ns: 10 * HOUR,
+                start_ns: 9 * HOUR,
+                end_ns: 10 * HOUR,
+            })
+        );
+
+        // The run is delayed by the lag
+        assert_eq!(
+            due_run(&cq, now, 10 * MINUTE),
+            Some(ContinuousQueryRun {
+                run_ns: 9 * HOUR,
+                start_ns: 8 * HOUR,
+                end_ns: 9 * HOUR,
+            })
+        );
+
+        // Nothing is due until the next interval boundary
+        cq.last_run_ns = Some(10 * HOUR);
+        assert_eq!(due_run(&cq, now, 0), None);
+        assert_eq!(due_run(&cq, 11 * HOUR - 1, 0), None);
+
+        // Missed runs are caught up one at a time
+        assert_eq!(
+            due_run(&cq, 13 * HOUR, 0),
+            Some(ContinuousQueryRun {
+                run_ns: 11 * HOUR,
+                start_ns: 10 * HOUR,
+                end_ns: 11 * HOUR,
+            })
+        );
+        cq.last_run_ns = Some(12 * HOUR);
+        assert_eq!(
+            due_run(&cq, 13 * HOUR, 0),
+            Some(ContinuousQueryRun {
+                run_ns: 13 * HOUR,
+                start_ns: 12 * HOUR,
+                end_ns: 13 * HOUR,
+            })
+        );
+
+        // Resampling more often than the interval reprocesses the last complete intervals
+        let mut cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo RESAMPLE EVERY 30m FOR 2h BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END",
+        );
+        cq.last_run_ns = Some(10 * HOUR);
+        assert_eq!(
+            due_run(&cq, 10 * HOUR + 40 * MINUTE, 0),
+            Some(ContinuousQueryRun {
+                run_ns: 10 * HOUR + 30 * MINUTE,
+                start_ns: 8 * HOUR,
+                end_ns: 10 * HOUR,
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_statement() {
+        let run = ContinuousQueryRun {
+            run_ns: 2 * HOUR,
+            start_ns: HOUR,
+            end_ns: 2 * HOUR,
+        };
+
+        let cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END",
+        );
+        assert_eq!(
+            run_statement(&cq, run).unwrap().to_string(),
+            "SELECT mean(usage) INTO cpu_1h FROM cpu WHERE time >= 3600000000000 AND time < 7200000000000 GROUP BY TIME(60m)"
+        );
+
+        let cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu WHERE host = 'a' OR host = 'b' GROUP BY time(1h), host END",
+        );
+        assert_eq!(
+            run_statement(&cq, run).unwrap().to_string(),
+            "SELECT mean(usage) INTO cpu_1h FROM cpu WHERE (host = 'a' OR host = 'b') AND time >= 3600000000000 AND time < 7200000000000 GROUP BY TIME(60m), host"
+        );
+    }
+
+    #[test]
+    fn test_show_continuous_queries_result() {
+        let cq = definition(
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END",
+        );
+        let batch = show_continuous_queries_result(&[cq]).unwrap();
+        assert_eq!(batch.num_rows(), 1);
+
+        let column = |name: &str| {
+            let column =
+                arrow::compute::cast(batch.column_by_name(name).unwrap(), &DataType::Utf8).unwrap();
+            arrow::array::as_string_array(&column).value(0).to_owned()
+        };
+        assert_eq!(column(INFLUXQL_MEASUREMENT_COLUMN_NAME), "foo");
+        assert_eq!(column("name"), "cq");
+        assert_eq!(
+            column("query"),
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY TIME(60m) END"
+        );
+    }
+}
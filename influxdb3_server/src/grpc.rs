@@ -0,0 +1,305 @@
+//! gRPC services, such as Arrow Flight, the OTLP metrics service and the storage API, which are
+//! served on the same port as the HTTP API
+
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::record_batch::RecordBatch;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use async_trait::async_trait;
+use data_types::NamespaceName;
+use futures::future::{Either, MapOk};
+use futures::TryFutureExt;
+use generated_types::influxdata::platform::storage::storage_server::{Storage, StorageServer};
+use generated_types::opentelemetry::proto::collector::metrics::v1::{
+    metrics_service_server::{MetricsService, MetricsServiceServer},
+    ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use hyper::body::{Bytes, HttpBody, SizeHint};
+use hyper::{Body, HeaderMap, Request, Response};
+use influxdb3_write::WriteBuffer;
+use iox_time::TimeProvider;
+use service_common::QueryNamespaceProvider;
+use service_grpc_flight::BatchWriter;
+use tonic::server::NamedService;
+use tower::Service;
+
+use crate::otlp;
+use crate::storage::StorageService;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Creates the Arrow Flight service, which runs queries with `query_executor` and writes the
+/// record batches sent with `DoPut` into `write_buffer`.
+pub(crate) fn make_flight_server<Q, W>(
+    query_executor: Arc<Q>,
+    write_buffer: Arc<W>,
+    time_provider: Arc<dyn TimeProvider>,
+) -> FlightServiceServer<impl FlightService>
+where
+    Q: QueryNamespaceProvider,
+    W: WriteBuffer,
+{
+    let writer = WriteBufferBatchWriter {
+        write_buffer,
+        time_provider,
+    };
+    service_grpc_flight::make_server(query_executor, None, Some(Arc::new(writer)))
+}
+
+/// Writes the record batches received by the Flight service into the write buffer.
+#[derive(Debug)]
+struct WriteBufferBatchWriter<W> {
+    write_buffer: Arc<W>,
+    time_provider: Arc<dyn TimeProvider>,
+}
+
+#[async_trait]
+impl<W: WriteBuffer> BatchWriter for WriteBufferBatchWriter<W> {
+    async fn table_exists(&self, namespace_name: &str, table: &str) -> bool {
+        self.write_buffer
+            .catalog()
+            .db_schema(namespace_name)
+            .map(|db| db.table_exists(table))
+            .unwrap_or(false)
+    }
+
+    async fn write_batch(
+        &self,
+        namespace_name: &str,
+        table: &str,
+        batch: RecordBatch,
+    ) -> Result<(), tonic::Status> {
+        if table.is_empty() {
+            return Err(tonic::Status::invalid_argument(
+                "table name must not be empty",
+            ));
+        }
+        let database = NamespaceName::new(namespace_name.to_string())
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let default_time = self.time_provider.now().timestamp_nanos();
+
+        self.write_buffer
+            .write_batch(database, table, &batch, default_time)
+            .await
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// The gRPC metadata key holding the database that OTLP metrics are written to, or that a
+/// storage API request reads from.
+pub(crate) const DATABASE_METADATA_KEY: &str = "database";
+
+/// Creates the OTLP metrics service, which writes the metrics exported to it into `write_buffer`.
+/// The database to write to is given by the `database` metadata of each request.
+pub(crate) fn make_otlp_metrics_server<W: WriteBuffer>(
+    write_buffer: Arc<W>,
+    time_provider: Arc<dyn TimeProvider>,
+) -> MetricsServiceServer<impl MetricsService> {
+    MetricsServiceServer::new(OtlpMetricsService {
+        write_buffer,
+        time_provider,
+    })
+}
+
+/// Writes the metrics received by the OTLP metrics service into the write buffer.
+#[derive(Debug)]
+struct OtlpMetricsService<W> {
+    write_buffer: Arc<W>,
+    time_provider: Arc<dyn TimeProvider>,
+}
+
+#[async_trait]
+impl<W: WriteBuffer> MetricsService for OtlpMetricsService<W> {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportMetricsServiceRequest>,
+    ) -> Result<tonic::Response<ExportMetricsServiceResponse>, tonic::Status> {
+        let database = request
+            .metadata()
+            .get(DATABASE_METADATA_KEY)
+            .ok_or_else(|| {
+                tonic::Status::invalid_argument(format!(
+                    "missing '{DATABASE_METADATA_KEY}' metadata"
+                ))
+            })?
+            .to_str()
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let database = NamespaceName::new(database.to_string())
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let default_time = self.time_provider.now().timestamp_nanos();
+
+        let response = otlp::write_metrics(
+            self.write_buffer.as_ref(),
+            database,
+            request.get_ref(),
+            default_time,
+        )
+        .await;
+
+        Ok(tonic::Response::new(response))
+    }
+}
+
+/// Creates the storage API service used by Flux and InfluxDB 2.x tools, which runs queries with
+/// `query_executor`.
+pub(crate) fn make_storage_server<Q: QueryNamespaceProvider>(
+    query_executor: Arc<Q>,
+) -> StorageServer<impl Storage> {
+    StorageServer::new(StorageService::new(query_executor))
+}
+
+/// Sends gRPC requests for the `Named` service to `named`, and all other gRPC requests to
+/// `other`, so that several gRPC services can be served alongside the HTTP API.
+#[derive(Debug, Clone)]
+pub(crate) struct GrpcRouter<Named, Other> {
+    named: Named,
+    other: Other,
+}
+
+impl<Named, Other> GrpcRouter<Named, Other> {
+    pub(crate) fn new(named: Named, other: Other) -> Self {
+        Self { named, other }
+    }
+}
+
+impl<Named, Other, ResBody> Service<Request<Body>> for GrpcRouter<Named, Other>
+where
+    Named: Service<Request<Body>, Response = Response<ResBody>, Error = Infallible> + NamedService,
+    Other: Service<Request<Body>, Response = Response<ResBody>, Error = Infallible>,
+{
+    type Response = Response<ResBody>;
+    type Error = Infallible;
+    type Future = Either<Named::Future, Other::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.named.poll_ready(cx) {
+            Poll::Ready(Ok(())) => self.other.poll_ready(cx),
+            not_ready => not_ready,
+        }
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // gRPC request paths are `/<service name>/<method name>`
+        let service_name = req.uri().path().trim_start_matches('/').split('/').next();
+        if service_name == Some(Named::NAME) {
+            Either::Left(self.named.call(req))
+        } else {
+            Either::Right(self.other.call(req))
+        }
+    }
+}
+
+/// Sends requests with a gRPC content type to the `grpc` service, and all other requests to the
+/// `rest` service, so that both can be served from the same port.
+#[derive(Debug, Clone)]
+pub(crate) struct HybridService<Rest, Grpc> {
+    rest: Rest,
+    grpc: Grpc,
+}
+
+impl<Rest, Grpc> HybridService<Rest, Grpc> {
+    pub(crate) fn new(rest: Rest, grpc: Grpc) -> Self {
+        Self { rest, grpc }
+    }
+}
+
+type HybridResponse<RestBody, GrpcBody> = Response<HybridBody<RestBody, GrpcBody>>;
+
+impl<Rest, Grpc, RestBody, GrpcBody> Service<Request<Body>> for HybridService<Rest, Grpc>
+where
+    Rest: Service<Request<Body>, Response = Response<RestBody>, Error = Infallible>,
+    Grpc: Service<Request<Body>, Response = Response<GrpcBody>, Error = Infallible>,
+{
+    type Response = HybridResponse<RestBody, GrpcBody>;
+    type Error = Infallible;
+    #[allow(clippy::type_complexity)]
+    type Future = Either<
+        MapOk<Rest::Future, fn(Response<RestBody>) -> HybridResponse<RestBody, GrpcBody>>,
+        MapOk<Grpc::Future, fn(Response<GrpcBody>) -> HybridResponse<RestBody, GrpcBody>>,
+    >;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.rest.poll_ready(cx) {
+            Poll::Ready(Ok(())) => self.grpc.poll_ready(cx),
+            not_ready => not_ready,
+        }
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if is_grpc_request(&req) {
+            let map: fn(_) -> _ = |res: Response<GrpcBody>| res.map(HybridBody::Grpc);
+            Either::Right(self.grpc.call(req).map_ok(map))
+        } else {
+            let map: fn(_) -> _ = |res: Response<RestBody>| res.map(HybridBody::Rest);
+            Either::Left(self.rest.call(req).map_ok(map))
+        }
+    }
+}
+
+fn is_grpc_request(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .map(|content_type| content_type.as_bytes().starts_with(b"application/grpc"))
+        .unwrap_or(false)
+}
+
+/// The body of a response from a [`HybridService`].
+#[derive(Debug)]
+pub(crate) enum HybridBody<RestBody, GrpcBody> {
+    Rest(RestBody),
+    Grpc(GrpcBody),
+}
+
+impl<RestBody, GrpcBody> HttpBody for HybridBody<RestBody, GrpcBody>
+where
+    RestBody: HttpBody<Data = Bytes> + Unpin,
+    RestBody::Error: Into<BoxError>,
+    GrpcBody: HttpBody<Data = Bytes> + Unpin,
+    GrpcBody::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        match self.get_mut() {
+            Self::Rest(body) => Pin::new(body)
+                .poll_data(cx)
+                .map(|data| data.map(|data| data.map_err(Into::into))),
+            Self::Grpc(body) => Pin::new(body)
+                .poll_data(cx)
+                .map(|data| data.map(|data| data.map_err(Into::into))),
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        match self.get_mut() {
+            Self::Rest(body) => Pin::new(body).poll_trailers(cx).map_err(Into::into),
+            Self::Grpc(body) => Pin::new(body).poll_trailers(cx).map_err(Into::into),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            Self::Rest(body) => body.is_end_stream(),
+            Self::Grpc(body) => body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            Self::Rest(body) => body.size_hint(),
+            Self::Grpc(body) => body.size_hint(),
+        }
+    }
+}
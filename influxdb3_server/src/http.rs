@@ -1,30 +1,46 @@
 //! HTTP API service implementations for `server`
 
-use crate::{CommonServerState, QueryExecutor};
+use crate::grpc::HybridService;
+use crate::otlp;
+use crate::tls::ReloadableTlsAcceptor;
+use crate::{CommonServerState, QueryExecutor, QueryKind};
 use arrow::record_batch::RecordBatch;
 use arrow::util::pretty;
 use authz::http::AuthorizationHeaderExtension;
 use bytes::{Bytes, BytesMut};
 use data_types::NamespaceName;
-use futures::StreamExt;
-use hyper::header::CONTENT_ENCODING;
+use futures::{StreamExt, TryStreamExt};
+use generated_types::opentelemetry::proto::collector::metrics::v1::ExportMetricsServiceRequest;
+use generated_types::prometheus::{QueryResult, ReadRequest, ReadResponse, WriteRequest};
+use generated_types::prost::Message;
+use hyper::body::HttpBody;
+use hyper::header::{CONTENT_ENCODING, CONTENT_TYPE};
 use hyper::http::HeaderValue;
 use hyper::server::conn::{AddrIncoming, AddrStream};
 use hyper::{Body, Method, Request, Response, StatusCode};
-use influxdb3_write::WriteBuffer;
-use iox_time::{SystemProvider, TimeProvider};
+use influxdb3_write::{catalog, WriteBuffer};
+use influxdb_influxql_parser::parse_statements;
+use influxdb_influxql_parser::statement::Statement;
+use influxdb_iox_client::format::QueryOutputFormat;
 use observability_deps::tracing::{debug, error, info};
 use serde::Deserialize;
+use service_common::planner::StatementParams;
 use std::convert::Infallible;
 use std::fmt::Debug;
 use std::num::NonZeroI32;
 use std::str::Utf8Error;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::server::TlsStream;
 use tokio_util::sync::CancellationToken;
-use tower::Layer;
+use tower::{Layer, Service};
 use trace_http::tower::TraceLayer;
 
+mod prometheus;
+mod rows;
+
 #[derive(Debug, Error)]
 pub enum Error {
     /// The requested path has no registered handler.
@@ -55,6 +71,39 @@ pub enum Error {
     #[error("error decoding gzip stream: {0}")]
     InvalidGzip(std::io::Error),
 
+    /// Decoding a snappy-compressed body failed.
+    #[error("error decoding snappy body: {0}")]
+    InvalidSnappy(snap::Error),
+
+    /// Decoding a protobuf request body failed.
+    #[error("error decoding protobuf body: {0}")]
+    InvalidProtobuf(generated_types::DecodeError),
+
+    /// Decoding a JSON request body failed.
+    #[error("error decoding JSON body: {0}")]
+    InvalidJson(serde_json::Error),
+
+    /// The request has a `Content-Type` that the endpoint doesn't accept.
+    #[error("unsupported content-type: {0}")]
+    UnsupportedContentType(String),
+
+    /// A JSON or CSV write request is invalid as a whole, rather than in
+    /// some of its rows.
+    #[error("invalid write request: {0}")]
+    InvalidRowWrite(String),
+
+    /// A Prometheus time series has no `__name__` label.
+    #[error("time series is missing the metric name label")]
+    MissingMetricName,
+
+    /// A Prometheus label matcher has an invalid regular expression.
+    #[error("invalid regular expression in label matcher: {0}")]
+    InvalidPromRegex(regex::Error),
+
+    /// A Prometheus remote read query result is missing an expected column.
+    #[error("remote read query result is missing column {0}")]
+    PromReadColumn(String),
+
     /// NamespaceName validation error.
     #[error("error validating namespace name: {0}")]
     InvalidNamespaceName(#[from] data_types::NamespaceNameError),
@@ -92,14 +141,30 @@ pub enum Error {
     #[error("error serving http: {0}")]
     ServingHttp(#[from] hyper::Error),
 
+    /// The listener could not be bound
+    #[error("error binding listener: {0}")]
+    Bind(std::io::Error),
+
     /// Missing parameters for query
-    #[error("missing query paramters 'db' and 'q'")]
+    #[error("missing query parameters 'db' and 'q'")]
     MissingQueryParams,
 
     /// MIssing parameters for write
-    #[error("missing query paramter 'db'")]
+    #[error("missing query parameter 'db'")]
     MissingWriteParams,
 
+    /// Missing parameters for database management
+    #[error("missing query parameter 'db'")]
+    MissingDatabaseParams,
+
+    /// The `params` of a query could not be parsed
+    #[error("invalid query parameter 'params': {0}")]
+    InvalidStatementParams(datafusion::error::DataFusionError),
+
+    /// A `SELECT ... INTO` query, which writes data, was sent with `GET`
+    #[error("SELECT ... INTO writes data and must be sent with POST")]
+    SelectIntoWithGet,
+
     /// Serde decode error
     #[error("serde error: {0}")]
     Serde(#[from] serde_urlencoded::de::Error),
@@ -115,15 +180,104 @@ pub enum Error {
     /// WriteBuffer error
     #[error("write buffer error: {0}")]
     WriteBuffer(#[from] influxdb3_write::write_buffer::Error),
+
+    /// Error from the write buffer when managing databases
+    #[error("database error: {0}")]
+    Database(#[from] influxdb3_write::Error),
+
+    /// Error planning or executing a query
+    #[error("query error: {0}")]
+    Query(Box<crate::Error>),
+
+    /// Error reading the results of a query
+    #[error("error reading query results: {0}")]
+    QueryStream(#[from] datafusion::error::DataFusionError),
+
+    /// The requested output format is unknown or the results could not be formatted
+    #[error("error formatting query results: {0}")]
+    QueryFormat(#[from] influxdb_iox_client::format::Error),
+}
+
+/// Returns true if the InfluxQL query `q` contains a `SELECT ... INTO` statement.
+fn is_select_into(q: &str) -> bool {
+    // Any error parsing the query is reported when it is planned
+    parse_statements(q).is_ok_and(|statements| {
+        statements
+            .iter()
+            .any(|s| matches!(s, Statement::Select(select) if select.into.is_some()))
+    })
 }
 
 impl Error {
     fn response(&self) -> Response<Body> {
+        let status = match self {
+            Self::NoHandler => StatusCode::NOT_FOUND,
+            Self::Query(e) if matches!(**e, crate::Error::DatabaseNotFound { .. }) => {
+                StatusCode::NOT_FOUND
+            }
+            Self::Query(e)
+                if matches!(
+                    **e,
+                    crate::Error::WriteBuffer(influxdb3_write::Error::Catalog(
+                        influxdb3_write::catalog::Error::DatabaseNotFound { .. }
+                            | influxdb3_write::catalog::Error::ContinuousQueryNotFound { .. }
+                    ))
+                ) =>
+            {
+                StatusCode::NOT_FOUND
+            }
+            Self::Query(e)
+                if matches!(
+                    **e,
+                    crate::Error::WriteBuffer(influxdb3_write::Error::Catalog(
+                        influxdb3_write::catalog::Error::ContinuousQueryAlreadyExists { .. }
+                    ))
+                ) =>
+            {
+                StatusCode::CONFLICT
+            }
+            // The query is invalid, e.g. it references a bind parameter without a value
+            Self::Query(e)
+                if matches!(
+                    **e,
+                    crate::Error::DataFusion(datafusion::error::DataFusionError::Plan(_))
+                ) =>
+            {
+                StatusCode::BAD_REQUEST
+            }
+            Self::Database(influxdb3_write::Error::Catalog(
+                influxdb3_write::catalog::Error::DatabaseNotFound { .. },
+            )) => StatusCode::NOT_FOUND,
+            Self::Database(influxdb3_write::Error::Catalog(
+                influxdb3_write::catalog::Error::DatabaseAlreadyExists { .. },
+            )) => StatusCode::CONFLICT,
+            Self::NonUtf8Body(_)
+            | Self::NonUtf8ContentHeader(_)
+            | Self::InvalidContentEncoding(_)
+            | Self::InvalidGzip(_)
+            | Self::InvalidSnappy(_)
+            | Self::InvalidProtobuf(_)
+            | Self::InvalidJson(_)
+            | Self::InvalidRowWrite(_)
+            | Self::MissingMetricName
+            | Self::InvalidPromRegex(_)
+            | Self::InvalidNamespaceName(_)
+            | Self::ParseLineProtocol(_)
+            | Self::MissingQueryParams
+            | Self::MissingWriteParams
+            | Self::MissingDatabaseParams
+            | Self::InvalidStatementParams(_)
+            | Self::Serde(_)
+            | Self::QueryFormat(influxdb_iox_client::format::Error::Invalid(_)) => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::SelectIntoWithGet => StatusCode::METHOD_NOT_ALLOWED,
+            Self::RequestSizeExceeded(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
         let body = Body::from(self.to_string());
-        Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(body)
-            .unwrap()
+        Response::builder().status(status).body(body).unwrap()
     }
 }
 
@@ -131,6 +285,12 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 const TRACE_SERVER_NAME: &str = "http_api";
 
+/// How long a client has to complete the TLS handshake before the connection is dropped.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of TLS handshakes that can be in progress at once.
+const MAX_CONCURRENT_TLS_HANDSHAKES: usize = 128;
+
 #[derive(Debug)]
 pub(crate) struct HttpApi<W, Q> {
     common_state: CommonServerState,
@@ -153,6 +313,10 @@ impl<W, Q> HttpApi<W, Q> {
             max_request_bytes,
         }
     }
+
+    pub(crate) fn common_state(&self) -> &CommonServerState {
+        &self.common_state
+    }
 }
 
 impl<W, Q> HttpApi<W, Q>
@@ -170,8 +334,7 @@ where
 
         let database = NamespaceName::new(params.db)?;
 
-        // TODO: use the time provider
-        let default_time = SystemProvider::new().now().timestamp_nanos();
+        let default_time = self.common_state.time_provider.now().timestamp_nanos();
 
         self.write_buffer
             .write_lp(database, body, default_time)
@@ -180,31 +343,256 @@ where
         Ok(Response::new(Body::from("{}")))
     }
 
-    async fn query_sql(&self, req: Request<Body>) -> Result<Response<Body>> {
+    /// Handle a write of rows as a JSON array of objects.
+    async fn write_json(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingWriteParams)?;
+        let params: RowWriteParams = serde_urlencoded::from_str(query)?;
+        info!("write_json to {}", params.db);
+
+        let body = self.read_body(req).await?;
+        let rows = rows::json_to_lp(&body, &params)?;
+        self.write_rows(params.db, rows).await
+    }
+
+    /// Handle a write of rows as annotated CSV.
+    async fn write_csv(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingWriteParams)?;
+        let params: RowWriteParams = serde_urlencoded::from_str(query)?;
+        info!("write_csv to {}", params.db);
+
+        let body = self.read_body(req).await?;
+        let rows = rows::csv_to_lp(&body, &params)?;
+        self.write_rows(params.db, rows).await
+    }
+
+    /// Write the rows of a JSON or CSV write, responding with the rows that
+    /// were rejected, if any. The valid rows are written either way.
+    async fn write_rows(&self, db: String, rows: rows::RowsLineProtocol) -> Result<Response<Body>> {
+        let database = NamespaceName::new(db)?;
+
+        let mut rejected_lines = vec![];
+        if !rows.lp.is_empty() {
+            let lp = std::str::from_utf8(&rows.lp).map_err(Error::NonUtf8Body)?;
+            let default_time = self.common_state.time_provider.now().timestamp_nanos();
+            rejected_lines = self
+                .write_buffer
+                .write_lp_partial(database, lp, default_time)
+                .await?
+                .invalid_lines;
+        }
+
+        let row_count = rows.row_count;
+        let invalid_rows = rows.invalid_rows(rejected_lines);
+        if invalid_rows.is_empty() {
+            return Ok(Response::new(Body::from("{}")));
+        }
+
+        let body = serde_json::json!({
+            "error": format!("{} of {} rows were rejected", invalid_rows.len(), row_count),
+            "data": invalid_rows,
+        });
+        Ok(Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?)
+    }
+
+    async fn query(&self, req: Request<Body>, kind: QueryKind) -> Result<Response<Body>> {
         let query = req.uri().query().ok_or(Error::MissingQueryParams)?;
-        let params: QuerySqlParams = serde_urlencoded::from_str(query)?;
+        let params: QueryParams = serde_urlencoded::from_str(query)?;
+        info!("query {} to {}", kind.as_str(), params.db);
 
-        println!("query_sql {:?}", params);
+        if kind == QueryKind::InfluxQl && req.method() == Method::GET && is_select_into(&params.q) {
+            return Err(Error::SelectIntoWithGet);
+        }
+
+        let format = params
+            .format
+            .as_deref()
+            .map(str::parse::<QueryOutputFormat>)
+            .transpose()?
+            .unwrap_or_default();
+
+        let statement_params = params
+            .params
+            .as_deref()
+            .map(StatementParams::from_json)
+            .transpose()
+            .map_err(Error::InvalidStatementParams)?
+            .unwrap_or_default();
 
         let result = self
             .query_executor
-            .query(&params.db, &params.q, None, None)
+            .query(&params.db, &params.q, statement_params, kind, None, None)
             .await
-            .unwrap();
+            .map_err(|e| Error::Query(Box::new(e)))?;
 
-        let batches: Vec<RecordBatch> = result
-            .collect::<Vec<datafusion::common::Result<RecordBatch>>>()
-            .await
-            .into_iter()
-            .map(|b| b.unwrap())
-            .collect();
-        let pretty_string = format!("{}", pretty::pretty_format_batches(&batches)?);
+        let batches: Vec<RecordBatch> = result.try_collect().await?;
+        // SQL queries are pretty printed the way they were before the other formats were added
+        let body = match (kind, format) {
+            (QueryKind::Sql, QueryOutputFormat::Pretty) => {
+                pretty::pretty_format_batches(&batches)?.to_string()
+            }
+            _ => format.format(&batches)?,
+        };
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(
+                "Content-Type",
+                format!("{}; charset=utf-8", format.content_type()),
+            )
+            .body(Body::from(body))?)
+    }
+
+    async fn create_database(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingDatabaseParams)?;
+        let params: DatabaseParams = serde_urlencoded::from_str(query)?;
+        info!("create database {}", params.db);
+
+        let database = NamespaceName::new(params.db)?;
+        self.write_buffer.create_database(database).await?;
+
+        Ok(Response::new(Body::from("{}")))
+    }
+
+    async fn delete_database(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingDatabaseParams)?;
+        let params: DatabaseParams = serde_urlencoded::from_str(query)?;
+        info!("delete database {}", params.db);
+
+        self.write_buffer.delete_database(&params.db).await?;
+
+        Ok(Response::new(Body::from("{}")))
+    }
+
+    /// Handle a Prometheus remote write request, writing each sample to the
+    /// table named after its metric.
+    async fn prom_write(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingWriteParams)?;
+        let params: WriteParams = serde_urlencoded::from_str(query)?;
+        info!("prom_write to {}", params.db);
+
+        let body = self.read_snappy_body(req).await?;
+        let request = WriteRequest::decode(body).map_err(Error::InvalidProtobuf)?;
+        let lp = prometheus::write_request_to_lp(&request)?;
+        let lp = std::str::from_utf8(&lp).map_err(Error::NonUtf8Body)?;
+
+        let database = NamespaceName::new(params.db)?;
+
+        if !lp.is_empty() {
+            let default_time = self.common_state.time_provider.now().timestamp_nanos();
+            self.write_buffer
+                .write_lp(database, lp, default_time)
+                .await?;
+        }
+
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())?)
+    }
+
+    /// Handle a Prometheus remote read request, returning the samples of
+    /// every series matching each query.
+    async fn prom_read(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingDatabaseParams)?;
+        let params: DatabaseParams = serde_urlencoded::from_str(query)?;
+        info!("prom_read from {}", params.db);
+
+        let body = self.read_snappy_body(req).await?;
+        let request = ReadRequest::decode(body).map_err(Error::InvalidProtobuf)?;
+
+        let db_schema = self
+            .write_buffer
+            .catalog()
+            .db_schema(&params.db)
+            .ok_or_else(|| {
+                Error::Database(influxdb3_write::Error::Catalog(
+                    catalog::Error::DatabaseNotFound {
+                        db_name: params.db.clone(),
+                    },
+                ))
+            })?;
+
+        let mut results = Vec::with_capacity(request.queries.len());
+        for query in &request.queries {
+            let mut timeseries = vec![];
+            for table_query in prometheus::plan_read_query(&db_schema, query)? {
+                debug!(sql = %table_query.sql, "prom_read query for {}", table_query.table_name);
+                let batches: Vec<RecordBatch> = self
+                    .query_executor
+                    .query(
+                        &params.db,
+                        &table_query.sql,
+                        StatementParams::default(),
+                        QueryKind::Sql,
+                        None,
+                        None,
+                    )
+                    .await
+                    .map_err(|e| Error::Query(Box::new(e)))?
+                    .try_collect()
+                    .await?;
+                timeseries.extend(prometheus::batches_to_timeseries(&table_query, &batches)?);
+            }
+            results.push(QueryResult { timeseries });
+        }
+
+        let body = ReadResponse { results }.encode_to_vec();
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&body)
+            .map_err(Error::InvalidSnappy)?;
 
-        // Create a response with the pretty-printed string as the body.
         Ok(Response::builder()
             .status(StatusCode::OK)
-            .header("Content-Type", "text/plain; charset=utf-8")
-            .body(Body::from(pretty_string))?) // Handle this unwrap in production.
+            .header("Content-Type", "application/x-protobuf")
+            .header(CONTENT_ENCODING, "snappy")
+            .body(Body::from(body))?)
+    }
+
+    /// Handle an OTLP/HTTP metrics export request, encoded as either protobuf or
+    /// JSON. The response uses the same encoding as the request.
+    async fn otlp_metrics(&self, req: Request<Body>) -> Result<Response<Body>> {
+        let query = req.uri().query().ok_or(Error::MissingWriteParams)?;
+        let params: WriteParams = serde_urlencoded::from_str(query)?;
+        info!("otlp_metrics to {}", params.db);
+
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+            .unwrap_or_default();
+        // Ignore any parameters, such as the charset.
+        let json = match content_type.split(';').next().unwrap_or_default().trim() {
+            "application/x-protobuf" => false,
+            "application/json" => true,
+            _ => return Err(Error::UnsupportedContentType(content_type)),
+        };
+
+        let body = self.read_body(req).await?;
+        let request = if json {
+            serde_json::from_slice::<ExportMetricsServiceRequest>(&body)
+                .map_err(Error::InvalidJson)?
+        } else {
+            ExportMetricsServiceRequest::decode(body).map_err(Error::InvalidProtobuf)?
+        };
+
+        let database = NamespaceName::new(params.db)?;
+        let default_time = self.common_state.time_provider.now().timestamp_nanos();
+
+        let response =
+            otlp::write_metrics(self.write_buffer.as_ref(), database, &request, default_time).await;
+
+        let (content_type, body) = if json {
+            let body = serde_json::to_vec(&response).expect("OTLP responses serialize to JSON");
+            ("application/json", body)
+        } else {
+            ("application/x-protobuf", response.encode_to_vec())
+        };
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))?)
     }
 
     fn health(&self) -> Result<Response<Body>> {
@@ -276,12 +664,46 @@ where
 
         Ok(decoded_data.into())
     }
+
+    /// Parse the request's snappy-compressed body, as sent by Prometheus remote
+    /// storage, into raw bytes, applying the configured size limits.
+    async fn read_snappy_body(&self, mut req: Request<Body>) -> Result<Bytes> {
+        // The body is always snappy-compressed, whether or not the header says so.
+        if let Some(encoding) = req.headers_mut().remove(CONTENT_ENCODING) {
+            if encoding != "snappy" {
+                return Err(Error::InvalidContentEncoding(
+                    String::from_utf8_lossy(encoding.as_bytes()).into_owned(),
+                ));
+            }
+        }
+        let body = self.read_body(req).await?;
+
+        // Check the decompressed length to prevent a decompression bomb based DoS.
+        let len = snap::raw::decompress_len(&body).map_err(Error::InvalidSnappy)?;
+        if len > self.max_request_bytes {
+            return Err(Error::RequestSizeExceeded(self.max_request_bytes));
+        }
+
+        snap::raw::Decoder::new()
+            .decompress_vec(&body)
+            .map(Bytes::from)
+            .map_err(Error::InvalidSnappy)
+    }
 }
 
 #[derive(Debug, Deserialize)]
-pub(crate) struct QuerySqlParams {
+pub(crate) struct QueryParams {
     pub(crate) db: String,
     pub(crate) q: String,
+    /// One of `pretty`, `csv` or `json`; defaults to `pretty`
+    pub(crate) format: Option<String>,
+    /// A JSON object of values for the bind parameters in `q`, such as `{"host":"a"}`
+    pub(crate) params: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DatabaseParams {
+    pub(crate) db: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -289,14 +711,66 @@ pub(crate) struct WriteParams {
     pub(crate) db: String,
 }
 
-pub(crate) async fn serve<W: WriteBuffer, Q: QueryExecutor>(
+#[derive(Debug, Deserialize)]
+pub(crate) struct RowWriteParams {
+    pub(crate) db: String,
+    /// The table to write to; required unless a CSV write has a measurement column
+    pub(crate) measurement: Option<String>,
+    /// The key or column holding the timestamp; defaults to `time`
+    pub(crate) time_key: Option<String>,
+    /// A comma-separated list of the JSON keys to write as tags
+    pub(crate) tags: Option<String>,
+    /// A comma-separated list of the JSON keys to write as fields; defaults to all
+    /// keys other than the tags and time
+    pub(crate) fields: Option<String>,
+    /// The precision of numeric timestamps, one of `s`, `ms`, `us` or `ns`; defaults
+    /// to `ns`
+    pub(crate) precision: Option<rows::Precision>,
+}
+
+impl RowWriteParams {
+    fn time_key(&self) -> &str {
+        self.time_key.as_deref().unwrap_or("time")
+    }
+
+    fn tag_keys(&self) -> Vec<&str> {
+        split_keys(self.tags.as_deref())
+    }
+
+    fn field_keys(&self) -> Option<Vec<&str>> {
+        self.fields
+            .as_deref()
+            .map(|fields| split_keys(Some(fields)))
+    }
+}
+
+fn split_keys(keys: Option<&str>) -> Vec<&str> {
+    keys.into_iter()
+        .flat_map(|keys| keys.split(','))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+/// Serves the HTTP API, along with the gRPC services in `grpc_service`, until `shutdown` is
+/// cancelled.
+pub(crate) async fn serve<W, Q, G, GrpcBody>(
     http_server: Arc<HttpApi<W, Q>>,
+    grpc_service: G,
+    tls_acceptor: Option<Arc<ReloadableTlsAcceptor>>,
     shutdown: CancellationToken,
-) -> Result<()> {
-    let listener = AddrIncoming::bind(&http_server.common_state.http_addr)?;
-    println!("binding listener");
-    info!(bind_addr=%listener.local_addr(), "bound HTTP listener");
-
+) -> Result<()>
+where
+    W: WriteBuffer,
+    Q: QueryExecutor,
+    G: Service<Request<Body>, Response = Response<GrpcBody>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    G::Future: Send + 'static,
+    GrpcBody: HttpBody<Data = Bytes> + Unpin + Send + 'static,
+    GrpcBody::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
     let trace_layer = TraceLayer::new(
         http_server.common_state.trace_header_parser.clone(),
         Arc::<metric::Registry>::clone(&http_server.common_state.metrics),
@@ -305,16 +779,81 @@ pub(crate) async fn serve<W: WriteBuffer, Q: QueryExecutor>(
         TRACE_SERVER_NAME,
     );
 
-    hyper::Server::builder(listener)
-        .serve(hyper::service::make_service_fn(|_conn: &AddrStream| {
-            let http_server = Arc::clone(&http_server);
-            let service = hyper::service::service_fn(move |request: Request<_>| {
-                route_request(Arc::clone(&http_server), request)
-            });
+    let Some(tls_acceptor) = tls_acceptor else {
+        let listener = AddrIncoming::bind(&http_server.common_state.http_addr)?;
+        info!(bind_addr=%listener.local_addr(), "bound HTTP listener");
+
+        hyper::Server::builder(listener)
+            .serve(hyper::service::make_service_fn(|_conn: &AddrStream| {
+                let http_server = Arc::clone(&http_server);
+                let rest_service = hyper::service::service_fn(move |request: Request<_>| {
+                    route_request(Arc::clone(&http_server), request)
+                });
+
+                let service = HybridService::new(rest_service, grpc_service.clone());
+                let service = trace_layer.layer(service);
+                futures::future::ready(Ok::<_, Infallible>(service))
+            }))
+            .with_graceful_shutdown(shutdown.cancelled())
+            .await?;
+
+        return Ok(());
+    };
 
-            let service = trace_layer.layer(service);
-            futures::future::ready(Ok::<_, Infallible>(service))
-        }))
+    let listener = TcpListener::bind(&http_server.common_state.http_addr)
+        .await
+        .map_err(Error::Bind)?;
+    info!(bind_addr=?listener.local_addr(), "bound HTTPS listener");
+
+    // Connections whose TLS handshake fails are dropped here so that a misbehaving client
+    // cannot terminate the accept loop.
+    let connections = futures::stream::unfold(listener, |listener| async move {
+        let accepted = listener.accept().await;
+        Some((accepted, listener))
+    })
+    .filter_map(|accepted| async move {
+        match accepted {
+            Ok((stream, _)) => Some(stream),
+            Err(error) => {
+                // back off, as this is usually caused by running out of file descriptors
+                error!(%error, "error accepting connection");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                None
+            }
+        }
+    })
+    .map(move |stream| {
+        let acceptor = tls_acceptor.acceptor();
+        tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+    })
+    .buffer_unordered(MAX_CONCURRENT_TLS_HANDSHAKES)
+    .filter_map(|handshake| async move {
+        match handshake {
+            Ok(Ok(stream)) => Some(Ok::<_, Infallible>(stream)),
+            Ok(Err(error)) => {
+                debug!(%error, "TLS handshake failed");
+                None
+            }
+            Err(_) => {
+                debug!("TLS handshake timed out");
+                None
+            }
+        }
+    });
+
+    hyper::Server::builder(hyper::server::accept::from_stream(connections))
+        .serve(hyper::service::make_service_fn(
+            |_conn: &TlsStream<TcpStream>| {
+                let http_server = Arc::clone(&http_server);
+                let rest_service = hyper::service::service_fn(move |request: Request<_>| {
+                    route_request(Arc::clone(&http_server), request)
+                });
+
+                let service = HybridService::new(rest_service, grpc_service.clone());
+                let service = trace_layer.layer(service);
+                futures::future::ready(Ok::<_, Infallible>(service))
+            },
+        ))
         .with_graceful_shutdown(shutdown.cancelled())
         .await?;
 
@@ -339,7 +878,19 @@ async fn route_request<W: WriteBuffer, Q: QueryExecutor>(
 
     let response = match (method.clone(), uri.path()) {
         (Method::POST, "/api/v3/write_lp") => http_server.write_lp(req).await,
-        (Method::GET | Method::POST, "/api/v3/query_sql") => http_server.query_sql(req).await,
+        (Method::POST, "/api/v3/write_json") => http_server.write_json(req).await,
+        (Method::POST, "/api/v3/write_csv") => http_server.write_csv(req).await,
+        (Method::GET | Method::POST, "/api/v3/query_sql") => {
+            http_server.query(req, QueryKind::Sql).await
+        }
+        (Method::GET | Method::POST, "/api/v3/query_influxql") => {
+            http_server.query(req, QueryKind::InfluxQl).await
+        }
+        (Method::POST, "/api/v1/prom/write") => http_server.prom_write(req).await,
+        (Method::POST, "/api/v1/prom/read") => http_server.prom_read(req).await,
+        (Method::POST, "/v1/metrics") => http_server.otlp_metrics(req).await,
+        (Method::POST, "/api/v3/configure/database") => http_server.create_database(req).await,
+        (Method::DELETE, "/api/v3/configure/database") => http_server.delete_database(req).await,
         (Method::GET, "/health") => http_server.health(),
         (Method::GET, "/metrics") => http_server.handle_metrics(),
         (Method::GET, "/debug/pprof") => pprof_home(req).await,
@@ -0,0 +1,416 @@
+//! Conversions between the [Prometheus remote storage] protocol and the
+//! data model of the write buffer and query engine.
+//!
+//! Each metric is stored in a table named after the metric, with a tag for
+//! each label and the sample in a `value` float field. Labels named `value`
+//! or `time` are stored in tags with the [`LABEL_TAG_PREFIX`] prefix, so
+//! they don't collide with the value field or time column.
+//!
+//! [Prometheus remote storage]: https://prometheus.io/docs/prometheus/latest/storage/#remote-storage-integrations
+use std::borrow::Cow;
+
+use arrow::array::{Array, AsArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Float64Type, TimeUnit, TimestampNanosecondType};
+use arrow::record_batch::RecordBatch;
+use generated_types::prometheus::{
+    label_matcher, Label, LabelMatcher, Query, Sample, TimeSeries, WriteRequest,
+};
+use influxdb3_write::catalog::DatabaseSchema;
+use influxdb_line_protocol::LineProtocolBuilder;
+use regex::Regex;
+use schema::{InfluxColumnType, InfluxFieldType, TIME_COLUMN_NAME};
+
+use super::{Error, Result};
+
+/// The label holding the name of the metric.
+const METRIC_NAME_LABEL: &str = "__name__";
+
+/// The field holding the value of each sample.
+const VALUE_FIELD: &str = "value";
+
+/// The prefix of the tags storing labels whose names collide with the
+/// value field or time column. Labels that already start with the prefix
+/// are prefixed again, so every tag maps back to a single label.
+const LABEL_TAG_PREFIX: &str = "__label_";
+
+/// Convert the time series of a remote write request to line protocol.
+///
+/// Samples with a NaN value, such as the staleness markers written by
+/// Prometheus, are skipped as they can't be represented in line protocol.
+/// Labels with empty values are treated as missing, as Prometheus does.
+pub(super) fn write_request_to_lp(request: &WriteRequest) -> Result<Vec<u8>> {
+    let mut lp = LineProtocolBuilder::new();
+    for series in &request.timeseries {
+        let table_name = series
+            .labels
+            .iter()
+            .find(|l| l.name == METRIC_NAME_LABEL)
+            .map(|l| l.value.as_str())
+            .filter(|name| !name.is_empty())
+            .ok_or(Error::MissingMetricName)?;
+        let tags = series
+            .labels
+            .iter()
+            .filter(|l| l.name != METRIC_NAME_LABEL && !l.value.is_empty());
+
+        for sample in series.samples.iter().filter(|s| !s.value.is_nan()) {
+            let mut line = lp.measurement(table_name);
+            for tag in tags.clone() {
+                line = line.tag(&label_to_tag(&tag.name), &tag.value);
+            }
+            lp = line
+                .field(VALUE_FIELD, sample.value)
+                .timestamp(sample.timestamp.saturating_mul(1_000_000))
+                .close_line();
+        }
+    }
+    Ok(lp.build())
+}
+
+/// A query of a single table, answering part of a remote read query.
+#[derive(Debug)]
+pub(super) struct TableQuery {
+    /// The name of the table, and of the metric.
+    pub(super) table_name: String,
+    /// The tag columns of the table, which are the labels of the series.
+    tags: Vec<String>,
+    /// The SQL query selecting the samples, ordered by series and time.
+    pub(super) sql: String,
+}
+
+/// Plan the SQL queries answering a remote read `query`, one for each table
+/// whose name matches the metric name matchers.
+///
+/// The label matchers are applied to the tags of each table. As in
+/// Prometheus, a missing or null label matches the empty string, and
+/// regular expressions must match the whole label value.
+pub(super) fn plan_read_query(
+    db_schema: &DatabaseSchema,
+    query: &Query,
+) -> Result<Vec<TableQuery>> {
+    let (name_matchers, label_matchers): (Vec<_>, Vec<_>) = query
+        .matchers
+        .iter()
+        .partition(|m| m.name == METRIC_NAME_LABEL);
+
+    let mut table_queries = vec![];
+    'tables: for table_name in db_schema.table_names() {
+        for m in &name_matchers {
+            if !label_matches(m, &table_name)? {
+                continue 'tables;
+            }
+        }
+        let Some(schema) = db_schema.get_table_schema(&table_name) else {
+            continue;
+        };
+        // Only tables with a float value field hold Prometheus samples.
+        if schema.field_type_by_name(VALUE_FIELD)
+            != Some(InfluxColumnType::Field(InfluxFieldType::Float))
+        {
+            continue;
+        }
+        let tags: Vec<String> = schema.tags_iter().map(|f| f.name().clone()).collect();
+
+        let time = quote_identifier(TIME_COLUMN_NAME);
+        let value = quote_identifier(VALUE_FIELD);
+        let mut predicates = vec![
+            format!(
+                "{time} >= to_timestamp_millis({})",
+                query.start_timestamp_ms
+            ),
+            format!("{time} <= to_timestamp_millis({})", query.end_timestamp_ms),
+            format!("{value} IS NOT NULL"),
+        ];
+        for m in &label_matchers {
+            let tag = label_to_tag(&m.name);
+            if tags.iter().any(|t| *t == tag) {
+                predicates.push(predicate(&tag, m)?);
+            } else if !label_matches(m, "")? {
+                // The label is missing from every series in the table.
+                continue 'tables;
+            }
+        }
+
+        let series_key: Vec<String> = tags
+            .iter()
+            .map(|t| quote_identifier(t))
+            .chain([time])
+            .collect();
+        let sql = format!(
+            "SELECT {}, {value} FROM {} WHERE {} ORDER BY {}",
+            series_key.join(", "),
+            quote_identifier(&table_name),
+            predicates.join(" AND "),
+            series_key.join(", "),
+        );
+
+        table_queries.push(TableQuery {
+            table_name,
+            tags,
+            sql,
+        });
+    }
+    Ok(table_queries)
+}
+
+/// Convert the results of a [`TableQuery`] into time series, with a series
+/// for each distinct set of tag values.
+pub(super) fn batches_to_timeseries(
+    table_query: &TableQuery,
+    batches: &[RecordBatch],
+) -> Result<Vec<TimeSeries>> {
+    let mut timeseries: Vec<TimeSeries> = vec![];
+    for batch in batches {
+        let tags = table_query
+            .tags
+            .iter()
+            .map(|t| {
+                let column = batch
+                    .column_by_name(t)
+                    .ok_or_else(|| Error::PromReadColumn(t.clone()))?;
+                Ok(cast(column, &DataType::Utf8)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let times = cast(
+            batch
+                .column_by_name(TIME_COLUMN_NAME)
+                .ok_or_else(|| Error::PromReadColumn(TIME_COLUMN_NAME.to_string()))?,
+            &DataType::Timestamp(TimeUnit::Nanosecond, None),
+        )?;
+        let times = times.as_primitive::<TimestampNanosecondType>();
+        let values = batch
+            .column_by_name(VALUE_FIELD)
+            .ok_or_else(|| Error::PromReadColumn(VALUE_FIELD.to_string()))?
+            .as_primitive_opt::<Float64Type>()
+            .ok_or_else(|| Error::PromReadColumn(VALUE_FIELD.to_string()))?;
+
+        for row in 0..batch.num_rows() {
+            let mut labels: Vec<Label> = table_query
+                .tags
+                .iter()
+                .zip(&tags)
+                .filter(|(_, values)| values.is_valid(row))
+                .map(|(name, values)| Label {
+                    name: tag_to_label(name).to_string(),
+                    value: values.as_string::<i32>().value(row).to_string(),
+                })
+                .filter(|l| !l.value.is_empty())
+                .collect();
+            labels.push(Label {
+                name: METRIC_NAME_LABEL.to_string(),
+                value: table_query.table_name.clone(),
+            });
+            labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let sample = Sample {
+                value: values.value(row),
+                timestamp: times.value(row).div_euclid(1_000_000),
+            };
+            // The rows are ordered by series, so a new series starts
+            // whenever the labels change.
+            match timeseries.last_mut() {
+                Some(series) if series.labels == labels => series.samples.push(sample),
+                _ => timeseries.push(TimeSeries {
+                    labels,
+                    samples: vec![sample],
+                }),
+            }
+        }
+    }
+    Ok(timeseries)
+}
+
+/// Returns `true` if `value` satisfies the matcher `m`.
+fn label_matches(m: &LabelMatcher, value: &str) -> Result<bool> {
+    Ok(match m.r#type() {
+        label_matcher::Type::Eq => value == m.value,
+        label_matcher::Type::Neq => value != m.value,
+        label_matcher::Type::Re => anchored_regex(&m.value)?.is_match(value),
+        label_matcher::Type::Nre => !anchored_regex(&m.value)?.is_match(value),
+    })
+}
+
+/// Returns a SQL predicate for the matcher `m` on the tag column `tag`.
+fn predicate(tag: &str, m: &LabelMatcher) -> Result<String> {
+    // Prometheus regular expressions must match the whole value, and a
+    // missing label has an empty value.
+    let column = format!("coalesce(CAST({} AS VARCHAR), '')", quote_identifier(tag));
+    let (op, value) = match m.r#type() {
+        label_matcher::Type::Eq => ("=", m.value.clone()),
+        label_matcher::Type::Neq => ("<>", m.value.clone()),
+        label_matcher::Type::Re => ("~", anchored_regex(&m.value)?.as_str().to_string()),
+        label_matcher::Type::Nre => ("!~", anchored_regex(&m.value)?.as_str().to_string()),
+    };
+    Ok(format!("{column} {op} {}", quote_literal(&value)))
+}
+
+/// Returns the name of the tag storing the label `name`.
+fn label_to_tag(name: &str) -> Cow<'_, str> {
+    if name == VALUE_FIELD || name == TIME_COLUMN_NAME || name.starts_with(LABEL_TAG_PREFIX) {
+        Cow::Owned(format!("{LABEL_TAG_PREFIX}{name}"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Returns the name of the label stored in the tag `name`, reversing
+/// [`label_to_tag`].
+fn tag_to_label(name: &str) -> &str {
+    name.strip_prefix(LABEL_TAG_PREFIX).unwrap_or(name)
+}
+
+fn anchored_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(&format!("^(?:{pattern})$")).map_err(Error::InvalidPromRegex)
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(name: &str, value: &str) -> Label {
+        Label {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn matcher(r#type: label_matcher::Type, name: &str, value: &str) -> LabelMatcher {
+        LabelMatcher {
+            r#type: r#type as i32,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_request_to_lp() {
+        let request = WriteRequest {
+            timeseries: vec![
+                TimeSeries {
+                    labels: vec![
+                        label("__name__", "http_requests_total"),
+                        label("code", "200"),
+                        label("instance", "a b"),
+                        label("job", ""),
+                    ],
+                    samples: vec![
+                        Sample {
+                            value: 1.0,
+                            timestamp: 1,
+                        },
+                        Sample {
+                            value: f64::NAN,
+                            timestamp: 2,
+                        },
+                        Sample {
+                            value: 2.5,
+                            timestamp: 3,
+                        },
+                    ],
+                },
+                TimeSeries {
+                    labels: vec![label("__name__", "up")],
+                    samples: vec![Sample {
+                        value: 1.0,
+                        timestamp: 1_000,
+                    }],
+                },
+            ],
+        };
+
+        let lp = String::from_utf8(write_request_to_lp(&request).unwrap()).unwrap();
+        assert_eq!(
+            lp,
+            "http_requests_total,code=200,instance=a\\ b value=1 1000000\n\
+             http_requests_total,code=200,instance=a\\ b value=2.5 3000000\n\
+             up value=1 1000000000\n"
+        );
+
+        let request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![label("job", "a")],
+                samples: vec![],
+            }],
+        };
+        assert!(matches!(
+            write_request_to_lp(&request),
+            Err(Error::MissingMetricName)
+        ));
+
+        // labels colliding with the value field or time column are prefixed
+        let request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![
+                    label("__name__", "up"),
+                    label("value", "a"),
+                    label("time", "b"),
+                    label("__label_c", "c"),
+                ],
+                samples: vec![Sample {
+                    value: 1.0,
+                    timestamp: 1,
+                }],
+            }],
+        };
+        let lp = String::from_utf8(write_request_to_lp(&request).unwrap()).unwrap();
+        assert_eq!(
+            lp,
+            "up,__label_value=a,__label_time=b,__label___label_c=c value=1 1000000\n"
+        );
+    }
+
+    #[test]
+    fn test_label_tag_names() {
+        for (label, tag) in [
+            ("job", "job"),
+            ("value", "__label_value"),
+            ("time", "__label_time"),
+            ("__label_value", "__label___label_value"),
+            ("values", "values"),
+        ] {
+            assert_eq!(label_to_tag(label), tag);
+            assert_eq!(tag_to_label(tag), label);
+        }
+    }
+
+    #[test]
+    fn test_label_matches() {
+        use label_matcher::Type;
+
+        assert!(label_matches(&matcher(Type::Eq, "job", "a"), "a").unwrap());
+        assert!(!label_matches(&matcher(Type::Eq, "job", "a"), "ab").unwrap());
+        assert!(label_matches(&matcher(Type::Neq, "job", "a"), "").unwrap());
+        assert!(label_matches(&matcher(Type::Re, "job", "a|b"), "b").unwrap());
+        // regular expressions are anchored
+        assert!(!label_matches(&matcher(Type::Re, "job", "a"), "ab").unwrap());
+        assert!(label_matches(&matcher(Type::Re, "job", ".*"), "").unwrap());
+        assert!(!label_matches(&matcher(Type::Nre, "job", ".+"), "a").unwrap());
+        assert!(matches!(
+            label_matches(&matcher(Type::Re, "job", "("), "a"),
+            Err(Error::InvalidPromRegex(_))
+        ));
+    }
+
+    #[test]
+    fn test_predicate() {
+        use label_matcher::Type;
+
+        assert_eq!(
+            predicate("job", &matcher(Type::Eq, "job", "it's")).unwrap(),
+            r#"coalesce(CAST("job" AS VARCHAR), '') = 'it''s'"#
+        );
+        assert_eq!(
+            predicate("job", &matcher(Type::Nre, "job", "a|b")).unwrap(),
+            r#"coalesce(CAST("job" AS VARCHAR), '') !~ '^(?:a|b)$'"#
+        );
+    }
+}
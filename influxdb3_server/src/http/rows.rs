@@ -0,0 +1,294 @@
+//! Conversion of rows written as JSON or annotated CSV to line protocol.
+//!
+//! Each row is converted to a single line of line protocol, which is then
+//! validated by the write buffer in the same way as any other write. Rows that
+//! can't be converted, and lines the write buffer rejects, are reported with
+//! the line number and original text of the row in the request body, so the
+//! errors read the same as those of a line protocol write.
+use std::fmt;
+
+use influxdb3_write::WriteLineError;
+use influxdb_line_protocol::{builder, parse_lines, LineProtocolBuilder};
+use serde::Deserialize;
+
+mod csv;
+mod json;
+
+pub(super) use self::csv::csv_to_lp;
+pub(super) use self::json::json_to_lp;
+
+/// The unit of numeric timestamps.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum Precision {
+    #[serde(rename = "s")]
+    Seconds,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "us")]
+    Microseconds,
+    #[default]
+    #[serde(rename = "ns")]
+    Nanoseconds,
+}
+
+impl Precision {
+    /// Convert a timestamp in this precision to nanoseconds, failing if it
+    /// doesn't fit in an `i64`.
+    fn to_nanos(self, timestamp: i64) -> Result<i64, String> {
+        let multiplier = match self {
+            Self::Seconds => 1_000_000_000,
+            Self::Milliseconds => 1_000_000,
+            Self::Microseconds => 1_000,
+            Self::Nanoseconds => 1,
+        };
+        timestamp
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("timestamp {timestamp} is out of range"))
+    }
+}
+
+/// Parse an RFC3339 timestamp, such as `2023-10-01T12:00:00Z`, to nanoseconds.
+fn parse_rfc3339(s: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map_err(|e| format!("invalid RFC3339 timestamp {s:?}: {e}"))?
+        .timestamp_nanos_opt()
+        .ok_or_else(|| format!("timestamp {s:?} is out of range"))
+}
+
+/// The value of a field.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Float(f64),
+    Integer(i64),
+    UInteger(u64),
+    Boolean(bool),
+    String(String),
+}
+
+impl builder::FieldValue for &Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Float(v) => builder::FieldValue::fmt(v, f),
+            Value::Integer(v) => builder::FieldValue::fmt(v, f),
+            Value::UInteger(v) => builder::FieldValue::fmt(v, f),
+            Value::Boolean(v) => builder::FieldValue::fmt(v, f),
+            Value::String(v) => builder::FieldValue::fmt(&v.as_str(), f),
+        }
+    }
+}
+
+/// A row of a JSON or CSV write, before conversion to line protocol.
+#[derive(Debug, Default)]
+struct Row {
+    measurement: String,
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, Value)>,
+    /// The timestamp in nanoseconds, or `None` to use the time of the write.
+    time: Option<i64>,
+}
+
+impl Row {
+    /// Convert the row to a line of line protocol, without a trailing newline.
+    ///
+    /// Tags with an empty value are treated as missing, as they can't be
+    /// written.
+    fn to_lp(&self) -> Result<Vec<u8>, String> {
+        if self.measurement.is_empty() {
+            return Err("missing measurement".to_string());
+        }
+        let Some(((first_key, first_value), fields)) = self.fields.split_first() else {
+            return Err("row has no fields".to_string());
+        };
+        for (key, value) in &self.fields {
+            if matches!(value, Value::Float(v) if !v.is_finite()) {
+                return Err(format!("field {key} is not a finite number"));
+            }
+        }
+
+        let mut line = LineProtocolBuilder::new().measurement(&self.measurement);
+        for (key, value) in self.tags.iter().filter(|(_, v)| !v.is_empty()) {
+            line = line.tag(key, value);
+        }
+        let mut line = line.field(first_key, first_value);
+        for (key, value) in fields {
+            line = line.field(key, value);
+        }
+        let lp = match self.time {
+            Some(time) => line.timestamp(time).close_line().build(),
+            None => line.close_line().build(),
+        };
+
+        // The builder doesn't escape every character that is special to the
+        // parser, such as newlines in names, so check the row reads back as a
+        // single line rather than letting it corrupt the lines around it.
+        let mut lp = String::from_utf8(lp).expect("line protocol is built from strings");
+        lp.truncate(lp.trim_end_matches('\n').len());
+        let mut lines = parse_lines(&lp);
+        match (lines.next(), lines.next()) {
+            (Some(Ok(_)), None) => Ok(lp.into_bytes()),
+            (Some(Err(e)), _) => Err(format!("row can't be written as line protocol: {e}")),
+            _ => Err("row can't be written as a single line of line protocol".to_string()),
+        }
+    }
+}
+
+/// Line protocol converted from the rows of a JSON or CSV write.
+#[derive(Debug, Default)]
+pub(super) struct RowsLineProtocol {
+    /// One line for each row that was converted.
+    pub(super) lp: Vec<u8>,
+    /// The line number and original text in the request body of each line
+    /// in `lp`.
+    lines: Vec<(usize, String)>,
+    /// The rows that couldn't be converted.
+    invalid_rows: Vec<WriteLineError>,
+    /// The total number of rows in the request.
+    pub(super) row_count: usize,
+}
+
+impl RowsLineProtocol {
+    /// Add the row found at `line_number` of the request, or the reason it
+    /// couldn't be read.
+    fn push(&mut self, line_number: usize, original_line: String, row: Result<Row, String>) {
+        self.row_count += 1;
+        match row.and_then(|row| row.to_lp()) {
+            Ok(lp) => {
+                self.lp.extend_from_slice(&lp);
+                self.lp.push(b'\n');
+                self.lines.push((line_number, original_line));
+            }
+            Err(error_message) => self.invalid_rows.push(WriteLineError {
+                original_line,
+                line_number,
+                error_message,
+            }),
+        }
+    }
+
+    /// Combine the rows that couldn't be converted with the lines of `lp`
+    /// rejected by the write buffer, mapped back to the rows of the request.
+    pub(super) fn invalid_rows(self, rejected_lines: Vec<WriteLineError>) -> Vec<WriteLineError> {
+        let mut invalid_rows = self.invalid_rows;
+        for rejected in rejected_lines {
+            let (line_number, original_line) = self
+                .lines
+                .get(rejected.line_number - 1)
+                .cloned()
+                .unwrap_or((rejected.line_number, rejected.original_line));
+            invalid_rows.push(WriteLineError {
+                original_line,
+                line_number,
+                error_message: rejected.error_message,
+            });
+        }
+        invalid_rows.sort_by_key(|e| e.line_number);
+        invalid_rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(fields: Vec<(&str, Value)>) -> Row {
+        Row {
+            measurement: "cpu".to_string(),
+            tags: vec![
+                ("host".to_string(), "a b".to_string()),
+                ("region".to_string(), "".to_string()),
+            ],
+            fields: fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            time: Some(10),
+        }
+    }
+
+    #[test]
+    fn row_to_lp() {
+        let lp = row(vec![
+            ("f", Value::Float(1.5)),
+            ("i", Value::Integer(-2)),
+            ("u", Value::UInteger(3)),
+            ("b", Value::Boolean(true)),
+            ("s", Value::String(r#"say "hi""#.to_string())),
+        ])
+        .to_lp()
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(lp).unwrap(),
+            r#"cpu,host=a\ b f=1.5,i=-2i,u=3u,b=true,s="say \"hi\"" 10"#
+        );
+
+        let mut no_time = row(vec![("f", Value::Float(1.0))]);
+        no_time.time = None;
+        assert_eq!(
+            String::from_utf8(no_time.to_lp().unwrap()).unwrap(),
+            r#"cpu,host=a\ b f=1"#
+        );
+    }
+
+    #[test]
+    fn invalid_row_to_lp() {
+        assert_eq!(row(vec![]).to_lp().unwrap_err(), "row has no fields");
+        assert_eq!(
+            row(vec![("f", Value::Float(f64::NAN))])
+                .to_lp()
+                .unwrap_err(),
+            "field f is not a finite number"
+        );
+
+        let mut row = row(vec![("f", Value::Float(1.0))]);
+        row.tags.push(("line\nbreak".to_string(), "a".to_string()));
+        assert!(row.to_lp().is_err());
+        row.measurement = String::new();
+        assert_eq!(row.to_lp().unwrap_err(), "missing measurement");
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let mut rows = RowsLineProtocol::default();
+        rows.push(2, "a".to_string(), Ok(row(vec![("f", Value::Float(1.0))])));
+        rows.push(3, "b".to_string(), Err("bad row".to_string()));
+        rows.push(5, "c".to_string(), Ok(row(vec![("f", Value::Integer(1))])));
+        assert_eq!(rows.row_count, 3);
+        assert_eq!(
+            String::from_utf8(rows.lp.clone()).unwrap(),
+            "cpu,host=a\\ b f=1 10\ncpu,host=a\\ b f=1i 10\n"
+        );
+
+        let rejected = vec![WriteLineError {
+            original_line: "cpu,host=a\\ b f=1i 10".to_string(),
+            line_number: 2,
+            error_message: "type conflict".to_string(),
+        }];
+        let invalid: Vec<_> = rows
+            .invalid_rows(rejected)
+            .into_iter()
+            .map(|e| (e.line_number, e.original_line, e.error_message))
+            .collect();
+        assert_eq!(
+            invalid,
+            vec![
+                (3, "b".to_string(), "bad row".to_string()),
+                (5, "c".to_string(), "type conflict".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn precision() {
+        assert_eq!(Precision::Seconds.to_nanos(2).unwrap(), 2_000_000_000);
+        assert_eq!(Precision::Milliseconds.to_nanos(2).unwrap(), 2_000_000);
+        assert_eq!(Precision::Microseconds.to_nanos(2).unwrap(), 2_000);
+        assert_eq!(Precision::Nanoseconds.to_nanos(2).unwrap(), 2);
+        assert!(Precision::Seconds.to_nanos(i64::MAX).is_err());
+
+        assert_eq!(
+            parse_rfc3339("1970-01-01T00:00:01.5Z").unwrap(),
+            1_500_000_000
+        );
+        assert!(parse_rfc3339("yesterday").is_err());
+    }
+}
@@ -0,0 +1,475 @@
+//! Conversion of CSV writes, annotated in the style of the InfluxDB 2 write
+//! API.
+//!
+//! The header row names the columns, and may be preceded by annotation rows
+//! that start with `#`:
+//!
+//! ```csv
+//! #datatype measurement,tag,double,boolean,dateTime:RFC3339
+//! #default ,,,false,
+//! m,host,used_percent,active,time
+//! cpu,host1,64.2,true,2023-10-01T12:00:00Z
+//! cpu,host2,43.1,,2023-10-01T12:00:00Z
+//! ```
+//!
+//! The `#datatype` annotation gives the type of each column:
+//!
+//! | Datatype                                     | Column                                        |
+//! |----------------------------------------------|-----------------------------------------------|
+//! | `measurement`                                | The table to write the row to                 |
+//! | `tag`                                        | A tag                                         |
+//! | `double`, `long`, `unsignedLong`, `boolean`  | A float, integer, unsigned integer or boolean |
+//! | `string`                                     | A string field                                |
+//! | `dateTime`                                   | The timestamp, as a number or RFC3339 string  |
+//! | `dateTime:RFC3339`, `dateTime:RFC3339Nano`   | The timestamp, as an RFC3339 string           |
+//! | `dateTime:number`                            | The timestamp, as a number                    |
+//! | `ignored`                                    | Not written                                   |
+//!
+//! Columns without a datatype are string fields, except for a column named
+//! `time`, which is the timestamp. Numeric timestamps are in the precision of
+//! the write, and rows without a timestamp use the time of the write. If
+//! there is no measurement column, every row is written to the `measurement`
+//! of the write.
+//!
+//! The `#default` annotation gives the value of each column for rows that
+//! leave it empty. Empty values without a default are treated as missing.
+//!
+//! Each `#constant` annotation adds a column with the same value in every
+//! row, given by its datatype, name and value, such as `#constant
+//! tag,region,west`. The name is left out for a measurement or dateTime, as
+//! in `#constant measurement,cpu`. The `#group` annotation is ignored.
+use ::csv::{ReaderBuilder, StringRecord};
+
+use super::{parse_rfc3339, Precision, Row, RowsLineProtocol, Value};
+use crate::http::{Error, Result, RowWriteParams};
+
+/// The type of a CSV column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Measurement,
+    Tag,
+    Double,
+    Long,
+    UnsignedLong,
+    Boolean,
+    String,
+    /// A timestamp in either format.
+    DateTime,
+    DateTimeRfc3339,
+    DateTimeNumber,
+    Ignored,
+}
+
+impl ColumnType {
+    fn parse(datatype: &str) -> Result<Self> {
+        Ok(match datatype {
+            "measurement" => Self::Measurement,
+            "tag" => Self::Tag,
+            "double" => Self::Double,
+            "long" => Self::Long,
+            "unsignedLong" => Self::UnsignedLong,
+            "boolean" => Self::Boolean,
+            "string" => Self::String,
+            "dateTime" => Self::DateTime,
+            "dateTime:RFC3339" | "dateTime:RFC3339Nano" => Self::DateTimeRfc3339,
+            "dateTime:number" => Self::DateTimeNumber,
+            "ignored" => Self::Ignored,
+            _ => {
+                return Err(Error::InvalidRowWrite(format!(
+                    "unknown CSV datatype {datatype:?}"
+                )))
+            }
+        })
+    }
+
+    fn is_time(self) -> bool {
+        matches!(
+            self,
+            Self::DateTime | Self::DateTimeRfc3339 | Self::DateTimeNumber
+        )
+    }
+}
+
+/// A CSV column, from the header row and annotations.
+#[derive(Debug)]
+struct Column {
+    name: String,
+    column_type: ColumnType,
+    default: Option<String>,
+}
+
+impl Column {
+    /// Add the `value` of this column to `row`.
+    fn add_to_row(&self, row: &mut Row, value: &str, precision: Precision) -> Result<(), String> {
+        let name = &self.name;
+        let field = match self.column_type {
+            ColumnType::Measurement => {
+                row.measurement = value.to_string();
+                return Ok(());
+            }
+            ColumnType::Tag => {
+                row.tags.push((name.clone(), value.to_string()));
+                return Ok(());
+            }
+            ColumnType::Ignored => return Ok(()),
+            ColumnType::DateTime => {
+                row.time = Some(match value.parse::<i64>() {
+                    Ok(time) => precision.to_nanos(time)?,
+                    Err(_) => parse_rfc3339(value)?,
+                });
+                return Ok(());
+            }
+            ColumnType::DateTimeRfc3339 => {
+                row.time = Some(parse_rfc3339(value)?);
+                return Ok(());
+            }
+            ColumnType::DateTimeNumber => {
+                let time = value
+                    .parse::<i64>()
+                    .map_err(|e| format!("invalid timestamp {value:?}: {e}"))?;
+                row.time = Some(precision.to_nanos(time)?);
+                return Ok(());
+            }
+            ColumnType::Double => Value::Float(
+                value
+                    .parse()
+                    .map_err(|e| format!("invalid double {value:?} for {name}: {e}"))?,
+            ),
+            ColumnType::Long => Value::Integer(
+                value
+                    .parse()
+                    .map_err(|e| format!("invalid long {value:?} for {name}: {e}"))?,
+            ),
+            ColumnType::UnsignedLong => Value::UInteger(
+                value
+                    .parse()
+                    .map_err(|e| format!("invalid unsignedLong {value:?} for {name}: {e}"))?,
+            ),
+            ColumnType::Boolean => Value::Boolean(
+                parse_bool(value).ok_or_else(|| format!("invalid boolean {value:?} for {name}"))?,
+            ),
+            ColumnType::String => Value::String(value.to_string()),
+        };
+        row.fields.push((name.clone(), field));
+        Ok(())
+    }
+}
+
+/// Parse a boolean in any of the forms accepted by the InfluxDB 2 write API.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Split an annotation row into its name, such as `#datatype`, and values.
+/// The name shares the first cell with the first value.
+fn annotation(record: &StringRecord) -> (&str, Vec<&str>) {
+    let (name, first) = record[0]
+        .split_once(char::is_whitespace)
+        .unwrap_or((&record[0], ""));
+    let values = std::iter::once(first.trim_start())
+        .chain(record.iter().skip(1))
+        .collect();
+    (name, values)
+}
+
+/// Build a column from the values of a `#constant` annotation, with the
+/// constant value as its default.
+fn constant_column(values: &[&str]) -> Result<Column> {
+    // The datatype may follow the name of the annotation in its own cell.
+    let values = match values {
+        ["", rest @ ..] => rest,
+        _ => values,
+    };
+    let Some((datatype, rest)) = values.split_first() else {
+        return Err(Error::InvalidRowWrite(
+            "CSV #constant annotation has no datatype".into(),
+        ));
+    };
+    let column_type = ColumnType::parse(datatype)?;
+    let (name, value) = match rest {
+        [value] if column_type == ColumnType::Measurement || column_type.is_time() => {
+            (format!("#constant {datatype}"), *value)
+        }
+        [name, value, ..] => (name.to_string(), *value),
+        [name] => (name.to_string(), ""),
+        [] => (String::new(), ""),
+    };
+    if name.is_empty() {
+        return Err(Error::InvalidRowWrite(format!(
+            "CSV #constant annotation of {datatype} has no column name"
+        )));
+    }
+    Ok(Column {
+        name,
+        column_type,
+        default: (!value.is_empty()).then(|| value.to_string()),
+    })
+}
+
+/// Build the columns from the `header` row and the annotations before it.
+/// The `constants` are checked along with the columns of the header.
+fn read_columns(
+    header: &StringRecord,
+    datatypes: &[String],
+    defaults: &[String],
+    constants: &[Column],
+    params: &RowWriteParams,
+) -> Result<Vec<Column>> {
+    let mut columns = Vec::with_capacity(header.len());
+    for (idx, name) in header.iter().enumerate() {
+        let column_type = match datatypes.get(idx).map(String::as_str).unwrap_or_default() {
+            "" if name == params.time_key() => ColumnType::DateTime,
+            "" => ColumnType::String,
+            datatype => ColumnType::parse(datatype)?,
+        };
+        let default = defaults.get(idx).filter(|v| !v.is_empty()).cloned();
+        columns.push(Column {
+            name: name.to_string(),
+            column_type,
+            default,
+        });
+    }
+
+    let all_columns = || columns.iter().chain(constants);
+    if all_columns().filter(|c| c.column_type.is_time()).count() > 1 {
+        return Err(Error::InvalidRowWrite(
+            "CSV has more than one dateTime column".into(),
+        ));
+    }
+    if params.measurement.is_none()
+        && !all_columns().any(|c| c.column_type == ColumnType::Measurement)
+    {
+        return Err(Error::InvalidRowWrite(
+            "CSV has no measurement column and there is no query parameter 'measurement'".into(),
+        ));
+    }
+    Ok(columns)
+}
+
+/// Convert the annotated CSV `body` of a write to line protocol.
+///
+/// An error is returned if the annotations or header are invalid, otherwise
+/// each row that can't be converted is an invalid row, numbered by the line
+/// it starts on.
+pub(in crate::http) fn csv_to_lp(body: &[u8], params: &RowWriteParams) -> Result<RowsLineProtocol> {
+    let precision = params.precision.unwrap_or_default();
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(body);
+    let mut record = StringRecord::new();
+    let mut read_record = |record: &mut StringRecord| {
+        reader
+            .read_record(record)
+            .map_err(|e| Error::InvalidRowWrite(format!("invalid CSV: {e}")))
+            .map(|more| more.then(|| reader.position().byte() as usize))
+    };
+
+    // Read the annotations, up to and including the header row.
+    let mut datatypes: Vec<String> = vec![];
+    let mut defaults: Vec<String> = vec![];
+    let mut constants: Vec<Column> = vec![];
+    let columns = loop {
+        if read_record(&mut record)?.is_none() {
+            return Ok(RowsLineProtocol::default());
+        }
+        if !record[0].starts_with('#') {
+            break read_columns(&record, &datatypes, &defaults, &constants, params)?;
+        }
+        let (name, values) = annotation(&record);
+        match name {
+            "#datatype" => datatypes = values.into_iter().map(str::to_string).collect(),
+            "#default" => defaults = values.into_iter().map(str::to_string).collect(),
+            "#constant" => constants.push(constant_column(&values)?),
+            "#group" => {}
+            _ => {
+                return Err(Error::InvalidRowWrite(format!(
+                    "unsupported CSV annotation {name:?}"
+                )))
+            }
+        }
+    };
+    let mut lp = RowsLineProtocol::default();
+    while let Some(end) = read_record(&mut record)? {
+        let start = record.position().expect("records read have a position");
+        let line_number = start.line() as usize;
+        let original_line = String::from_utf8_lossy(&body[start.byte() as usize..end])
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+
+        let row = if record.len() > columns.len() {
+            Err(format!(
+                "row has {} values but the header has {} columns",
+                record.len(),
+                columns.len()
+            ))
+        } else {
+            record_to_row(&columns, &constants, &record, params, precision)
+        };
+        lp.push(line_number, original_line, row);
+    }
+    Ok(lp)
+}
+
+fn record_to_row(
+    columns: &[Column],
+    constants: &[Column],
+    record: &StringRecord,
+    params: &RowWriteParams,
+    precision: Precision,
+) -> Result<Row, String> {
+    let mut row = Row {
+        measurement: params.measurement.clone().unwrap_or_default(),
+        ..Default::default()
+    };
+    for (idx, column) in columns.iter().enumerate() {
+        let value = record
+            .get(idx)
+            .filter(|v| !v.is_empty())
+            .or(column.default.as_deref());
+        if let Some(value) = value {
+            column.add_to_row(&mut row, value, precision)?;
+        }
+    }
+    for constant in constants {
+        if let Some(value) = &constant.default {
+            constant.add_to_row(&mut row, value, precision)?;
+        }
+    }
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(query: &str) -> RowWriteParams {
+        serde_urlencoded::from_str(query).unwrap()
+    }
+
+    fn convert(body: &str, query: &str) -> (String, Vec<(usize, String, String)>) {
+        let lp = csv_to_lp(body.as_bytes(), &params(query)).unwrap();
+        let text = String::from_utf8(lp.lp.clone()).unwrap();
+        let invalid = lp
+            .invalid_rows(vec![])
+            .into_iter()
+            .map(|e| (e.line_number, e.original_line, e.error_message))
+            .collect();
+        (text, invalid)
+    }
+
+    #[test]
+    fn annotated() {
+        let body = "#datatype measurement,tag,double,long,unsignedLong,boolean,string,ignored,dateTime:RFC3339\n\
+                    #default ,,,,,false,,,\n\
+                    m,host,f,i,u,b,s,x,time\n\
+                    cpu,a,1.5,-2,3,yes,hello,skip,1970-01-01T00:00:01Z\r\n\
+                    disk,,2,,,,,,\n";
+        let (lp, invalid) = convert(body, "db=foo");
+        assert_eq!(
+            lp,
+            "cpu,host=a f=1.5,i=-2i,u=3u,b=true,s=\"hello\" 1000000000\n\
+             disk f=2,b=false\n"
+        );
+        assert!(invalid.is_empty(), "{invalid:?}");
+    }
+
+    #[test]
+    fn constant_and_group() {
+        let body = "#group false,false,false\n\
+                    #constant measurement,cpu\n\
+                    #constant,tag,region,west\n\
+                    #constant long,core,2\n\
+                    #constant dateTime:number,10\n\
+                    #datatype tag,double,ignored\n\
+                    host,f,x\n\
+                    a,1.5,\n\
+                    b,,\n";
+        let (lp, invalid) = convert(body, "db=foo&precision=s");
+        assert_eq!(
+            lp,
+            "cpu,host=a,region=west f=1.5,core=2i 10000000000\n\
+             cpu,host=b,region=west core=2i 10000000000\n"
+        );
+        assert!(invalid.is_empty(), "{invalid:?}");
+
+        // a constant replaces the measurement of the write
+        let (lp, _) = convert(
+            "#constant measurement,disk\nfree\n10\n",
+            "db=foo&measurement=m",
+        );
+        assert_eq!(lp, "disk free=\"10\"\n");
+    }
+
+    #[test]
+    fn unannotated() {
+        let body = "time,note\n10,a\n,b\n";
+        let (lp, _) = convert(body, "db=foo&measurement=m&precision=us");
+        assert_eq!(lp, "m note=\"a\" 10000\nm note=\"b\"\n");
+
+        let (lp, _) = convert(
+            "ts,note\n2023-10-01T00:00:00Z,a\n",
+            "db=foo&measurement=m&time_key=ts",
+        );
+        assert_eq!(lp, "m note=\"a\" 1696118400000000000\n");
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let body = "#datatype tag,double\n\
+                    host,f\n\
+                    a,x\n\
+                    \n\
+                    b,1,extra\n\
+                    c,\n\
+                    d,2\n";
+        let (lp, invalid) = convert(body, "db=foo&measurement=m");
+        assert_eq!(lp, "m,host=d f=2\n");
+        let expected = [
+            (3, "a,x", "invalid double \"x\" for f"),
+            (
+                5,
+                "b,1,extra",
+                "row has 3 values but the header has 2 columns",
+            ),
+            (6, "c,", "row has no fields"),
+        ];
+        assert_eq!(invalid.len(), expected.len(), "{invalid:?}");
+        for ((line_number, original_line, error), expected) in invalid.iter().zip(expected) {
+            assert_eq!(*line_number, expected.0);
+            assert_eq!(original_line, expected.1);
+            assert!(error.starts_with(expected.2), "{error}");
+        }
+    }
+
+    #[test]
+    fn invalid_csv() {
+        for (body, query) in [
+            ("#datatype tag,float\nhost,f\n", "db=foo&measurement=m"),
+            ("#foo false,false\nhost,f\n", "db=foo&measurement=m"),
+            ("#constant tag\nhost,f\n", "db=foo&measurement=m"),
+            ("#constant float,f,1\nhost,f\n", "db=foo&measurement=m"),
+            (
+                "#constant dateTime,1\n#datatype dateTime\ntime\n",
+                "db=foo&measurement=m",
+            ),
+            ("host,f\n", "db=foo"),
+            (
+                "#datatype dateTime,dateTime:number\na,b\n",
+                "db=foo&measurement=m",
+            ),
+        ] {
+            assert!(
+                csv_to_lp(body.as_bytes(), &params(query)).is_err(),
+                "{body}"
+            );
+        }
+
+        let (lp, invalid) = convert("", "db=foo");
+        assert!(lp.is_empty() && invalid.is_empty());
+    }
+}
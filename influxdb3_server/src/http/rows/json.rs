@@ -0,0 +1,193 @@
+//! Conversion of JSON writes, which are an array of objects with one object
+//! for each row.
+//!
+//! For example, with `measurement=weather&tags=location`:
+//!
+//! ```json
+//! [
+//!     {"time": "2023-10-01T12:00:00Z", "location": "london", "temp": 12.5, "raining": true},
+//!     {"time": 1696165200000000000, "location": "paris", "temp": 15.0, "raining": false}
+//! ]
+//! ```
+//!
+//! The `time_key` of each object is its timestamp, either a number in the
+//! precision of the write or an RFC3339 string. Rows without a timestamp use
+//! the time of the write. The keys listed in `tags` are tags, and the keys
+//! listed in `fields`, or all other keys if `fields` isn't given, are fields.
+//! Numbers are written as float fields, so that a column doesn't change type
+//! when a value happens to be whole. Null values are treated as missing.
+use serde_json::{Map, Value as JsonValue};
+
+use super::{parse_rfc3339, Precision, Row, RowsLineProtocol, Value};
+use crate::http::{Error, Result, RowWriteParams};
+
+/// Convert the JSON `body` of a write to line protocol.
+///
+/// An error is returned if the body isn't a JSON array, otherwise each
+/// element that can't be converted is an invalid row, numbered from 1.
+pub(in crate::http) fn json_to_lp(
+    body: &[u8],
+    params: &RowWriteParams,
+) -> Result<RowsLineProtocol> {
+    let measurement = params
+        .measurement
+        .as_deref()
+        .ok_or_else(|| Error::InvalidRowWrite("missing query parameter 'measurement'".into()))?;
+    let rows: Vec<JsonValue> = match serde_json::from_slice(body).map_err(Error::InvalidJson)? {
+        JsonValue::Array(rows) => rows,
+        _ => {
+            return Err(Error::InvalidRowWrite(
+                "JSON body must be an array of objects".into(),
+            ))
+        }
+    };
+
+    let time_key = params.time_key();
+    let tags = params.tag_keys();
+    let fields = params.field_keys();
+    let precision = params.precision.unwrap_or_default();
+
+    let mut lp = RowsLineProtocol::default();
+    for (idx, value) in rows.iter().enumerate() {
+        let row = match value {
+            JsonValue::Object(object) => object_to_row(
+                measurement,
+                object,
+                time_key,
+                &tags,
+                fields.as_deref(),
+                precision,
+            ),
+            _ => Err("row is not a JSON object".to_string()),
+        };
+        lp.push(idx + 1, value.to_string(), row);
+    }
+    Ok(lp)
+}
+
+fn object_to_row(
+    measurement: &str,
+    object: &Map<String, JsonValue>,
+    time_key: &str,
+    tags: &[&str],
+    fields: Option<&[&str]>,
+    precision: Precision,
+) -> Result<Row, String> {
+    let mut row = Row {
+        measurement: measurement.to_string(),
+        ..Default::default()
+    };
+    for (key, value) in object {
+        if value.is_null() {
+            continue;
+        }
+        if key == time_key {
+            row.time = Some(match value {
+                JsonValue::Number(n) => {
+                    let time = n
+                        .as_i64()
+                        .ok_or_else(|| format!("timestamp {n} is not an integer"))?;
+                    precision.to_nanos(time)?
+                }
+                JsonValue::String(s) => parse_rfc3339(s)?,
+                _ => return Err(format!("timestamp {value} is not a number or string")),
+            });
+        } else if tags.contains(&key.as_str()) {
+            let tag = match value {
+                JsonValue::String(s) => s.clone(),
+                JsonValue::Number(_) | JsonValue::Bool(_) => value.to_string(),
+                _ => return Err(format!("tag {key} must be a string, number or boolean")),
+            };
+            row.tags.push((key.clone(), tag));
+        } else if fields.map_or(true, |fields| fields.contains(&key.as_str())) {
+            let field = match value {
+                JsonValue::Number(n) => Value::Float(
+                    n.as_f64()
+                        .ok_or_else(|| format!("field {key} is not a valid number"))?,
+                ),
+                JsonValue::Bool(b) => Value::Boolean(*b),
+                JsonValue::String(s) => Value::String(s.clone()),
+                _ => return Err(format!("field {key} must be a string, number or boolean")),
+            };
+            row.fields.push((key.clone(), field));
+        }
+    }
+    // Order the tags and fields by key, whatever order the object has.
+    row.tags.sort();
+    row.fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(query: &str) -> RowWriteParams {
+        serde_urlencoded::from_str(query).unwrap()
+    }
+
+    fn convert(body: &str, query: &str) -> (String, Vec<(usize, String)>) {
+        let lp = json_to_lp(body.as_bytes(), &params(query)).unwrap();
+        let text = String::from_utf8(lp.lp.clone()).unwrap();
+        let invalid = lp
+            .invalid_rows(vec![])
+            .into_iter()
+            .map(|e| (e.line_number, e.error_message))
+            .collect();
+        (text, invalid)
+    }
+
+    #[test]
+    fn rows() {
+        let body = r#"[
+            {"time": "1970-01-01T00:00:01Z", "location": "london", "temp": 12.5, "raining": true, "note": null},
+            {"time": 2, "location": "paris", "temp": 15, "desc": "sunny"}
+        ]"#;
+        let (lp, invalid) = convert(body, "db=foo&measurement=weather&tags=location");
+        assert_eq!(
+            lp,
+            "weather,location=london raining=true,temp=12.5 1000000000\n\
+             weather,location=paris desc=\"sunny\",temp=15 2\n"
+        );
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn mapping() {
+        let body = r#"[{"ts": 2, "id": 7, "temp": 15.5, "extra": "x"}]"#;
+        let (lp, _) = convert(
+            body,
+            "db=foo&measurement=m&time_key=ts&tags=id&fields=temp&precision=s",
+        );
+        assert_eq!(lp, "m,id=7 temp=15.5 2000000000\n");
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let body = r#"[
+            {"temp": 1},
+            42,
+            {"temp": {"nested": 1}},
+            {"time": "yesterday", "temp": 1},
+            {"other": null}
+        ]"#;
+        let (lp, invalid) = convert(body, "db=foo&measurement=m");
+        assert_eq!(lp, "m temp=1\n");
+        let invalid: Vec<_> = invalid.into_iter().map(|(n, _)| n).collect();
+        assert_eq!(invalid, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn invalid_body() {
+        for (body, query) in [
+            (r#"{"temp": 1}"#, "db=foo&measurement=m"),
+            ("[", "db=foo&measurement=m"),
+            ("[]", "db=foo"),
+        ] {
+            assert!(
+                json_to_lp(body.as_bytes(), &params(query)).is_err(),
+                "{body}"
+            );
+        }
+    }
+}
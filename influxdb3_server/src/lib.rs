@@ -1,6 +1,7 @@
 //! InfluxDB 3.0 Edge server implementation
 //!
-//! The server is responsible for handling the HTTP API
+//! The server is responsible for handling the HTTP API, and the gRPC services such as Arrow
+//! Flight, which are served on the same port
 #![deny(rustdoc::broken_intra_doc_links, rustdoc::bare_urls, rust_2018_idioms)]
 #![warn(
 missing_debug_implementations,
@@ -11,17 +12,29 @@ clippy::clone_on_ref_ptr,
 clippy::future_not_send
 )]
 
+pub mod continuous_query;
+mod grpc;
 mod http;
+mod otlp;
 pub mod query_executor;
+mod storage;
+pub mod tls;
 
+use crate::continuous_query::ContinuousQueryScheduler;
 use crate::http::HttpApi;
+use crate::tls::{ReloadableTlsAcceptor, TlsConfig};
 use async_trait::async_trait;
 use datafusion::execution::SendableRecordBatchStream;
-use influxdb3_write::{Persister, WriteBuffer};
-use observability_deps::tracing::info;
+use influxdb3_write::{Persister, Wal, WriteBuffer};
+use iox_time::TimeProvider;
+use metric::DurationHistogram;
+use observability_deps::tracing::{error, info};
+use service_common::planner::StatementParams;
+use service_common::QueryNamespaceProvider;
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tokio_util::sync::CancellationToken;
 use trace::ctx::SpanContext;
@@ -39,6 +52,15 @@ pub enum Error {
 
     #[error("datafusion error: {0}")]
     DataFusion(#[from] datafusion::error::DataFusionError),
+
+    #[error("tls error: {0}")]
+    Tls(#[from] tls::Error),
+
+    #[error("write buffer error: {0}")]
+    WriteBuffer(#[from] influxdb3_write::Error),
+
+    #[error("timed out after {timeout:?} persisting the open segment")]
+    PersistTimeout { timeout: Duration },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -49,6 +71,8 @@ pub struct CommonServerState {
     trace_exporter: Option<Arc<trace_exporters::export::AsyncExporter>>,
     trace_header_parser: TraceHeaderParser,
     http_addr: SocketAddr,
+    tls_config: Option<TlsConfig>,
+    time_provider: Arc<dyn TimeProvider>,
 }
 
 impl CommonServerState {
@@ -57,12 +81,16 @@ impl CommonServerState {
         trace_exporter: Option<Arc<trace_exporters::export::AsyncExporter>>,
         trace_header_parser: TraceHeaderParser,
         http_addr: SocketAddr,
+        tls_config: Option<TlsConfig>,
+        time_provider: Arc<dyn TimeProvider>,
     ) -> Self {
         Self {
             metrics,
             trace_exporter,
             trace_header_parser,
             http_addr,
+            tls_config,
+            time_provider,
         }
     }
 
@@ -83,19 +111,48 @@ impl CommonServerState {
     pub fn metric_registry(&self) -> Arc<metric::Registry> {
         Arc::<metric::Registry>::clone(&self.metrics)
     }
+
+    pub fn time_provider(&self) -> Arc<dyn TimeProvider> {
+        Arc::clone(&self.time_provider)
+    }
 }
 
 #[derive(Debug)]
 pub struct Server<W, Q> {
     http: Arc<HttpApi<W, Q>>,
+    persister: Arc<dyn Persister>,
+    write_buffer: Arc<W>,
+    query_executor: Arc<Q>,
+    continuous_queries: Option<ContinuousQueryScheduler<W, Q>>,
+    persist_timeout: Duration,
+}
+
+/// The language a query is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    Sql,
+    InfluxQl,
+}
+
+impl QueryKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sql => "sql",
+            Self::InfluxQl => "influxql",
+        }
+    }
 }
 
+/// Runs queries against the databases in the write buffer. It is also the
+/// [`QueryNamespaceProvider`] used by the Arrow Flight service.
 #[async_trait]
-pub trait QueryExecutor: Debug + Send + Sync + 'static {
+pub trait QueryExecutor: QueryNamespaceProvider + Debug + Send + Sync + 'static {
     async fn query(
         &self,
         database: &str,
         q: &str,
+        params: StatementParams,
+        kind: QueryKind,
         span_ctx: Option<SpanContext>,
         external_span_ctx: Option<RequestLogContext>,
     ) -> Result<SendableRecordBatchStream>;
@@ -104,10 +161,11 @@ pub trait QueryExecutor: Debug + Send + Sync + 'static {
 impl<W, Q> Server<W, Q> {
     pub fn new(
         common_state: CommonServerState,
-        _persister: Arc<dyn Persister>,
+        persister: Arc<dyn Persister>,
         write_buffer: Arc<W>,
         query_executor: Arc<Q>,
         max_http_request_size: usize,
+        persist_timeout: Duration,
     ) -> Self {
         let http = Arc::new(HttpApi::new(
             common_state.clone(),
@@ -116,23 +174,150 @@ impl<W, Q> Server<W, Q> {
             max_http_request_size,
         ));
 
-        Self { http }
+        Self {
+            http,
+            persister,
+            write_buffer,
+            query_executor,
+            continuous_queries: None,
+            persist_timeout,
+        }
+    }
+
+    /// Run the continuous queries of `scheduler` while the server is serving. They are stopped
+    /// before the open segment is persisted on shutdown.
+    pub fn with_continuous_queries(mut self, scheduler: ContinuousQueryScheduler<W, Q>) -> Self {
+        self.continuous_queries = Some(scheduler);
+        self
     }
 }
 
 pub async fn serve<W: WriteBuffer, Q: QueryExecutor>(
-    server: Server<W, Q>,
+    mut server: Server<W, Q>,
     shutdown: CancellationToken,
 ) -> Result<()> {
+    // The persisted catalog and segments are loaded when the write buffer is created.
     // TODO:
-    //  1. load the persisted catalog and segments from the persister
-    //  2. load semgments into the buffer
-    //  3. persist any segments from the buffer that are closed and haven't yet been persisted
-    //  4. start serving
+    //  1. load WAL segments that weren't persisted into the buffer
+    //  2. persist any segments from the buffer that are closed and haven't yet been persisted
+
+    let tls_acceptor = match server.http.common_state().tls_config.clone() {
+        Some(tls_config) => {
+            let acceptor = Arc::new(ReloadableTlsAcceptor::new(tls_config)?);
+            tokio::spawn(tls::reload_on_sighup(
+                Arc::clone(&acceptor),
+                shutdown.clone(),
+            ));
+            Some(acceptor)
+        }
+        None => None,
+    };
+
+    let grpc_service = grpc::GrpcRouter::new(
+        grpc::make_otlp_metrics_server(
+            Arc::clone(&server.write_buffer),
+            server.http.common_state().time_provider(),
+        ),
+        grpc::GrpcRouter::new(
+            grpc::make_storage_server(Arc::clone(&server.query_executor)),
+            grpc::make_flight_server(
+                Arc::clone(&server.query_executor),
+                Arc::clone(&server.write_buffer),
+                server.http.common_state().time_provider(),
+            ),
+        ),
+    );
+
+    let continuous_queries = server.continuous_queries.take().map(|scheduler| {
+        let stop = CancellationToken::new();
+        (stop.clone(), tokio::spawn(scheduler.run(stop)))
+    });
+
+    http::serve(
+        Arc::clone(&server.http),
+        grpc_service,
+        tls_acceptor,
+        shutdown,
+    )
+    .await?;
+
+    // Continuous queries write into the buffer independently of any request, so wait for a
+    // run that is in progress to finish.
+    if let Some((stop, handle)) = continuous_queries {
+        stop.cancel();
+        if let Err(e) = handle.await {
+            error!(%e, "continuous query scheduler failed");
+        }
+    }
+
+    // The listener is closed, all in-flight requests have completed and continuous queries
+    // have stopped, so nothing else can be written into the open segment.
+    info!("HTTP server stopped, persisting open segment");
+    persist_open_segment(
+        server.write_buffer.as_ref(),
+        Arc::clone(&server.persister),
+        server.persist_timeout,
+        &server.http.common_state().metrics,
+    )
+    .await
+}
+
+/// Closes the open segment of the buffer and persists it along with the catalog, then deletes
+/// its WAL segments, giving up if that does not complete within `timeout`.
+async fn persist_open_segment<W: WriteBuffer>(
+    write_buffer: &W,
+    persister: Arc<dyn Persister>,
+    timeout: Duration,
+    metrics: &metric::Registry,
+) -> Result<()> {
+    let start = Instant::now();
+    let persisted = tokio::time::timeout(timeout, async {
+        let segment = write_buffer.close_open_segment().await?;
+        info!(segment_id = ?segment.id(), "persisting closed segment");
+        segment.persist(persister).await?;
+
+        // The writes of the segment, and of any earlier segments that were replayed into it,
+        // are persisted, so their WAL segments are no longer needed.
+        if let Some(wal) = write_buffer.wal() {
+            for segment_file in wal.segment_files()? {
+                if segment_file.segment_id <= segment.id() {
+                    wal.delete_wal_segment(segment_file.segment_id)?;
+                }
+            }
+        }
+
+        Ok::<_, influxdb3_write::Error>(())
+    })
+    .await;
+    let elapsed = start.elapsed();
 
-    http::serve(Arc::clone(&server.http), shutdown).await?;
+    let outcome = match &persisted {
+        Ok(Ok(())) => "success",
+        Ok(Err(_)) => "error",
+        Err(_) => "timeout",
+    };
+    metrics
+        .register_metric::<DurationHistogram>(
+            "influxdb3_shutdown_persist_duration",
+            "time taken to persist the open segment on shutdown",
+        )
+        .recorder(&[("outcome", outcome)])
+        .record(elapsed);
 
-    Ok(())
+    match persisted {
+        Ok(Ok(())) => {
+            info!(?elapsed, "persisted open segment");
+            Ok(())
+        }
+        Ok(Err(e)) => {
+            error!(%e, ?elapsed, "failed to persist open segment");
+            Err(e.into())
+        }
+        Err(_) => {
+            error!(?timeout, "timed out persisting open segment");
+            Err(Error::PersistTimeout { timeout })
+        }
+    }
 }
 
 /// On unix platforms we want to intercept SIGINT and SIGTERM
@@ -159,10 +344,17 @@ pub async fn wait_for_signal() {
 #[cfg(test)]
 mod tests {
     use crate::serve;
+    use crate::tls::TlsConfig;
+    use arrow::record_batch::RecordBatch;
+    use async_trait::async_trait;
+    use data_types::NamespaceName;
     use datafusion::parquet::data_type::AsBytes;
-    use hyper::{body, Body, Client, Request, Response};
+    use hyper::{body, Body, Client, Method, Request, Response, StatusCode};
     use influxdb3_write::persister::PersisterImpl;
+    use influxdb3_write::{Bufferer, PersistedCatalog, PersistedSegment, Persister, SegmentId};
     use iox_query::exec::{Executor, ExecutorConfig};
+    use iox_time::{MockProvider, SystemProvider, Time, TimeProvider};
+    use metric::{Attributes, DurationHistogram, Metric};
     use object_store::DynObjectStore;
     use parquet_file::storage::{ParquetStorage, StorageId};
     use std::collections::HashMap;
@@ -170,6 +362,7 @@ mod tests {
     use std::num::NonZeroUsize;
     use std::sync::atomic::{AtomicU16, Ordering};
     use std::sync::Arc;
+    use std::time::Duration;
     use tokio_util::sync::CancellationToken;
 
     static NEXT_PORT: AtomicU16 = AtomicU16::new(8090);
@@ -179,8 +372,15 @@ mod tests {
         let addr = get_free_port();
         let trace_header_parser = trace_http::ctx::TraceHeaderParser::new();
         let metrics = Arc::new(metric::Registry::new());
-        let common_state =
-            crate::CommonServerState::new(Arc::clone(&metrics), None, trace_header_parser, addr);
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(SystemProvider::new());
+        let common_state = crate::CommonServerState::new(
+            Arc::clone(&metrics),
+            None,
+            trace_header_parser,
+            addr,
+            None,
+            Arc::clone(&time_provider),
+        );
         let catalog = Arc::new(influxdb3_write::catalog::Catalog::new());
         let object_store: Arc<DynObjectStore> = Arc::new(object_store::memory::InMemory::new());
         let parquet_store =
@@ -200,6 +400,7 @@ mod tests {
         let write_buffer = Arc::new(influxdb3_write::write_buffer::WriteBufferImpl::new(
             Arc::clone(&catalog),
             None::<Arc<influxdb3_write::wal::WalImpl>>,
+            Arc::clone(&time_provider),
         ));
         let query_executor = crate::query_executor::QueryExecutorImpl::new(
             catalog,
@@ -208,6 +409,7 @@ mod tests {
             Arc::clone(&metrics),
             Arc::new(HashMap::new()),
             10,
+            Arc::clone(&time_provider),
         );
         let persister = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
 
@@ -217,6 +419,7 @@ mod tests {
             Arc::clone(&write_buffer),
             Arc::new(query_executor),
             usize::MAX,
+            Duration::from_secs(10),
         );
         let frontend_shutdown = CancellationToken::new();
         let shutdown = frontend_shutdown.clone();
@@ -225,7 +428,7 @@ mod tests {
 
         let server = format!("http://{}", addr);
         write_lp(&server, "foo", "cpu,host=a val=1i 123", None).await;
-        let res = query(server, "foo", "select * from cpu", None).await;
+        let res = query(&server, "foo", "select * from cpu", None).await;
 
         let body = body::to_bytes(res.into_body()).await.unwrap();
         let body = String::from_utf8(body.as_bytes().to_vec()).unwrap();
@@ -243,63 +446,1811 @@ mod tests {
             expected, actual
         );
 
+        let res = query_with_format(&server, "foo", "select host, val from cpu", "csv").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), "host,val\na,1\n");
+
+        let res = query_with_format(&server, "foo", "select * from cpu", "xml").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
         shutdown.cancel();
     }
 
-    pub(crate) async fn write_lp(
-        server: impl Into<String> + Send,
-        database: impl Into<String> + Send,
-        lp: impl Into<String> + Send,
-        authorization: Option<&str>,
-    ) -> Response<Body> {
-        let server = server.into();
-        let client = Client::new();
-        let url = format!("{}/api/v3/write_lp?db={}", server, database.into());
-        println!("{}", url);
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn create_and_delete_database() {
+        let (server, shutdown) = setup_server().await;
 
-        let mut builder = Request::builder().uri(url).method("POST");
-        if let Some(authorization) = authorization {
-            builder = builder.header(hyper::header::AUTHORIZATION, authorization);
+        let res = configure_database(&server, Method::POST, "bar").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = configure_database(&server, Method::POST, "bar").await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        write_lp(&server, "bar", "cpu,host=a val=1i 123", None).await;
+        let res = query(&server, "bar", "select * from cpu", None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = configure_database(&server, Method::DELETE, "bar").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = configure_database(&server, Method::DELETE, "bar").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = query(&server, "bar", "select * from cpu", None).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn write_and_query_with_mock_time() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(
+            1_000_000_000_123,
+        )));
+        let (addr, shutdown) = setup_server_with(None, Arc::clone(&time_provider) as _).await;
+        let server = format!("http://{}", addr);
+
+        // lines without a timestamp are written at the current time of the provider
+        write_lp(&server, "foo", "cpu,host=a val=1i", None).await;
+        let sql = "select count(val) as n from cpu where time > now() - interval '1 minute'";
+        let res = query_with_format(&server, "foo", sql, "csv").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), "n\n1\n");
+
+        // including in subqueries
+        let subquery_sql = "select count(val) as n from cpu where host in \
+            (select host from cpu where time > now() - interval '1 minute')";
+        let res = query_with_format(&server, "foo", subquery_sql, "csv").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), "n\n1\n");
+
+        let res = query_influxql(&server, "foo", "select val from cpu where time >= now()").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+--------------------------------+-----+",
+            "| iox::measurement | time                           | val |",
+            "+------------------+--------------------------------+-----+",
+            "| cpu              | 1970-01-01T00:16:40.000000123Z | 1   |",
+            "+------------------+--------------------------------+-----+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // now() follows the provider rather than the wall clock
+        time_provider.inc(Duration::from_secs(3600));
+        for sql in [sql, subquery_sql] {
+            let res = query_with_format(&server, "foo", sql, "csv").await;
+            let body = body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(String::from_utf8(body.to_vec()).unwrap(), "n\n0\n", "{sql}");
+        }
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn query_influxql_bind_parameters() {
+        let (addr, shutdown) = setup_server_with(None, Arc::new(SystemProvider::new())).await;
+        let server = format!("http://{}", addr);
+
+        write_lp(
+            &server,
+            "foo",
+            "cpu,host=a val=1i 123\ncpu,host=b val=2i 456",
+            None,
+        )
+        .await;
+
+        let q = "select val from cpu where host = $host and val >= $min";
+        let res =
+            query_influxql_with_params(&server, "foo", q, Some(r#"{"host": "b", "min": 1}"#)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+--------------------------------+-----+",
+            "| iox::measurement | time                           | val |",
+            "+------------------+--------------------------------+-----+",
+            "| cpu              | 1970-01-01T00:00:00.000000456Z | 2   |",
+            "+------------------+--------------------------------+-----+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // a parameter without a value is rejected
+        let res = query_influxql_with_params(&server, "foo", q, Some(r#"{"host": "b"}"#)).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // malformed parameters are rejected
+        let res = query_influxql_with_params(&server, "foo", q, Some("[1]")).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn query_influxql_mixed_projections() {
+        let (addr, shutdown) = setup_server_with(None, Arc::new(SystemProvider::new())).await;
+        let server = format!("http://{}", addr);
+
+        write_lp(
+            &server,
+            "foo",
+            "cpu,host=a val=1 0\n\
+             cpu,host=a val=3 5000000000\n\
+             cpu,host=a val=4 10000000000\n\
+             cpu,host=a val=8 15000000000\n\
+             cpu,host=b val=10 20000000000\n\
+             cpu,host=b val=14 25000000000",
+            None,
+        )
+        .await;
+
+        // InfluxDB 1.x returns:
+        //
+        // name: cpu
+        // time        mean derivative
+        // ----        ---- ----------
+        // 0           2
+        // 10000000000 6    4
+        // 20000000000 12   6
+        let res = query_influxql(
+            &server,
+            "foo",
+            "SELECT MEAN(val), DERIVATIVE(MEAN(val)) FROM cpu WHERE time >= 0 AND time < 30s GROUP BY TIME(10s)",
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+----------------------+------+------------+",
+            "| iox::measurement | time                 | mean | derivative |",
+            "+------------------+----------------------+------+------------+",
+            "| cpu              | 1970-01-01T00:00:00Z | 2.0  |            |",
+            "| cpu              | 1970-01-01T00:00:10Z | 6.0  | 4.0        |",
+            "| cpu              | 1970-01-01T00:00:20Z | 12.0 | 6.0        |",
+            "+------------------+----------------------+------+------------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // InfluxDB 1.x rejects multiple selectors with tags or fields, which are taken from the
+        // row selected by the first selector.
+        let res = query_influxql(&server, "foo", "SELECT MAX(val), MIN(val), host FROM cpu").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+----------------------+------+-----+------+",
+            "| iox::measurement | time                 | max  | min | host |",
+            "+------------------+----------------------+------+-----+------+",
+            "| cpu              | 1970-01-01T00:00:00Z | 14.0 | 1.0 | b    |",
+            "+------------------+----------------------+------+-----+------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn query_influxql_sample_with_seed() {
+        let (addr, shutdown) = setup_server_with_datafusion_config(
+            None,
+            Arc::new(SystemProvider::new()),
+            HashMap::from([("iox.influxql_sample_seed".to_string(), "42".to_string())]),
+        )
+        .await;
+        let server = format!("http://{}", addr);
+
+        let lp = (1..=10)
+            .map(|i| format!("cpu,host=a val={i} {}", i * 1_000_000_000))
+            .collect::<Vec<_>>()
+            .join("\n");
+        write_lp(&server, "foo", lp, None).await;
+
+        // The seed selects the same rows every time the query runs.
+        let expected = [
+            "+------------------+----------------------+--------+",
+            "| iox::measurement | time                 | sample |",
+            "+------------------+----------------------+--------+",
+            "| cpu              | 1970-01-01T00:00:06Z | 6.0    |",
+            "| cpu              | 1970-01-01T00:00:09Z | 9.0    |",
+            "| cpu              | 1970-01-01T00:00:10Z | 10.0   |",
+            "+------------------+----------------------+--------+",
+        ]
+        .join("\n");
+        for _ in 0..2 {
+            let res = query_influxql(&server, "foo", "SELECT SAMPLE(val, 3) FROM cpu").await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+        }
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn query_influxql_select_into() {
+        let (addr, shutdown) = setup_server_with(None, Arc::new(SystemProvider::new())).await;
+        let server = format!("http://{}", addr);
+
+        write_lp(
+            &server,
+            "foo",
+            "cpu,host=a val=1 10\ncpu,host=a val=3 20\ncpu,host=b val=5 30",
+            None,
+        )
+        .await;
+
+        let q = "SELECT mean(val) INTO cpu_1h FROM cpu \
+                 WHERE time >= 0 AND time < 3600000000000 GROUP BY time(1h), host FILL(none)";
+
+        // a query that writes data must be sent with POST
+        let res = query_influxql(&server, "foo", q).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let res = query_influxql_post(&server, "foo", q).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+----------------------+---------+",
+            "| iox::measurement | time                 | written |",
+            "+------------------+----------------------+---------+",
+            "| result           | 1970-01-01T00:00:00Z | 2       |",
+            "+------------------+----------------------+---------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = query_influxql(&server, "foo", "SELECT mean FROM cpu_1h GROUP BY host").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+----------------------+------+------+",
+            "| iox::measurement | time                 | host | mean |",
+            "+------------------+----------------------+------+------+",
+            "| cpu_1h           | 1970-01-01T00:00:00Z | a    | 2.0  |",
+            "| cpu_1h           | 1970-01-01T00:00:00Z | b    | 5.0  |",
+            "+------------------+----------------------+------+------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // :MEASUREMENT writes to the source measurement in the target database
+        let q = "SELECT val INTO bar..:MEASUREMENT FROM cpu GROUP BY *";
+        let res = query_influxql_post(&server, "foo", q).await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("| result           | 1970-01-01T00:00:00Z | 3       |"));
+
+        let res = query_influxql(&server, "bar", "SELECT val FROM cpu GROUP BY host").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+--------------------------------+------+-----+",
+            "| iox::measurement | time                           | host | val |",
+            "+------------------+--------------------------------+------+-----+",
+            "| cpu              | 1970-01-01T00:00:00.000000010Z | a    | 1.0 |",
+            "| cpu              | 1970-01-01T00:00:00.000000020Z | a    | 3.0 |",
+            "| cpu              | 1970-01-01T00:00:00.000000030Z | b    | 5.0 |",
+            "+------------------+--------------------------------+------+-----+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // a retention policy other than the default writes to `<database>/<retention policy>`
+        let q = r#"SELECT val INTO "1d".cpu_1d FROM cpu WHERE host = 'b'"#;
+        let res = query_influxql_post(&server, "foo", q).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = query_influxql(&server, "foo/1d", "SELECT val FROM cpu_1d").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+--------------------------------+-----+",
+            "| iox::measurement | time                           | val |",
+            "+------------------+--------------------------------+-----+",
+            "| cpu_1d           | 1970-01-01T00:00:00.000000030Z | 5.0 |",
+            "+------------------+--------------------------------+-----+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // a Flight `DoGet` request can not write the results
+        let channel = tonic::transport::Channel::from_shared(server.clone())
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = arrow_flight::FlightClient::new(channel);
+        let ticket = serde_json::json!({
+            "database": "foo",
+            "sql_query": "SELECT val INTO cpu_copy FROM cpu",
+            "query_type": "influxql",
+        });
+        match client
+            .do_get(arrow_flight::Ticket::new(ticket.to_string()))
+            .await
+        {
+            Err(arrow_flight::error::FlightError::Tonic(status)) => {
+                assert_eq!(status.code(), tonic::Code::InvalidArgument, "{status}")
+            }
+            res => panic!("unexpected result: {:?}", res.map(|_| ())),
+        }
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn query_influxql_qualified_measurements() {
+        let (addr, shutdown) = setup_server_with(None, Arc::new(SystemProvider::new())).await;
+        let server = format!("http://{}", addr);
+
+        write_lp(&server, "foo", "cpu,host=a val=1 10", None).await;
+        write_lp(&server, "bar", "mem,host=b free=2 20", None).await;
+
+        let expected = [
+            "+------------------+--------------------------------+-----+",
+            "| iox::measurement | time                           | val |",
+            "+------------------+--------------------------------+-----+",
+            "| cpu              | 1970-01-01T00:00:00.000000010Z | 1.0 |",
+            "+------------------+--------------------------------+-----+",
+        ]
+        .join("\n");
+        for q in [
+            r#"SELECT val FROM "foo"."autogen"."cpu""#,
+            "SELECT val FROM foo..cpu",
+            "SELECT val FROM autogen.cpu",
+        ] {
+            let res = query_influxql(&server, "foo", q).await;
+            assert_eq!(res.status(), StatusCode::OK, "{q}");
+            let body = body::to_bytes(res.into_body()).await.unwrap();
+            assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected, "{q}");
+        }
+
+        // measurements in another database
+        let res = query_influxql(&server, "foo", "SELECT free FROM bar.autogen.mem").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+--------------------------------+------+",
+            "| iox::measurement | time                           | free |",
+            "+------------------+--------------------------------+------+",
+            "| mem              | 1970-01-01T00:00:00.000000020Z | 2.0  |",
+            "+------------------+--------------------------------+------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = query_influxql(&server, "foo", "SHOW MEASUREMENTS ON bar").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body =
+            String::from_utf8(body::to_bytes(res.into_body()).await.unwrap().to_vec()).unwrap();
+        assert!(body.contains("| mem "), "{body}");
+        assert!(!body.contains("| cpu "), "{body}");
+
+        let res = query_influxql(&server, "foo", "SHOW TAG KEYS ON bar").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body =
+            String::from_utf8(body::to_bytes(res.into_body()).await.unwrap().to_vec()).unwrap();
+        assert!(body.contains("| mem "), "{body}");
+
+        let res = query_influxql(&server, "foo", "SHOW RETENTION POLICIES ON bar").await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // unknown databases are not found and unknown retention policies are rejected
+        for (q, status) in [
+            ("SELECT val FROM none..cpu", StatusCode::NOT_FOUND),
+            ("SELECT val FROM foo.none.cpu", StatusCode::BAD_REQUEST),
+            ("SHOW FIELD KEYS ON none", StatusCode::NOT_FOUND),
+        ] {
+            let res = query_influxql(&server, "foo", q).await;
+            assert_eq!(res.status(), status, "{q}");
+        }
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn flight_do_put_ingest() {
+        use arrow::array::{
+            ArrayRef, DictionaryArray, Float64Array, Int64Array, StringArray,
+            TimestampNanosecondArray,
         };
-        let request = builder
-            .body(Body::from(lp.into()))
-            .expect("failed to construct HTTP request");
+        use arrow::datatypes::Int32Type;
+        use arrow_flight::encode::{DictionaryHandling, FlightDataEncoderBuilder};
+        use arrow_flight::error::FlightError;
+        use arrow_flight::{FlightClient, FlightDescriptor};
+        use futures::TryStreamExt;
+        use prost::Message;
 
-        client
-            .request(request)
+        let (server, shutdown) = setup_server().await;
+        let channel = tonic::transport::Channel::from_shared(server.clone())
+            .unwrap()
+            .connect()
             .await
-            .expect("http error sending write")
+            .unwrap();
+        let mut client = FlightClient::new(channel);
+
+        async fn do_put(
+            client: &mut FlightClient,
+            batch: RecordBatch,
+        ) -> Result<Vec<arrow_flight::PutResult>, FlightError> {
+            let descriptor = FlightDescriptor::new_path(vec!["foo".into(), "cpu".into()]);
+            let flight_data = FlightDataEncoderBuilder::new()
+                .with_flight_descriptor(Some(descriptor))
+                .with_dictionary_handling(DictionaryHandling::Resend)
+                .build(futures::stream::iter([Ok(batch)]));
+            client.do_put(flight_data).await?.try_collect().await
+        }
+
+        let host: DictionaryArray<Int32Type> = vec!["a", "b"].into_iter().collect();
+        let batch = RecordBatch::try_from_iter([
+            ("host", Arc::new(host) as ArrayRef),
+            ("usage", Arc::new(Float64Array::from(vec![1.0, 2.0])) as _),
+            (
+                "time",
+                Arc::new(TimestampNanosecondArray::from(vec![10, 20])) as _,
+            ),
+        ])
+        .unwrap();
+        let results = do_put(&mut client, batch).await.unwrap();
+        assert_eq!(results.len(), 1);
+        let result = flightsql::DoPutUpdateResult::decode(results[0].app_metadata.clone()).unwrap();
+        assert_eq!(result.record_count, 2);
+
+        // strings can be written to the existing tag, and new fields extend the table
+        let batch = RecordBatch::try_from_iter([
+            ("host", Arc::new(StringArray::from(vec!["c"])) as ArrayRef),
+            ("load", Arc::new(Int64Array::from(vec![3])) as _),
+            (
+                "time",
+                Arc::new(TimestampNanosecondArray::from(vec![30])) as _,
+            ),
+        ])
+        .unwrap();
+        do_put(&mut client, batch).await.unwrap();
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT host, usage, load, time FROM cpu ORDER BY time",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------+-------+------+-------------------------------+",
+            "| host | usage | load | time                          |",
+            "+------+-------+------+-------------------------------+",
+            "| a    | 1.0   |      | 1970-01-01T00:00:00.000000010 |",
+            "| b    | 2.0   |      | 1970-01-01T00:00:00.000000020 |",
+            "| c    |       | 3    | 1970-01-01T00:00:00.000000030 |",
+            "+------+-------+------+-------------------------------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // the type of an existing field can not change
+        let batch = RecordBatch::try_from_iter([(
+            "usage",
+            Arc::new(StringArray::from(vec!["high"])) as ArrayRef,
+        )])
+        .unwrap();
+        match do_put(&mut client, batch).await {
+            Err(FlightError::Tonic(status)) => {
+                assert_eq!(status.code(), tonic::Code::InvalidArgument, "{status}")
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+
+        shutdown.cancel();
     }
 
-    pub(crate) async fn query(
-        server: impl Into<String> + Send,
-        database: impl Into<String> + Send,
-        query: impl Into<String> + Send,
-        authorization: Option<&str>,
-    ) -> Response<Body> {
-        let client = Client::new();
-        // query escaped for uri
-        let query = urlencoding::encode(&query.into());
-        let url = format!(
-            "{}/api/v3/query_sql?db={}&q={}",
-            server.into(),
-            database.into(),
-            query
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn prometheus_remote_write_and_read() {
+        use generated_types::prometheus::{
+            label_matcher, Label, LabelMatcher, Query, ReadRequest, ReadResponse, Sample,
+            TimeSeries, WriteRequest,
+        };
+        use prost::Message;
+
+        fn label(name: &str, value: &str) -> Label {
+            Label {
+                name: name.into(),
+                value: value.into(),
+            }
+        }
+
+        fn sample(value: f64, timestamp: i64) -> Sample {
+            Sample { value, timestamp }
+        }
+
+        fn matcher(r#type: label_matcher::Type, name: &str, value: &str) -> LabelMatcher {
+            LabelMatcher {
+                r#type: r#type as i32,
+                name: name.into(),
+                value: value.into(),
+            }
+        }
+
+        let (server, shutdown) = setup_server().await;
+
+        let request = WriteRequest {
+            timeseries: vec![
+                TimeSeries {
+                    labels: vec![label("__name__", "up"), label("job", "a")],
+                    samples: vec![sample(1.0, 1_000), sample(0.0, 2_000)],
+                },
+                TimeSeries {
+                    labels: vec![label("__name__", "up"), label("job", "b")],
+                    samples: vec![sample(1.0, 1_000)],
+                },
+                TimeSeries {
+                    labels: vec![label("__name__", "down"), label("job", "a")],
+                    samples: vec![sample(5.0, 1_000)],
+                },
+            ],
+        };
+        let res = prom_request(&server, "write", "foo", request.encode_to_vec()).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT job, time, value FROM up ORDER BY job, time",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+-----+---------------------+-------+",
+            "| job | time                | value |",
+            "+-----+---------------------+-------+",
+            "| a   | 1970-01-01T00:00:01 | 1.0   |",
+            "| a   | 1970-01-01T00:00:02 | 0.0   |",
+            "| b   | 1970-01-01T00:00:01 | 1.0   |",
+            "+-----+---------------------+-------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let request = ReadRequest {
+            queries: vec![
+                Query {
+                    start_timestamp_ms: 0,
+                    end_timestamp_ms: 10_000,
+                    matchers: vec![
+                        matcher(label_matcher::Type::Eq, "__name__", "up"),
+                        matcher(label_matcher::Type::Re, "job", "a|c"),
+                    ],
+                    hints: None,
+                },
+                Query {
+                    start_timestamp_ms: 1_500,
+                    end_timestamp_ms: 10_000,
+                    matchers: vec![matcher(label_matcher::Type::Neq, "job", "b")],
+                    hints: None,
+                },
+            ],
+            accepted_response_types: vec![],
+        };
+        let res = prom_request(&server, "read", "foo", request.encode_to_vec()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let body = snap::raw::Decoder::new().decompress_vec(&body).unwrap();
+        let response = ReadResponse::decode(body.as_slice()).unwrap();
+
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(
+            response.results[0].timeseries,
+            vec![TimeSeries {
+                labels: vec![label("__name__", "up"), label("job", "a")],
+                samples: vec![sample(1.0, 1_000), sample(0.0, 2_000)],
+            }]
+        );
+        assert_eq!(
+            response.results[1].timeseries,
+            vec![TimeSeries {
+                labels: vec![label("__name__", "up"), label("job", "a")],
+                samples: vec![sample(0.0, 2_000)],
+            }]
         );
 
-        println!("query url: {}", url);
-        let mut builder = Request::builder().uri(url).method("GET");
-        if let Some(authorization) = authorization {
-            builder = builder.header(hyper::header::AUTHORIZATION, authorization);
+        // labels named after the value field or time column are round-tripped
+        let request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![
+                    label("__name__", "requests"),
+                    label("time", "b"),
+                    label("value", "a"),
+                ],
+                samples: vec![sample(3.0, 1_000)],
+            }],
         };
-        let request = builder
-            .body(Body::empty())
-            .expect("failed to construct HTTP request");
+        let res = prom_request(&server, "write", "foo", request.encode_to_vec()).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
 
-        client
-            .request(request)
+        let request = ReadRequest {
+            queries: vec![Query {
+                start_timestamp_ms: 0,
+                end_timestamp_ms: 10_000,
+                matchers: vec![matcher(label_matcher::Type::Eq, "value", "a")],
+                hints: None,
+            }],
+            accepted_response_types: vec![],
+        };
+        let res = prom_request(&server, "read", "foo", request.encode_to_vec()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let body = snap::raw::Decoder::new().decompress_vec(&body).unwrap();
+        let response = ReadResponse::decode(body.as_slice()).unwrap();
+        assert_eq!(
+            response.results[0].timeseries,
+            vec![TimeSeries {
+                labels: vec![
+                    label("__name__", "requests"),
+                    label("time", "b"),
+                    label("value", "a"),
+                ],
+                samples: vec![sample(3.0, 1_000)],
+            }]
+        );
+
+        // series must have a metric name
+        let request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![label("job", "a")],
+                samples: vec![sample(1.0, 1_000)],
+            }],
+        };
+        let res = prom_request(&server, "write", "foo", request.encode_to_vec()).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = prom_request(
+            &server,
+            "read",
+            "bar",
+            ReadRequest::default().encode_to_vec(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn otlp_metrics_export() {
+        use generated_types::opentelemetry::proto::{
+            collector::metrics::v1::{
+                metrics_service_client::MetricsServiceClient, ExportMetricsServiceRequest,
+                ExportMetricsServiceResponse,
+            },
+            common::v1::{any_value, AnyValue, KeyValue},
+            metrics::v1::{
+                metric, number_data_point, Gauge, Histogram, HistogramDataPoint,
+                Metric as OtlpMetric, NumberDataPoint, ResourceMetrics, ScopeMetrics,
+            },
+            resource::v1::Resource,
+        };
+        use prost::Message;
+
+        fn export_request(host: &str, metrics: Vec<OtlpMetric>) -> ExportMetricsServiceRequest {
+            ExportMetricsServiceRequest {
+                resource_metrics: vec![ResourceMetrics {
+                    resource: Some(Resource {
+                        attributes: vec![KeyValue {
+                            key: "host".into(),
+                            value: Some(AnyValue {
+                                value: Some(any_value::Value::StringValue(host.into())),
+                            }),
+                        }],
+                        ..Default::default()
+                    }),
+                    scope_metrics: vec![ScopeMetrics {
+                        metrics,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+            }
+        }
+
+        fn gauge(name: &str, points: Vec<(f64, u64)>) -> OtlpMetric {
+            OtlpMetric {
+                name: name.into(),
+                data: Some(metric::Data::Gauge(Gauge {
+                    data_points: points
+                        .into_iter()
+                        .map(|(value, time_unix_nano)| NumberDataPoint {
+                            time_unix_nano,
+                            value: Some(number_data_point::Value::AsDouble(value)),
+                            ..Default::default()
+                        })
+                        .collect(),
+                })),
+                ..Default::default()
+            }
+        }
+
+        let (server, shutdown) = setup_server().await;
+
+        // OTLP/HTTP with protobuf
+        let latency = OtlpMetric {
+            name: "latency".into(),
+            data: Some(metric::Data::Histogram(Histogram {
+                data_points: vec![HistogramDataPoint {
+                    time_unix_nano: 1_000_000_000,
+                    count: 3,
+                    sum: Some(12.0),
+                    bucket_counts: vec![1, 2],
+                    explicit_bounds: vec![5.0],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let request = export_request(
+            "a",
+            vec![
+                gauge("cpu", vec![(1.0, 1_000_000_000), (2.0, 2_000_000_000)]),
+                latency,
+            ],
+        );
+        let res = otlp_request(
+            &server,
+            "foo",
+            "application/x-protobuf",
+            request.encode_to_vec(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let response = ExportMetricsServiceResponse::decode(body).unwrap();
+        assert_eq!(response.partial_success, None);
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT host, time, value FROM cpu ORDER BY time",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------+---------------------+-------+",
+            "| host | time                | value |",
+            "+------+---------------------+-------+",
+            "| a    | 1970-01-01T00:00:01 | 1.0   |",
+            "| a    | 1970-01-01T00:00:02 | 2.0   |",
+            "+------+---------------------+-------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT le, bucket_count FROM latency WHERE le IS NOT NULL ORDER BY le",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------+--------------+",
+            "| le   | bucket_count |",
+            "+------+--------------+",
+            "| +Inf | 2            |",
+            "| 5    | 1            |",
+            "+------+--------------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        // OTLP/HTTP with JSON, where the cpu point without a value is rejected,
+        // without rejecting the other points
+        let request = r#"{"resourceMetrics": [{
+            "resource": {"attributes": [{"key": "host", "value": {"stringValue": "b"}}]},
+            "scopeMetrics": [{"metrics": [
+                {"name": "requests", "sum": {
+                    "dataPoints": [{"asInt": "3", "timeUnixNano": "3000000000"}],
+                    "aggregationTemporality": 2,
+                    "isMonotonic": true
+                }},
+                {"name": "cpu", "gauge": {
+                    "dataPoints": [
+                        {"timeUnixNano": "3000000000"},
+                        {"asDouble": 3.5, "timeUnixNano": "4000000000"}
+                    ]
+                }}
+            ]}]
+        }]}"#;
+        let res = otlp_request(&server, "foo", "application/json", request.into()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let response: ExportMetricsServiceResponse = serde_json::from_slice(&body).unwrap();
+        let partial_success = response.partial_success.unwrap();
+        assert_eq!(partial_success.rejected_data_points, 1);
+        assert!(
+            partial_success.error_message.starts_with("metric cpu: "),
+            "{}",
+            partial_success.error_message
+        );
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT host, time, value FROM requests",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------+---------------------+-------+",
+            "| host | time                | value |",
+            "+------+---------------------+-------+",
+            "| b    | 1970-01-01T00:00:03 | 3     |",
+            "+------+---------------------+-------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT host, time, value FROM cpu WHERE host = 'b'",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------+---------------------+-------+",
+            "| host | time                | value |",
+            "+------+---------------------+-------+",
+            "| b    | 1970-01-01T00:00:04 | 3.5   |",
+            "+------+---------------------+-------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = otlp_request(&server, "foo", "text/plain", vec![]).await;
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        // OTLP/gRPC, with the database in the request metadata
+        let channel = tonic::transport::Channel::from_shared(server.clone())
+            .unwrap()
+            .connect()
             .await
-            .expect("http error sending query")
+            .unwrap();
+        let mut client = MetricsServiceClient::new(channel);
+
+        let mut request = tonic::Request::new(export_request(
+            "c",
+            vec![gauge("up", vec![(1.0, 1_000_000_000)])],
+        ));
+        request
+            .metadata_mut()
+            .insert("database", "foo".parse().unwrap());
+        let response = client.export(request).await.unwrap().into_inner();
+        assert_eq!(response.partial_success, None);
+
+        let res = query(&server, "foo", "SELECT host, time, value FROM up", None).await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------+---------------------+-------+",
+            "| host | time                | value |",
+            "+------+---------------------+-------+",
+            "| c    | 1970-01-01T00:00:01 | 1.0   |",
+            "+------+---------------------+-------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let status = client
+            .export(export_request("c", vec![]))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument, "{status}");
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn storage_api() {
+        use futures::TryStreamExt;
+        use generated_types::google::protobuf::Any;
+        use generated_types::influxdata::platform::storage::{
+            measurement_fields_response::FieldType,
+            node,
+            read_response::{frame::Data, DataType, FloatPointsFrame, SeriesFrame},
+            storage_client::StorageClient,
+            MeasurementFieldsRequest, Node, Predicate, ReadFilterRequest, ReadSource,
+            StringValuesResponse, Tag, TagKeyMetaNames, TagKeysRequest, TagValuesRequest,
+            TimestampRange,
+        };
+        use prost::Message;
+
+        fn tag_equals(key: &[u8], value: &str) -> Node {
+            Node {
+                node_type: node::Type::ComparisonExpression as i32,
+                children: vec![
+                    Node {
+                        node_type: node::Type::TagRef as i32,
+                        children: vec![],
+                        value: Some(node::Value::TagRefValue(key.to_vec())),
+                    },
+                    Node {
+                        node_type: node::Type::Literal as i32,
+                        children: vec![],
+                        value: Some(node::Value::StringValue(value.to_string())),
+                    },
+                ],
+                value: Some(node::Value::Comparison(node::Comparison::Equal as i32)),
+            }
+        }
+
+        fn and(children: Vec<Node>) -> Option<Predicate> {
+            Some(Predicate {
+                root: Some(Node {
+                    node_type: node::Type::LogicalExpression as i32,
+                    children,
+                    value: Some(node::Value::Logical(node::Logical::And as i32)),
+                }),
+            })
+        }
+
+        fn values(responses: Vec<StringValuesResponse>) -> Vec<Vec<u8>> {
+            responses.into_iter().flat_map(|r| r.values).collect()
+        }
+
+        let (server, shutdown) = setup_server().await;
+
+        // The database that 2.x clients read from with org ID 1 and bucket ID 2
+        let lp = "cpu,host=a usage=1.5,idle=8i 1\n\
+                  cpu,host=b usage=2.5,idle=7i 2\n\
+                  mem,host=c free=10i 3\n";
+        let res = write_lp(&server, "0000000000000001_0000000000000002", lp, None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let channel = tonic::transport::Channel::from_shared(server.clone())
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = StorageClient::new(channel);
+        let source = Some(Any {
+            type_url: "type.googleapis.com/influxdata.platform.storage.read.ReadSource".into(),
+            value: ReadSource {
+                org_id: 1,
+                bucket_id: 2,
+                partition_id: 0,
+            }
+            .encode_to_vec()
+            .into(),
+        });
+
+        // Select the usage field of cpu with the special tag keys
+        let request = ReadFilterRequest {
+            read_source: source.clone(),
+            range: Some(TimestampRange { start: 0, end: 10 }),
+            predicate: and(vec![
+                tag_equals(b"_measurement", "cpu"),
+                tag_equals(&[255], "usage"),
+            ]),
+            tag_key_meta_names: TagKeyMetaNames::Binary as i32,
+            ..Default::default()
+        };
+        let frames: Vec<Data> = client
+            .read_filter(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+            .into_iter()
+            .flat_map(|r| r.frames)
+            .filter_map(|f| f.data)
+            .collect();
+        let series = |host: &str| {
+            Data::Series(SeriesFrame {
+                tags: vec![
+                    Tag {
+                        key: vec![0],
+                        value: b"cpu".to_vec(),
+                    },
+                    Tag {
+                        key: b"host".to_vec(),
+                        value: host.as_bytes().to_vec(),
+                    },
+                    Tag {
+                        key: vec![255],
+                        value: b"usage".to_vec(),
+                    },
+                ],
+                data_type: DataType::Float as i32,
+            })
+        };
+        assert_eq!(
+            frames,
+            vec![
+                series("a"),
+                Data::FloatPoints(FloatPointsFrame {
+                    timestamps: vec![1],
+                    values: vec![1.5],
+                }),
+                series("b"),
+                Data::FloatPoints(FloatPointsFrame {
+                    timestamps: vec![2],
+                    values: vec![2.5],
+                }),
+            ]
+        );
+
+        let request = TagKeysRequest {
+            tags_source: source.clone(),
+            range: None,
+            predicate: None,
+        };
+        let tag_keys = client
+            .tag_keys(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(values(tag_keys), vec![vec![0], b"host".to_vec(), vec![255]]);
+
+        // The values of the special tag keys are measurement names and field keys
+        for (tag_key, predicate, expected) in [
+            (
+                b"host".to_vec(),
+                and(vec![tag_equals(&[0], "mem")]),
+                vec!["c"],
+            ),
+            (vec![0], None, vec!["cpu", "mem"]),
+            (
+                vec![255],
+                and(vec![tag_equals(&[0], "cpu")]),
+                vec!["idle", "usage"],
+            ),
+        ] {
+            let request = TagValuesRequest {
+                tags_source: source.clone(),
+                range: None,
+                predicate,
+                tag_key,
+            };
+            let tag_values = client
+                .tag_values(request)
+                .await
+                .unwrap()
+                .into_inner()
+                .try_collect()
+                .await
+                .unwrap();
+            let expected: Vec<_> = expected
+                .into_iter()
+                .map(|v| v.as_bytes().to_vec())
+                .collect();
+            assert_eq!(values(tag_values), expected);
+        }
+
+        // The database can also be given by the request metadata
+        let mut request = tonic::Request::new(MeasurementFieldsRequest {
+            source: None,
+            measurement: "cpu".into(),
+            range: None,
+            predicate: None,
+        });
+        request.metadata_mut().insert(
+            "database",
+            "0000000000000001_0000000000000002".parse().unwrap(),
+        );
+        let responses: Vec<_> = client
+            .measurement_fields(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .try_collect()
+            .await
+            .unwrap();
+        let mut fields: Vec<_> = responses
+            .into_iter()
+            .flat_map(|r| r.fields)
+            .map(|f| (f.key, f.r#type, f.timestamp))
+            .collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec![
+                ("idle".to_string(), FieldType::Integer as i32, 2),
+                ("usage".to_string(), FieldType::Float as i32, 2),
+            ]
+        );
+
+        let request = TagKeysRequest {
+            tags_source: None,
+            range: None,
+            predicate: None,
+        };
+        let status = client.tag_keys(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument, "{status}");
+
+        let mut request = tonic::Request::new(TagKeysRequest {
+            tags_source: None,
+            range: None,
+            predicate: None,
+        });
+        request
+            .metadata_mut()
+            .insert("database", "unknown".parse().unwrap());
+        let status = client.tag_keys(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound, "{status}");
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn write_json_and_csv() {
+        let (server, shutdown) = setup_server().await;
+
+        // The second row isn't an object, but the first is still written.
+        let body = r#"[
+            {"time": 1, "location": "london", "temp": 12.5},
+            42
+        ]"#;
+        let res = row_write_request(
+            &server,
+            "write_json?db=foo&measurement=weather&tags=location&precision=s",
+            body,
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "1 of 2 rows were rejected");
+        let data = body["data"].as_array().unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0]["line_number"], 2);
+        assert_eq!(data[0]["original_line"], "42");
+
+        let body = "#datatype measurement,tag,double,dateTime:number\n\
+                    m,location,temp,time\n\
+                    weather,berlin,9.5,3000000000\n";
+        let res = row_write_request(&server, "write_csv?db=foo", body).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = query(
+            &server,
+            "foo",
+            "SELECT location, temp, time FROM weather ORDER BY time",
+            None,
+        )
+        .await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+----------+------+---------------------+",
+            "| location | temp | time                |",
+            "+----------+------+---------------------+",
+            "| london   | 12.5 | 1970-01-01T00:00:01 |",
+            "| berlin   | 9.5  | 1970-01-01T00:00:03 |",
+            "+----------+------+---------------------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = row_write_request(&server, "write_json?db=foo", "[]").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn continuous_queries() {
+        const HOUR: i64 = 3_600_000_000_000;
+
+        let addr = get_free_port();
+        let metrics = Arc::new(metric::Registry::new());
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let common_state = crate::CommonServerState::new(
+            Arc::clone(&metrics),
+            None,
+            trace_http::ctx::TraceHeaderParser::new(),
+            addr,
+            None,
+            Arc::clone(&time_provider) as _,
+        );
+        let catalog = Arc::new(influxdb3_write::catalog::Catalog::new());
+        let object_store: Arc<DynObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let exec = Arc::new(Executor::new_with_config(ExecutorConfig {
+            num_threads: NonZeroUsize::new(2).unwrap(),
+            target_query_partitions: NonZeroUsize::new(1).unwrap(),
+            object_stores: [&parquet_store]
+                .into_iter()
+                .map(|store| (store.id(), Arc::clone(store.object_store())))
+                .collect(),
+            metric_registry: Arc::clone(&metrics),
+            mem_pool_size: usize::MAX,
+        }));
+        let write_buffer = Arc::new(influxdb3_write::write_buffer::WriteBufferImpl::new(
+            Arc::clone(&catalog),
+            None::<Arc<influxdb3_write::wal::WalImpl>>,
+            Arc::clone(&time_provider) as _,
+        ));
+        let query_executor = Arc::new(crate::query_executor::QueryExecutorImpl::new(
+            Arc::clone(&catalog),
+            Arc::clone(&write_buffer),
+            exec,
+            Arc::clone(&metrics),
+            Arc::new(HashMap::new()),
+            10,
+            Arc::clone(&time_provider) as _,
+        ));
+        let persister = Arc::new(PersisterImpl::new(object_store));
+        let scheduler = crate::continuous_query::ContinuousQueryScheduler::new(
+            Arc::clone(&write_buffer),
+            Arc::clone(&query_executor),
+            Arc::clone(&time_provider) as _,
+            Duration::ZERO,
+            Duration::from_secs(1),
+        );
+
+        let server = crate::Server::new(
+            common_state,
+            persister,
+            write_buffer,
+            query_executor,
+            usize::MAX,
+            Duration::from_secs(10),
+        );
+        let frontend_shutdown = CancellationToken::new();
+        let shutdown = frontend_shutdown.clone();
+        tokio::spawn(async move { serve(server, frontend_shutdown).await });
+        let server = format!("http://{}", addr);
+
+        write_lp(
+            &server,
+            "foo",
+            format!("cpu,host=a val=1 10\ncpu,host=a val=3 20\ncpu,host=b val=5 {HOUR}"),
+            None,
+        )
+        .await;
+
+        let create = "CREATE CONTINUOUS QUERY cq ON foo BEGIN \
+                      SELECT mean(val) INTO cpu_1h FROM cpu GROUP BY time(1h), host \
+                      END";
+        let res = query_influxql(&server, "foo", create).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = query_influxql(&server, "foo", create).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = query_influxql(&server, "foo", "SHOW CONTINUOUS QUERIES").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains(
+            "| foo              | cq   | CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(val) INTO cpu_1h FROM cpu GROUP BY TIME(60m), host END |"
+        ));
+
+        // The first run covers the interval that ended before the query was created
+        time_provider.set(Time::from_timestamp_nanos(HOUR + 1));
+        scheduler.run_due_queries().await;
+        assert_eq!(catalog.continuous_queries()[0].last_run_ns, Some(HOUR));
+
+        // Nothing is due until the end of the next interval
+        time_provider.set(Time::from_timestamp_nanos(2 * HOUR - 1));
+        scheduler.run_due_queries().await;
+        assert_eq!(catalog.continuous_queries()[0].last_run_ns, Some(HOUR));
+
+        time_provider.set(Time::from_timestamp_nanos(2 * HOUR));
+        scheduler.run_due_queries().await;
+        assert_eq!(catalog.continuous_queries()[0].last_run_ns, Some(2 * HOUR));
+
+        let res = query_influxql(&server, "foo", "SELECT mean FROM cpu_1h GROUP BY host").await;
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        let expected = [
+            "+------------------+----------------------+------+------+",
+            "| iox::measurement | time                 | host | mean |",
+            "+------------------+----------------------+------+------+",
+            "| cpu_1h           | 1970-01-01T00:00:00Z | a    | 2.0  |",
+            "| cpu_1h           | 1970-01-01T01:00:00Z | b    | 5.0  |",
+            "+------------------+----------------------+------+------+",
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+
+        let res = query_influxql(&server, "foo", "DROP CONTINUOUS QUERY cq ON foo").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(catalog.continuous_queries().is_empty());
+        let res = query_influxql(&server, "foo", "DROP CONTINUOUS QUERY cq ON foo").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn tls_with_client_verification() {
+        let fixture = |name: &str| {
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../test_fixtures/tls")
+                .join(name)
+        };
+        let (addr, shutdown) = setup_server_with(
+            Some(TlsConfig {
+                cert_path: fixture("server.pem"),
+                key_path: fixture("server.key"),
+                client_ca_path: Some(fixture("ca.pem")),
+            }),
+            Arc::new(SystemProvider::new()),
+        )
+        .await;
+        let url = format!("https://localhost:{}/health", addr.port());
+
+        let ca = std::fs::read(fixture("ca.pem")).unwrap();
+        let ca = reqwest::Certificate::from_pem(&ca).unwrap();
+        let mut identity = std::fs::read(fixture("client.pem")).unwrap();
+        identity.extend(std::fs::read(fixture("client.key")).unwrap());
+        let identity = reqwest::Identity::from_pem(&identity).unwrap();
+
+        let client = reqwest::Client::builder()
+            .add_root_certificate(ca.clone())
+            .identity(identity)
+            .build()
+            .unwrap();
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+        // clients without a certificate signed by the client CA are rejected
+        let client = reqwest::Client::builder()
+            .add_root_certificate(ca)
+            .build()
+            .unwrap();
+        assert!(client.get(&url).send().await.is_err());
+
+        // plain HTTP is not served on a TLS listener
+        let res = Client::new()
+            .get(format!("http://{}/health", addr).parse().unwrap())
+            .await;
+        assert!(res.is_err());
+
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn persist_open_segment_on_shutdown() {
+        let metrics = metric::Registry::new();
+        let catalog = Arc::new(influxdb3_write::catalog::Catalog::new());
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(SystemProvider::new());
+        let write_buffer = influxdb3_write::write_buffer::WriteBufferImpl::new(
+            Arc::clone(&catalog),
+            None::<Arc<influxdb3_write::wal::WalImpl>>,
+            time_provider,
+        );
+        write_buffer
+            .write_lp(
+                NamespaceName::new("foo").unwrap(),
+                "cpu,host=a val=1i 123",
+                0,
+            )
+            .await
+            .unwrap();
+        let persister = Arc::new(PersisterImpl::new(Arc::new(
+            object_store::memory::InMemory::new(),
+        )));
+
+        crate::persist_open_segment(
+            &write_buffer,
+            Arc::clone(&persister) as _,
+            Duration::from_secs(10),
+            &metrics,
+        )
+        .await
+        .unwrap();
+
+        let segments = persister.load_segments(10).await.unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].segment_row_count, 1);
+        assert!(persister.load_catalog().await.unwrap().is_some());
+        assert_persist_duration_recorded(&metrics, "success");
+    }
+
+    #[tokio::test]
+    async fn persist_open_segment_on_shutdown_times_out() {
+        let metrics = metric::Registry::new();
+        let write_buffer = influxdb3_write::write_buffer::WriteBufferImpl::new(
+            Arc::new(influxdb3_write::catalog::Catalog::new()),
+            None::<Arc<influxdb3_write::wal::WalImpl>>,
+            Arc::new(SystemProvider::new()),
+        );
+
+        let res = crate::persist_open_segment(
+            &write_buffer,
+            Arc::new(PendingPersister),
+            Duration::from_millis(10),
+            &metrics,
+        )
+        .await;
+
+        assert!(matches!(res, Err(crate::Error::PersistTimeout { .. })));
+        assert_persist_duration_recorded(&metrics, "timeout");
+    }
+
+    fn assert_persist_duration_recorded(metrics: &metric::Registry, outcome: &'static str) {
+        let histogram = metrics
+            .get_instrument::<Metric<DurationHistogram>>("influxdb3_shutdown_persist_duration")
+            .expect("failed to read histogram")
+            .get_observer(&Attributes::from(&[("outcome", outcome)]))
+            .expect("failed to get observer")
+            .fetch();
+        assert_eq!(histogram.sample_count(), 1);
+    }
+
+    /// A persister that never completes, to simulate a hung object store.
+    #[derive(Debug)]
+    struct PendingPersister;
+
+    #[async_trait]
+    impl Persister for PendingPersister {
+        async fn load_catalog(&self) -> influxdb3_write::Result<Option<PersistedCatalog>> {
+            futures::future::pending().await
+        }
+
+        async fn load_segments(
+            &self,
+            _most_recent_n: usize,
+        ) -> influxdb3_write::Result<Vec<PersistedSegment>> {
+            futures::future::pending().await
+        }
+
+        async fn persist_catalog(
+            &self,
+            _segment_id: SegmentId,
+            _catalog: influxdb3_write::catalog::Catalog,
+        ) -> influxdb3_write::Result<()> {
+            futures::future::pending().await
+        }
+
+        async fn persist_segment(
+            &self,
+            _persisted_segment: PersistedSegment,
+        ) -> influxdb3_write::Result<()> {
+            futures::future::pending().await
+        }
+
+        async fn persist_parquet_file(
+            &self,
+            _path: &str,
+            _batch: RecordBatch,
+        ) -> influxdb3_write::Result<u64> {
+            futures::future::pending().await
+        }
+
+        async fn delete_database(&self, _db_name: &str) -> influxdb3_write::Result<()> {
+            futures::future::pending().await
+        }
+
+        fn object_store(&self) -> Arc<DynObjectStore> {
+            unimplemented!()
+        }
+    }
+
+    async fn setup_server() -> (String, CancellationToken) {
+        let (addr, shutdown) = setup_server_with(None, Arc::new(SystemProvider::new())).await;
+        (format!("http://{}", addr), shutdown)
+    }
+
+    async fn setup_server_with(
+        tls_config: Option<TlsConfig>,
+        time_provider: Arc<dyn TimeProvider>,
+    ) -> (SocketAddr, CancellationToken) {
+        setup_server_with_datafusion_config(tls_config, time_provider, HashMap::new()).await
+    }
+
+    async fn setup_server_with_datafusion_config(
+        tls_config: Option<TlsConfig>,
+        time_provider: Arc<dyn TimeProvider>,
+        datafusion_config: HashMap<String, String>,
+    ) -> (SocketAddr, CancellationToken) {
+        let addr = get_free_port();
+        let trace_header_parser = trace_http::ctx::TraceHeaderParser::new();
+        let metrics = Arc::new(metric::Registry::new());
+        let common_state = crate::CommonServerState::new(
+            Arc::clone(&metrics),
+            None,
+            trace_header_parser,
+            addr,
+            tls_config,
+            Arc::clone(&time_provider),
+        );
+        let catalog = Arc::new(influxdb3_write::catalog::Catalog::new());
+        let object_store: Arc<DynObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let exec = Arc::new(Executor::new_with_config(ExecutorConfig {
+            num_threads: NonZeroUsize::new(2).unwrap(),
+            target_query_partitions: NonZeroUsize::new(1).unwrap(),
+            object_stores: [&parquet_store]
+                .into_iter()
+                .map(|store| (store.id(), Arc::clone(store.object_store())))
+                .collect(),
+            metric_registry: Arc::clone(&metrics),
+            mem_pool_size: usize::MAX,
+        }));
+
+        let write_buffer = Arc::new(influxdb3_write::write_buffer::WriteBufferImpl::new(
+            Arc::clone(&catalog),
+            None::<Arc<influxdb3_write::wal::WalImpl>>,
+            Arc::clone(&time_provider),
+        ));
+        let query_executor = crate::query_executor::QueryExecutorImpl::new(
+            catalog,
+            Arc::clone(&write_buffer),
+            Arc::clone(&exec),
+            Arc::clone(&metrics),
+            Arc::new(datafusion_config),
+            10,
+            Arc::clone(&time_provider),
+        );
+        let persister = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+
+        let server = crate::Server::new(
+            common_state,
+            persister,
+            Arc::clone(&write_buffer),
+            Arc::new(query_executor),
+            usize::MAX,
+            Duration::from_secs(10),
+        );
+        let frontend_shutdown = CancellationToken::new();
+        let shutdown = frontend_shutdown.clone();
+
+        tokio::spawn(async move { serve(server, frontend_shutdown).await });
+
+        (addr, shutdown)
+    }
+
+    pub(crate) async fn write_lp(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        lp: impl Into<String> + Send,
+        authorization: Option<&str>,
+    ) -> Response<Body> {
+        let server = server.into();
+        let client = Client::new();
+        let url = format!("{}/api/v3/write_lp?db={}", server, database.into());
+        println!("{}", url);
+
+        let mut builder = Request::builder().uri(url).method("POST");
+        if let Some(authorization) = authorization {
+            builder = builder.header(hyper::header::AUTHORIZATION, authorization);
+        };
+        let request = builder
+            .body(Body::from(lp.into()))
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending write")
+    }
+
+    pub(crate) async fn query(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        query: impl Into<String> + Send,
+        authorization: Option<&str>,
+    ) -> Response<Body> {
+        let client = Client::new();
+        // query escaped for uri
+        let query = urlencoding::encode(&query.into());
+        let url = format!(
+            "{}/api/v3/query_sql?db={}&q={}",
+            server.into(),
+            database.into(),
+            query
+        );
+
+        println!("query url: {}", url);
+        let mut builder = Request::builder().uri(url).method("GET");
+        if let Some(authorization) = authorization {
+            builder = builder.header(hyper::header::AUTHORIZATION, authorization);
+        };
+        let request = builder
+            .body(Body::empty())
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending query")
+    }
+
+    /// Send a snappy-compressed Prometheus remote storage request to `path`, which is
+    /// `read` or `write`.
+    pub(crate) async fn prom_request(
+        server: impl Into<String> + Send,
+        path: &str,
+        database: impl Into<String> + Send,
+        body: Vec<u8>,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let url = format!(
+            "{}/api/v1/prom/{}?db={}",
+            server.into(),
+            path,
+            database.into()
+        );
+
+        let body = snap::raw::Encoder::new().compress_vec(&body).unwrap();
+        let request = Request::builder()
+            .uri(url)
+            .method("POST")
+            .header(hyper::header::CONTENT_ENCODING, "snappy")
+            .header(hyper::header::CONTENT_TYPE, "application/x-protobuf")
+            .body(Body::from(body))
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending prometheus request")
+    }
+
+    /// Send an OTLP/HTTP metrics export request, encoded as `content_type`.
+    pub(crate) async fn otlp_request(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let url = format!("{}/v1/metrics?db={}", server.into(), database.into());
+
+        let request = Request::builder()
+            .uri(url)
+            .method("POST")
+            .header(hyper::header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending OTLP request")
+    }
+
+    pub(crate) async fn row_write_request(
+        server: impl Into<String> + Send,
+        path_and_query: &str,
+        body: impl Into<String> + Send,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let url = format!("{}/api/v3/{}", server.into(), path_and_query);
+
+        let request = Request::builder()
+            .uri(url)
+            .method("POST")
+            .body(Body::from(body.into()))
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending write")
+    }
+
+    pub(crate) async fn query_with_format(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        query: impl Into<String> + Send,
+        format: &str,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let query = urlencoding::encode(&query.into());
+        let url = format!(
+            "{}/api/v3/query_sql?db={}&q={}&format={}",
+            server.into(),
+            database.into(),
+            query,
+            format
+        );
+
+        let request = Request::builder()
+            .uri(url)
+            .method("GET")
+            .body(Body::empty())
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending query")
+    }
+
+    pub(crate) async fn query_influxql(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        query: impl Into<String> + Send,
+    ) -> Response<Body> {
+        query_influxql_with_params(server, database, query, None).await
+    }
+
+    /// Send the InfluxQL `query` with `POST`, as is required for queries that write data.
+    pub(crate) async fn query_influxql_post(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        query: impl Into<String> + Send,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let url = format!(
+            "{}/api/v3/query_influxql?db={}&q={}",
+            server.into(),
+            database.into(),
+            urlencoding::encode(&query.into())
+        );
+
+        let request = Request::builder()
+            .uri(url)
+            .method("POST")
+            .body(Body::empty())
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending query")
+    }
+
+    pub(crate) async fn query_influxql_with_params(
+        server: impl Into<String> + Send,
+        database: impl Into<String> + Send,
+        query: impl Into<String> + Send,
+        params: Option<&str>,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let query = urlencoding::encode(&query.into());
+        let mut url = format!(
+            "{}/api/v3/query_influxql?db={}&q={}",
+            server.into(),
+            database.into(),
+            query
+        );
+        if let Some(params) = params {
+            url = format!("{url}&params={}", urlencoding::encode(params));
+        }
+
+        let request = Request::builder()
+            .uri(url)
+            .method("GET")
+            .body(Body::empty())
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error sending query")
+    }
+
+    pub(crate) async fn configure_database(
+        server: impl Into<String> + Send,
+        method: Method,
+        database: impl Into<String> + Send,
+    ) -> Response<Body> {
+        let client = Client::new();
+        let url = format!(
+            "{}/api/v3/configure/database?db={}",
+            server.into(),
+            database.into()
+        );
+
+        let request = Request::builder()
+            .uri(url)
+            .method(method)
+            .body(Body::empty())
+            .expect("failed to construct HTTP request");
+
+        client
+            .request(request)
+            .await
+            .expect("http error configuring database")
     }
 
     pub(crate) fn get_free_port() -> SocketAddr {
@@ -0,0 +1,903 @@
+//! Conversion of [OpenTelemetry] (OTLP) metrics to line protocol, shared by the
+//! OTLP/HTTP and OTLP/gRPC receivers.
+//!
+//! Each metric is written to a table named after the metric. The attributes of
+//! the resource, the instrumentation scope and the data point are stored as
+//! tags, with data point attributes replacing scope attributes of the same
+//! name, and scope attributes replacing resource attributes. The name and
+//! version of the scope, when set, are stored in the `otel.scope.name` and
+//! `otel.scope.version` tags. String attributes are stored as they are, other
+//! scalar attributes in their text form, bytes as base64 and arrays and
+//! key-value lists as JSON. Attributes with an empty key or value are skipped.
+//!
+//! The rows and fields written for each data point depend on the type of the
+//! metric:
+//!
+//! | Metric                | Rows                     | Tag        | Fields                                              |
+//! |-----------------------|--------------------------|------------|-----------------------------------------------------|
+//! | Gauge, Sum            | one per point            |            | `value` (float or integer)                          |
+//! | Histogram             | one per point            |            | `count`, `sum`, `min`, `max`                        |
+//! |                       | one per bucket           | `le`       | `bucket_count`                                      |
+//! | Exponential histogram | one per point            |            | `count`, `sum`, `min`, `max`, `scale`, `zero_count` |
+//! |                       | one per non-empty bucket | `le`       | `bucket_count`                                      |
+//! | Summary               | one per point            |            | `count`, `sum`                                      |
+//! |                       | one per quantile         | `quantile` | `value`                                             |
+//!
+//! Counts are unsigned integers and all other fields are floats, except for
+//! the integer values of gauges and sums and the integer `scale`. `sum`,
+//! `min` and `max` are omitted when the point doesn't record them. Bucket
+//! counts are not cumulative: the `le` tag holds the inclusive upper bound of
+//! the bucket, which is `+Inf` for the last bucket of a histogram. The zero
+//! bucket of an exponential histogram has the zero threshold as its bound, and
+//! its negative buckets have negative bounds.
+//!
+//! Data points flagged as having no recorded value are skipped. Data points
+//! that can't be written, such as those without a value or with a value that
+//! isn't finite, are rejected and reported in the partial success of the
+//! response, along with any points with a row that the write buffer rejects.
+//!
+//! [OpenTelemetry]: https://opentelemetry.io/docs/specs/otlp/
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use data_types::NamespaceName;
+use generated_types::opentelemetry::proto::{
+    collector::metrics::v1::{
+        ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+    },
+    common::v1::{any_value, AnyValue, KeyValue},
+    metrics::v1::{
+        exponential_histogram_data_point::Buckets, metric, number_data_point, DataPointFlags,
+        ExponentialHistogramDataPoint, HistogramDataPoint, Metric, NumberDataPoint,
+        ResourceMetrics, SummaryDataPoint,
+    },
+};
+use influxdb3_write::Bufferer;
+use influxdb_line_protocol::{builder::FieldValue, LineProtocolBuilder};
+use observability_deps::tracing::debug;
+
+/// The tag holding the name of the instrumentation scope.
+const SCOPE_NAME_TAG: &str = "otel.scope.name";
+/// The tag holding the version of the instrumentation scope.
+const SCOPE_VERSION_TAG: &str = "otel.scope.version";
+/// The tag holding the upper bound of a histogram bucket.
+const BUCKET_TAG: &str = "le";
+/// The tag holding the quantile of a summary value.
+const QUANTILE_TAG: &str = "quantile";
+
+const VALUE_FIELD: &str = "value";
+const COUNT_FIELD: &str = "count";
+const SUM_FIELD: &str = "sum";
+const MIN_FIELD: &str = "min";
+const MAX_FIELD: &str = "max";
+const SCALE_FIELD: &str = "scale";
+const ZERO_COUNT_FIELD: &str = "zero_count";
+const BUCKET_COUNT_FIELD: &str = "bucket_count";
+
+/// The maximum number of distinct error messages returned in a partial success.
+const MAX_ERROR_MESSAGES: usize = 10;
+
+/// Write the metrics of an OTLP export `request` to `database`, returning the
+/// response to the exporter.
+///
+/// The metrics of each table are written separately, and each line is
+/// validated independently, so that only the points with a row that the write
+/// buffer rejects are rejected. Rejected points are reported in the partial
+/// success of the response rather than failing the request.
+pub(crate) async fn write_metrics<B: Bufferer>(
+    write_buffer: &B,
+    database: NamespaceName<'static>,
+    request: &ExportMetricsServiceRequest,
+    default_time: i64,
+) -> ExportMetricsServiceResponse {
+    let MetricsLineProtocol {
+        tables,
+        mut rejected,
+    } = metrics_to_lp(&request.resource_metrics);
+
+    for (table_name, table) in tables.into_iter().filter(|(_, t)| t.points > 0) {
+        // The builder only writes valid UTF-8.
+        let lp = String::from_utf8_lossy(&table.lp);
+        match write_buffer
+            .write_lp_partial(database.clone(), &lp, default_time)
+            .await
+        {
+            Ok(result) => {
+                // A point is rejected if any of its rows is. Line numbers
+                // start at 1, and every line is one written for a point.
+                let mut rejected_points = BTreeSet::new();
+                for line in &result.invalid_lines {
+                    let point = table.line_points[line.line_number - 1];
+                    if rejected_points.insert(point) {
+                        let message = format!("metric {table_name}: {}", line.error_message);
+                        rejected.add(1, message);
+                    }
+                }
+                if !rejected_points.is_empty() {
+                    let points = rejected_points.len();
+                    debug!(%table_name, points, "rejected OTLP metric points");
+                }
+            }
+            Err(e) => {
+                debug!(%table_name, error=%e, "rejected OTLP metric points");
+                rejected.add(table.points, format!("metric {table_name}: {e}"));
+            }
+        }
+    }
+
+    rejected.into_response()
+}
+
+/// The line protocol for the metrics of an export request, by table.
+#[derive(Debug, Default)]
+struct MetricsLineProtocol {
+    tables: BTreeMap<String, TableLines>,
+    rejected: Rejections,
+}
+
+/// The line protocol written to a table, and the number of data points it
+/// holds.
+#[derive(Debug, Default)]
+struct TableLines {
+    lp: Vec<u8>,
+    points: i64,
+    /// The index of the data point that each line was written for.
+    line_points: Vec<i64>,
+}
+
+/// The data points that were rejected, and why.
+#[derive(Debug, Default)]
+struct Rejections {
+    points: i64,
+    messages: Vec<String>,
+}
+
+impl Rejections {
+    fn add(&mut self, points: i64, message: String) {
+        self.points += points;
+        if self.messages.len() < MAX_ERROR_MESSAGES && !self.messages.contains(&message) {
+            self.messages.push(message);
+        }
+    }
+
+    fn into_response(self) -> ExportMetricsServiceResponse {
+        let partial_success = (self.points > 0).then(|| ExportMetricsPartialSuccess {
+            rejected_data_points: self.points,
+            error_message: self.messages.join("; "),
+        });
+        ExportMetricsServiceResponse { partial_success }
+    }
+}
+
+type Tags = BTreeMap<String, String>;
+
+/// Convert OTLP metrics to line protocol, following the schema described in
+/// the [module documentation](self).
+fn metrics_to_lp(resource_metrics: &[ResourceMetrics]) -> MetricsLineProtocol {
+    let mut lp = MetricsLineProtocol::default();
+    for resource_metrics in resource_metrics {
+        let mut resource_tags = Tags::new();
+        if let Some(resource) = &resource_metrics.resource {
+            insert_attributes(&mut resource_tags, &resource.attributes);
+        }
+
+        for scope_metrics in &resource_metrics.scope_metrics {
+            let mut scope_tags = resource_tags.clone();
+            if let Some(scope) = &scope_metrics.scope {
+                insert_tag(&mut scope_tags, SCOPE_NAME_TAG, scope.name.clone());
+                insert_tag(&mut scope_tags, SCOPE_VERSION_TAG, scope.version.clone());
+                insert_attributes(&mut scope_tags, &scope.attributes);
+            }
+
+            for metric in &scope_metrics.metrics {
+                lp.add_metric(metric, &scope_tags);
+            }
+        }
+    }
+    lp
+}
+
+impl MetricsLineProtocol {
+    fn add_metric(&mut self, metric: &Metric, tags: &Tags) {
+        let points: Vec<Result<Point<'_>, String>> = match &metric.data {
+            Some(metric::Data::Gauge(gauge)) => gauge
+                .data_points
+                .iter()
+                .filter(|p| is_recorded(p.flags))
+                .map(|p| Point::try_new(&p.attributes, p.time_unix_nano, number_rows(p)))
+                .collect(),
+            Some(metric::Data::Sum(sum)) => sum
+                .data_points
+                .iter()
+                .filter(|p| is_recorded(p.flags))
+                .map(|p| Point::try_new(&p.attributes, p.time_unix_nano, number_rows(p)))
+                .collect(),
+            Some(metric::Data::Histogram(histogram)) => histogram
+                .data_points
+                .iter()
+                .filter(|p| is_recorded(p.flags))
+                .map(|p| Point::try_new(&p.attributes, p.time_unix_nano, histogram_rows(p)))
+                .collect(),
+            Some(metric::Data::ExponentialHistogram(histogram)) => histogram
+                .data_points
+                .iter()
+                .filter(|p| is_recorded(p.flags))
+                .map(|p| {
+                    Point::try_new(
+                        &p.attributes,
+                        p.time_unix_nano,
+                        Ok(exponential_histogram_rows(p)),
+                    )
+                })
+                .collect(),
+            Some(metric::Data::Summary(summary)) => summary
+                .data_points
+                .iter()
+                .filter(|p| is_recorded(p.flags))
+                .map(|p| Point::try_new(&p.attributes, p.time_unix_nano, Ok(summary_rows(p))))
+                .collect(),
+            None => vec![],
+        };
+        if points.is_empty() {
+            return;
+        }
+
+        if metric.name.is_empty() {
+            self.rejected
+                .add(points.len() as i64, "metric name must not be empty".into());
+            return;
+        }
+
+        let table = self.tables.entry(metric.name.clone()).or_default();
+        for point in points {
+            let point = match point {
+                Ok(point) => point,
+                Err(e) => {
+                    self.rejected.add(1, format!("metric {}: {e}", metric.name));
+                    continue;
+                }
+            };
+
+            let mut point_tags = tags.clone();
+            insert_attributes(&mut point_tags, point.attributes);
+            for row in &point.rows {
+                table.lp = write_row(
+                    std::mem::take(&mut table.lp),
+                    &metric.name,
+                    &point_tags,
+                    row,
+                    point.time,
+                );
+                table.line_points.push(table.points);
+            }
+            table.points += 1;
+        }
+    }
+}
+
+/// A data point of a metric, converted to the rows written for it.
+#[derive(Debug)]
+struct Point<'a> {
+    attributes: &'a [KeyValue],
+    /// The time of the point, or `None` to write it at the time it is received.
+    time: Option<i64>,
+    rows: Vec<Row>,
+}
+
+impl<'a> Point<'a> {
+    fn try_new(
+        attributes: &'a [KeyValue],
+        time_unix_nano: u64,
+        rows: Result<Vec<Row>, String>,
+    ) -> Result<Self, String> {
+        let rows = rows?;
+        let not_finite = rows
+            .iter()
+            .flat_map(|row| &row.fields)
+            .any(|(_, value)| matches!(value, Value::Float(v) if !v.is_finite()));
+        if not_finite {
+            return Err("value is not finite".into());
+        }
+
+        let time = match time_unix_nano {
+            0 => None,
+            t => Some(i64::try_from(t).map_err(|_| format!("timestamp {t} is out of range"))?),
+        };
+
+        Ok(Self {
+            attributes,
+            time,
+            rows,
+        })
+    }
+}
+
+/// A row of line protocol, with a tag that distinguishes it from the other
+/// rows of the same data point.
+#[derive(Debug, PartialEq)]
+struct Row {
+    tag: Option<(&'static str, String)>,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl Row {
+    fn new(fields: Vec<(&'static str, Value)>) -> Self {
+        Self { tag: None, fields }
+    }
+
+    fn tagged(tag: &'static str, value: String, fields: Vec<(&'static str, Value)>) -> Self {
+        Self {
+            tag: Some((tag, value)),
+            fields,
+        }
+    }
+}
+
+/// The value of a field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Float(f64),
+    Integer(i64),
+    UInteger(u64),
+}
+
+impl FieldValue for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(v) => FieldValue::fmt(v, f),
+            Self::Integer(v) => FieldValue::fmt(v, f),
+            Self::UInteger(v) => FieldValue::fmt(v, f),
+        }
+    }
+}
+
+fn number_rows(p: &NumberDataPoint) -> Result<Vec<Row>, String> {
+    let value = match p.value {
+        Some(number_data_point::Value::AsDouble(v)) => Value::Float(v),
+        Some(number_data_point::Value::AsInt(v)) => Value::Integer(v),
+        None => return Err("data point has no value".into()),
+    };
+    Ok(vec![Row::new(vec![(VALUE_FIELD, value)])])
+}
+
+fn histogram_rows(p: &HistogramDataPoint) -> Result<Vec<Row>, String> {
+    let mut rows = vec![Row::new(summary_fields(p.count, p.sum, p.min, p.max))];
+    if p.bucket_counts.is_empty() {
+        return Ok(rows);
+    }
+    if p.bucket_counts.len() != p.explicit_bounds.len() + 1 {
+        return Err(format!(
+            "histogram has {} bucket counts for {} explicit bounds",
+            p.bucket_counts.len(),
+            p.explicit_bounds.len()
+        ));
+    }
+
+    let bounds = p.explicit_bounds.iter().copied().chain([f64::INFINITY]);
+    rows.extend(
+        p.bucket_counts
+            .iter()
+            .zip(bounds)
+            .map(|(&count, bound)| bucket_row(bound, count)),
+    );
+    Ok(rows)
+}
+
+fn exponential_histogram_rows(p: &ExponentialHistogramDataPoint) -> Vec<Row> {
+    let mut fields = summary_fields(p.count, p.sum, p.min, p.max);
+    fields.push((SCALE_FIELD, Value::Integer(p.scale.into())));
+    fields.push((ZERO_COUNT_FIELD, Value::UInteger(p.zero_count)));
+    let mut rows = vec![Row::new(fields)];
+
+    if p.zero_count > 0 {
+        rows.push(bucket_row(p.zero_threshold, p.zero_count));
+    }
+    // The positive bucket with index i holds values in (base^i, base^(i+1)], and
+    // the negative bucket with index i those in [-base^(i+1), -base^i).
+    if let Some(positive) = &p.positive {
+        rows.extend(
+            exponential_buckets(positive)
+                .map(|(index, count)| bucket_row(exponential_bound(p.scale, index + 1), count)),
+        );
+    }
+    if let Some(negative) = &p.negative {
+        rows.extend(
+            exponential_buckets(negative)
+                .map(|(index, count)| bucket_row(-exponential_bound(p.scale, index), count)),
+        );
+    }
+    rows
+}
+
+fn summary_rows(p: &SummaryDataPoint) -> Vec<Row> {
+    let mut rows = vec![Row::new(summary_fields(p.count, Some(p.sum), None, None))];
+    rows.extend(p.quantile_values.iter().map(|q| {
+        Row::tagged(
+            QUANTILE_TAG,
+            q.quantile.to_string(),
+            vec![(VALUE_FIELD, Value::Float(q.value))],
+        )
+    }));
+    rows
+}
+
+fn summary_fields(
+    count: u64,
+    sum: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Vec<(&'static str, Value)> {
+    let mut fields = vec![(COUNT_FIELD, Value::UInteger(count))];
+    fields.extend(sum.map(|v| (SUM_FIELD, Value::Float(v))));
+    fields.extend(min.map(|v| (MIN_FIELD, Value::Float(v))));
+    fields.extend(max.map(|v| (MAX_FIELD, Value::Float(v))));
+    fields
+}
+
+fn bucket_row(upper_bound: f64, count: u64) -> Row {
+    let bound = if upper_bound == f64::INFINITY {
+        "+Inf".to_string()
+    } else if upper_bound == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        upper_bound.to_string()
+    };
+    Row::tagged(
+        BUCKET_TAG,
+        bound,
+        vec![(BUCKET_COUNT_FIELD, Value::UInteger(count))],
+    )
+}
+
+/// The index and count of each non-empty bucket of an exponential histogram.
+fn exponential_buckets(buckets: &Buckets) -> impl Iterator<Item = (i64, u64)> + '_ {
+    buckets
+        .bucket_counts
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(i, &count)| (i64::from(buckets.offset) + i as i64, count))
+}
+
+/// Returns `base^index` for an exponential histogram with the given `scale`,
+/// where `base = 2^(2^-scale)`.
+fn exponential_bound(scale: i32, index: i64) -> f64 {
+    (index as f64 * (-f64::from(scale)).exp2()).exp2()
+}
+
+fn is_recorded(flags: u32) -> bool {
+    flags & DataPointFlags::NoRecordedValueMask as u32 == 0
+}
+
+fn insert_attributes(tags: &mut Tags, attributes: &[KeyValue]) {
+    for KeyValue { key, value } in attributes {
+        let value = value.as_ref().map(attribute_value).unwrap_or_default();
+        insert_tag(tags, key, value);
+    }
+}
+
+/// Insert a tag, replacing any existing tag with the same key. Line protocol
+/// can't represent empty tag keys or values, so these are skipped.
+fn insert_tag(tags: &mut Tags, key: &str, value: String) {
+    if !key.is_empty() && !value.is_empty() {
+        tags.insert(key.to_string(), value);
+    }
+}
+
+/// Render an attribute value as a tag value.
+fn attribute_value(value: &AnyValue) -> String {
+    match &value.value {
+        Some(any_value::Value::StringValue(v)) => v.clone(),
+        Some(any_value::Value::BoolValue(v)) => v.to_string(),
+        Some(any_value::Value::IntValue(v)) => v.to_string(),
+        Some(any_value::Value::DoubleValue(v)) => v.to_string(),
+        Some(any_value::Value::BytesValue(v)) => BASE64_STANDARD.encode(v),
+        Some(any_value::Value::ArrayValue(_) | any_value::Value::KvlistValue(_)) => {
+            attribute_json(value).to_string()
+        }
+        None => String::new(),
+    }
+}
+
+fn attribute_json(value: &AnyValue) -> serde_json::Value {
+    match &value.value {
+        Some(any_value::Value::StringValue(v)) => v.as_str().into(),
+        Some(any_value::Value::BoolValue(v)) => (*v).into(),
+        Some(any_value::Value::IntValue(v)) => (*v).into(),
+        Some(any_value::Value::DoubleValue(v)) => (*v).into(),
+        Some(any_value::Value::BytesValue(v)) => BASE64_STANDARD.encode(v).into(),
+        Some(any_value::Value::ArrayValue(array)) => {
+            serde_json::Value::Array(array.values.iter().map(attribute_json).collect())
+        }
+        Some(any_value::Value::KvlistValue(list)) => serde_json::Value::Object(
+            list.values
+                .iter()
+                .map(|kv| {
+                    let value = kv
+                        .value
+                        .as_ref()
+                        .map(attribute_json)
+                        .unwrap_or(serde_json::Value::Null);
+                    (kv.key.clone(), value)
+                })
+                .collect(),
+        ),
+        None => serde_json::Value::Null,
+    }
+}
+
+/// Append a row of a data point to the line protocol in `lp`.
+fn write_row(lp: Vec<u8>, table_name: &str, tags: &Tags, row: &Row, time: Option<i64>) -> Vec<u8> {
+    let row_tag = row.tag.as_ref().map(|(key, value)| (*key, value.as_str()));
+
+    let mut line = LineProtocolBuilder::new_with(lp).measurement(table_name);
+    // The tag of the row replaces any attribute with the same key.
+    for (key, value) in tags {
+        if row_tag.map_or(true, |(row_key, _)| row_key != key.as_str()) {
+            line = line.tag(key, value);
+        }
+    }
+    if let Some((key, value)) = row_tag {
+        line = line.tag(key, value);
+    }
+
+    let ((name, value), rest) = row
+        .fields
+        .split_first()
+        .expect("rows have at least one field");
+    let mut line = line.field(name, *value);
+    for (name, value) in rest {
+        line = line.field(name, *value);
+    }
+
+    match time {
+        Some(time) => line.timestamp(time).close_line().build(),
+        None => line.close_line().build(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generated_types::opentelemetry::proto::{
+        common::v1::{ArrayValue, InstrumentationScope, KeyValueList},
+        metrics::v1::{
+            summary_data_point::ValueAtQuantile, ExponentialHistogram, Gauge, Histogram,
+            ScopeMetrics, Sum, Summary,
+        },
+        resource::v1::Resource,
+    };
+
+    use super::*;
+
+    fn kv(key: &str, value: any_value::Value) -> KeyValue {
+        KeyValue {
+            key: key.into(),
+            value: Some(AnyValue { value: Some(value) }),
+        }
+    }
+
+    fn string(value: &str) -> any_value::Value {
+        any_value::Value::StringValue(value.into())
+    }
+
+    fn metric(name: &str, data: metric::Data) -> Metric {
+        Metric {
+            name: name.into(),
+            data: Some(data),
+            ..Default::default()
+        }
+    }
+
+    /// Convert `metrics`, with no resource or scope, returning the line
+    /// protocol of each table.
+    fn convert(metrics: Vec<Metric>) -> (BTreeMap<String, String>, Rejections) {
+        let resource_metrics = vec![ResourceMetrics {
+            scope_metrics: vec![ScopeMetrics {
+                metrics,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        let lp = metrics_to_lp(&resource_metrics);
+        let tables = lp
+            .tables
+            .into_iter()
+            .map(|(name, table)| (name, String::from_utf8(table.lp).unwrap()))
+            .collect();
+        (tables, lp.rejected)
+    }
+
+    #[test]
+    fn test_gauge_and_sum() {
+        let number = |value, time_unix_nano, attributes| NumberDataPoint {
+            attributes,
+            time_unix_nano,
+            value,
+            ..Default::default()
+        };
+        let resource_metrics = vec![ResourceMetrics {
+            resource: Some(Resource {
+                attributes: vec![kv("service.name", string("api")), kv("host", string("a"))],
+                ..Default::default()
+            }),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Some(InstrumentationScope {
+                    name: "meter".into(),
+                    attributes: vec![kv("host", string("b"))],
+                    ..Default::default()
+                }),
+                metrics: vec![
+                    metric(
+                        "cpu",
+                        metric::Data::Gauge(Gauge {
+                            data_points: vec![
+                                number(
+                                    Some(number_data_point::Value::AsDouble(1.5)),
+                                    1_000,
+                                    vec![
+                                        kv("code", any_value::Value::IntValue(200)),
+                                        kv("ok", any_value::Value::BoolValue(true)),
+                                        kv("empty", string("")),
+                                    ],
+                                ),
+                                NumberDataPoint {
+                                    flags: DataPointFlags::NoRecordedValueMask as u32,
+                                    ..number(None, 2_000, vec![])
+                                },
+                                number(
+                                    Some(number_data_point::Value::AsDouble(f64::NAN)),
+                                    3_000,
+                                    vec![],
+                                ),
+                                number(None, 4_000, vec![]),
+                            ],
+                        }),
+                    ),
+                    metric(
+                        "requests",
+                        metric::Data::Sum(Sum {
+                            data_points: vec![number(
+                                Some(number_data_point::Value::AsInt(2)),
+                                0,
+                                vec![kv("host", string("c"))],
+                            )],
+                            ..Default::default()
+                        }),
+                    ),
+                    metric(
+                        "",
+                        metric::Data::Gauge(Gauge {
+                            data_points: vec![number(
+                                Some(number_data_point::Value::AsInt(1)),
+                                1_000,
+                                vec![],
+                            )],
+                        }),
+                    ),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+
+        let lp = metrics_to_lp(&resource_metrics);
+        let tables: Vec<_> = lp
+            .tables
+            .iter()
+            .map(|(name, table)| {
+                (
+                    name.as_str(),
+                    std::str::from_utf8(&table.lp).unwrap(),
+                    table.points,
+                )
+            })
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                (
+                    "cpu",
+                    "cpu,code=200,host=b,ok=true,otel.scope.name=meter,service.name=api \
+                     value=1.5 1000\n",
+                    1
+                ),
+                (
+                    "requests",
+                    "requests,host=c,otel.scope.name=meter,service.name=api value=2i\n",
+                    1
+                ),
+            ]
+        );
+        assert_eq!(lp.rejected.points, 3);
+        assert_eq!(
+            lp.rejected.messages,
+            vec![
+                "metric cpu: value is not finite",
+                "metric cpu: data point has no value",
+                "metric name must not be empty",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let point = |bucket_counts, explicit_bounds| HistogramDataPoint {
+            time_unix_nano: 10,
+            count: 3,
+            sum: Some(6.0),
+            max: Some(4.0),
+            bucket_counts,
+            explicit_bounds,
+            ..Default::default()
+        };
+        let (tables, rejected) = convert(vec![metric(
+            "latency",
+            metric::Data::Histogram(Histogram {
+                data_points: vec![
+                    point(vec![1, 2, 0], vec![1.0, 5.0]),
+                    point(vec![3], vec![1.0, 5.0]),
+                ],
+                ..Default::default()
+            }),
+        )]);
+
+        assert_eq!(
+            tables["latency"],
+            "latency count=3u,sum=6,max=4 10\n\
+             latency,le=1 bucket_count=1u 10\n\
+             latency,le=5 bucket_count=2u 10\n\
+             latency,le=+Inf bucket_count=0u 10\n"
+        );
+        assert_eq!(rejected.points, 1);
+        assert_eq!(
+            rejected.messages,
+            vec!["metric latency: histogram has 1 bucket counts for 2 explicit bounds"]
+        );
+    }
+
+    #[test]
+    fn test_line_points() {
+        let point = |time_unix_nano| HistogramDataPoint {
+            time_unix_nano,
+            count: 1,
+            bucket_counts: vec![1, 0],
+            explicit_bounds: vec![1.0],
+            ..Default::default()
+        };
+        let resource_metrics = vec![ResourceMetrics {
+            scope_metrics: vec![ScopeMetrics {
+                metrics: vec![metric(
+                    "latency",
+                    metric::Data::Histogram(Histogram {
+                        data_points: vec![point(10), point(20)],
+                        ..Default::default()
+                    }),
+                )],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+
+        // each line is mapped back to the point it was written for
+        let lp = metrics_to_lp(&resource_metrics);
+        let table = &lp.tables["latency"];
+        assert_eq!(table.points, 2);
+        assert_eq!(table.line_points, vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_exponential_histogram() {
+        let (tables, rejected) = convert(vec![metric(
+            "size",
+            metric::Data::ExponentialHistogram(ExponentialHistogram {
+                data_points: vec![ExponentialHistogramDataPoint {
+                    time_unix_nano: 10,
+                    count: 6,
+                    sum: Some(10.0),
+                    scale: 0,
+                    zero_count: 1,
+                    positive: Some(Buckets {
+                        offset: 0,
+                        bucket_counts: vec![2, 0, 1],
+                    }),
+                    negative: Some(Buckets {
+                        offset: 1,
+                        bucket_counts: vec![2],
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+        )]);
+
+        assert_eq!(
+            tables["size"],
+            "size count=6u,sum=10,scale=0i,zero_count=1u 10\n\
+             size,le=0 bucket_count=1u 10\n\
+             size,le=2 bucket_count=2u 10\n\
+             size,le=8 bucket_count=1u 10\n\
+             size,le=-2 bucket_count=2u 10\n"
+        );
+        assert_eq!(rejected.points, 0);
+    }
+
+    #[test]
+    fn test_exponential_bound() {
+        assert_eq!(exponential_bound(0, 0), 1.0);
+        assert_eq!(exponential_bound(0, 3), 8.0);
+        assert_eq!(exponential_bound(0, -1), 0.5);
+        assert_eq!(exponential_bound(-1, 1), 4.0);
+        assert!((exponential_bound(1, 1) - std::f64::consts::SQRT_2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_summary() {
+        let quantile = |quantile, value| ValueAtQuantile { quantile, value };
+        let (tables, _) = convert(vec![metric(
+            "rpc",
+            metric::Data::Summary(Summary {
+                data_points: vec![SummaryDataPoint {
+                    attributes: vec![kv("quantile", string("ignored"))],
+                    time_unix_nano: 10,
+                    count: 2,
+                    sum: 3.0,
+                    quantile_values: vec![quantile(0.5, 1.0), quantile(0.99, 2.0)],
+                    ..Default::default()
+                }],
+            }),
+        )]);
+
+        assert_eq!(
+            tables["rpc"],
+            "rpc,quantile=ignored count=2u,sum=3 10\n\
+             rpc,quantile=0.5 value=1 10\n\
+             rpc,quantile=0.99 value=2 10\n"
+        );
+    }
+
+    #[test]
+    fn test_attribute_value() {
+        let value = |value| AnyValue { value: Some(value) };
+
+        assert_eq!(attribute_value(&value(string("a b"))), "a b");
+        assert_eq!(
+            attribute_value(&value(any_value::Value::DoubleValue(0.25))),
+            "0.25"
+        );
+        assert_eq!(
+            attribute_value(&value(any_value::Value::BytesValue(vec![1, 2, 3]))),
+            "AQID"
+        );
+        assert_eq!(
+            attribute_value(&value(any_value::Value::ArrayValue(ArrayValue {
+                values: vec![value(any_value::Value::IntValue(1)), value(string("a"))],
+            }))),
+            r#"[1,"a"]"#
+        );
+        assert_eq!(
+            attribute_value(&value(any_value::Value::KvlistValue(KeyValueList {
+                values: vec![kv("k", any_value::Value::BoolValue(true))],
+            }))),
+            r#"{"k":true}"#
+        );
+        assert_eq!(attribute_value(&AnyValue { value: None }), "");
+    }
+
+    #[test]
+    fn test_rejections() {
+        assert_eq!(Rejections::default().into_response().partial_success, None);
+
+        let mut rejected = Rejections::default();
+        rejected.add(1, "a".into());
+        rejected.add(2, "a".into());
+        rejected.add(1, "b".into());
+        assert_eq!(
+            rejected.into_response().partial_success,
+            Some(ExportMetricsPartialSuccess {
+                rejected_data_points: 4,
+                error_message: "a; b".into(),
+            })
+        );
+    }
+}
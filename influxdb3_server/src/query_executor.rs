@@ -1,8 +1,15 @@
 //! module for query executor
-use crate::QueryExecutor;
-use arrow::datatypes::SchemaRef;
+use crate::continuous_query::{definition_from_statement, show_continuous_queries_result};
+use crate::{QueryExecutor, QueryKind};
+use arrow::array::{
+    as_string_array, Array, ArrayRef, BooleanArray, DictionaryArray, Int64Array,
+    TimestampNanosecondArray,
+};
+use arrow::compute::{cast, filter, filter_record_batch};
+use arrow::datatypes::{DataType, Field, Int32Type, Schema as ArrowSchema, SchemaRef};
+use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
-use data_types::{ChunkId, ChunkOrder, TransitionPartitionId};
+use data_types::{ChunkId, ChunkOrder, NamespaceName, TransitionPartitionId};
 use datafusion::catalog::schema::SchemaProvider;
 use datafusion::catalog::CatalogProvider;
 use datafusion::common::Statistics;
@@ -10,24 +17,37 @@ use datafusion::datasource::{TableProvider, TableType};
 use datafusion::error::DataFusionError;
 use datafusion::execution::context::SessionState;
 use datafusion::execution::SendableRecordBatchStream;
+use datafusion::physical_plan::memory::MemoryStream;
 use datafusion::physical_plan::ExecutionPlan;
 use datafusion::prelude::Expr;
-use datafusion_util::config::DEFAULT_SCHEMA;
+use datafusion_util::config::{DEFAULT_CATALOG, DEFAULT_SCHEMA};
+use futures::TryStreamExt;
+use generated_types::influxdata::iox::querier::v1::InfluxQlMetadata;
 use influxdb3_write::{
     catalog::{Catalog, DatabaseSchema},
     WriteBuffer,
 };
+use influxdb_influxql_parser::parse_statements;
+use influxdb_influxql_parser::statement::Statement;
 use iox_query::exec::{Executor, ExecutorType, IOxSessionContext};
 use iox_query::provider::ProviderBuilder;
 use iox_query::{QueryChunk, QueryChunkData, QueryCompletedToken, QueryNamespace, QueryText};
+use iox_query_influxql::frontend::planner::{database_catalog_name, referenced_databases};
+use iox_query_influxql::plan::IntoTarget;
+use iox_time::TimeProvider;
 use metric::Registry;
 use observability_deps::tracing::info;
+use parquet_to_line_protocol::convert_to_lines;
+use schema::builder::SchemaBuilder;
 use schema::sort::SortKey;
-use schema::Schema;
-use service_common::planner::Planner;
+use schema::{
+    InfluxFieldType, Schema, INFLUXQL_MEASUREMENT_COLUMN_NAME, INFLUXQL_METADATA_KEY,
+    TIME_COLUMN_NAME, TIME_DATA_TYPE,
+};
+use service_common::planner::{Planner, StatementParams};
 use service_common::QueryNamespaceProvider;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
 use trace::ctx::SpanContext;
@@ -44,6 +64,7 @@ pub struct QueryExecutorImpl<W> {
     exec: Arc<Executor>,
     datafusion_config: Arc<HashMap<String, String>>,
     query_execution_semaphore: Arc<InstrumentedAsyncSemaphore>,
+    time_provider: Arc<dyn TimeProvider>,
 }
 
 impl<W: WriteBuffer> QueryExecutorImpl<W> {
@@ -54,6 +75,7 @@ impl<W: WriteBuffer> QueryExecutorImpl<W> {
         metrics: Arc<Registry>,
         datafusion_config: Arc<HashMap<String, String>>,
         concurrent_query_limit: usize,
+        time_provider: Arc<dyn TimeProvider>,
     ) -> Self {
         let semaphore_metrics = Arc::new(AsyncSemaphoreMetrics::new(
             &metrics,
@@ -67,6 +89,7 @@ impl<W: WriteBuffer> QueryExecutorImpl<W> {
             exec,
             datafusion_config,
             query_execution_semaphore,
+            time_provider,
         }
     }
 }
@@ -77,6 +100,8 @@ impl<W: WriteBuffer> QueryExecutor for QueryExecutorImpl<W> {
         &self,
         database: &str,
         q: &str,
+        params: StatementParams,
+        kind: QueryKind,
         span_ctx: Option<SpanContext>,
         external_span_ctx: Option<RequestLogContext>,
     ) -> crate::Result<SendableRecordBatchStream> {
@@ -91,11 +116,30 @@ impl<W: WriteBuffer> QueryExecutor for QueryExecutorImpl<W> {
         let ctx = db.new_query_context(span_ctx);
         let _token = db.record_query(
             external_span_ctx.as_ref().map(RequestLogContext::ctx),
-            "sql",
+            kind.as_str(),
             Box::new(q.to_string()),
         );
+        if kind == QueryKind::InfluxQl {
+            if let Some(result) = self.continuous_query_statement(q).await? {
+                return Ok(result);
+            }
+            self.register_influxql_databases(&ctx, q)?;
+        }
+
         info!("plan");
-        let plan = Planner::new(&ctx).sql(q).await?;
+        let planner = Planner::new(&ctx);
+        let plan = match kind {
+            QueryKind::Sql if !params.is_empty() => Err(DataFusionError::NotImplemented(
+                "bind parameters for SQL queries".to_string(),
+            )),
+            QueryKind::Sql => planner.sql(q).await,
+            QueryKind::InfluxQl => planner.influxql(q, params).await,
+        }?;
+
+        if let Some(into) = IntoTarget::from_schema_metadata(plan.schema().metadata())? {
+            info!("select into");
+            return self.select_into(database, &ctx, plan, into).await;
+        }
 
         info!("execute_stream");
         let query_results = ctx.execute_stream(Arc::clone(&plan)).await?;
@@ -104,6 +148,282 @@ impl<W: WriteBuffer> QueryExecutor for QueryExecutorImpl<W> {
     }
 }
 
+impl<W: WriteBuffer> QueryExecutorImpl<W> {
+    /// Execute `q` if it is a `CREATE CONTINUOUS QUERY`, `DROP CONTINUOUS QUERY` or
+    /// `SHOW CONTINUOUS QUERIES` statement, which manage the continuous queries in the
+    /// catalog rather than being planned.
+    ///
+    /// Returns `None` for any other statement.
+    async fn continuous_query_statement(
+        &self,
+        q: &str,
+    ) -> crate::Result<Option<SendableRecordBatchStream>> {
+        // Any error parsing the statement is reported by the planner
+        let Ok(mut statements) = parse_statements(q) else {
+            return Ok(None);
+        };
+        if statements.len() != 1 {
+            return Ok(None);
+        }
+
+        let batches = match statements.pop() {
+            Some(Statement::CreateContinuousQuery(create)) => {
+                info!("create continuous query");
+                let cq = definition_from_statement(&create)?;
+                self.write_buffer.create_continuous_query(cq).await?;
+                vec![]
+            }
+            Some(Statement::DropContinuousQuery(drop)) => {
+                info!("drop continuous query");
+                self.write_buffer
+                    .drop_continuous_query(drop.database.as_str(), drop.name.as_str())
+                    .await?;
+                vec![]
+            }
+            Some(Statement::ShowContinuousQueries(_)) => {
+                vec![show_continuous_queries_result(
+                    &self.catalog.continuous_queries(),
+                )?]
+            }
+            _ => return Ok(None),
+        };
+
+        let schema = batches
+            .first()
+            .map_or_else(|| Arc::new(ArrowSchema::empty()), RecordBatch::schema);
+        Ok(Some(Box::pin(MemoryStream::try_new(
+            batches, schema, None,
+        )?)))
+    }
+
+    /// Register the catalog of each database referenced by the InfluxQL query `q` with `ctx`,
+    /// so that its measurements can be queried with a `database.retention_policy.measurement`
+    /// name or an `ON <database>` clause. No other databases are visible to the query.
+    ///
+    /// Returns an error if a referenced database does not exist. A retention policy that does
+    /// not exist is reported by the planner.
+    fn register_influxql_databases(&self, ctx: &IOxSessionContext, q: &str) -> crate::Result<()> {
+        // Any error parsing the query is reported by the planner
+        let Ok(statements) = parse_statements(q) else {
+            return Ok(());
+        };
+
+        for statement in &statements {
+            for (database, retention_policy) in referenced_databases(statement)? {
+                let name = database_catalog_name(&database, retention_policy.as_deref());
+                if name == DEFAULT_CATALOG {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "querying the database {DEFAULT_CATALOG} by name"
+                    ))
+                    .into());
+                }
+
+                let Some(db_schema) = self.catalog.db_schema(&name) else {
+                    if self.catalog.db_schema(&database).is_none() {
+                        return Err(crate::Error::DatabaseNotFound { db_name: database });
+                    }
+                    continue;
+                };
+                ctx.inner().register_catalog(
+                    name,
+                    Arc::new(QueryDatabase::new(
+                        db_schema,
+                        Arc::clone(&self.write_buffer),
+                        Arc::clone(&self.exec),
+                        Arc::clone(&self.datafusion_config),
+                        Arc::clone(&self.time_provider),
+                    )),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Execute the `plan` of an InfluxQL `SELECT ... INTO` statement, writing the results
+    /// to the measurement described by `into` rather than returning them.
+    ///
+    /// Each record batch of results is written as it is produced, so the results are
+    /// never held in memory all at once. Returns a single row with the number of points
+    /// written.
+    async fn select_into(
+        &self,
+        database: &str,
+        ctx: &IOxSessionContext,
+        plan: Arc<dyn ExecutionPlan>,
+        into: IntoTarget,
+    ) -> crate::Result<SendableRecordBatchStream> {
+        let metadata = influxql_metadata(&plan.schema())?;
+        let database = NamespaceName::new(into.database_name(database))
+            .map_err(|e| DataFusionError::Plan(format!("invalid INTO database: {e}")))?;
+
+        let mut written = 0;
+        let mut results = ctx.execute_stream(plan).await?;
+        while let Some(batch) = results.try_next().await? {
+            let lp = select_into_lines(&into, &metadata, &batch)?;
+            if lp.is_empty() {
+                continue;
+            }
+
+            let lp = String::from_utf8(lp).map_err(|e| DataFusionError::External(Box::new(e)))?;
+            let default_time = self.time_provider.now().timestamp_nanos();
+            written += self
+                .write_buffer
+                .write_lp(database.clone(), &lp, default_time)
+                .await
+                .map_err(influxdb3_write::Error::from)?
+                .line_count;
+        }
+
+        let result = select_into_result(written)?;
+        let schema = result.schema();
+        Ok(Box::pin(MemoryStream::try_new(vec![result], schema, None)?))
+    }
+}
+
+/// Returns the [`InfluxQlMetadata`] of the output `schema` of an InfluxQL query.
+fn influxql_metadata(schema: &ArrowSchema) -> Result<InfluxQlMetadata, DataFusionError> {
+    let md = schema
+        .metadata()
+        .get(INFLUXQL_METADATA_KEY)
+        .ok_or_else(|| DataFusionError::Internal("missing InfluxQL metadata".to_owned()))?;
+
+    serde_json::from_str(md).map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// Convert a `batch` of results of a `SELECT ... INTO` statement to line protocol.
+///
+/// The `GROUP BY` tags of the query are written as tags and all other columns, apart
+/// from the measurement and time, are written as fields. Rows without any field values
+/// are skipped.
+fn select_into_lines(
+    into: &IntoTarget,
+    metadata: &InfluxQlMetadata,
+    batch: &RecordBatch,
+) -> Result<Vec<u8>, DataFusionError> {
+    let measurement_index = metadata.measurement_column_index as usize;
+    let tag_indexes = metadata
+        .tag_key_columns
+        .iter()
+        .map(|tk| tk.column_index as usize)
+        .collect::<HashSet<_>>();
+
+    let mut builder = SchemaBuilder::new();
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(batch.num_columns());
+    let mut field_indexes = Vec::new();
+    for (index, field) in batch.schema().fields().iter().enumerate() {
+        let column = batch.column(index);
+        if index == measurement_index {
+            continue;
+        } else if field.name() == TIME_COLUMN_NAME {
+            builder.timestamp();
+            columns.push(cast(column, &TIME_DATA_TYPE())?);
+        } else if tag_indexes.contains(&index) {
+            builder.tag(field.name());
+            columns.push(cast(
+                column,
+                &DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+            )?);
+        } else {
+            let field_type = match field.data_type() {
+                DataType::Float64 => InfluxFieldType::Float,
+                DataType::Int64 => InfluxFieldType::Integer,
+                DataType::UInt64 => InfluxFieldType::UInteger,
+                DataType::Boolean => InfluxFieldType::Boolean,
+                DataType::Utf8 | DataType::Dictionary(..) => InfluxFieldType::String,
+                data_type => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "SELECT ... INTO for column {} of type {data_type}",
+                        field.name()
+                    )))
+                }
+            };
+            builder.influx_field(field.name(), field_type);
+            field_indexes.push(columns.len());
+            columns.push(cast(column, &DataType::from(field_type))?);
+        }
+    }
+
+    if field_indexes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let schema = builder
+        .build()
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+    let has_fields = (0..batch.num_rows())
+        .map(|row| field_indexes.iter().any(|&i| columns[i].is_valid(row)))
+        .collect::<Vec<_>>();
+    let has_fields = BooleanArray::from(has_fields);
+    let columns = columns
+        .iter()
+        .map(|column| filter(column, &has_fields))
+        .collect::<Result<Vec<_>, _>>()?;
+    let rows = RecordBatch::try_new(schema.as_arrow(), columns)?;
+
+    let to_lines = |measurement: &str, rows: &RecordBatch| {
+        convert_to_lines(measurement, &schema, rows).map_err(DataFusionError::Execution)
+    };
+
+    match &into.measurement {
+        Some(measurement) => to_lines(measurement, &rows),
+        None => {
+            // Write each row to the measurement it was selected from
+            let measurements = cast(
+                &filter(batch.column(measurement_index), &has_fields)?,
+                &DataType::Utf8,
+            )?;
+            let measurements = as_string_array(&measurements);
+
+            let mut lines = Vec::new();
+            for measurement in measurements.iter().flatten().collect::<BTreeSet<_>>() {
+                let matches = measurements
+                    .iter()
+                    .map(|v| v == Some(measurement))
+                    .collect::<Vec<_>>();
+                let rows = filter_record_batch(&rows, &BooleanArray::from(matches))?;
+                lines.extend(to_lines(measurement, &rows)?);
+            }
+            Ok(lines)
+        }
+    }
+}
+
+/// Returns the result of a `SELECT ... INTO` statement, which reports the number
+/// of points `written`.
+fn select_into_result(written: usize) -> Result<RecordBatch, DataFusionError> {
+    let metadata = InfluxQlMetadata {
+        measurement_column_index: 0,
+        tag_key_columns: vec![],
+    };
+    let metadata =
+        serde_json::to_string(&metadata).map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+    let schema = ArrowSchema::new(vec![
+        Field::new(
+            INFLUXQL_MEASUREMENT_COLUMN_NAME,
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+            false,
+        ),
+        Field::new(TIME_COLUMN_NAME, TIME_DATA_TYPE(), false),
+        Field::new("written", DataType::Int64, false),
+    ])
+    .with_metadata(HashMap::from([(
+        INFLUXQL_METADATA_KEY.to_owned(),
+        metadata,
+    )]));
+
+    Ok(RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(DictionaryArray::<Int32Type>::from_iter(["result"])),
+            Arc::new(TimestampNanosecondArray::from(vec![0])),
+            Arc::new(Int64Array::from(vec![written as i64])),
+        ],
+    )?)
+}
+
 // This implementation is for the Flight service
 #[async_trait]
 impl<W: WriteBuffer> QueryNamespaceProvider for QueryExecutorImpl<W> {
@@ -124,6 +444,7 @@ impl<W: WriteBuffer> QueryNamespaceProvider for QueryExecutorImpl<W> {
             write_buffer: Arc::clone(&self.write_buffer) as _,
             exec: Arc::clone(&self.exec),
             datafusion_config: Arc::clone(&self.datafusion_config),
+            time_provider: Arc::clone(&self.time_provider),
         }))
     }
 
@@ -141,6 +462,7 @@ pub struct QueryDatabase<B> {
     write_buffer: Arc<B>,
     exec: Arc<Executor>,
     datafusion_config: Arc<HashMap<String, String>>,
+    time_provider: Arc<dyn TimeProvider>,
 }
 
 impl<B: WriteBuffer> QueryDatabase<B> {
@@ -149,12 +471,14 @@ impl<B: WriteBuffer> QueryDatabase<B> {
         write_buffer: Arc<B>,
         exec: Arc<Executor>,
         datafusion_config: Arc<HashMap<String, String>>,
+        time_provider: Arc<dyn TimeProvider>,
     ) -> Self {
         Self {
             db_schema,
             write_buffer,
             exec,
             datafusion_config,
+            time_provider,
         }
     }
 }
@@ -163,13 +487,22 @@ impl<B: WriteBuffer> QueryDatabase<B> {
 impl<B: WriteBuffer> QueryNamespace for QueryDatabase<B> {
     async fn chunks(
         &self,
-        _table_name: &str,
-        _filters: &[Expr],
-        _projection: Option<&Vec<usize>>,
-        _ctx: IOxSessionContext,
+        table_name: &str,
+        filters: &[Expr],
+        projection: Option<&Vec<usize>>,
+        ctx: IOxSessionContext,
     ) -> Result<Vec<Arc<dyn QueryChunk>>, DataFusionError> {
-        info!("called chunks on querydatabase");
-        todo!()
+        if !self.db_schema.table_exists(table_name) {
+            return Ok(vec![]);
+        }
+
+        self.write_buffer.get_table_chunks(
+            &self.db_schema.name,
+            table_name,
+            filters,
+            projection,
+            &ctx.inner().state(),
+        )
     }
 
     fn retention_time_ns(&self) -> Option<i64> {
@@ -194,19 +527,39 @@ impl<B: WriteBuffer> QueryNamespace for QueryDatabase<B> {
             Arc::clone(&self.write_buffer),
             Arc::clone(&self.exec),
             Arc::clone(&self.datafusion_config),
+            Arc::clone(&self.time_provider),
         );
 
         let mut cfg = self
             .exec
             .new_execution_config(ExecutorType::Query)
             .with_default_catalog(Arc::new(qdb))
-            .with_span_context(span_ctx);
+            .with_span_context(span_ctx)
+            .with_query_start_time(self.time_provider.now().date_time());
 
         for (k, v) in self.datafusion_config.as_ref() {
             cfg = cfg.with_config_option(k, v);
         }
 
-        cfg.build()
+        let ctx = cfg.build();
+
+        // The database is also a catalog named after it, so that InfluxQL queries can refer to
+        // its measurements by a qualified name. The executor registers any other databases
+        // that an InfluxQL query refers to.
+        if self.db_schema.name != DEFAULT_CATALOG {
+            ctx.inner().register_catalog(
+                self.db_schema.name.clone(),
+                Arc::new(Self::new(
+                    Arc::clone(&self.db_schema),
+                    Arc::clone(&self.write_buffer),
+                    Arc::clone(&self.exec),
+                    Arc::clone(&self.datafusion_config),
+                    Arc::clone(&self.time_provider),
+                )),
+            );
+        }
+
+        ctx
     }
 }
 
@@ -227,6 +580,7 @@ impl<B: WriteBuffer> CatalogProvider for QueryDatabase<B> {
             Arc::clone(&self.write_buffer),
             Arc::clone(&self.exec),
             Arc::clone(&self.datafusion_config),
+            Arc::clone(&self.time_provider),
         );
 
         match name {
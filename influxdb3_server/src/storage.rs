@@ -0,0 +1,1393 @@
+//! The InfluxDB storage gRPC API, `influxdata.platform.storage.Storage`, which Flux and
+//! InfluxDB 2.x tools use to read series and the tag keys, tag values and fields of
+//! measurements.
+//!
+//! Requests are planned by the InfluxRPC planner against the same databases as SQL and
+//! InfluxQL queries. The database of a request is given by its `database` metadata or, as
+//! 2.x clients only know about organizations and buckets, by the org and bucket IDs of its
+//! read source, as the database `<org_id>_<bucket_id>` with each ID written as 16 hex digits.
+//!
+//! Predicates can refer to the measurement and field of a series with the special
+//! `_measurement` and `_field` tag keys, which are also the tag keys `\x00` and `\xff` used by
+//! the 2.x storage engine.
+use std::collections::{BTreeSet, HashMap};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use arrow::datatypes::DataType;
+use datafusion::common::Column;
+use datafusion::error::DataFusionError;
+use datafusion::logical_expr::{lit, Expr};
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt, TryStreamExt};
+use generated_types::google::protobuf::{Any, Empty};
+use generated_types::influxdata::platform::storage::{
+    aggregate::AggregateType,
+    measurement_fields_response::{FieldType, MessageField},
+    node::{Comparison, Logical, Type as NodeType, Value as NodeValue},
+    read_group_request::Group as GroupType,
+    read_response::{
+        frame::Data as FrameData, BooleanPointsFrame, DataType as FrameDataType, FloatPointsFrame,
+        Frame, GroupFrame, IntegerPointsFrame, SeriesFrame, StringPointsFrame, UnsignedPointsFrame,
+    },
+    storage_server::Storage,
+    Aggregate as RpcAggregate, CapabilitiesResponse, Capability, Duration as RpcDuration,
+    Int64ValuesResponse, MeasurementFieldsRequest, MeasurementFieldsResponse,
+    MeasurementNamesRequest, MeasurementTagKeysRequest, MeasurementTagValuesRequest, Node,
+    OffsetsResponse, Predicate as RpcPredicate, ReadFilterRequest, ReadGroupRequest, ReadResponse,
+    ReadSeriesCardinalityRequest, ReadSource, ReadWindowAggregateRequest, StringValuesResponse,
+    Tag as RpcTag, TagKeyMetaNames, TagKeysRequest, TagValuesGroupedByMeasurementAndTagKeyRequest,
+    TagValuesRequest, TagValuesResponse, TimestampRange as RpcTimestampRange, Window,
+};
+use generated_types::protobuf_type_url_eq;
+use iox_query::exec::fieldlist::FieldList;
+use iox_query::exec::seriesset::series::{Data, Either, Group, Series};
+use iox_query::exec::stringset::StringSetRef;
+use iox_query::exec::IOxSessionContext;
+use iox_query::plan::{
+    fieldlist::FieldListPlan, seriesset::SeriesSetPlans, stringset::StringSetPlan,
+};
+use iox_query::{Aggregate, QueryCompletedToken, QueryNamespace, WindowDuration};
+use predicate::rpc_predicate::{InfluxRpcPredicate, FIELD_COLUMN_NAME, MEASUREMENT_COLUMN_NAME};
+use predicate::Predicate;
+use prost::Message;
+use query_functions::{regex_match_expr, regex_not_match_expr};
+use serde::Serialize;
+use service_common::planner::Planner;
+use service_common::{datafusion_error_to_tonic_code, QueryNamespaceProvider};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status};
+use trace::ctx::SpanContext;
+use trace::span::SpanExt;
+use trace_http::ctx::RequestLogContext;
+use tracker::InstrumentedAsyncOwnedSemaphorePermit;
+
+use crate::grpc::DATABASE_METADATA_KEY;
+
+/// The protobuf type of the read source of a request.
+const READ_SOURCE_TYPE: &str = "influxdata.platform.storage.read.ReadSource";
+
+/// The tag key of the measurement of a series when binary tag keys are used.
+const MEASUREMENT_TAG_KEY: &[u8] = &[0];
+
+/// The tag key of the field of a series when binary tag keys are used.
+const FIELD_TAG_KEY: &[u8] = &[255];
+
+/// The maximum number of points in each points frame of a series.
+const POINTS_PER_BATCH: usize = 1000;
+
+/// Frames and values are split across responses of about this many bytes, which keeps each
+/// response well within the 4MB message size limit of most gRPC clients.
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Serves the storage gRPC API from the databases of `query_executor`.
+#[derive(Debug)]
+pub(crate) struct StorageService<Q> {
+    query_executor: Arc<Q>,
+}
+
+impl<Q> StorageService<Q> {
+    pub(crate) fn new(query_executor: Arc<Q>) -> Self {
+        Self { query_executor }
+    }
+}
+
+impl<Q: QueryNamespaceProvider> StorageService<Q> {
+    /// Look up the database read by `request`, once a permit to run it has been acquired, and
+    /// record the request as a query of type `query_type`, which completes when the returned
+    /// token is dropped.
+    async fn start_query<T: Serialize + Sync>(
+        &self,
+        request: &Request<T>,
+        source: Option<&Any>,
+        query_type: &'static str,
+    ) -> Result<(Query<Q::Db>, QueryCompletedToken), Status> {
+        let database = database_name(request.metadata(), source)?;
+        let query_text = serde_json::to_string(request.get_ref()).unwrap_or_default();
+        let external_span_ctx: Option<RequestLogContext> = request.extensions().get().cloned();
+        let span_ctx: Option<SpanContext> = request.extensions().get().cloned();
+
+        let permit = self
+            .query_executor
+            .acquire_semaphore(span_ctx.child_span("query rate limit semaphore"))
+            .await;
+        let db = self
+            .query_executor
+            .db(&database, span_ctx.child_span("get database"), false)
+            .await
+            .ok_or_else(|| Status::not_found(format!("database {database} not found")))?;
+
+        let ctx = db.new_query_context(span_ctx);
+        let completed = db.record_query(
+            external_span_ctx.as_ref().map(RequestLogContext::ctx),
+            query_type,
+            Box::new(query_text),
+        );
+
+        let query = Query { db, ctx, permit };
+        Ok((query, completed))
+    }
+}
+
+#[tonic::async_trait]
+impl<Q: QueryNamespaceProvider> Storage for StorageService<Q> {
+    type ReadFilterStream = ResponseStream<ReadResponse>;
+
+    async fn read_filter(
+        &self,
+        request: Request<ReadFilterRequest>,
+    ) -> Result<Response<Self::ReadFilterStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().read_source.as_ref(),
+                "read_filter",
+            )
+            .await?;
+        let request = request.into_inner();
+        let binary_tag_keys = binary_tag_keys(request.tag_key_meta_names)?;
+        let predicate = make_predicate(None, request.range, request.predicate)?;
+
+        let plans = Planner::new(&query.ctx)
+            .read_filter(Arc::clone(&query.db), predicate)
+            .await
+            .map_err(datafusion_status)?;
+        let series = query.series(plans).await?;
+
+        Ok(query.respond(completed, read_responses(series, binary_tag_keys)))
+    }
+
+    type ReadGroupStream = ResponseStream<ReadResponse>;
+
+    async fn read_group(
+        &self,
+        request: Request<ReadGroupRequest>,
+    ) -> Result<Response<Self::ReadGroupStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().read_source.as_ref(),
+                "read_group",
+            )
+            .await?;
+        let request = request.into_inner();
+        let predicate = make_predicate(None, request.range, request.predicate)?;
+        let aggregate = make_aggregate(request.aggregate.as_ref())?;
+        let group_keys = match GroupType::from_i32(request.group) {
+            Some(GroupType::None) => vec![],
+            Some(GroupType::By) => request.group_keys,
+            None => {
+                return Err(Status::invalid_argument(format!(
+                    "unknown group type {}",
+                    request.group
+                )))
+            }
+        };
+
+        let plans = Planner::new(&query.ctx)
+            .read_group(Arc::clone(&query.db), predicate, aggregate, group_keys)
+            .await
+            .map_err(datafusion_status)?;
+        let series = query.series(plans).await?;
+
+        Ok(query.respond(completed, read_responses(series, false)))
+    }
+
+    type ReadWindowAggregateStream = ResponseStream<ReadResponse>;
+
+    async fn read_window_aggregate(
+        &self,
+        request: Request<ReadWindowAggregateRequest>,
+    ) -> Result<Response<Self::ReadWindowAggregateStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().read_source.as_ref(),
+                "read_window_aggregate",
+            )
+            .await?;
+        let request = request.into_inner();
+        let binary_tag_keys = binary_tag_keys(request.tag_key_meta_names)?;
+        let predicate = make_predicate(None, request.range, request.predicate)?;
+        let aggregate = match request.aggregate.as_slice() {
+            [aggregate] => make_aggregate(Some(aggregate))?,
+            _ => {
+                return Err(Status::invalid_argument(
+                    "exactly one aggregate must be given",
+                ))
+            }
+        };
+        if aggregate == Aggregate::None {
+            return Err(Status::invalid_argument(
+                "an aggregate other than none must be given",
+            ));
+        }
+        let (every, offset) = make_window(request.window_every, request.offset, request.window)?;
+
+        let plans = Planner::new(&query.ctx)
+            .read_window_aggregate(Arc::clone(&query.db), predicate, aggregate, every, offset)
+            .await
+            .map_err(datafusion_status)?;
+        let series = query.series(plans).await?;
+
+        Ok(query.respond(completed, read_responses(series, binary_tag_keys)))
+    }
+
+    type TagKeysStream = ResponseStream<StringValuesResponse>;
+
+    async fn tag_keys(
+        &self,
+        request: Request<TagKeysRequest>,
+    ) -> Result<Response<Self::TagKeysStream>, Status> {
+        let (query, completed) = self
+            .start_query(&request, request.get_ref().tags_source.as_ref(), "tag_keys")
+            .await?;
+        let request = request.into_inner();
+        let predicate = make_predicate(None, request.range, request.predicate)?;
+
+        let tag_keys = query.tag_keys(predicate).await?;
+
+        Ok(query.respond(completed, iter_responses(tag_keys)))
+    }
+
+    type TagValuesStream = ResponseStream<StringValuesResponse>;
+
+    async fn tag_values(
+        &self,
+        request: Request<TagValuesRequest>,
+    ) -> Result<Response<Self::TagValuesStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().tags_source.as_ref(),
+                "tag_values",
+            )
+            .await?;
+        let request = request.into_inner();
+        let predicate = make_predicate(None, request.range, request.predicate)?;
+        let tag_key = tag_key_column(&request.tag_key)?;
+
+        let tag_values = query.tag_values(predicate, tag_key).await?;
+
+        Ok(query.respond(completed, iter_responses(tag_values)))
+    }
+
+    type TagValuesGroupedByMeasurementAndTagKeyStream = ResponseStream<TagValuesResponse>;
+
+    async fn tag_values_grouped_by_measurement_and_tag_key(
+        &self,
+        _request: Request<TagValuesGroupedByMeasurementAndTagKeyRequest>,
+    ) -> Result<Response<Self::TagValuesGroupedByMeasurementAndTagKeyStream>, Status> {
+        Err(Status::unimplemented(
+            "tag_values_grouped_by_measurement_and_tag_key",
+        ))
+    }
+
+    type ReadSeriesCardinalityStream = ResponseStream<Int64ValuesResponse>;
+
+    async fn read_series_cardinality(
+        &self,
+        _request: Request<ReadSeriesCardinalityRequest>,
+    ) -> Result<Response<Self::ReadSeriesCardinalityStream>, Status> {
+        Err(Status::unimplemented("read_series_cardinality"))
+    }
+
+    async fn capabilities(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<CapabilitiesResponse>, Status> {
+        let capability = |features: &[&str]| Capability {
+            features: features.iter().map(ToString::to_string).collect(),
+        };
+        let caps = HashMap::from([
+            (
+                "WindowAggregate".to_string(),
+                capability(&[
+                    "Count", "Sum", "Min", "Max", "Mean", "First", "Last", "Offset",
+                ]),
+            ),
+            (
+                "Group".to_string(),
+                capability(&["First", "Last", "Min", "Max", "Count", "Sum", "Mean"]),
+            ),
+        ]);
+
+        Ok(Response::new(CapabilitiesResponse { caps }))
+    }
+
+    type MeasurementNamesStream = ResponseStream<StringValuesResponse>;
+
+    async fn measurement_names(
+        &self,
+        request: Request<MeasurementNamesRequest>,
+    ) -> Result<Response<Self::MeasurementNamesStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().source.as_ref(),
+                "measurement_names",
+            )
+            .await?;
+        let request = request.into_inner();
+        let predicate = make_predicate(None, request.range, request.predicate)?;
+
+        let names = query.tag_values(predicate, MEASUREMENT_COLUMN_NAME).await?;
+
+        Ok(query.respond(completed, iter_responses(names)))
+    }
+
+    type MeasurementTagKeysStream = ResponseStream<StringValuesResponse>;
+
+    async fn measurement_tag_keys(
+        &self,
+        request: Request<MeasurementTagKeysRequest>,
+    ) -> Result<Response<Self::MeasurementTagKeysStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().source.as_ref(),
+                "measurement_tag_keys",
+            )
+            .await?;
+        let request = request.into_inner();
+        let predicate =
+            make_predicate(Some(request.measurement), request.range, request.predicate)?;
+
+        let tag_keys = query.tag_keys(predicate).await?;
+
+        Ok(query.respond(completed, iter_responses(tag_keys)))
+    }
+
+    type MeasurementTagValuesStream = ResponseStream<StringValuesResponse>;
+
+    async fn measurement_tag_values(
+        &self,
+        request: Request<MeasurementTagValuesRequest>,
+    ) -> Result<Response<Self::MeasurementTagValuesStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().source.as_ref(),
+                "measurement_tag_values",
+            )
+            .await?;
+        let request = request.into_inner();
+        let predicate =
+            make_predicate(Some(request.measurement), request.range, request.predicate)?;
+        let tag_key = tag_key_column(request.tag_key.as_bytes())?;
+
+        let tag_values = query.tag_values(predicate, tag_key).await?;
+
+        Ok(query.respond(completed, iter_responses(tag_values)))
+    }
+
+    type MeasurementFieldsStream = ResponseStream<MeasurementFieldsResponse>;
+
+    async fn measurement_fields(
+        &self,
+        request: Request<MeasurementFieldsRequest>,
+    ) -> Result<Response<Self::MeasurementFieldsStream>, Status> {
+        let (query, completed) = self
+            .start_query(
+                &request,
+                request.get_ref().source.as_ref(),
+                "measurement_fields",
+            )
+            .await?;
+        let request = request.into_inner();
+        let predicate =
+            make_predicate(Some(request.measurement), request.range, request.predicate)?;
+
+        let plan = Planner::new(&query.ctx)
+            .field_columns(Arc::clone(&query.db), predicate)
+            .await
+            .map_err(datafusion_status)?;
+        let fields = query.field_list(plan).await?;
+
+        let responses = vec![measurement_fields_response(fields)];
+        Ok(query.respond(completed, iter_responses(responses)))
+    }
+
+    async fn offsets(&self, _request: Request<Empty>) -> Result<Response<OffsetsResponse>, Status> {
+        Err(Status::unimplemented("offsets"))
+    }
+}
+
+/// A request that is running against a database.
+struct Query<D> {
+    db: Arc<D>,
+    ctx: IOxSessionContext,
+    permit: InstrumentedAsyncOwnedSemaphorePermit,
+}
+
+impl<D> Query<D> {
+    /// Stream the `responses` to the request, which holds its permit until they have all been
+    /// sent.
+    fn respond<T>(
+        self,
+        completed: QueryCompletedToken,
+        responses: impl Stream<Item = Result<T, Status>> + Send + 'static,
+    ) -> Response<ResponseStream<T>> {
+        Response::new(ResponseStream {
+            inner: responses.boxed(),
+            completed,
+            _permit: self.permit,
+            done: false,
+        })
+    }
+}
+
+impl<D: QueryNamespace + 'static> Query<D> {
+    /// Run the series set `plans`, returning a stream of their series and groups.
+    async fn series(
+        &self,
+        plans: SeriesSetPlans,
+    ) -> Result<BoxStream<'static, Result<Either, Status>>, Status> {
+        let memory_pool = Arc::clone(&self.ctx.inner().runtime_env().memory_pool);
+        let series = self
+            .ctx
+            .to_series_and_groups(plans, memory_pool, POINTS_PER_BATCH)
+            .await
+            .map_err(datafusion_status)?;
+        Ok(series.map_err(datafusion_status).boxed())
+    }
+
+    /// Run the string set `plan`.
+    async fn string_set(&self, plan: StringSetPlan) -> Result<StringSetRef, Status> {
+        self.ctx
+            .to_string_set(plan)
+            .await
+            .map_err(datafusion_status)
+    }
+
+    /// Run the field list `plan`.
+    async fn field_list(&self, plan: FieldListPlan) -> Result<FieldList, Status> {
+        self.ctx
+            .to_field_list(plan)
+            .await
+            .map_err(datafusion_status)
+    }
+
+    /// Find the tag keys of the series matching `predicate`, including the special
+    /// measurement and field tag keys.
+    async fn tag_keys(
+        &self,
+        predicate: InfluxRpcPredicate,
+    ) -> Result<Vec<StringValuesResponse>, Status> {
+        let plan = Planner::new(&self.ctx)
+            .tag_keys(Arc::clone(&self.db), predicate)
+            .await
+            .map_err(datafusion_status)?;
+        let tag_keys = self.string_set(plan).await?;
+
+        let tag_keys = std::iter::once(MEASUREMENT_TAG_KEY.to_vec())
+            .chain(tag_keys.iter().map(|key| key.as_bytes().to_vec()))
+            .chain(std::iter::once(FIELD_TAG_KEY.to_vec()));
+        Ok(string_values_responses(tag_keys))
+    }
+
+    /// Find the values of `tag_key` in the series matching `predicate`, which are measurement
+    /// names and field keys for the special measurement and field tag keys.
+    async fn tag_values(
+        &self,
+        predicate: InfluxRpcPredicate,
+        tag_key: &str,
+    ) -> Result<Vec<StringValuesResponse>, Status> {
+        let planner = Planner::new(&self.ctx);
+        let values: BTreeSet<String> = match tag_key {
+            MEASUREMENT_COLUMN_NAME => {
+                let plan = planner
+                    .table_names(Arc::clone(&self.db), predicate)
+                    .await
+                    .map_err(datafusion_status)?;
+                self.string_set(plan).await?.iter().cloned().collect()
+            }
+            FIELD_COLUMN_NAME => {
+                let plan = planner
+                    .field_columns(Arc::clone(&self.db), predicate)
+                    .await
+                    .map_err(datafusion_status)?;
+                let fields = self.field_list(plan).await?;
+                fields.fields.into_iter().map(|field| field.name).collect()
+            }
+            _ => {
+                let plan = planner
+                    .tag_values(Arc::clone(&self.db), tag_key, predicate)
+                    .await
+                    .map_err(datafusion_status)?;
+                self.string_set(plan).await?.iter().cloned().collect()
+            }
+        };
+
+        Ok(string_values_responses(
+            values.into_iter().map(String::into_bytes),
+        ))
+    }
+}
+
+/// The stream of responses to a request, which records that its query succeeded once the last
+/// response has been sent, and releases its permit when dropped.
+pub(crate) struct ResponseStream<T> {
+    inner: BoxStream<'static, Result<T, Status>>,
+    completed: QueryCompletedToken,
+    _permit: InstrumentedAsyncOwnedSemaphorePermit,
+    done: bool,
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        let next = ready!(self.inner.poll_next_unpin(cx));
+        match &next {
+            Some(Ok(_)) => {}
+            Some(Err(_)) => self.done = true,
+            None => {
+                self.done = true;
+                self.completed.set_success();
+            }
+        }
+        Poll::Ready(next)
+    }
+}
+
+/// A stream of responses that have already been built.
+fn iter_responses<T: Send + 'static>(
+    responses: Vec<T>,
+) -> impl Stream<Item = Result<T, Status>> + Send + 'static {
+    futures::stream::iter(responses.into_iter().map(Ok))
+}
+
+fn datafusion_status(e: DataFusionError) -> Status {
+    Status::new(datafusion_error_to_tonic_code(&e), e.to_string())
+}
+
+/// Returns the database a request reads from, which is given by its `database` metadata or
+/// else by the org and bucket IDs of its read `source`.
+fn database_name(metadata: &MetadataMap, source: Option<&Any>) -> Result<String, Status> {
+    if let Some(database) = metadata.get(DATABASE_METADATA_KEY) {
+        let database = database
+            .to_str()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        return Ok(database.to_string());
+    }
+
+    let source = source.ok_or_else(|| {
+        Status::invalid_argument(format!(
+            "missing read source or '{DATABASE_METADATA_KEY}' metadata"
+        ))
+    })?;
+    if !protobuf_type_url_eq(&source.type_url, READ_SOURCE_TYPE) {
+        return Err(Status::invalid_argument(format!(
+            "unsupported read source type {}",
+            source.type_url
+        )));
+    }
+    let source = ReadSource::decode(source.value.clone())
+        .map_err(|e| Status::invalid_argument(format!("invalid read source: {e}")))?;
+
+    Ok(format!("{:016x}_{:016x}", source.org_id, source.bucket_id))
+}
+
+/// Returns true if the series of a read response should use the binary measurement and field
+/// tag keys.
+fn binary_tag_keys(tag_key_meta_names: i32) -> Result<bool, Status> {
+    match TagKeyMetaNames::from_i32(tag_key_meta_names) {
+        Some(TagKeyMetaNames::Text) => Ok(false),
+        Some(TagKeyMetaNames::Binary) => Ok(true),
+        None => Err(Status::invalid_argument(format!(
+            "unknown tag key meta names {tag_key_meta_names}"
+        ))),
+    }
+}
+
+/// Returns the column of the tag `key` of a request, which is either a tag or the special
+/// measurement or field tag key.
+fn tag_key_column(key: &[u8]) -> Result<&str, Status> {
+    match key {
+        MEASUREMENT_TAG_KEY => Ok(MEASUREMENT_COLUMN_NAME),
+        FIELD_TAG_KEY => Ok(FIELD_COLUMN_NAME),
+        _ => std::str::from_utf8(key)
+            .map_err(|e| Status::invalid_argument(format!("tag key is not UTF-8: {e}"))),
+    }
+}
+
+/// Build the predicate of a request from its time `range` and `predicate`, restricted to
+/// `measurement` if one is given.
+fn make_predicate(
+    measurement: Option<String>,
+    range: Option<RpcTimestampRange>,
+    predicate: Option<RpcPredicate>,
+) -> Result<InfluxRpcPredicate, Status> {
+    let mut result = Predicate::new();
+    if let Some(range) = range {
+        result = result.with_range(range.start, range.end);
+    }
+    if let Some(root) = predicate.and_then(|predicate| predicate.root) {
+        result = result.with_expr(node_to_expr(&root)?);
+    }
+
+    Ok(match measurement {
+        Some(measurement) => InfluxRpcPredicate::new_table(measurement, result),
+        None => InfluxRpcPredicate::new(None, result),
+    })
+}
+
+/// Convert a predicate `node` to an expression, which refers to the measurement and field of
+/// a series by the `_measurement` and `_field` columns.
+fn node_to_expr(node: &Node) -> Result<Expr, Status> {
+    let node_type = NodeType::from_i32(node.node_type).ok_or_else(|| {
+        Status::invalid_argument(format!("unknown predicate node type {}", node.node_type))
+    })?;
+
+    match node_type {
+        NodeType::ParenExpression => match node.children.as_slice() {
+            [child] => node_to_expr(child),
+            _ => Err(Status::invalid_argument(
+                "parenthesized expression must have one child",
+            )),
+        },
+        NodeType::LogicalExpression => {
+            let logical = match node.value {
+                Some(NodeValue::Logical(logical)) => Logical::from_i32(logical),
+                _ => None,
+            }
+            .ok_or_else(|| Status::invalid_argument("logical expression without operator"))?;
+            let (first, rest) = node
+                .children
+                .split_first()
+                .ok_or_else(|| Status::invalid_argument("logical expression must have children"))?;
+            rest.iter().try_fold(node_to_expr(first)?, |expr, child| {
+                let child = node_to_expr(child)?;
+                Ok(match logical {
+                    Logical::And => expr.and(child),
+                    Logical::Or => expr.or(child),
+                })
+            })
+        }
+        NodeType::ComparisonExpression => {
+            let comparison = match node.value {
+                Some(NodeValue::Comparison(comparison)) => Comparison::from_i32(comparison),
+                _ => None,
+            }
+            .ok_or_else(|| Status::invalid_argument("comparison without operator"))?;
+            let [left, right] = node.children.as_slice() else {
+                return Err(Status::invalid_argument(
+                    "comparison must have two children",
+                ));
+            };
+            let left = node_to_expr(left)?;
+
+            Ok(match comparison {
+                Comparison::Regex => regex_match_expr(left, regex_pattern(right)?),
+                Comparison::NotRegex => regex_not_match_expr(left, regex_pattern(right)?),
+                Comparison::StartsWith => match &right.value {
+                    Some(NodeValue::StringValue(prefix)) => {
+                        regex_match_expr(left, format!("^{}", regex::escape(prefix)))
+                    }
+                    _ => {
+                        return Err(Status::invalid_argument(
+                            "startsWith requires a string literal",
+                        ))
+                    }
+                },
+                Comparison::Equal => left.eq(node_to_expr(right)?),
+                Comparison::NotEqual => left.not_eq(node_to_expr(right)?),
+                Comparison::Lt => left.lt(node_to_expr(right)?),
+                Comparison::Lte => left.lt_eq(node_to_expr(right)?),
+                Comparison::Gt => left.gt(node_to_expr(right)?),
+                Comparison::Gte => left.gt_eq(node_to_expr(right)?),
+            })
+        }
+        NodeType::TagRef => match &node.value {
+            Some(NodeValue::TagRefValue(key)) => Ok(column(tag_key_column(key)?)),
+            _ => Err(Status::invalid_argument("tag reference without a key")),
+        },
+        NodeType::FieldRef => match &node.value {
+            Some(NodeValue::FieldRefValue(key)) => Ok(column(key)),
+            _ => Err(Status::invalid_argument("field reference without a key")),
+        },
+        NodeType::Literal => match &node.value {
+            Some(NodeValue::StringValue(v)) => Ok(lit(v.as_str())),
+            Some(NodeValue::BoolValue(v)) => Ok(lit(*v)),
+            Some(NodeValue::IntValue(v)) => Ok(lit(*v)),
+            Some(NodeValue::UintValue(v)) => Ok(lit(*v)),
+            Some(NodeValue::FloatValue(v)) => Ok(lit(*v)),
+            Some(NodeValue::RegexValue(_)) => Err(Status::invalid_argument(
+                "regular expression used outside of a regex comparison",
+            )),
+            _ => Err(Status::invalid_argument("literal without a value")),
+        },
+    }
+}
+
+/// A column by its name, which may contain periods or capital letters.
+fn column(name: &str) -> Expr {
+    Expr::Column(Column::from_name(name))
+}
+
+/// Returns the regular expression on the right side of a regex comparison.
+fn regex_pattern(node: &Node) -> Result<String, Status> {
+    match &node.value {
+        Some(NodeValue::RegexValue(pattern)) => Ok(pattern.clone()),
+        _ => Err(Status::invalid_argument(
+            "regex comparison requires a regular expression",
+        )),
+    }
+}
+
+fn make_aggregate(aggregate: Option<&RpcAggregate>) -> Result<Aggregate, Status> {
+    let Some(aggregate) = aggregate else {
+        return Ok(Aggregate::None);
+    };
+    let aggregate_type = AggregateType::from_i32(aggregate.r#type).ok_or_else(|| {
+        Status::invalid_argument(format!("unknown aggregate type {}", aggregate.r#type))
+    })?;
+
+    Ok(match aggregate_type {
+        AggregateType::None => Aggregate::None,
+        AggregateType::Sum => Aggregate::Sum,
+        AggregateType::Count => Aggregate::Count,
+        AggregateType::Min => Aggregate::Min,
+        AggregateType::Max => Aggregate::Max,
+        AggregateType::First => Aggregate::First,
+        AggregateType::Last => Aggregate::Last,
+        AggregateType::Mean => Aggregate::Mean,
+    })
+}
+
+/// Returns the window size and offset of a window aggregate, which are given by `window` or,
+/// from older clients, by `every` and `offset` in nanoseconds.
+fn make_window(
+    every: i64,
+    offset: i64,
+    window: Option<Window>,
+) -> Result<(WindowDuration, WindowDuration), Status> {
+    let (every, offset) = match window {
+        Some(window) => (make_duration(window.every)?, make_duration(window.offset)?),
+        None => (
+            WindowDuration::from_nanoseconds(every),
+            WindowDuration::from_nanoseconds(offset),
+        ),
+    };
+    if every == WindowDuration::empty() {
+        return Err(Status::invalid_argument("window size must not be zero"));
+    }
+
+    Ok((every, offset))
+}
+
+fn make_duration(duration: Option<RpcDuration>) -> Result<WindowDuration, Status> {
+    let Some(duration) = duration else {
+        return Ok(WindowDuration::empty());
+    };
+    match (duration.months, duration.nsecs) {
+        (0, nsecs) if duration.negative => Ok(WindowDuration::from_nanoseconds(-nsecs)),
+        (0, nsecs) => Ok(WindowDuration::from_nanoseconds(nsecs)),
+        (months, 0) => Ok(WindowDuration::from_months(months, duration.negative)),
+        _ => Err(Status::invalid_argument(
+            "durations of both months and nanoseconds are not supported",
+        )),
+    }
+}
+
+/// Convert a stream of series and groups to read responses. The measurement and field of each
+/// series are given by the `_measurement` and `_field` tag keys, or by the binary tag keys if
+/// `binary_tag_keys` is set.
+///
+/// Each response holds as many frames as fit in [`MAX_RESPONSE_BYTES`], except that a larger
+/// frame is sent on its own, and is sent as soon as it is full rather than once every series has
+/// been read.
+fn read_responses(
+    series: BoxStream<'static, Result<Either, Status>>,
+    binary_tag_keys: bool,
+) -> impl Stream<Item = Result<ReadResponse, Status>> + Send + 'static {
+    let frames = series
+        .map_ok(move |series_or_group| {
+            let mut frames = Vec::new();
+            match series_or_group {
+                Either::Series(series) => series_frames(series, binary_tag_keys, &mut frames),
+                Either::Group(group) => frames.push(group_frame(group)),
+            }
+            futures::stream::iter(frames.into_iter().map(Ok))
+        })
+        .try_flatten()
+        .boxed();
+
+    // The state is the frames still to be read, and a frame that didn't fit in the previous
+    // response, or `None` once the last response has been sent.
+    futures::stream::unfold(Some((frames, None)), |state| async move {
+        let (mut frames, carried): (BoxStream<'static, Result<Frame, Status>>, Option<Frame>) =
+            state?;
+        let mut response = ReadResponse {
+            frames: carried.into_iter().collect(),
+        };
+        let mut size: usize = response.frames.iter().map(Message::encoded_len).sum();
+        loop {
+            match frames.next().await {
+                Some(Ok(frame)) => {
+                    let frame_size = frame.encoded_len();
+                    if !response.frames.is_empty() && size + frame_size > MAX_RESPONSE_BYTES {
+                        return Some((Ok(response), Some((frames, Some(frame)))));
+                    }
+                    size += frame_size;
+                    response.frames.push(frame);
+                }
+                Some(Err(e)) => return Some((Err(e), None)),
+                None if response.frames.is_empty() => return None,
+                None => return Some((Ok(response), None)),
+            }
+        }
+    })
+}
+
+/// Add the frame of a series, followed by the frames of its points, to `frames`.
+fn series_frames(series: Series, binary_tag_keys: bool, frames: &mut Vec<Frame>) {
+    let mut tags: Vec<_> = series
+        .tags
+        .iter()
+        .map(|tag| {
+            let key = match (tag.key.as_ref(), binary_tag_keys) {
+                (MEASUREMENT_COLUMN_NAME, true) => MEASUREMENT_TAG_KEY.to_vec(),
+                (FIELD_COLUMN_NAME, true) => FIELD_TAG_KEY.to_vec(),
+                (key, _) => key.as_bytes().to_vec(),
+            };
+            RpcTag {
+                key,
+                value: tag.value.as_bytes().to_vec(),
+            }
+        })
+        .collect();
+    // The binary tag keys sort first and last, as in the 2.x storage engine
+    tags.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let (data_type, points): (_, Vec<_>) = match series.data {
+        Data::FloatPoints(batches) => (
+            FrameDataType::Float,
+            batches
+                .into_iter()
+                .map(|b| {
+                    FrameData::FloatPoints(FloatPointsFrame {
+                        timestamps: b.timestamps,
+                        values: b.values,
+                    })
+                })
+                .collect(),
+        ),
+        Data::IntegerPoints(batches) => (
+            FrameDataType::Integer,
+            batches
+                .into_iter()
+                .map(|b| {
+                    FrameData::IntegerPoints(IntegerPointsFrame {
+                        timestamps: b.timestamps,
+                        values: b.values,
+                    })
+                })
+                .collect(),
+        ),
+        Data::UnsignedPoints(batches) => (
+            FrameDataType::Unsigned,
+            batches
+                .into_iter()
+                .map(|b| {
+                    FrameData::UnsignedPoints(UnsignedPointsFrame {
+                        timestamps: b.timestamps,
+                        values: b.values,
+                    })
+                })
+                .collect(),
+        ),
+        Data::BooleanPoints(batches) => (
+            FrameDataType::Boolean,
+            batches
+                .into_iter()
+                .map(|b| {
+                    FrameData::BooleanPoints(BooleanPointsFrame {
+                        timestamps: b.timestamps,
+                        values: b.values,
+                    })
+                })
+                .collect(),
+        ),
+        Data::StringPoints(batches) => (
+            FrameDataType::String,
+            batches
+                .into_iter()
+                .map(|b| {
+                    FrameData::StringPoints(StringPointsFrame {
+                        timestamps: b.timestamps,
+                        values: b.values,
+                    })
+                })
+                .collect(),
+        ),
+    };
+
+    frames.push(Frame {
+        data: Some(FrameData::Series(SeriesFrame {
+            tags,
+            data_type: data_type as i32,
+        })),
+    });
+    frames.extend(points.into_iter().map(|data| Frame { data: Some(data) }));
+}
+
+fn group_frame(group: Group) -> Frame {
+    Frame {
+        data: Some(FrameData::Group(GroupFrame {
+            tag_keys: group
+                .tag_keys
+                .iter()
+                .map(|key| key.as_bytes().to_vec())
+                .collect(),
+            partition_key_vals: group
+                .partition_key_vals
+                .iter()
+                .map(|value| value.as_bytes().to_vec())
+                .collect(),
+        })),
+    }
+}
+
+fn string_values_responses(values: impl IntoIterator<Item = Vec<u8>>) -> Vec<StringValuesResponse> {
+    split_by_size(values, Vec::len)
+        .into_iter()
+        .map(|values| StringValuesResponse { values })
+        .collect()
+}
+
+fn measurement_fields_response(fields: FieldList) -> MeasurementFieldsResponse {
+    let fields = fields
+        .fields
+        .into_iter()
+        .map(|field| {
+            let field_type = match field.data_type {
+                DataType::Float64 => FieldType::Float,
+                DataType::Int64 => FieldType::Integer,
+                DataType::UInt64 => FieldType::Unsigned,
+                DataType::Utf8 => FieldType::String,
+                DataType::Boolean => FieldType::Boolean,
+                _ => FieldType::Undefined,
+            };
+            MessageField {
+                key: field.name,
+                r#type: field_type as i32,
+                timestamp: field.last_timestamp,
+            }
+        })
+        .collect();
+
+    MeasurementFieldsResponse { fields }
+}
+
+/// Split `items` into consecutive runs of at most [`MAX_RESPONSE_BYTES`], going by `size`,
+/// except that an item larger than that is sent on its own.
+fn split_by_size<T>(items: impl IntoIterator<Item = T>, size: impl Fn(&T) -> usize) -> Vec<Vec<T>> {
+    let mut runs = Vec::new();
+    let mut run = Vec::new();
+    let mut run_size = 0;
+    for item in items {
+        let item_size = size(&item);
+        if !run.is_empty() && run_size + item_size > MAX_RESPONSE_BYTES {
+            runs.push(std::mem::take(&mut run));
+            run_size = 0;
+        }
+        run_size += item_size;
+        run.push(item);
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_query::exec::seriesset::series::{Batch, Tag};
+    use tracker::AsyncSemaphoreMetrics;
+
+    use super::*;
+
+    fn tag_ref(key: &[u8]) -> Node {
+        Node {
+            node_type: NodeType::TagRef as i32,
+            children: vec![],
+            value: Some(NodeValue::TagRefValue(key.to_vec())),
+        }
+    }
+
+    fn literal(value: NodeValue) -> Node {
+        Node {
+            node_type: NodeType::Literal as i32,
+            children: vec![],
+            value: Some(value),
+        }
+    }
+
+    fn comparison(comparison: Comparison, left: Node, right: Node) -> Node {
+        Node {
+            node_type: NodeType::ComparisonExpression as i32,
+            children: vec![left, right],
+            value: Some(NodeValue::Comparison(comparison as i32)),
+        }
+    }
+
+    fn logical(logical: Logical, children: Vec<Node>) -> Node {
+        Node {
+            node_type: NodeType::LogicalExpression as i32,
+            children,
+            value: Some(NodeValue::Logical(logical as i32)),
+        }
+    }
+
+    fn string(value: &str) -> NodeValue {
+        NodeValue::StringValue(value.to_string())
+    }
+
+    #[test]
+    fn predicate_to_expr() {
+        let node = logical(
+            Logical::And,
+            vec![
+                comparison(Comparison::Equal, tag_ref(&[0]), literal(string("cpu"))),
+                comparison(
+                    Comparison::Equal,
+                    tag_ref(b"_field"),
+                    literal(string("usage")),
+                ),
+                Node {
+                    node_type: NodeType::ParenExpression as i32,
+                    children: vec![logical(
+                        Logical::Or,
+                        vec![
+                            comparison(
+                                Comparison::NotEqual,
+                                tag_ref(b"host.name"),
+                                literal(string("a")),
+                            ),
+                            comparison(
+                                Comparison::Gt,
+                                Node {
+                                    node_type: NodeType::FieldRef as i32,
+                                    children: vec![],
+                                    value: Some(NodeValue::FieldRefValue("_value".to_string())),
+                                },
+                                literal(NodeValue::FloatValue(1.5)),
+                            ),
+                        ],
+                    )],
+                    value: None,
+                },
+            ],
+        );
+
+        let expected = column("_measurement")
+            .eq(lit("cpu"))
+            .and(column("_field").eq(lit("usage")))
+            .and(
+                column("host.name")
+                    .not_eq(lit("a"))
+                    .or(column("_value").gt(lit(1.5))),
+            );
+        assert_eq!(node_to_expr(&node).unwrap(), expected);
+    }
+
+    #[test]
+    fn regex_predicate_to_expr() {
+        let node = comparison(
+            Comparison::Regex,
+            tag_ref(&[255]),
+            literal(NodeValue::RegexValue("^us".to_string())),
+        );
+        assert_eq!(
+            node_to_expr(&node).unwrap(),
+            regex_match_expr(column("_field"), "^us".to_string())
+        );
+
+        let node = comparison(
+            Comparison::StartsWith,
+            tag_ref(b"host"),
+            literal(string("a.b")),
+        );
+        assert_eq!(
+            node_to_expr(&node).unwrap(),
+            regex_match_expr(column("host"), r"^a\.b".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_predicate() {
+        let nodes = [
+            // a regex comparison with a string
+            comparison(Comparison::Regex, tag_ref(b"host"), literal(string("a"))),
+            // a comparison with one side
+            Node {
+                node_type: NodeType::ComparisonExpression as i32,
+                children: vec![tag_ref(b"host")],
+                value: Some(NodeValue::Comparison(Comparison::Equal as i32)),
+            },
+            // a logical expression without children
+            logical(Logical::And, vec![]),
+            // an unknown node type
+            Node {
+                node_type: 100,
+                children: vec![],
+                value: None,
+            },
+        ];
+        for node in nodes {
+            let status = node_to_expr(&node).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{node:?}");
+        }
+    }
+
+    #[test]
+    fn database_from_read_source() {
+        let source = ReadSource {
+            org_id: 0x1234,
+            bucket_id: 0xabcd,
+            partition_id: 0,
+        };
+        let source = Any {
+            type_url: format!("type.googleapis.com/{READ_SOURCE_TYPE}"),
+            value: source.encode_to_vec().into(),
+        };
+        assert_eq!(
+            database_name(&MetadataMap::new(), Some(&source)).unwrap(),
+            "0000000000001234_000000000000abcd"
+        );
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert(DATABASE_METADATA_KEY, "foo".parse().unwrap());
+        assert_eq!(database_name(&metadata, Some(&source)).unwrap(), "foo");
+
+        let status = database_name(&MetadataMap::new(), None).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn window() {
+        let duration = |nsecs, months, negative| {
+            Some(RpcDuration {
+                nsecs,
+                months,
+                negative,
+            })
+        };
+
+        assert_eq!(
+            make_window(10, 2, None).unwrap(),
+            (
+                WindowDuration::from_nanoseconds(10),
+                WindowDuration::from_nanoseconds(2)
+            )
+        );
+        let window = Window {
+            every: duration(0, 1, false),
+            offset: duration(5, 0, true),
+        };
+        assert_eq!(
+            make_window(10, 2, Some(window)).unwrap(),
+            (
+                WindowDuration::from_months(1, false),
+                WindowDuration::from_nanoseconds(-5)
+            )
+        );
+
+        assert!(make_window(0, 0, None).is_err());
+        let window = Window {
+            every: duration(5, 1, false),
+            offset: None,
+        };
+        assert!(make_window(0, 0, Some(window)).is_err());
+    }
+
+    /// Collect the read responses to `series`.
+    async fn collect_read_responses(
+        series: Vec<Either>,
+        binary_tag_keys: bool,
+    ) -> Vec<ReadResponse> {
+        let series = futures::stream::iter(series.into_iter().map(Ok)).boxed();
+        read_responses(series, binary_tag_keys)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn series_to_frames() {
+        let series = Series {
+            tags: vec![
+                Tag {
+                    key: "_field".into(),
+                    value: "usage".into(),
+                },
+                Tag {
+                    key: "_measurement".into(),
+                    value: "cpu".into(),
+                },
+                Tag {
+                    key: "host".into(),
+                    value: "a".into(),
+                },
+            ],
+            data: Data::FloatPoints(vec![
+                Batch {
+                    timestamps: vec![1, 2],
+                    values: vec![1.0, 2.0],
+                },
+                Batch {
+                    timestamps: vec![3],
+                    values: vec![3.0],
+                },
+            ]),
+        };
+        let group = Group {
+            tag_keys: vec!["_field".into(), "host".into()],
+            partition_key_vals: vec!["a".into()],
+        };
+
+        let responses = collect_read_responses(
+            vec![Either::Group(group), Either::Series(series.clone())],
+            false,
+        )
+        .await;
+        assert_eq!(responses.len(), 1);
+        let frames: Vec<_> = responses[0]
+            .frames
+            .iter()
+            .map(|frame| frame.data.clone().unwrap())
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                FrameData::Group(GroupFrame {
+                    tag_keys: vec![b"_field".to_vec(), b"host".to_vec()],
+                    partition_key_vals: vec![b"a".to_vec()],
+                }),
+                FrameData::Series(SeriesFrame {
+                    tags: vec![
+                        RpcTag {
+                            key: b"_field".to_vec(),
+                            value: b"usage".to_vec()
+                        },
+                        RpcTag {
+                            key: b"_measurement".to_vec(),
+                            value: b"cpu".to_vec()
+                        },
+                        RpcTag {
+                            key: b"host".to_vec(),
+                            value: b"a".to_vec()
+                        },
+                    ],
+                    data_type: FrameDataType::Float as i32,
+                }),
+                FrameData::FloatPoints(FloatPointsFrame {
+                    timestamps: vec![1, 2],
+                    values: vec![1.0, 2.0],
+                }),
+                FrameData::FloatPoints(FloatPointsFrame {
+                    timestamps: vec![3],
+                    values: vec![3.0],
+                }),
+            ]
+        );
+
+        // With binary tag keys, the measurement comes first and the field last
+        let responses = collect_read_responses(vec![Either::Series(series)], true).await;
+        let Some(FrameData::Series(frame)) = &responses[0].frames[0].data else {
+            panic!("expected a series frame: {responses:?}");
+        };
+        let keys: Vec<_> = frame.tags.iter().map(|tag| tag.key.clone()).collect();
+        assert_eq!(keys, vec![vec![0], b"host".to_vec(), vec![255]]);
+    }
+
+    #[tokio::test]
+    async fn read_responses_are_streamed() {
+        let make_series = |host: &str| {
+            Either::Series(Series {
+                tags: vec![Tag {
+                    key: "host".into(),
+                    value: host.into(),
+                }],
+                data: Data::FloatPoints(vec![Batch {
+                    timestamps: (0..70_000).collect(),
+                    values: vec![1.0; 70_000],
+                }]),
+            })
+        };
+
+        // The first response is sent once the next frame doesn't fit, without waiting for
+        // the end of the series, which never comes.
+        let series = futures::stream::iter([Ok(make_series("a")), Ok(make_series("b"))])
+            .chain(futures::stream::pending())
+            .boxed();
+        let mut responses = read_responses(series, false).boxed();
+        let response = responses.next().await.unwrap().unwrap();
+        assert_eq!(response.frames.len(), 3);
+
+        // An error ends the responses
+        let series =
+            futures::stream::iter([Ok(make_series("a")), Err(Status::internal("boom"))]).boxed();
+        let responses: Vec<_> = read_responses(series, false).collect().await;
+        assert_eq!(responses.len(), 1);
+        assert_eq!(
+            responses[0].as_ref().unwrap_err().code(),
+            tonic::Code::Internal
+        );
+    }
+
+    #[tokio::test]
+    async fn response_stream_records_success_at_end() {
+        let semaphore = Arc::new(AsyncSemaphoreMetrics::new_unregistered()).new_semaphore(1);
+        let semaphore = Arc::new(semaphore);
+        let success = Arc::new(std::sync::Mutex::new(None));
+
+        let stream = |permit| {
+            let success = Arc::clone(&success);
+            let completed = QueryCompletedToken::new(move |s| *success.lock().unwrap() = Some(s));
+            ResponseStream {
+                inner: futures::stream::iter([Ok(1), Ok(2)]).boxed(),
+                completed,
+                _permit: permit,
+                done: false,
+            }
+        };
+
+        // A query whose responses aren't all sent didn't succeed, and its permit is released
+        // when they are dropped
+        let mut responses = stream(semaphore.acquire_owned(None).await.unwrap());
+        assert_eq!(responses.next().await.unwrap().unwrap(), 1);
+        assert_eq!(*success.lock().unwrap(), None);
+        drop(responses);
+        assert_eq!(*success.lock().unwrap(), Some(false));
+
+        let responses = stream(semaphore.acquire_owned(None).await.unwrap());
+        let values: Vec<i32> = responses.try_collect().await.unwrap();
+        assert_eq!(values, vec![1, 2]);
+        assert_eq!(*success.lock().unwrap(), Some(true));
+    }
+
+    #[test]
+    fn split_responses() {
+        let values = vec![vec![0; MAX_RESPONSE_BYTES / 2]; 3];
+        let responses = string_values_responses(values);
+        let lengths: Vec<_> = responses.iter().map(|r| r.values.len()).collect();
+        assert_eq!(lengths, vec![2, 1]);
+
+        let values = vec![vec![0; MAX_RESPONSE_BYTES + 1], vec![0; 1]];
+        let lengths: Vec<_> = string_values_responses(values)
+            .iter()
+            .map(|r| r.values.len())
+            .collect();
+        assert_eq!(lengths, vec![1, 1]);
+
+        assert!(string_values_responses(vec![]).is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+//! TLS termination for the server's listeners.
+//!
+//! Certificates are loaded from PEM files on disk and can be reloaded without restarting the
+//! server, so that rotated certificates are picked up on `SIGHUP`. Each new connection is
+//! accepted with whichever configuration was most recently loaded successfully.
+
+use observability_deps::tracing::{error, info};
+use parking_lot::RwLock;
+use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc};
+use thiserror::Error;
+use tokio_rustls::{
+    rustls::{
+        server::AllowAnyAuthenticatedClient, Certificate, PrivateKey, RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("error reading {path:?}: {source}")]
+    ReadFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("no certificates found in {path:?}")]
+    NoCertificates { path: PathBuf },
+
+    #[error("no private key found in {path:?}")]
+    NoPrivateKey { path: PathBuf },
+
+    #[error("invalid client CA certificate in {path:?}: {source}")]
+    InvalidClientCa {
+        path: PathBuf,
+        source: tokio_rustls::rustls::Error,
+    },
+
+    #[error("invalid certificate or private key: {0}")]
+    InvalidCertificate(#[from] tokio_rustls::rustls::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Locations of the PEM files used to configure TLS.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// The server certificate chain
+    pub cert_path: PathBuf,
+    /// The private key for the server certificate
+    pub key_path: PathBuf,
+    /// If set, clients must present a certificate signed by one of the CAs in this file
+    pub client_ca_path: Option<PathBuf>,
+}
+
+/// Accepts TLS connections using a [`ServerConfig`] that can be reloaded from disk.
+#[derive(Debug)]
+pub(crate) struct ReloadableTlsAcceptor {
+    config: TlsConfig,
+    server_config: RwLock<Arc<ServerConfig>>,
+}
+
+impl ReloadableTlsAcceptor {
+    /// Loads the certificates described by `config`, failing if they are missing or invalid.
+    pub(crate) fn new(config: TlsConfig) -> Result<Self> {
+        let server_config = RwLock::new(Arc::new(load_server_config(&config)?));
+        Ok(Self {
+            config,
+            server_config,
+        })
+    }
+
+    /// Reloads the certificates from disk. On error the previously loaded certificates stay in
+    /// use.
+    pub(crate) fn reload(&self) -> Result<()> {
+        let server_config = load_server_config(&self.config)?;
+        *self.server_config.write() = Arc::new(server_config);
+        Ok(())
+    }
+
+    /// Returns an acceptor for the most recently loaded certificates.
+    pub(crate) fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(Arc::clone(&self.server_config.read()))
+    }
+}
+
+/// Reloads the certificates of `acceptor` every time the process receives `SIGHUP`, until
+/// `shutdown` is cancelled.
+#[cfg(unix)]
+pub(crate) async fn reload_on_sighup(
+    acceptor: Arc<ReloadableTlsAcceptor>,
+    shutdown: CancellationToken,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangup = signal(SignalKind::hangup()).expect("failed to register signal handler");
+
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {
+                info!("Received SIGHUP, reloading TLS certificates");
+                match acceptor.reload() {
+                    Ok(()) => info!(
+                        cert_path=?acceptor.config.cert_path,
+                        "reloaded TLS certificates"
+                    ),
+                    Err(error) => error!(
+                        %error,
+                        "failed to reload TLS certificates, continuing with previous certificates"
+                    ),
+                }
+            }
+            _ = shutdown.cancelled() => return,
+        }
+    }
+}
+
+#[cfg(windows)]
+pub(crate) async fn reload_on_sighup(
+    _acceptor: Arc<ReloadableTlsAcceptor>,
+    _shutdown: CancellationToken,
+) {
+}
+
+fn load_server_config(config: &TlsConfig) -> Result<ServerConfig> {
+    let cert_chain = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = match &config.client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(client_ca_path)? {
+                roots.add(&cert).map_err(|source| Error::InvalidClientCa {
+                    path: client_ca_path.clone(),
+                    source,
+                })?;
+            }
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder.with_single_cert(cert_chain, key)?;
+    // Offer HTTP/2 so that gRPC clients can negotiate it over the same listener
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(server_config)
+}
+
+fn load_certs(path: &PathBuf) -> Result<Vec<Certificate>> {
+    let mut reader = open(path)?;
+    let certs = rustls_pemfile::certs(&mut reader).map_err(|source| Error::ReadFile {
+        path: path.clone(),
+        source,
+    })?;
+    if certs.is_empty() {
+        return Err(Error::NoCertificates { path: path.clone() });
+    }
+
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_private_key(path: &PathBuf) -> Result<PrivateKey> {
+    let mut reader = open(path)?;
+    let items = rustls_pemfile::read_all(&mut reader).map_err(|source| Error::ReadFile {
+        path: path.clone(),
+        source,
+    })?;
+
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| Error::NoPrivateKey { path: path.clone() })
+}
+
+fn open(path: &PathBuf) -> Result<BufReader<File>> {
+    let file = File::open(path).map_err(|source| Error::ReadFile {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../test_fixtures/tls")
+            .join(name)
+    }
+
+    #[test]
+    fn load_certificates() {
+        let config = TlsConfig {
+            cert_path: fixture("server.pem"),
+            key_path: fixture("server.key"),
+            client_ca_path: Some(fixture("ca.pem")),
+        };
+        ReloadableTlsAcceptor::new(config).unwrap();
+    }
+
+    #[test]
+    fn missing_private_key() {
+        let config = TlsConfig {
+            cert_path: fixture("server.pem"),
+            key_path: fixture("server.pem"),
+            client_ca_path: None,
+        };
+        assert!(matches!(
+            ReloadableTlsAcceptor::new(config),
+            Err(Error::NoPrivateKey { .. })
+        ));
+    }
+
+    #[test]
+    fn reload_keeps_previous_certificates_on_error() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::copy(fixture("server.pem"), &cert_path).unwrap();
+        std::fs::copy(fixture("server.key"), &key_path).unwrap();
+
+        let acceptor = ReloadableTlsAcceptor::new(TlsConfig {
+            cert_path: cert_path.clone(),
+            key_path: key_path.clone(),
+            client_ca_path: None,
+        })
+        .unwrap();
+        let original = Arc::clone(&acceptor.server_config.read());
+
+        // a broken certificate is rejected and the original configuration stays in place
+        std::fs::write(&cert_path, "not a certificate").unwrap();
+        assert!(acceptor.reload().is_err());
+        assert!(Arc::ptr_eq(&original, &acceptor.server_config.read()));
+
+        // a rotated certificate is picked up
+        std::fs::copy(fixture("server2.pem"), &cert_path).unwrap();
+        std::fs::copy(fixture("server2.key"), &key_path).unwrap();
+        acceptor.reload().unwrap();
+        assert!(!Arc::ptr_eq(&original, &acceptor.server_config.read()));
+    }
+}
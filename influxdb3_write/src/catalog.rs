@@ -15,6 +15,18 @@ use thiserror::Error;
 pub enum Error {
     #[error("catalog updated elsewhere")]
     CatalogUpdatedElsewhere,
+
+    #[error("database {db_name} already exists")]
+    DatabaseAlreadyExists { db_name: String },
+
+    #[error("database {db_name} not found")]
+    DatabaseNotFound { db_name: String },
+
+    #[error("continuous query {name} already exists in database {db_name}")]
+    ContinuousQueryAlreadyExists { db_name: String, name: String },
+
+    #[error("continuous query {name} not found in database {db_name}")]
+    ContinuousQueryNotFound { db_name: String, name: String },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -37,6 +49,22 @@ impl Catalog {
         }
     }
 
+    pub fn from_inner(inner: InnerCatalog) -> Self {
+        Self {
+            inner: RwLock::new(inner),
+        }
+    }
+
+    /// Returns a copy of the current state of the catalog, e.g. to persist it while writes
+    /// continue against this one.
+    pub fn clone_inner(&self) -> InnerCatalog {
+        self.inner.read().clone()
+    }
+
+    pub fn into_inner(self) -> InnerCatalog {
+        self.inner.into_inner()
+    }
+
     pub(crate) fn replace_database(&self, sequence: u64, db: Arc<DatabaseSchema>) -> Result<()> {
         let mut inner = self.inner.write();
         if inner.sequence != sequence {
@@ -75,17 +103,143 @@ impl Catalog {
         (sequence, db)
     }
 
+    /// Creates a new, empty database. Returns an error if a database with that name already
+    /// exists.
+    pub fn create_database(&self, db_name: &str) -> Result<Arc<DatabaseSchema>> {
+        let mut inner = self.inner.write();
+        if inner.databases.contains_key(db_name) {
+            return Err(Error::DatabaseAlreadyExists {
+                db_name: db_name.to_string(),
+            });
+        }
+
+        info!("create db {}", db_name);
+        let db = Arc::new(DatabaseSchema::new(db_name));
+        inner.sequence += 1;
+        inner.databases.insert(db.name.clone(), Arc::clone(&db));
+
+        Ok(db)
+    }
+
+    /// Removes the database and all of its table definitions from the catalog.
+    pub fn delete_database(&self, db_name: &str) -> Result<Arc<DatabaseSchema>> {
+        let mut inner = self.inner.write();
+        let db = inner
+            .databases
+            .remove(db_name)
+            .ok_or_else(|| Error::DatabaseNotFound {
+                db_name: db_name.to_string(),
+            })?;
+
+        info!("deleted db {}", db_name);
+        inner.sequence += 1;
+        inner.continuous_queries.remove(db_name);
+
+        Ok(db)
+    }
+
     pub fn db_schema(&self, name: &str) -> Option<Arc<DatabaseSchema>> {
         info!("db_schema {}", name);
         self.inner.read().databases.get(name).cloned()
     }
+
+    /// Returns the names of all databases in the catalog, sorted.
+    pub fn db_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.inner.read().databases.keys().cloned().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Adds a continuous query to its database. Returns an error if the database does not
+    /// exist or already has a continuous query with the same name.
+    pub fn create_continuous_query(&self, cq: ContinuousQueryDefinition) -> Result<()> {
+        let mut inner = self.inner.write();
+        if !inner.databases.contains_key(&cq.database) {
+            return Err(Error::DatabaseNotFound {
+                db_name: cq.database,
+            });
+        }
+
+        let queries = inner
+            .continuous_queries
+            .entry(cq.database.clone())
+            .or_default();
+        if queries.contains_key(&cq.name) {
+            return Err(Error::ContinuousQueryAlreadyExists {
+                db_name: cq.database,
+                name: cq.name,
+            });
+        }
+
+        info!("create continuous query {} in db {}", cq.name, cq.database);
+        queries.insert(cq.name.clone(), cq);
+
+        Ok(())
+    }
+
+    /// Removes a continuous query from its database.
+    pub fn drop_continuous_query(
+        &self,
+        db_name: &str,
+        name: &str,
+    ) -> Result<ContinuousQueryDefinition> {
+        let mut inner = self.inner.write();
+        let cq = inner
+            .continuous_queries
+            .get_mut(db_name)
+            .and_then(|queries| queries.remove(name))
+            .ok_or_else(|| Error::ContinuousQueryNotFound {
+                db_name: db_name.to_string(),
+                name: name.to_string(),
+            })?;
+
+        info!("dropped continuous query {} in db {}", name, db_name);
+
+        Ok(cq)
+    }
+
+    /// Returns all continuous queries, sorted by database and name.
+    pub fn continuous_queries(&self) -> Vec<ContinuousQueryDefinition> {
+        self.inner
+            .read()
+            .continuous_queries
+            .values()
+            .flat_map(|queries| queries.values().cloned())
+            .collect()
+    }
+
+    /// Records the time of the last run of the continuous query.
+    pub fn set_continuous_query_last_run(
+        &self,
+        db_name: &str,
+        name: &str,
+        run_ns: i64,
+    ) -> Result<()> {
+        let mut inner = self.inner.write();
+        let cq = inner
+            .continuous_queries
+            .get_mut(db_name)
+            .and_then(|queries| queries.get_mut(name))
+            .ok_or_else(|| Error::ContinuousQueryNotFound {
+                db_name: db_name.to_string(),
+                name: name.to_string(),
+            })?;
+        cq.last_run_ns = Some(run_ns);
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct InnerCatalog {
     /// The catalog is a map of databases with their table schemas
     databases: HashMap<String, Arc<DatabaseSchema>>,
     sequence: u64,
+    /// The continuous queries of each database, by name. These are kept apart from the
+    /// [`DatabaseSchema`] so that updating their state does not conflict with schema changes
+    /// made by concurrent writes.
+    #[serde(default)]
+    continuous_queries: BTreeMap<String, BTreeMap<String, ContinuousQueryDefinition>>,
 }
 
 impl InnerCatalog {
@@ -93,10 +247,29 @@ impl InnerCatalog {
         Self {
             databases: HashMap::new(),
             sequence: 0,
+            continuous_queries: BTreeMap::new(),
         }
     }
 }
 
+/// A continuous query, which periodically runs a `SELECT ... INTO` statement over the most
+/// recent complete intervals of its `GROUP BY time()` clause.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ContinuousQueryDefinition {
+    pub name: String,
+    pub database: String,
+    /// The InfluxQL `CREATE CONTINUOUS QUERY` statement that defined the query.
+    pub query: String,
+    /// The interval of the `GROUP BY time()` clause, in nanoseconds.
+    pub interval_ns: i64,
+    /// How often the query runs, in nanoseconds.
+    pub every_ns: i64,
+    /// The time range covered by each run, in nanoseconds.
+    pub for_ns: i64,
+    /// The time of the last run, aligned to `every_ns`, if the query has run.
+    pub last_run_ns: Option<i64>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct DatabaseSchema {
     pub name: String,
@@ -204,18 +377,17 @@ impl TableDefinition {
         self.columns.contains_key(column)
     }
 
-    pub(crate) fn add_columns(&mut self, mut columns: Vec<(String, ColumnType)>) {
-        let mut schema_builder = SchemaBuilder::with_capacity(columns.len());
-        columns.sort_by(|(a, _), (b, _)| a.cmp(b));
-        for (name, column_type) in &columns {
-            schema_builder.influx_column(name, column_type_to_influx_column_type(column_type));
-        }
-        let schema = schema_builder.build().unwrap();
-
+    pub(crate) fn add_columns(&mut self, columns: Vec<(String, ColumnType)>) {
         for (name, column_type) in columns.into_iter() {
             self.columns.insert(name, column_type);
         }
-        self.schema = Some(schema);
+
+        // rebuild the schema so that it includes both the existing and the new columns
+        let mut schema_builder = SchemaBuilder::with_capacity(self.columns.len());
+        for (name, column_type) in &self.columns {
+            schema_builder.influx_column(name, column_type_to_influx_column_type(column_type));
+        }
+        self.schema = Some(schema_builder.build().unwrap());
     }
 
     pub(crate) fn columns(&self) -> &BTreeMap<String, ColumnType> {
@@ -262,4 +434,98 @@ mod tests {
 
         assert_eq!(*inner, deserialized);
     }
+
+    #[test]
+    fn create_and_delete_database() {
+        let catalog = Catalog::new();
+
+        catalog.create_database("foo").unwrap();
+        catalog.create_database("bar").unwrap();
+        assert!(matches!(
+            catalog.create_database("foo"),
+            Err(Error::DatabaseAlreadyExists { .. })
+        ));
+        assert_eq!(
+            catalog.db_names(),
+            vec!["bar".to_string(), "foo".to_string()]
+        );
+
+        catalog.delete_database("foo").unwrap();
+        assert!(catalog.db_schema("foo").is_none());
+        assert!(matches!(
+            catalog.delete_database("foo"),
+            Err(Error::DatabaseNotFound { .. })
+        ));
+        assert_eq!(catalog.db_names(), vec!["bar".to_string()]);
+    }
+
+    fn continuous_query(database: &str, name: &str) -> ContinuousQueryDefinition {
+        ContinuousQueryDefinition {
+            name: name.to_string(),
+            database: database.to_string(),
+            query: format!(
+                "CREATE CONTINUOUS QUERY {name} ON {database} BEGIN SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY TIME(60m) END"
+            ),
+            interval_ns: 3_600_000_000_000,
+            every_ns: 3_600_000_000_000,
+            for_ns: 3_600_000_000_000,
+            last_run_ns: None,
+        }
+    }
+
+    #[test]
+    fn create_and_drop_continuous_query() {
+        let catalog = Catalog::new();
+
+        assert!(matches!(
+            catalog.create_continuous_query(continuous_query("foo", "cq")),
+            Err(Error::DatabaseNotFound { .. })
+        ));
+
+        catalog.create_database("foo").unwrap();
+        catalog
+            .create_continuous_query(continuous_query("foo", "cq"))
+            .unwrap();
+        assert!(matches!(
+            catalog.create_continuous_query(continuous_query("foo", "cq")),
+            Err(Error::ContinuousQueryAlreadyExists { .. })
+        ));
+
+        catalog
+            .set_continuous_query_last_run("foo", "cq", 42)
+            .unwrap();
+        assert_eq!(
+            catalog.continuous_queries(),
+            vec![ContinuousQueryDefinition {
+                last_run_ns: Some(42),
+                ..continuous_query("foo", "cq")
+            }]
+        );
+
+        // the state of continuous queries survives persisting the catalog
+        let serialized = serde_json::to_string(&catalog.clone_inner()).unwrap();
+        let deserialized: InnerCatalog = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            Catalog::from_inner(deserialized).continuous_queries(),
+            catalog.continuous_queries()
+        );
+
+        catalog.drop_continuous_query("foo", "cq").unwrap();
+        assert!(catalog.continuous_queries().is_empty());
+        assert!(matches!(
+            catalog.drop_continuous_query("foo", "cq"),
+            Err(Error::ContinuousQueryNotFound { .. })
+        ));
+        assert!(matches!(
+            catalog.set_continuous_query_last_run("foo", "cq", 42),
+            Err(Error::ContinuousQueryNotFound { .. })
+        ));
+
+        // deleting a database removes its continuous queries
+        catalog
+            .create_continuous_query(continuous_query("foo", "cq"))
+            .unwrap();
+        catalog.delete_database("foo").unwrap();
+        assert!(catalog.continuous_queries().is_empty());
+    }
 }
@@ -11,7 +11,8 @@ pub mod persister;
 pub mod wal;
 pub mod write_buffer;
 
-use crate::catalog::Catalog;
+use crate::catalog::{Catalog, ContinuousQueryDefinition};
+use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
 use data_types::NamespaceName;
 use datafusion::error::DataFusionError;
@@ -35,6 +36,15 @@ pub enum Error {
 
     #[error("write buffer error: {0}")]
     WriteBuffer(#[from] write_buffer::Error),
+
+    #[error("catalog error: {0}")]
+    Catalog(#[from] catalog::Error),
+
+    #[error("persister error: {0}")]
+    Persister(#[from] persister::Error),
+
+    #[error("wal error: {0}")]
+    Wal(#[from] wal::Error),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -61,6 +71,55 @@ pub trait Bufferer: Debug + Send + Sync + 'static {
         default_time: i64,
     ) -> write_buffer::Result<BufferedWriteRequest>;
 
+    /// Validates and buffers the line protocol in the same way as `write_lp`, except that each line is validated
+    /// independently. Lines that fail to parse or conflict with the schema are returned in the `invalid_lines` of the
+    /// result, rather than failing the write, and all other lines are written.
+    async fn write_lp_partial(
+        &self,
+        database: NamespaceName<'static>,
+        lp: &str,
+        default_time: i64,
+    ) -> write_buffer::Result<BufferedWriteRequest>;
+
+    /// Validates the columns of the record batch against the schema of the table, adding the table or any new
+    /// columns to the catalog, and writes its rows into the WAL if configured and into the in memory buffer in the
+    /// same way as `write_lp`. Each row of the batch is counted as a line in the result.
+    async fn write_batch(
+        &self,
+        database: NamespaceName<'static>,
+        table_name: &str,
+        batch: &RecordBatch,
+        default_time: i64,
+    ) -> write_buffer::Result<BufferedWriteRequest>;
+
+    /// Creates a new, empty database in the catalog. Returns an error if the database already
+    /// exists. If the buffer persists its data, the catalog is persisted before this returns.
+    async fn create_database(&self, database: NamespaceName<'static>) -> Result<()>;
+
+    /// Removes a database from the catalog and drops any of its data that is held in the buffer.
+    /// If the buffer persists its data, the catalog is persisted and the parquet files of the
+    /// database are deleted from object storage before this returns.
+    async fn delete_database(&self, database: &str) -> Result<()>;
+
+    /// Adds a continuous query to the catalog. Returns an error if its database does not exist or
+    /// already has a continuous query with the same name. If the buffer persists its data, the
+    /// catalog is persisted before this returns.
+    async fn create_continuous_query(&self, cq: ContinuousQueryDefinition) -> Result<()>;
+
+    /// Removes a continuous query from the catalog. If the buffer persists its data, the catalog
+    /// is persisted before this returns.
+    async fn drop_continuous_query(&self, database: &str, name: &str) -> Result<()>;
+
+    /// Records the time of the last run of a continuous query in the catalog. If the buffer
+    /// persists its data, the catalog is persisted before this returns, so that the query isn't
+    /// run over the same interval again after a restart.
+    async fn set_continuous_query_last_run(
+        &self,
+        database: &str,
+        name: &str,
+        run_ns: i64,
+    ) -> Result<()>;
+
     /// Closes the open segment and returns it so that it can be persisted or thrown away. A new segment will be opened
     /// with the catalog rolling over.
     async fn close_open_segment(&self) -> Result<Arc<dyn BufferSegment>>;
@@ -76,6 +135,9 @@ pub trait Bufferer: Debug + Send + Sync + 'static {
 
     /// Returns the configured WAL, if there is one.
     fn wal(&self) -> Option<Arc<impl Wal>>;
+
+    /// Returns the catalog of databases and tables that the buffer keeps up to date.
+    fn catalog(&self) -> Arc<Catalog>;
 }
 
 /// A segment in the buffer that corresponds to a single WAL segment file. It contains a catalog with any updates
@@ -161,6 +223,14 @@ pub trait Persister: Debug + Send + Sync + 'static {
     /// for this segment.
     async fn persist_segment(&self, persisted_segment: PersistedSegment) -> Result<()>;
 
+    /// Writes the record batch as a parquet file to object storage at the given path and returns
+    /// the size of the file in bytes.
+    async fn persist_parquet_file(&self, path: &str, batch: RecordBatch) -> Result<u64>;
+
+    /// Removes the files of a deleted database from the persisted segments and then deletes the
+    /// files from object storage.
+    async fn delete_database(&self, db_name: &str) -> Result<()>;
+
     /// Returns the configured `ObjectStore` that data is loaded from and persisted to.
     fn object_store(&self) -> Arc<dyn object_store::ObjectStore>;
 }
@@ -213,6 +283,7 @@ pub struct WalOpBatch {
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub enum WalOp {
     LpWrite(LpWriteOp),
+    BatchWrite(BatchWriteOp),
 }
 
 /// A write of 1 or more lines of line protocol to a single database. The default time is set by the server at the
@@ -224,6 +295,16 @@ pub struct LpWriteOp {
     pub default_time: u64,
 }
 
+/// A write of an Arrow record batch to a single table of a database, such as one sent with Flight `DoPut`. The batch
+/// is encoded in the Arrow IPC streaming format. The default time is set by the server at the time the write comes in.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BatchWriteOp {
+    pub db_name: String,
+    pub table_name: String,
+    pub batch: Vec<u8>,
+    pub default_time: u64,
+}
+
 /// A single write request can have many lines in it. A writer can request to accept all lines that are valid, while
 /// returning an error for any invalid lines. This is the error information for a single invalid line.
 #[derive(Debug, Serialize)]
@@ -272,7 +353,14 @@ pub struct PersistedSegment {
     pub segment_max_time: i64,
     /// The collection of databases that had tables persisted in this segment. The tables will then have their
     /// name and the parquet files.
-    pub databases: HashMap<String, TableParquetFiles>,
+    pub databases: HashMap<String, DatabaseTables>,
+}
+
+/// The tables of a single database that were persisted in a segment.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DatabaseTables {
+    /// Map of table name to the parquet files persisted for it.
+    pub tables: HashMap<String, TableParquetFiles>,
 }
 
 /// A collection of parquet files persisted in a segment for a specific table.
@@ -3,13 +3,36 @@
 
 use crate::catalog::Catalog;
 use crate::{PersistedCatalog, PersistedSegment, Persister, SegmentId};
+use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use object_store::path::Path as ObjPath;
 use object_store::ObjectStore;
+use parquet::arrow::ArrowWriter;
 use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+
+    #[error("serde error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+const CATALOG_DIR: &str = "catalogs";
+const SEGMENT_DIR: &str = "segments";
+const DATABASE_DIR: &str = "dbs";
 
 #[derive(Debug)]
 pub struct PersisterImpl {
-    #[allow(dead_code)]
     object_store: Arc<dyn ObjectStore>,
 }
 
@@ -17,31 +40,232 @@ impl PersisterImpl {
     pub fn new(object_store: Arc<dyn ObjectStore>) -> Self {
         Self { object_store }
     }
+
+    /// Returns the segment ids of the files found under `dir`, sorted with the most recent first.
+    async fn list_segment_ids(&self, dir: &str) -> Result<Vec<SegmentId>> {
+        let prefix = ObjPath::from(dir);
+        let files: Vec<_> = self
+            .object_store
+            .list(Some(&prefix))
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut segment_ids: Vec<_> = files
+            .into_iter()
+            .filter_map(|meta| segment_id_from_file_name(meta.location.filename()?))
+            .collect();
+        segment_ids.sort_unstable_by(|a, b| b.cmp(a));
+
+        Ok(segment_ids)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &ObjPath) -> Result<T> {
+        let bytes = self.object_store.get(path).await?.bytes().await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    async fn put_json<T: serde::Serialize>(&self, path: &ObjPath, value: &T) -> Result<()> {
+        let json = serde_json::to_vec_pretty(value)?;
+        self.object_store.put(path, Bytes::from(json)).await?;
+        Ok(())
+    }
+
+    async fn load_catalog(&self) -> Result<Option<PersistedCatalog>> {
+        let segment_ids = self.list_segment_ids(CATALOG_DIR).await?;
+        let Some(&segment_id) = segment_ids.first() else {
+            return Ok(None);
+        };
+
+        let catalog = self.get_json(&catalog_path(segment_id)).await?;
+        Ok(Some(catalog))
+    }
+
+    async fn load_segments(&self, most_recent_n: usize) -> Result<Vec<PersistedSegment>> {
+        let segment_ids = self.list_segment_ids(SEGMENT_DIR).await?;
+
+        let mut segments = Vec::with_capacity(most_recent_n.min(segment_ids.len()));
+        for segment_id in segment_ids.into_iter().take(most_recent_n) {
+            segments.push(self.get_json(&segment_path(segment_id)).await?);
+        }
+
+        Ok(segments)
+    }
+
+    async fn persist_catalog(&self, segment_id: SegmentId, catalog: Catalog) -> Result<()> {
+        let persisted = PersistedCatalog {
+            segment_id,
+            catalog: catalog.into_inner(),
+        };
+        self.put_json(&catalog_path(segment_id), &persisted).await
+    }
+
+    async fn persist_segment(&self, persisted_segment: PersistedSegment) -> Result<()> {
+        self.put_json(
+            &segment_path(persisted_segment.segment_id),
+            &persisted_segment,
+        )
+        .await
+    }
+
+    async fn persist_parquet_file(&self, path: &str, batch: RecordBatch) -> Result<u64> {
+        let mut bytes = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut bytes, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        let size_bytes = bytes.len() as u64;
+        self.object_store
+            .put(&ObjPath::from(path), Bytes::from(bytes))
+            .await?;
+
+        Ok(size_bytes)
+    }
+
+    async fn delete_database(&self, db_name: &str) -> Result<()> {
+        // Remove the files from the segments first, so that they are never referenced by a
+        // segment once they are deleted.
+        for segment_id in self.list_segment_ids(SEGMENT_DIR).await? {
+            let path = segment_path(segment_id);
+            let mut segment: PersistedSegment = self.get_json(&path).await?;
+            let Some(db_tables) = segment.databases.remove(db_name) else {
+                continue;
+            };
+
+            for file in db_tables.tables.values().flat_map(|t| &t.parquet_files) {
+                segment.segment_parquet_size_bytes -= file.size_bytes;
+                segment.segment_row_count -= file.row_count as u64;
+            }
+            self.put_json(&path, &segment).await?;
+        }
+
+        let prefix = ObjPath::from(format!("{DATABASE_DIR}/{db_name}"));
+        let files: Vec<_> = self
+            .object_store
+            .list(Some(&prefix))
+            .await?
+            .try_collect()
+            .await?;
+        for meta in files {
+            self.object_store.delete(&meta.location).await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl Persister for PersisterImpl {
     async fn load_catalog(&self) -> crate::Result<Option<PersistedCatalog>> {
-        todo!()
+        Ok(self.load_catalog().await?)
+    }
+
+    async fn load_segments(&self, most_recent_n: usize) -> crate::Result<Vec<PersistedSegment>> {
+        Ok(self.load_segments(most_recent_n).await?)
+    }
+
+    async fn persist_catalog(&self, segment_id: SegmentId, catalog: Catalog) -> crate::Result<()> {
+        Ok(self.persist_catalog(segment_id, catalog).await?)
     }
 
-    async fn load_segments(&self, _most_recent_n: usize) -> crate::Result<Vec<PersistedSegment>> {
-        todo!()
+    async fn persist_segment(&self, persisted_segment: PersistedSegment) -> crate::Result<()> {
+        Ok(self.persist_segment(persisted_segment).await?)
     }
 
-    async fn persist_catalog(
-        &self,
-        _segment_id: SegmentId,
-        _catalog: Catalog,
-    ) -> crate::Result<()> {
-        todo!()
+    async fn persist_parquet_file(&self, path: &str, batch: RecordBatch) -> crate::Result<u64> {
+        Ok(self.persist_parquet_file(path, batch).await?)
     }
 
-    async fn persist_segment(&self, _persisted_segment: PersistedSegment) -> crate::Result<()> {
-        todo!()
+    async fn delete_database(&self, db_name: &str) -> crate::Result<()> {
+        Ok(self.delete_database(db_name).await?)
     }
 
     fn object_store(&self) -> Arc<dyn ObjectStore> {
-        todo!()
+        Arc::clone(&self.object_store)
+    }
+}
+
+/// The path of the parquet file holding the data of a single partition of a table that was
+/// persisted with the given segment.
+pub fn parquet_file_path(
+    db_name: &str,
+    table_name: &str,
+    partition_key: &str,
+    segment_id: SegmentId,
+) -> String {
+    format!(
+        "{DATABASE_DIR}/{db_name}/{table_name}/{partition_key}/{:010}.parquet",
+        segment_id.0
+    )
+}
+
+fn catalog_path(segment_id: SegmentId) -> ObjPath {
+    ObjPath::from(format!("{CATALOG_DIR}/{:010}.json", segment_id.0))
+}
+
+fn segment_path(segment_id: SegmentId) -> ObjPath {
+    ObjPath::from(format!("{SEGMENT_DIR}/{:010}.json", segment_id.0))
+}
+
+fn segment_id_from_file_name(file_name: &str) -> Option<SegmentId> {
+    let id = file_name.strip_suffix(".json")?.parse().ok()?;
+    Some(SegmentId::new(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn persist_and_load_catalog() {
+        let persister = PersisterImpl::new(Arc::new(InMemory::new()));
+        assert!(persister.load_catalog().await.unwrap().is_none());
+
+        let catalog = Catalog::new();
+        catalog.create_database("foo").unwrap();
+        persister
+            .persist_catalog(
+                SegmentId::new(1),
+                Catalog::from_inner(catalog.clone_inner()),
+            )
+            .await
+            .unwrap();
+        catalog.create_database("bar").unwrap();
+        persister
+            .persist_catalog(
+                SegmentId::new(2),
+                Catalog::from_inner(catalog.clone_inner()),
+            )
+            .await
+            .unwrap();
+
+        let persisted = persister.load_catalog().await.unwrap().unwrap();
+        assert_eq!(persisted.segment_id, SegmentId::new(2));
+        assert_eq!(persisted.catalog, catalog.clone_inner());
+    }
+
+    #[tokio::test]
+    async fn persist_and_load_segments() {
+        let persister = PersisterImpl::new(Arc::new(InMemory::new()));
+
+        for id in [3, 1, 2] {
+            persister
+                .persist_segment(PersistedSegment {
+                    segment_id: SegmentId::new(id),
+                    segment_wal_size_bytes: 0,
+                    segment_parquet_size_bytes: 0,
+                    segment_row_count: 0,
+                    segment_min_time: 0,
+                    segment_max_time: 0,
+                    databases: Default::default(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let segments = persister.load_segments(2).await.unwrap();
+        let ids: Vec<_> = segments.iter().map(|s| s.segment_id).collect();
+        assert_eq!(ids, vec![SegmentId::new(3), SegmentId::new(2)]);
     }
 }
@@ -1,39 +1,52 @@
 //! Implementation of an in-memory buffer for writes
 
-use crate::catalog::{Catalog, DatabaseSchema, TableDefinition};
+use crate::catalog::{Catalog, ContinuousQueryDefinition, DatabaseSchema, TableDefinition};
+use crate::persister::parquet_file_path;
 use crate::{
-    BufferSegment, BufferedWriteRequest, Bufferer, ChunkContainer, SegmentId, Wal, WriteBuffer,
+    BatchWriteOp, BufferSegment, BufferedWriteRequest, Bufferer, ChunkContainer, DatabaseTables,
+    LpWriteOp, ParquetFile, PersistedSegment, Persister, SegmentId, TableParquetFiles, Wal, WalOp,
+    WalSegmentReader, WalSegmentWriter, WriteBuffer, WriteLineError,
 };
 use arrow::array::ArrayRef;
 use arrow::{
     array::{
-        BooleanBuilder, Float64Builder, Int64Builder, StringBuilder, StringDictionaryBuilder,
-        TimestampNanosecondBuilder, UInt64Builder,
+        AsArray, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder,
+        StringDictionaryBuilder, TimestampNanosecondBuilder, UInt64Builder,
     },
-    datatypes::Int32Type,
+    compute::{cast_with_options, CastOptions},
+    datatypes::{
+        DataType, Float64Type, Int32Type, Int64Type, TimeUnit, TimestampNanosecondType, UInt64Type,
+    },
+    error::ArrowError,
+    ipc::{reader::StreamReader, writer::StreamWriter},
     record_batch::RecordBatch,
 };
 use async_trait::async_trait;
 use chrono::{TimeZone, Utc};
 use data_types::{
-    column_type_from_field, ChunkId, ChunkOrder, ColumnType, NamespaceName, PartitionKey, TableId,
-    TimestampMinMax, TransitionPartitionId,
+    column_type_from_field, ChunkId, ChunkOrder, ColumnType, NamespaceName, NamespaceNameError,
+    PartitionKey, TableId, TimestampMinMax, TransitionPartitionId,
 };
 use datafusion::common::{DataFusionError, Statistics};
 use datafusion::execution::context::SessionState;
+use datafusion::execution::object_store::ObjectStoreUrl;
 use datafusion::logical_expr::Expr;
-use influxdb_line_protocol::{parse_lines, FieldValue, ParsedLine};
+use influxdb_line_protocol::{parse_lines, split_lines, FieldValue, ParsedLine};
 use iox_catalog::TIME_COLUMN;
 use iox_query::chunk_statistics::{create_chunk_statistics, ColumnRange};
 use iox_query::{QueryChunk, QueryChunkData};
+use iox_time::{Time, TimeProvider};
+use object_store::path::Path as ObjPath;
+use object_store::ObjectMeta;
 use observability_deps::tracing::{debug, info};
 use parking_lot::RwLock;
+use parquet_file::storage::ParquetExecInput;
 use schema::sort::SortKey;
 use schema::Schema;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -48,6 +61,24 @@ pub enum Error {
         existing: ColumnType,
         new: ColumnType,
     },
+
+    #[error("unsupported data type {data_type} for column {name}")]
+    UnsupportedColumnType { name: String, data_type: DataType },
+
+    #[error("error converting column {name}: {source}")]
+    ColumnConversion { name: String, source: ArrowError },
+
+    #[error("error writing to the wal: {0}")]
+    Wal(#[from] crate::wal::Error),
+
+    #[error("error converting a record batch to or from the wal format: {0}")]
+    WalBatch(ArrowError),
+
+    #[error("invalid database name in wal segment {}: {source}", segment_id.0)]
+    WalDatabaseName {
+        segment_id: SegmentId,
+        source: NamespaceNameError,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -62,21 +93,180 @@ pub struct WriteRequest<'a> {
 #[derive(Debug)]
 pub struct WriteBufferImpl<W> {
     catalog: Arc<Catalog>,
-    buffered_data: RwLock<HashMap<String, DatabaseBuffer>>,
-    #[allow(dead_code)]
+    open_segment: RwLock<OpenBufferSegment>,
+    persisted_files: Option<PersistedFiles>,
+    /// Persists the catalog when it is changed by something other than a write, if the write
+    /// buffer was loaded from persisted state.
+    persister: Option<Arc<dyn Persister>>,
+    /// Held while the catalog is persisted, so that an older snapshot of the catalog can't
+    /// overwrite a newer one.
+    persist_catalog_lock: tokio::sync::Mutex<()>,
     wal: Option<Arc<W>>,
+    time_provider: Arc<dyn TimeProvider>,
 }
 
 impl<W: Wal> WriteBufferImpl<W> {
-    pub fn new(catalog: Arc<Catalog>, wal: Option<Arc<W>>) -> Self {
+    pub fn new(
+        catalog: Arc<Catalog>,
+        wal: Option<Arc<W>>,
+        time_provider: Arc<dyn TimeProvider>,
+    ) -> Self {
+        Self::new_with_segment_id(catalog, wal, time_provider, SegmentId(0))
+    }
+
+    /// Create a write buffer whose first open segment is `segment_id`, e.g. to follow on from
+    /// the segments persisted before a restart.
+    pub fn new_with_segment_id(
+        catalog: Arc<Catalog>,
+        wal: Option<Arc<W>>,
+        time_provider: Arc<dyn TimeProvider>,
+        segment_id: SegmentId,
+    ) -> Self {
+        let open_segment = OpenBufferSegment::new(segment_id, time_provider.now());
         Self {
             catalog,
-            buffered_data: RwLock::new(HashMap::new()),
+            open_segment: RwLock::new(open_segment),
+            persisted_files: None,
+            persister: None,
+            persist_catalog_lock: tokio::sync::Mutex::new(()),
             wal,
+            time_provider,
+        }
+    }
+
+    /// Create a write buffer that continues from the state persisted by `persister` before a
+    /// restart. The catalog is the most recently persisted one and the first open segment
+    /// follows the segment it was persisted with, so that persisting the buffer doesn't
+    /// overwrite the files of earlier segments. The parquet files of persisted segments are
+    /// queried from the object store registered with the executor at `object_store_url`.
+    pub async fn load(
+        persister: Arc<dyn Persister>,
+        object_store_url: ObjectStoreUrl,
+        wal: Option<Arc<W>>,
+        time_provider: Arc<dyn TimeProvider>,
+    ) -> crate::Result<Self> {
+        let (catalog, segment_id) = match persister.load_catalog().await? {
+            Some(persisted) => {
+                info!(segment_id = ?persisted.segment_id, "loaded persisted catalog");
+                (
+                    Catalog::from_inner(persisted.catalog),
+                    persisted.segment_id.next(),
+                )
+            }
+            None => (Catalog::new(), SegmentId(0)),
+        };
+
+        // Segments are persisted after the catalog they were closed with, so the catalog has
+        // every table with persisted files, unless its database has since been deleted.
+        let mut databases: HashMap<String, DatabaseTables> = HashMap::new();
+        let mut last_persisted_segment_id = None;
+        for segment in persister.load_segments(usize::MAX).await? {
+            last_persisted_segment_id = last_persisted_segment_id.max(Some(segment.segment_id));
+            for (db_name, db_tables) in segment.databases {
+                if catalog.db_schema(&db_name).is_none() {
+                    continue;
+                }
+                let tables = &mut databases.entry(db_name).or_default().tables;
+                for (table_name, table_files) in db_tables.tables {
+                    match tables.entry(table_name) {
+                        Entry::Occupied(mut e) => {
+                            e.get_mut().parquet_files.extend(table_files.parquet_files)
+                        }
+                        Entry::Vacant(e) => {
+                            e.insert(table_files);
+                        }
+                    }
+                }
+            }
+        }
+
+        // The WAL segments of persisted segments are deleted once they are persisted, unless
+        // the server stopped first. The writes in any other WAL segments were not persisted, so
+        // they are replayed into the open segment, which continues the last of them.
+        let mut replay_segment_ids = vec![];
+        if let Some(wal) = &wal {
+            for segment_file in wal.segment_files()? {
+                if Some(segment_file.segment_id) <= last_persisted_segment_id {
+                    wal.delete_wal_segment(segment_file.segment_id)?;
+                } else {
+                    replay_segment_ids.push(segment_file.segment_id);
+                }
+            }
+        }
+        let segment_id = replay_segment_ids
+            .iter()
+            .copied()
+            .fold(segment_id, SegmentId::max);
+
+        let mut write_buffer =
+            Self::new_with_segment_id(Arc::new(catalog), wal, time_provider, segment_id);
+        write_buffer.persisted_files = Some(PersistedFiles {
+            object_store_url,
+            databases: RwLock::new(databases),
+        });
+        write_buffer.persister = Some(persister);
+
+        if let Some(wal) = &write_buffer.wal {
+            for segment_id in replay_segment_ids {
+                write_buffer.replay_wal_segment(wal.as_ref(), segment_id)?;
+            }
+        }
+
+        Ok(write_buffer)
+    }
+
+    /// Buffers the writes of the WAL segment `segment_id` into the open segment, without
+    /// writing them to the WAL again.
+    fn replay_wal_segment(&self, wal: &W, segment_id: SegmentId) -> crate::Result<()> {
+        let mut reader = wal.open_segment_reader(segment_id)?;
+        let mut op_count = 0;
+        while let Some(batch) = reader.next_batch()? {
+            for op in batch.ops {
+                self.replay_wal_op(segment_id, op)?;
+                op_count += 1;
+            }
         }
+        info!(segment_id = segment_id.0, op_count, "replayed wal segment");
+
+        Ok(())
+    }
+
+    fn replay_wal_op(&self, segment_id: SegmentId, op: WalOp) -> Result<()> {
+        let partitioner = Partitioner::new_per_day_partitioner();
+        match op {
+            WalOp::LpWrite(op) => {
+                let db_name = wal_db_name(segment_id, op.db_name)?;
+                // The line protocol of a partial write includes its invalid lines, which are
+                // skipped again. Every line of any other write is valid.
+                self.validate_and_buffer_write(db_name, None, |db| {
+                    let (result, _) = parse_validate_and_update_schema_partial(
+                        &op.lp,
+                        db,
+                        &partitioner,
+                        op.default_time as i64,
+                    );
+                    Ok(result)
+                })?;
+            }
+            WalOp::BatchWrite(op) => {
+                let db_name = wal_db_name(segment_id, op.db_name)?;
+                for batch in decode_wal_batch(&op.batch)? {
+                    self.validate_and_buffer_write(db_name.clone(), None, |db| {
+                        validate_batch_and_update_schema(
+                            &op.table_name,
+                            &batch,
+                            db,
+                            &partitioner,
+                            op.default_time as i64,
+                        )
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
     }
 
-    // TODO: write into segments and wal
     async fn write_lp(
         &self,
         db_name: NamespaceName<'static>,
@@ -84,45 +274,205 @@ impl<W: Wal> WriteBufferImpl<W> {
         default_time: i64,
     ) -> Result<BufferedWriteRequest> {
         debug!("write_lp to {} in writebuffer", db_name);
-        let (sequence, db) = self.catalog.db_or_create(db_name.as_str());
-        let result = parse_validate_and_update_schema(
-            lp,
-            &db,
-            &Partitioner::new_per_day_partitioner(),
-            default_time,
-        )?;
+        let wal_op = self.wal.is_some().then(|| {
+            WalOp::LpWrite(LpWriteOp {
+                db_name: db_name.to_string(),
+                lp: lp.to_string(),
+                default_time: default_time as u64,
+            })
+        });
+        self.validate_and_buffer_write(db_name, wal_op, |db| {
+            parse_validate_and_update_schema(
+                lp,
+                db,
+                &Partitioner::new_per_day_partitioner(),
+                default_time,
+            )
+        })
+    }
 
-        if let Some(schema) = result.schema {
-            debug!("replacing schema for {:?}", schema);
-            self.catalog
-                .replace_database(sequence, Arc::new(schema))
-                .unwrap();
-        }
+    async fn write_lp_partial(
+        &self,
+        db_name: NamespaceName<'static>,
+        lp: &str,
+        default_time: i64,
+    ) -> Result<BufferedWriteRequest> {
+        debug!("write_lp_partial to {} in writebuffer", db_name);
+        let wal_op = self.wal.is_some().then(|| {
+            WalOp::LpWrite(LpWriteOp {
+                db_name: db_name.to_string(),
+                lp: lp.to_string(),
+                default_time: default_time as u64,
+            })
+        });
+        let mut invalid_lines = vec![];
+        let mut write = self.validate_and_buffer_write(db_name, wal_op, |db| {
+            let (result, lines) = parse_validate_and_update_schema_partial(
+                lp,
+                db,
+                &Partitioner::new_per_day_partitioner(),
+                default_time,
+            );
+            invalid_lines = lines;
+            Ok(result)
+        })?;
+        write.invalid_lines = invalid_lines;
+        Ok(write)
+    }
+
+    async fn write_batch(
+        &self,
+        db_name: NamespaceName<'static>,
+        table_name: &str,
+        batch: &RecordBatch,
+        default_time: i64,
+    ) -> Result<BufferedWriteRequest> {
+        debug!("write_batch to {}.{} in writebuffer", db_name, table_name);
+        let wal_op = match self.wal {
+            Some(_) => Some(WalOp::BatchWrite(BatchWriteOp {
+                db_name: db_name.to_string(),
+                table_name: table_name.to_string(),
+                batch: encode_wal_batch(batch)?,
+                default_time: default_time as u64,
+            })),
+            None => None,
+        };
+        self.validate_and_buffer_write(db_name, wal_op, |db| {
+            validate_batch_and_update_schema(
+                table_name,
+                batch,
+                db,
+                &Partitioner::new_per_day_partitioner(),
+                default_time,
+            )
+        })
+    }
+
+    /// Validates a write against the current schema of the database with `validate`, applies
+    /// any schema changes to the catalog, writes `wal_op` to the WAL of the open segment and
+    /// adds the rows to the open segment. If the catalog was changed by another write, or by
+    /// creating or deleting a database, after the write was validated, it is validated again.
+    fn validate_and_buffer_write(
+        &self,
+        db_name: NamespaceName<'static>,
+        wal_op: Option<WalOp>,
+        mut validate: impl FnMut(&DatabaseSchema) -> Result<ValidationResult>,
+    ) -> Result<BufferedWriteRequest> {
+        loop {
+            let (sequence, db) = self.catalog.db_or_create(db_name.as_str());
+            let result = validate(&db)?;
+
+            let mut open_segment = self.open_segment.write();
+
+            // The validated schema must still be the current one, or the write may conflict
+            // with columns added since, or be to a database that has since been deleted.
+            let schema_is_current = match result.schema {
+                Some(schema) => {
+                    debug!("replacing schema for {:?}", schema);
+                    self.catalog
+                        .replace_database(sequence, Arc::new(schema))
+                        .is_ok()
+                }
+                None => self
+                    .catalog
+                    .db_schema(db_name.as_str())
+                    .is_some_and(|current| Arc::ptr_eq(&current, &db)),
+            };
+            if !schema_is_current {
+                debug!(
+                    "catalog updated during write to {}, validating it again",
+                    db_name
+                );
+                continue;
+            }
 
-        let mut buffered_data = self.buffered_data.write();
-        let db_buffer = buffered_data
-            .entry(db_name.as_str().to_string())
-            .or_default();
-        for (table_name, table_batch) in result.table_batches {
-            let table_buffer = db_buffer.table_buffers.entry(table_name).or_default();
-            for (partition_key, partition_batch) in table_batch.partition_batches {
-                let partition_buffer = table_buffer
-                    .partition_buffers
-                    .entry(partition_key)
-                    .or_default();
-                partition_buffer.rows.extend(partition_batch.rows);
+            // The write is durable before it is buffered, so that it can be replayed if the
+            // server stops before the segment is persisted.
+            if let (Some(wal), Some(wal_op)) = (&self.wal, wal_op) {
+                open_segment.write_to_wal(wal.as_ref(), wal_op)?;
             }
+
+            let segment_id = open_segment.segment_id;
+            let db_buffer = open_segment
+                .buffered_data
+                .entry(db_name.as_str().to_string())
+                .or_default();
+            for (table_name, table_batch) in result.table_batches {
+                let table_buffer = db_buffer.table_buffers.entry(table_name).or_default();
+                for (partition_key, partition_batch) in table_batch.partition_batches {
+                    let partition_buffer = table_buffer
+                        .partition_buffers
+                        .entry(partition_key)
+                        .or_default();
+                    partition_buffer.rows.extend(partition_batch.rows);
+                }
+            }
+
+            return Ok(BufferedWriteRequest {
+                db_name,
+                invalid_lines: vec![],
+                line_count: result.line_count,
+                field_count: result.field_count,
+                tag_count: result.tag_count,
+                total_buffer_memory_used: 0,
+                segment_id,
+            });
         }
+    }
 
-        Ok(BufferedWriteRequest {
-            db_name,
-            invalid_lines: vec![],
-            line_count: result.line_count,
-            field_count: result.field_count,
-            tag_count: result.tag_count,
-            total_buffer_memory_used: 0,
-            segment_id: SegmentId(0),
-        })
+    async fn create_database(&self, db_name: &str) -> crate::Result<()> {
+        self.catalog.create_database(db_name)?;
+        self.persist_catalog().await
+    }
+
+    async fn delete_database(&self, db_name: &str) -> crate::Result<()> {
+        self.catalog.delete_database(db_name)?;
+        self.open_segment.write().buffered_data.remove(db_name);
+        if let Some(persisted_files) = &self.persisted_files {
+            persisted_files.databases.write().remove(db_name);
+        }
+
+        self.persist_catalog().await?;
+        if let Some(persister) = &self.persister {
+            persister.delete_database(db_name).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn create_continuous_query(&self, cq: ContinuousQueryDefinition) -> crate::Result<()> {
+        self.catalog.create_continuous_query(cq)?;
+        self.persist_catalog().await
+    }
+
+    async fn drop_continuous_query(&self, db_name: &str, name: &str) -> crate::Result<()> {
+        self.catalog.drop_continuous_query(db_name, name)?;
+        self.persist_catalog().await
+    }
+
+    async fn set_continuous_query_last_run(
+        &self,
+        db_name: &str,
+        name: &str,
+        run_ns: i64,
+    ) -> crate::Result<()> {
+        self.catalog
+            .set_continuous_query_last_run(db_name, name, run_ns)?;
+        self.persist_catalog().await
+    }
+
+    /// Persists the catalog with the open segment, so that changes to it survive a restart
+    /// before the segment is persisted. Does nothing if the write buffer wasn't loaded from
+    /// persisted state.
+    async fn persist_catalog(&self) -> crate::Result<()> {
+        let Some(persister) = &self.persister else {
+            return Ok(());
+        };
+
+        let _guard = self.persist_catalog_lock.lock().await;
+        let segment_id = self.open_segment.read().segment_id;
+        let catalog = Catalog::from_inner(self.catalog.clone_inner());
+        persister.persist_catalog(segment_id, catalog).await
     }
 
     fn get_table_chunks(
@@ -137,9 +487,12 @@ impl<W: Wal> WriteBufferImpl<W> {
         let table = db_schema.tables.get(table_name).unwrap();
         let schema = table.schema.as_ref().cloned().unwrap();
 
-        let table_buffer = self.clone_table_buffer(database_name, table_name).unwrap();
+        let mut chunks = self.persisted_chunks(database_name, table_name, &schema);
 
-        let mut chunks = Vec::with_capacity(table_buffer.partition_buffers.len());
+        // The table may only have data in persisted segments
+        let Some(table_buffer) = self.clone_table_buffer(database_name, table_name) else {
+            return Ok(chunks);
+        };
 
         for (partition_key, partition_buffer) in table_buffer.partition_buffers {
             let partition_key: PartitionKey = partition_key.into();
@@ -171,11 +524,97 @@ impl<W: Wal> WriteBufferImpl<W> {
         Ok(chunks)
     }
 
+    /// Returns a chunk for each parquet file of the table that was persisted before the write
+    /// buffer was loaded. Files persisted before a column was added to the table are read with
+    /// nulls for that column.
+    fn persisted_chunks(
+        &self,
+        database_name: &str,
+        table_name: &str,
+        schema: &Schema,
+    ) -> Vec<Arc<dyn QueryChunk>> {
+        let Some(persisted_files) = &self.persisted_files else {
+            return vec![];
+        };
+        let databases = persisted_files.databases.read();
+        let Some(table_files) = databases
+            .get(database_name)
+            .and_then(|db| db.tables.get(table_name))
+        else {
+            return vec![];
+        };
+
+        let partitioner = Partitioner::new_per_day_partitioner();
+        table_files
+            .parquet_files
+            .iter()
+            .map(|file| {
+                let partition_key: PartitionKey =
+                    partitioner.partition_key_for_time(file.min_time).into();
+                let stats = create_chunk_statistics(
+                    file.row_count as u64,
+                    schema,
+                    Some(TimestampMinMax {
+                        min: file.min_time,
+                        max: file.max_time,
+                    }),
+                    &Default::default(),
+                );
+
+                let chunk = ParquetChunk {
+                    schema: schema.clone(),
+                    stats: Arc::new(stats),
+                    partition_id: TransitionPartitionId::new(TableId::new(0), &partition_key),
+                    exec_input: ParquetExecInput {
+                        object_store_url: persisted_files.object_store_url.clone(),
+                        object_meta: ObjectMeta {
+                            location: ObjPath::from(file.path.as_str()),
+                            last_modified: Default::default(),
+                            size: file.size_bytes as usize,
+                            e_tag: None,
+                        },
+                    },
+                    id: ChunkId::new(),
+                    chunk_order: ChunkOrder::new(0),
+                };
+
+                Arc::new(chunk) as _
+            })
+            .collect()
+    }
+
     fn clone_table_buffer(&self, database_name: &str, table_name: &str) -> Option<TableBuffer> {
-        let binding = self.buffered_data.read();
-        let table_buffer = binding.get(database_name)?.table_buffers.get(table_name)?;
+        let binding = self.open_segment.read();
+        let table_buffer = binding
+            .buffered_data
+            .get(database_name)?
+            .table_buffers
+            .get(table_name)?;
         Some(table_buffer.clone())
     }
+
+    fn close_open_segment(&self) -> ClosedBufferSegment {
+        // Snapshot the catalog while holding the segment lock so that it contains every table
+        // and column referenced by the buffered data.
+        let now = self.time_provider.now();
+        let mut open_segment = self.open_segment.write();
+        let next_segment = OpenBufferSegment::new(open_segment.segment_id.next(), now);
+        let closed = std::mem::replace(&mut *open_segment, next_segment);
+        let catalog = Arc::new(Catalog::from_inner(self.catalog.clone_inner()));
+        drop(open_segment);
+
+        info!(
+            segment_id = closed.segment_id.0,
+            age = ?now.checked_duration_since(closed.opened_at),
+            "closed open buffer segment"
+        );
+
+        ClosedBufferSegment {
+            segment_id: closed.segment_id,
+            catalog,
+            buffered_data: closed.buffered_data,
+        }
+    }
 }
 
 #[async_trait]
@@ -189,8 +628,54 @@ impl<W: Wal> Bufferer for WriteBufferImpl<W> {
         self.write_lp(database, lp, default_time).await
     }
 
+    async fn write_lp_partial(
+        &self,
+        database: NamespaceName<'static>,
+        lp: &str,
+        default_time: i64,
+    ) -> Result<BufferedWriteRequest> {
+        self.write_lp_partial(database, lp, default_time).await
+    }
+
+    async fn write_batch(
+        &self,
+        database: NamespaceName<'static>,
+        table_name: &str,
+        batch: &RecordBatch,
+        default_time: i64,
+    ) -> Result<BufferedWriteRequest> {
+        self.write_batch(database, table_name, batch, default_time)
+            .await
+    }
+
+    async fn create_database(&self, database: NamespaceName<'static>) -> crate::Result<()> {
+        self.create_database(database.as_str()).await
+    }
+
+    async fn delete_database(&self, database: &str) -> crate::Result<()> {
+        self.delete_database(database).await
+    }
+
+    async fn create_continuous_query(&self, cq: ContinuousQueryDefinition) -> crate::Result<()> {
+        self.create_continuous_query(cq).await
+    }
+
+    async fn drop_continuous_query(&self, database: &str, name: &str) -> crate::Result<()> {
+        self.drop_continuous_query(database, name).await
+    }
+
+    async fn set_continuous_query_last_run(
+        &self,
+        database: &str,
+        name: &str,
+        run_ns: i64,
+    ) -> crate::Result<()> {
+        self.set_continuous_query_last_run(database, name, run_ns)
+            .await
+    }
+
     async fn close_open_segment(&self) -> crate::Result<Arc<dyn BufferSegment>> {
-        todo!()
+        Ok(Arc::new(self.close_open_segment()))
     }
 
     async fn load_segments_after(
@@ -204,6 +689,10 @@ impl<W: Wal> Bufferer for WriteBufferImpl<W> {
     fn wal(&self) -> Option<Arc<impl Wal>> {
         self.wal.clone()
     }
+
+    fn catalog(&self) -> Arc<Catalog> {
+        Arc::clone(&self.catalog)
+    }
 }
 
 impl<W: Wal> ChunkContainer for WriteBufferImpl<W> {
@@ -221,6 +710,156 @@ impl<W: Wal> ChunkContainer for WriteBufferImpl<W> {
 
 impl<W: Wal> WriteBuffer for WriteBufferImpl<W> {}
 
+/// The parquet files persisted with segments before the write buffer was loaded, by database.
+#[derive(Debug)]
+struct PersistedFiles {
+    object_store_url: ObjectStoreUrl,
+    databases: RwLock<HashMap<String, DatabaseTables>>,
+}
+
+/// The segment that writes are currently buffered into.
+#[derive(Debug)]
+struct OpenBufferSegment {
+    segment_id: SegmentId,
+    /// When the segment was opened, according to the write buffer's time provider.
+    opened_at: Time,
+    buffered_data: HashMap<String, DatabaseBuffer>,
+    /// The writer to the WAL file of the segment, opened by the first write to it.
+    wal_writer: Option<Box<dyn WalSegmentWriter>>,
+}
+
+impl OpenBufferSegment {
+    fn new(segment_id: SegmentId, opened_at: Time) -> Self {
+        Self {
+            segment_id,
+            opened_at,
+            buffered_data: HashMap::new(),
+            wal_writer: None,
+        }
+    }
+
+    fn write_to_wal<W: Wal>(&mut self, wal: &W, op: WalOp) -> Result<()> {
+        if self.wal_writer.is_none() {
+            self.wal_writer = Some(Box::new(wal.open_segment_writer(self.segment_id)?));
+        }
+        let wal_writer = self.wal_writer.as_mut().expect("wal writer was opened");
+        wal_writer.write_batch(vec![op])?;
+
+        Ok(())
+    }
+}
+
+/// A segment that no longer accepts writes and is ready to be persisted, along with a snapshot
+/// of the catalog taken when it was closed.
+#[derive(Debug)]
+pub struct ClosedBufferSegment {
+    segment_id: SegmentId,
+    catalog: Arc<Catalog>,
+    buffered_data: HashMap<String, DatabaseBuffer>,
+}
+
+#[async_trait]
+impl BufferSegment for ClosedBufferSegment {
+    fn id(&self) -> SegmentId {
+        self.segment_id
+    }
+
+    fn catalog(&self) -> Arc<Catalog> {
+        Arc::clone(&self.catalog)
+    }
+
+    async fn persist(&self, persister: Arc<dyn Persister>) -> crate::Result<()> {
+        let mut persisted_segment = PersistedSegment {
+            segment_id: self.segment_id,
+            segment_wal_size_bytes: 0,
+            segment_parquet_size_bytes: 0,
+            segment_row_count: 0,
+            segment_min_time: i64::MAX,
+            segment_max_time: i64::MIN,
+            databases: HashMap::with_capacity(self.buffered_data.len()),
+        };
+
+        for (db_name, db_buffer) in &self.buffered_data {
+            let db_schema = self
+                .catalog
+                .db_schema(db_name)
+                .expect("buffered database must exist in the catalog");
+            let mut database_tables = DatabaseTables::default();
+
+            for (table_name, table_buffer) in &db_buffer.table_buffers {
+                let table = db_schema
+                    .tables
+                    .get(table_name)
+                    .expect("buffered table must exist in the catalog");
+                let schema = table.schema.as_ref().expect("table schema must be set");
+                let mut parquet_files = Vec::with_capacity(table_buffer.partition_buffers.len());
+
+                for (partition_key, partition_buffer) in &table_buffer.partition_buffers {
+                    if partition_buffer.rows.is_empty() {
+                        continue;
+                    }
+
+                    let batch = partition_buffer.rows_to_record_batch(schema, table.columns());
+                    let path =
+                        parquet_file_path(db_name, table_name, partition_key, self.segment_id);
+                    let size_bytes = persister.persist_parquet_file(&path, batch).await?;
+
+                    let (min_time, max_time) = partition_buffer
+                        .rows
+                        .iter()
+                        .fold((i64::MAX, i64::MIN), |(min, max), row| {
+                            (min.min(row.time), max.max(row.time))
+                        });
+                    let row_count = partition_buffer.rows.len();
+
+                    persisted_segment.segment_parquet_size_bytes += size_bytes;
+                    persisted_segment.segment_row_count += row_count as u64;
+                    persisted_segment.segment_min_time =
+                        persisted_segment.segment_min_time.min(min_time);
+                    persisted_segment.segment_max_time =
+                        persisted_segment.segment_max_time.max(max_time);
+
+                    parquet_files.push(ParquetFile {
+                        path,
+                        size_bytes,
+                        row_count: row_count as u32,
+                        min_time,
+                        max_time,
+                    });
+                }
+
+                database_tables.tables.insert(
+                    table_name.clone(),
+                    TableParquetFiles {
+                        table_name: table_name.clone(),
+                        parquet_files,
+                        sort_key: vec![],
+                    },
+                );
+            }
+
+            persisted_segment
+                .databases
+                .insert(db_name.clone(), database_tables);
+        }
+
+        if persisted_segment.segment_row_count == 0 {
+            persisted_segment.segment_min_time = 0;
+            persisted_segment.segment_max_time = 0;
+        }
+
+        persister
+            .persist_catalog(
+                self.segment_id,
+                Catalog::from_inner(self.catalog.clone_inner()),
+            )
+            .await?;
+        persister.persist_segment(persisted_segment).await?;
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default)]
 struct DatabaseBuffer {
     table_buffers: HashMap<String, TableBuffer>,
@@ -402,6 +1041,60 @@ impl QueryChunk for BufferChunk {
         self
     }
 }
+/// A parquet file persisted with a previous segment, which is read from object storage when it
+/// is queried.
+#[derive(Debug)]
+pub struct ParquetChunk {
+    schema: Schema,
+    stats: Arc<Statistics>,
+    partition_id: data_types::partition::TransitionPartitionId,
+    exec_input: ParquetExecInput,
+    id: data_types::ChunkId,
+    chunk_order: data_types::ChunkOrder,
+}
+
+impl QueryChunk for ParquetChunk {
+    fn stats(&self) -> Arc<Statistics> {
+        Arc::clone(&self.stats)
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn partition_id(&self) -> &data_types::partition::TransitionPartitionId {
+        &self.partition_id
+    }
+
+    fn sort_key(&self) -> Option<&SortKey> {
+        None
+    }
+
+    fn id(&self) -> data_types::ChunkId {
+        self.id
+    }
+
+    fn may_contain_pk_duplicates(&self) -> bool {
+        false
+    }
+
+    fn data(&self) -> QueryChunkData {
+        QueryChunkData::Parquet(self.exec_input.clone())
+    }
+
+    fn chunk_type(&self) -> &str {
+        "ParquetChunk"
+    }
+
+    fn order(&self) -> data_types::ChunkOrder {
+        self.chunk_order
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 const YEAR_MONTH_DAY_TIME_FORMAT: &str = "%Y-%m-%d";
 
 /// Takes &str of line protocol, parses lines, validates the schema, and inserts new columns
@@ -424,6 +1117,75 @@ pub(crate) fn parse_validate_and_update_schema(
     validate_or_insert_schema_and_partitions(lines, schema, partitioner, default_time)
 }
 
+/// Like [`parse_validate_and_update_schema`], but parses and validates each line independently.
+/// Lines that fail to parse or validate are returned as errors and left out of the
+/// ValidationResult, rather than failing the whole write.
+pub(crate) fn parse_validate_and_update_schema_partial(
+    lp: &str,
+    schema: &DatabaseSchema,
+    partitioner: &Partitioner,
+    default_time: i64,
+) -> (ValidationResult, Vec<WriteLineError>) {
+    // The (potentially updated) DatabaseSchema to return to the caller.
+    let mut schema = Cow::Borrowed(schema);
+
+    // The parsed and validated table_batches
+    let mut table_batches: HashMap<String, TableBatch> = HashMap::new();
+    let mut invalid_lines = vec![];
+
+    let mut line_count = 0;
+    let mut field_count = 0;
+    let mut tag_count = 0;
+
+    for (line_idx, original_line) in split_lines(lp).enumerate() {
+        // blank lines and comments have nothing to parse
+        let Some(maybe_line) = parse_lines(original_line).next() else {
+            continue;
+        };
+
+        let result = maybe_line.map_err(|e| e.to_string()).and_then(|line| {
+            let fields = line.field_set.len();
+            let tags = line.series.tag_set.as_ref().map(|t| t.len()).unwrap_or(0);
+            validate_and_convert_parsed_line(
+                line,
+                &mut table_batches,
+                &mut schema,
+                partitioner,
+                default_time,
+            )
+            .map(|()| (fields, tags))
+            .map_err(|e| e.to_string())
+        });
+
+        match result {
+            Ok((fields, tags)) => {
+                line_count += 1;
+                field_count += fields;
+                tag_count += tags;
+            }
+            Err(error_message) => invalid_lines.push(WriteLineError {
+                original_line: original_line.to_string(),
+                line_number: line_idx + 1,
+                error_message,
+            }),
+        }
+    }
+
+    let schema = match schema {
+        Cow::Owned(s) => Some(s),
+        Cow::Borrowed(_) => None,
+    };
+
+    let result = ValidationResult {
+        schema,
+        table_batches,
+        line_count,
+        field_count,
+        tag_count,
+    };
+    (result, invalid_lines)
+}
+
 /// Takes parsed lines, validates their schema. If new tables or columns are defined, they
 /// are passed back as a new DatabaseSchema as part of the ValidationResult. Lines are split
 /// into partitions and the validation result contains the data that can then be serialized
@@ -586,6 +1348,205 @@ fn validate_and_convert_parsed_line(
     Ok(())
 }
 
+/// Validates the columns of `batch` against the schema of the table `table_name`. If the table
+/// or any of the columns do not exist they are defined in a new DatabaseSchema, passed back as
+/// part of the ValidationResult. A column that has a dictionary of strings is a tag, a column
+/// named `time` is the timestamp, and every other column is a field. Assigns the default time
+/// to any rows that do not include a time.
+pub(crate) fn validate_batch_and_update_schema(
+    table_name: &str,
+    batch: &RecordBatch,
+    schema: &DatabaseSchema,
+    partitioner: &Partitioner,
+    default_time: i64,
+) -> Result<ValidationResult> {
+    // The (potentially updated) DatabaseSchema to return to the caller.
+    let mut schema = Cow::Borrowed(schema);
+
+    // Resolve the type of each column, checking it against any existing definition, and cast
+    // the data to the arrow type used to buffer that column type.
+    let mut columns = Vec::with_capacity(batch.num_columns());
+    let mut new_cols = Vec::new();
+    {
+        let table = schema.tables.get(table_name);
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            let name = field.name();
+            let new = column_type_from_arrow(name, field.data_type())?;
+            let column_type = match table.and_then(|t| t.columns().get(name)) {
+                Some(&existing) if column_types_compatible(existing, new) => existing,
+                Some(&existing) => {
+                    return Err(Error::ColumnTypeMismatch {
+                        name: name.clone(),
+                        existing,
+                        new,
+                    })
+                }
+                None => {
+                    new_cols.push((name.clone(), new));
+                    new
+                }
+            };
+            // Values that can't be represented by the buffered type, such as a time that
+            // overflows when converted to nanoseconds, are an error rather than null.
+            let options = CastOptions {
+                safe: false,
+                ..Default::default()
+            };
+            let array = cast_with_options(array, &buffered_data_type(column_type), &options)
+                .map_err(|source| Error::ColumnConversion {
+                    name: name.clone(),
+                    source,
+                })?;
+            columns.push((name.as_str(), column_type, array));
+        }
+    }
+
+    if schema.tables.contains_key(table_name) {
+        if !new_cols.is_empty() {
+            let t = schema.to_mut().tables.get_mut(table_name).unwrap();
+            t.add_columns(new_cols);
+        }
+    } else {
+        let mut columns: BTreeMap<_, _> = new_cols.into_iter().collect();
+        columns.insert(TIME_COLUMN.to_string(), ColumnType::Time);
+        let table = TableDefinition::new(table_name, columns);
+        schema.to_mut().tables.insert(table_name.to_string(), table);
+    }
+
+    let mut table_batch = TableBatch {
+        name: table_name.to_string(),
+        ..Default::default()
+    };
+    let mut field_count = 0;
+    let mut tag_count = 0;
+
+    for row in 0..batch.num_rows() {
+        let mut time_value = default_time;
+        let mut values = Vec::with_capacity(columns.len() + 1);
+
+        for (name, column_type, array) in &columns {
+            if array.is_null(row) {
+                continue;
+            }
+            let value = match column_type {
+                ColumnType::Time => {
+                    time_value = array.as_primitive::<TimestampNanosecondType>().value(row);
+                    continue;
+                }
+                ColumnType::Tag => {
+                    tag_count += 1;
+                    FieldData::Tag(array.as_string::<i32>().value(row).to_string())
+                }
+                ColumnType::String => {
+                    FieldData::String(array.as_string::<i32>().value(row).to_string())
+                }
+                ColumnType::I64 => FieldData::Integer(array.as_primitive::<Int64Type>().value(row)),
+                ColumnType::U64 => {
+                    FieldData::UInteger(array.as_primitive::<UInt64Type>().value(row))
+                }
+                ColumnType::F64 => FieldData::Float(array.as_primitive::<Float64Type>().value(row)),
+                ColumnType::Bool => FieldData::Boolean(array.as_boolean().value(row)),
+            };
+            if !matches!(value, FieldData::Tag(_)) {
+                field_count += 1;
+            }
+            values.push(Field {
+                name: name.to_string(),
+                value,
+            });
+        }
+
+        values.push(Field {
+            name: TIME_COLUMN.to_string(),
+            value: FieldData::Timestamp(time_value),
+        });
+
+        let partition_key = partitioner.partition_key_for_time(time_value);
+        table_batch
+            .partition_batches
+            .entry(partition_key)
+            .or_default()
+            .rows
+            .push(Row {
+                time: time_value,
+                fields: values,
+            });
+    }
+
+    let schema = match schema {
+        Cow::Owned(s) => Some(s),
+        Cow::Borrowed(_) => None,
+    };
+
+    Ok(ValidationResult {
+        schema,
+        table_batches: HashMap::from([(table_name.to_string(), table_batch)]),
+        line_count: batch.num_rows(),
+        field_count,
+        tag_count,
+    })
+}
+
+/// The column type for an arrow column being written, or an error if it has no equivalent.
+fn column_type_from_arrow(name: &str, data_type: &DataType) -> Result<ColumnType> {
+    match data_type {
+        DataType::Timestamp(_, _) if name == TIME_COLUMN => Ok(ColumnType::Time),
+        _ if name == TIME_COLUMN => Err(unsupported_column_type(name, data_type)),
+        DataType::Dictionary(_, value) if value.as_ref() == &DataType::Utf8 => Ok(ColumnType::Tag),
+        DataType::Utf8 | DataType::LargeUtf8 => Ok(ColumnType::String),
+        DataType::Int64 => Ok(ColumnType::I64),
+        DataType::UInt64 => Ok(ColumnType::U64),
+        DataType::Float64 => Ok(ColumnType::F64),
+        DataType::Boolean => Ok(ColumnType::Bool),
+        _ => Err(unsupported_column_type(name, data_type)),
+    }
+}
+
+/// Encodes `batch` in the Arrow IPC streaming format to write it to the WAL.
+fn encode_wal_batch(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let mut writer = StreamWriter::try_new(Vec::new(), &batch.schema()).map_err(Error::WalBatch)?;
+    writer.write(batch).map_err(Error::WalBatch)?;
+    writer.finish().map_err(Error::WalBatch)?;
+    writer.into_inner().map_err(Error::WalBatch)
+}
+
+/// Returns the database name of a write read from the WAL segment `segment_id`.
+fn wal_db_name(segment_id: SegmentId, db_name: String) -> Result<NamespaceName<'static>> {
+    NamespaceName::new(db_name).map_err(|source| Error::WalDatabaseName { segment_id, source })
+}
+
+/// Decodes the record batches of a write read from the WAL.
+fn decode_wal_batch(data: &[u8]) -> Result<Vec<RecordBatch>> {
+    StreamReader::try_new(data, None)
+        .and_then(|reader| reader.collect::<Result<Vec<_>, ArrowError>>())
+        .map_err(Error::WalBatch)
+}
+
+fn unsupported_column_type(name: &str, data_type: &DataType) -> Error {
+    Error::UnsupportedColumnType {
+        name: name.to_string(),
+        data_type: data_type.clone(),
+    }
+}
+
+/// Strings can be written to a tag column, and dictionary encoded strings to a string field.
+fn column_types_compatible(existing: ColumnType, new: ColumnType) -> bool {
+    use ColumnType::{String, Tag};
+    existing == new || matches!((existing, new), (Tag, String) | (String, Tag))
+}
+
+/// The arrow type that the data for a column type is converted to before it is buffered.
+fn buffered_data_type(column_type: ColumnType) -> DataType {
+    match column_type {
+        ColumnType::Tag | ColumnType::String => DataType::Utf8,
+        ColumnType::I64 => DataType::Int64,
+        ColumnType::U64 => DataType::UInt64,
+        ColumnType::F64 => DataType::Float64,
+        ColumnType::Bool => DataType::Boolean,
+        ColumnType::Time => DataType::Timestamp(TimeUnit::Nanosecond, None),
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub(crate) struct TableBatch {
     #[allow(dead_code)]
@@ -661,7 +1622,11 @@ impl Partitioner {
 
     /// Given a parsed line and a default time, generate the string partition key
     pub fn partition_key_for_line(&self, line: &ParsedLine<'_>, default_time: i64) -> String {
-        let timestamp = line.timestamp.unwrap_or(default_time);
+        self.partition_key_for_time(line.timestamp.unwrap_or(default_time))
+    }
+
+    /// Given a timestamp, generate the string partition key
+    pub fn partition_key_for_time(&self, timestamp: i64) -> String {
         format!(
             "{}",
             Utc.timestamp_nanos(timestamp).format(&self.time_format)
@@ -672,6 +1637,13 @@ impl Partitioner {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::persister::PersisterImpl;
+    use crate::wal::WalImpl;
+    use futures::TryStreamExt;
+    use iox_time::MockProvider;
+    use object_store::memory::InMemory;
+    use object_store::ObjectStore;
+    use parquet_file::storage::{ParquetStorage, StorageId};
     use std::sync::Arc;
 
     #[test]
@@ -688,4 +1660,520 @@ mod tests {
         assert_eq!(db.tables.get("cpu").unwrap().columns().len(), 3);
         assert_eq!(db.tables.get("foo").unwrap().columns().len(), 2);
     }
+
+    #[test]
+    fn parse_lp_partial() {
+        let db = Arc::new(DatabaseSchema::new("foo"));
+        let partitioner = Partitioner::new_per_day_partitioner();
+        let lp = "cpu,region=west user=23.2 100\n\
+                  \n\
+                  cpu user=\n\
+                  cpu,region=east user=1.5,system=0.5 300";
+        let (result, invalid_lines) =
+            parse_validate_and_update_schema_partial(lp, &db, &partitioner, 0);
+
+        assert_eq!(result.line_count, 2);
+        assert_eq!(result.field_count, 3);
+        assert_eq!(result.tag_count, 2);
+        let rows: usize = result.table_batches["cpu"]
+            .partition_batches
+            .values()
+            .map(|b| b.rows.len())
+            .sum();
+        assert_eq!(rows, 2);
+        let db = result.schema.unwrap();
+        assert_eq!(db.tables.get("cpu").unwrap().columns().len(), 4);
+
+        let invalid: Vec<_> = invalid_lines
+            .iter()
+            .map(|e| (e.line_number, e.original_line.as_str()))
+            .collect();
+        assert_eq!(invalid, vec![(3, "cpu user=")]);
+    }
+
+    #[test]
+    fn validate_batch_into_buffer() {
+        use arrow::array::{
+            DictionaryArray, Float64Array, StringArray, TimestampMillisecondArray,
+            TimestampNanosecondArray, TimestampSecondArray,
+        };
+
+        let db = DatabaseSchema::new("foo");
+        let partitioner = Partitioner::new_per_day_partitioner();
+        let host: DictionaryArray<Int32Type> = vec![Some("a"), None].into_iter().collect();
+        let batch = RecordBatch::try_from_iter([
+            ("host", Arc::new(host) as ArrayRef),
+            ("usage", Arc::new(Float64Array::from(vec![1.0, 2.0])) as _),
+            (
+                "time",
+                Arc::new(TimestampNanosecondArray::from(vec![Some(100), None])) as _,
+            ),
+        ])
+        .unwrap();
+        let result =
+            validate_batch_and_update_schema("cpu", &batch, &db, &partitioner, 42).unwrap();
+
+        assert_eq!(result.line_count, 2);
+        assert_eq!(result.field_count, 2);
+        assert_eq!(result.tag_count, 1);
+        let db = result.schema.unwrap();
+        let cpu = db.tables.get("cpu").unwrap();
+        assert_eq!(
+            cpu.columns(),
+            &BTreeMap::from([
+                ("host".to_string(), ColumnType::Tag),
+                ("time".to_string(), ColumnType::Time),
+                ("usage".to_string(), ColumnType::F64),
+            ])
+        );
+        let rows = &result.table_batches["cpu"].partition_batches["1970-01-01"].rows;
+        assert_eq!(rows.iter().map(|r| r.time).collect::<Vec<_>>(), [100, 42]);
+
+        // plain strings can be written to an existing tag, and new columns extend the table
+        let batch = RecordBatch::try_from_iter([
+            ("host", Arc::new(StringArray::from(vec!["b"])) as ArrayRef),
+            ("region", Arc::new(StringArray::from(vec!["west"])) as _),
+        ])
+        .unwrap();
+        let result =
+            validate_batch_and_update_schema("cpu", &batch, &db, &partitioner, 42).unwrap();
+        let cpu = result.schema.unwrap().tables.remove("cpu").unwrap();
+        assert_eq!(cpu.columns()["host"], ColumnType::Tag);
+        assert_eq!(cpu.columns()["region"], ColumnType::String);
+        assert_eq!(cpu.schema.unwrap().len(), 4);
+
+        // but the type of an existing field can not change
+        let batch = RecordBatch::try_from_iter([(
+            "usage",
+            Arc::new(StringArray::from(vec!["high"])) as ArrayRef,
+        )])
+        .unwrap();
+        let err =
+            validate_batch_and_update_schema("cpu", &batch, &db, &partitioner, 42).unwrap_err();
+        assert!(
+            matches!(err, Error::ColumnTypeMismatch { ref name, .. } if name == "usage"),
+            "{err}"
+        );
+
+        // a time column with a coarser unit is converted to nanoseconds
+        let batch = RecordBatch::try_from_iter([
+            ("usage", Arc::new(Float64Array::from(vec![3.0])) as ArrayRef),
+            (
+                "time",
+                Arc::new(TimestampMillisecondArray::from(vec![7])) as _,
+            ),
+        ])
+        .unwrap();
+        let result =
+            validate_batch_and_update_schema("cpu", &batch, &db, &partitioner, 42).unwrap();
+        let rows = &result.table_batches["cpu"].partition_batches["1970-01-01"].rows;
+        assert_eq!(rows[0].time, 7_000_000);
+
+        // unless it overflows
+        let batch = RecordBatch::try_from_iter([(
+            "time",
+            Arc::new(TimestampSecondArray::from(vec![i64::MAX])) as ArrayRef,
+        )])
+        .unwrap();
+        let err =
+            validate_batch_and_update_schema("cpu", &batch, &db, &partitioner, 42).unwrap_err();
+        assert!(matches!(err, Error::ColumnConversion { .. }), "{err}");
+
+        // and the time column must be a timestamp
+        let batch = RecordBatch::try_from_iter([(
+            "time",
+            Arc::new(Float64Array::from(vec![1.0])) as ArrayRef,
+        )])
+        .unwrap();
+        let err =
+            validate_batch_and_update_schema("mem", &batch, &db, &partitioner, 42).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedColumnType { .. }), "{err}");
+    }
+
+    #[tokio::test]
+    async fn close_and_persist_segment() {
+        let catalog = Arc::new(Catalog::new());
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(MockProvider::new(Time::MIN));
+        let write_buffer =
+            WriteBufferImpl::<WalImpl>::new(Arc::clone(&catalog), None, Arc::clone(&time_provider));
+        let db_name = NamespaceName::new("foo").unwrap();
+        write_buffer
+            .write_lp(
+                db_name.clone(),
+                "cpu,host=a usage=1 10\ncpu,host=b usage=2 20",
+                0,
+            )
+            .await
+            .unwrap();
+
+        let segment = write_buffer.close_open_segment();
+        assert_eq!(segment.id(), SegmentId::new(0));
+
+        // writes after closing go into the next segment
+        let result = write_buffer
+            .write_lp(db_name, "cpu,host=a usage=3 30", 0)
+            .await
+            .unwrap();
+        assert_eq!(result.segment_id, SegmentId::new(1));
+
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let persister = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+        segment.persist(Arc::clone(&persister) as _).await.unwrap();
+
+        let persisted_catalog = persister.load_catalog().await.unwrap().unwrap();
+        assert_eq!(persisted_catalog.segment_id, SegmentId::new(0));
+        assert_eq!(persisted_catalog.catalog, catalog.clone_inner());
+
+        let segments = persister.load_segments(10).await.unwrap();
+        assert_eq!(segments.len(), 1);
+        let persisted = &segments[0];
+        assert_eq!(persisted.segment_row_count, 2);
+        assert_eq!(persisted.segment_min_time, 10);
+        assert_eq!(persisted.segment_max_time, 20);
+
+        let files = &persisted.databases["foo"].tables["cpu"].parquet_files;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "dbs/foo/cpu/1970-01-01/0000000000.parquet");
+        let meta = object_store
+            .head(&object_store::path::Path::from(files[0].path.as_str()))
+            .await
+            .unwrap();
+        assert_eq!(meta.size as u64, files[0].size_bytes);
+    }
+
+    #[tokio::test]
+    async fn load_continues_from_persisted_state() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(MockProvider::new(Time::MIN));
+        let persister: Arc<dyn Persister> = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+        let load = || {
+            WriteBufferImpl::<WalImpl>::load(
+                Arc::clone(&persister),
+                parquet_store.object_store_url(),
+                None,
+                Arc::clone(&time_provider),
+            )
+        };
+        let db_name = NamespaceName::new("foo").unwrap();
+
+        // each restart writes into, and persists, the segment after the last persisted one
+        for (segment_id, lp) in [(0, "cpu,host=a usage=1 10"), (1, "cpu,host=b usage=2 20")] {
+            let write_buffer = load().await.unwrap();
+            let result = write_buffer.write_lp(db_name.clone(), lp, 0).await.unwrap();
+            assert_eq!(result.segment_id, SegmentId::new(segment_id));
+
+            let segment = write_buffer.close_open_segment();
+            segment.persist(Arc::clone(&persister)).await.unwrap();
+        }
+
+        let segments = persister.load_segments(10).await.unwrap();
+        let ids: Vec<_> = segments.iter().map(|s| s.segment_id).collect();
+        assert_eq!(ids, vec![SegmentId::new(1), SegmentId::new(0)]);
+        for segment in &segments {
+            assert_eq!(segment.segment_row_count, 1);
+        }
+
+        // after another restart the catalog is restored and the persisted data is queryable
+        let write_buffer = load().await.unwrap();
+        let db_schema = write_buffer.catalog().db_schema("foo").unwrap();
+        let schema = db_schema.get_table_schema("cpu").unwrap();
+        assert_eq!(schema.len(), 3);
+
+        let ctx = parquet_store.test_df_context();
+        let chunks = write_buffer
+            .get_table_chunks("foo", "cpu", &[], None, &ctx.state())
+            .unwrap();
+        assert_eq!(chunks.len(), 2);
+
+        let mut rows = 0;
+        for chunk in chunks {
+            assert_eq!(chunk.chunk_type(), "ParquetChunk");
+            let batches = chunk.data().read_to_batches(chunk.schema(), &ctx).await;
+            rows += batches.iter().map(|b| b.num_rows()).sum::<usize>();
+        }
+        assert_eq!(rows, 2);
+    }
+
+    #[tokio::test]
+    async fn writes_are_replayed_from_the_wal() {
+        use arrow::array::{Float64Array, TimestampMillisecondArray};
+
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(MockProvider::new(Time::MIN));
+        let persister: Arc<dyn Persister> = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+        let wal_dir = test_helpers::tmp_dir().unwrap().into_path();
+        let load = || {
+            WriteBufferImpl::load(
+                Arc::clone(&persister),
+                parquet_store.object_store_url(),
+                Some(Arc::new(WalImpl::new(wal_dir.clone()).unwrap())),
+                Arc::clone(&time_provider),
+            )
+        };
+        let buffered_times = |write_buffer: &WriteBufferImpl<WalImpl>| {
+            let mut times: Vec<_> = write_buffer
+                .clone_table_buffer("foo", "cpu")
+                .map(|table_buffer| {
+                    table_buffer
+                        .partition_buffers
+                        .values()
+                        .flat_map(|p| p.rows.iter().map(|r| r.time).collect::<Vec<_>>())
+                        .collect()
+                })
+                .unwrap_or_default();
+            times.sort_unstable();
+            times
+        };
+        let db_name = NamespaceName::new("foo").unwrap();
+
+        // line protocol, partial line protocol and record batch writes are all written to the WAL
+        let write_buffer = load().await.unwrap();
+        write_buffer
+            .write_lp(db_name.clone(), "cpu,host=a usage=1 10", 0)
+            .await
+            .unwrap();
+        write_buffer
+            .write_lp_partial(db_name.clone(), "cpu,host=b usage=2 20\ncpu usage=", 0)
+            .await
+            .unwrap();
+        let batch = RecordBatch::try_from_iter([
+            ("usage", Arc::new(Float64Array::from(vec![3.0])) as ArrayRef),
+            (
+                "time",
+                Arc::new(TimestampMillisecondArray::from(vec![1])) as _,
+            ),
+        ])
+        .unwrap();
+        write_buffer
+            .write_batch(db_name.clone(), "cpu", &batch, 0)
+            .await
+            .unwrap();
+        drop(write_buffer);
+
+        // and are replayed into the open segment when the segment was not persisted
+        let write_buffer = load().await.unwrap();
+        assert_eq!(buffered_times(&write_buffer), [10, 20, 1_000_000]);
+        let db_schema = write_buffer.catalog().db_schema("foo").unwrap();
+        assert_eq!(db_schema.get_table_schema("cpu").unwrap().len(), 3);
+
+        // later writes are appended to the WAL of the same segment
+        let result = write_buffer
+            .write_lp(db_name, "cpu,host=c usage=4 30", 0)
+            .await
+            .unwrap();
+        assert_eq!(result.segment_id, SegmentId::new(0));
+        drop(write_buffer);
+
+        let write_buffer = load().await.unwrap();
+        assert_eq!(buffered_times(&write_buffer), [10, 20, 30, 1_000_000]);
+
+        // once the segment is persisted its WAL segment is not replayed again
+        let segment = write_buffer.close_open_segment();
+        segment.persist(Arc::clone(&persister)).await.unwrap();
+        drop(write_buffer);
+
+        let write_buffer = load().await.unwrap();
+        assert!(buffered_times(&write_buffer).is_empty());
+        let wal = WalImpl::new(wal_dir.clone()).unwrap();
+        assert!(wal.segment_files().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn wal_writes_with_invalid_database_names_fail_the_replay() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let persister: Arc<dyn Persister> = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+        let wal_dir = test_helpers::tmp_dir().unwrap().into_path();
+
+        let wal = WalImpl::new(wal_dir.clone()).unwrap();
+        let mut writer = Wal::open_segment_writer(&wal, SegmentId::new(0)).unwrap();
+        writer
+            .write_batch(vec![WalOp::LpWrite(LpWriteOp {
+                db_name: "".to_string(),
+                lp: "cpu usage=1 10".to_string(),
+                default_time: 0,
+            })])
+            .unwrap();
+        drop(writer);
+
+        let err = WriteBufferImpl::load(
+            persister,
+            parquet_store.object_store_url(),
+            Some(Arc::new(wal)),
+            Arc::new(MockProvider::new(Time::MIN)),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::WriteBuffer(Error::WalDatabaseName { segment_id, .. })
+                    if segment_id == SegmentId::new(0)
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn writes_are_validated_again_after_catalog_changes() {
+        let write_buffer = WriteBufferImpl::<WalImpl>::new(
+            Arc::new(Catalog::new()),
+            None,
+            Arc::new(MockProvider::new(Time::MIN)),
+        );
+        let db_name = NamespaceName::new("foo").unwrap();
+
+        let mut validations = 0;
+        let result = write_buffer
+            .validate_and_buffer_write(db_name, None, |db| {
+                validations += 1;
+                // another database is created after the write is validated the first time
+                if validations == 1 {
+                    write_buffer.catalog.create_database("bar").unwrap();
+                }
+                parse_validate_and_update_schema(
+                    "cpu usage=1 10",
+                    db,
+                    &Partitioner::new_per_day_partitioner(),
+                    0,
+                )
+            })
+            .unwrap();
+
+        assert_eq!(validations, 2);
+        assert_eq!(result.line_count, 1);
+        let db_schema = write_buffer.catalog.db_schema("foo").unwrap();
+        assert_eq!(db_schema.get_table_schema("cpu").unwrap().len(), 2);
+        assert_eq!(
+            write_buffer
+                .clone_table_buffer("foo", "cpu")
+                .unwrap()
+                .partition_buffers
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn database_changes_are_persisted() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(MockProvider::new(Time::MIN));
+        let persister: Arc<dyn Persister> = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+        let load = || async {
+            WriteBufferImpl::<WalImpl>::load(
+                Arc::clone(&persister),
+                parquet_store.object_store_url(),
+                None,
+                Arc::clone(&time_provider),
+            )
+            .await
+            .unwrap()
+        };
+
+        let write_buffer = load().await;
+        write_buffer.create_database("bar").await.unwrap();
+        write_buffer
+            .write_lp(NamespaceName::new("foo").unwrap(), "cpu usage=1 10", 0)
+            .await
+            .unwrap();
+        let segment = write_buffer.close_open_segment();
+        segment.persist(Arc::clone(&persister)).await.unwrap();
+
+        // a new database survives a restart before the open segment is persisted
+        let write_buffer = load().await;
+        write_buffer.create_database("baz").await.unwrap();
+        let write_buffer = load().await;
+        let mut db_names = write_buffer.catalog().db_names();
+        db_names.sort();
+        assert_eq!(db_names, ["bar", "baz", "foo"]);
+
+        // deleting a database deletes its persisted files
+        write_buffer.delete_database("foo").await.unwrap();
+        let files: Vec<_> = object_store
+            .list(None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(
+            files
+                .iter()
+                .all(|meta| !meta.location.as_ref().starts_with("dbs/foo/")),
+            "{files:?}"
+        );
+        let segments = persister.load_segments(10).await.unwrap();
+        assert_eq!(segments.len(), 1);
+        assert!(segments[0].databases.is_empty());
+        assert_eq!(segments[0].segment_row_count, 0);
+
+        let write_buffer = load().await;
+        assert!(write_buffer.catalog().db_schema("foo").is_none());
+    }
+
+    #[tokio::test]
+    async fn continuous_query_changes_are_persisted() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let parquet_store =
+            ParquetStorage::new(Arc::clone(&object_store), StorageId::from("influxdb3"));
+        let time_provider: Arc<dyn TimeProvider> = Arc::new(MockProvider::new(Time::MIN));
+        let persister: Arc<dyn Persister> = Arc::new(PersisterImpl::new(Arc::clone(&object_store)));
+        let load = || async {
+            WriteBufferImpl::<WalImpl>::load(
+                Arc::clone(&persister),
+                parquet_store.object_store_url(),
+                None,
+                Arc::clone(&time_provider),
+            )
+            .await
+            .unwrap()
+        };
+        let hour = 3_600_000_000_000;
+        let cq = ContinuousQueryDefinition {
+            name: "cq".to_string(),
+            database: "foo".to_string(),
+            query: "CREATE CONTINUOUS QUERY cq ON foo BEGIN \
+                    SELECT mean(usage) INTO cpu_1h FROM cpu GROUP BY time(1h) END"
+                .to_string(),
+            interval_ns: hour,
+            every_ns: hour,
+            for_ns: hour,
+            last_run_ns: None,
+        };
+
+        let write_buffer = load().await;
+        write_buffer.create_database("foo").await.unwrap();
+        write_buffer
+            .create_continuous_query(cq.clone())
+            .await
+            .unwrap();
+        write_buffer
+            .set_continuous_query_last_run("foo", "cq", hour)
+            .await
+            .unwrap();
+
+        // the query and its last run survive a restart before the open segment is persisted
+        let write_buffer = load().await;
+        assert_eq!(
+            write_buffer.catalog().continuous_queries(),
+            [ContinuousQueryDefinition {
+                last_run_ns: Some(hour),
+                ..cq
+            }]
+        );
+
+        write_buffer
+            .drop_continuous_query("foo", "cq")
+            .await
+            .unwrap();
+        let write_buffer = load().await;
+        assert!(write_buffer.catalog().continuous_queries().is_empty());
+    }
 }
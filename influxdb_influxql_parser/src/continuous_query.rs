@@ -0,0 +1,323 @@
+//! Types and parsers for the [`CREATE CONTINUOUS QUERY`][create], `DROP CONTINUOUS QUERY`
+//! and `SHOW CONTINUOUS QUERIES` statements.
+//!
+//! [create]: https://docs.influxdata.com/influxdb/v1.8/query_language/continuous_queries/
+
+use crate::common::{ws0, ws1};
+use crate::identifier::{identifier, Identifier};
+use crate::internal::{expect, verify, ParseResult};
+use crate::keywords::keyword;
+use crate::literal::{duration, Duration};
+use crate::select::{select_statement, SelectStatement};
+use crate::show::{on_clause, OnClause};
+use nom::combinator::{map, opt};
+use nom::sequence::{pair, preceded, tuple};
+use std::fmt::{Display, Formatter};
+
+/// Represents a `CREATE CONTINUOUS QUERY` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateContinuousQueryStatement {
+    /// The name of the continuous query.
+    pub name: Identifier,
+
+    /// The database the continuous query is created in.
+    pub database: OnClause,
+
+    /// How often the query is run, specified by `RESAMPLE EVERY`. If `None`,
+    /// the query runs at the interval of the `GROUP BY time()` clause.
+    pub resample_every: Option<Duration>,
+
+    /// The time range covered by each run, specified by `RESAMPLE FOR`. If `None`,
+    /// each run covers the interval of the `GROUP BY time()` clause.
+    pub resample_for: Option<Duration>,
+
+    /// The `SELECT ... INTO` statement run by the continuous query.
+    pub source: Box<SelectStatement>,
+}
+
+impl Display for CreateContinuousQueryStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CREATE CONTINUOUS QUERY {} {}", self.name, self.database)?;
+
+        if self.resample_every.is_some() || self.resample_for.is_some() {
+            f.write_str(" RESAMPLE")?;
+
+            if let Some(v) = self.resample_every {
+                write!(f, " EVERY {v}")?;
+            }
+
+            if let Some(v) = self.resample_for {
+                write!(f, " FOR {v}")?;
+            }
+        }
+
+        write!(f, " BEGIN {} END", self.source)
+    }
+}
+
+/// Parse a `CREATE CONTINUOUS QUERY` statement, starting from the `CONTINUOUS` token.
+pub(crate) fn create_continuous_query(
+    i: &str,
+) -> ParseResult<&str, CreateContinuousQueryStatement> {
+    let (
+        remaining,
+        (
+            _, // "CONTINUOUS QUERY"
+            name,
+            database,
+            resample,
+            _, // "BEGIN"
+            source,
+            _, // "END"
+        ),
+    ) = tuple((
+        continuous_query,
+        expect(
+            "invalid CREATE CONTINUOUS QUERY statement, expected identifier",
+            identifier,
+        ),
+        expect(
+            "invalid CREATE CONTINUOUS QUERY statement, expected ON clause",
+            preceded(ws1, on_clause),
+        ),
+        opt(preceded(pair(ws1, keyword("RESAMPLE")), resample_clause)),
+        expect(
+            "invalid CREATE CONTINUOUS QUERY statement, expected BEGIN",
+            preceded(ws1, keyword("BEGIN")),
+        ),
+        expect(
+            "invalid CREATE CONTINUOUS QUERY statement, expected SELECT statement",
+            preceded(ws1, select_statement),
+        ),
+        expect(
+            "invalid CREATE CONTINUOUS QUERY statement, expected END",
+            preceded(ws0, keyword("END")),
+        ),
+    ))(i)?;
+
+    let (resample_every, resample_for) = resample.unwrap_or_default();
+
+    Ok((
+        remaining,
+        CreateContinuousQueryStatement {
+            name,
+            database,
+            resample_every,
+            resample_for,
+            source: Box::new(source),
+        },
+    ))
+}
+
+/// Parse the `EVERY` and `FOR` options of a `RESAMPLE` clause, of which at least one
+/// must be present.
+fn resample_clause(i: &str) -> ParseResult<&str, (Option<Duration>, Option<Duration>)> {
+    verify(
+        "invalid RESAMPLE clause, expected EVERY or FOR",
+        pair(
+            opt(preceded(
+                pair(ws1, keyword("EVERY")),
+                expect(
+                    "invalid EVERY clause, expected duration",
+                    preceded(ws1, duration),
+                ),
+            )),
+            opt(preceded(
+                pair(ws1, keyword("FOR")),
+                expect(
+                    "invalid FOR clause, expected duration",
+                    preceded(ws1, duration),
+                ),
+            )),
+        ),
+        |(every, for_)| every.is_some() || for_.is_some(),
+    )(i)
+}
+
+/// Represents a `DROP CONTINUOUS QUERY` statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropContinuousQueryStatement {
+    /// The name of the continuous query.
+    pub name: Identifier,
+
+    /// The database of the continuous query.
+    pub database: OnClause,
+}
+
+impl Display for DropContinuousQueryStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DROP CONTINUOUS QUERY {} {}", self.name, self.database)
+    }
+}
+
+/// Parse a `DROP CONTINUOUS QUERY` statement, starting from the `CONTINUOUS` token.
+pub(crate) fn drop_continuous_query(i: &str) -> ParseResult<&str, DropContinuousQueryStatement> {
+    map(
+        tuple((
+            continuous_query,
+            expect(
+                "invalid DROP CONTINUOUS QUERY statement, expected identifier",
+                identifier,
+            ),
+            expect(
+                "invalid DROP CONTINUOUS QUERY statement, expected ON clause",
+                preceded(ws1, on_clause),
+            ),
+        )),
+        |(_, name, database)| DropContinuousQueryStatement { name, database },
+    )(i)
+}
+
+/// Parse the `CONTINUOUS QUERY` tokens.
+fn continuous_query(i: &str) -> ParseResult<&str, ()> {
+    map(
+        pair(
+            keyword("CONTINUOUS"),
+            expect(
+                "invalid CONTINUOUS QUERY statement, expected QUERY",
+                preceded(ws1, keyword("QUERY")),
+            ),
+        ),
+        |_| (),
+    )(i)
+}
+
+/// Represents a `SHOW CONTINUOUS QUERIES` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowContinuousQueriesStatement;
+
+impl Display for ShowContinuousQueriesStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SHOW CONTINUOUS QUERIES")
+    }
+}
+
+/// Parse a `SHOW CONTINUOUS QUERIES` statement, starting from the `CONTINUOUS` token.
+pub(crate) fn show_continuous_queries(
+    i: &str,
+) -> ParseResult<&str, ShowContinuousQueriesStatement> {
+    map(
+        pair(
+            keyword("CONTINUOUS"),
+            expect(
+                "invalid SHOW CONTINUOUS QUERIES statement, expected QUERIES",
+                preceded(ws1, keyword("QUERIES")),
+            ),
+        ),
+        |_| ShowContinuousQueriesStatement,
+    )(i)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assert_expect_error;
+
+    #[test]
+    fn test_create_continuous_query() {
+        let (rem, got) = create_continuous_query(
+            r#"CONTINUOUS QUERY cq_1h ON telegraf BEGIN SELECT mean(*) INTO "rollup"."cpu_1h" FROM cpu GROUP BY time(1h), * END"#,
+        )
+        .unwrap();
+        assert_eq!(rem, "");
+        assert_eq!(got.name, "cq_1h".into());
+        assert_eq!(*got.database, "telegraf".into());
+        assert_eq!(got.resample_every, None);
+        assert_eq!(got.resample_for, None);
+        assert_eq!(
+            got.to_string(),
+            "CREATE CONTINUOUS QUERY cq_1h ON telegraf BEGIN SELECT mean(*) INTO rollup.cpu_1h FROM cpu GROUP BY TIME(60m), * END"
+        );
+
+        let (_, got) = create_continuous_query(
+            "CONTINUOUS QUERY cq ON db RESAMPLE EVERY 30m BEGIN SELECT count(v) INTO c FROM m GROUP BY time(1h) END",
+        )
+        .unwrap();
+        assert_eq!(got.resample_every.unwrap().to_string(), "30m");
+        assert_eq!(got.resample_for, None);
+        assert_eq!(
+            got.to_string(),
+            "CREATE CONTINUOUS QUERY cq ON db RESAMPLE EVERY 30m BEGIN SELECT count(v) INTO c FROM m GROUP BY TIME(60m) END"
+        );
+
+        let (_, got) = create_continuous_query(
+            "CONTINUOUS QUERY cq ON db RESAMPLE FOR 2h BEGIN SELECT count(v) INTO c FROM m GROUP BY time(1h) END",
+        )
+        .unwrap();
+        assert_eq!(got.resample_every, None);
+        assert_eq!(got.resample_for.unwrap().to_string(), "2h");
+
+        let (_, got) = create_continuous_query(
+            "CONTINUOUS QUERY cq ON db RESAMPLE EVERY 30m FOR 2h BEGIN SELECT count(v) INTO c FROM m GROUP BY time(1h) END",
+        )
+        .unwrap();
+        assert_eq!(
+            got.to_string(),
+            "CREATE CONTINUOUS QUERY cq ON db RESAMPLE EVERY 30m FOR 2h BEGIN SELECT count(v) INTO c FROM m GROUP BY TIME(60m) END"
+        );
+
+        // Fallible cases
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS foo"),
+            "invalid CONTINUOUS QUERY statement, expected QUERY"
+        );
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS QUERY cq BEGIN"),
+            "invalid CREATE CONTINUOUS QUERY statement, expected ON clause"
+        );
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS QUERY cq ON db RESAMPLE BEGIN"),
+            "invalid RESAMPLE clause, expected EVERY or FOR"
+        );
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS QUERY cq ON db RESAMPLE EVERY foo"),
+            "invalid EVERY clause, expected duration"
+        );
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS QUERY cq ON db SELECT"),
+            "invalid CREATE CONTINUOUS QUERY statement, expected BEGIN"
+        );
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS QUERY cq ON db BEGIN foo"),
+            "invalid CREATE CONTINUOUS QUERY statement, expected SELECT statement"
+        );
+
+        assert_expect_error!(
+            create_continuous_query("CONTINUOUS QUERY cq ON db BEGIN SELECT v INTO c FROM m"),
+            "invalid CREATE CONTINUOUS QUERY statement, expected END"
+        );
+    }
+
+    #[test]
+    fn test_drop_continuous_query() {
+        let (rem, got) = drop_continuous_query("CONTINUOUS QUERY cq_1h ON telegraf").unwrap();
+        assert_eq!(rem, "");
+        assert_eq!(got.name, "cq_1h".into());
+        assert_eq!(got.to_string(), "DROP CONTINUOUS QUERY cq_1h ON telegraf");
+
+        // Fallible cases
+        assert_expect_error!(
+            drop_continuous_query("CONTINUOUS QUERY cq_1h"),
+            "invalid DROP CONTINUOUS QUERY statement, expected ON clause"
+        );
+    }
+
+    #[test]
+    fn test_show_continuous_queries() {
+        let (rem, got) = show_continuous_queries("CONTINUOUS QUERIES").unwrap();
+        assert_eq!(rem, "");
+        assert_eq!(got.to_string(), "SHOW CONTINUOUS QUERIES");
+
+        // Fallible cases
+        assert_expect_error!(
+            show_continuous_queries("CONTINUOUS foo"),
+            "invalid SHOW CONTINUOUS QUERIES statement, expected QUERIES"
+        );
+    }
+}
@@ -3,6 +3,7 @@
 //! [sql]: https://docs.influxdata.com/influxdb/v1.8/query_language/manage-database/#create-database
 
 use crate::common::ws1;
+use crate::continuous_query::create_continuous_query;
 use crate::identifier::{identifier, Identifier};
 use crate::internal::{expect, ParseResult};
 use crate::keywords::keyword;
@@ -17,8 +18,13 @@ pub(crate) fn create_statement(i: &str) -> ParseResult<&str, Statement> {
     preceded(
         pair(keyword("CREATE"), ws1),
         expect(
-            "Invalid CREATE statement, expected DATABASE following CREATE",
-            map(create_database, |s| Statement::CreateDatabase(Box::new(s))),
+            "Invalid CREATE statement, expected CONTINUOUS or DATABASE following CREATE",
+            alt((
+                map(create_continuous_query, |s| {
+                    Statement::CreateContinuousQuery(Box::new(s))
+                }),
+                map(create_database, |s| Statement::CreateDatabase(Box::new(s))),
+            )),
         ),
     )(i)
 }
@@ -165,6 +171,16 @@ mod test {
     #[test]
     fn test_create_statement() {
         create_statement("CREATE DATABASE telegraf").unwrap();
+        create_statement(
+            "CREATE CONTINUOUS QUERY cq ON telegraf BEGIN SELECT mean(v) INTO m_1h FROM m GROUP BY time(1h) END",
+        )
+        .unwrap();
+
+        // Fallible cases
+        assert_expect_error!(
+            create_statement("CREATE foo"),
+            "Invalid CREATE statement, expected CONTINUOUS or DATABASE following CREATE"
+        );
     }
 
     #[test]
@@ -3,9 +3,12 @@
 //! [sql]: https://docs.influxdata.com/influxdb/v1.8/query_language/manage-database/#delete-measurements-with-drop-measurement
 
 use crate::common::ws1;
+use crate::continuous_query::drop_continuous_query;
 use crate::identifier::{identifier, Identifier};
 use crate::internal::{expect, ParseResult};
 use crate::keywords::keyword;
+use crate::statement::Statement;
+use nom::branch::alt;
 use nom::combinator::map;
 use nom::sequence::{pair, preceded};
 use std::fmt::{Display, Formatter};
@@ -23,12 +26,19 @@ impl Display for DropMeasurementStatement {
     }
 }
 
-pub(crate) fn drop_statement(i: &str) -> ParseResult<&str, DropMeasurementStatement> {
+pub(crate) fn drop_statement(i: &str) -> ParseResult<&str, Statement> {
     preceded(
         pair(keyword("DROP"), ws1),
         expect(
-            "invalid DROP statement, expected MEASUREMENT",
-            drop_measurement,
+            "invalid DROP statement, expected CONTINUOUS or MEASUREMENT",
+            alt((
+                map(drop_continuous_query, |s| {
+                    Statement::DropContinuousQuery(Box::new(s))
+                }),
+                map(drop_measurement, |s| {
+                    Statement::DropMeasurement(Box::new(s))
+                }),
+            )),
         ),
     )(i)
 }
@@ -54,11 +64,12 @@ mod test {
     #[test]
     fn test_drop_statement() {
         drop_statement("DROP MEASUREMENT foo").unwrap();
+        drop_statement("DROP CONTINUOUS QUERY cq ON foo").unwrap();
 
         // Fallible cases
         assert_expect_error!(
             drop_statement("DROP foo"),
-            "invalid DROP statement, expected MEASUREMENT"
+            "invalid DROP statement, expected CONTINUOUS or MEASUREMENT"
         );
     }
 
@@ -42,7 +42,9 @@ pub fn is_aggregate_function(name: &str) -> bool {
             "sample",
             "top",
             // Aggregate functions
+            "approx_percentile",
             "count",
+            "count_hll",
             "integral",
             "mean",
             "median",
@@ -50,6 +52,7 @@ pub fn is_aggregate_function(name: &str) -> bool {
             "spread",
             "stddev",
             "sum",
+            "sum_hll",
             // Prediction functions
             "holt_winters",
             "holt_winters_with_fit",
@@ -30,6 +30,7 @@ use nom::Offset;
 mod test_util;
 
 pub mod common;
+pub mod continuous_query;
 pub mod create;
 pub mod delete;
 pub mod drop;
@@ -43,9 +44,11 @@ pub mod literal;
 pub mod parameter;
 pub mod select;
 pub mod show;
+pub mod show_cardinality;
 pub mod show_field_keys;
 pub mod show_measurements;
 pub mod show_retention_policies;
+pub mod show_series;
 pub mod show_tag_keys;
 pub mod show_tag_values;
 pub mod simple_from_clause;
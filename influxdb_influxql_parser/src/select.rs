@@ -25,7 +25,7 @@ use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::char;
 use nom::combinator::{map, opt, value};
-use nom::sequence::{delimited, pair, preceded, tuple};
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::Offset;
 use std::fmt;
 use std::fmt::{Display, Formatter, Write};
@@ -37,6 +37,10 @@ pub struct SelectStatement {
     /// Expressions returned by the selection.
     pub fields: FieldList,
 
+    /// The destination of the selection, when results are written back
+    /// to a measurement rather than returned to the client.
+    pub into: Option<IntoClause>,
+
     /// A list of measurements or subqueries used as the source data for the selection.
     pub from: FromMeasurementClause,
 
@@ -84,7 +88,13 @@ impl SelectStatement {
 
 impl Display for SelectStatement {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "SELECT {} {}", self.fields, self.from)?;
+        write!(f, "SELECT {}", self.fields)?;
+
+        if let Some(into_clause) = &self.into {
+            write!(f, " {into_clause}")?;
+        }
+
+        write!(f, " {}", self.from)?;
 
         if let Some(where_clause) = &self.condition {
             write!(f, " {where_clause}")?;
@@ -133,6 +143,7 @@ pub(crate) fn select_statement(i: &str) -> ParseResult<&str, SelectStatement> {
             _, // SELECT
             _, // whitespace
             fields,
+            into,
             from,
             condition,
             group_by,
@@ -148,6 +159,7 @@ pub(crate) fn select_statement(i: &str) -> ParseResult<&str, SelectStatement> {
         keyword("SELECT"),
         ws0,
         field_list,
+        opt(preceded(ws0, into_clause)),
         preceded(ws0, from_clause),
         opt(preceded(ws0, where_clause)),
         opt(preceded(ws0, group_by_clause)),
@@ -164,6 +176,7 @@ pub(crate) fn select_statement(i: &str) -> ParseResult<&str, SelectStatement> {
         remaining,
         SelectStatement {
             fields,
+            into,
             from,
             condition,
             group_by,
@@ -178,6 +191,104 @@ pub(crate) fn select_statement(i: &str) -> ParseResult<&str, SelectStatement> {
     ))
 }
 
+/// Represents the target measurement of an `INTO` clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntoMeasurement {
+    /// Results are written to the named measurement.
+    Name(Identifier),
+
+    /// Results are written to a measurement with the same name as the
+    /// source measurement, specified as `:MEASUREMENT`.
+    Backreference,
+}
+
+impl Display for IntoMeasurement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => Display::fmt(name, f),
+            Self::Backreference => f.write_str(":MEASUREMENT"),
+        }
+    }
+}
+
+/// Represents an `INTO` clause for a `SELECT` statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntoClause {
+    /// An optional database name.
+    pub database: Option<Identifier>,
+
+    /// An optional retention policy.
+    pub retention_policy: Option<Identifier>,
+
+    /// The target measurement.
+    pub measurement: IntoMeasurement,
+}
+
+impl Display for IntoClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("INTO ")?;
+        match (&self.database, &self.retention_policy) {
+            (None, None) => {}
+            (Some(db), None) => write!(f, "{db}..")?,
+            (None, Some(rp)) => write!(f, "{rp}.")?,
+            (Some(db), Some(rp)) => write!(f, "{db}.{rp}.")?,
+        }
+        Display::fmt(&self.measurement, f)
+    }
+}
+
+/// Parse an `INTO` clause.
+///
+/// ```text
+/// into_clause      ::= "INTO" ( measurement_name |
+///                               ( policy_name "." measurement_name ) |
+///                               ( db_name "." policy_name? "." measurement_name ) )
+///
+/// measurement_name ::= identifier | ":MEASUREMENT"
+/// ```
+fn into_clause(i: &str) -> ParseResult<&str, IntoClause> {
+    let (remaining, (opt_db_rp, measurement)) = preceded(
+        pair(keyword("INTO"), ws1),
+        expect(
+            "invalid INTO clause, expected measurement name or :MEASUREMENT",
+            pair(
+                opt(alt((
+                    // database "." retention_policy "."
+                    map(
+                        pair(
+                            terminated(identifier, tag(".")),
+                            terminated(identifier, tag(".")),
+                        ),
+                        |(db, rp)| (Some(db), Some(rp)),
+                    ),
+                    // database ".."
+                    map(terminated(identifier, tag("..")), |db| (Some(db), None)),
+                    // retention_policy "."
+                    map(terminated(identifier, tag(".")), |rp| (None, Some(rp))),
+                ))),
+                alt((
+                    value(
+                        IntoMeasurement::Backreference,
+                        pair(char(':'), keyword("MEASUREMENT")),
+                    ),
+                    map(identifier, IntoMeasurement::Name),
+                )),
+            ),
+        ),
+    )(i)?;
+
+    let (database, retention_policy) = opt_db_rp.unwrap_or((None, None));
+
+    Ok((
+        remaining,
+        IntoClause {
+            database,
+            retention_policy,
+            measurement,
+        },
+    ))
+}
+
 /// Represents a single measurement selection for a `FROM` clause.
 #[derive(Clone, Debug, PartialEq)]
 pub enum MeasurementSelection {
@@ -938,6 +1049,49 @@ mod test {
         assert_eq!(rem, "");
     }
 
+    #[test]
+    fn test_into_clause() {
+        let (_, got) = into_clause("INTO cpu_1h").unwrap();
+        assert_eq!(got.measurement, IntoMeasurement::Name("cpu_1h".into()));
+        assert_eq!(got.to_string(), "INTO cpu_1h");
+
+        let (_, got) = into_clause(r#"INTO "rollup"."cpu_1h""#).unwrap();
+        assert_eq!(got.retention_policy, Some("rollup".into()));
+        assert_eq!(got.to_string(), "INTO rollup.cpu_1h");
+
+        let (_, got) = into_clause("INTO telegraf..cpu_1h").unwrap();
+        assert_eq!(got.database, Some("telegraf".into()));
+        assert_eq!(got.to_string(), "INTO telegraf..cpu_1h");
+
+        let (_, got) = into_clause("INTO telegraf.rollup.:MEASUREMENT").unwrap();
+        assert_eq!(got.measurement, IntoMeasurement::Backreference);
+        assert_eq!(got.to_string(), "INTO telegraf.rollup.:MEASUREMENT");
+
+        let (_, got) = into_clause("INTO :measurement").unwrap();
+        assert_eq!(got.to_string(), "INTO :MEASUREMENT");
+
+        let (_, got) = select_statement(
+            "SELECT mean(*) INTO rollup.:MEASUREMENT FROM /cpu/ GROUP BY time(1h), *",
+        )
+        .unwrap();
+        assert_eq!(
+            got.to_string(),
+            "SELECT mean(*) INTO rollup.:MEASUREMENT FROM /cpu/ GROUP BY TIME(60m), *"
+        );
+
+        // Fallible cases
+
+        assert_expect_error!(
+            into_clause("INTO /cpu/"),
+            "invalid INTO clause, expected measurement name or :MEASUREMENT"
+        );
+
+        assert_expect_error!(
+            into_clause("INTO :FOO"),
+            "invalid INTO clause, expected measurement name or :MEASUREMENT"
+        );
+    }
+
     #[test]
     fn test_field() {
         // Parse a VarRef
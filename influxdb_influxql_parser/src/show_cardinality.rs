@@ -0,0 +1,313 @@
+//! Types and parsers for the [`SHOW ... CARDINALITY`][sql] statements.
+//!
+//! [sql]: https://docs.influxdata.com/influxdb/v1.8/query_language/spec/#show-series-cardinality
+
+use crate::common::{
+    limit_clause, offset_clause, where_clause, ws1, LimitClause, OffsetClause, WhereClause,
+};
+use crate::internal::{expect, ParseResult};
+use crate::keywords::keyword;
+use crate::show::{on_clause, OnClause};
+use crate::show_tag_values::{with_key_clause, WithKeyClause};
+use crate::simple_from_clause::{show_from_clause, ShowFromClause};
+use nom::combinator::{cond, map, opt};
+use nom::sequence::{pair, preceded, terminated, tuple};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// The schema object counted by a `SHOW ... CARDINALITY` statement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardinalityKind {
+    /// `SHOW SERIES CARDINALITY`
+    Series,
+    /// `SHOW MEASUREMENT CARDINALITY`
+    Measurement,
+    /// `SHOW TAG KEY CARDINALITY`
+    TagKey,
+    /// `SHOW TAG VALUES CARDINALITY`
+    TagValues,
+    /// `SHOW FIELD KEY CARDINALITY`
+    FieldKey,
+}
+
+impl Display for CardinalityKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Series => "SERIES",
+            Self::Measurement => "MEASUREMENT",
+            Self::TagKey => "TAG KEY",
+            Self::TagValues => "TAG VALUES",
+            Self::FieldKey => "FIELD KEY",
+        })
+    }
+}
+
+/// Represents one of the `SHOW ... CARDINALITY` InfluxQL statements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShowCardinalityStatement {
+    /// The schema object to count.
+    pub kind: CardinalityKind,
+
+    /// `true` if the `EXACT` keyword was specified, otherwise the
+    /// cardinality may be estimated.
+    pub exact: bool,
+
+    /// The name of the database to query. If `None`, a default
+    /// database will be used.
+    pub database: Option<OnClause>,
+
+    /// The measurement or measurements to restrict which schema objects
+    /// are counted.
+    pub from: Option<ShowFromClause>,
+
+    /// Represents the `WITH KEY` clause, to restrict the tag values to
+    /// the matching tag keys.
+    ///
+    /// Always present for [`CardinalityKind::TagValues`] and absent otherwise.
+    pub with_key: Option<WithKeyClause>,
+
+    /// A conditional expression to filter the schema objects.
+    pub condition: Option<WhereClause>,
+
+    /// A value to restrict the number of rows returned.
+    pub limit: Option<LimitClause>,
+
+    /// A value to specify an offset to start retrieving rows.
+    pub offset: Option<OffsetClause>,
+}
+
+impl Display for ShowCardinalityStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SHOW {}", self.kind)?;
+
+        if self.exact {
+            f.write_str(" EXACT")?;
+        }
+
+        f.write_str(" CARDINALITY")?;
+
+        if let Some(ref on_clause) = self.database {
+            write!(f, " {on_clause}")?;
+        }
+
+        if let Some(ref from_clause) = self.from {
+            write!(f, " {from_clause}")?;
+        }
+
+        if let Some(ref with_key) = self.with_key {
+            write!(f, " {with_key}")?;
+        }
+
+        if let Some(ref where_clause) = self.condition {
+            write!(f, " {where_clause}")?;
+        }
+
+        if let Some(ref limit) = self.limit {
+            write!(f, " {limit}")?;
+        }
+
+        if let Some(ref offset) = self.offset {
+            write!(f, " {offset}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the `[EXACT] CARDINALITY` tokens, returning `true` if `EXACT` was specified.
+fn exact_cardinality(i: &str) -> ParseResult<&str, bool> {
+    map(
+        pair(
+            opt(terminated(keyword("EXACT"), ws1)),
+            keyword("CARDINALITY"),
+        ),
+        |(exact, _)| exact.is_some(),
+    )(i)
+}
+
+/// Parse the clauses of a `SHOW ... CARDINALITY` statement that follow
+/// the `CARDINALITY` token.
+fn cardinality_clauses(
+    kind: CardinalityKind,
+    exact: bool,
+) -> impl FnMut(&str) -> ParseResult<&str, ShowCardinalityStatement> {
+    move |i| {
+        let (remaining_input, (database, from, with_key, condition, limit, offset)) = tuple((
+            opt(preceded(ws1, on_clause)),
+            opt(preceded(ws1, show_from_clause)),
+            cond(
+                kind == CardinalityKind::TagValues,
+                expect(
+                    "invalid SHOW TAG VALUES CARDINALITY statement, expected WITH KEY clause",
+                    preceded(ws1, with_key_clause),
+                ),
+            ),
+            opt(preceded(ws1, where_clause)),
+            opt(preceded(ws1, limit_clause)),
+            opt(preceded(ws1, offset_clause)),
+        ))(i)?;
+
+        Ok((
+            remaining_input,
+            ShowCardinalityStatement {
+                kind,
+                exact,
+                database,
+                from,
+                with_key,
+                condition,
+                limit,
+                offset,
+            },
+        ))
+    }
+}
+
+/// Parse a `SHOW SERIES [EXACT] CARDINALITY` statement, starting from the `SERIES` token.
+pub(crate) fn show_series_cardinality(i: &str) -> ParseResult<&str, ShowCardinalityStatement> {
+    let (i, exact) = preceded(pair(keyword("SERIES"), ws1), exact_cardinality)(i)?;
+    cardinality_clauses(CardinalityKind::Series, exact)(i)
+}
+
+/// Parse a `SHOW MEASUREMENT [EXACT] CARDINALITY` statement, starting from the `MEASUREMENT` token.
+pub(crate) fn show_measurement_cardinality(i: &str) -> ParseResult<&str, ShowCardinalityStatement> {
+    let (i, exact) = preceded(
+        pair(keyword("MEASUREMENT"), ws1),
+        expect(
+            "invalid SHOW MEASUREMENT CARDINALITY statement, expected CARDINALITY",
+            exact_cardinality,
+        ),
+    )(i)?;
+    cardinality_clauses(CardinalityKind::Measurement, exact)(i)
+}
+
+/// Parse a `SHOW TAG KEY [EXACT] CARDINALITY` statement, starting from the `KEY` token.
+pub(crate) fn show_tag_key_cardinality(i: &str) -> ParseResult<&str, ShowCardinalityStatement> {
+    let (i, exact) = preceded(pair(keyword("KEY"), ws1), exact_cardinality)(i)?;
+    cardinality_clauses(CardinalityKind::TagKey, exact)(i)
+}
+
+/// Parse a `SHOW TAG VALUES [EXACT] CARDINALITY` statement, starting from the `VALUES` token.
+pub(crate) fn show_tag_values_cardinality(i: &str) -> ParseResult<&str, ShowCardinalityStatement> {
+    let (i, exact) = preceded(pair(keyword("VALUES"), ws1), exact_cardinality)(i)?;
+    cardinality_clauses(CardinalityKind::TagValues, exact)(i)
+}
+
+/// Parse a `SHOW FIELD KEY [EXACT] CARDINALITY` statement, starting from the `FIELD` token.
+pub(crate) fn show_field_key_cardinality(i: &str) -> ParseResult<&str, ShowCardinalityStatement> {
+    let (i, exact) = preceded(
+        tuple((keyword("FIELD"), ws1, keyword("KEY"), ws1)),
+        exact_cardinality,
+    )(i)?;
+    cardinality_clauses(CardinalityKind::FieldKey, exact)(i)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assert_expect_error;
+
+    #[test]
+    fn test_show_series_cardinality() {
+        let (_, got) = show_series_cardinality("SERIES CARDINALITY").unwrap();
+        assert_eq!(got.kind, CardinalityKind::Series);
+        assert!(!got.exact);
+        assert_eq!(got.to_string(), "SHOW SERIES CARDINALITY");
+
+        let (_, got) = show_series_cardinality("SERIES EXACT CARDINALITY").unwrap();
+        assert!(got.exact);
+        assert_eq!(got.to_string(), "SHOW SERIES EXACT CARDINALITY");
+
+        // all optional clauses
+        let (_, got) = show_series_cardinality(
+            "SERIES EXACT CARDINALITY ON db FROM cpu WHERE host = 'server01' LIMIT 1 OFFSET 2",
+        )
+        .unwrap();
+        assert_eq!(
+            got.to_string(),
+            "SHOW SERIES EXACT CARDINALITY ON db FROM cpu WHERE host = 'server01' LIMIT 1 OFFSET 2"
+        );
+
+        // Not a cardinality statement
+        show_series_cardinality("SERIES FROM cpu").unwrap_err();
+        show_series_cardinality("SERIES EXACT").unwrap_err();
+    }
+
+    #[test]
+    fn test_show_measurement_cardinality() {
+        let (_, got) = show_measurement_cardinality("MEASUREMENT CARDINALITY").unwrap();
+        assert_eq!(got.kind, CardinalityKind::Measurement);
+        assert_eq!(got.to_string(), "SHOW MEASUREMENT CARDINALITY");
+
+        let (_, got) =
+            show_measurement_cardinality("MEASUREMENT EXACT CARDINALITY ON db FROM /cpu/ LIMIT 1")
+                .unwrap();
+        assert_eq!(
+            got.to_string(),
+            "SHOW MEASUREMENT EXACT CARDINALITY ON db FROM /cpu/ LIMIT 1"
+        );
+
+        // Fallible cases
+        assert_expect_error!(
+            show_measurement_cardinality("MEASUREMENT EXACT"),
+            "invalid SHOW MEASUREMENT CARDINALITY statement, expected CARDINALITY"
+        );
+    }
+
+    #[test]
+    fn test_show_tag_key_cardinality() {
+        let (_, got) = show_tag_key_cardinality("KEY CARDINALITY").unwrap();
+        assert_eq!(got.kind, CardinalityKind::TagKey);
+        assert_eq!(got.to_string(), "SHOW TAG KEY CARDINALITY");
+
+        let (_, got) =
+            show_tag_key_cardinality("KEY EXACT CARDINALITY FROM cpu WHERE host = 'a'").unwrap();
+        assert_eq!(
+            got.to_string(),
+            "SHOW TAG KEY EXACT CARDINALITY FROM cpu WHERE host = 'a'"
+        );
+    }
+
+    #[test]
+    fn test_show_tag_values_cardinality() {
+        let (_, got) = show_tag_values_cardinality("VALUES CARDINALITY WITH KEY = host").unwrap();
+        assert_eq!(got.kind, CardinalityKind::TagValues);
+        assert_eq!(
+            got.to_string(),
+            "SHOW TAG VALUES CARDINALITY WITH KEY = host"
+        );
+
+        let (_, got) = show_tag_values_cardinality(
+            "VALUES EXACT CARDINALITY ON db FROM cpu WITH KEY =~ /h/ WHERE region = 'west' LIMIT 1 OFFSET 2",
+        )
+        .unwrap();
+        assert_eq!(
+            got.to_string(),
+            "SHOW TAG VALUES EXACT CARDINALITY ON db FROM cpu WITH KEY =~ /h/ WHERE region = 'west' LIMIT 1 OFFSET 2"
+        );
+
+        // Fallible cases
+        assert_expect_error!(
+            show_tag_values_cardinality("VALUES CARDINALITY FROM cpu"),
+            "invalid SHOW TAG VALUES CARDINALITY statement, expected WITH KEY clause"
+        );
+    }
+
+    #[test]
+    fn test_show_field_key_cardinality() {
+        let (_, got) = show_field_key_cardinality("FIELD KEY CARDINALITY").unwrap();
+        assert_eq!(got.kind, CardinalityKind::FieldKey);
+        assert_eq!(got.to_string(), "SHOW FIELD KEY CARDINALITY");
+
+        let (_, got) =
+            show_field_key_cardinality("FIELD KEY EXACT CARDINALITY ON db FROM cpu OFFSET 2")
+                .unwrap();
+        assert_eq!(
+            got.to_string(),
+            "SHOW FIELD KEY EXACT CARDINALITY ON db FROM cpu OFFSET 2"
+        );
+
+        // Not a cardinality statement
+        show_field_key_cardinality("FIELD KEYS").unwrap_err();
+    }
+}
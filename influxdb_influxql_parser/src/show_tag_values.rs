@@ -169,7 +169,8 @@ fn identifier_list(i: &str) -> ParseResult<&str, InList> {
     )(i)
 }
 
-fn with_key_clause(i: &str) -> ParseResult<&str, WithKeyClause> {
+/// Parse a `WITH KEY` clause.
+pub(crate) fn with_key_clause(i: &str) -> ParseResult<&str, WithKeyClause> {
     preceded(
         tuple((
             keyword("WITH"),
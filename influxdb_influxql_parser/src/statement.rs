@@ -1,5 +1,8 @@
 //! Types and parsers for an InfluxQL statement.
 
+use crate::continuous_query::{
+    CreateContinuousQueryStatement, DropContinuousQueryStatement, ShowContinuousQueriesStatement,
+};
 use crate::create::{create_statement, CreateDatabaseStatement};
 use crate::delete::{delete_statement, DeleteStatement};
 use crate::drop::{drop_statement, DropMeasurementStatement};
@@ -7,9 +10,11 @@ use crate::explain::{explain_statement, ExplainStatement};
 use crate::internal::ParseResult;
 use crate::select::{select_statement, SelectStatement};
 use crate::show::{show_statement, ShowDatabasesStatement};
+use crate::show_cardinality::ShowCardinalityStatement;
 use crate::show_field_keys::ShowFieldKeysStatement;
 use crate::show_measurements::ShowMeasurementsStatement;
 use crate::show_retention_policies::ShowRetentionPoliciesStatement;
+use crate::show_series::ShowSeriesStatement;
 use crate::show_tag_keys::ShowTagKeysStatement;
 use crate::show_tag_values::ShowTagValuesStatement;
 use nom::branch::alt;
@@ -19,16 +24,22 @@ use std::fmt::{Display, Formatter};
 /// An InfluxQL statement.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
+    /// Represents a `CREATE CONTINUOUS QUERY` statement.
+    CreateContinuousQuery(Box<CreateContinuousQueryStatement>),
     /// Represents a `CREATE DATABASE` statement.
     CreateDatabase(Box<CreateDatabaseStatement>),
     /// Represents a `DELETE` statement.
     Delete(Box<DeleteStatement>),
+    /// Represents a `DROP CONTINUOUS QUERY` statement.
+    DropContinuousQuery(Box<DropContinuousQueryStatement>),
     /// Represents a `DROP MEASUREMENT` statement.
     DropMeasurement(Box<DropMeasurementStatement>),
     /// Represents an `EXPLAIN` statement.
     Explain(Box<ExplainStatement>),
     /// Represents a `SELECT` statement.
     Select(Box<SelectStatement>),
+    /// Represents a `SHOW CONTINUOUS QUERIES` statement.
+    ShowContinuousQueries(Box<ShowContinuousQueriesStatement>),
     /// Represents a `SHOW DATABASES` statement.
     ShowDatabases(Box<ShowDatabasesStatement>),
     /// Represents a `SHOW MEASUREMENTS` statement.
@@ -41,22 +52,31 @@ pub enum Statement {
     ShowTagValues(Box<ShowTagValuesStatement>),
     /// Represents a `SHOW FIELD KEYS` statement.
     ShowFieldKeys(Box<ShowFieldKeysStatement>),
+    /// Represents a `SHOW SERIES` statement.
+    ShowSeries(Box<ShowSeriesStatement>),
+    /// Represents one of the `SHOW ... CARDINALITY` statements.
+    ShowCardinality(Box<ShowCardinalityStatement>),
 }
 
 impl Display for Statement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::CreateContinuousQuery(s) => Display::fmt(s, f),
             Self::CreateDatabase(s) => Display::fmt(s, f),
             Self::Delete(s) => Display::fmt(s, f),
+            Self::DropContinuousQuery(s) => Display::fmt(s, f),
             Self::DropMeasurement(s) => Display::fmt(s, f),
             Self::Explain(s) => Display::fmt(s, f),
             Self::Select(s) => Display::fmt(s, f),
+            Self::ShowContinuousQueries(s) => Display::fmt(s, f),
             Self::ShowDatabases(s) => Display::fmt(s, f),
             Self::ShowMeasurements(s) => Display::fmt(s, f),
             Self::ShowRetentionPolicies(s) => Display::fmt(s, f),
             Self::ShowTagKeys(s) => Display::fmt(s, f),
             Self::ShowTagValues(s) => Display::fmt(s, f),
             Self::ShowFieldKeys(s) => Display::fmt(s, f),
+            Self::ShowSeries(s) => Display::fmt(s, f),
+            Self::ShowCardinality(s) => Display::fmt(s, f),
         }
     }
 }
@@ -65,7 +85,7 @@ impl Display for Statement {
 pub fn statement(i: &str) -> ParseResult<&str, Statement> {
     alt((
         map(delete_statement, |s| Statement::Delete(Box::new(s))),
-        map(drop_statement, |s| Statement::DropMeasurement(Box::new(s))),
+        drop_statement,
         map(explain_statement, |s| Statement::Explain(Box::new(s))),
         map(select_statement, |s| Statement::Select(Box::new(s))),
         create_statement,
@@ -84,6 +104,11 @@ mod test {
         // create_statement combinator
         let (got, _) = statement("CREATE DATABASE foo").unwrap();
         assert_eq!(got, "");
+        let (got, _) = statement(
+            "CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(v) INTO bar FROM foo GROUP BY TIME(1h) END",
+        )
+        .unwrap();
+        assert_eq!(got, "");
 
         // delete_statement combinator
         let (got, _) = statement("DELETE FROM foo").unwrap();
@@ -92,6 +117,8 @@ mod test {
         // drop_statement combinator
         let (got, _) = statement("DROP MEASUREMENT foo").unwrap();
         assert_eq!(got, "");
+        let (got, _) = statement("DROP CONTINUOUS QUERY cq ON foo").unwrap();
+        assert_eq!(got, "");
 
         // explain_statement combinator
         let (got, _) = statement("EXPLAIN SELECT * FROM cpu").unwrap();
@@ -103,5 +130,7 @@ mod test {
         // show_statement combinator
         let (got, _) = statement("SHOW TAG KEYS").unwrap();
         assert_eq!(got, "");
+        let (got, _) = statement("SHOW CONTINUOUS QUERIES").unwrap();
+        assert_eq!(got, "");
     }
 }
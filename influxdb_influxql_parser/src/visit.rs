@@ -28,6 +28,9 @@ use crate::common::{
     LimitClause, MeasurementName, OffsetClause, OrderByClause, QualifiedMeasurementName,
     WhereClause,
 };
+use crate::continuous_query::{
+    CreateContinuousQueryStatement, DropContinuousQueryStatement, ShowContinuousQueriesStatement,
+};
 use crate::create::CreateDatabaseStatement;
 use crate::delete::DeleteStatement;
 use crate::drop::DropMeasurementStatement;
@@ -37,16 +40,18 @@ use crate::expression::conditional::ConditionalExpression;
 use crate::expression::{Binary, Call, ConditionalBinary, VarRef};
 use crate::literal::Literal;
 use crate::select::{
-    Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause,
+    Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause, IntoClause,
     MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
     TimeZoneClause,
 };
 use crate::show::{OnClause, ShowDatabasesStatement};
+use crate::show_cardinality::ShowCardinalityStatement;
 use crate::show_field_keys::ShowFieldKeysStatement;
 use crate::show_measurements::{
     ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
 };
 use crate::show_retention_policies::ShowRetentionPoliciesStatement;
+use crate::show_series::ShowSeriesStatement;
 use crate::show_tag_keys::ShowTagKeysStatement;
 use crate::show_tag_values::{ShowTagValuesStatement, WithKeyClause};
 use crate::simple_from_clause::{DeleteFromClause, ShowFromClause};
@@ -78,6 +83,57 @@ pub trait Visitor: Sized {
         Ok(self)
     }
 
+    /// Invoked before any children of `n` are visited.
+    fn pre_visit_create_continuous_query_statement(
+        self,
+        _n: &CreateContinuousQueryStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of `n` are visited. Default
+    /// implementation does nothing.
+    fn post_visit_create_continuous_query_statement(
+        self,
+        _n: &CreateContinuousQueryStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    /// Invoked before any children of `n` are visited.
+    fn pre_visit_drop_continuous_query_statement(
+        self,
+        _n: &DropContinuousQueryStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of `n` are visited. Default
+    /// implementation does nothing.
+    fn post_visit_drop_continuous_query_statement(
+        self,
+        _n: &DropContinuousQueryStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    /// Invoked before any children of `n` are visited.
+    fn pre_visit_show_continuous_queries_statement(
+        self,
+        _n: &ShowContinuousQueriesStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of `n` are visited. Default
+    /// implementation does nothing.
+    fn post_visit_show_continuous_queries_statement(
+        self,
+        _n: &ShowContinuousQueriesStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
     /// Invoked before any children of `n` are visited.
     fn pre_visit_create_database_statement(
         self,
@@ -272,6 +328,38 @@ pub trait Visitor: Sized {
         Ok(self)
     }
 
+    /// Invoked before any children of the `SHOW SERIES` statement are visited.
+    fn pre_visit_show_series_statement(
+        self,
+        _n: &ShowSeriesStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of the `SHOW SERIES` statement are visited.
+    fn post_visit_show_series_statement(
+        self,
+        _n: &ShowSeriesStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    /// Invoked before any children of the `SHOW ... CARDINALITY` statement are visited.
+    fn pre_visit_show_cardinality_statement(
+        self,
+        _n: &ShowCardinalityStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of the `SHOW ... CARDINALITY` statement are visited.
+    fn post_visit_show_cardinality_statement(
+        self,
+        _n: &ShowCardinalityStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
     /// Invoked before any children of the conditional expression are visited.
     fn pre_visit_conditional_expression(
         self,
@@ -318,6 +406,16 @@ pub trait Visitor: Sized {
         Ok(self)
     }
 
+    /// Invoked before any children of the `INTO` clause of a `SELECT` statement are visited.
+    fn pre_visit_into_clause(self, _n: &IntoClause) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of the `INTO` clause of a `SELECT` statement are visited.
+    fn post_visit_into_clause(self, _n: &IntoClause) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
     /// Invoked before any children of the `FROM` clause of a `SELECT` statement are visited.
     fn pre_visit_select_from_clause(
         self,
@@ -612,23 +710,66 @@ impl Visitable for Statement {
         };
 
         let visitor = match self {
+            Self::CreateContinuousQuery(s) => s.accept(visitor),
             Self::CreateDatabase(s) => s.accept(visitor),
             Self::Delete(s) => s.accept(visitor),
+            Self::DropContinuousQuery(s) => s.accept(visitor),
             Self::DropMeasurement(s) => s.accept(visitor),
             Self::Explain(s) => s.accept(visitor),
             Self::Select(s) => s.accept(visitor),
+            Self::ShowContinuousQueries(s) => s.accept(visitor),
             Self::ShowDatabases(s) => s.accept(visitor),
             Self::ShowMeasurements(s) => s.accept(visitor),
             Self::ShowRetentionPolicies(s) => s.accept(visitor),
             Self::ShowTagKeys(s) => s.accept(visitor),
             Self::ShowTagValues(s) => s.accept(visitor),
             Self::ShowFieldKeys(s) => s.accept(visitor),
+            Self::ShowSeries(s) => s.accept(visitor),
+            Self::ShowCardinality(s) => s.accept(visitor),
         }?;
 
         visitor.post_visit_statement(self)
     }
 }
 
+impl Visitable for CreateContinuousQueryStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_create_continuous_query_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+
+        let visitor = self.database.accept(visitor)?;
+        let visitor = self.source.accept(visitor)?;
+
+        visitor.post_visit_create_continuous_query_statement(self)
+    }
+}
+
+impl Visitable for DropContinuousQueryStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_drop_continuous_query_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+
+        let visitor = self.database.accept(visitor)?;
+
+        visitor.post_visit_drop_continuous_query_statement(self)
+    }
+}
+
+impl Visitable for ShowContinuousQueriesStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_show_continuous_queries_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+
+        visitor.post_visit_show_continuous_queries_statement(self)
+    }
+}
+
 impl Visitable for CreateDatabaseStatement {
     fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
         let visitor = match visitor.pre_visit_create_database_statement(self)? {
@@ -734,6 +875,12 @@ impl Visitable for SelectStatement {
 
         let visitor = self.fields.accept(visitor)?;
 
+        let visitor = if let Some(into) = &self.into {
+            into.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
         let visitor = self.from.accept(visitor)?;
 
         let visitor = if let Some(condition) = &self.condition {
@@ -805,6 +952,17 @@ impl Visitable for TimeZoneClause {
     }
 }
 
+impl Visitable for IntoClause {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_into_clause(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+
+        visitor.post_visit_into_clause(self)
+    }
+}
+
 impl Visitable for LimitClause {
     fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
         let visitor = match visitor.pre_visit_limit_clause(self)? {
@@ -1114,6 +1272,94 @@ impl Visitable for ShowFieldKeysStatement {
     }
 }
 
+impl Visitable for ShowSeriesStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_show_series_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+
+        let visitor = if let Some(on_clause) = &self.database {
+            on_clause.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(from) = &self.from {
+            from.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(condition) = &self.condition {
+            condition.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(limit) = &self.limit {
+            limit.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(offset) = &self.offset {
+            offset.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        visitor.post_visit_show_series_statement(self)
+    }
+}
+
+impl Visitable for ShowCardinalityStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_show_cardinality_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+
+        let visitor = if let Some(on_clause) = &self.database {
+            on_clause.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(from) = &self.from {
+            from.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(with_key) = &self.with_key {
+            with_key.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(condition) = &self.condition {
+            condition.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(limit) = &self.limit {
+            limit.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        let visitor = if let Some(offset) = &self.offset {
+            offset.accept(visitor)
+        } else {
+            Ok(visitor)
+        }?;
+
+        visitor.post_visit_show_cardinality_statement(self)
+    }
+}
+
 impl Visitable for FieldList {
     fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
         let visitor = match visitor.pre_visit_select_field_list(self)? {
@@ -1346,6 +1592,10 @@ mod test {
         LimitClause, MeasurementName, OffsetClause, OrderByClause, QualifiedMeasurementName,
         WhereClause,
     };
+    use crate::continuous_query::{
+        CreateContinuousQueryStatement, DropContinuousQueryStatement,
+        ShowContinuousQueriesStatement,
+    };
     use crate::delete::DeleteStatement;
     use crate::drop::DropMeasurementStatement;
     use crate::explain::ExplainStatement;
@@ -1354,16 +1604,18 @@ mod test {
     use crate::expression::{Binary, Call, ConditionalBinary, VarRef};
     use crate::literal::Literal;
     use crate::select::{
-        Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause,
+        Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause, IntoClause,
         MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
         TimeZoneClause,
     };
     use crate::show::{OnClause, ShowDatabasesStatement};
+    use crate::show_cardinality::ShowCardinalityStatement;
     use crate::show_field_keys::ShowFieldKeysStatement;
     use crate::show_measurements::{
         ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
     };
     use crate::show_retention_policies::ShowRetentionPoliciesStatement;
+    use crate::show_series::ShowSeriesStatement;
     use crate::show_tag_keys::ShowTagKeysStatement;
     use crate::show_tag_values::{ShowTagValuesStatement, WithKeyClause};
     use crate::simple_from_clause::{DeleteFromClause, ShowFromClause};
@@ -1407,6 +1659,18 @@ mod test {
         type Error = ();
 
         trace_visit!(statement, Statement);
+        trace_visit!(
+            create_continuous_query_statement,
+            CreateContinuousQueryStatement
+        );
+        trace_visit!(
+            drop_continuous_query_statement,
+            DropContinuousQueryStatement
+        );
+        trace_visit!(
+            show_continuous_queries_statement,
+            ShowContinuousQueriesStatement
+        );
         trace_visit!(delete_statement, DeleteStatement);
         trace_visit!(delete_from_clause, DeleteFromClause);
         trace_visit!(measurement_name, MeasurementName);
@@ -1422,10 +1686,13 @@ mod test {
         trace_visit!(show_tag_keys_statement, ShowTagKeysStatement);
         trace_visit!(show_tag_values_statement, ShowTagValuesStatement);
         trace_visit!(show_field_keys_statement, ShowFieldKeysStatement);
+        trace_visit!(show_series_statement, ShowSeriesStatement);
+        trace_visit!(show_cardinality_statement, ShowCardinalityStatement);
         trace_visit!(conditional_expression, ConditionalExpression);
         trace_visit!(expr, Expr);
         trace_visit!(select_field_list, FieldList);
         trace_visit!(select_field, Field);
+        trace_visit!(into_clause, IntoClause);
         trace_visit!(select_from_clause, FromMeasurementClause);
         trace_visit!(select_measurement_selection, MeasurementSelection);
         trace_visit!(group_by_clause, GroupByClause);
@@ -1467,6 +1734,15 @@ mod test {
         insta::assert_yaml_snapshot!(visit_statement!("DELETE FROM /^cpu/"));
     }
 
+    #[test]
+    fn test_continuous_query_statements() {
+        insta::assert_yaml_snapshot!(visit_statement!(
+            "CREATE CONTINUOUS QUERY cq ON telegraf BEGIN SELECT mean(value) INTO cpu_1h FROM cpu GROUP BY TIME(1h) END"
+        ));
+        insta::assert_yaml_snapshot!(visit_statement!("DROP CONTINUOUS QUERY cq ON telegraf"));
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW CONTINUOUS QUERIES"));
+    }
+
     #[test]
     fn test_drop_measurement_statement() {
         insta::assert_yaml_snapshot!(visit_statement!("DROP MEASUREMENT cpu"))
@@ -1499,6 +1775,13 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_select_into_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!(
+            r#"SELECT mean(value) INTO db.rp.:MEASUREMENT FROM cpu GROUP BY TIME(1h)"#
+        ));
+    }
+
     #[test]
     fn test_show_databases_statement() {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW DATABASES"));
@@ -1553,4 +1836,20 @@ mod test {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW FIELD KEYS FROM cpu"));
         insta::assert_yaml_snapshot!(visit_statement!("SHOW FIELD KEYS ON telegraf FROM /cpu/"));
     }
+
+    #[test]
+    fn test_show_series_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW SERIES"));
+        insta::assert_yaml_snapshot!(visit_statement!(
+            "SHOW SERIES ON telegraf FROM cpu WHERE host = \"west\" LIMIT 5 OFFSET 10"
+        ));
+    }
+
+    #[test]
+    fn test_show_cardinality_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW SERIES CARDINALITY"));
+        insta::assert_yaml_snapshot!(visit_statement!(
+            "SHOW TAG VALUES EXACT CARDINALITY ON telegraf FROM cpu WITH KEY = host WHERE host = \"west\" LIMIT 5 OFFSET 10"
+        ));
+    }
 }
@@ -28,6 +28,9 @@ use crate::common::{
     LimitClause, MeasurementName, OffsetClause, OrderByClause, QualifiedMeasurementName,
     WhereClause,
 };
+use crate::continuous_query::{
+    CreateContinuousQueryStatement, DropContinuousQueryStatement, ShowContinuousQueriesStatement,
+};
 use crate::create::CreateDatabaseStatement;
 use crate::delete::DeleteStatement;
 use crate::drop::DropMeasurementStatement;
@@ -37,16 +40,18 @@ use crate::expression::conditional::ConditionalExpression;
 use crate::expression::{Binary, Call, ConditionalBinary, VarRef};
 use crate::literal::Literal;
 use crate::select::{
-    Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause,
+    Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause, IntoClause,
     MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
     TimeZoneClause,
 };
 use crate::show::{OnClause, ShowDatabasesStatement};
+use crate::show_cardinality::ShowCardinalityStatement;
 use crate::show_field_keys::ShowFieldKeysStatement;
 use crate::show_measurements::{
     ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
 };
 use crate::show_retention_policies::ShowRetentionPoliciesStatement;
+use crate::show_series::ShowSeriesStatement;
 use crate::show_tag_keys::ShowTagKeysStatement;
 use crate::show_tag_values::{ShowTagValuesStatement, WithKeyClause};
 use crate::simple_from_clause::{DeleteFromClause, ShowFromClause};
@@ -79,6 +84,57 @@ pub trait VisitorMut: Sized {
         Ok(())
     }
 
+    /// Invoked before any children of `n` are visited.
+    fn pre_visit_create_continuous_query_statement(
+        &mut self,
+        _n: &mut CreateContinuousQueryStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of `n` are visited. Default
+    /// implementation does nothing.
+    fn post_visit_create_continuous_query_statement(
+        &mut self,
+        _n: &mut CreateContinuousQueryStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Invoked before any children of `n` are visited.
+    fn pre_visit_drop_continuous_query_statement(
+        &mut self,
+        _n: &mut DropContinuousQueryStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of `n` are visited. Default
+    /// implementation does nothing.
+    fn post_visit_drop_continuous_query_statement(
+        &mut self,
+        _n: &mut DropContinuousQueryStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Invoked before any children of `n` are visited.
+    fn pre_visit_show_continuous_queries_statement(
+        &mut self,
+        _n: &mut ShowContinuousQueriesStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of `n` are visited. Default
+    /// implementation does nothing.
+    fn post_visit_show_continuous_queries_statement(
+        &mut self,
+        _n: &mut ShowContinuousQueriesStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Invoked before any children of `n` are visited.
     fn pre_visit_create_database_statement(
         &mut self,
@@ -279,6 +335,38 @@ pub trait VisitorMut: Sized {
         Ok(())
     }
 
+    /// Invoked before any children of the `SHOW SERIES` statement are visited.
+    fn pre_visit_show_series_statement(
+        &mut self,
+        _n: &mut ShowSeriesStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of the `SHOW SERIES` statement are visited.
+    fn post_visit_show_series_statement(
+        &mut self,
+        _n: &mut ShowSeriesStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Invoked before any children of the `SHOW ... CARDINALITY` statement are visited.
+    fn pre_visit_show_cardinality_statement(
+        &mut self,
+        _n: &mut ShowCardinalityStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of the `SHOW ... CARDINALITY` statement are visited.
+    fn post_visit_show_cardinality_statement(
+        &mut self,
+        _n: &mut ShowCardinalityStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Invoked before any children of the conditional expression are visited.
     fn pre_visit_conditional_expression(
         &mut self,
@@ -328,6 +416,16 @@ pub trait VisitorMut: Sized {
         Ok(())
     }
 
+    /// Invoked before any children of the `INTO` clause of a `SELECT` statement are visited.
+    fn pre_visit_into_clause(&mut self, _n: &mut IntoClause) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of the `INTO` clause of a `SELECT` statement are visited.
+    fn post_visit_into_clause(&mut self, _n: &mut IntoClause) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Invoked before any children of the `FROM` clause of a `SELECT` statement are visited.
     fn pre_visit_select_from_clause(
         &mut self,
@@ -642,23 +740,63 @@ impl VisitableMut for Statement {
         };
 
         match self {
+            Self::CreateContinuousQuery(s) => s.accept(visitor),
             Self::CreateDatabase(s) => s.accept(visitor),
             Self::Delete(s) => s.accept(visitor),
+            Self::DropContinuousQuery(s) => s.accept(visitor),
             Self::DropMeasurement(s) => s.accept(visitor),
             Self::Explain(s) => s.accept(visitor),
             Self::Select(s) => s.accept(visitor),
+            Self::ShowContinuousQueries(s) => s.accept(visitor),
             Self::ShowDatabases(s) => s.accept(visitor),
             Self::ShowMeasurements(s) => s.accept(visitor),
             Self::ShowRetentionPolicies(s) => s.accept(visitor),
             Self::ShowTagKeys(s) => s.accept(visitor),
             Self::ShowTagValues(s) => s.accept(visitor),
             Self::ShowFieldKeys(s) => s.accept(visitor),
+            Self::ShowSeries(s) => s.accept(visitor),
+            Self::ShowCardinality(s) => s.accept(visitor),
         }?;
 
         visitor.post_visit_statement(self)
     }
 }
 
+impl VisitableMut for CreateContinuousQueryStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_create_continuous_query_statement(self)? {
+            return Ok(());
+        };
+
+        self.database.accept(visitor)?;
+        self.source.accept(visitor)?;
+
+        visitor.post_visit_create_continuous_query_statement(self)
+    }
+}
+
+impl VisitableMut for DropContinuousQueryStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_drop_continuous_query_statement(self)? {
+            return Ok(());
+        };
+
+        self.database.accept(visitor)?;
+
+        visitor.post_visit_drop_continuous_query_statement(self)
+    }
+}
+
+impl VisitableMut for ShowContinuousQueriesStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_show_continuous_queries_statement(self)? {
+            return Ok(());
+        };
+
+        visitor.post_visit_show_continuous_queries_statement(self)
+    }
+}
+
 impl VisitableMut for CreateDatabaseStatement {
     fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
         if let Stop = visitor.pre_visit_create_database_statement(self)? {
@@ -756,6 +894,10 @@ impl VisitableMut for SelectStatement {
 
         self.fields.accept(visitor)?;
 
+        if let Some(into) = &mut self.into {
+            into.accept(visitor)?;
+        }
+
         self.from.accept(visitor)?;
 
         if let Some(condition) = &mut self.condition {
@@ -808,6 +950,16 @@ impl VisitableMut for TimeZoneClause {
     }
 }
 
+impl VisitableMut for IntoClause {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_into_clause(self)? {
+            return Ok(());
+        };
+
+        visitor.post_visit_into_clause(self)
+    }
+}
+
 impl VisitableMut for LimitClause {
     fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
         if let Stop = visitor.pre_visit_limit_clause(self)? {
@@ -1065,6 +1217,70 @@ impl VisitableMut for ShowFieldKeysStatement {
     }
 }
 
+impl VisitableMut for ShowSeriesStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_show_series_statement(self)? {
+            return Ok(());
+        };
+
+        if let Some(on_clause) = &mut self.database {
+            on_clause.accept(visitor)?;
+        }
+
+        if let Some(from) = &mut self.from {
+            from.accept(visitor)?;
+        }
+
+        if let Some(condition) = &mut self.condition {
+            condition.accept(visitor)?;
+        }
+
+        if let Some(limit) = &mut self.limit {
+            limit.accept(visitor)?;
+        }
+
+        if let Some(offset) = &mut self.offset {
+            offset.accept(visitor)?;
+        }
+
+        visitor.post_visit_show_series_statement(self)
+    }
+}
+
+impl VisitableMut for ShowCardinalityStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_show_cardinality_statement(self)? {
+            return Ok(());
+        };
+
+        if let Some(on_clause) = &mut self.database {
+            on_clause.accept(visitor)?;
+        }
+
+        if let Some(from) = &mut self.from {
+            from.accept(visitor)?;
+        }
+
+        if let Some(with_key) = &mut self.with_key {
+            with_key.accept(visitor)?;
+        }
+
+        if let Some(condition) = &mut self.condition {
+            condition.accept(visitor)?;
+        }
+
+        if let Some(limit) = &mut self.limit {
+            limit.accept(visitor)?;
+        }
+
+        if let Some(offset) = &mut self.offset {
+            offset.accept(visitor)?;
+        }
+
+        visitor.post_visit_show_cardinality_statement(self)
+    }
+}
+
 impl VisitableMut for FieldList {
     fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
         if let Stop = visitor.pre_visit_select_field_list(self)? {
@@ -1283,6 +1499,10 @@ mod test {
         LimitClause, MeasurementName, OffsetClause, OrderByClause, QualifiedMeasurementName,
         WhereClause,
     };
+    use crate::continuous_query::{
+        CreateContinuousQueryStatement, DropContinuousQueryStatement,
+        ShowContinuousQueriesStatement,
+    };
     use crate::delete::DeleteStatement;
     use crate::drop::DropMeasurementStatement;
     use crate::explain::ExplainStatement;
@@ -1292,16 +1512,18 @@ mod test {
     use crate::literal::Literal;
     use crate::parse_statements;
     use crate::select::{
-        Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause,
+        Dimension, Field, FieldList, FillClause, FromMeasurementClause, GroupByClause, IntoClause,
         MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
         TimeZoneClause,
     };
     use crate::show::{OnClause, ShowDatabasesStatement};
+    use crate::show_cardinality::ShowCardinalityStatement;
     use crate::show_field_keys::ShowFieldKeysStatement;
     use crate::show_measurements::{
         ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
     };
     use crate::show_retention_policies::ShowRetentionPoliciesStatement;
+    use crate::show_series::ShowSeriesStatement;
     use crate::show_tag_keys::ShowTagKeysStatement;
     use crate::show_tag_values::{ShowTagValuesStatement, WithKeyClause};
     use crate::simple_from_clause::{DeleteFromClause, ShowFromClause};
@@ -1343,6 +1565,18 @@ mod test {
         type Error = ();
 
         trace_visit!(statement, Statement);
+        trace_visit!(
+            create_continuous_query_statement,
+            CreateContinuousQueryStatement
+        );
+        trace_visit!(
+            drop_continuous_query_statement,
+            DropContinuousQueryStatement
+        );
+        trace_visit!(
+            show_continuous_queries_statement,
+            ShowContinuousQueriesStatement
+        );
         trace_visit!(delete_statement, DeleteStatement);
         trace_visit!(delete_from_clause, DeleteFromClause);
         trace_visit!(measurement_name, MeasurementName);
@@ -1358,10 +1592,13 @@ mod test {
         trace_visit!(show_tag_keys_statement, ShowTagKeysStatement);
         trace_visit!(show_tag_values_statement, ShowTagValuesStatement);
         trace_visit!(show_field_keys_statement, ShowFieldKeysStatement);
+        trace_visit!(show_series_statement, ShowSeriesStatement);
+        trace_visit!(show_cardinality_statement, ShowCardinalityStatement);
         trace_visit!(conditional_expression, ConditionalExpression);
         trace_visit!(expr, Expr);
         trace_visit!(select_field_list, FieldList);
         trace_visit!(select_field, Field);
+        trace_visit!(into_clause, IntoClause);
         trace_visit!(select_from_clause, FromMeasurementClause);
         trace_visit!(select_measurement_selection, MeasurementSelection);
         trace_visit!(group_by_clause, GroupByClause);
@@ -1405,6 +1642,15 @@ mod test {
         insta::assert_yaml_snapshot!(visit_statement!("DELETE FROM /^cpu/"));
     }
 
+    #[test]
+    fn test_continuous_query_statements() {
+        insta::assert_yaml_snapshot!(visit_statement!(
+            "CREATE CONTINUOUS QUERY cq ON telegraf BEGIN SELECT mean(value) INTO cpu_1h FROM cpu GROUP BY TIME(1h) END"
+        ));
+        insta::assert_yaml_snapshot!(visit_statement!("DROP CONTINUOUS QUERY cq ON telegraf"));
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW CONTINUOUS QUERIES"));
+    }
+
     #[test]
     fn test_drop_measurement_statement() {
         insta::assert_yaml_snapshot!(visit_statement!("DROP MEASUREMENT cpu"))
@@ -1437,6 +1683,13 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_select_into_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!(
+            r#"SELECT mean(value) INTO db.rp.:MEASUREMENT FROM cpu GROUP BY TIME(1h)"#
+        ));
+    }
+
     #[test]
     fn test_show_databases_statement() {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW DATABASES"));
@@ -1492,6 +1745,22 @@ mod test {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW FIELD KEYS ON telegraf FROM /cpu/"));
     }
 
+    #[test]
+    fn test_show_series_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW SERIES"));
+        insta::assert_yaml_snapshot!(visit_statement!(
+            "SHOW SERIES ON telegraf FROM cpu WHERE host = \"west\" LIMIT 5 OFFSET 10"
+        ));
+    }
+
+    #[test]
+    fn test_show_cardinality_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW SERIES CARDINALITY"));
+        insta::assert_yaml_snapshot!(visit_statement!(
+            "SHOW TAG VALUES EXACT CARDINALITY ON telegraf FROM cpu WITH KEY = host WHERE host = \"west\" LIMIT 5 OFFSET 10"
+        ));
+    }
+
     #[test]
     fn test_mutability() {
         struct AddLimit;
@@ -23,9 +23,13 @@
 
 use std::sync::Arc;
 
-use arrow::datatypes::{Schema, SchemaRef};
+use arrow::{
+    datatypes::{Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
 use arrow_flight::{
     decode::FlightRecordBatchStream,
+    encode::FlightDataEncoderBuilder,
     error::{FlightError, Result},
     sql::{
         ActionCreatePreparedStatementRequest, ActionCreatePreparedStatementResult, Any,
@@ -34,7 +38,7 @@ use arrow_flight::{
         CommandGetTables, CommandGetXdbcTypeInfo, CommandPreparedStatementQuery,
         CommandStatementQuery, ProstMessageExt,
     },
-    Action, FlightClient, FlightDescriptor, FlightInfo, IpcMessage, Ticket,
+    Action, FlightClient, FlightDescriptor, FlightInfo, IpcMessage, PutResult, Ticket,
 };
 use bytes::Bytes;
 use futures_util::TryStreamExt;
@@ -500,17 +504,27 @@ impl FlightSqlClient {
     ///
     /// Step 2: Fetch the results described in the [`FlightInfo`]
     ///
+    /// If parameter values were set with
+    /// [`PreparedStatement::with_parameters`], they are first sent to
+    /// the `DoPut` endpoint of the FlightSQL server.
+    ///
     /// This implementation does not support alternate endpoints
     pub async fn execute(
         &mut self,
         statement: PreparedStatement,
     ) -> Result<FlightRecordBatchStream> {
         let PreparedStatement {
-            prepared_statement_handle,
+            mut prepared_statement_handle,
             dataset_schema: _,
             parameter_schema: _,
+            parameters,
         } = statement;
-        // TODO handle parameters (via DoPut)
+
+        if let Some(parameters) = parameters {
+            prepared_statement_handle = self
+                .bind_parameters(prepared_statement_handle, parameters)
+                .await?;
+        }
 
         let cmd = CommandPreparedStatementQuery {
             prepared_statement_handle,
@@ -518,6 +532,47 @@ impl FlightSqlClient {
 
         self.do_get_with_cmd(cmd.as_any()).await
     }
+
+    /// Send the parameter values for a prepared statement to the
+    /// `DoPut` endpoint of the FlightSQL server, returning the handle
+    /// to use to execute the statement.
+    async fn bind_parameters(
+        &mut self,
+        prepared_statement_handle: Bytes,
+        parameters: RecordBatch,
+    ) -> Result<Bytes> {
+        let cmd = CommandPreparedStatementQuery {
+            prepared_statement_handle: prepared_statement_handle.clone(),
+        };
+        let flight_data = FlightDataEncoderBuilder::new()
+            .with_flight_descriptor(Some(FlightDescriptor::new_cmd(
+                cmd.as_any().encode_to_vec(),
+            )))
+            .build(futures_util::stream::iter([Ok(parameters)]));
+
+        let mut results: Vec<PutResult> =
+            self.inner.do_put(flight_data).await?.try_collect().await?;
+
+        // Servers that keep the parameter values themselves do not return a new handle
+        let Some(PutResult { app_metadata }) = results.pop() else {
+            return Ok(prepared_statement_handle);
+        };
+        let result: DoPutPreparedStatementResult =
+            Message::decode(app_metadata).map_err(|e| FlightError::ExternalError(Box::new(e)))?;
+
+        Ok(result
+            .prepared_statement_handle
+            .unwrap_or(prepared_statement_handle))
+    }
+}
+
+/// The response to binding the parameters of a prepared statement with
+/// `DoPut`, which is not yet defined by `arrow-flight`.
+#[derive(Clone, PartialEq, Message)]
+struct DoPutPreparedStatementResult {
+    /// The handle to use in place of the original one, if changed
+    #[prost(bytes = "bytes", optional, tag = "1")]
+    prepared_statement_handle: Option<Bytes>,
 }
 
 fn schema_bytes_to_schema(schema: Bytes) -> Result<SchemaRef> {
@@ -541,6 +596,9 @@ pub struct PreparedStatement {
 
     /// Schema of parameters, if any
     parameter_schema: SchemaRef,
+
+    /// The values of the parameters, if any
+    parameters: Option<RecordBatch>,
 }
 
 impl PreparedStatement {
@@ -556,6 +614,7 @@ impl PreparedStatement {
             prepared_statement_handle,
             dataset_schema,
             parameter_schema,
+            parameters: None,
         }
     }
 
@@ -568,4 +627,13 @@ impl PreparedStatement {
     pub fn get_parameter_schema(&self) -> SchemaRef {
         Arc::clone(&self.parameter_schema)
     }
+
+    /// Set the values of the parameters, as a single row matching
+    /// [`Self::get_parameter_schema`], to use when the statement is executed
+    pub fn with_parameters(self, parameters: RecordBatch) -> Self {
+        Self {
+            parameters: Some(parameters),
+            ..self
+        }
+    }
 }
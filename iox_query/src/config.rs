@@ -43,6 +43,15 @@ extensions_options! {
 
         /// Cuttoff date for InfluxQL metadata queries.
         pub influxql_metadata_cutoff: MetadataCutoff, default = MetadataCutoff::Relative(Duration::from_secs(3600 * 24))
+
+        /// How far before the start of the time range gap filling looks for the most recent value of each
+        /// series, so that `LOCF`, `INTERPOLATE`, and the InfluxQL `FILL(previous)` and `FILL(linear)`
+        /// options can fill the first gaps of the range.
+        pub gap_fill_lookback: GapFillLookback, default = GapFillLookback::Disabled
+
+        /// Seed for the random number generator used by the InfluxQL `SAMPLE` function, so that the rows it
+        /// selects are the same for every run of a query. By default every query is sampled differently.
+        pub influxql_sample_seed: SampleSeed, default = SampleSeed::Random
     }
 }
 
@@ -57,6 +66,41 @@ pub enum MetadataCutoff {
     Relative(Duration),
 }
 
+/// How far gap filling looks back for values from before the start of the time range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapFillLookback {
+    Disabled,
+    Duration(Duration),
+}
+
+impl GapFillLookback {
+    /// Returns the lookback window, or `None` if looking back is disabled.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::Disabled => None,
+            Self::Duration(d) if d.is_zero() => None,
+            Self::Duration(d) => Some(*d),
+        }
+    }
+}
+
+/// Seed for the random number generator used to sample rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleSeed {
+    Random,
+    Seed(u64),
+}
+
+impl SampleSeed {
+    /// Returns the seed, or `None` if rows are sampled randomly.
+    pub fn seed(&self) -> Option<u64> {
+        match self {
+            Self::Random => None,
+            Self::Seed(seed) => Some(*seed),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ParseError(String);
 
@@ -92,3 +136,103 @@ impl std::fmt::Display for MetadataCutoff {
         }
     }
 }
+
+impl FromStr for GapFillLookback {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" | "none" => Ok(Self::Disabled),
+            s => humantime::parse_duration(s)
+                .map(Self::Duration)
+                .map_err(|e| ParseError(e.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for GapFillLookback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "none"),
+            Self::Duration(d) => write!(f, "{}", humantime::format_duration(*d)),
+        }
+    }
+}
+
+impl FromStr for SampleSeed {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" | "random" => Ok(Self::Random),
+            s => u64::from_str(s)
+                .map(Self::Seed)
+                .map_err(|e| ParseError(e.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for SampleSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::Seed(seed) => write!(f, "{seed}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::config::ExtensionOptions;
+
+    use super::*;
+
+    #[test]
+    fn test_gap_fill_lookback_roundtrip() {
+        for (input, expected) in [
+            ("none", GapFillLookback::Disabled),
+            ("", GapFillLookback::Disabled),
+            ("1h", GapFillLookback::Duration(Duration::from_secs(3600))),
+            (
+                "1h 30m",
+                GapFillLookback::Duration(Duration::from_secs(5400)),
+            ),
+        ] {
+            let lookback = GapFillLookback::from_str(input).unwrap();
+            assert_eq!(lookback, expected);
+            assert_eq!(
+                GapFillLookback::from_str(&lookback.to_string()).unwrap(),
+                expected
+            );
+        }
+
+        GapFillLookback::from_str("forever").unwrap_err();
+    }
+
+    #[test]
+    fn test_gap_fill_lookback_option() {
+        let mut config = IoxConfigExt::default();
+        assert_eq!(config.gap_fill_lookback.duration(), None);
+
+        config.set("gap_fill_lookback", "15m").unwrap();
+        assert_eq!(
+            config.gap_fill_lookback.duration(),
+            Some(Duration::from_secs(900))
+        );
+    }
+
+    #[test]
+    fn test_sample_seed_option() {
+        let mut config = IoxConfigExt::default();
+        assert_eq!(config.influxql_sample_seed.seed(), None);
+
+        config.set("influxql_sample_seed", "42").unwrap();
+        assert_eq!(config.influxql_sample_seed.seed(), Some(42));
+        assert_eq!(config.influxql_sample_seed.to_string(), "42");
+
+        config.set("influxql_sample_seed", "random").unwrap();
+        assert_eq!(config.influxql_sample_seed.seed(), None);
+
+        config.set("influxql_sample_seed", "-1").unwrap_err();
+    }
+}
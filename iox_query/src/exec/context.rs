@@ -32,27 +32,38 @@ use crate::{
 };
 use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use datafusion::{
     catalog::CatalogProvider,
+    common::tree_node::TreeNodeRewriter,
     execution::{
         context::{QueryPlanner, SessionState, TaskContext},
         memory_pool::MemoryPool,
         runtime_env::RuntimeEnv,
     },
-    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    logical_expr::{
+        expr::{Exists, InSubquery, ScalarFunction},
+        expr_rewriter::rewrite_preserving_name,
+        BuiltinScalarFunction, LogicalPlan, Subquery, UserDefinedLogicalNode,
+    },
+    physical_expr::execution_props::ExecutionProps,
     physical_plan::{
         coalesce_partitions::CoalescePartitionsExec, displayable, stream::RecordBatchStreamAdapter,
         EmptyRecordBatchStream, ExecutionPlan, RecordBatchStream, SendableRecordBatchStream,
     },
     physical_planner::{DefaultPhysicalPlanner, ExtensionPlanner, PhysicalPlanner},
     prelude::*,
+    scalar::ScalarValue,
 };
 use datafusion_util::config::{iox_session_config, DEFAULT_CATALOG};
 use executor::DedicatedExecutor;
-use futures::{Stream, StreamExt, TryStreamExt};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use observability_deps::tracing::{debug, warn};
-use query_functions::{register_scalar_functions, selectors::register_selector_aggregates};
-use std::{fmt, num::NonZeroUsize, sync::Arc};
+use query_functions::{
+    approx::register_approx_aggregates, register_scalar_functions,
+    selectors::register_selector_aggregates, window_functions::register_window_functions,
+};
+use std::{borrow::Cow, fmt, num::NonZeroUsize, sync::Arc};
 use trace::{
     ctx::SpanContext,
     span::{MetaValue, Span, SpanExt, SpanRecorder},
@@ -176,6 +187,9 @@ pub struct IOxSessionConfig {
 
     /// Span context from which to create spans for this query
     span_ctx: Option<SpanContext>,
+
+    /// The time `now()` evaluates to, if not the time the query is planned
+    query_start_time: Option<DateTime<Utc>>,
 }
 
 impl fmt::Debug for IOxSessionConfig {
@@ -198,6 +212,7 @@ impl IOxSessionConfig {
             runtime,
             default_catalog: None,
             span_ctx: None,
+            query_start_time: None,
         }
     }
 
@@ -222,6 +237,15 @@ impl IOxSessionConfig {
         Self { span_ctx, ..self }
     }
 
+    /// Set the time `now()` evaluates to in queries, so that they do not depend on the wall
+    /// clock at the time they are planned
+    pub fn with_query_start_time(self, query_start_time: DateTime<Utc>) -> Self {
+        Self {
+            query_start_time: Some(query_start_time),
+            ..self
+        }
+    }
+
     /// Set DataFusion [config option].
     ///
     /// May be used to set [IOx-specific] option as well.
@@ -259,12 +283,16 @@ impl IOxSessionConfig {
 
         let inner = SessionContext::with_state(state);
         register_selector_aggregates(&inner);
+        register_approx_aggregates(&inner);
         register_scalar_functions(&inner);
+        register_window_functions(&inner);
         if let Some(default_catalog) = self.default_catalog {
             inner.register_catalog(DEFAULT_CATALOG, default_catalog);
         }
 
-        IOxSessionContext::new(inner, self.exec, recorder)
+        let mut ctx = IOxSessionContext::new(inner, self.exec, recorder);
+        ctx.query_start_time = self.query_start_time;
+        ctx
     }
 }
 
@@ -293,6 +321,9 @@ pub struct IOxSessionContext {
 
     /// Span context from which to create spans for this query
     recorder: SpanRecorder,
+
+    /// The time `now()` evaluates to, if not the time the query is planned
+    query_start_time: Option<DateTime<Utc>>,
 }
 
 impl fmt::Debug for IOxSessionContext {
@@ -301,6 +332,7 @@ impl fmt::Debug for IOxSessionContext {
             .field("inner", &"<DataFusion ExecutionContext>")
             .field("exec", &self.exec)
             .field("recorder", &self.recorder)
+            .field("query_start_time", &self.query_start_time)
             .finish()
     }
 }
@@ -315,6 +347,7 @@ impl IOxSessionContext {
             inner: SessionContext::default(),
             exec: DedicatedExecutor::new_testing(),
             recorder: SpanRecorder::default(),
+            query_start_time: None,
         }
     }
 
@@ -328,6 +361,7 @@ impl IOxSessionContext {
             inner,
             exec,
             recorder,
+            query_start_time: None,
         }
     }
 
@@ -336,6 +370,16 @@ impl IOxSessionContext {
         &self.inner
     }
 
+    /// Returns the [`ExecutionProps`] to plan queries with, using the configured query start
+    /// time if there is one.
+    pub fn execution_props(&self) -> ExecutionProps {
+        let mut execution_props = self.inner.state().execution_props().clone();
+        if let Some(query_start_time) = self.query_start_time {
+            execution_props.query_execution_start_time = query_start_time;
+        }
+        execution_props
+    }
+
     /// Plan a SQL statement. This assumes that any tables referenced
     /// in the SQL have been registered with this context. Use
     /// `create_physical_plan` to actually execute the query.
@@ -376,7 +420,15 @@ impl IOxSessionContext {
     ) -> Result<Arc<dyn ExecutionPlan>> {
         let mut ctx = self.child_ctx("create_physical_plan");
         debug!(text=%logical_plan.display_indent_schema(), "create_physical_plan: initial plan");
-        let physical_plan = ctx.inner.state().create_physical_plan(logical_plan).await?;
+        let logical_plan = match ctx.query_start_time {
+            Some(query_start_time) => Cow::Owned(replace_now(logical_plan, query_start_time)?),
+            None => Cow::Borrowed(logical_plan),
+        };
+        let physical_plan = ctx
+            .inner
+            .state()
+            .create_physical_plan(&logical_plan)
+            .await?;
 
         ctx.recorder.event("physical plan");
         debug!(text=%displayable(physical_plan.as_ref()).indent(false), "create_physical_plan: plan to run");
@@ -458,13 +510,14 @@ impl IOxSessionContext {
     }
 
     /// Executes the SeriesSetPlans on the query executor, in
-    /// parallel, producing series or groups
+    /// parallel, producing series or groups. The stream does not
+    /// borrow the context, so it can be sent as a response.
     pub async fn to_series_and_groups(
         &self,
         series_set_plans: SeriesSetPlans,
         memory_pool: Arc<dyn MemoryPool>,
         points_per_batch: usize,
-    ) -> Result<impl Stream<Item = Result<Either>>> {
+    ) -> Result<BoxStream<'static, Result<Either>>> {
         let SeriesSetPlans {
             mut plans,
             group_columns,
@@ -662,11 +715,13 @@ impl IOxSessionContext {
 
     /// Returns a IOxSessionContext with a SpanRecorder that is a child of the current
     pub fn child_ctx(&self, name: &'static str) -> Self {
-        Self::new(
+        let mut ctx = Self::new(
             self.inner.clone(),
             self.exec.clone(),
             self.recorder.child(name),
-        )
+        );
+        ctx.query_start_time = self.query_start_time;
+        ctx
     }
 
     /// Record an event on the span recorder
@@ -695,6 +750,76 @@ impl IOxSessionContext {
     }
 }
 
+/// Replaces every call to `now()` in `plan`, including in the plans of any subqueries, with
+/// `query_start_time`.
+///
+/// DataFusion evaluates `now()` as the time the plan is optimized, which cannot be overridden
+/// through the session, so the calls are substituted before the plan is handed to it.
+fn replace_now(plan: &LogicalPlan, query_start_time: DateTime<Utc>) -> Result<LogicalPlan> {
+    let new_inputs = plan
+        .inputs()
+        .iter()
+        .map(|input| replace_now(input, query_start_time))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut rewriter = NowRewriter { query_start_time };
+    let new_exprs = plan
+        .expressions()
+        .into_iter()
+        .map(|expr| rewrite_preserving_name(expr, &mut rewriter))
+        .collect::<Result<Vec<_>>>()?;
+
+    plan.with_new_exprs(new_exprs, &new_inputs)
+}
+
+struct NowRewriter {
+    query_start_time: DateTime<Utc>,
+}
+
+impl NowRewriter {
+    /// The plan of a subquery is not a child of the expression that contains it, so it is
+    /// rewritten separately.
+    fn rewrite_subquery(&self, subquery: Subquery) -> Result<Subquery> {
+        Ok(Subquery {
+            subquery: Arc::new(replace_now(&subquery.subquery, self.query_start_time)?),
+            outer_ref_columns: subquery.outer_ref_columns,
+        })
+    }
+}
+
+impl TreeNodeRewriter for NowRewriter {
+    type N = Expr;
+
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        match expr {
+            Expr::ScalarFunction(ScalarFunction {
+                fun: BuiltinScalarFunction::Now,
+                ..
+            }) => Ok(Expr::Literal(ScalarValue::TimestampNanosecond(
+                self.query_start_time.timestamp_nanos_opt(),
+                Some("+00:00".into()),
+            ))),
+            Expr::ScalarSubquery(subquery) => {
+                Ok(Expr::ScalarSubquery(self.rewrite_subquery(subquery)?))
+            }
+            Expr::Exists(Exists { subquery, negated }) => Ok(Expr::Exists(Exists {
+                subquery: self.rewrite_subquery(subquery)?,
+                negated,
+            })),
+            Expr::InSubquery(InSubquery {
+                expr,
+                subquery,
+                negated,
+            }) => Ok(Expr::InSubquery(InSubquery {
+                expr,
+                subquery: self.rewrite_subquery(subquery)?,
+                negated,
+            })),
+            _ => Ok(expr),
+        }
+    }
+}
+
 /// Extension trait to pull IOx spans out of DataFusion contexts.
 pub trait SessionContextIOxExt {
     /// Get child span of the current context.
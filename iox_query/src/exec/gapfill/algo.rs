@@ -479,6 +479,7 @@ impl Cursor {
                     RowStatus::Present { ts, .. } | RowStatus::Missing { ts, .. } => {
                         self.times.push(Some(ts))
                     }
+                    RowStatus::Lookback { .. } => (),
                 }
                 Ok(())
             }
@@ -516,6 +517,7 @@ impl Cursor {
                     | RowStatus::Missing {
                         series_end_offset, ..
                     } => self.take_idxs.push(series_end_offset as u64 - 1),
+                    RowStatus::Lookback { .. } => (),
                 }
                 Ok(())
             }
@@ -585,6 +587,7 @@ impl Cursor {
                         self.take_idxs.push(Some(offset as u64))
                     }
                     RowStatus::Missing { .. } => self.take_idxs.push(None),
+                    RowStatus::Lookback { .. } => (),
                 }
                 Ok(())
             }
@@ -631,6 +634,11 @@ impl Cursor {
                         }
                     }
                     RowStatus::Missing { .. } => self.take_idxs.push(self.prev_offset),
+                    RowStatus::Lookback { offset, .. } => {
+                        if !self.null_as_missing || self.input_aggr_array.is_valid(offset) {
+                            self.prev_offset = Some(offset as u64);
+                        }
+                    }
                 }
                 Ok(())
             }
@@ -766,6 +774,22 @@ impl Cursor {
             self.next_input_offset += 1;
         }
 
+        // Rows from before the first timestamp to be output are only used
+        // to fill the gaps that follow them.
+        if let Some(first_ts) = params.first_ts {
+            while self.next_input_offset < series_end
+                && input_times.is_valid(self.next_input_offset)
+                && input_times.value(self.next_input_offset) < first_ts
+            {
+                vec_builder.push(RowStatus::Lookback {
+                    series_end_offset: series_end,
+                    offset: self.next_input_offset,
+                    ts: input_times.value(self.next_input_offset),
+                })?;
+                self.next_input_offset += 1;
+            }
+        }
+
         if !self.maybe_init_next_ts(input_times, series_end) {
             return Ok(());
         }
@@ -780,7 +804,7 @@ impl Cursor {
         }
 
         // last_ts is the last timestamp that will fit in the output batch
-        let last_ts = next_ts + (output_row_count - 1) as i64 * params.stride;
+        let last_ts = params.stride.nth(next_ts, (output_row_count - 1) as i64);
 
         loop {
             if self.next_input_offset >= series_end {
@@ -795,14 +819,14 @@ impl Cursor {
                     series_end_offset: series_end,
                     ts: next_ts,
                 })?;
-                next_ts += params.stride;
+                next_ts = params.stride.next(next_ts);
             }
             vec_builder.push(RowStatus::Present {
                 series_end_offset: series_end,
                 offset: self.next_input_offset,
                 ts: next_ts,
             })?;
-            next_ts += params.stride;
+            next_ts = params.stride.next(next_ts);
             self.next_input_offset += 1;
         }
 
@@ -812,10 +836,10 @@ impl Cursor {
                 series_end_offset: series_end,
                 ts: next_ts,
             })?;
-            next_ts += params.stride;
+            next_ts = params.stride.next(next_ts);
         }
 
-        self.next_ts = Some(last_ts + params.stride);
+        self.next_ts = Some(params.stride.next(last_ts));
         self.remaining_output_batch_size -= output_row_count;
         Ok(())
     }
@@ -950,6 +974,18 @@ enum RowStatus {
         /// The timestamp corresponding to this row.
         ts: i64,
     },
+    /// This row is from before the first timestamp to be output, which
+    /// happens when the query looks back before the start of the time range.
+    /// It does not appear in the output, but may provide the values that fill
+    /// the first gaps in the series.
+    Lookback {
+        /// The exclusive offset of the series end in the input.
+        series_end_offset: usize,
+        /// The offset of the row in the input.
+        offset: usize,
+        /// The timestamp of this row.
+        ts: i64,
+    },
 }
 
 /// Implements [`VecBuilder`] for [`FillStrategy::PrevNullAsMissing`],
@@ -1013,6 +1049,11 @@ impl<'a> VecBuilder for StashedAggrBuilder<'a> {
                 self.interleave_idxs.push(Self::buffered_input(offset));
                 self.state = StashedAggrState::PrevSome { offset };
             }
+            RowStatus::Lookback { offset, .. } => {
+                if self.input_aggr_array.is_valid(offset) {
+                    self.state = StashedAggrState::PrevSome { offset };
+                }
+            }
             RowStatus::Present { .. } | RowStatus::Missing { .. } => match self.state {
                 StashedAggrState::Stashed => self.interleave_idxs.push(Self::STASHED_VALUE),
                 StashedAggrState::PrevNone => self.interleave_idxs.push(Self::STASHED_NULL),
@@ -1047,7 +1088,7 @@ mod tests {
 
     use crate::exec::gapfill::{
         algo::{AggrColState, Cursor},
-        params::GapFillParams,
+        params::{GapFillParams, Stride},
         FillStrategy,
     };
 
@@ -1058,7 +1099,7 @@ mod tests {
         let series = input_times.len();
 
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: simple_fill_strategy(),
@@ -1093,7 +1134,7 @@ mod tests {
         let series = input_times.len();
 
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: None,
             last_ts: 1250,
             fill_strategy: simple_fill_strategy(),
@@ -1121,7 +1162,7 @@ mod tests {
         let series = input_times.len();
 
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: simple_fill_strategy(),
@@ -1156,7 +1197,7 @@ mod tests {
         let series = input_times.len();
 
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: simple_fill_strategy(),
@@ -1179,7 +1220,7 @@ mod tests {
         let series = input_times.len();
 
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: simple_fill_strategy(),
@@ -1225,7 +1266,7 @@ mod tests {
         let series = input_times.len();
 
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: simple_fill_strategy(),
@@ -1278,7 +1319,7 @@ mod tests {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: prev_fill_strategy(idx),
@@ -1334,7 +1375,7 @@ mod tests {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1250,
             fill_strategy: prev_fill_strategy(idx),
@@ -1390,7 +1431,7 @@ mod tests {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1100,
             fill_strategy: prev_fill_strategy(idx),
@@ -1454,7 +1495,7 @@ mod tests {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1100,
             fill_strategy: prev_null_as_missing_fill_strategy(idx),
@@ -1531,7 +1572,7 @@ mod tests {
 
         let aggr_col_idx = 0;
         let params = GapFillParams {
-            stride: 50,
+            stride: Stride::Nanos(50),
             first_ts: Some(950),
             last_ts: 1150,
             fill_strategy: prev_null_as_missing_fill_strategy(aggr_col_idx),
@@ -1614,7 +1655,7 @@ mod tests {
         params: &GapFillParams,
     ) {
         assert_eq!(input_times.len(), cursor.next_input_offset);
-        assert_eq!(params.last_ts + params.stride, cursor.next_ts.unwrap());
+        assert_eq!(params.stride.next(params.last_ts), cursor.next_ts.unwrap());
     }
 
     fn simple_fill_strategy() -> HashMap<usize, FillStrategy> {
@@ -257,6 +257,24 @@ where
                     .as_ref()
                     .map(|seg| T::Native::interpolate(seg, ts)),
             ),
+            RowStatus::Lookback {
+                ts,
+                offset,
+                series_end_offset,
+            } => {
+                // Points from before the first timestamp are not output, but may
+                // start the segment that fills the first gaps.
+                if self.input_aggr_array.is_valid(offset) {
+                    let end_offset = self.find_end_offset(offset, series_end_offset);
+                    self.segment = end_offset.map(|end_offset| Segment {
+                        start_point: (ts, self.input_aggr_array.value(offset)),
+                        end_point: (
+                            self.input_time_array.value(end_offset),
+                            self.input_aggr_array.value(end_offset),
+                        ),
+                    });
+                }
+            }
         }
         Ok(())
     }
@@ -356,7 +374,7 @@ mod test {
 
     use crate::exec::gapfill::{
         algo::tests::{array_to_lines, assert_cursor_end_state, new_cursor_with_batch_size},
-        params::GapFillParams,
+        params::{GapFillParams, Stride},
         FillStrategy,
     };
 
@@ -395,7 +413,7 @@ mod test {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 100,
+            stride: Stride::Nanos(100),
             first_ts: Some(1000),
             last_ts: 2000,
             fill_strategy: interpolate_fill_strategy(idx),
@@ -467,7 +485,7 @@ mod test {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 100,
+            stride: Stride::Nanos(100),
             first_ts: Some(1000),
             last_ts: 2000,
             fill_strategy: interpolate_fill_strategy(idx),
@@ -539,7 +557,7 @@ mod test {
 
         let idx = 0;
         let params = GapFillParams {
-            stride: 100,
+            stride: Stride::Nanos(100),
             first_ts: Some(1000),
             last_ts: 2000,
             fill_strategy: interpolate_fill_strategy(idx),
@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use arrow::{
-    array::{as_struct_array, ArrayRef},
+    array::{as_struct_array, ArrayRef, TimestampNanosecondArray},
     datatypes::DataType,
     record_batch::RecordBatch,
     row::{RowConverter, Rows, SortField},
@@ -23,11 +23,19 @@ use super::{params::GapFillParams, FillStrategy};
 /// to ensure that we read ahead far enough to a non-null value, or a change
 /// of group columns, in the columns being interpolated.
 ///
+/// Rows from before the first timestamp to be output, which come from looking
+/// back before the start of the time range, do not appear in the output,
+/// so an additional row is buffered for each of them.
+///
 /// [`FillStrategy::LinearInterpolate`]: super::FillStrategy::LinearInterpolate
 /// [`GapFillStream`]: super::stream::GapFillStream
 pub(super) struct BufferedInput {
     /// Indexes of group columns in the schema (not including time).
     group_cols: Vec<usize>,
+    /// Index of the time column in the schema.
+    time_col: usize,
+    /// The first timestamp to be output for each series, if any.
+    first_ts: Option<i64>,
     /// Indexes of aggregate columns filled via interpolation.
     interpolate_cols: Vec<usize>,
     /// Buffered records from the input stream.
@@ -42,7 +50,7 @@ pub(super) struct BufferedInput {
 }
 
 impl BufferedInput {
-    pub(super) fn new(params: &GapFillParams, group_cols: Vec<usize>) -> Self {
+    pub(super) fn new(params: &GapFillParams, group_cols: Vec<usize>, time_col: usize) -> Self {
         let interpolate_cols = params
             .fill_strategy
             .iter()
@@ -52,6 +60,8 @@ impl BufferedInput {
             .collect::<Vec<usize>>();
         Self {
             group_cols,
+            time_col,
+            first_ts: params.first_ts,
             interpolate_cols,
             batches: vec![],
             row_converter: None,
@@ -73,6 +83,7 @@ impl BufferedInput {
     /// Determine if we need more input before we start processing.
     pub(super) fn need_more(&mut self, last_output_row_offset: usize) -> Result<bool> {
         let record_count: usize = self.batches.iter().map(|rb| rb.num_rows()).sum();
+        let last_output_row_offset = last_output_row_offset + self.lookback_row_count()?;
         // min number of rows needed is the number of rows up to and including
         // the last row that may appear in the output, plus one more row.
         let min_needed = last_output_row_offset + 2;
@@ -154,6 +165,23 @@ impl BufferedInput {
         Ok(!cols_that_need_more.is_empty())
     }
 
+    /// Count the buffered rows from before the first timestamp to be output.
+    fn lookback_row_count(&self) -> Result<usize> {
+        let Some(first_ts) = self.first_ts else {
+            return Ok(0);
+        };
+
+        self.batches.iter().try_fold(0, |count, batch| {
+            let times: &TimestampNanosecondArray =
+                batch.column(self.time_col).as_any().downcast_ref().ok_or(
+                    DataFusionError::Internal(
+                        "time array must be a TimestampNanosecondArray".to_string(),
+                    ),
+                )?;
+            Ok(count + times.iter().flatten().filter(|ts| *ts < first_ts).count())
+        })
+    }
+
     /// Check to see if the group column values have changed between the last row
     /// that may be in the output and the last buffered input row.
     ///
@@ -248,7 +276,7 @@ mod tests {
     use arrow_util::test_util::batches_to_lines;
 
     use super::*;
-    use crate::exec::gapfill::exec_tests::TestRecords;
+    use crate::exec::gapfill::{exec_tests::TestRecords, params::Stride};
 
     fn test_records(batch_size: usize) -> VecDeque<RecordBatch> {
         let records = TestRecords {
@@ -354,7 +382,7 @@ mod tests {
 
     fn test_params() -> GapFillParams {
         GapFillParams {
-            stride: 50_000_000,
+            stride: Stride::Nanos(50_000_000),
             first_ts: Some(1_000_000_000),
             last_ts: 1_055_000_000,
             fill_strategy: [
@@ -398,7 +426,7 @@ mod tests {
         let mut params = test_params();
         params.fill_strategy = [].into();
 
-        let mut buffered_input = BufferedInput::new(&params, vec![]);
+        let mut buffered_input = BufferedInput::new(&params, vec![], 2);
         let mut batches = test_records(batch_size);
 
         // There are no rows, so that is less than the batch size,
@@ -415,11 +443,33 @@ mod tests {
         assert!(!buffered_input.need_more(batch_size - 1).unwrap());
     }
 
+    #[test]
+    fn no_group_no_interpolate_lookback() {
+        let batch_size = 3;
+        let mut params = test_params();
+        params.fill_strategy = [].into();
+        // The first 3 rows are from before the first timestamp.
+        params.first_ts = Some(1_015_000_000);
+
+        let mut buffered_input = BufferedInput::new(&params, vec![], 2);
+        let mut batches = test_records(batch_size);
+
+        buffered_input.push(batches.pop_front().unwrap());
+        assert!(buffered_input.need_more(batch_size - 1).unwrap());
+
+        // Without the lookback rows, 6 rows would be enough.
+        buffered_input.push(batches.pop_front().unwrap());
+        assert!(buffered_input.need_more(batch_size - 1).unwrap());
+
+        buffered_input.push(batches.pop_front().unwrap());
+        assert!(!buffered_input.need_more(batch_size - 1).unwrap());
+    }
+
     #[test]
     fn no_group() {
         let batch_size = 3;
         let params = test_params();
-        let mut buffered_input = BufferedInput::new(&params, vec![]);
+        let mut buffered_input = BufferedInput::new(&params, vec![], 2);
         let mut batches = test_records(batch_size);
 
         // There are no rows, so that is less than the batch size,
@@ -454,7 +504,7 @@ mod tests {
     fn with_group() {
         let params = test_params();
         let group_cols = vec![0, 1];
-        let mut buffered_input = BufferedInput::new(&params, group_cols);
+        let mut buffered_input = BufferedInput::new(&params, group_cols, 2);
 
         let batch_size = 3;
         let mut batches = test_records(batch_size);
@@ -479,7 +529,7 @@ mod tests {
     fn struct_with_group() {
         let params = test_params();
         let group_cols = vec![0, 1];
-        let mut buffered_input = BufferedInput::new(&params, group_cols);
+        let mut buffered_input = BufferedInput::new(&params, group_cols, 2);
 
         let batch_size = 3;
         let mut batches = test_struct_records(batch_size);
@@ -112,6 +112,91 @@ fn test_gapfill_simple_tz() {
     }}
 }
 
+#[test]
+fn test_gapfill_timezone_dst_start() {
+    // Daylight saving time starts in Berlin on Sunday, March 26, 2023,
+    // so that day is only 23 hours long.
+    test_helpers::maybe_start_logging();
+    insta::allow_duplicates! { for output_batch_size in [1, 2, 4, 8] {
+        for input_batch_size in [1, 2] {
+            let batch = TestRecords {
+                group_cols: vec![vec![Some("a"), Some("a")]],
+                time_col: vec![Some(1_679_698_800_000), Some(1_679_954_400_000)],
+                timezone: Some("Europe/Berlin".into()),
+                agg_cols: vec![vec![Some(10), Some(11)]],
+                struct_cols: vec![],
+                input_batch_size,
+            };
+            let mut params =
+                get_params_ms(&batch, 0, Some(1_679_698_800_000), 1_679_954_400_000);
+            params.stride = phys_lit(ScalarValue::new_interval_mdn(0, 1, 0));
+            params.timezone = Some(chrono_tz::Europe::Berlin);
+            let tc = TestCase {
+                test_records: batch,
+                output_batch_size,
+                params,
+            };
+            let batches = tc.run().unwrap();
+            let actual = batches_to_lines(&batches);
+            insta::assert_yaml_snapshot!(actual, @r###"
+            ---
+            - +----+---------------------------+----+
+            - "| g0 | time                      | a0 |"
+            - +----+---------------------------+----+
+            - "| a  | 2023-03-25T00:00:00+01:00 | 10 |"
+            - "| a  | 2023-03-26T00:00:00+01:00 |    |"
+            - "| a  | 2023-03-27T00:00:00+02:00 |    |"
+            - "| a  | 2023-03-28T00:00:00+02:00 | 11 |"
+            - +----+---------------------------+----+
+            "###);
+            assert_batch_count(&batches, output_batch_size);
+        }
+    }}
+}
+
+#[test]
+fn test_gapfill_timezone_dst_end() {
+    // Daylight saving time ends in Berlin on Sunday, October 29, 2023,
+    // so that day is 25 hours long.
+    test_helpers::maybe_start_logging();
+    insta::allow_duplicates! { for output_batch_size in [1, 2, 4, 8] {
+        for input_batch_size in [1, 2] {
+            let batch = TestRecords {
+                group_cols: vec![vec![Some("a"), Some("a")]],
+                time_col: vec![Some(1_698_530_400_000), Some(1_698_706_800_000)],
+                timezone: Some("Europe/Berlin".into()),
+                agg_cols: vec![vec![Some(10), Some(11)]],
+                struct_cols: vec![],
+                input_batch_size,
+            };
+            // The time range starts and ends part way through a day
+            let mut params =
+                get_params_ms(&batch, 0, Some(1_698_480_000_000), 1_698_750_000_000);
+            params.stride = phys_lit(ScalarValue::new_interval_mdn(0, 1, 0));
+            params.timezone = Some(chrono_tz::Europe::Berlin);
+            let tc = TestCase {
+                test_records: batch,
+                output_batch_size,
+                params,
+            };
+            let batches = tc.run().unwrap();
+            let actual = batches_to_lines(&batches);
+            insta::assert_yaml_snapshot!(actual, @r###"
+            ---
+            - +----+---------------------------+----+
+            - "| g0 | time                      | a0 |"
+            - +----+---------------------------+----+
+            - "| a  | 2023-10-28T00:00:00+02:00 |    |"
+            - "| a  | 2023-10-29T00:00:00+02:00 | 10 |"
+            - "| a  | 2023-10-30T00:00:00+01:00 |    |"
+            - "| a  | 2023-10-31T00:00:00+01:00 | 11 |"
+            - +----+---------------------------+----+
+            "###);
+            assert_batch_count(&batches, output_batch_size);
+        }
+    }}
+}
+
 #[test]
 fn test_gapfill_simple_no_group_no_aggr() {
     // There may be no group columns in a gap fill query,
@@ -978,6 +1063,164 @@ fn test_gapfill_fill_interpolate() {
     }
 }
 
+#[test]
+fn test_gapfill_fill_prev_lookback() {
+    test_helpers::maybe_start_logging();
+    insta::allow_duplicates! {
+        for output_batch_size in [16, 1] {
+        for input_batch_size in [8, 1] {
+            let records = TestRecords {
+                group_cols: vec![vec![
+                    Some("a"),
+                    Some("a"),
+                    Some("a"),
+                    // --- new series
+                    Some("b"),
+                    // --- new series
+                    Some("c"),
+                ]],
+                time_col: vec![
+                    // lookback rows before the range
+                    Some(950),
+                    Some(975),
+                    // 1000
+                    // 1025
+                    Some(1050),
+                    // 1075
+                    // 1100
+                    // --- new series
+                    // only a lookback row
+                    Some(925),
+                    // --- new series
+                    Some(1000),
+                ],
+                timezone: None,
+                agg_cols: vec![vec![
+                    Some(5),   // a: 950
+                    None,      // a: 975
+                    Some(10),  // a: 1050
+                    Some(7),   // b: 925
+                    Some(20),  // c: 1000
+                ]],
+                struct_cols: vec![],
+                input_batch_size,
+            };
+            let params = get_params_ms_with_fill_strategy(&records, 25, Some(1000), 1_100, FillStrategy::PrevNullAsMissing);
+            let tc = TestCase {
+                test_records: records,
+                output_batch_size,
+                params,
+            };
+            let batches = tc.run().unwrap();
+            let actual = batches_to_lines(&batches);
+            insta::with_settings!({
+                description => format!("input_batch_size: {input_batch_size}, output_batch_size: {output_batch_size}"),
+            }, {
+                insta::assert_yaml_snapshot!(actual, @r###"
+                ---
+                - +----+--------------------------+----+
+                - "| g0 | time                     | a0 |"
+                - +----+--------------------------+----+
+                - "| a  | 1970-01-01T00:00:01Z     | 5  |"
+                - "| a  | 1970-01-01T00:00:01.025Z | 5  |"
+                - "| a  | 1970-01-01T00:00:01.050Z | 10 |"
+                - "| a  | 1970-01-01T00:00:01.075Z | 10 |"
+                - "| a  | 1970-01-01T00:00:01.100Z | 10 |"
+                - "| b  | 1970-01-01T00:00:01Z     | 7  |"
+                - "| b  | 1970-01-01T00:00:01.025Z | 7  |"
+                - "| b  | 1970-01-01T00:00:01.050Z | 7  |"
+                - "| b  | 1970-01-01T00:00:01.075Z | 7  |"
+                - "| b  | 1970-01-01T00:00:01.100Z | 7  |"
+                - "| c  | 1970-01-01T00:00:01Z     | 20 |"
+                - "| c  | 1970-01-01T00:00:01.025Z | 20 |"
+                - "| c  | 1970-01-01T00:00:01.050Z | 20 |"
+                - "| c  | 1970-01-01T00:00:01.075Z | 20 |"
+                - "| c  | 1970-01-01T00:00:01.100Z | 20 |"
+                - +----+--------------------------+----+
+                "###)
+            });
+            assert_batch_count(&batches, output_batch_size);
+        }
+    }}
+}
+
+#[test]
+fn test_gapfill_fill_interpolate_lookback() {
+    test_helpers::maybe_start_logging();
+    insta::allow_duplicates! {
+        for output_batch_size in [16, 1] {
+        for input_batch_size in [8, 1] {
+            let records = TestRecords {
+                group_cols: vec![vec![
+                    Some("a"),
+                    Some("a"),
+                    // --- new series
+                    Some("b"),
+                    Some("b"),
+                    Some("b"),
+                ]],
+                time_col: vec![
+                    // lookback row before the range
+                    Some(950),
+                    // 1000
+                    // 1025
+                    Some(1050),
+                    // 1075
+                    // 1100
+                    // --- new series
+                    // lookback row before the range
+                    Some(975),
+                    Some(1000),
+                    // 1025
+                    // 1050
+                    Some(1075),
+                    // 1100
+                ],
+                timezone: None,
+                agg_cols: vec![vec![
+                    Some(100), // a: 950
+                    Some(200), // a: 1050
+                    Some(10),  // b: 975
+                    None,      // b: 1000 (this null value will be filled)
+                    Some(50),  // b: 1075
+                ]],
+                struct_cols: vec![],
+                input_batch_size,
+            };
+            let params = get_params_ms_with_fill_strategy(&records, 25, Some(1000), 1_100, FillStrategy::LinearInterpolate);
+            let tc = TestCase {
+                test_records: records,
+                output_batch_size,
+                params,
+            };
+            let batches = tc.run().unwrap();
+            let actual = batches_to_lines(&batches);
+            insta::with_settings!({
+                description => format!("input_batch_size: {input_batch_size}, output_batch_size: {output_batch_size}"),
+            }, {
+                insta::assert_yaml_snapshot!(actual, @r###"
+                ---
+                - +----+--------------------------+-----+
+                - "| g0 | time                     | a0  |"
+                - +----+--------------------------+-----+
+                - "| a  | 1970-01-01T00:00:01Z     | 150 |"
+                - "| a  | 1970-01-01T00:00:01.025Z | 175 |"
+                - "| a  | 1970-01-01T00:00:01.050Z | 200 |"
+                - "| a  | 1970-01-01T00:00:01.075Z |     |"
+                - "| a  | 1970-01-01T00:00:01.100Z |     |"
+                - "| b  | 1970-01-01T00:00:01Z     | 20  |"
+                - "| b  | 1970-01-01T00:00:01.025Z | 30  |"
+                - "| b  | 1970-01-01T00:00:01.050Z | 40  |"
+                - "| b  | 1970-01-01T00:00:01.075Z | 50  |"
+                - "| b  | 1970-01-01T00:00:01.100Z |     |"
+                - +----+--------------------------+-----+
+                "###)
+            });
+            assert_batch_count(&batches, output_batch_size);
+        }
+    }}
+}
+
 #[test]
 fn test_gapfill_simple_no_lower_bound_with_nulls() {
     test_helpers::maybe_start_logging();
@@ -1595,6 +1838,7 @@ fn get_params_ms_with_origin_fill_strategy(
         stride: phys_lit(stride),
         time_column: Column::new("t", batch.group_cols.len()),
         origin,
+        timezone: None,
         // timestamps are nanos, so scale them accordingly
         time_range: Range {
             start: bound_included_from_option(start.map(|start| {
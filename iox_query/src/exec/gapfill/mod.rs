@@ -15,6 +15,7 @@ use std::{
 };
 
 use arrow::{compute::SortOptions, datatypes::SchemaRef};
+use chrono_tz::Tz;
 use datafusion::{
     common::DFSchemaRef,
     error::{DataFusionError, Result},
@@ -58,6 +59,9 @@ pub struct GapFillParams {
     pub time_column: Expr,
     /// The origin argument from the call to DATE_BIN_GAPFILL
     pub origin: Option<Expr>,
+    /// The timezone argument from the call to DATE_BIN_GAPFILL. If specified,
+    /// time is binned on the wall clock of the timezone.
+    pub timezone: Option<Tz>,
     /// The time range of the time column inferred from predicates
     /// in the overall query. The lower bound may be [`Bound::Unbounded`]
     /// which implies that gap-filling should just start from the
@@ -138,6 +142,7 @@ impl GapFillParams {
             stride,
             time_column,
             origin,
+            timezone: self.timezone,
             time_range,
             fill_strategy,
         }
@@ -238,7 +243,11 @@ impl UserDefinedLogicalNodeCore for GapFill {
             self.params.time_column,
             self.params.stride,
             self.params.time_range,
-        )
+        )?;
+        if let Some(tz) = self.params.timezone {
+            write!(f, ", timezone={tz}")?;
+        }
+        Ok(())
     }
 
     fn from_template(&self, exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
@@ -327,6 +336,7 @@ pub(crate) fn plan_gap_fill(
         stride,
         time_column,
         origin,
+        timezone: gap_fill.params.timezone,
         time_range,
         fill_strategy,
     };
@@ -390,6 +400,8 @@ struct GapFillExecParams {
     time_column: Column,
     /// The origin argument from the all to DATE_BIN_GAPFILL
     origin: Option<Arc<dyn PhysicalExpr>>,
+    /// The timezone argument from the call to DATE_BIN_GAPFILL
+    timezone: Option<Tz>,
     /// The time range of source input to DATE_BIN_GAPFILL.
     /// Inferred from predicates in the overall query.
     time_range: Range<Bound<Arc<dyn PhysicalExpr>>>,
@@ -568,7 +580,11 @@ impl DisplayAs for GapFillExec {
                     aggr_expr.join(", "),
                     self.params.stride,
                     time_range
-                )
+                )?;
+                if let Some(tz) = self.params.timezone {
+                    write!(f, ", timezone={tz}")?;
+                }
+                Ok(())
             }
         }
     }
@@ -627,6 +643,7 @@ mod test {
                 stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
                 time_column: col("time"),
                 origin: None,
+                timezone: None,
                 time_range: Range {
                     start: Bound::Included(lit_timestamp_nano(1000)),
                     end: Bound::Unbounded,
@@ -667,6 +684,7 @@ mod test {
                 stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
                 time_column: col("time"),
                 origin: None,
+                timezone: None,
                 time_range: Range {
                     start: Bound::Unbounded,
                     end: Bound::Excluded(lit_timestamp_nano(2000)),
@@ -678,6 +696,7 @@ mod test {
                 stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
                 time_column: col("time"),
                 origin: None,
+                timezone: None,
                 time_range: Range {
                     start: Bound::Included(lit_timestamp_nano(1000)),
                     end: Bound::Excluded(lit_timestamp_nano(2000)),
@@ -689,6 +708,7 @@ mod test {
                 stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
                 time_column: col("time"),
                 origin: Some(lit_timestamp_nano(1_000_000_000)),
+                timezone: None,
                 time_range: Range {
                     start: Bound::Unbounded,
                     end: Bound::Excluded(lit_timestamp_nano(2000)),
@@ -700,6 +720,19 @@ mod test {
                 stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
                 time_column: col("time"),
                 origin: Some(lit_timestamp_nano(1_000_000_000)),
+                timezone: None,
+                time_range: Range {
+                    start: Bound::Included(lit_timestamp_nano(1000)),
+                    end: Bound::Excluded(lit_timestamp_nano(2000)),
+                },
+                fill_strategy: fill_strategy_null(vec![col("temp")]),
+            },
+            // yes origin, yes start bound, yes timezone
+            GapFillParams {
+                stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
+                time_column: col("time"),
+                origin: Some(lit_timestamp_nano(1_000_000_000)),
+                timezone: Some(chrono_tz::Europe::Berlin),
                 time_range: Range {
                     start: Bound::Included(lit_timestamp_nano(1000)),
                     end: Bound::Excluded(lit_timestamp_nano(2000)),
@@ -733,6 +766,7 @@ mod test {
                 stride: lit(ScalarValue::IntervalDayTime(Some(60_000))),
                 time_column: col("time"),
                 origin: None,
+                timezone: None,
                 time_range: Range {
                     start: Bound::Included(lit_timestamp_nano(1000)),
                     end: Bound::Excluded(lit_timestamp_nano(2000)),
@@ -13,6 +13,7 @@ use datafusion::{
     scalar::ScalarValue,
 };
 use hashbrown::HashMap;
+use query_functions::date_bin_wallclock::WallclockBins;
 
 use super::{try_map_bound, try_map_range, FillStrategy, GapFillExecParams};
 
@@ -22,8 +23,8 @@ use super::{try_map_bound, try_map_range, FillStrategy, GapFillExecParams};
 /// When we support `locf` for aggregate columns, that will be tracked here.
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct GapFillParams {
-    /// The stride in nanoseconds of the timestamps to be output.
-    pub stride: i64,
+    /// The stride of the timestamps to be output.
+    pub stride: Stride,
     /// The first timestamp (inclusive) to be output for each series,
     /// in nanoseconds since the epoch. `None` means gap filling should
     /// start from the first timestamp in each series.
@@ -75,17 +76,40 @@ impl GapFillParams {
             }
         };
 
-        // Call date_bin on the timestamps to find the first and last time bins
-        // for each series
-        let mut args = vec![stride, i64_to_columnar_ts(first_ts)];
-        if let Some(v) = origin {
-            args.push(v)
-        }
-        let first_ts = first_ts
-            .map(|_| extract_timestamp_nanos(&date_bin(&args)?))
-            .transpose()?;
-        args[1] = i64_to_columnar_ts(Some(last_ts));
-        let last_ts = extract_timestamp_nanos(&date_bin(&args)?)?;
+        let (stride, first_ts, last_ts) = if params.timezone.is_some() || is_month_interval(&stride)
+        {
+            // Bin on the wall clock, the same way as the date_bin_wallclock
+            // function that produced the input
+            let ColumnarValue::Scalar(stride) = &stride else {
+                return Err(DataFusionError::Execution(
+                    "gap filling expects a stride parameter to be a scalar interval".to_string(),
+                ));
+            };
+            let origin = origin.as_ref().map(extract_timestamp_nanos).transpose()?;
+            let bins = WallclockBins::try_new(stride, origin, params.timezone)?;
+            (
+                Stride::Wallclock(bins),
+                first_ts.map(|ts| bins.bin(ts)),
+                bins.bin(last_ts),
+            )
+        } else {
+            // Call date_bin on the timestamps to find the first and last time bins
+            // for each series
+            let mut args = vec![stride, i64_to_columnar_ts(first_ts)];
+            if let Some(v) = origin {
+                args.push(v)
+            }
+            let first_ts = first_ts
+                .map(|_| extract_timestamp_nanos(&date_bin(&args)?))
+                .transpose()?;
+            args[1] = i64_to_columnar_ts(Some(last_ts));
+            let last_ts = extract_timestamp_nanos(&date_bin(&args)?)?;
+            (
+                Stride::Nanos(extract_interval_nanos(&args[0])?),
+                first_ts,
+                last_ts,
+            )
+        };
 
         let fill_strategy = params
             .fill_strategy
@@ -103,7 +127,7 @@ impl GapFillParams {
             .collect::<Result<HashMap<usize, FillStrategy>>>()?;
 
         Ok(Self {
-            stride: extract_interval_nanos(&args[0])?,
+            stride,
             first_ts,
             last_ts,
             fill_strategy,
@@ -112,10 +136,42 @@ impl GapFillParams {
 
     /// Returns the number of rows remaining for a series that starts with first_ts.
     pub fn valid_row_count(&self, first_ts: i64) -> usize {
-        if self.last_ts >= first_ts {
-            ((self.last_ts - first_ts) / self.stride + 1) as usize
-        } else {
-            0
+        self.stride.count(first_ts, self.last_ts)
+    }
+}
+
+/// The stride of the timestamps output by gap filling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Stride {
+    /// A fixed number of nanoseconds.
+    Nanos(i64),
+    /// Bins on the wall clock of a timezone, or of calendar months,
+    /// which vary in length.
+    Wallclock(WallclockBins),
+}
+
+impl Stride {
+    /// Returns the timestamp `n` strides after `ts`, which must be the
+    /// start of a bin.
+    pub fn nth(&self, ts: i64, n: i64) -> i64 {
+        match self {
+            Self::Nanos(stride) => ts + n * stride,
+            Self::Wallclock(bins) => bins.nth(ts, n),
+        }
+    }
+
+    /// Returns the timestamp one stride after `ts`, which must be the
+    /// start of a bin.
+    pub fn next(&self, ts: i64) -> i64 {
+        self.nth(ts, 1)
+    }
+
+    /// Returns the number of timestamps from `first_ts` to `last_ts`, inclusive.
+    fn count(&self, first_ts: i64, last_ts: i64) -> usize {
+        match self {
+            Self::Nanos(_) if last_ts < first_ts => 0,
+            Self::Nanos(stride) => ((last_ts - first_ts) / stride + 1) as usize,
+            Self::Wallclock(bins) => bins.count(first_ts, last_ts),
         }
     }
 }
@@ -138,6 +194,16 @@ fn extract_timestamp_nanos(cv: &ColumnarValue) -> Result<i64> {
     })
 }
 
+fn is_month_interval(cv: &ColumnarValue) -> bool {
+    match cv {
+        ColumnarValue::Scalar(ScalarValue::IntervalMonthDayNano(Some(v))) => {
+            IntervalMonthDayNanoType::to_parts(*v).0 != 0
+        }
+        ColumnarValue::Scalar(ScalarValue::IntervalYearMonth(Some(v))) => *v != 0,
+        _ => false,
+    }
+}
+
 fn extract_interval_nanos(cv: &ColumnarValue) -> Result<i64> {
     match cv {
         ColumnarValue::Scalar(ScalarValue::IntervalMonthDayNano(Some(v))) => {
@@ -170,6 +236,7 @@ mod tests {
 
     use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
     use datafusion::{
+        common::assert_contains,
         datasource::empty::EmptyTable,
         error::Result,
         physical_plan::{
@@ -185,7 +252,7 @@ mod tests {
         Executor, ExecutorType,
     };
 
-    use super::GapFillParams;
+    use super::{GapFillParams, Stride};
 
     #[tokio::test]
     async fn test_evaluate_params() -> Result<()> {
@@ -200,7 +267,7 @@ mod tests {
         )
         .await?;
         let expected = GapFillParams {
-            stride: 60_000_000_000,                  // 1 minute
+            stride: Stride::Nanos(60_000_000_000),   // 1 minute
             first_ts: Some(441_820_500_000_000_000), // Sunday, January 1, 1984 3:55:00 PM
             last_ts: 441_820_800_000_000_000,        // Sunday, January 1, 1984 3:59:00 PM
             fill_strategy: HashMap::new(),
@@ -222,7 +289,7 @@ mod tests {
                \ngroup by minute",
             ).await?;
         let expected = GapFillParams {
-            stride: 60_000_000_000,                  // 1 minute
+            stride: Stride::Nanos(60_000_000_000),   // 1 minute
             first_ts: Some(441_820_500_000_000_000), // Sunday, January 1, 1984 3:55:00 PM
             last_ts: 441_820_800_000_000_000,        // Sunday, January 1, 1984 3:59:00 PM
             fill_strategy: HashMap::new(),
@@ -244,7 +311,7 @@ mod tests {
         )
         .await?;
         let expected = GapFillParams {
-            stride: 60_000_000_000,                  // 1 minute
+            stride: Stride::Nanos(60_000_000_000),   // 1 minute
             first_ts: Some(441_820_500_000_000_000), // Sunday, January 1, 1984 3:55:00 PM
             // Last bin at 16:00 is excluded
             last_ts: 441_820_740_000_000_000, // Sunday, January 1, 1984 3:59:00 PM
@@ -267,7 +334,7 @@ mod tests {
         )
         .await?;
         let expected = GapFillParams {
-            stride: 60_000_000_000, // 1 minute
+            stride: Stride::Nanos(60_000_000_000), // 1 minute
             // First bin not exluded since it truncates to 15:55:00
             first_ts: Some(441_820_500_000_000_000), // Sunday, January 1, 1984 3:55:00 PM
             last_ts: 441_820_800_000_000_000,        // Sunday, January 1, 1984 3:59:00 PM
@@ -290,7 +357,7 @@ mod tests {
                \ngroup by minute",
             ).await?;
         let expected = GapFillParams {
-            stride: 60_000_000_000,                  // 1 minute
+            stride: Stride::Nanos(60_000_000_000),   // 1 minute
             first_ts: Some(441_820_449_000_000_000), // Sunday, January 1, 1984 3:54:09 PM
             last_ts: 441_820_749_000_000_000,        // Sunday, January 1, 1984 3:59:09 PM
             fill_strategy: HashMap::new(),
@@ -299,6 +366,72 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_evaluate_params_timezone() -> Result<()> {
+        test_helpers::maybe_start_logging();
+        // Daylight saving time starts in Berlin on Sunday, March 26, 2023
+        let actual = plan_statement_and_get_params(
+                "select\
+               \n    date_bin_gapfill(interval '1 day', time, timestamp '1970-01-01T00:00:00Z', 'Europe/Berlin') day\
+               \nfrom t\
+               \nwhere time >= timestamp '2023-03-24T23:00:00Z'\
+               \n    and time < timestamp '2023-03-28T22:00:00Z'\
+               \ngroup by day",
+            ).await?;
+        assert!(matches!(actual.stride, Stride::Wallclock(_)));
+        // Saturday, March 25, 2023 00:00:00 CET
+        assert_eq!(actual.first_ts, Some(1_679_698_800_000_000_000));
+        // Tuesday, March 28, 2023 00:00:00 CEST
+        assert_eq!(actual.last_ts, 1_679_954_400_000_000_000);
+        assert_eq!(actual.valid_row_count(1_679_698_800_000_000_000), 4);
+        // Sunday, March 26, 2023 00:00:00 CET
+        let sunday = actual.stride.next(1_679_698_800_000_000_000);
+        assert_eq!(sunday, 1_679_785_200_000_000_000);
+        // Monday, March 27, 2023 00:00:00 CEST, 23 hours later
+        assert_eq!(actual.stride.next(sunday), 1_679_868_000_000_000_000);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_params_months() -> Result<()> {
+        test_helpers::maybe_start_logging();
+        let actual = plan_statement_and_get_params(
+            "select\
+               \n    date_bin_gapfill(interval '1 month', time) month\
+               \nfrom t\
+               \nwhere time >= timestamp '2023-01-15T00:00:00Z'\
+               \n    and time < timestamp '2023-04-15T00:00:00Z'\
+               \ngroup by month",
+        )
+        .await?;
+        assert!(matches!(actual.stride, Stride::Wallclock(_)));
+        // Sunday, January 1, 2023 00:00:00
+        assert_eq!(actual.first_ts, Some(1_672_531_200_000_000_000));
+        // Saturday, April 1, 2023 00:00:00
+        assert_eq!(actual.last_ts, 1_680_307_200_000_000_000);
+        assert_eq!(actual.valid_row_count(1_672_531_200_000_000_000), 4);
+        // Wednesday, February 1, 2023 00:00:00
+        assert_eq!(
+            actual.stride.next(1_672_531_200_000_000_000),
+            1_675_209_600_000_000_000
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_params_invalid_timezone() {
+        test_helpers::maybe_start_logging();
+        let err = plan_statement_and_get_params(
+                "select\
+               \n    date_bin_gapfill(interval '1 day', time, timestamp '1970-01-01T00:00:00Z', 'Europe/Atlantis') day\
+               \nfrom t\
+               \nwhere time >= timestamp '2023-03-24T23:00:00Z'\
+               \n    and time < timestamp '2023-03-28T22:00:00Z'\
+               \ngroup by day",
+            ).await.unwrap_err();
+        assert_contains!(err.to_string(), "invalid timezone: 'Europe/Atlantis'");
+    }
+
     fn interval(ns: i64) -> Arc<dyn PhysicalExpr> {
         Arc::new(Literal::new(ScalarValue::new_interval_mdn(0, 0, ns)))
     }
@@ -316,6 +449,7 @@ mod tests {
             stride: interval(1_000_000_000),
             time_column: Column::new("time", 0),
             origin: None,
+            timezone: None,
             time_range: Range {
                 start: Bound::Unbounded,
                 end: Bound::Excluded(timestamp(20_000_000_000)),
@@ -330,7 +464,7 @@ mod tests {
         let actual = GapFillParams::try_new(schema().into(), &exec_params).unwrap();
         assert_eq!(
             GapFillParams {
-                stride: 1_000_000_000,
+                stride: Stride::Nanos(1_000_000_000),
                 first_ts: None,
                 last_ts: 19_000_000_000,
                 fill_strategy: simple_fill_strategy(),
@@ -344,7 +478,7 @@ mod tests {
     fn test_params_row_count() -> Result<()> {
         test_helpers::maybe_start_logging();
         let params = GapFillParams {
-            stride: 10,
+            stride: Stride::Nanos(10),
             first_ts: Some(1000),
             last_ts: 1050,
             fill_strategy: simple_fill_strategy(),
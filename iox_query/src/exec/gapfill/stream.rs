@@ -91,7 +91,7 @@ impl GapFillStream {
 
         let group_cols = group_expr.iter().map(expr_to_index).collect::<Vec<_>>();
         let params = GapFillParams::try_new(Arc::clone(&schema), params)?;
-        let buffered_input = BufferedInput::new(&params, group_cols);
+        let buffered_input = BufferedInput::new(&params, group_cols, expr_to_index(&time_expr));
 
         let gap_filler = GapFiller::new(params, batch_size);
         Ok(Self {
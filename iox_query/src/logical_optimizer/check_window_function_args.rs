@@ -0,0 +1,158 @@
+use datafusion::{
+    common::tree_node::{TreeNode, VisitRecursion},
+    error::Result,
+    logical_expr::{expr::WindowFunction, window_function, LogicalPlan},
+    optimizer::{optimizer::ApplyOrder, OptimizerConfig, OptimizerRule},
+    prelude::Expr,
+};
+use query_functions::window_functions::check_constant_arguments;
+
+/// Rejects calls to the IOx window functions whose constant arguments, such as
+/// the `unit` of `derivative` or the window size of `moving_average`, are not
+/// literals.
+///
+/// This rule does not change the plan.
+#[derive(Debug, Default)]
+pub struct CheckWindowFunctionArgs;
+
+impl CheckWindowFunctionArgs {
+    /// Create new optimizer rule.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for CheckWindowFunctionArgs {
+    fn name(&self) -> &str {
+        "check_window_function_args"
+    }
+
+    fn try_optimize(
+        &self,
+        plan: &LogicalPlan,
+        _config: &dyn OptimizerConfig,
+    ) -> Result<Option<LogicalPlan>> {
+        if let LogicalPlan::Window(window) = plan {
+            for expr in &window.window_expr {
+                expr.apply(&mut |expr| {
+                    if let Expr::WindowFunction(WindowFunction {
+                        fun: window_function::WindowFunction::WindowUDF(fun),
+                        args,
+                        ..
+                    }) = expr
+                    {
+                        check_constant_arguments(fun, args)?;
+                    }
+                    Ok(VisitRecursion::Continue)
+                })?;
+            }
+        }
+        Ok(None)
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::BottomUp)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::CheckWindowFunctionArgs;
+
+    use arrow::{
+        array::{ArrayRef, Float64Array, Int64Array, TimestampNanosecondArray},
+        record_batch::RecordBatch,
+    };
+    use datafusion::error::Result;
+    use datafusion::logical_expr::LogicalPlan;
+    use datafusion::optimizer::optimizer::Optimizer;
+    use datafusion::optimizer::OptimizerContext;
+    use datafusion_util::context_with_table;
+    use query_functions::window_functions::register_window_functions;
+
+    async fn sql_plan(sql: &str) -> LogicalPlan {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "time",
+                Arc::new(TimestampNanosecondArray::from(vec![0, 60_000_000_000])) as ArrayRef,
+            ),
+            (
+                "usage",
+                Arc::new(Float64Array::from(vec![1.0, 4.0])) as ArrayRef,
+            ),
+            ("n", Arc::new(Int64Array::from(vec![2, 3])) as ArrayRef),
+        ])
+        .unwrap();
+
+        let ctx = context_with_table(batch);
+        register_window_functions(&ctx);
+        ctx.state().create_logical_plan(sql).await.unwrap()
+    }
+
+    fn optimize(plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+        let optimizer = Optimizer::with_rules(vec![Arc::new(CheckWindowFunctionArgs)]);
+        optimizer.optimize_recursively(
+            optimizer.rules.first().unwrap(),
+            plan,
+            &OptimizerContext::new(),
+        )
+    }
+
+    fn assert_optimizer_err(plan: &LogicalPlan, expected: &str) {
+        match optimize(plan) {
+            Ok(plan) => assert_eq!(format!("{plan:?}"), "an error"),
+            Err(ref e) => {
+                let actual = e.to_string();
+                if expected.is_empty() || !actual.contains(expected) {
+                    assert_eq!(actual, expected)
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn constant_arguments() {
+        let plan = sql_plan(
+            "SELECT \
+               derivative(usage, time) OVER (ORDER BY time), \
+               derivative(usage, time, INTERVAL '1 minute') OVER (ORDER BY time), \
+               non_negative_derivative(usage, time, INTERVAL '1 minute') OVER (ORDER BY time), \
+               moving_average(usage, 2) OVER (ORDER BY time) \
+             FROM t",
+        )
+        .await;
+        assert!(optimize(&plan).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn derivative_unit_not_constant() {
+        let plan = sql_plan(
+            "SELECT derivative(usage, time, CASE WHEN n > 2 THEN INTERVAL '1 minute' ELSE INTERVAL '1 second' END) OVER (ORDER BY time) FROM t",
+        )
+        .await;
+        assert_optimizer_err(
+            &plan,
+            "Error during planning: derivative unit must be a constant",
+        );
+
+        let plan = sql_plan(
+            "SELECT non_negative_derivative(usage, time, CASE WHEN n > 2 THEN INTERVAL '1 minute' ELSE INTERVAL '1 second' END) OVER (ORDER BY time) FROM t",
+        )
+        .await;
+        assert_optimizer_err(
+            &plan,
+            "Error during planning: non_negative_derivative unit must be a constant",
+        );
+    }
+
+    #[tokio::test]
+    async fn moving_average_window_size_not_constant() {
+        let plan = sql_plan("SELECT moving_average(usage, n) OVER (ORDER BY time) FROM t").await;
+        assert_optimizer_err(
+            &plan,
+            "Error during planning: moving_average window size must be a constant, got t.n",
+        );
+    }
+}
@@ -1,9 +1,14 @@
 //! An optimizer rule that transforms a plan
 //! to fill gaps in time series data.
 
+pub mod lookback;
 pub mod range_predicate;
 
-use crate::exec::gapfill::{FillStrategy, GapFill, GapFillParams};
+use crate::{
+    config::IoxConfigExt,
+    exec::gapfill::{FillStrategy, GapFill, GapFillParams},
+};
+use arrow::datatypes::IntervalMonthDayNanoType;
 use datafusion::{
     common::tree_node::{RewriteRecursion, TreeNode, TreeNodeRewriter, VisitRecursion},
     error::{DataFusionError, Result},
@@ -14,13 +19,18 @@ use datafusion::{
     },
     optimizer::{optimizer::ApplyOrder, OptimizerConfig, OptimizerRule},
     prelude::{col, Expr},
+    scalar::ScalarValue,
 };
 use hashbrown::{hash_map, HashMap};
-use query_functions::gapfill::{DATE_BIN_GAPFILL_UDF_NAME, INTERPOLATE_UDF_NAME, LOCF_UDF_NAME};
+use query_functions::{
+    date_bin_wallclock::{parse_timezone, DATE_BIN_WALLCLOCK_UDF_NAME},
+    gapfill::{DATE_BIN_GAPFILL_UDF_NAME, INTERPOLATE_UDF_NAME, LOCF_UDF_NAME},
+};
 use std::{
     collections::HashSet,
     ops::{Bound, Range},
     sync::Arc,
+    time::Duration,
 };
 
 /// This optimizer rule enables gap-filling semantics for SQL queries
@@ -56,13 +66,17 @@ use std::{
 /// ```
 ///
 /// For `Aggregate` nodes that contain calls to `DATE_BIN_GAPFILL`, this rule will:
-/// - Convert `DATE_BIN_GAPFILL()` to `DATE_BIN()`
+/// - Convert `DATE_BIN_GAPFILL()` to `DATE_BIN()`, or to `DATE_BIN_WALLCLOCK()` if it
+///   specifies a timezone or a stride of months
 /// - Create a `GapFill` node that fills in gaps in the query
 /// - The range for gap filling is found by analyzing any preceding `Filter` nodes
 ///
 /// If there is a `Projection` above the `GapFill` node that gets created:
 /// - Look for calls to gap-filling functions like `LOCF`
 /// - Push down these functions into the `GapFill` node, updating the fill strategy for the column.
+/// - If the `iox.gap_fill_lookback` option is set, also aggregate the window before the
+///   time range, so the first gaps can be filled with the most recent values from before
+///   the range. See [`lookback::with_lookback`].
 ///
 /// Note: both `DATE_BIN_GAPFILL` and `LOCF` are functions that don't have implementations.
 /// This rule must rewrite the plan to get rid of them.
@@ -84,9 +98,14 @@ impl OptimizerRule for HandleGapFill {
     fn try_optimize(
         &self,
         plan: &LogicalPlan,
-        _config: &dyn OptimizerConfig,
+        config: &dyn OptimizerConfig,
     ) -> Result<Option<LogicalPlan>> {
-        handle_gap_fill(plan)
+        let lookback = config
+            .options()
+            .extensions
+            .get::<IoxConfigExt>()
+            .and_then(|ext| ext.gap_fill_lookback.duration());
+        handle_gap_fill(plan, lookback)
     }
 
     fn name(&self) -> &str {
@@ -98,10 +117,10 @@ impl OptimizerRule for HandleGapFill {
     }
 }
 
-fn handle_gap_fill(plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+fn handle_gap_fill(plan: &LogicalPlan, lookback: Option<Duration>) -> Result<Option<LogicalPlan>> {
     let res = match plan {
         LogicalPlan::Aggregate(aggr) => handle_aggregate(aggr)?,
-        LogicalPlan::Projection(proj) => handle_projection(proj)?,
+        LogicalPlan::Projection(proj) => handle_projection(proj, lookback)?,
         _ => None,
     };
 
@@ -162,10 +181,10 @@ fn build_gapfill_node(
     date_bin_gapfill_args: Vec<Expr>,
 ) -> Result<LogicalPlan> {
     match date_bin_gapfill_args.len() {
-        2 | 3 => (),
+        2..=4 => (),
         nargs => {
             return Err(DataFusionError::Plan(format!(
-                "DATE_BIN_GAPFILL expects 2 or 3 arguments, got {nargs}",
+                "DATE_BIN_GAPFILL expects 2 to 4 arguments, got {nargs}",
             )));
         }
     }
@@ -193,6 +212,17 @@ fn build_gapfill_node(
         validate_scalar_expr("origin argument to DATE_BIN_GAPFILL", origin)?;
     }
 
+    // Ensure that timezone argument is the name of a timezone
+    let timezone = args_iter
+        .next()
+        .map(|tz| match tz {
+            Expr::Literal(ScalarValue::Utf8(Some(name))) => parse_timezone(&name),
+            _ => Err(DataFusionError::Plan(
+                "timezone argument to DATE_BIN_GAPFILL must be a string literal".to_string(),
+            )),
+        })
+        .transpose()?;
+
     // Make sure the time output to the gapfill node matches what the
     // aggregate output was.
     let time_column =
@@ -227,6 +257,7 @@ fn build_gapfill_node(
                 stride,
                 time_column,
                 origin,
+                timezone,
                 time_range,
                 fill_strategy: fill_behavior,
             },
@@ -270,7 +301,7 @@ fn validate_scalar_expr(what: &str, e: &Expr) -> Result<()> {
 }
 
 struct RewriteInfo {
-    // Group expressions with DATE_BIN_GAPFILL rewritten to DATE_BIN.
+    // Group expressions with DATE_BIN_GAPFILL rewritten to DATE_BIN or DATE_BIN_WALLCLOCK.
     new_group_expr: Vec<Expr>,
     // The index of the group expression that contained the call to DATE_BIN_GAPFILL.
     date_bin_gapfill_index: usize,
@@ -372,17 +403,36 @@ impl TreeNodeRewriter for DateBinGapfillRewriter {
         match expr {
             Expr::ScalarUDF(ScalarUDF { fun, args }) if fun.name == DATE_BIN_GAPFILL_UDF_NAME => {
                 self.args = Some(args.clone());
-                Ok(Expr::ScalarFunction(ScalarFunction {
-                    fun: BuiltinScalarFunction::DateBin,
-                    args,
-                })
-                .alias(orig_name))
+                // DATE_BIN bins on fixed strides in UTC, so binning on the wall clock of
+                // a timezone, or by calendar months, needs DATE_BIN_WALLCLOCK.
+                let expr = if args.len() == 4 || is_month_interval(&args[0]) {
+                    query_functions::registry()
+                        .udf(DATE_BIN_WALLCLOCK_UDF_NAME)?
+                        .call(args)
+                } else {
+                    Expr::ScalarFunction(ScalarFunction {
+                        fun: BuiltinScalarFunction::DateBin,
+                        args,
+                    })
+                };
+                Ok(expr.alias(orig_name))
             }
             _ => Ok(expr),
         }
     }
 }
 
+// Returns true if `e` is a literal interval with a number of months.
+fn is_month_interval(e: &Expr) -> bool {
+    match e {
+        Expr::Literal(ScalarValue::IntervalMonthDayNano(Some(v))) => {
+            IntervalMonthDayNanoType::to_parts(*v).0 != 0
+        }
+        Expr::Literal(ScalarValue::IntervalYearMonth(Some(v))) => *v != 0,
+        _ => false,
+    }
+}
+
 fn udf_to_fill_strategy(name: &str) -> Option<FillStrategy> {
     match name {
         LOCF_UDF_NAME => Some(FillStrategy::PrevNullAsMissing),
@@ -401,7 +451,7 @@ fn fill_strategy_to_udf(fs: &FillStrategy) -> Result<&'static str> {
     }
 }
 
-fn handle_projection(proj: &Projection) -> Result<Option<LogicalPlan>> {
+fn handle_projection(proj: &Projection, lookback: Option<Duration>) -> Result<Option<LogicalPlan>> {
     let Projection {
         input,
         expr: proj_exprs,
@@ -443,6 +493,16 @@ fn handle_projection(proj: &Projection) -> Result<Option<LogicalPlan>> {
         }
     }
 
+    // Now that the fill strategies are known, add the values from
+    // before the time range that they need.
+    if let Some(lookback) = lookback {
+        if let Some(input) =
+            lookback::with_lookback(&new_gapfill.input, &new_gapfill.params, lookback)?
+        {
+            new_gapfill.input = Arc::new(input);
+        }
+    }
+
     let new_proj = {
         let mut proj = proj.clone();
         proj.expr = new_proj_exprs;
@@ -553,6 +613,7 @@ mod test {
     use std::sync::Arc;
 
     use super::HandleGapFill;
+    use crate::config::IoxConfigExt;
 
     use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
     use datafusion::error::Result;
@@ -561,6 +622,7 @@ mod test {
     use datafusion::optimizer::optimizer::Optimizer;
     use datafusion::optimizer::OptimizerContext;
     use datafusion::prelude::{avg, case, col, lit, lit_timestamp_nano, min, Expr};
+    use datafusion::prelude::{SessionConfig, SessionContext};
     use datafusion::scalar::ScalarValue;
     use query_functions::gapfill::{
         DATE_BIN_GAPFILL_UDF_NAME, INTERPOLATE_UDF_NAME, LOCF_UDF_NAME,
@@ -658,6 +720,26 @@ mod test {
         Ok(plan.split('\n').map(|s| s.to_string()).collect())
     }
 
+    fn format_optimized_plan_with_lookback(
+        plan: &LogicalPlan,
+        lookback: &str,
+    ) -> Result<Vec<String>> {
+        let mut config = SessionConfig::new();
+        config.options_mut().extensions.insert(IoxConfigExt {
+            gap_fill_lookback: lookback.parse().unwrap(),
+            ..Default::default()
+        });
+        let state = SessionContext::with_config(config).state();
+
+        let optimizer = Optimizer::with_rules(vec![Arc::new(HandleGapFill)]);
+        let plan = optimizer
+            .optimize_recursively(optimizer.rules.first().unwrap(), plan, &state)?
+            .expect("plan should have been optimized")
+            .display_indent()
+            .to_string();
+        Ok(plan.split('\n').map(|s| s.to_string()).collect())
+    }
+
     #[test]
     fn misplaced_dbg_err() -> Result<()> {
         // date_bin_gapfill used in a filter should produce an error
@@ -927,6 +1009,97 @@ mod test {
         "###);
         Ok(())
     }
+    #[test]
+    fn date_bin_gapfill_timezone() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(table_scan()?)
+            .filter(
+                col("time")
+                    .gt_eq(lit_timestamp_nano(1000))
+                    .and(col("time").lt(lit_timestamp_nano(2000))),
+            )?
+            .aggregate(
+                vec![Expr::ScalarUDF(ScalarUDF {
+                    fun: query_functions::registry().udf(DATE_BIN_GAPFILL_UDF_NAME)?,
+                    args: vec![
+                        lit(ScalarValue::IntervalDayTime(Some(60_000))),
+                        col("time"),
+                        lit_timestamp_nano(7),
+                        lit("Europe/Berlin"),
+                    ],
+                })],
+                vec![avg(col("temp"))],
+            )?
+            .build()?;
+
+        insta::assert_yaml_snapshot!(
+            format_optimized_plan(&plan)?,
+            @r###"
+        ---
+        - "GapFill: groupBy=[date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time,TimestampNanosecond(7, None),Utf8(\"Europe/Berlin\"))], aggr=[[AVG(temps.temp)]], time_column=date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time,TimestampNanosecond(7, None),Utf8(\"Europe/Berlin\")), stride=IntervalDayTime(\"60000\"), range=Included(Literal(TimestampNanosecond(1000, None)))..Excluded(Literal(TimestampNanosecond(2000, None))), timezone=Europe/Berlin"
+        - "  Aggregate: groupBy=[[date_bin_wallclock(IntervalDayTime(\"60000\"), temps.time, TimestampNanosecond(7, None), Utf8(\"Europe/Berlin\")) AS date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time,TimestampNanosecond(7, None),Utf8(\"Europe/Berlin\"))]], aggr=[[AVG(temps.temp)]]"
+        - "    Filter: temps.time >= TimestampNanosecond(1000, None) AND temps.time < TimestampNanosecond(2000, None)"
+        - "      TableScan: temps"
+        "###);
+        Ok(())
+    }
+
+    #[test]
+    fn date_bin_gapfill_months() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(table_scan()?)
+            .filter(
+                col("time")
+                    .gt_eq(lit_timestamp_nano(1000))
+                    .and(col("time").lt(lit_timestamp_nano(2000))),
+            )?
+            .aggregate(
+                vec![date_bin_gapfill(
+                    lit(ScalarValue::IntervalYearMonth(Some(3))),
+                    col("time"),
+                )?],
+                vec![avg(col("temp"))],
+            )?
+            .build()?;
+
+        insta::assert_yaml_snapshot!(
+            format_optimized_plan(&plan)?,
+            @r###"
+        ---
+        - "GapFill: groupBy=[date_bin_gapfill(IntervalYearMonth(\"3\"),temps.time)], aggr=[[AVG(temps.temp)]], time_column=date_bin_gapfill(IntervalYearMonth(\"3\"),temps.time), stride=IntervalYearMonth(\"3\"), range=Included(Literal(TimestampNanosecond(1000, None)))..Excluded(Literal(TimestampNanosecond(2000, None)))"
+        - "  Aggregate: groupBy=[[date_bin_wallclock(IntervalYearMonth(\"3\"), temps.time) AS date_bin_gapfill(IntervalYearMonth(\"3\"),temps.time)]], aggr=[[AVG(temps.temp)]]"
+        - "    Filter: temps.time >= TimestampNanosecond(1000, None) AND temps.time < TimestampNanosecond(2000, None)"
+        - "      TableScan: temps"
+        "###);
+        Ok(())
+    }
+
+    #[test]
+    fn nonliteral_timezone() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(table_scan()?)
+            .filter(
+                col("time")
+                    .gt_eq(lit_timestamp_nano(1000))
+                    .and(col("time").lt(lit_timestamp_nano(2000))),
+            )?
+            .aggregate(
+                vec![Expr::ScalarUDF(ScalarUDF {
+                    fun: query_functions::registry().udf(DATE_BIN_GAPFILL_UDF_NAME)?,
+                    args: vec![
+                        lit(ScalarValue::IntervalDayTime(Some(60_000))),
+                        col("time"),
+                        lit_timestamp_nano(7),
+                        col("loc"),
+                    ],
+                })],
+                vec![avg(col("temp"))],
+            )?
+            .build()?;
+        assert_optimizer_err(
+            &plan,
+            "Error during planning: timezone argument to DATE_BIN_GAPFILL must be a string literal",
+        );
+        Ok(())
+    }
+
     #[test]
     fn two_group_exprs() -> Result<()> {
         // grouping by date_bin_gapfill(...), loc
@@ -1044,6 +1217,80 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn with_locf_lookback() -> Result<()> {
+        let plan = LogicalPlanBuilder::from(table_scan()?)
+            .filter(
+                col("time")
+                    .gt_eq(lit_timestamp_nano(1000))
+                    .and(col("time").lt(lit_timestamp_nano(2000)))
+                    .and(col("loc").eq(lit("a"))),
+            )?
+            .aggregate(
+                vec![date_bin_gapfill(
+                    lit(ScalarValue::IntervalDayTime(Some(60_000))),
+                    col("time"),
+                )?],
+                vec![avg(col("temp"))],
+            )?
+            .project(vec![
+                col("date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)"),
+                locf(col("AVG(temps.temp)"))?,
+            ])?
+            .build()?;
+
+        insta::assert_yaml_snapshot!(
+            format_optimized_plan_with_lookback(&plan, "1s")?,
+            @r###"
+        ---
+        - "Projection: date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time), AVG(temps.temp) AS locf(AVG(temps.temp))"
+        - "  GapFill: groupBy=[date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)], aggr=[[LOCF(AVG(temps.temp))]], time_column=date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time), stride=IntervalDayTime(\"60000\"), range=Included(Literal(TimestampNanosecond(1000, None)))..Excluded(Literal(TimestampNanosecond(2000, None)))"
+        - "    Union"
+        - "      Aggregate: groupBy=[[date_bin(IntervalDayTime(\"60000\"), temps.time) AS date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)]], aggr=[[AVG(temps.temp)]]"
+        - "        Filter: temps.time >= TimestampNanosecond(1000, None) AND temps.time < TimestampNanosecond(2000, None) AND temps.loc = Utf8(\"a\")"
+        - "          TableScan: temps"
+        - "      Aggregate: groupBy=[[date_bin(IntervalDayTime(\"60000\"), temps.time) AS date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)]], aggr=[[AVG(temps.temp)]]"
+        - "        Filter: temps.loc = Utf8(\"a\") AND temps.time >= TimestampNanosecond(-999999000, None) AND temps.time < date_bin(IntervalDayTime(\"60000\"), TimestampNanosecond(1000, None))"
+        - "          TableScan: temps"
+        "###);
+        Ok(())
+    }
+
+    #[test]
+    fn with_locf_lookback_no_fill() -> Result<()> {
+        // Looking back is not needed without LOCF or INTERPOLATE
+        let plan = LogicalPlanBuilder::from(table_scan()?)
+            .filter(
+                col("time")
+                    .gt_eq(lit_timestamp_nano(1000))
+                    .and(col("time").lt(lit_timestamp_nano(2000))),
+            )?
+            .aggregate(
+                vec![date_bin_gapfill(
+                    lit(ScalarValue::IntervalDayTime(Some(60_000))),
+                    col("time"),
+                )?],
+                vec![avg(col("temp"))],
+            )?
+            .project(vec![
+                col("date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)"),
+                col("AVG(temps.temp)"),
+            ])?
+            .build()?;
+
+        insta::assert_yaml_snapshot!(
+            format_optimized_plan_with_lookback(&plan, "1s")?,
+            @r###"
+        ---
+        - "Projection: date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time), AVG(temps.temp)"
+        - "  GapFill: groupBy=[date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)], aggr=[[AVG(temps.temp)]], time_column=date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time), stride=IntervalDayTime(\"60000\"), range=Included(Literal(TimestampNanosecond(1000, None)))..Excluded(Literal(TimestampNanosecond(2000, None)))"
+        - "    Aggregate: groupBy=[[date_bin(IntervalDayTime(\"60000\"), temps.time) AS date_bin_gapfill(IntervalDayTime(\"60000\"),temps.time)]], aggr=[[AVG(temps.temp)]]"
+        - "      Filter: temps.time >= TimestampNanosecond(1000, None) AND temps.time < TimestampNanosecond(2000, None)"
+        - "        TableScan: temps"
+        "###);
+        Ok(())
+    }
+
     #[test]
     fn with_locf_aliased() -> Result<()> {
         let plan = LogicalPlanBuilder::from(table_scan()?)
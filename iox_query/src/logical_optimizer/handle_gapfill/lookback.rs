@@ -0,0 +1,231 @@
+//! Look back before the time range of a gap-filling query for the values
+//! that fill the first gaps of each series.
+use std::{ops::Bound, sync::Arc, time::Duration};
+
+use datafusion::{
+    common::{
+        tree_node::{Transformed, TreeNode},
+        DFSchema,
+    },
+    error::Result,
+    logical_expr::{
+        expr::{ScalarFunction, ScalarUDF},
+        Aggregate, BuiltinScalarFunction, Filter, LogicalPlan, TableScan, Union,
+    },
+    optimizer::utils::{conjunction, split_conjunction},
+    prelude::{lit, Column, Expr},
+    scalar::ScalarValue,
+};
+use query_functions::date_bin_wallclock::DATE_BIN_WALLCLOCK_UDF_NAME;
+
+use crate::exec::gapfill::{FillStrategy, GapFillParams};
+
+use super::{range_predicate::is_time_bound, unwrap_alias};
+
+/// Returns a plan that produces the rows of `input`, the aggregate below a
+/// `GapFill` node, followed by the same aggregation over the `lookback` window
+/// before the start of the time range in `params`.
+///
+/// The rows from the lookback window are all binned before the first timestamp
+/// that is gap filled, so the gap-filling operator does not output them. Instead
+/// it uses the most recent of them in each series to fill the gaps at the
+/// start of the range with previous or interpolated values.
+///
+/// Returns `None` if looking back would not change the output, because no column
+/// is filled with previous or interpolated values or the time range has no lower
+/// bound, or if the time range of `input` can't be rewritten.
+pub fn with_lookback(
+    input: &LogicalPlan,
+    params: &GapFillParams,
+    lookback: Duration,
+) -> Result<Option<LogicalPlan>> {
+    let fills_from_previous_values = params.fill_strategy.iter().any(|(_, fs)| {
+        matches!(
+            fs,
+            FillStrategy::PrevNullAsIntentional
+                | FillStrategy::PrevNullAsMissing
+                | FillStrategy::LinearInterpolate
+        )
+    });
+    if !fills_from_previous_values {
+        return Ok(None);
+    }
+
+    let start = match &params.time_range.start {
+        Bound::Included(start) | Bound::Excluded(start) => start,
+        Bound::Unbounded => return Ok(None),
+    };
+
+    let LogicalPlan::Aggregate(aggr) = input else {
+        return Ok(None);
+    };
+
+    // Find the call to DATE_BIN that produces the time column
+    let time_column = params.time_column.try_into_col()?;
+    let date_bin = match aggr
+        .group_expr
+        .get(aggr.schema.index_of_column(&time_column)?)
+    {
+        Some(e) => unwrap_alias(e),
+        None => return Ok(None),
+    };
+    let Some(source_column) = date_bin_source(date_bin) else {
+        return Ok(None);
+    };
+
+    // The lookback window ends at the start of the bin that contains the start of
+    // the time range, so that its rows do not overlap with the rows of the first bin.
+    let mut rewriter = TimeRangeRewriter {
+        lower: sub_duration(start.clone(), lookback),
+        upper: replace_column(date_bin.clone(), &source_column, start)?,
+        rewritten: false,
+    };
+    let lookback_input = rewriter.rewrite(&aggr.input, source_column)?;
+    if !rewriter.rewritten {
+        return Ok(None);
+    }
+
+    let lookback_aggr = Aggregate::try_new_with_schema(
+        Arc::new(lookback_input),
+        aggr.group_expr.clone(),
+        aggr.aggr_expr.clone(),
+        Arc::clone(&aggr.schema),
+    )?;
+
+    Ok(Some(LogicalPlan::Union(Union {
+        inputs: vec![
+            Arc::new(input.clone()),
+            Arc::new(LogicalPlan::Aggregate(lookback_aggr)),
+        ],
+        schema: Arc::clone(&aggr.schema),
+    })))
+}
+
+/// Returns the column binned by a call to `DATE_BIN` or `DATE_BIN_WALLCLOCK`.
+fn date_bin_source(e: &Expr) -> Option<Column> {
+    let args = match e {
+        Expr::ScalarFunction(ScalarFunction {
+            fun: BuiltinScalarFunction::DateBin,
+            args,
+        }) => args,
+        Expr::ScalarUDF(ScalarUDF { fun, args }) if fun.name == DATE_BIN_WALLCLOCK_UDF_NAME => args,
+        _ => return None,
+    };
+    args.get(1)?.try_into_col().ok()
+}
+
+/// Replaces references to `column` in `e` with `replacement`.
+fn replace_column(e: Expr, column: &Column, replacement: &Expr) -> Result<Expr> {
+    e.transform_up(&|e| {
+        Ok(match e {
+            Expr::Column(ref c) if c == column => Transformed::Yes(replacement.clone()),
+            e => Transformed::No(e),
+        })
+    })
+}
+
+/// Returns an expression that subtracts `duration` from the timestamp expression `e`.
+fn sub_duration(e: Expr, duration: Duration) -> Expr {
+    let nanos = i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX);
+    match e {
+        Expr::Literal(ScalarValue::TimestampNanosecond(Some(ts), tz)) => lit(
+            ScalarValue::TimestampNanosecond(Some(ts.saturating_sub(nanos)), tz),
+        ),
+        e => e - lit(ScalarValue::new_interval_mdn(0, 0, nanos)),
+    }
+}
+
+/// Rewrites the predicates that bound a time column in a plan, so that the
+/// plan produces the rows from a different time range.
+///
+/// This traverses the same nodes as [`find_time_range`], so the predicates that
+/// are rewritten are the ones that determined the time range for gap filling.
+///
+/// [`find_time_range`]: super::range_predicate::find_time_range
+struct TimeRangeRewriter {
+    /// The inclusive lower bound of the new time range.
+    lower: Expr,
+    /// The exclusive upper bound of the new time range.
+    upper: Expr,
+    /// Set if any predicates were rewritten.
+    rewritten: bool,
+}
+
+impl TimeRangeRewriter {
+    fn rewrite(&mut self, plan: &LogicalPlan, mut col: Column) -> Result<LogicalPlan> {
+        match plan {
+            LogicalPlan::Projection(p) => {
+                let idx = p.schema.index_of_column(&col)?;
+                match unwrap_alias(&p.expr[idx]) {
+                    Expr::Column(c) => {
+                        let input = self.rewrite(&p.input, c.clone())?;
+                        plan.with_new_inputs(&[input])
+                    }
+                    _ => Ok(plan.clone()),
+                }
+            }
+            LogicalPlan::Filter(f) => {
+                let input = self.rewrite(&f.input, col.clone())?;
+                let predicates = split_conjunction(&f.predicate);
+                match self.rewrite_predicates(f.input.schema(), &col, predicates)? {
+                    Some(predicate) => Ok(LogicalPlan::Filter(Filter::try_new(
+                        predicate,
+                        Arc::new(input),
+                    )?)),
+                    None => plan.with_new_inputs(&[input]),
+                }
+            }
+            LogicalPlan::TableScan(t) => {
+                let predicates = t.filters.iter().flat_map(split_conjunction).collect();
+                match self.rewrite_predicates(&t.projected_schema, &col, predicates)? {
+                    Some(predicate) => Ok(LogicalPlan::TableScan(TableScan {
+                        filters: split_conjunction(&predicate).into_iter().cloned().collect(),
+                        ..t.clone()
+                    })),
+                    None => Ok(plan.clone()),
+                }
+            }
+            LogicalPlan::SubqueryAlias(_) => {
+                // The nodes below this one refer to the column with a different table name,
+                // just unset the relation so we match on the column name.
+                col.relation = None;
+                let input = self.rewrite(plan.inputs()[0], col)?;
+                plan.with_new_inputs(&[input])
+            }
+            // These nodes do not alter their schema, so we can recurse through them
+            LogicalPlan::Sort(_) | LogicalPlan::Repartition(_) | LogicalPlan::Distinct(_) => {
+                let input = self.rewrite(plan.inputs()[0], col)?;
+                plan.with_new_inputs(&[input])
+            }
+            _ => Ok(plan.clone()),
+        }
+    }
+
+    /// If any of `predicates` bound the time column `col`, returns the conjunction
+    /// of the other predicates with the new time range.
+    fn rewrite_predicates(
+        &mut self,
+        schema: &DFSchema,
+        col: &Column,
+        predicates: Vec<&Expr>,
+    ) -> Result<Option<Expr>> {
+        let mut found = false;
+        let mut new_predicates = vec![];
+        for predicate in predicates {
+            if is_time_bound(schema, col, predicate)? {
+                found = true;
+            } else {
+                new_predicates.push(predicate.clone());
+            }
+        }
+        if !found {
+            return Ok(None);
+        }
+
+        self.rewritten = true;
+        let time = Expr::Column(col.clone());
+        new_predicates.push(time.clone().gt_eq(self.lower.clone()));
+        new_predicates.push(time.lt(self.upper.clone()));
+        Ok(conjunction(new_predicates))
+    }
+}
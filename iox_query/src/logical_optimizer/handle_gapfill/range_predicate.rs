@@ -25,6 +25,13 @@ pub fn find_time_range(plan: &LogicalPlan, time_col: &Column) -> Result<Range<Bo
     Ok(v.range.0)
 }
 
+/// Returns true if `expr` is a predicate that bounds `time_col`, i.e. one that
+/// [`find_time_range`] would use to find the time range.
+pub(super) fn is_time_bound(schema: &DFSchema, time_col: &Column, expr: &Expr) -> Result<bool> {
+    let TimeRange(range) = TimeRange::default().with_expr(schema, time_col, expr)?;
+    Ok(range.start != Bound::Unbounded || range.end != Bound::Unbounded)
+}
+
 struct TimeRangeVisitor {
     col: Column,
     range: TimeRange,
@@ -3,12 +3,14 @@ use std::sync::Arc;
 use datafusion::execution::context::SessionState;
 
 use self::{
-    handle_gapfill::HandleGapFill, influx_regex_to_datafusion_regex::InfluxRegexToDataFusionRegex,
+    check_window_function_args::CheckWindowFunctionArgs, handle_gapfill::HandleGapFill,
+    influx_regex_to_datafusion_regex::InfluxRegexToDataFusionRegex,
 };
 
+mod check_window_function_args;
 mod handle_gapfill;
 mod influx_regex_to_datafusion_regex;
-pub use handle_gapfill::range_predicate;
+pub use handle_gapfill::{lookback, range_predicate};
 
 /// Register IOx-specific logical [`OptimizerRule`]s with the SessionContext
 ///
@@ -17,4 +19,5 @@ pub fn register_iox_logical_optimizers(state: SessionState) -> SessionState {
     state
         .add_optimizer_rule(Arc::new(InfluxRegexToDataFusionRegex::new()))
         .add_optimizer_rule(Arc::new(HandleGapFill::new()))
+        .add_optimizer_rule(Arc::new(CheckWindowFunctionArgs::new()))
 }
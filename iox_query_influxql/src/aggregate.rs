@@ -6,7 +6,40 @@ use datafusion::logical_expr::{
 use once_cell::sync::Lazy;
 use std::sync::Arc;
 
+mod integral;
+mod mode;
 mod percentile;
+mod spread;
+
+/// Definition of the `INTEGRAL` user-defined aggregate function.
+pub(crate) static INTEGRAL: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(integral::return_type);
+    let accumulator: AccumulatorFactoryFunction = Arc::new(integral::accumulator);
+    let state_type: StateTypeFunction = Arc::new(integral::state_type);
+
+    Arc::new(AggregateUDF::new(
+        integral::NAME,
+        &integral::SIGNATURE,
+        &return_type,
+        &accumulator,
+        &state_type,
+    ))
+});
+
+/// Definition of the `MODE` user-defined aggregate function.
+pub(crate) static MODE: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(mode::return_type);
+    let accumulator: AccumulatorFactoryFunction = Arc::new(mode::accumulator);
+    let state_type: StateTypeFunction = Arc::new(mode::state_type);
+
+    Arc::new(AggregateUDF::new(
+        mode::NAME,
+        &mode::SIGNATURE,
+        &return_type,
+        &accumulator,
+        &state_type,
+    ))
+});
 
 /// Definition of the `PERCENTILE` user-defined aggregate function.
 pub(crate) static PERCENTILE: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
@@ -22,3 +55,18 @@ pub(crate) static PERCENTILE: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
         &state_type,
     ))
 });
+
+/// Definition of the `SPREAD` user-defined aggregate function.
+pub(crate) static SPREAD: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(spread::return_type);
+    let accumulator: AccumulatorFactoryFunction = Arc::new(spread::accumulator);
+    let state_type: StateTypeFunction = Arc::new(spread::state_type);
+
+    Arc::new(AggregateUDF::new(
+        spread::NAME,
+        &spread::SIGNATURE,
+        &return_type,
+        &accumulator,
+        &state_type,
+    ))
+});
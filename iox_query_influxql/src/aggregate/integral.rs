@@ -0,0 +1,218 @@
+use crate::error;
+use arrow::array::{as_list_array, Array, ArrayRef, Float64Array, TimestampNanosecondArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, IntervalUnit, TimeUnit};
+use datafusion::common::{downcast_value, DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::{Accumulator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+/// The name of the integral aggregate function.
+pub(super) const NAME: &str = "integral";
+
+/// Valid signatures for the integral aggregate function.
+pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        crate::NUMERICS
+            .iter()
+            .map(|dt| {
+                TypeSignature::Exact(vec![
+                    dt.clone(),
+                    DataType::Interval(IntervalUnit::MonthDayNano),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                ])
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Calculate the return type given the function signature. Integral
+/// always returns a float.
+pub(super) fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(DataType::Float64))
+}
+
+/// Create a new accumulator for the data type.
+pub(super) fn accumulator(_: &DataType) -> Result<Box<dyn Accumulator>> {
+    Ok(Box::new(IntegralAccumulator::new()))
+}
+
+/// Calculate the intermediate merge state for the aggregator.
+pub(super) fn state_type(_: &DataType) -> Result<Arc<Vec<DataType>>> {
+    Ok(Arc::new(vec![
+        DataType::List(Arc::new(Field::new(
+            "item",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        ))),
+        DataType::List(Arc::new(Field::new("item", DataType::Float64, true))),
+        DataType::Interval(IntervalUnit::MonthDayNano),
+    ]))
+}
+
+/// Accumulator that collects the points of a series and evaluates
+/// the area under the curve using the trapezoidal rule.
+///
+/// This is only used without a `GROUP BY TIME` interval, as the area of
+/// each window is otherwise interpolated up to the window boundaries by
+/// the INTEGRAL window function.
+#[derive(Debug)]
+struct IntegralAccumulator {
+    points: Vec<(i64, f64)>,
+    unit: Option<i128>,
+}
+
+impl IntegralAccumulator {
+    fn new() -> Self {
+        Self {
+            points: vec![],
+            unit: None,
+        }
+    }
+
+    fn update(&mut self, values: &ArrayRef, times: &ArrayRef) -> Result<()> {
+        let values = cast(values, &DataType::Float64)?;
+        let values = downcast_value!(values, Float64Array);
+        let times = downcast_value!(times, TimestampNanosecondArray);
+
+        self.points.reserve(values.len() - values.null_count());
+        for (v, t) in values.iter().zip(times.iter()) {
+            if let (Some(v), Some(t)) = (v, t) {
+                self.points.push((t, v));
+            }
+        }
+        Ok(())
+    }
+
+    fn set_unit(&mut self, array: &ArrayRef) -> Result<()> {
+        if self.unit.is_none() && array.is_valid(0) {
+            self.unit = match ScalarValue::try_from_array(array, 0)? {
+                ScalarValue::IntervalMonthDayNano(Some(v)) => Some(v),
+                v => {
+                    return error::internal(format!(
+                        "invalid value ({v}) for INTEGRAL unit argument"
+                    ))
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for IntegralAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        assert_eq!(values.len(), 3);
+
+        self.set_unit(&values[1])?;
+        self.update(&values[0], &values[2])
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        let Some(unit) = self.unit else {
+            return Ok(ScalarValue::Float64(None));
+        };
+        if self.points.is_empty() {
+            return Ok(ScalarValue::Float64(None));
+        }
+
+        let mut points = self.points.clone();
+        points.sort_by_key(|(t, _)| *t);
+        Ok(ScalarValue::Float64(Some(integral(&points, unit as f64))))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.points.capacity() * std::mem::size_of::<(i64, f64)>()
+    }
+
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        let (times, values): (Vec<_>, Vec<_>) = self
+            .points
+            .iter()
+            .map(|(t, v)| {
+                (
+                    ScalarValue::TimestampNanosecond(Some(*t), None),
+                    ScalarValue::Float64(Some(*v)),
+                )
+            })
+            .unzip();
+
+        Ok(vec![
+            ScalarValue::new_list(Some(times), DataType::Timestamp(TimeUnit::Nanosecond, None)),
+            ScalarValue::new_list(Some(values), DataType::Float64),
+            ScalarValue::IntervalMonthDayNano(self.unit),
+        ])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        assert_eq!(states.len(), 3);
+
+        self.set_unit(&states[2])?;
+
+        let times = as_list_array(&states[0]);
+        let values = as_list_array(&states[1]);
+        for idx in 0..times.len() {
+            self.update(&values.value(idx), &times.value(idx))?;
+        }
+        Ok(())
+    }
+}
+
+/// Calculate the area under the curve described by `points`, which
+/// must be sorted by time, in multiples of `unit` nanoseconds.
+///
+/// As with InfluxDB 1.x, when multiple points share the same timestamp,
+/// the first of those points closes the preceding trapezoid and the last
+/// opens the following one.
+fn integral(points: &[(i64, f64)], unit: f64) -> f64 {
+    let mut sum = 0.0;
+    let mut iter = points.iter();
+    let Some(mut prev) = iter.next() else {
+        return sum;
+    };
+    for p in iter {
+        if p.0 != prev.0 {
+            let elapsed = (p.0 - prev.0) as f64 / unit;
+            sum += 0.5 * (p.1 + prev.1) * elapsed;
+        }
+        prev = p;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_integral() {
+        // no points
+        assert_eq!(integral(&[], 1.0), 0.0);
+
+        // single point
+        assert_eq!(integral(&[(10, 5.0)], 1.0), 0.0);
+
+        // trapezoids with a unit of 1ns
+        assert_eq!(integral(&[(0, 2.0), (10, 4.0), (20, 0.0)], 1.0), 50.0);
+
+        // trapezoids with a unit of 10ns
+        assert_eq!(integral(&[(0, 2.0), (10, 4.0), (20, 0.0)], 10.0), 5.0);
+
+        // the points of the INTEGRAL tests of InfluxDB 1.x
+        let second = 1_000_000_000;
+        let points = [
+            (10 * second, 20.0),
+            (15 * second, 10.0),
+            (20 * second, 0.0),
+            (30 * second, -10.0),
+        ];
+        assert_eq!(integral(&points, second as f64), 50.0);
+        assert_eq!(integral(&points, (10 * second) as f64), 5.0);
+
+        // points sharing a timestamp do not contribute any area between them
+        assert_eq!(
+            integral(&[(0, 2.0), (10, 100.0), (10, 4.0), (20, 0.0)], 1.0),
+            530.0
+        );
+    }
+}
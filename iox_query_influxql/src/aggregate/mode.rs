@@ -0,0 +1,133 @@
+use arrow::array::{as_list_array, Array, ArrayRef};
+use arrow::datatypes::{DataType, Field};
+use datafusion::common::{Result, ScalarValue};
+use datafusion::logical_expr::{Accumulator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+/// The name of the mode aggregate function.
+pub(super) const NAME: &str = "mode";
+
+/// Valid signatures for the mode aggregate function.
+pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        crate::NUMERICS
+            .iter()
+            .chain(&[DataType::Utf8, DataType::Boolean])
+            .map(|dt| TypeSignature::Exact(vec![dt.clone()]))
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Calculate the return type given the function signature. Mode
+/// always returns the same type as the input column.
+pub(super) fn return_type(signature: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(signature[0].clone()))
+}
+
+/// Create a new accumulator for the data type.
+pub(super) fn accumulator(dt: &DataType) -> Result<Box<dyn Accumulator>> {
+    Ok(Box::new(ModeAccumulator::new(dt.clone())))
+}
+
+/// Calculate the intermediate merge state for the aggregator.
+pub(super) fn state_type(dt: &DataType) -> Result<Arc<Vec<DataType>>> {
+    Ok(Arc::new(vec![DataType::List(Arc::new(Field::new(
+        "item",
+        dt.clone(),
+        true,
+    )))]))
+}
+
+#[derive(Debug)]
+struct ModeAccumulator {
+    data_type: DataType,
+    data: Vec<ScalarValue>,
+}
+
+impl ModeAccumulator {
+    fn new(data_type: DataType) -> Self {
+        Self {
+            data_type,
+            data: vec![],
+        }
+    }
+
+    fn update(&mut self, array: ArrayRef) -> Result<()> {
+        assert_eq!(array.data_type(), &self.data_type);
+
+        let nulls = array.nulls();
+        let null_len = nulls.map_or(0, |nb| nb.null_count());
+        self.data.reserve(array.len() - null_len);
+        for idx in 0..array.len() {
+            if nulls.map_or(true, |nb| nb.is_valid(idx)) {
+                self.data.push(ScalarValue::try_from_array(&array, idx)?)
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for ModeAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        assert_eq!(values.len(), 1);
+
+        self.update(Arc::clone(&values[0]))
+    }
+
+    /// Returns the most frequent value. If multiple values occur with
+    /// the same frequency, the lowest of these values is returned, as
+    /// is done by InfluxDB 1.x.
+    fn evaluate(&self) -> Result<ScalarValue> {
+        if self.data.is_empty() {
+            return (&self.data_type).try_into();
+        }
+
+        let array = ScalarValue::iter_to_array(self.data.clone())?;
+        let indices = arrow::compute::sort_to_indices(&array, None, None)?;
+
+        // The values are visited in ascending order, and the mode is only
+        // replaced by a value that occurs strictly more often, so the lowest
+        // value wins a tie.
+        let mut mode = (0, 0);
+        let mut run = (0, 0);
+        let mut prev: Option<ScalarValue> = None;
+        for (i, idx) in indices.values().iter().enumerate() {
+            let v = ScalarValue::try_from_array(&array, *idx as usize)?;
+            if prev.as_ref() == Some(&v) {
+                run.1 += 1;
+            } else {
+                run = (i, 1);
+                prev = Some(v);
+            }
+            if run.1 > mode.1 {
+                mode = run;
+            }
+        }
+
+        ScalarValue::try_from_array(&array, indices.value(mode.0) as usize)
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of::<DataType>() + ScalarValue::size_of_vec(&self.data)
+    }
+
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::new_list(
+            Some(self.data.clone()),
+            self.data_type.clone(),
+        )])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        assert_eq!(states.len(), 1);
+
+        let array = Arc::clone(&states[0]);
+        let list_array = as_list_array(&array);
+        for idx in 0..list_array.len() {
+            self.update(list_array.value(idx))?;
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,23 @@
 use arrow::datatypes::SchemaRef;
 use datafusion::physical_expr::execution_props::ExecutionProps;
+use influxdb_influxql_parser::show::OnClause;
+use influxdb_influxql_parser::show_cardinality::ShowCardinalityStatement;
 use influxdb_influxql_parser::show_field_keys::ShowFieldKeysStatement;
-use influxdb_influxql_parser::show_measurements::ShowMeasurementsStatement;
+use influxdb_influxql_parser::show_measurements::{ExtendedOnClause, ShowMeasurementsStatement};
+use influxdb_influxql_parser::show_retention_policies::ShowRetentionPoliciesStatement;
+use influxdb_influxql_parser::show_series::ShowSeriesStatement;
 use influxdb_influxql_parser::show_tag_keys::ShowTagKeysStatement;
 use influxdb_influxql_parser::show_tag_values::ShowTagValuesStatement;
 use std::any::Any;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::fmt::Debug;
 use std::ops::Deref;
 use std::sync::Arc;
 
-use crate::plan::{parse_regex, InfluxQLToLogicalPlan, SchemaProvider};
+use crate::plan::{
+    bind_params, parse_regex, InfluxQLToLogicalPlan, SchemaProvider, StatementParams,
+};
 use datafusion::common::Statistics;
 use datafusion::datasource::provider_as_source;
 use datafusion::execution::context::{SessionState, TaskContext};
@@ -24,16 +30,18 @@ use datafusion::{
     error::{DataFusionError, Result},
     physical_plan::ExecutionPlan,
 };
-use influxdb_influxql_parser::common::MeasurementName;
+use influxdb_influxql_parser::common::{MeasurementName, QualifiedMeasurementName};
 use influxdb_influxql_parser::parse_statements;
 use influxdb_influxql_parser::statement::Statement;
 use influxdb_influxql_parser::visit::{Visitable, Visitor};
+use influxdb_influxql_parser::visit_mut::{VisitableMut, VisitorMut};
 use iox_query::exec::IOxSessionContext;
 use observability_deps::tracing::debug;
 use schema::Schema;
 
 struct ContextSchemaProvider<'a> {
     state: &'a SessionState,
+    execution_props: ExecutionProps,
     tables: HashMap<String, (Arc<dyn TableSource>, Schema)>,
 }
 
@@ -66,7 +74,7 @@ impl<'a> SchemaProvider for ContextSchemaProvider<'a> {
     }
 
     fn execution_props(&self) -> &ExecutionProps {
-        self.state.execution_props()
+        &self.execution_props
     }
 }
 
@@ -146,14 +154,18 @@ impl InfluxQLQueryPlanner {
 
     /// Plan an InfluxQL query against the catalogs registered with `ctx`, and return a
     /// DataFusion physical execution plan that runs on the query executor.
+    ///
+    /// Any bind parameters referenced by the query are replaced with the values in `params`.
     pub async fn query(
         &self,
         query: &str,
+        params: &StatementParams,
         ctx: &IOxSessionContext,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         debug!(text=%query, "planning InfluxQL query");
 
-        let statement = self.query_to_statement(query)?;
+        let mut statement = self.query_to_statement(query)?;
+        bind_params(&mut statement, params)?;
         let logical_plan = self.statement_to_plan(statement, ctx).await?;
 
         let input = ctx.create_physical_plan(&logical_plan).await?;
@@ -174,21 +186,20 @@ impl InfluxQLQueryPlanner {
 
     async fn statement_to_plan(
         &self,
-        statement: Statement,
+        mut statement: Statement,
         ctx: &IOxSessionContext,
     ) -> Result<LogicalPlan> {
         use std::collections::hash_map::Entry;
 
         let session_cfg = ctx.inner().copied_config();
         let cfg = session_cfg.options();
+        let catalog_name = resolve_database(&mut statement, &ctx.inner().catalog_names())?
+            .unwrap_or_else(|| cfg.catalog.default_catalog.clone());
         let schema = ctx
             .inner()
-            .catalog(&cfg.catalog.default_catalog)
+            .catalog(&catalog_name)
             .ok_or_else(|| {
-                DataFusionError::Plan(format!(
-                    "failed to resolve catalog: {}",
-                    cfg.catalog.default_catalog
-                ))
+                DataFusionError::Plan(format!("failed to resolve catalog: {catalog_name}"))
             })?
             .schema(&cfg.catalog.default_schema)
             .ok_or_else(|| {
@@ -202,6 +213,7 @@ impl InfluxQLQueryPlanner {
 
         let mut sp = ContextSchemaProvider {
             state: &ctx.inner().state(),
+            execution_props: ctx.execution_props(),
             tables: HashMap::with_capacity(query_tables.len()),
         };
 
@@ -237,6 +249,253 @@ impl InfluxQLQueryPlanner {
     }
 }
 
+/// The retention policy that measurements belong to when a query does not name one.
+const DEFAULT_RETENTION_POLICY: &str = "autogen";
+
+/// Returns the name of the catalog for the `database` and `retention_policy`.
+///
+/// A database with the default retention policy is the catalog named after the database, and
+/// any other retention policy is a catalog named `<database>/<retention policy>`.
+pub fn database_catalog_name(database: &str, retention_policy: Option<&str>) -> String {
+    match retention_policy.filter(|rp| *rp != DEFAULT_RETENTION_POLICY) {
+        Some(rp) => format!("{database}/{rp}"),
+        None => database.to_owned(),
+    }
+}
+
+/// Returns the databases named by the measurements and `ON` clauses of `stmt`, with the
+/// retention policy if it is not the default.
+///
+/// The catalog of each, named by [`database_catalog_name`], must be registered with the
+/// context that `stmt` is planned with.
+pub fn referenced_databases(stmt: &Statement) -> Result<BTreeSet<(String, Option<String>)>> {
+    struct Collector(BTreeSet<(String, Option<String>)>);
+
+    impl Collector {
+        fn add(mut self, database: Option<&str>, retention_policy: Option<&str>) -> Self {
+            if let Some(database) = database {
+                let retention_policy =
+                    retention_policy.filter(|rp| *rp != DEFAULT_RETENTION_POLICY);
+                self.0
+                    .insert((database.to_owned(), retention_policy.map(str::to_owned)));
+            }
+            self
+        }
+
+        fn add_on_clause(self, on: &Option<OnClause>) -> Self {
+            self.add(on.as_deref().map(|db| db.as_str()), None)
+        }
+    }
+
+    impl Visitor for Collector {
+        type Error = DataFusionError;
+
+        fn post_visit_qualified_measurement_name(
+            self,
+            n: &QualifiedMeasurementName,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add(
+                n.database.as_deref().map(|db| db.as_str()),
+                n.retention_policy.as_deref().map(|rp| rp.as_str()),
+            ))
+        }
+
+        fn post_visit_show_measurements_statement(
+            self,
+            n: &ShowMeasurementsStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(match &n.on {
+                Some(ExtendedOnClause::Database(db)) => self.add(Some(db.as_str()), None),
+                Some(ExtendedOnClause::DatabaseRetentionPolicy(db, rp)) => {
+                    self.add(Some(db.as_str()), Some(rp.as_str()))
+                }
+                _ => self,
+            })
+        }
+
+        fn post_visit_show_retention_policies_statement(
+            self,
+            n: &ShowRetentionPoliciesStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add_on_clause(&n.database))
+        }
+
+        fn post_visit_show_tag_keys_statement(
+            self,
+            n: &ShowTagKeysStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add_on_clause(&n.database))
+        }
+
+        fn post_visit_show_tag_values_statement(
+            self,
+            n: &ShowTagValuesStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add_on_clause(&n.database))
+        }
+
+        fn post_visit_show_field_keys_statement(
+            self,
+            n: &ShowFieldKeysStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add_on_clause(&n.database))
+        }
+
+        fn post_visit_show_series_statement(
+            self,
+            n: &ShowSeriesStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add_on_clause(&n.database))
+        }
+
+        fn post_visit_show_cardinality_statement(
+            self,
+            n: &ShowCardinalityStatement,
+        ) -> Result<Self, Self::Error> {
+            Ok(self.add_on_clause(&n.database))
+        }
+    }
+
+    Ok(stmt.accept(Collector(BTreeSet::new()))?.0)
+}
+
+/// Resolves the database and retention policy qualifiers of the measurements and the
+/// `ON` clause of `stmt` to one of the catalogs in `catalog_names`, and removes them from
+/// the statement so that it may be planned against the schema of that catalog.
+///
+/// The catalog of a database and retention policy is named by [`database_catalog_name`].
+/// Returns `None` if the statement does not name a database, and must be planned against
+/// the default catalog.
+fn resolve_database(stmt: &mut Statement, catalog_names: &[String]) -> Result<Option<String>> {
+    struct Resolver<'a> {
+        catalog_names: &'a [String],
+        database: Option<String>,
+    }
+
+    impl<'a> Resolver<'a> {
+        fn resolve(
+            &mut self,
+            database: Option<&str>,
+            retention_policy: Option<&str>,
+        ) -> Result<()> {
+            let retention_policy = retention_policy.filter(|rp| *rp != DEFAULT_RETENTION_POLICY);
+            let Some(database) = database else {
+                return match retention_policy {
+                    Some(rp) => Err(DataFusionError::Plan(format!(
+                        "retention policy not found: {rp}"
+                    ))),
+                    None => Ok(()),
+                };
+            };
+
+            let name = database_catalog_name(database, retention_policy);
+            if !self.catalog_names.contains(&name) {
+                return Err(DataFusionError::Plan(match retention_policy {
+                    Some(rp) if self.catalog_names.iter().any(|n| n == database) => {
+                        format!("retention policy not found: {rp}")
+                    }
+                    _ => format!("database not found: {database}"),
+                }));
+            }
+
+            match &self.database {
+                Some(other) if *other != name => Err(DataFusionError::NotImplemented(
+                    "measurements from more than one database in a single statement".to_string(),
+                )),
+                _ => {
+                    self.database = Some(name);
+                    Ok(())
+                }
+            }
+        }
+
+        fn resolve_on_clause(&mut self, on: Option<OnClause>) -> Result<()> {
+            self.resolve(on.as_deref().map(|db| db.as_str()), None)
+        }
+    }
+
+    impl<'a> VisitorMut for Resolver<'a> {
+        type Error = DataFusionError;
+
+        fn post_visit_qualified_measurement_name(
+            &mut self,
+            n: &mut QualifiedMeasurementName,
+        ) -> Result<(), Self::Error> {
+            self.resolve(
+                n.database.take().as_deref().map(|db| db.as_str()),
+                n.retention_policy.take().as_deref().map(|rp| rp.as_str()),
+            )
+        }
+
+        fn post_visit_show_measurements_statement(
+            &mut self,
+            n: &mut ShowMeasurementsStatement,
+        ) -> Result<(), Self::Error> {
+            match n.on.take() {
+                Some(ExtendedOnClause::Database(db)) => self.resolve(Some(db.as_str()), None),
+                Some(ExtendedOnClause::DatabaseRetentionPolicy(db, rp)) => {
+                    self.resolve(Some(db.as_str()), Some(rp.as_str()))
+                }
+                // Left for the planner to report as unsupported
+                on => {
+                    n.on = on;
+                    Ok(())
+                }
+            }
+        }
+
+        fn post_visit_show_retention_policies_statement(
+            &mut self,
+            n: &mut ShowRetentionPoliciesStatement,
+        ) -> Result<(), Self::Error> {
+            self.resolve_on_clause(n.database.take())
+        }
+
+        fn post_visit_show_tag_keys_statement(
+            &mut self,
+            n: &mut ShowTagKeysStatement,
+        ) -> Result<(), Self::Error> {
+            self.resolve_on_clause(n.database.take())
+        }
+
+        fn post_visit_show_tag_values_statement(
+            &mut self,
+            n: &mut ShowTagValuesStatement,
+        ) -> Result<(), Self::Error> {
+            self.resolve_on_clause(n.database.take())
+        }
+
+        fn post_visit_show_field_keys_statement(
+            &mut self,
+            n: &mut ShowFieldKeysStatement,
+        ) -> Result<(), Self::Error> {
+            self.resolve_on_clause(n.database.take())
+        }
+
+        fn post_visit_show_series_statement(
+            &mut self,
+            n: &mut ShowSeriesStatement,
+        ) -> Result<(), Self::Error> {
+            self.resolve_on_clause(n.database.take())
+        }
+
+        fn post_visit_show_cardinality_statement(
+            &mut self,
+            n: &mut ShowCardinalityStatement,
+        ) -> Result<(), Self::Error> {
+            self.resolve_on_clause(n.database.take())
+        }
+    }
+
+    let mut resolver = Resolver {
+        catalog_names,
+        database: None,
+    };
+    VisitableMut::accept(stmt, &mut resolver)?;
+
+    Ok(resolver.database)
+}
+
 fn find_all_measurements(stmt: &Statement, tables: &[String]) -> Result<HashSet<String>> {
     struct Matcher<'a>(&'a mut HashSet<String>, &'a [String]);
     impl<'a> Visitor for Matcher<'a> {
@@ -311,6 +570,28 @@ fn find_all_measurements(stmt: &Statement, tables: &[String]) -> Result<HashSet<
 
             Ok(self)
         }
+
+        fn post_visit_show_series_statement(
+            self,
+            ss: &ShowSeriesStatement,
+        ) -> Result<Self, Self::Error> {
+            if ss.from.is_none() {
+                self.0.extend(self.1.iter().cloned());
+            }
+
+            Ok(self)
+        }
+
+        fn post_visit_show_cardinality_statement(
+            self,
+            sc: &ShowCardinalityStatement,
+        ) -> Result<Self, Self::Error> {
+            if sc.from.is_none() {
+                self.0.extend(self.1.iter().cloned());
+            }
+
+            Ok(self)
+        }
     }
 
     let mut m = HashSet::new();
@@ -341,6 +622,159 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_resolve_database() {
+        fn resolve(q: &str) -> (Result<Option<String>>, String) {
+            let p = InfluxQLQueryPlanner::new();
+            let mut s = p.query_to_statement(q).unwrap();
+            let catalogs = vec!["public".into(), "foo".into(), "bar".into(), "bar/rp".into()];
+            let res = resolve_database(&mut s, &catalogs);
+            (res, s.to_string())
+        }
+
+        fn database(q: &str) -> (Option<String>, String) {
+            let (res, s) = resolve(q);
+            (res.unwrap(), s)
+        }
+
+        // No qualifiers
+        assert_eq!(
+            database("SELECT * FROM cpu"),
+            (None, "SELECT * FROM cpu".into())
+        );
+        assert_eq!(
+            database("SELECT * FROM autogen.cpu"),
+            (None, "SELECT * FROM cpu".into())
+        );
+
+        // Default retention policy
+        assert_eq!(
+            database("SELECT * FROM foo..cpu"),
+            (Some("foo".into()), "SELECT * FROM cpu".into())
+        );
+        assert_eq!(
+            database("SELECT * FROM foo.autogen.cpu, foo..mem"),
+            (Some("foo".into()), "SELECT * FROM cpu, mem".into())
+        );
+        assert_eq!(
+            database("SELECT * FROM (SELECT * FROM foo.autogen./^c/)"),
+            (
+                Some("foo".into()),
+                "SELECT * FROM (SELECT * FROM /^c/)".into()
+            )
+        );
+
+        // Other retention policy
+        assert_eq!(
+            database("SELECT * FROM bar.rp.cpu"),
+            (Some("bar/rp".into()), "SELECT * FROM cpu".into())
+        );
+
+        // ON clauses
+        assert_eq!(
+            database("SHOW MEASUREMENTS ON foo"),
+            (Some("foo".into()), "SHOW MEASUREMENTS".into())
+        );
+        assert_eq!(
+            database("SHOW MEASUREMENTS ON bar.rp"),
+            (Some("bar/rp".into()), "SHOW MEASUREMENTS".into())
+        );
+        assert_eq!(
+            database("SHOW MEASUREMENTS ON *"),
+            (None, "SHOW MEASUREMENTS ON *".into())
+        );
+        assert_eq!(
+            database("SHOW RETENTION POLICIES ON foo"),
+            (Some("foo".into()), "SHOW RETENTION POLICIES".into())
+        );
+        assert_eq!(
+            database("SHOW TAG KEYS ON foo FROM foo.autogen.cpu"),
+            (Some("foo".into()), "SHOW TAG KEYS FROM cpu".into())
+        );
+        assert_eq!(database("SHOW FIELD KEYS ON foo").0, Some("foo".into()));
+        assert_eq!(
+            database("SHOW TAG VALUES ON foo WITH KEY = host").0,
+            Some("foo".into())
+        );
+        assert_eq!(database("SHOW SERIES ON foo").0, Some("foo".into()));
+        assert_eq!(
+            database("SHOW SERIES CARDINALITY ON foo").0,
+            Some("foo".into())
+        );
+
+        // Fallible
+
+        assert_error!(
+            resolve("SELECT * FROM none..cpu").0,
+            DataFusionError::Plan(ref s) if s == "database not found: none"
+        );
+        assert_error!(
+            resolve("SELECT * FROM foo.none.cpu").0,
+            DataFusionError::Plan(ref s) if s == "retention policy not found: none"
+        );
+        assert_error!(
+            resolve("SELECT * FROM none.cpu").0,
+            DataFusionError::Plan(ref s) if s == "retention policy not found: none"
+        );
+        assert_error!(
+            resolve("SHOW TAG KEYS ON none").0,
+            DataFusionError::Plan(ref s) if s == "database not found: none"
+        );
+        assert_error!(
+            resolve("SELECT * FROM foo..cpu, bar..cpu").0,
+            DataFusionError::NotImplemented(ref s) if s == "measurements from more than one database in a single statement"
+        );
+
+        // Qualifiers are resolved even when only the default catalog is registered
+        let p = InfluxQLQueryPlanner::new();
+        let mut s = p.query_to_statement("SELECT * FROM foo..cpu").unwrap();
+        assert_error!(
+            resolve_database(&mut s, &["public".into()]),
+            DataFusionError::Plan(ref s) if s == "database not found: foo"
+        );
+    }
+
+    #[test]
+    fn test_referenced_databases() {
+        fn referenced(q: &str) -> Vec<(String, Option<String>)> {
+            let p = InfluxQLQueryPlanner::new();
+            let s = p.query_to_statement(q).unwrap();
+            referenced_databases(&s).unwrap().into_iter().collect()
+        }
+
+        assert!(referenced("SELECT * FROM cpu").is_empty());
+        assert!(referenced("SELECT * FROM autogen.cpu").is_empty());
+        assert!(referenced("SHOW MEASUREMENTS ON *").is_empty());
+        assert_eq!(
+            referenced("SELECT * FROM foo.autogen.cpu, (SELECT * FROM bar.rp.cpu)"),
+            [("bar".into(), Some("rp".into())), ("foo".into(), None)]
+        );
+        assert_eq!(
+            referenced("SHOW MEASUREMENTS ON bar.rp"),
+            [("bar".into(), Some("rp".into()))]
+        );
+        assert_eq!(
+            referenced("SHOW TAG KEYS ON foo FROM foo..cpu"),
+            [("foo".into(), None)]
+        );
+        for q in [
+            "SHOW RETENTION POLICIES ON foo",
+            "SHOW FIELD KEYS ON foo",
+            "SHOW TAG VALUES ON foo WITH KEY = host",
+            "SHOW SERIES ON foo",
+            "SHOW SERIES CARDINALITY ON foo",
+        ] {
+            assert_eq!(referenced(q), [("foo".into(), None)], "{q}");
+        }
+    }
+
+    #[test]
+    fn test_database_catalog_name() {
+        assert_eq!(database_catalog_name("foo", None), "foo");
+        assert_eq!(database_catalog_name("foo", Some("autogen")), "foo");
+        assert_eq!(database_catalog_name("foo", Some("rp")), "foo/rp");
+    }
+
     #[test]
     fn test_find_all_measurements() {
         fn find(q: &str) -> Vec<String> {
@@ -398,6 +832,20 @@ mod test {
         assert_eq!(find("SHOW TAG KEYS"), vec!["bar", "foo", "foobar"]);
         assert_eq!(find("SHOW TAG KEYS FROM /^foo/"), vec!["foo", "foobar"]);
 
+        // Find all measurements in `SHOW SERIES`
+        assert_eq!(find("SHOW SERIES"), vec!["bar", "foo", "foobar"]);
+        assert_eq!(find("SHOW SERIES FROM /^foo/"), vec!["foo", "foobar"]);
+
+        // Find all measurements in `SHOW ... CARDINALITY`
+        assert_eq!(
+            find("SHOW SERIES EXACT CARDINALITY"),
+            vec!["bar", "foo", "foobar"]
+        );
+        assert_eq!(
+            find("SHOW TAG VALUES CARDINALITY FROM /^foo/ WITH KEY = \"k\""),
+            vec!["foo", "foobar"]
+        );
+
         // Finds no measurements
         assert!(find("SELECT * FROM none").is_empty());
         assert!(find("SELECT * FROM (SELECT * FROM none)").is_empty());
@@ -187,14 +187,19 @@ impl<'a> TypeEvaluator<'a> {
             // See: https://github.com/influxdata/influxdb/blob/e484c4d87193a475466c0285c018d16f168139e6/query/functions.go#L54-L60
             "mean" => Some(VarRefDataType::Float),
             "count" => Some(VarRefDataType::Integer),
+            "count_hll" => Some(VarRefDataType::Unsigned),
+            "sum_hll" => Some(VarRefDataType::String),
             // These functions return the same type as their first argument
-            "min" | "max" | "sum" | "first" | "last" | "distinct" => match arg_types.first() {
-                Some(v) => *v,
-                None => None,
-            },
+            "min" | "max" | "sum" | "first" | "last" | "distinct" | "mode" | "spread" => {
+                match arg_types.first() {
+                    Some(v) => *v,
+                    None => None,
+                }
+            }
 
             // See: https://github.com/influxdata/influxdb/blob/e484c4d87193a475466c0285c018d16f168139e6/query/functions.go#L80
             "median"
+            | "approx_percentile"
             | "integral"
             | "stddev"
             | "derivative"
@@ -211,6 +216,8 @@ impl<'a> TypeEvaluator<'a> {
             | "holt_winters"
             | "holt_winters_with_fit" => Some(VarRefDataType::Float),
             "elapsed" => Some(VarRefDataType::Integer),
+            // now() evaluates to a timestamp, which is not a valid field type
+            "now" => None,
 
             name => self.eval_scalar(name, &arg_types)?,
         })
@@ -613,6 +620,16 @@ mod test {
             .unwrap();
         assert_matches!(res, VarRefDataType::String);
 
+        let res = evaluate_type(&namespace, "MODE(field_str)", &["temp_01"])
+            .unwrap()
+            .unwrap();
+        assert_matches!(res, VarRefDataType::String);
+
+        let res = evaluate_type(&namespace, "SPREAD(field_u64)", &["temp_01"])
+            .unwrap()
+            .unwrap();
+        assert_matches!(res, VarRefDataType::Unsigned);
+
         let res = evaluate_type(&namespace, "MEAN(field_i64)", &["temp_01"])
             .unwrap()
             .unwrap();
@@ -643,9 +660,20 @@ mod test {
             .unwrap();
         assert_matches!(res, VarRefDataType::Integer);
 
+        let res = evaluate_type(&namespace, "COUNT_HLL(field_str)", &["temp_01"])
+            .unwrap()
+            .unwrap();
+        assert_matches!(res, VarRefDataType::Unsigned);
+
+        let res = evaluate_type(&namespace, "SUM_HLL(field_i64)", &["temp_01"])
+            .unwrap()
+            .unwrap();
+        assert_matches!(res, VarRefDataType::String);
+
         // Float functions
         for call in [
             "median(field_i64)",
+            "approx_percentile(field_i64, 50)",
             "integral(field_i64)",
             "stddev(field_i64)",
             "derivative(field_i64)",
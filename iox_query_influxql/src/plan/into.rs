@@ -0,0 +1,129 @@
+//! The destination of an InfluxQL `SELECT ... INTO` statement.
+use crate::error;
+use crate::frontend::planner::database_catalog_name;
+use datafusion::common::Result;
+use influxdb_influxql_parser::select::{IntoClause, IntoMeasurement};
+use schema::INFLUXQL_INTO_METADATA_KEY;
+use std::collections::HashMap;
+
+/// The measurement the results of a `SELECT ... INTO` statement are written to.
+///
+/// The planner records the target in the schema metadata of the plan, under
+/// the [`INFLUXQL_INTO_METADATA_KEY`] key, so that the server executing the plan
+/// can write the results back rather than return them to the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntoTarget {
+    /// The target database. If `None`, the database of the query is used.
+    pub database: Option<String>,
+
+    /// The target retention policy. If `None`, the default retention policy is used.
+    pub retention_policy: Option<String>,
+
+    /// The target measurement. If `None`, results are written to a measurement with the
+    /// same name as the source measurement, as specified by `:MEASUREMENT`.
+    pub measurement: Option<String>,
+}
+
+impl IntoTarget {
+    /// Returns the target recorded in the schema `metadata`, if any.
+    pub fn from_schema_metadata(metadata: &HashMap<String, String>) -> Result<Option<Self>> {
+        let Some(data) = metadata.get(INFLUXQL_INTO_METADATA_KEY) else {
+            return Ok(None);
+        };
+
+        let value: serde_json::Value = serde_json::from_str(data).map_err(|err| {
+            error::map::internal(format!("error deserializing INTO metadata: {err}"))
+        })?;
+
+        let get = |key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+
+        Ok(Some(Self {
+            database: get("database"),
+            retention_policy: get("retention_policy"),
+            measurement: get("measurement"),
+        }))
+    }
+
+    /// Returns the name of the database the results are written to, where `database`
+    /// is the database of the query.
+    ///
+    /// As when resolving the measurements of a query, a retention policy other than the
+    /// default refers to the database named `<database>/<retention policy>`.
+    pub fn database_name(&self, database: &str) -> String {
+        database_catalog_name(
+            self.database.as_deref().unwrap_or(database),
+            self.retention_policy.as_deref(),
+        )
+    }
+
+    /// Serialize the target to the value stored in the schema metadata.
+    pub(crate) fn to_metadata_value(&self) -> String {
+        serde_json::json!({
+            "database": self.database,
+            "retention_policy": self.retention_policy,
+            "measurement": self.measurement,
+        })
+        .to_string()
+    }
+}
+
+impl From<&IntoClause> for IntoTarget {
+    fn from(into: &IntoClause) -> Self {
+        Self {
+            database: into.database.as_ref().map(|v| v.as_str().to_owned()),
+            retention_policy: into
+                .retention_policy
+                .as_ref()
+                .map(|v| v.as_str().to_owned()),
+            measurement: match &into.measurement {
+                IntoMeasurement::Name(name) => Some(name.as_str().to_owned()),
+                IntoMeasurement::Backreference => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let target = IntoTarget {
+            database: Some("telegraf".into()),
+            retention_policy: None,
+            measurement: Some("cpu_1h".into()),
+        };
+        let metadata = HashMap::from([(
+            INFLUXQL_INTO_METADATA_KEY.to_owned(),
+            target.to_metadata_value(),
+        )]);
+        assert_eq!(
+            IntoTarget::from_schema_metadata(&metadata).unwrap(),
+            Some(target)
+        );
+
+        assert_eq!(
+            IntoTarget::from_schema_metadata(&HashMap::new()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_database_name() {
+        let target = |database: Option<&str>, retention_policy: Option<&str>| IntoTarget {
+            database: database.map(String::from),
+            retention_policy: retention_policy.map(String::from),
+            measurement: None,
+        };
+
+        assert_eq!(target(None, None).database_name("foo"), "foo");
+        assert_eq!(target(Some("bar"), None).database_name("foo"), "bar");
+        assert_eq!(target(None, Some("autogen")).database_name("foo"), "foo");
+        assert_eq!(target(None, Some("1h")).database_name("foo"), "foo/1h");
+        assert_eq!(
+            target(Some("bar"), Some("1h")).database_name("foo"),
+            "bar/1h"
+        );
+    }
+}
@@ -1,7 +1,9 @@
 mod expr_type_evaluator;
 mod field;
 mod field_mapper;
+mod into;
 mod ir;
+mod params;
 mod planner;
 mod planner_rewrite_expression;
 mod planner_time_range_expression;
@@ -11,6 +13,9 @@ mod udf;
 mod util;
 mod var_ref;
 
+pub use into::IntoTarget;
+pub(crate) use params::bind_params;
+pub use params::StatementParams;
 pub use planner::InfluxQLToLogicalPlan;
 pub use planner::SchemaProvider;
 pub(crate) use util::parse_regex;
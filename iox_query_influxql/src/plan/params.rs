@@ -0,0 +1,214 @@
+//! Binding of values to the parameters of an InfluxQL statement.
+use crate::error;
+use datafusion::common::{DataFusionError, Result};
+use influxdb_influxql_parser::expression::Expr;
+use influxdb_influxql_parser::literal::Literal;
+use influxdb_influxql_parser::statement::Statement;
+use influxdb_influxql_parser::visit_mut::{VisitableMut, VisitorMut};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// The values bound to the parameters of an InfluxQL statement,
+/// which are referenced as `$name` in the statement text.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StatementParams(HashMap<String, Literal>);
+
+impl StatementParams {
+    /// Create an empty set of parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind `value` to the parameter `name`, replacing any existing value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Literal>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    /// Returns the value bound to the parameter `name`.
+    pub fn get(&self, name: &str) -> Option<&Literal> {
+        self.0.get(name)
+    }
+
+    /// Returns `true` if no parameters have been bound.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Parse the parameters from a JSON object, as accepted by the `params` argument
+    /// of the InfluxDB 1.x `/query` API, such as `{"host": "server01", "limit": 10}`.
+    ///
+    /// Values must be strings, numbers or booleans. Integral numbers are bound as
+    /// integers, and any other number as a float.
+    pub fn from_json(s: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(s)
+            .map_err(|err| error::map::query(format!("invalid parameters: {err}")))?;
+        Self::try_from(value)
+    }
+}
+
+impl TryFrom<serde_json::Value> for StatementParams {
+    type Error = DataFusionError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        use serde_json::Value;
+
+        let Value::Object(map) = value else {
+            return error::query("invalid parameters: expected a JSON object");
+        };
+
+        map.into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::Bool(v) => Literal::Boolean(v),
+                    Value::String(v) => Literal::String(v),
+                    Value::Number(v) => {
+                        if let Some(v) = v.as_i64() {
+                            Literal::Integer(v)
+                        } else if let Some(v) = v.as_u64() {
+                            Literal::Unsigned(v)
+                        } else if let Some(v) = v.as_f64() {
+                            Literal::Float(v)
+                        } else {
+                            return error::query(format!("invalid value for parameter {name}"));
+                        }
+                    }
+                    Value::Null | Value::Array(_) | Value::Object(_) => {
+                        return error::query(format!(
+                            "unsupported value for parameter {name}: {value}"
+                        ))
+                    }
+                };
+                Ok((name, value))
+            })
+            .collect::<Result<HashMap<_, _>>>()
+            .map(Self)
+    }
+}
+
+impl<K: Into<String>, V: Into<Literal>> FromIterator<(K, V)> for StatementParams {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Replace every bind parameter in `stmt` with the literal value bound to it in `params`.
+///
+/// Returns an error if `stmt` references a parameter that has no value.
+pub(crate) fn bind_params(stmt: &mut Statement, params: &StatementParams) -> Result<()> {
+    struct Binder<'a>(&'a StatementParams);
+
+    impl<'a> VisitorMut for Binder<'a> {
+        type Error = DataFusionError;
+
+        fn post_visit_expr(&mut self, n: &mut Expr) -> Result<()> {
+            if let Expr::BindParameter(name) = n {
+                let name = name.deref();
+                match self.0.get(name) {
+                    Some(value) => *n = Expr::Literal(value.clone()),
+                    None => return error::query(format!("missing parameter: {name}")),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    stmt.accept(&mut Binder(params))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use influxdb_influxql_parser::parse_statements;
+
+    fn bind(q: &str, params: &StatementParams) -> Result<String> {
+        let mut stmt = parse_statements(q).unwrap().pop().unwrap();
+        bind_params(&mut stmt, params)?;
+        Ok(stmt.to_string())
+    }
+
+    #[test]
+    fn test_bind_params() {
+        let params = StatementParams::from_iter([
+            ("host", Literal::from("server01".to_owned())),
+            ("value", Literal::from(10_i64)),
+        ]);
+
+        assert_eq!(
+            bind(
+                "SELECT usage_idle + $value FROM cpu WHERE host = $host",
+                &params
+            )
+            .unwrap(),
+            "SELECT usage_idle + 10 FROM cpu WHERE host = 'server01'"
+        );
+
+        // parameters in subqueries and SHOW statements
+        assert_eq!(
+            bind(
+                "SELECT * FROM (SELECT usage_idle FROM cpu WHERE host = $host)",
+                &params
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT usage_idle FROM cpu WHERE host = 'server01')"
+        );
+        assert_eq!(
+            bind(
+                "SHOW TAG VALUES WITH KEY = host WHERE host = $host",
+                &params
+            )
+            .unwrap(),
+            "SHOW TAG VALUES WITH KEY = host WHERE host = 'server01'"
+        );
+
+        // no parameters
+        assert_eq!(
+            bind("SELECT usage_idle FROM cpu", &StatementParams::new()).unwrap(),
+            "SELECT usage_idle FROM cpu"
+        );
+
+        // Fallible
+
+        assert_eq!(
+            bind("SELECT usage_idle FROM cpu WHERE region = $region", &params)
+                .unwrap_err()
+                .to_string(),
+            "Error during planning: missing parameter: region"
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let params = StatementParams::from_json(
+            r#"{"s": "foo", "b": true, "i": -3, "u": 18446744073709551615, "f": 1.5}"#,
+        )
+        .unwrap();
+        assert_eq!(params.get("s"), Some(&Literal::String("foo".to_owned())));
+        assert_eq!(params.get("b"), Some(&Literal::Boolean(true)));
+        assert_eq!(params.get("i"), Some(&Literal::Integer(-3)));
+        assert_eq!(params.get("u"), Some(&Literal::Unsigned(u64::MAX)));
+        assert_eq!(params.get("f"), Some(&Literal::Float(1.5)));
+
+        assert!(StatementParams::from_json("{}").unwrap().is_empty());
+
+        // Fallible
+
+        assert_eq!(
+            StatementParams::from_json("[1]").unwrap_err().to_string(),
+            "Error during planning: invalid parameters: expected a JSON object"
+        );
+        assert_eq!(
+            StatementParams::from_json(r#"{"n": null}"#)
+                .unwrap_err()
+                .to_string(),
+            "Error during planning: unsupported value for parameter n: null"
+        );
+        assert!(StatementParams::from_json("{")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Error during planning: invalid parameters: "));
+    }
+}
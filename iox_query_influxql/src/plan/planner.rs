@@ -1,6 +1,6 @@
 mod select;
 
-use crate::aggregate::PERCENTILE;
+use crate::aggregate::{INTEGRAL, MODE, PERCENTILE, SPREAD};
 use crate::error;
 use crate::plan::ir::{DataSource, Field, Interval, Select, SelectQuery};
 use crate::plan::planner::select::{
@@ -10,19 +10,23 @@ use crate::plan::planner::select::{
 use crate::plan::planner_time_range_expression::time_range_to_df_expr;
 use crate::plan::rewriter::{find_table_names, rewrite_statement, ProjectionType};
 use crate::plan::udf::{
-    cumulative_sum, derivative, difference, find_window_udfs, moving_average,
-    non_negative_derivative, non_negative_difference,
+    cumulative_sum, derivative, difference, elapsed, find_window_udfs, holt_winters,
+    holt_winters_with_fit, moving_average, non_negative_derivative, non_negative_difference,
 };
 use crate::plan::util::{binary_operator_to_df_operator, rebase_expr, IQLSchema};
 use crate::plan::var_ref::var_ref_data_type_to_data_type;
 use crate::plan::{planner_rewrite_expression, udf};
 use crate::window::{
-    CUMULATIVE_SUM, DERIVATIVE, DIFFERENCE, MOVING_AVERAGE, NON_NEGATIVE_DERIVATIVE,
-    NON_NEGATIVE_DIFFERENCE, PERCENT_ROW_NUMBER,
+    sample_with_seed, CHANDE_MOMENTUM_OSCILLATOR, CUMULATIVE_SUM, DERIVATIVE, DIFFERENCE,
+    DOUBLE_EXPONENTIAL_MOVING_AVERAGE, ELAPSED, EXPONENTIAL_MOVING_AVERAGE, HOLT_WINTERS,
+    HOLT_WINTERS_WITH_FIT, KAUFMANS_ADAPTIVE_MOVING_AVERAGE, KAUFMANS_EFFICIENCY_RATIO,
+    MOVING_AVERAGE, NON_NEGATIVE_DERIVATIVE, NON_NEGATIVE_DIFFERENCE, PERCENT_ROW_NUMBER,
+    RELATIVE_STRENGTH_INDEX, SAMPLE, TRIPLE_EXPONENTIAL_DERIVATIVE,
+    TRIPLE_EXPONENTIAL_MOVING_AVERAGE,
 };
 use arrow::array::{
-    BooleanArray, DictionaryArray, Int32Array, Int64Array, StringArray, StringBuilder,
-    StringDictionaryBuilder,
+    BooleanArray, DictionaryArray, Int32Array, Int64Array, Int64Builder, StringArray,
+    StringBuilder, StringDictionaryBuilder,
 };
 use arrow::datatypes::{DataType, Field as ArrowField, Int32Type, Schema as ArrowSchema};
 use arrow::record_batch::RecordBatch;
@@ -45,7 +49,7 @@ use datafusion::logical_expr::{
 };
 use datafusion::optimizer::utils::conjunction;
 use datafusion::physical_expr::execution_props::ExecutionProps;
-use datafusion::prelude::{cast, sum, when, Column};
+use datafusion::prelude::{approx_distinct, cast, count, count_distinct, sum, when, Column};
 use datafusion_util::{lit_dict, AsExpr};
 use generated_types::influxdata::iox::querier::v1::InfluxQlMetadata;
 use influxdb_influxql_parser::common::{LimitClause, OffsetClause, OrderByClause};
@@ -58,12 +62,14 @@ use influxdb_influxql_parser::expression::{
 use influxdb_influxql_parser::functions::{
     is_aggregate_function, is_now_function, is_scalar_math_function,
 };
-use influxdb_influxql_parser::select::{FillClause, GroupByClause};
+use influxdb_influxql_parser::select::{FillClause, GroupByClause, SLimitClause, SOffsetClause};
+use influxdb_influxql_parser::show_cardinality::{CardinalityKind, ShowCardinalityStatement};
 use influxdb_influxql_parser::show_field_keys::ShowFieldKeysStatement;
 use influxdb_influxql_parser::show_measurements::{
     ShowMeasurementsStatement, WithMeasurementClause,
 };
 use influxdb_influxql_parser::show_retention_policies::ShowRetentionPoliciesStatement;
+use influxdb_influxql_parser::show_series::ShowSeriesStatement;
 use influxdb_influxql_parser::show_tag_keys::ShowTagKeysStatement;
 use influxdb_influxql_parser::show_tag_values::{ShowTagValuesStatement, WithKeyClause};
 use influxdb_influxql_parser::simple_from_clause::ShowFromClause;
@@ -79,16 +85,18 @@ use influxdb_influxql_parser::{
 use iox_query::config::{IoxConfigExt, MetadataCutoff};
 use iox_query::exec::gapfill::{FillStrategy, GapFill, GapFillParams};
 use iox_query::exec::IOxSessionContext;
-use iox_query::logical_optimizer::range_predicate::find_time_range;
+use iox_query::logical_optimizer::{lookback::with_lookback, range_predicate::find_time_range};
 use itertools::Itertools;
 use observability_deps::tracing::debug;
 use query_functions::{
+    approx::{APPROX_PERCENTILE, COUNT_HLL, SUM_HLL},
     clean_non_meta_escapes,
+    date_bin_wallclock::{date_bin_wallclock, parse_timezone, DATE_BIN_WALLCLOCK_UDF_NAME},
     selectors::{selector_first, selector_last, selector_max, selector_min},
 };
 use schema::{
-    InfluxColumnType, InfluxFieldType, Schema, INFLUXQL_MEASUREMENT_COLUMN_NAME,
-    INFLUXQL_METADATA_KEY,
+    InfluxColumnType, InfluxFieldType, Schema, INFLUXQL_INTO_METADATA_KEY,
+    INFLUXQL_MEASUREMENT_COLUMN_NAME, INFLUXQL_METADATA_KEY,
 };
 use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
@@ -96,9 +104,11 @@ use std::iter;
 use std::ops::{Bound, ControlFlow, Deref, Not, Range};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::parse_regex;
 use super::util::contains_expr;
+use super::IntoTarget;
 
 /// The column index of the measurement column.
 const MEASUREMENT_COLUMN_INDEX: u32 = 0;
@@ -443,12 +453,24 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
 
     pub fn statement_to_plan(&self, statement: Statement) -> Result<LogicalPlan> {
         match statement {
+            Statement::CreateContinuousQuery(_) => {
+                error::not_implemented("CREATE CONTINUOUS QUERY")
+            }
             Statement::CreateDatabase(_) => error::not_implemented("CREATE DATABASE"),
             Statement::Delete(_) => error::not_implemented("DELETE"),
+            Statement::DropContinuousQuery(_) => error::not_implemented("DROP CONTINUOUS QUERY"),
             Statement::DropMeasurement(_) => error::not_implemented("DROP MEASUREMENT"),
             Statement::Explain(explain) => self.explain_statement_to_plan(*explain),
-            Statement::Select(select) => {
-                self.select_query_to_plan(&self.rewrite_select_statement(*select)?)
+            Statement::Select(mut select) => {
+                let into = select.into.take();
+                let plan = self.select_query_to_plan(&self.rewrite_select_statement(*select)?)?;
+                match into {
+                    Some(into) => plan_with_into_target(plan, &IntoTarget::from(&into)),
+                    None => Ok(plan),
+                }
+            }
+            Statement::ShowContinuousQueries(_) => {
+                error::not_implemented("SHOW CONTINUOUS QUERIES")
             }
             Statement::ShowDatabases(_) => error::not_implemented("SHOW DATABASES"),
             Statement::ShowMeasurements(show_measurements) => {
@@ -464,6 +486,10 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             Statement::ShowFieldKeys(show_field_keys) => {
                 self.show_field_keys_to_plan(*show_field_keys)
             }
+            Statement::ShowSeries(show_series) => self.show_series_to_plan(*show_series),
+            Statement::ShowCardinality(show_cardinality) => {
+                self.show_cardinality_to_plan(*show_cardinality)
+            }
         }
     }
 
@@ -638,6 +664,14 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             false,
         );
 
+        let plan = self.series_limit(
+            plan,
+            select.series_offset,
+            select.series_limit,
+            sort_by_measurement,
+            &group_by_tag_set,
+        )?;
+
         let plan = plan_with_sort(
             plan,
             vec![time_sort_expr.clone()],
@@ -686,6 +720,14 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             false,
         );
 
+        let plan = self.series_limit(
+            plan,
+            select.series_offset,
+            select.series_limit,
+            false,
+            &group_by_tag_set,
+        )?;
+
         let plan = plan_with_sort(
             plan,
             vec![time_sort_expr.clone()],
@@ -774,6 +816,7 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         })
     }
 
+    #[rustfmt::skip]
     fn project_select(
         &self,
         ctx: &Context<'_>,
@@ -787,7 +830,7 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             ProjectionType::Aggregate   => self.project_select_aggregate(ctx, input, fields, group_by_tag_set),
             ProjectionType::Window => self.project_select_window(ctx, input, fields, group_by_tag_set),
             ProjectionType::WindowAggregate => self.project_select_window_aggregate(ctx, input, fields, group_by_tag_set),
-            ProjectionType::WindowAggregateMixed => error::not_implemented("mixed window-aggregate and aggregate columns, such as DIFFERENCE(MEAN(col)), MEAN(col)"),
+            ProjectionType::WindowAggregateMixed => self.project_select_window_aggregate_mixed(ctx, input, fields, group_by_tag_set),
             ProjectionType::Selector{..} => self.project_select_selector(ctx, input, fields, group_by_tag_set),
             ProjectionType::TopBottomSelector => self.project_select_top_bottom_selector(ctx, input, fields, group_by_tag_set),
         }
@@ -941,12 +984,44 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         }
     }
 
+    /// Plan "WindowAggregateMixed" SELECT queries. These are queries that use
+    /// a combination of window and nested aggregate functions, along with
+    /// projections that are only aggregates, such as:
+    ///
+    /// ```sql
+    /// SELECT MEAN(usage), DERIVATIVE(MEAN(usage)) FROM cpu GROUP BY TIME(1m)
+    /// ```
+    ///
+    /// The aggregates are computed once for each window, and the window functions
+    /// are evaluated over the aggregated rows, so that every column is produced
+    /// for the same set of windows.
+    fn project_select_window_aggregate_mixed(
+        &self,
+        ctx: &Context<'_>,
+        input: LogicalPlan,
+        fields: &[Field],
+        group_by_tag_set: &[&str],
+    ) -> Result<LogicalPlan> {
+        let schema = IQLSchema::new_from_fields(input.schema(), fields)?;
+
+        // Transform InfluxQL AST field expressions to a list of DataFusion expressions.
+        let select_exprs = self.field_list_to_exprs(&input, fields, &schema)?;
+
+        let (plan, select_exprs) =
+            self.select_aggregate(ctx, input, fields, select_exprs, group_by_tag_set)?;
+
+        let (plan, select_exprs) = self.select_window(ctx, plan, select_exprs, group_by_tag_set)?;
+
+        // Unlike "WindowAggregate" queries, rows where the window functions produce `NULL`,
+        // such as the first row of a DERIVATIVE, are retained, as InfluxQL OG emits a row
+        // for every window produced by the aggregate projections.
+        project(plan, select_exprs)
+    }
+
     /// Plan the execution of SELECT queries that have the Selector projection
     /// type. These a queries that include a single FIRST, LAST, MAX, MIN,
     /// PERCENTILE, or SAMPLE function call, possibly requesting additional
     /// tags or fields.
-    ///
-    /// N.B SAMPLE is not yet implemented.
     fn project_select_selector(
         &self,
         ctx: &Context<'_>,
@@ -1010,8 +1085,33 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
 
                 (idx, field_key, plan)
             }
-            (_, Selector::Sample { field_key: _, n: _ }) => {
-                return error::not_implemented("sample selector function")
+            (idx, Selector::Sample { field_key, n }) => {
+                // Select n random rows from each partition, using reservoir
+                // sampling over the rows with a value for the field.
+                let sample = match self.sample_seed() {
+                    Some(seed) => sample_with_seed(seed),
+                    None => SAMPLE.clone(),
+                };
+                let window_sample = Expr::WindowFunction(WindowFunction::new(
+                    sample,
+                    vec![lit(n)],
+                    window_partition_by(ctx, input.schema(), group_by_tag_set),
+                    vec![ctx.time_sort_expr()],
+                    WindowFrame {
+                        units: WindowFrameUnits::Rows,
+                        start_bound: WindowFrameBound::Preceding(ScalarValue::Null),
+                        end_bound: WindowFrameBound::Following(ScalarValue::Null),
+                    },
+                ));
+                let sample_column_name = window_sample.display_name()?;
+
+                let plan = LogicalPlanBuilder::from(input)
+                    .filter(field_key.as_expr().is_not_null())?
+                    .window(vec![window_sample.alias(sample_column_name.clone())])?
+                    .filter(col(sample_column_name))?
+                    .build()?;
+
+                (idx, field_key, plan)
             }
 
             (_, s) => {
@@ -1143,51 +1243,62 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             return error::internal("unable to find time column");
         };
 
-        // if there's only a single selector, wrap non-aggregated fields into that selector
+        // wrap non-aggregated fields into the first FIRST, LAST, MAX or MIN selector, so
+        // they are taken from the row it selects
         let mut should_fill_expr = fields.iter().map(is_aggregate_field).collect::<Vec<_>>();
-        if aggr_exprs.len() == 1 {
-            let selector = aggr_exprs[0].clone();
+        let selector_position = aggr_exprs.iter().position(
+            |expr| matches!(expr, Expr::AggregateUDF(udf) if udf.fun.name.starts_with("selector_")),
+        );
+        if let Some(selector_position) = selector_position {
+            let selector = aggr_exprs[selector_position].clone();
 
             if let Expr::AggregateUDF(mut udf) = selector.clone() {
-                if udf.fun.name.starts_with("selector_") {
-                    let selector_index = select_exprs
-                        .iter()
-                        .enumerate()
-                        .find(|(_i, expr)| contains_expr(expr, &selector))
-                        .map(|(i, _expr)| i)
-                        .ok_or_else(|| error::map::internal("cannot find selector expression"))?;
-
-                    let group_by_tag_set = group_by_tag_set.iter().copied().collect::<HashSet<_>>();
-
-                    let mut additional_args = vec![];
-                    let mut fields_to_extract = vec![];
-                    for (idx, expr) in select_exprs.iter().enumerate() {
-                        if (idx == time_column_index) || (idx == selector_index) {
-                            continue;
-                        }
-                        let (expr, out_name) = match expr.clone() {
-                            Expr::Alias(Alias {
-                                expr,
-                                name: out_name,
-                            }) => (*expr, out_name),
-                            _ => {
-                                return error::internal("other field is not aliased");
-                            }
-                        };
-                        if group_by_tag_set.contains(&out_name.as_str()) {
-                            continue;
+                let selector_index = select_exprs
+                    .iter()
+                    .enumerate()
+                    .find(|(_i, expr)| contains_expr(expr, &selector))
+                    .map(|(i, _expr)| i)
+                    .ok_or_else(|| error::map::internal("cannot find selector expression"))?;
+
+                let group_by_tag_set = group_by_tag_set.iter().copied().collect::<HashSet<_>>();
+
+                let mut additional_args = vec![];
+                let mut fields_to_extract = vec![];
+                for (idx, expr) in select_exprs.iter().enumerate() {
+                    // Skip the other aggregate and selector fields
+                    if (idx == time_column_index)
+                        || (idx == selector_index)
+                        || should_fill_expr[idx]
+                    {
+                        continue;
+                    }
+                    let (expr, out_name) = match expr.clone() {
+                        Expr::Alias(Alias {
+                            expr,
+                            name: out_name,
+                        }) => (*expr, out_name),
+                        _ => {
+                            return error::internal("other field is not aliased");
                         }
-                        additional_args.push(expr);
-                        fields_to_extract.push((
-                            idx,
-                            format!("other_{}", additional_args.len()),
-                            out_name,
-                        ));
+                    };
+                    if group_by_tag_set.contains(&out_name.as_str()) {
+                        continue;
                     }
+                    additional_args.push(expr);
+                    fields_to_extract.push((
+                        idx,
+                        format!("other_{}", additional_args.len()),
+                        out_name,
+                    ));
+                }
 
-                    udf.args.append(&mut additional_args);
-                    let selector_new = Expr::AggregateUDF(udf);
-                    select_exprs[selector_index] = select_exprs[selector_index]
+                udf.args.append(&mut additional_args);
+                let selector_new = Expr::AggregateUDF(udf);
+                for expr in select_exprs
+                    .iter_mut()
+                    .filter(|e| contains_expr(e, &selector))
+                {
+                    *expr = expr
                         .clone()
                         .transform_up(&|expr| {
                             if expr == selector {
@@ -1197,13 +1308,12 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
                             }
                         })
                         .expect("cannot fail");
-                    aggr_exprs[0] = selector_new.clone();
+                }
+                aggr_exprs[selector_position] = selector_new.clone();
 
-                    for (idx, struct_name, out_alias) in fields_to_extract {
-                        select_exprs[idx] =
-                            selector_new.clone().field(struct_name).alias(out_alias);
-                        should_fill_expr[idx] = true;
-                    }
+                for (idx, struct_name, out_alias) in fields_to_extract {
+                    select_exprs[idx] = selector_new.clone().field(struct_name).alias(out_alias);
+                    should_fill_expr[idx] = true;
                 }
             }
         }
@@ -1226,14 +1336,7 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             // 2. is a single-selector query, project the `time` field of the selector aggregate,
             // 3. otherwise, project the Unix epoch (0)
             select_exprs[time_column_index] = if let Some(i) = ctx.interval {
-                let stride = lit(ScalarValue::new_interval_mdn(0, 0, i.duration));
-                let offset = i.offset.unwrap_or_default();
-
-                date_bin(
-                    stride,
-                    "time".as_expr(),
-                    lit(ScalarValue::TimestampNanosecond(Some(offset), None)),
-                )
+                date_bin_time(i, ctx.tz)
             } else if let ProjectionType::Selector { has_fields: _ } = ctx.projection_type {
                 let selector = match aggr_exprs.len() {
                     1 => aggr_exprs[0].clone(),
@@ -1251,7 +1354,7 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             }
             .alias(alias);
 
-            &select_exprs[time_column_index]
+            select_exprs[time_column_index].clone()
         };
 
         let aggr_group_by_exprs = {
@@ -1284,12 +1387,39 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             return Ok((LogicalPlanBuilder::empty(true).build()?, select_exprs));
         }
 
+        // The area of each INTEGRAL aggregate with a `GROUP BY TIME` interval is calculated
+        // by a window function, and the aggregate selects it in place of the INTEGRAL.
+        let (input, integrals) = plan_integral_windows(ctx, input, &aggr_exprs, group_by_tag_set)?;
+        for (integral, window_integral) in &integrals {
+            let replace = |expr: Expr| {
+                expr.transform_up(&|expr| {
+                    Ok(if &expr == integral {
+                        Transformed::Yes(window_integral.clone())
+                    } else {
+                        Transformed::No(expr)
+                    })
+                })
+            };
+            aggr_exprs = aggr_exprs.into_iter().map(replace).collect::<Result<_>>()?;
+            select_exprs = select_exprs
+                .into_iter()
+                .map(replace)
+                .collect::<Result<_>>()?;
+        }
+
         let plan = LogicalPlanBuilder::from(input)
             .aggregate(aggr_group_by_exprs.clone(), aggr_exprs.clone())?
             .build()?;
 
         let fill_option = ctx.fill();
 
+        // The `HOLT_WINTERS` functions forecast values past the end of the time range, so the
+        // gap-filled time range is extended to provide a row for each forecasted value.
+        let forecast_duration = match (max_holt_winters_forecast(&select_exprs), ctx.interval) {
+            (n @ 1.., Some(interval)) => Some(n * interval.duration),
+            _ => None,
+        };
+
         // Wrap the plan in a GapFill operator if the statement specifies a `GROUP BY TIME` clause and
         // the FILL option is one of
         //
@@ -1298,17 +1428,23 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         // * `literal` value
         // * `linear`
         //
+        // or the projection forecasts values using one of the `HOLT_WINTERS` functions.
         let plan = if ctx.group_by.and_then(|gb| gb.time_dimension()).is_some()
-            && fill_option != FillClause::None
+            && (fill_option != FillClause::None || forecast_duration.is_some())
         {
             let fill_strategy = match fill_option {
-                FillClause::Null | FillClause::Value(_) => FillStrategy::Null,
+                FillClause::Null | FillClause::Value(_) | FillClause::None => FillStrategy::Null,
                 FillClause::Previous => FillStrategy::PrevNullAsMissing,
                 FillClause::Linear => FillStrategy::LinearInterpolate,
-                FillClause::None => unreachable!(),
             };
 
-            build_gap_fill_node(plan, time_column, fill_strategy)?
+            build_gap_fill_node(
+                plan,
+                &time_column,
+                fill_strategy,
+                forecast_duration,
+                self.gap_fill_lookback(),
+            )?
         } else {
             plan
         };
@@ -1554,6 +1690,96 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
                 },
             })
             .alias(alias)),
+            Some(udf::WindowFunction::Elapsed) => Ok(Expr::WindowFunction(WindowFunction {
+                fun: ELAPSED.clone(),
+                args: vec![
+                    args[0].clone(),
+                    // The unit defaults to 1ns
+                    args.get(1)
+                        .cloned()
+                        .unwrap_or_else(|| lit(ScalarValue::new_interval_mdn(0, 0, 1))),
+                    "time".as_expr(),
+                ],
+                partition_by,
+                order_by,
+                window_frame: WindowFrame {
+                    units: WindowFrameUnits::Rows,
+                    start_bound: WindowFrameBound::Preceding(ScalarValue::Null),
+                    end_bound: WindowFrameBound::Following(ScalarValue::Null),
+                },
+            })
+            .alias(alias)),
+            Some(
+                hw @ (udf::WindowFunction::HoltWinters | udf::WindowFunction::HoltWintersWithFit),
+            ) => {
+                let Some(interval) = ctx.interval else {
+                    return error::internal(format!("{} requires a GROUP BY interval", fun.name));
+                };
+
+                Ok(Expr::WindowFunction(WindowFunction {
+                    fun: match hw {
+                        udf::WindowFunction::HoltWinters => HOLT_WINTERS.clone(),
+                        _ => HOLT_WINTERS_WITH_FIT.clone(),
+                    },
+                    args: vec![
+                        args[0].clone(),
+                        args[1].clone(),
+                        args[2].clone(),
+                        lit(ScalarValue::new_interval_mdn(0, 0, interval.duration)),
+                        "time".as_expr(),
+                    ],
+                    partition_by,
+                    order_by,
+                    window_frame: WindowFrame {
+                        units: WindowFrameUnits::Rows,
+                        start_bound: WindowFrameBound::Preceding(ScalarValue::Null),
+                        end_bound: WindowFrameBound::Following(ScalarValue::Null),
+                    },
+                })
+                .alias(alias))
+            }
+            Some(
+                ta @ (udf::WindowFunction::ExponentialMovingAverage
+                | udf::WindowFunction::DoubleExponentialMovingAverage
+                | udf::WindowFunction::TripleExponentialMovingAverage
+                | udf::WindowFunction::TripleExponentialDerivative
+                | udf::WindowFunction::RelativeStrengthIndex
+                | udf::WindowFunction::ChandeMomentumOscillator
+                | udf::WindowFunction::KaufmansEfficiencyRatio
+                | udf::WindowFunction::KaufmansAdaptiveMovingAverage),
+            ) => Ok(Expr::WindowFunction(WindowFunction {
+                fun: match ta {
+                    udf::WindowFunction::ExponentialMovingAverage => {
+                        EXPONENTIAL_MOVING_AVERAGE.clone()
+                    }
+                    udf::WindowFunction::DoubleExponentialMovingAverage => {
+                        DOUBLE_EXPONENTIAL_MOVING_AVERAGE.clone()
+                    }
+                    udf::WindowFunction::TripleExponentialMovingAverage => {
+                        TRIPLE_EXPONENTIAL_MOVING_AVERAGE.clone()
+                    }
+                    udf::WindowFunction::TripleExponentialDerivative => {
+                        TRIPLE_EXPONENTIAL_DERIVATIVE.clone()
+                    }
+                    udf::WindowFunction::RelativeStrengthIndex => RELATIVE_STRENGTH_INDEX.clone(),
+                    udf::WindowFunction::ChandeMomentumOscillator => {
+                        CHANDE_MOMENTUM_OSCILLATOR.clone()
+                    }
+                    udf::WindowFunction::KaufmansEfficiencyRatio => {
+                        KAUFMANS_EFFICIENCY_RATIO.clone()
+                    }
+                    _ => KAUFMANS_ADAPTIVE_MOVING_AVERAGE.clone(),
+                },
+                args,
+                partition_by,
+                order_by,
+                window_frame: WindowFrame {
+                    units: WindowFrameUnits::Rows,
+                    start_bound: WindowFrameBound::Preceding(ScalarValue::Null),
+                    end_bound: WindowFrameBound::Following(ScalarValue::Null),
+                },
+            })
+            .alias(alias)),
             None => error::internal(format!(
                 "unexpected user-defined window function: {}",
                 fun.name
@@ -1661,31 +1887,9 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             // a reference to the ROW_NUMBER column.
             let row_alias = IOX_ROW_ALIAS.as_expr();
 
-            let row_filter_expr = match (limit, offset) {
-                // WHERE "iox::row" BETWEEN OFFSET + 1 AND OFFSET + LIMIT
-                (Some(limit), Some(offset)) => {
-                    let low = offset + 1;
-                    let high = offset + limit;
-
-                    Expr::Between(Between {
-                        expr: Box::new(row_alias),
-                        negated: false,
-                        low: Box::new(lit(low)),
-                        high: Box::new(lit(high)),
-                    })
-                }
-
-                // WHERE "iox::row" <= LIMIT
-                (Some(limit), None) => row_alias.lt_eq(lit(limit)),
-
-                // WHERE "iox::row" > OFFSET
-                (None, Some(offset)) => row_alias.gt(lit(offset)),
-                (None, None) => unreachable!("limit and offset cannot not be None"),
-            };
-
             let plan = LogicalPlanBuilder::from(plan)
                 // Filter by the LIMIT and OFFSET clause
-                .filter(row_filter_expr)?
+                .filter(limit_offset_filter(row_alias, limit, offset))?
                 // Project the output without the IOX_ROW_ALIAS column
                 .project(proj_exprs)?
                 .build()?;
@@ -1702,6 +1906,104 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         }
     }
 
+    /// Generate a plan that restricts the input to a window of series, first omitting a
+    /// specified number of series, followed by restricting the quantity of series.
+    ///
+    /// A series is identified by the measurement and the values of the `GROUP BY` tags, and
+    /// the series are ordered by the same columns, in ascending order.
+    ///
+    /// ## Arguments
+    ///
+    /// - `input`: The plan to apply the series limit to.
+    /// - `series_offset`: The number of series to skip.
+    /// - `series_limit`: The maximum number of series to return in the output plan.
+    /// - `sort_by_measurement`: `true` if the `input` includes multiple measurements.
+    /// - `group_by_tag_set`: Tag columns from the `input` plan that identify each series.
+    fn series_limit(
+        &self,
+        input: LogicalPlan,
+        series_offset: Option<SOffsetClause>,
+        series_limit: Option<SLimitClause>,
+        sort_by_measurement: bool,
+        group_by_tag_set: &[&str],
+    ) -> Result<LogicalPlan> {
+        if series_offset.is_none() && series_limit.is_none() {
+            return Ok(input);
+        }
+
+        let limit = series_limit
+            .map(|v| <u64 as TryInto<i64>>::try_into(*v))
+            .transpose()
+            .map_err(|_| error::map::query("slimit out of range"))?;
+        let offset = series_offset
+            .map(|v| <u64 as TryInto<i64>>::try_into(*v))
+            .transpose()
+            .map_err(|_| error::map::query("soffset out of range"))?;
+
+        let series_exprs = if sort_by_measurement {
+            iter::once(INFLUXQL_MEASUREMENT_COLUMN_NAME.as_expr())
+                .chain(fields_to_exprs_no_nulls(input.schema(), group_by_tag_set))
+                .collect::<Vec<_>>()
+        } else {
+            fields_to_exprs_no_nulls(input.schema(), group_by_tag_set).collect::<Vec<_>>()
+        };
+
+        if series_exprs.is_empty() {
+            // The input is a single series, so it is either retained or skipped entirely.
+            return if offset.unwrap_or_default() > 0 || limit == Some(0) {
+                LogicalPlanBuilder::from(input).limit(0, Some(0))?.build()
+            } else {
+                Ok(input)
+            };
+        }
+
+        // The name of the DENSE_RANK window expression
+        const IOX_SERIES_ALIAS: &str = "iox::series";
+
+        // Number each series, such that all the rows of a series share the same number:
+        //
+        // DENSE_RANK() OVER (
+        //   ORDER BY [iox::measurement, group_by_tag_set] ASC
+        // ) AS iox::series
+        let window_func_exprs = vec![Expr::WindowFunction(WindowFunction {
+            fun: window_function::WindowFunction::BuiltInWindowFunction(
+                BuiltInWindowFunction::DenseRank,
+            ),
+            args: vec![],
+            partition_by: vec![],
+            order_by: series_exprs
+                .into_iter()
+                .map(|expr| expr.sort(true, false))
+                .collect(),
+            window_frame: WindowFrame {
+                units: WindowFrameUnits::Range,
+                start_bound: WindowFrameBound::Preceding(ScalarValue::Null),
+                end_bound: WindowFrameBound::CurrentRow,
+            },
+        })
+        .alias(IOX_SERIES_ALIAS)];
+
+        // Prepare new projection.
+        let proj_exprs = input
+            .schema()
+            .fields()
+            .iter()
+            .map(|expr| Expr::Column(expr.unqualified_column()))
+            .collect::<Vec<_>>();
+
+        LogicalPlanBuilder::from(input)
+            .window(window_func_exprs)?
+            // Filter by the SLIMIT and SOFFSET clause
+            .filter(limit_offset_filter(
+                IOX_SERIES_ALIAS.as_expr(),
+                limit,
+                offset,
+            ))?
+            // Project the output without the IOX_SERIES_ALIAS column
+            .project(proj_exprs)?
+            .build()
+    }
+
     /// Map the InfluxQL `SELECT` projection list into a list of DataFusion expressions.
     fn field_list_to_exprs(
         &self,
@@ -1836,7 +2138,8 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
                     },
                 })
             }
-            IQLExpr::BindParameter(_) => error::not_implemented("parameter"),
+            // Bind parameters should be substituted prior to planning.
+            IQLExpr::BindParameter(_) => error::internal("unexpected bind parameter"),
             IQLExpr::Literal(val) => match val {
                 Literal::Integer(v) => Ok(lit(*v)),
                 Literal::Unsigned(v) => Ok(lit(*v)),
@@ -1893,19 +2196,38 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             return self.scalar_math_func_to_df_expr(scope, call, schema);
         }
 
+        if is_now_function(&call.name) {
+            return self.now_to_df_expr(call);
+        }
+
         match scope {
             ExprScope::Where => {
-                if is_now_function(&call.name) {
-                    error::not_implemented("now")
-                } else {
-                    let name = &call.name;
-                    error::query(format!("invalid function call in condition: {name}"))
-                }
+                let name = &call.name;
+                error::query(format!("invalid function call in condition: {name}"))
             }
             ExprScope::Projection => self.function_to_df_expr(scope, call, schema),
         }
     }
 
+    /// Map a call to the InfluxQL `now()` function to the start time of the query,
+    /// so that every reference to `now()` evaluates to the same value.
+    fn now_to_df_expr(&self, call: &Call) -> Result<Expr> {
+        let got = call.args.len();
+        if got != 0 {
+            return error::query(format!(
+                "invalid number of arguments for now: expected 0, got {got}"
+            ));
+        }
+
+        let now = self
+            .s
+            .execution_props()
+            .query_execution_start_time
+            .timestamp_nanos_opt()
+            .ok_or_else(|| error::map::query("timestamp out of range"))?;
+        Ok(lit(ScalarValue::TimestampNanosecond(Some(now), None)))
+    }
+
     fn function_to_df_expr(
         &self,
         scope: ExprScope,
@@ -1981,6 +2303,42 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
                     None,
                 )))
             }
+            name @ ("mode" | "spread") => {
+                let expr = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = expr {
+                    return Ok(expr);
+                }
+
+                check_arg_count(name, args, 1)?;
+                Ok(Expr::AggregateUDF(expr::AggregateUDF::new(
+                    match name {
+                        "mode" => MODE.clone(),
+                        _ => SPREAD.clone(),
+                    },
+                    vec![expr],
+                    None,
+                    None,
+                )))
+            }
+            "integral" => {
+                let expr = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = expr {
+                    return Ok(expr);
+                }
+
+                check_arg_count_range(name, args, 1, 2)?;
+                // The unit defaults to 1s
+                let unit = match args.get(1) {
+                    Some(arg) => self.expr_to_df_expr(scope, arg, schema)?,
+                    None => lit(ScalarValue::new_interval_mdn(0, 0, 1_000_000_000)),
+                };
+                Ok(Expr::AggregateUDF(expr::AggregateUDF::new(
+                    INTEGRAL.clone(),
+                    vec![expr, unit, "time".as_expr()],
+                    None,
+                    None,
+                )))
+            }
             "percentile" => {
                 let expr = self.expr_to_df_expr(scope, &args[0], schema)?;
                 if let Expr::Literal(ScalarValue::Null) = expr {
@@ -1996,6 +2354,38 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
                     None,
                 )))
             }
+            name @ ("count_hll" | "sum_hll") => {
+                let expr = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = expr {
+                    return Ok(expr);
+                }
+
+                check_arg_count(name, args, 1)?;
+                Ok(Expr::AggregateUDF(expr::AggregateUDF::new(
+                    match name {
+                        "count_hll" => COUNT_HLL.clone(),
+                        _ => SUM_HLL.clone(),
+                    },
+                    vec![expr],
+                    None,
+                    None,
+                )))
+            }
+            "approx_percentile" => {
+                let expr = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = expr {
+                    return Ok(expr);
+                }
+
+                check_arg_count(name, args, 2)?;
+                let nexpr = self.expr_to_df_expr(scope, &args[1], schema)?;
+                Ok(Expr::AggregateUDF(expr::AggregateUDF::new(
+                    APPROX_PERCENTILE.clone(),
+                    vec![expr, nexpr],
+                    None,
+                    None,
+                )))
+            }
             name @ ("first" | "last" | "min" | "max") => {
                 let expr = self.expr_to_df_expr(scope, &args[0], schema)?;
                 if let Expr::Literal(ScalarValue::Null) = expr {
@@ -2091,8 +2481,8 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
 
                 Ok(non_negative_derivative(eargs))
             }
-            "cumulative_sum" => {
-                check_arg_count(name, args, 1)?;
+            name @ ("holt_winters" | "holt_winters_with_fit") => {
+                check_arg_count(name, args, 3)?;
 
                 // arg0 should be a column or function
                 let arg0 = self.expr_to_df_expr(scope, &args[0], schema)?;
@@ -2100,16 +2490,122 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
                     return Ok(arg0);
                 }
 
-                Ok(cumulative_sum(vec![arg0]))
-            }
-            // The TOP/BOTTOM function is handled as a `ProjectionType::TopBottomSelector`
-            // query, so the planner only needs to project the single column
-            // argument.
-            "top" | "bottom" => self.expr_to_df_expr(scope, &args[0], schema),
+                // arg1 and arg2 should be integers.
+                let mut eargs = vec![arg0];
+                for arg in &args[1..] {
+                    eargs.push(lit(ScalarValue::Int64(Some(
+                        match self.expr_to_df_expr(scope, arg, schema)? {
+                            Expr::Literal(ScalarValue::Int64(Some(v))) => v,
+                            Expr::Literal(ScalarValue::UInt64(Some(v))) => v as i64,
+                            _ => {
+                                return error::query(format!(
+                                    "{name} expects numbers for the second and third arguments"
+                                ))
+                            }
+                        },
+                    ))));
+                }
 
-            _ => error::query(format!("Invalid function '{name}'")),
-        }
-    }
+                Ok(match name {
+                    "holt_winters" => holt_winters(eargs),
+                    _ => holt_winters_with_fit(eargs),
+                })
+            }
+            "elapsed" => {
+                check_arg_count_range(name, args, 1, 2)?;
+
+                // arg0 should be a column or function
+                let arg0 = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = arg0 {
+                    return Ok(arg0);
+                }
+                let mut eargs = vec![arg0];
+                if args.len() > 1 {
+                    let arg1 = self.expr_to_df_expr(scope, &args[1], schema)?;
+                    eargs.push(arg1);
+                }
+
+                Ok(elapsed(eargs))
+            }
+            name @ ("exponential_moving_average"
+            | "double_exponential_moving_average"
+            | "triple_exponential_moving_average"
+            | "triple_exponential_derivative"
+            | "relative_strength_index"
+            | "chande_momentum_oscillator"
+            | "kaufmans_efficiency_ratio"
+            | "kaufmans_adaptive_moving_average") => {
+                let (max_args, default_warmup) = match name {
+                    "kaufmans_efficiency_ratio" | "kaufmans_adaptive_moving_average" => (3, None),
+                    "chande_momentum_oscillator" => (4, Some("none")),
+                    _ => (4, Some("exponential")),
+                };
+                check_arg_count_range(name, args, 2, max_args)?;
+
+                // arg0 should be a column or function
+                let arg0 = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = arg0 {
+                    return Ok(arg0);
+                }
+
+                // arg1, the period, and the optional arg2, the hold period,
+                // should be integers. The hold period defaults to -1, which
+                // holds values until the algorithm has warmed up.
+                let mut eargs = vec![arg0];
+                for idx in 1..3 {
+                    let v = match args.get(idx) {
+                        Some(arg) => match self.expr_to_df_expr(scope, arg, schema)? {
+                            Expr::Literal(ScalarValue::Int64(Some(v))) => v,
+                            Expr::Literal(ScalarValue::UInt64(Some(v))) => v as i64,
+                            _ => {
+                                return error::query(format!(
+                                    "{name} expects integers for the second and third arguments"
+                                ))
+                            }
+                        },
+                        None => -1,
+                    };
+                    eargs.push(lit(v));
+                }
+
+                // The optional arg3 should be a string specifying the warmup type.
+                if let Some(default_warmup) = default_warmup {
+                    let warmup = match args.get(3) {
+                        Some(arg) => match self.expr_to_df_expr(scope, arg, schema)? {
+                            Expr::Literal(ScalarValue::Utf8(Some(v))) => v,
+                            _ => {
+                                return error::query(format!(
+                                    "{name} expects a string for the fourth argument"
+                                ))
+                            }
+                        },
+                        None => default_warmup.to_owned(),
+                    };
+                    eargs.push(lit(warmup));
+                }
+
+                technical_analysis(name, eargs)
+                    .ok_or_else(|| error::map::internal(format!("unexpected function {name}")))
+            }
+            "cumulative_sum" => {
+                check_arg_count(name, args, 1)?;
+
+                // arg0 should be a column or function
+                let arg0 = self.expr_to_df_expr(scope, &args[0], schema)?;
+                if let Expr::Literal(ScalarValue::Null) = arg0 {
+                    return Ok(arg0);
+                }
+
+                Ok(cumulative_sum(vec![arg0]))
+            }
+            // The TOP/BOTTOM function is handled as a `ProjectionType::TopBottomSelector`
+            // query, so the planner only needs to project the single column
+            // argument.
+            "top" | "bottom" => self.expr_to_df_expr(scope, &args[0], schema),
+
+            _ => error::query(format!("Invalid function '{name}'")),
+        }
+    }
 
     /// Map the InfluxQL scalar function call to a DataFusion scalar function expression.
     fn scalar_math_func_to_df_expr(
@@ -2371,11 +2867,6 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
     }
 
     fn show_tag_keys_to_plan(&self, show_tag_keys: ShowTagKeysStatement) -> Result<LogicalPlan> {
-        if show_tag_keys.database.is_some() {
-            // How do we handle this? Do we need to perform cross-namespace queries here?
-            return error::not_implemented("SHOW TAG KEYS ON <database>");
-        }
-
         let tag_key_col = "tagKey";
         let output_schema = Arc::new(ArrowSchema::new(vec![
             ArrowField::new(
@@ -2544,11 +3035,6 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         &self,
         show_field_keys: ShowFieldKeysStatement,
     ) -> Result<LogicalPlan> {
-        if show_field_keys.database.is_some() {
-            // How do we handle this? Do we need to perform cross-namespace queries here?
-            return error::not_implemented("SHOW FIELD KEYS ON <database>");
-        }
-
         let field_key_col = "fieldKey";
         let output_schema = Arc::new(ArrowSchema::new(vec![
             ArrowField::new(INFLUXQL_MEASUREMENT_COLUMN_NAME, DataType::Utf8, false),
@@ -2624,11 +3110,6 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         &self,
         show_tag_values: ShowTagValuesStatement,
     ) -> Result<LogicalPlan> {
-        if show_tag_values.database.is_some() {
-            // How do we handle this? Do we need to perform cross-namespace queries here?
-            return error::not_implemented("SHOW TAG VALUES ON <database>");
-        }
-
         let key_col = "key";
         let value_col = "value";
         let output_schema = Arc::new(ArrowSchema::new(vec![
@@ -2731,7 +3212,8 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         show_measurements: ShowMeasurementsStatement,
     ) -> Result<LogicalPlan> {
         if show_measurements.on.is_some() {
-            // How do we handle this? Do we need to perform cross-namespace queries here?
+            // `ON <database>` clauses are resolved to a schema before planning, which
+            // leaves `ON *`, as listing measurements across databases is not supported.
             return error::not_implemented("SHOW MEASUREMENTS ON <database>");
         }
 
@@ -2846,6 +3328,371 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         Ok(plan)
     }
 
+    fn show_series_to_plan(&self, show_series: ShowSeriesStatement) -> Result<LogicalPlan> {
+        let key_col = "key";
+        let output_schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new(
+                INFLUXQL_MEASUREMENT_COLUMN_NAME,
+                (&InfluxColumnType::Tag).into(),
+                false,
+            ),
+            ArrowField::new(key_col, DataType::Utf8, false),
+        ]));
+        let dummy_measurement_name = "series";
+
+        let tables = self.expand_show_from_clause(show_series.from)?;
+        let metadata_cutoff = self.metadata_cutoff();
+
+        let mut plans = vec![];
+        for table in tables {
+            let Some(table_schema) = self.s.table_schema(&table) else {
+                continue;
+            };
+            let Some((plan, _measurement_expr)) =
+                self.plan_show_table_scan(&table, &show_series.condition, metadata_cutoff)?
+            else {
+                continue;
+            };
+
+            plans.push(
+                LogicalPlanBuilder::from(plan)
+                    .project([
+                        lit_dict(dummy_measurement_name).alias(INFLUXQL_MEASUREMENT_COLUMN_NAME),
+                        series_key_expr(&table, &table_schema)?.alias(key_col),
+                    ])?
+                    .distinct()?
+                    .build()?,
+            );
+        }
+
+        let plan = LogicalPlanBuilder::from(union_or_empty(plans, &output_schema)?)
+            .sort([
+                INFLUXQL_MEASUREMENT_COLUMN_NAME.as_expr().sort(true, false),
+                key_col.as_expr().sort(true, false),
+            ])?
+            .build()?;
+        let plan = plan_with_metadata(
+            plan,
+            &InfluxQlMetadata {
+                measurement_column_index: MEASUREMENT_COLUMN_INDEX,
+                tag_key_columns: vec![],
+            },
+        )?;
+        let plan = self.limit(
+            plan,
+            show_series.offset,
+            show_series.limit,
+            vec![key_col.as_expr().sort(true, false)],
+            true,
+            &[],
+            &[],
+        )?;
+
+        Ok(plan)
+    }
+
+    /// Plan one of the `SHOW ... CARDINALITY` statements.
+    ///
+    /// The `SERIES` and `MEASUREMENT` statements produce a single count for the
+    /// database, named `cardinality estimation`, unless `EXACT` is specified, in which
+    /// case the series are counted per measurement. The remaining statements
+    /// produce a `count` per measurement.
+    ///
+    /// Estimated series and tag value cardinalities are computed using `APPROX_DISTINCT`.
+    fn show_cardinality_to_plan(
+        &self,
+        show_cardinality: ShowCardinalityStatement,
+    ) -> Result<LogicalPlan> {
+        let ShowCardinalityStatement {
+            kind,
+            exact,
+            database: _,
+            from,
+            with_key,
+            condition,
+            limit,
+            offset,
+        } = show_cardinality;
+
+        let count_col = match kind {
+            CardinalityKind::Series | CardinalityKind::Measurement if !exact => {
+                "cardinality estimation"
+            }
+            _ => "count",
+        };
+        let output_schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new(
+                INFLUXQL_MEASUREMENT_COLUMN_NAME,
+                (&InfluxColumnType::Tag).into(),
+                false,
+            ),
+            ArrowField::new(count_col, DataType::Int64, false),
+        ]));
+
+        // Count the distinct values of an expression, per the `EXACT` keyword.
+        let count_distinct_expr = |expr: Expr| {
+            if exact {
+                count_distinct(expr)
+            } else {
+                cast(approx_distinct(expr), DataType::Int64)
+            }
+        };
+
+        let tables = self.expand_show_from_clause(from)?;
+        let metadata_cutoff = self.metadata_cutoff();
+
+        let plan = match kind {
+            CardinalityKind::Series => {
+                let mut plans = vec![];
+                for table in tables {
+                    let Some(table_schema) = self.s.table_schema(&table) else {
+                        continue;
+                    };
+                    let Some((plan, measurement_expr)) =
+                        self.plan_show_table_scan(&table, &condition, metadata_cutoff)?
+                    else {
+                        continue;
+                    };
+
+                    plans.push(
+                        LogicalPlanBuilder::from(plan)
+                            .aggregate(
+                                [] as [Expr; 0],
+                                [count_distinct_expr(series_key_expr(&table, &table_schema)?)
+                                    .alias(count_col)],
+                            )?
+                            .project(measurement_expr.into_iter().chain([count_col.as_expr()]))?
+                            .build()?,
+                    );
+                }
+
+                let pb = LogicalPlanBuilder::from(union_or_empty(plans, &output_schema)?);
+                if exact {
+                    pb.filter(count_col.as_expr().gt(lit(0_i64)))?.build()?
+                } else {
+                    // sum the estimates of each measurement, as the series keys of
+                    // different measurements are distinct.
+                    pb.aggregate([] as [Expr; 0], [sum(count_col.as_expr()).alias(count_col)])?
+                        .project([
+                            lit_dict("series").alias(INFLUXQL_MEASUREMENT_COLUMN_NAME),
+                            coalesce_zero(count_col.as_expr()).alias(count_col),
+                        ])?
+                        .build()?
+                }
+            }
+            CardinalityKind::Measurement => {
+                let dummy_measurement_name = "measurements";
+                match condition {
+                    Some(condition) => {
+                        let condition = Some(condition);
+                        let mut plans = vec![];
+                        for table in tables {
+                            let Some((plan, measurement_expr)) =
+                                self.plan_show_table_scan(&table, &condition, metadata_cutoff)?
+                            else {
+                                continue;
+                            };
+
+                            plans.push(
+                                LogicalPlanBuilder::from(plan)
+                                    .limit(0, Some(1))?
+                                    .project(measurement_expr)?
+                                    .build()?,
+                            );
+                        }
+
+                        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+                            INFLUXQL_MEASUREMENT_COLUMN_NAME,
+                            (&InfluxColumnType::Tag).into(),
+                            false,
+                        )]));
+                        LogicalPlanBuilder::from(union_or_empty(plans, &schema)?)
+                            .aggregate(
+                                [] as [Expr; 0],
+                                [count(INFLUXQL_MEASUREMENT_COLUMN_NAME.as_expr())
+                                    .alias(count_col)],
+                            )?
+                            .project([
+                                lit_dict(dummy_measurement_name)
+                                    .alias(INFLUXQL_MEASUREMENT_COLUMN_NAME),
+                                count_col.as_expr(),
+                            ])?
+                            .build()?
+                    }
+                    None => {
+                        let n = tables
+                            .iter()
+                            .filter(|table| self.s.table_schema(table).is_some())
+                            .count();
+                        cardinality_mem_table(
+                            "measurement_cardinality",
+                            &output_schema,
+                            [(dummy_measurement_name, n as i64)],
+                        )?
+                    }
+                }
+            }
+            CardinalityKind::TagKey | CardinalityKind::FieldKey => {
+                let column_names = |schema: &Schema| -> Vec<String> {
+                    if kind == CardinalityKind::TagKey {
+                        schema.tags_iter().map(|f| f.name().to_owned()).collect()
+                    } else {
+                        schema.fields_iter().map(|f| f.name().to_owned()).collect()
+                    }
+                };
+
+                match condition {
+                    Some(condition) => {
+                        let condition = Some(condition);
+                        let mut plans = vec![];
+                        for table in tables {
+                            let Some(table_schema) = self.s.table_schema(&table) else {
+                                continue;
+                            };
+                            let columns = column_names(&table_schema);
+                            if columns.is_empty() {
+                                continue;
+                            }
+                            let Some((plan, measurement_expr)) =
+                                self.plan_show_table_scan(&table, &condition, metadata_cutoff)?
+                            else {
+                                continue;
+                            };
+
+                            // Count the columns that have at least one non-null value.
+                            let count_expr = columns
+                                .iter()
+                                .map(|name| {
+                                    when(name.as_str().as_expr().gt(lit(0_u64)), lit(1_i64))
+                                        .otherwise(lit(0_i64))
+                                })
+                                .collect::<Result<Vec<_>>>()?
+                                .into_iter()
+                                .reduce(|acc, expr| acc + expr)
+                                .expect("at least one column");
+
+                            plans.push(
+                                LogicalPlanBuilder::from(plan)
+                                    .aggregate(
+                                        [] as [Expr; 0],
+                                        columns.iter().map(|name| {
+                                            sum(cast(
+                                                name.as_str().as_expr().is_not_null(),
+                                                DataType::UInt64,
+                                            ))
+                                            .alias(name)
+                                        }),
+                                    )?
+                                    .project(
+                                        measurement_expr
+                                            .into_iter()
+                                            .chain([count_expr.alias(count_col)]),
+                                    )?
+                                    .filter(count_col.as_expr().gt(lit(0_i64)))?
+                                    .build()?,
+                            );
+                        }
+
+                        union_or_empty(plans, &output_schema)?
+                    }
+                    None => {
+                        let rows = tables
+                            .iter()
+                            .filter_map(|table| {
+                                let n = column_names(&self.s.table_schema(table)?).len();
+                                (n > 0).then_some((table.as_str(), n as i64))
+                            })
+                            .collect::<Vec<_>>();
+                        let name = if kind == CardinalityKind::TagKey {
+                            "tag_key_cardinality"
+                        } else {
+                            "field_key_cardinality"
+                        };
+                        cardinality_mem_table(name, &output_schema, rows)?
+                    }
+                }
+            }
+            CardinalityKind::TagValues => {
+                let Some(with_key) = with_key else {
+                    return error::internal("expected WITH KEY clause");
+                };
+
+                let mut plans = vec![];
+                for table in tables {
+                    let Some(table_schema) = self.s.table_schema(&table) else {
+                        continue;
+                    };
+
+                    let keys = eval_with_key_clause(
+                        table_schema.tags_iter().map(|field| field.name().as_str()),
+                        &with_key,
+                    )?;
+                    if keys.is_empty() {
+                        // don't bother to create a plan for this table
+                        continue;
+                    }
+
+                    let Some((plan, measurement_expr)) =
+                        self.plan_show_table_scan(&table, &condition, metadata_cutoff)?
+                    else {
+                        continue;
+                    };
+
+                    // The number of distinct key-value pairs is the sum of the
+                    // distinct values of each key.
+                    let count_expr = keys
+                        .iter()
+                        .map(|key| count_distinct_expr(key.as_expr()))
+                        .reduce(|acc, expr| acc + expr)
+                        .expect("at least one key");
+
+                    plans.push(
+                        LogicalPlanBuilder::from(plan)
+                            .aggregate([] as [Expr; 0], [count_expr.alias(count_col)])?
+                            .project(measurement_expr.into_iter().chain([count_col.as_expr()]))?
+                            .filter(count_col.as_expr().gt(lit(0_i64)))?
+                            .build()?,
+                    );
+                }
+
+                union_or_empty(plans, &output_schema)?
+            }
+        };
+
+        let plan = LogicalPlanBuilder::from(plan)
+            .sort([INFLUXQL_MEASUREMENT_COLUMN_NAME.as_expr().sort(true, false)])?
+            .build()?;
+        let plan = plan_with_metadata(
+            plan,
+            &InfluxQlMetadata {
+                measurement_column_index: MEASUREMENT_COLUMN_INDEX,
+                tag_key_columns: vec![],
+            },
+        )?;
+
+        // The LIMIT and OFFSET clauses apply to the measurements.
+        self.limit(plan, offset, limit, vec![], false, &[], &[])
+    }
+
+    /// Returns a plan to scan `table`, filtered by the `condition` of a `SHOW`
+    /// statement, along with the expression to project the measurement name.
+    fn plan_show_table_scan(
+        &self,
+        table: &str,
+        condition: &Option<WhereClause>,
+        cutoff: MetadataCutoff,
+    ) -> Result<Option<(LogicalPlan, Vec<Expr>)>> {
+        let Some((plan, measurement_expr)) = self.create_table_ref(table)? else {
+            return Ok(None);
+        };
+
+        let ds = DataSource::Table(table.to_owned());
+        let schema = IQLSchema::new_from_ds_schema(plan.schema(), ds.schema(self.s)?)?;
+        let plan = self.plan_where_clause(plan, condition, cutoff, &schema)?;
+
+        Ok(Some((plan, measurement_expr)))
+    }
+
     /// A limited implementation of SHOW RETENTION POLICIES that assumes
     /// any database has a single, default, retention policy.
     fn show_retention_policies_to_plan(
@@ -2928,6 +3775,28 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             .unwrap_or_default()
             .influxql_metadata_cutoff
     }
+
+    fn gap_fill_lookback(&self) -> Option<Duration> {
+        self.iox_ctx
+            .inner()
+            .state()
+            .config()
+            .options()
+            .extensions
+            .get::<IoxConfigExt>()
+            .and_then(|ext| ext.gap_fill_lookback.duration())
+    }
+
+    fn sample_seed(&self) -> Option<u64> {
+        self.iox_ctx
+            .inner()
+            .state()
+            .config()
+            .options()
+            .extensions
+            .get::<IoxConfigExt>()
+            .and_then(|ext| ext.influxql_sample_seed.seed())
+    }
 }
 
 /// Returns a [`LogicalPlan`] that performs gap-filling for the `input` plan.
@@ -2937,21 +3806,35 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
 /// * `input` - An aggregate plan which requires gap-filling.
 /// * `time_column` - The `date_bin` expression.
 /// * `fill_strategy` - The strategy used to fill gaps in the data.
+/// * `extend_end_by` - An optional duration, in nanoseconds, to extend the upper bound of the
+///   time range by.
+/// * `lookback` - An optional duration to look back before the start of the time range for
+///   the values that fill the first gaps, when filling with previous or interpolated values.
 fn build_gap_fill_node(
     input: LogicalPlan,
     time_column: &Expr,
     fill_strategy: FillStrategy,
+    extend_end_by: Option<i64>,
+    lookback: Option<Duration>,
 ) -> Result<LogicalPlan> {
     let (expr, alias) = match time_column {
         Expr::Alias(Alias { expr, name: alias }) => (expr.as_ref(), alias),
         _ => return error::internal("expected time column to have an alias function"),
     };
 
-    let date_bin_args = match expr {
+    let (date_bin_args, timezone) = match expr {
         Expr::ScalarFunction(ScalarFunction {
             fun: BuiltinScalarFunction::DateBin,
             args,
-        }) => args,
+        }) => (args.as_slice(), None),
+        Expr::ScalarUDF(expr::ScalarUDF { fun, args })
+            if fun.name == DATE_BIN_WALLCLOCK_UDF_NAME && args.len() == 4 =>
+        {
+            let Expr::Literal(ScalarValue::Utf8(Some(tz))) = &args[3] else {
+                return error::internal("expected DATE_BIN_WALLCLOCK timezone to be a literal");
+            };
+            (&args[..3], Some(parse_timezone(tz)?))
+        }
         _ => {
             // The InfluxQL planner adds the `date_bin` function,
             // so this condition represents an internal failure.
@@ -3000,68 +3883,354 @@ fn build_gap_fill_node(
                     .ok_or_else(|| error::map::internal("expected to find a Filter or TableScan"))
             }?;
 
+            let time_range = match extend_end_by {
+                Some(duration) => Range {
+                    start: time_range.start,
+                    end: match time_range.end {
+                        Bound::Included(end) => Bound::Included(add_duration(end, duration)),
+                        Bound::Excluded(end) => Bound::Excluded(add_duration(end, duration)),
+                        Bound::Unbounded => Bound::Unbounded,
+                    },
+                },
+                None => time_range,
+            };
+
             let origin = (nargs == 3).then_some(date_bin_args[2].clone());
 
-            (date_bin_args[0].clone(), time_range, origin)
+            (date_bin_args[0].clone(), time_range, origin)
+        }
+        nargs => {
+            // This is an internal error as the date_bin function is added by the planner and should
+            // always contain the correct number of arguments.
+            return error::internal(format!("DATE_BIN expects 2 or 3 arguments, got {nargs}"));
+        }
+    };
+
+    let LogicalPlan::Aggregate(aggr) = &input else {
+        return Err(DataFusionError::Internal(format!(
+            "Expected Aggregate plan, got {}",
+            input.display()
+        )));
+    };
+    let mut new_group_expr: Vec<_> = aggr
+        .schema
+        .fields()
+        .iter()
+        .map(|f| Expr::Column(f.qualified_column()))
+        .collect();
+    let aggr_expr = new_group_expr.split_off(aggr.group_expr.len());
+
+    // The fill strategy for InfluxQL is specified at the query level
+    let fill_strategy = aggr_expr
+        .iter()
+        .cloned()
+        .map(|e| (e, fill_strategy.clone()))
+        .collect();
+
+    let time_column = col(input
+        .schema()
+        .field_with_unqualified_name(alias)
+        .map(|f| f.qualified_column())?);
+
+    let params = GapFillParams {
+        stride,
+        time_column,
+        origin,
+        timezone,
+        time_range,
+        fill_strategy,
+    };
+
+    let input = match lookback {
+        Some(lookback) => with_lookback(&input, &params, lookback)?.unwrap_or(input),
+        None => input,
+    };
+
+    Ok(LogicalPlan::Extension(Extension {
+        node: Arc::new(GapFill::try_new(
+            Arc::new(input),
+            new_group_expr,
+            aggr_expr,
+            params,
+        )?),
+    }))
+}
+
+/// Plan a window function over each series to calculate the area of each `INTEGRAL`
+/// aggregate in `aggr_exprs` for every `GROUP BY TIME` window. Returns the new input
+/// and, for each `INTEGRAL` aggregate, the aggregate expression that selects its area.
+///
+/// As with InfluxDB 1.x, the area of a window is interpolated up to the window boundaries
+/// using the neighbouring points of the series, which are outside the aggregate group.
+fn plan_integral_windows(
+    ctx: &Context<'_>,
+    input: LogicalPlan,
+    aggr_exprs: &[Expr],
+    group_by_tag_set: &[&str],
+) -> Result<(LogicalPlan, Vec<(Expr, Expr)>)> {
+    let Some(interval) = ctx.interval else {
+        return Ok((input, vec![]));
+    };
+
+    let mut window_exprs = vec![];
+    let mut integrals = vec![];
+    for expr in aggr_exprs {
+        let Expr::AggregateUDF(expr::AggregateUDF { fun, args, .. }) = expr else {
+            continue;
+        };
+        if fun.name != INTEGRAL.name {
+            continue;
+        }
+
+        let [value, unit, time] = args.as_slice() else {
+            return error::internal(format!(
+                "INTEGRAL expects three arguments, got {}",
+                args.len()
+            ));
+        };
+        let window_expr = Expr::WindowFunction(WindowFunction::new(
+            crate::window::INTEGRAL.clone(),
+            vec![
+                value.clone(),
+                unit.clone(),
+                time.clone(),
+                date_bin_time(interval, ctx.tz),
+                lit(ScalarValue::new_interval_mdn(0, 0, interval.duration)),
+            ],
+            fields_to_exprs_no_nulls(input.schema(), group_by_tag_set).collect(),
+            vec![time.clone().sort(true, false)],
+            WindowFrame {
+                units: WindowFrameUnits::Rows,
+                start_bound: WindowFrameBound::Preceding(ScalarValue::Null),
+                end_bound: WindowFrameBound::Following(ScalarValue::Null),
+            },
+        ));
+        let name = normalize_col(expr.clone(), &input)?.display_name()?;
+        let aggr_expr = Expr::AggregateFunction(expr::AggregateFunction::new(
+            AggregateFunction::Max,
+            vec![Expr::Column(Column::from_name(name.clone()))],
+            false,
+            None,
+            None,
+        ));
+
+        window_exprs.push(window_expr.alias(name));
+        integrals.push((expr.clone(), aggr_expr));
+    }
+
+    if window_exprs.is_empty() {
+        return Ok((input, integrals));
+    }
+    let plan = LogicalPlanBuilder::from(input)
+        .window(window_exprs)?
+        .build()?;
+    Ok((plan, integrals))
+}
+
+/// Returns an expression that adds `duration` nanoseconds to the timestamp expression `expr`.
+fn add_duration(expr: Expr, duration: i64) -> Expr {
+    match expr {
+        Expr::Literal(ScalarValue::TimestampNanosecond(Some(ts), tz)) => {
+            lit(ScalarValue::TimestampNanosecond(Some(ts + duration), tz))
         }
-        nargs => {
-            // This is an internal error as the date_bin function is added by the planner and should
-            // always contain the correct number of arguments.
-            return error::internal(format!("DATE_BIN expects 2 or 3 arguments, got {nargs}"));
+        expr => expr + lit(ScalarValue::new_interval_mdn(0, 0, duration)),
+    }
+}
+
+/// Returns the largest number of values forecast by the `HOLT_WINTERS` or
+/// `HOLT_WINTERS_WITH_FIT` functions in `exprs`, or `0` if there are none.
+fn max_holt_winters_forecast(exprs: &[Expr]) -> i64 {
+    find_window_udfs(exprs)
+        .iter()
+        .filter_map(|e| match e {
+            Expr::ScalarUDF(expr::ScalarUDF { fun, args })
+                if matches!(
+                    udf::WindowFunction::try_from_scalar_udf(Arc::clone(fun)),
+                    Some(
+                        udf::WindowFunction::HoltWinters | udf::WindowFunction::HoltWintersWithFit
+                    )
+                ) =>
+            {
+                match args.get(1) {
+                    Some(Expr::Literal(ScalarValue::Int64(Some(n)))) => Some(*n),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// Returns a predicate which restricts the 1-based, sequential `expr` to the window
+/// described by the `limit` and `offset`, one of which must be specified.
+fn limit_offset_filter(expr: Expr, limit: Option<i64>, offset: Option<i64>) -> Expr {
+    match (limit, offset) {
+        // WHERE expr BETWEEN OFFSET + 1 AND OFFSET + LIMIT
+        (Some(limit), Some(offset)) => {
+            let low = offset + 1;
+            let high = offset + limit;
+
+            Expr::Between(Between {
+                expr: Box::new(expr),
+                negated: false,
+                low: Box::new(lit(low)),
+                high: Box::new(lit(high)),
+            })
         }
-    };
 
-    let LogicalPlan::Aggregate(aggr) = &input else {
-        return Err(DataFusionError::Internal(format!(
-            "Expected Aggregate plan, got {}",
-            input.display()
-        )));
+        // WHERE expr <= LIMIT
+        (Some(limit), None) => expr.lt_eq(lit(limit)),
+
+        // WHERE expr > OFFSET
+        (None, Some(offset)) => expr.gt(lit(offset)),
+        (None, None) => unreachable!("limit and offset cannot not be None"),
+    }
+}
+
+/// Returns the union of `plans`, or an empty relation with the specified
+/// `schema` if there are no plans.
+fn union_or_empty(plans: Vec<LogicalPlan>, schema: &Arc<ArrowSchema>) -> Result<LogicalPlan> {
+    let mut plans = plans.into_iter();
+    let Some(first) = plans.next() else {
+        return Ok(LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row: false,
+            schema: Arc::clone(schema).to_dfschema_ref()?,
+        }));
     };
-    let mut new_group_expr: Vec<_> = aggr
-        .schema
-        .fields()
-        .iter()
-        .map(|f| Expr::Column(f.qualified_column()))
-        .collect();
-    let aggr_expr = new_group_expr.split_off(aggr.group_expr.len());
 
-    // The fill strategy for InfluxQL is specified at the query level
-    let fill_strategy = aggr_expr
-        .iter()
-        .cloned()
-        .map(|e| (e, fill_strategy.clone()))
-        .collect();
+    plans.try_fold(first, |union_plan, plan| {
+        LogicalPlanBuilder::from(union_plan).union(plan)?.build()
+    })
+}
 
-    let time_column = col(input
-        .schema()
-        .field_with_unqualified_name(alias)
-        .map(|f| f.qualified_column())?);
+/// Returns a plan that scans the measurement names and counts of `rows`,
+/// as produced by the `SHOW ... CARDINALITY` statements.
+fn cardinality_mem_table<'a>(
+    name: &str,
+    schema: &Arc<ArrowSchema>,
+    rows: impl IntoIterator<Item = (&'a str, i64)>,
+) -> Result<LogicalPlan> {
+    let mut measurement_names_builder = StringDictionaryBuilder::<Int32Type>::new();
+    let mut count_builder = Int64Builder::new();
+    for (measurement_name, count) in rows {
+        measurement_names_builder.append_value(measurement_name);
+        count_builder.append_value(count);
+    }
 
-    Ok(LogicalPlan::Extension(Extension {
-        node: Arc::new(GapFill::try_new(
-            Arc::new(input),
-            new_group_expr,
-            aggr_expr,
-            GapFillParams {
-                stride,
-                time_column,
-                origin,
-                time_range,
-                fill_strategy,
-            },
-        )?),
+    LogicalPlanBuilder::scan(
+        name,
+        provider_as_source(Arc::new(MemTable::try_new(
+            Arc::clone(schema),
+            vec![vec![RecordBatch::try_new(
+                Arc::clone(schema),
+                vec![
+                    Arc::new(measurement_names_builder.finish()),
+                    Arc::new(count_builder.finish()),
+                ],
+            )?]],
+        )?)),
+        None,
+    )?
+    .build()
+}
+
+/// Returns an expression that evaluates to the series key of each row of `table`,
+/// such as `cpu,host=server01,region=west`. Tags with a `NULL` value are omitted.
+///
+/// The measurement, tag keys and tag values are escaped as they are in line protocol.
+fn series_key_expr(table: &str, schema: &Schema) -> Result<Expr> {
+    let args = iter::once(Ok(lit(escape_line_protocol(
+        table,
+        MEASUREMENT_ESCAPED_CHARS,
+    ))))
+    .chain(
+        schema
+            .tags_iter()
+            .map(|f| f.name().as_str())
+            .sorted()
+            .map(|tag| {
+                let tag_col = tag.as_expr();
+                when(
+                    tag_col.clone().is_not_null(),
+                    Expr::ScalarFunction(ScalarFunction {
+                        fun: BuiltinScalarFunction::Concat,
+                        args: vec![
+                            lit(format!(
+                                ",{}=",
+                                escape_line_protocol(tag, TAG_ESCAPED_CHARS)
+                            )),
+                            escape_tag_value_expr(cast(tag_col, DataType::Utf8)),
+                        ],
+                    }),
+                )
+                .end()
+            }),
+    )
+    .collect::<Result<Vec<_>>>()?;
+
+    // `concat` ignores `NULL` arguments
+    Ok(Expr::ScalarFunction(ScalarFunction {
+        fun: BuiltinScalarFunction::Concat,
+        args,
     }))
 }
 
+/// The characters that are escaped with a backslash in line protocol measurement names.
+const MEASUREMENT_ESCAPED_CHARS: &[char] = &[',', ' '];
+
+/// The characters that are escaped with a backslash in line protocol tag keys and values.
+const TAG_ESCAPED_CHARS: &[char] = &[',', '=', ' '];
+
+/// Escapes each of the `chars` in `s` with a backslash, as they are in line protocol.
+fn escape_line_protocol(s: &str, chars: &[char]) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if chars.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Returns an expression that escapes the tag value `expr`, as it is in line protocol.
+fn escape_tag_value_expr(expr: Expr) -> Expr {
+    TAG_ESCAPED_CHARS.iter().fold(expr, |expr, c| {
+        Expr::ScalarFunction(ScalarFunction {
+            fun: BuiltinScalarFunction::Replace,
+            args: vec![expr, lit(c.to_string()), lit(format!("\\{c}"))],
+        })
+    })
+}
+
+/// Returns an expression that evaluates to `expr`, or `0` if `expr` is `NULL`.
+fn coalesce_zero(expr: Expr) -> Expr {
+    Expr::ScalarFunction(ScalarFunction {
+        fun: BuiltinScalarFunction::Coalesce,
+        args: vec![expr, lit(0_i64)],
+    })
+}
+
 /// Adds [`InfluxQlMetadata`] to the `plan`.
 fn plan_with_metadata(plan: LogicalPlan, metadata: &InfluxQlMetadata) -> Result<LogicalPlan> {
-    fn make_schema(schema: DFSchemaRef, metadata: &InfluxQlMetadata) -> Result<DFSchemaRef> {
-        let data = serde_json::to_string(metadata).map_err(|err| {
-            error::map::internal(format!("error serializing InfluxQL metadata: {err}"))
-        })?;
+    let data = serde_json::to_string(metadata).map_err(|err| {
+        error::map::internal(format!("error serializing InfluxQL metadata: {err}"))
+    })?;
+
+    plan_with_schema_metadata(plan, INFLUXQL_METADATA_KEY, data)
+}
 
+/// Adds the target of a `SELECT ... INTO` statement to the `plan`.
+fn plan_with_into_target(plan: LogicalPlan, into: &IntoTarget) -> Result<LogicalPlan> {
+    plan_with_schema_metadata(plan, INFLUXQL_INTO_METADATA_KEY, into.to_metadata_value())
+}
+
+/// Adds the schema-level metadata `key` with the value `data` to the `plan`.
+fn plan_with_schema_metadata(plan: LogicalPlan, key: &str, data: String) -> Result<LogicalPlan> {
+    fn make_schema(schema: DFSchemaRef, key: &str, data: &str) -> Result<DFSchemaRef> {
         let mut md = schema.metadata().clone();
-        md.insert(INFLUXQL_METADATA_KEY.to_owned(), data);
+        md.insert(key.to_owned(), data.to_owned());
 
         Ok(Arc::new(DFSchema::new_with_metadata(
             schema.fields().clone(),
@@ -3070,103 +4239,103 @@ fn plan_with_metadata(plan: LogicalPlan, metadata: &InfluxQlMetadata) -> Result<
     }
 
     // Reconstruct the plan, altering the first node which defines the output schema
-    fn set_schema(input: &LogicalPlan, metadata: &InfluxQlMetadata) -> Result<LogicalPlan> {
+    fn set_schema(input: &LogicalPlan, key: &str, data: &str) -> Result<LogicalPlan> {
         Ok(match input {
             LogicalPlan::Projection(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Projection(v)
             }
             LogicalPlan::Filter(src) => {
                 let mut v = src.clone();
-                v.input = Arc::new(set_schema(&src.input, metadata)?);
+                v.input = Arc::new(set_schema(&src.input, key, data)?);
                 LogicalPlan::Filter(v)
             }
             LogicalPlan::Window(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Window(v)
             }
             LogicalPlan::Aggregate(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Aggregate(v)
             }
             LogicalPlan::Sort(src) => {
                 let mut v = src.clone();
-                v.input = Arc::new(set_schema(&src.input, metadata)?);
+                v.input = Arc::new(set_schema(&src.input, key, data)?);
                 LogicalPlan::Sort(v)
             }
             LogicalPlan::Join(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Join(v)
             }
             LogicalPlan::CrossJoin(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::CrossJoin(v)
             }
             LogicalPlan::Repartition(src) => {
                 let mut v = src.clone();
-                v.input = Arc::new(set_schema(&src.input, metadata)?);
+                v.input = Arc::new(set_schema(&src.input, key, data)?);
                 LogicalPlan::Repartition(v)
             }
             LogicalPlan::Union(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Union(v)
             }
             LogicalPlan::EmptyRelation(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::EmptyRelation(v)
             }
             LogicalPlan::SubqueryAlias(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::SubqueryAlias(v)
             }
             LogicalPlan::Limit(src) => {
                 let mut v = src.clone();
-                v.input = Arc::new(set_schema(&src.input, metadata)?);
+                v.input = Arc::new(set_schema(&src.input, key, data)?);
                 LogicalPlan::Limit(v)
             }
             LogicalPlan::Values(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Values(v)
             }
             LogicalPlan::Explain(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Explain(v)
             }
             LogicalPlan::Analyze(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Analyze(v)
             }
             LogicalPlan::Distinct(src) => {
                 let mut v = src.clone();
-                v.input = Arc::new(set_schema(&src.input, metadata)?);
+                v.input = Arc::new(set_schema(&src.input, key, data)?);
                 LogicalPlan::Distinct(v)
             }
             LogicalPlan::Unnest(src) => {
                 let mut v = src.clone();
-                v.schema = make_schema(Arc::clone(&src.schema), metadata)?;
+                v.schema = make_schema(Arc::clone(&src.schema), key, data)?;
                 LogicalPlan::Unnest(v)
             }
             LogicalPlan::TableScan(src) => {
                 let mut t = src.clone();
-                t.projected_schema = make_schema(Arc::clone(&src.projected_schema), metadata)?;
+                t.projected_schema = make_schema(Arc::clone(&src.projected_schema), key, data)?;
                 LogicalPlan::TableScan(t)
             }
             _ => return error::internal(format!("unexpected LogicalPlan: {}", input.display())),
         })
     }
 
-    set_schema(&plan, metadata)
+    set_schema(&plan, key, &data)
 }
 
 /// A utility function that checks whether `f` is an
@@ -3303,18 +4472,31 @@ fn window_partition_by(
 ) -> Vec<Expr> {
     let mut parition_by = fields_to_exprs_no_nulls(schema, group_by_tags).collect::<Vec<_>>();
     if let Some(i) = ctx.interval {
-        let stride = lit(ScalarValue::new_interval_mdn(0, 0, i.duration));
-        let offset = i.offset.unwrap_or_default();
-
-        parition_by.push(date_bin(
-            stride,
-            "time".as_expr(),
-            lit(ScalarValue::TimestampNanosecond(Some(offset), None)),
-        ));
+        parition_by.push(date_bin_time(i, ctx.tz));
     }
     parition_by
 }
 
+/// Returns an expression that bins the `time` column by the `GROUP BY TIME` interval.
+///
+/// If the query specifies a timezone with the `TZ` clause, time is binned on the wall
+/// clock of that timezone, so that the offset of the interval is relative to midnight
+/// in that timezone, and daily intervals follow changes to daylight saving time.
+/// Intervals shorter than a day are binned in UTC, so the hour repeated when daylight
+/// saving time ends is two hourly intervals.
+fn date_bin_time(interval: Interval, tz: Option<Tz>) -> Expr {
+    let stride = lit(ScalarValue::new_interval_mdn(0, 0, interval.duration));
+    let origin = lit(ScalarValue::TimestampNanosecond(
+        Some(interval.offset.unwrap_or_default()),
+        None,
+    ));
+
+    match tz {
+        Some(tz) => date_bin_wallclock(stride, "time".as_expr(), origin, tz),
+        None => date_bin(stride, "time".as_expr(), origin),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -3455,6 +4637,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_escape_line_protocol() {
+        assert_eq!(
+            escape_line_protocol("my cpu,eu", MEASUREMENT_ESCAPED_CHARS),
+            r"my\ cpu\,eu"
+        );
+        assert_eq!(
+            escape_line_protocol("a=b, c", TAG_ESCAPED_CHARS),
+            r"a\=b\,\ c"
+        );
+        assert_eq!(escape_line_protocol("cpu", TAG_ESCAPED_CHARS), "cpu");
+    }
+
     /// Verify the list of unsupported statements.
     ///
     /// It is expected certain statements will be unsupported, indefinitely.
@@ -3464,6 +4659,9 @@ mod test {
         assert_snapshot!(plan("DELETE FROM foo"), @"This feature is not implemented: DELETE");
         assert_snapshot!(plan("DROP MEASUREMENT foo"), @"This feature is not implemented: DROP MEASUREMENT");
         assert_snapshot!(plan("SHOW DATABASES"), @"This feature is not implemented: SHOW DATABASES");
+        assert_snapshot!(plan("CREATE CONTINUOUS QUERY cq ON foo BEGIN SELECT mean(usage_idle) INTO bar FROM cpu GROUP BY TIME(1h) END"), @"This feature is not implemented: CREATE CONTINUOUS QUERY");
+        assert_snapshot!(plan("DROP CONTINUOUS QUERY cq ON foo"), @"This feature is not implemented: DROP CONTINUOUS QUERY");
+        assert_snapshot!(plan("SHOW CONTINUOUS QUERIES"), @"This feature is not implemented: SHOW CONTINUOUS QUERIES");
     }
 
     mod metadata_queries {
@@ -3823,6 +5021,38 @@ mod test {
             "###);
         }
 
+        #[test]
+        fn test_show_series() {
+            assert_snapshot!(plan("SHOW SERIES FROM name_clash"), @r###"
+            Sort: iox::measurement ASC NULLS LAST, key ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), key:Utf8;N]
+              Distinct: [iox::measurement:Dictionary(Int32, Utf8), key:Utf8;N]
+                Projection: Dictionary(Int32, Utf8("series")) AS iox::measurement, concat(Utf8("name_clash"), CASE WHEN name_clash.first IS NOT NULL THEN concat(Utf8(",first="), replace(replace(replace(CAST(name_clash.first AS Utf8), Utf8(","), Utf8("\,")), Utf8("="), Utf8("\=")), Utf8(" "), Utf8("\ "))) END) AS key [iox::measurement:Dictionary(Int32, Utf8), key:Utf8;N]
+                  Filter: name_clash.time >= TimestampNanosecond(1672444800000000000, None) [f:Float64;N, first:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None)]
+                    TableScan: name_clash [f:Float64;N, first:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None)]
+            "###);
+        }
+
+        #[test]
+        fn test_show_cardinality() {
+            assert_snapshot!(plan("SHOW MEASUREMENT EXACT CARDINALITY"), @r###"
+            Sort: measurement_cardinality.iox::measurement ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+              TableScan: measurement_cardinality [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+            "###);
+            assert_snapshot!(plan("SHOW MEASUREMENT CARDINALITY"), @r###"
+            Sort: measurement_cardinality.iox::measurement ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), cardinality estimation:Int64]
+              TableScan: measurement_cardinality [iox::measurement:Dictionary(Int32, Utf8), cardinality estimation:Int64]
+            "###);
+            assert_snapshot!(plan("SHOW TAG KEY CARDINALITY"), @r###"
+            Sort: tag_key_cardinality.iox::measurement ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+              TableScan: tag_key_cardinality [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+            "###);
+            assert_snapshot!(plan("SHOW FIELD KEY EXACT CARDINALITY LIMIT 1 OFFSET 2"), @r###"
+            Limit: skip=2, fetch=1 [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+              Sort: field_key_cardinality.iox::measurement ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+                TableScan: field_key_cardinality [iox::measurement:Dictionary(Int32, Utf8), count:Int64]
+            "###);
+        }
+
         #[test]
         fn test_show_retention_policies() {
             assert_snapshot!(plan("SHOW RETENTION POLICIES"), @r###"
@@ -4086,6 +5316,38 @@ mod test {
                 assert_snapshot!(plan("SELECT MOVING_AVERAGE(MEAN(usage_idle), usage_system) FROM cpu GROUP BY TIME(10s)"), @"Error during planning: expected integer argument in moving_average()");
             }
 
+            #[test]
+            fn test_holt_winters() {
+                // The gap-filled time range is extended by N intervals to make room for the forecast
+                assert_snapshot!(plan("SELECT HOLT_WINTERS(MEAN(usage_idle), 10, 4) FROM cpu GROUP BY TIME(10s)"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, holt_winters:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, holt_winters [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, holt_winters:Float64;N]
+                    Filter: NOT holt_winters IS NULL [time:Timestamp(Nanosecond, None);N, holt_winters:Float64;N]
+                      Projection: time, holt_winters(AVG(cpu.usage_idle),Int64(10),Int64(4)) AS holt_winters [time:Timestamp(Nanosecond, None);N, holt_winters:Float64;N]
+                        WindowAggr: windowExpr=[[holt_winters(AVG(cpu.usage_idle), Int64(10), Int64(4), IntervalMonthDayNano("10000000000"), time) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS holt_winters(AVG(cpu.usage_idle),Int64(10),Int64(4))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, holt_winters(AVG(cpu.usage_idle),Int64(10),Int64(4)):Float64;N]
+                          GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531300000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                            Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                              Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                                TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+
+                // FILL(none) still requires gap-filling
+                assert_snapshot!(plan("SELECT HOLT_WINTERS_WITH_FIT(MEAN(usage_idle), 5, 4) FROM cpu GROUP BY TIME(10s) FILL(none)"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, holt_winters_with_fit:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, holt_winters_with_fit [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, holt_winters_with_fit:Float64;N]
+                    Filter: NOT holt_winters_with_fit IS NULL [time:Timestamp(Nanosecond, None);N, holt_winters_with_fit:Float64;N]
+                      Projection: time, holt_winters_with_fit(AVG(cpu.usage_idle),Int64(5),Int64(4)) AS holt_winters_with_fit [time:Timestamp(Nanosecond, None);N, holt_winters_with_fit:Float64;N]
+                        WindowAggr: windowExpr=[[holt_winters_with_fit(AVG(cpu.usage_idle), Int64(5), Int64(4), IntervalMonthDayNano("10000000000"), time) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS holt_winters_with_fit(AVG(cpu.usage_idle),Int64(5),Int64(4))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, holt_winters_with_fit(AVG(cpu.usage_idle),Int64(5),Int64(4)):Float64;N]
+                          GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531250000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                            Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                              Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                                TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+
+                // Invariant: second and third arguments are always constants
+                assert_snapshot!(plan("SELECT HOLT_WINTERS(MEAN(usage_idle), usage_system, 4) FROM cpu GROUP BY TIME(10s)"), @"Error during planning: expected integer argument in holt_winters()");
+            }
+
             #[test]
             fn test_derivative() {
                 // no aggregates
@@ -4159,7 +5421,7 @@ mod test {
                   Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, cumulative_sum [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cumulative_sum:Float64;N]
                     Filter: NOT cumulative_sum IS NULL [time:Timestamp(Nanosecond, None), cumulative_sum:Float64;N]
                       Projection: cpu.time AS time, cumulative_sum(cpu.usage_idle) AS cumulative_sum [time:Timestamp(Nanosecond, None), cumulative_sum:Float64;N]
-                        WindowAggr: windowExpr=[[cumumlative_sum(cpu.usage_idle) ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS cumulative_sum(cpu.usage_idle)]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, cumulative_sum(cpu.usage_idle):Float64;N]
+                        WindowAggr: windowExpr=[[cumulative_sum(cpu.usage_idle) ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS cumulative_sum(cpu.usage_idle)]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, cumulative_sum(cpu.usage_idle):Float64;N]
                           TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
                 "###);
 
@@ -4169,17 +5431,93 @@ mod test {
                   Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, cumulative_sum [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, cumulative_sum:Float64;N]
                     Filter: NOT cumulative_sum IS NULL [time:Timestamp(Nanosecond, None);N, cumulative_sum:Float64;N]
                       Projection: time, cumulative_sum(AVG(cpu.usage_idle)) AS cumulative_sum [time:Timestamp(Nanosecond, None);N, cumulative_sum:Float64;N]
-                        WindowAggr: windowExpr=[[cumumlative_sum(AVG(cpu.usage_idle)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS cumulative_sum(AVG(cpu.usage_idle))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, cumulative_sum(AVG(cpu.usage_idle)):Float64;N]
+                        WindowAggr: windowExpr=[[cumulative_sum(AVG(cpu.usage_idle)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS cumulative_sum(AVG(cpu.usage_idle))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, cumulative_sum(AVG(cpu.usage_idle)):Float64;N]
+                          GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531200000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                            Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                              Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                                TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+            }
+
+            #[test]
+            fn test_elapsed() {
+                // no aggregates
+                assert_snapshot!(plan("SELECT ELAPSED(usage_idle) FROM cpu"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), elapsed:Int64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, elapsed [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), elapsed:Int64;N]
+                    Filter: NOT elapsed IS NULL [time:Timestamp(Nanosecond, None), elapsed:Int64;N]
+                      Projection: cpu.time AS time, elapsed(cpu.usage_idle) AS elapsed [time:Timestamp(Nanosecond, None), elapsed:Int64;N]
+                        WindowAggr: windowExpr=[[elapsed(cpu.usage_idle, IntervalMonthDayNano("1"), cpu.time) ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS elapsed(cpu.usage_idle)]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, elapsed(cpu.usage_idle):Int64;N]
+                          TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+
+                // aggregate
+                assert_snapshot!(plan("SELECT ELAPSED(MEAN(usage_idle), 1s) FROM cpu GROUP BY TIME(10s)"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, elapsed:Int64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, elapsed [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, elapsed:Int64;N]
+                    Filter: NOT elapsed IS NULL [time:Timestamp(Nanosecond, None);N, elapsed:Int64;N]
+                      Projection: time, elapsed(AVG(cpu.usage_idle),IntervalMonthDayNano("1000000000")) AS elapsed [time:Timestamp(Nanosecond, None);N, elapsed:Int64;N]
+                        WindowAggr: windowExpr=[[elapsed(AVG(cpu.usage_idle), IntervalMonthDayNano("1000000000"), time) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS elapsed(AVG(cpu.usage_idle),IntervalMonthDayNano("1000000000"))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, elapsed(AVG(cpu.usage_idle),IntervalMonthDayNano("1000000000")):Int64;N]
+                          GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531200000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                            Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                              Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                                TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+            }
+
+            #[test]
+            fn test_technical_analysis() {
+                // no aggregates, with default hold period and warmup type
+                assert_snapshot!(plan("SELECT EXPONENTIAL_MOVING_AVERAGE(usage_idle, 2) FROM cpu"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), exponential_moving_average:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, exponential_moving_average [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), exponential_moving_average:Float64;N]
+                    Filter: NOT exponential_moving_average IS NULL [time:Timestamp(Nanosecond, None), exponential_moving_average:Float64;N]
+                      Projection: cpu.time AS time, exponential_moving_average(cpu.usage_idle,Int64(2),Int64(-1),Utf8("exponential")) AS exponential_moving_average [time:Timestamp(Nanosecond, None), exponential_moving_average:Float64;N]
+                        WindowAggr: windowExpr=[[exponential_moving_average(cpu.usage_idle, Int64(2), Int64(-1), Utf8("exponential")) ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS exponential_moving_average(cpu.usage_idle,Int64(2),Int64(-1),Utf8("exponential"))]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, exponential_moving_average(cpu.usage_idle,Int64(2),Int64(-1),Utf8("exponential")):Float64;N]
+                          TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+
+                // aggregate, with explicit hold period
+                assert_snapshot!(plan("SELECT KAUFMANS_EFFICIENCY_RATIO(MEAN(usage_idle), 3, 5) FROM cpu GROUP BY TIME(10s)"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, kaufmans_efficiency_ratio:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, kaufmans_efficiency_ratio [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, kaufmans_efficiency_ratio:Float64;N]
+                    Filter: NOT kaufmans_efficiency_ratio IS NULL [time:Timestamp(Nanosecond, None);N, kaufmans_efficiency_ratio:Float64;N]
+                      Projection: time, kaufmans_efficiency_ratio(AVG(cpu.usage_idle),Int64(3),Int64(5)) AS kaufmans_efficiency_ratio [time:Timestamp(Nanosecond, None);N, kaufmans_efficiency_ratio:Float64;N]
+                        WindowAggr: windowExpr=[[kaufmans_efficiency_ratio(AVG(cpu.usage_idle), Int64(3), Int64(5)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS kaufmans_efficiency_ratio(AVG(cpu.usage_idle),Int64(3),Int64(5))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, kaufmans_efficiency_ratio(AVG(cpu.usage_idle),Int64(3),Int64(5)):Float64;N]
                           GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531200000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
                             Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
                               Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
                                 TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
                 "###);
+
+                // invalid arguments
+                assert_snapshot!(plan("SELECT CHANDE_MOMENTUM_OSCILLATOR(usage_idle, 2, 1, 'simple', 4) FROM cpu"), @"Error during planning: invalid number of arguments for chande_momentum_oscillator, expected at least 2 but no more than 4 arguments, got 5");
             }
 
             #[test]
-            fn test_not_implemented() {
-                assert_snapshot!(plan("SELECT DIFFERENCE(MEAN(usage_idle)), MEAN(usage_idle) FROM cpu GROUP BY TIME(10s)"), @"This feature is not implemented: mixed window-aggregate and aggregate columns, such as DIFFERENCE(MEAN(col)), MEAN(col)");
+            fn test_window_aggregate_mixed() {
+                assert_snapshot!(plan("SELECT DIFFERENCE(MEAN(usage_idle)), MEAN(usage_idle) FROM cpu GROUP BY TIME(10s)"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, difference:Float64;N, mean:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, difference, mean [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, difference:Float64;N, mean:Float64;N]
+                    Projection: time, difference(AVG(cpu.usage_idle)) AS difference, AVG(cpu.usage_idle) AS mean [time:Timestamp(Nanosecond, None);N, difference:Float64;N, mean:Float64;N]
+                      WindowAggr: windowExpr=[[difference(AVG(cpu.usage_idle)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS difference(AVG(cpu.usage_idle))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, difference(AVG(cpu.usage_idle)):Float64;N]
+                        GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531200000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                          Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                            Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                              TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+
+                // multiple window functions over the same aggregate
+                assert_snapshot!(plan("SELECT MEAN(usage_idle), DERIVATIVE(MEAN(usage_idle)), DIFFERENCE(MEAN(usage_idle)) FROM cpu GROUP BY TIME(10s)"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, mean:Float64;N, derivative:Float64;N, difference:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, mean, derivative, difference [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, mean:Float64;N, derivative:Float64;N, difference:Float64;N]
+                    Projection: time, AVG(cpu.usage_idle) AS mean, derivative(AVG(cpu.usage_idle)) AS derivative, difference(AVG(cpu.usage_idle)) AS difference [time:Timestamp(Nanosecond, None);N, mean:Float64;N, derivative:Float64;N, difference:Float64;N]
+                      WindowAggr: windowExpr=[[derivative(AVG(cpu.usage_idle), IntervalMonthDayNano("10000000000"), time) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS derivative(AVG(cpu.usage_idle)), difference(AVG(cpu.usage_idle)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS difference(AVG(cpu.usage_idle))]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N, derivative(AVG(cpu.usage_idle)):Float64;N, difference(AVG(cpu.usage_idle)):Float64;N]
+                        GapFill: groupBy=[time], aggr=[[AVG(cpu.usage_idle)]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Unbounded..Included(Literal(TimestampNanosecond(1672531200000000000, None))) [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                          Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[AVG(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, AVG(cpu.usage_idle):Float64;N]
+                            Filter: cpu.time <= TimestampNanosecond(1672531200000000000, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                              TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
             }
         }
 
@@ -4296,6 +5634,16 @@ mod test {
                 "###);
             }
             #[test]
+            fn test_multiple_selectors_additional_fields() {
+                // the additional fields are taken from the row selected by the first selector
+                assert_snapshot!(plan("SELECT LAST(usage_idle), FIRST(usage_idle), usage_system FROM cpu"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), last:Float64;N, first:Float64;N, usage_system:Float64;N]
+                  Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, (selector_last(cpu.usage_idle,cpu.time,cpu.usage_system))[value] AS last, (selector_first(cpu.usage_idle,cpu.time))[value] AS first, (selector_last(cpu.usage_idle,cpu.time,cpu.usage_system))[other_1] AS usage_system [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), last:Float64;N, first:Float64;N, usage_system:Float64;N]
+                    Aggregate: groupBy=[[]], aggr=[[selector_last(cpu.usage_idle, cpu.time, cpu.usage_system), selector_first(cpu.usage_idle, cpu.time)]] [selector_last(cpu.usage_idle,cpu.time,cpu.usage_system):Struct([Field { name: "value", data_type: Float64, nullable: true, dict_id: 0, dict_is_ordered: false, metadata: {} }, Field { name: "time", data_type: Timestamp(Nanosecond, None), nullable: true, dict_id: 0, dict_is_ordered: false, metadata: {} }, Field { name: "other_1", data_type: Float64, nullable: true, dict_id: 0, dict_is_ordered: false, metadata: {} }]);N, selector_first(cpu.usage_idle,cpu.time):Struct([Field { name: "value", data_type: Float64, nullable: true, dict_id: 0, dict_is_ordered: false, metadata: {} }, Field { name: "time", data_type: Timestamp(Nanosecond, None), nullable: true, dict_id: 0, dict_is_ordered: false, metadata: {} }]);N]
+                      TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                "###);
+            }
+            #[test]
             fn test_selectors_additional_fields_2() {
                 assert_snapshot!(plan("SELECT LAST(usage_idle), usage_system FROM cpu GROUP BY TIME(5s)"), @r###"
                 Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, last:Float64;N, usage_system:Float64;N]
@@ -4362,6 +5710,27 @@ mod test {
             }
         }
 
+        #[test]
+        fn test_sample() {
+            assert_snapshot!(plan("SELECT sample(usage_idle, 2), usage_system FROM cpu"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), sample:Float64;N, usage_system:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, cpu.usage_idle AS sample, cpu.usage_system AS usage_system [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), sample:Float64;N, usage_system:Float64;N]
+                Filter: selector_sample(Int64(2)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, selector_sample(Int64(2)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING:Boolean;N]
+                  WindowAggr: windowExpr=[[selector_sample(Int64(2)) ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS selector_sample(Int64(2)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, selector_sample(Int64(2)) ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING:Boolean;N]
+                    Filter: cpu.usage_idle IS NOT NULL [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                      TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            assert_snapshot!(plan("SELECT sample(usage_idle, 2), host FROM cpu GROUP BY cpu"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, sample:Float64;N, host:Dictionary(Int32, Utf8);N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, cpu.cpu AS cpu, cpu.usage_idle AS sample, cpu.host AS host [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, sample:Float64;N, host:Dictionary(Int32, Utf8);N]
+                Filter: selector_sample(Int64(2)) PARTITION BY [cpu] ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, selector_sample(Int64(2)) PARTITION BY [cpu] ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING:Boolean;N]
+                  WindowAggr: windowExpr=[[selector_sample(Int64(2)) PARTITION BY [cpu.cpu] ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS selector_sample(Int64(2)) PARTITION BY [cpu] ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, selector_sample(Int64(2)) PARTITION BY [cpu] ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING:Boolean;N]
+                    Filter: cpu.usage_idle IS NOT NULL [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                      TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+        }
+
         #[test]
         fn test_percentile() {
             assert_snapshot!(plan("SELECT percentile(usage_idle,50),usage_system FROM cpu"), @r###"
@@ -4414,6 +5783,88 @@ mod test {
             "###);
         }
 
+        #[test]
+        fn test_integral() {
+            assert_snapshot!(plan("SELECT integral(usage_idle) FROM cpu"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), integral:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, integral(cpu.usage_idle,IntervalMonthDayNano("1000000000"),cpu.time) AS integral [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), integral:Float64;N]
+                Aggregate: groupBy=[[]], aggr=[[integral(cpu.usage_idle, IntervalMonthDayNano("1000000000"), cpu.time)]] [integral(cpu.usage_idle,IntervalMonthDayNano("1000000000"),cpu.time):Float64;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            assert_snapshot!(plan("SELECT integral(usage_idle, 1m) FROM cpu WHERE time >= 0 AND time < 60000000000 GROUP BY TIME(10s) FILL(0)"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, integral:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, coalesce_struct(MAX(integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time)), Float64(0)) AS integral [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, integral:Float64;N]
+                GapFill: groupBy=[time], aggr=[[MAX(integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time))]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Included(Literal(TimestampNanosecond(0, None)))..Included(Literal(TimestampNanosecond(59999999999, None))) [time:Timestamp(Nanosecond, None);N, MAX(integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time)):Float64;N]
+                  Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[MAX(integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time))]] [time:Timestamp(Nanosecond, None);N, MAX(integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time)):Float64;N]
+                    WindowAggr: windowExpr=[[integral(cpu.usage_idle, IntervalMonthDayNano("60000000000"), cpu.time, date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)), IntervalMonthDayNano("10000000000")) ORDER BY [cpu.time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING AS integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time)]] [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N, integral(cpu.usage_idle,IntervalMonthDayNano("60000000000"),cpu.time):Float64;N]
+                      Filter: cpu.time >= TimestampNanosecond(0, None) AND cpu.time <= TimestampNanosecond(59999999999, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                        TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+        }
+
+        #[test]
+        fn test_spread() {
+            assert_snapshot!(plan("SELECT spread(usage_idle) FROM cpu GROUP BY cpu"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, spread:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, cpu.cpu AS cpu, spread(cpu.usage_idle) AS spread [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, spread:Float64;N]
+                Aggregate: groupBy=[[cpu.cpu]], aggr=[[spread(cpu.usage_idle)]] [cpu:Dictionary(Int32, Utf8);N, spread(cpu.usage_idle):Float64;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            assert_snapshot!(plan("SELECT spread(usage_idle) FROM cpu WHERE time >= 0 AND time < 60000000000 GROUP BY TIME(10s) FILL(previous)"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, spread:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, time, spread(cpu.usage_idle) AS spread [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, spread:Float64;N]
+                GapFill: groupBy=[time], aggr=[[LOCF(spread(cpu.usage_idle))]], time_column=time, stride=IntervalMonthDayNano("10000000000"), range=Included(Literal(TimestampNanosecond(0, None)))..Included(Literal(TimestampNanosecond(59999999999, None))) [time:Timestamp(Nanosecond, None);N, spread(cpu.usage_idle):Float64;N]
+                  Aggregate: groupBy=[[date_bin(IntervalMonthDayNano("10000000000"), cpu.time, TimestampNanosecond(0, None)) AS time]], aggr=[[spread(cpu.usage_idle)]] [time:Timestamp(Nanosecond, None);N, spread(cpu.usage_idle):Float64;N]
+                    Filter: cpu.time >= TimestampNanosecond(0, None) AND cpu.time <= TimestampNanosecond(59999999999, None) [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                      TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+        }
+
+        #[test]
+        fn test_hll() {
+            assert_snapshot!(plan("SELECT count_hll(usage_idle) FROM cpu GROUP BY cpu"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, count_hll:UInt64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, cpu.cpu AS cpu, count_hll(cpu.usage_idle) AS count_hll [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, count_hll:UInt64;N]
+                Aggregate: groupBy=[[cpu.cpu]], aggr=[[count_hll(cpu.usage_idle)]] [cpu:Dictionary(Int32, Utf8);N, count_hll(cpu.usage_idle):UInt64;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            assert_snapshot!(plan("SELECT sum_hll(usage_idle) FROM cpu GROUP BY cpu"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, sum_hll:Utf8;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, cpu.cpu AS cpu, sum_hll(cpu.usage_idle) AS sum_hll [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, sum_hll:Utf8;N]
+                Aggregate: groupBy=[[cpu.cpu]], aggr=[[sum_hll(cpu.usage_idle)]] [cpu:Dictionary(Int32, Utf8);N, sum_hll(cpu.usage_idle):Utf8;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            // Invalid number of arguments
+            assert_snapshot!(plan("SELECT count_hll(usage_idle, usage_system) FROM cpu"), @"Error during planning: invalid number of arguments for count_hll, expected 1, got 2");
+        }
+
+        #[test]
+        fn test_approx_percentile() {
+            assert_snapshot!(plan("SELECT approx_percentile(usage_idle, 95) FROM cpu GROUP BY cpu"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, approx_percentile:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, cpu.cpu AS cpu, approx_percentile(cpu.usage_idle,Int64(95)) AS approx_percentile [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, approx_percentile:Float64;N]
+                Aggregate: groupBy=[[cpu.cpu]], aggr=[[approx_percentile(cpu.usage_idle, Int64(95))]] [cpu:Dictionary(Int32, Utf8);N, approx_percentile(cpu.usage_idle,Int64(95)):Float64;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+        }
+
+        #[test]
+        fn test_mode() {
+            assert_snapshot!(plan("SELECT mode(usage_idle) FROM cpu"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), mode:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, TimestampNanosecond(0, None) AS time, mode(cpu.usage_idle) AS mode [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), mode:Float64;N]
+                Aggregate: groupBy=[[]], aggr=[[mode(cpu.usage_idle)]] [mode(cpu.usage_idle):Float64;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            // Invalid number of arguments
+            assert_snapshot!(plan("SELECT mode(usage_idle, usage_system) FROM cpu"), @"Error during planning: invalid number of arguments for mode, expected 1, got 2");
+        }
+
         #[test]
         fn test_top() {
             assert_snapshot!(plan("SELECT top(usage_idle,10) FROM cpu"), @r###"
@@ -4580,6 +6031,44 @@ mod test {
             );
         }
 
+        /// Verify the target of a `SELECT ... INTO` statement is recorded in the schema metadata.
+        #[test]
+        fn test_select_into() {
+            fn into_target(sql: &str) -> Option<IntoTarget> {
+                IntoTarget::from_schema_metadata(logical_plan(sql).unwrap().schema().metadata())
+                    .unwrap()
+            }
+
+            assert_eq!(into_target("SELECT usage_idle FROM cpu"), None);
+
+            assert_eq!(
+                into_target(
+                    "SELECT mean(usage_idle) INTO rollup.cpu_1h FROM cpu GROUP BY TIME(1h), *"
+                ),
+                Some(IntoTarget {
+                    database: None,
+                    retention_policy: Some("rollup".to_owned()),
+                    measurement: Some("cpu_1h".to_owned()),
+                })
+            );
+
+            assert_eq!(
+                into_target("SELECT usage_idle INTO foo..:MEASUREMENT FROM cpu"),
+                Some(IntoTarget {
+                    database: Some("foo".to_owned()),
+                    retention_policy: None,
+                    measurement: None,
+                })
+            );
+
+            // The InfluxQL metadata is retained
+            let md = metadata("SELECT usage_idle INTO cpu_copy FROM cpu GROUP BY host").unwrap();
+            assert_eq!(md.measurement_column_index, 0);
+
+            // INTO is not supported by subqueries
+            assert_snapshot!(plan("SELECT usage_idle FROM (SELECT usage_idle INTO cpu_copy FROM cpu)"), @"Error during planning: INTO clause is not allowed in a subquery");
+        }
+
         /// Verify the behaviour of the `FROM` clause when selecting from zero to many measurements.
         #[test]
         fn test_from_zero_to_many() {
@@ -5051,6 +6540,18 @@ mod test {
                 "###);
             }
 
+            #[test]
+            fn group_by_time_tz() {
+                // bins on the wall clock of the timezone
+                assert_snapshot!(plan("SELECT COUNT(f64_field) FROM data GROUP BY TIME(1d) FILL(none) TZ('Europe/Berlin')"), @r###"
+                Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, count:Int64;N]
+                  Projection: Dictionary(Int32, Utf8("data")) AS iox::measurement, time, coalesce_struct(COUNT(data.f64_field), Int64(0)) AS count [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None);N, count:Int64;N]
+                    Aggregate: groupBy=[[date_bin_wallclock(IntervalMonthDayNano("86400000000000"), data.time, TimestampNanosecond(0, None), Utf8("Europe/Berlin")) AS time]], aggr=[[COUNT(data.f64_field)]] [time:Timestamp(Nanosecond, None);N, COUNT(data.f64_field):Int64;N]
+                      Filter: data.time <= TimestampNanosecond(1672531200000000000, None) [TIME:Boolean;N, bar:Dictionary(Int32, Utf8);N, bool_field:Boolean;N, f64_field:Float64;N, foo:Dictionary(Int32, Utf8);N, i64_field:Int64;N, mixedCase:Float64;N, str_field:Utf8;N, time:Timestamp(Nanosecond, None), with space:Float64;N]
+                        TableScan: data [TIME:Boolean;N, bar:Dictionary(Int32, Utf8);N, bool_field:Boolean;N, f64_field:Float64;N, foo:Dictionary(Int32, Utf8);N, i64_field:Int64;N, mixedCase:Float64;N, str_field:Utf8;N, time:Timestamp(Nanosecond, None), with space:Float64;N]
+                "###);
+            }
+
             #[test]
             fn group_by_time_gapfill_no_bounds() {
                 // No time bounds
@@ -5499,6 +7000,65 @@ mod test {
             "###);
         }
 
+        #[test]
+        fn test_select_slimit_soffset() {
+            assert_snapshot!(plan("SELECT usage_idle FROM cpu GROUP BY cpu SLIMIT 1 SOFFSET 1"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+              Projection: iox::measurement, time, cpu, usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+                Filter: iox::series BETWEEN Int64(2) AND Int64(2) [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N, iox::series:UInt64;N]
+                  WindowAggr: windowExpr=[[DENSE_RANK() ORDER BY [cpu ASC NULLS LAST] RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW AS iox::series]] [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N, iox::series:UInt64;N]
+                    Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, cpu.cpu AS cpu, cpu.usage_idle AS usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+                      TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            // The series are limited before the LIMIT is applied to each series
+            assert_snapshot!(plan("SELECT usage_idle FROM cpu GROUP BY cpu LIMIT 1 SLIMIT 2"), @r###"
+            Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+              Projection: iox::measurement, time, cpu, usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+                Filter: iox::row <= Int64(1) [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N, iox::row:UInt64;N]
+                  WindowAggr: windowExpr=[[ROW_NUMBER() PARTITION BY [cpu] ORDER BY [time ASC NULLS LAST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW AS iox::row]] [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N, iox::row:UInt64;N]
+                    Sort: cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+                      Projection: iox::measurement, time, cpu, usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+                        Filter: iox::series <= Int64(2) [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N, iox::series:UInt64;N]
+                          WindowAggr: windowExpr=[[DENSE_RANK() ORDER BY [cpu ASC NULLS LAST] RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW AS iox::series]] [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N, iox::series:UInt64;N]
+                            Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, cpu.cpu AS cpu, cpu.usage_idle AS usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Dictionary(Int32, Utf8);N, usage_idle:Float64;N]
+                              TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            // Each measurement is a distinct series
+            assert_snapshot!(plan("SELECT usage_idle, bytes_free FROM cpu, disk GROUP BY cpu SOFFSET 1"), @r###"
+            Sort: iox::measurement ASC NULLS LAST, cpu ASC NULLS LAST, time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N]
+              Projection: iox::measurement, time, cpu, usage_idle, bytes_free [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N]
+                Filter: iox::series > Int64(1) [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N, iox::series:UInt64;N]
+                  WindowAggr: windowExpr=[[DENSE_RANK() ORDER BY [iox::measurement ASC NULLS LAST, cpu ASC NULLS LAST] RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW AS iox::series]] [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N, iox::series:UInt64;N]
+                    Union [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N]
+                      Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, CAST(cpu.cpu AS Utf8) AS cpu, cpu.usage_idle AS usage_idle, CAST(NULL AS Int64) AS bytes_free [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N]
+                        TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+                      Projection: Dictionary(Int32, Utf8("disk")) AS iox::measurement, disk.time AS time, CAST(NULL AS Utf8) AS cpu, CAST(NULL AS Float64) AS usage_idle, disk.bytes_free AS bytes_free [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), cpu:Utf8;N, usage_idle:Float64;N, bytes_free:Int64;N]
+                        TableScan: disk [bytes_free:Int64;N, bytes_used:Int64;N, device:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None)]
+            "###);
+
+            // Without a GROUP BY, a single measurement is a single series
+            assert_snapshot!(plan("SELECT usage_idle FROM cpu SLIMIT 1"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), usage_idle:Float64;N]
+              Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, cpu.usage_idle AS usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), usage_idle:Float64;N]
+                TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+            assert_snapshot!(plan("SELECT usage_idle FROM cpu SOFFSET 1"), @r###"
+            Sort: time ASC NULLS LAST [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), usage_idle:Float64;N]
+              Limit: skip=0, fetch=0 [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), usage_idle:Float64;N]
+                Projection: Dictionary(Int32, Utf8("cpu")) AS iox::measurement, cpu.time AS time, cpu.usage_idle AS usage_idle [iox::measurement:Dictionary(Int32, Utf8), time:Timestamp(Nanosecond, None), usage_idle:Float64;N]
+                  TableScan: cpu [cpu:Dictionary(Int32, Utf8);N, host:Dictionary(Int32, Utf8);N, region:Dictionary(Int32, Utf8);N, time:Timestamp(Nanosecond, None), usage_idle:Float64;N, usage_system:Float64;N, usage_user:Float64;N]
+            "###);
+
+            // Fallible
+
+            // returns an error if SLIMIT or SOFFSET values exceed i64::MAX
+            let max = (i64::MAX as u64) + 1;
+            assert_snapshot!(plan(format!("SELECT usage_idle FROM cpu GROUP BY cpu SLIMIT {max}")), @"Error during planning: slimit out of range");
+            assert_snapshot!(plan(format!("SELECT usage_idle FROM cpu GROUP BY cpu SOFFSET {max}")), @"Error during planning: soffset out of range");
+        }
+
         #[test]
         fn test_select_function_tag_column() {
             assert_snapshot!(plan("SELECT last(foo) as foo, first(usage_idle) from cpu group by foo"), @r###"
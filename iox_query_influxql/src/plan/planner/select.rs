@@ -344,7 +344,7 @@ impl<'a> Selector<'a> {
     fn sample(call: &'a Call) -> Result<Self> {
         if call.args.len() != 2 {
             return error::internal(format!(
-                "invalid number of arguments for min: expected 1, got {}",
+                "invalid number of arguments for sample: expected 2, got {}",
                 call.args.len()
             ));
         }
@@ -13,7 +13,7 @@ use influxdb_influxql_parser::expression::walk::{
 use influxdb_influxql_parser::expression::{
     AsVarRefExpr, Call, Expr, VarRef, VarRefDataType, WildcardType,
 };
-use influxdb_influxql_parser::functions::is_scalar_math_function;
+use influxdb_influxql_parser::functions::{is_now_function, is_scalar_math_function};
 use influxdb_influxql_parser::identifier::Identifier;
 use influxdb_influxql_parser::literal::Literal;
 use influxdb_influxql_parser::select::{
@@ -68,24 +68,6 @@ fn rewrite_select(s: &dyn SchemaProvider, stmt: &SelectStatement) -> Result<Sele
     rw.rewrite(s, stmt)
 }
 
-/// Asserts that the `SELECT` statement does not use any unimplemented features.
-///
-/// The list of unimplemented or unsupported features are listed below.
-///
-/// # `SLIMIT` and `SOFFSET`
-///
-/// * `SLIMIT` and `SOFFSET` don't work as expected per issue [#7571]
-/// * This issue [is noted](https://docs.influxdata.com/influxdb/v1.8/query_language/explore-data/#the-slimit-clause) in our official documentation
-///
-/// [#7571]: https://github.com/influxdata/influxdb/issues/7571
-fn check_features(stmt: &SelectStatement) -> Result<()> {
-    if stmt.series_limit.is_some() || stmt.series_offset.is_some() {
-        return error::not_implemented("SLIMIT or SOFFSET");
-    }
-
-    Ok(())
-}
-
 #[derive(Default)]
 struct RewriteSelect {
     /// The depth of the `SELECT` statement currently processed by the rewriter.
@@ -96,7 +78,9 @@ impl RewriteSelect {
     /// Transform a `SelectStatement` to a `Select`, which is an intermediate representation used by
     /// the InfluxQL planner. Transformations include expanding wildcards.
     fn rewrite(&self, s: &dyn SchemaProvider, stmt: &SelectStatement) -> Result<Select> {
-        check_features(stmt)?;
+        if self.is_subquery() && stmt.into.is_some() {
+            return error::query("INTO clause is not allowed in a subquery");
+        }
 
         let from = self.expand_from(s, stmt)?;
         let tag_set = from_tag_set(s, &from);
@@ -160,6 +144,8 @@ impl RewriteSelect {
             order_by: stmt.order_by,
             limit: stmt.limit,
             offset: stmt.offset,
+            series_limit: stmt.series_limit,
+            series_offset: stmt.series_offset,
             timezone: stmt.timezone.map(|v| *v),
         })
     }
@@ -724,7 +710,8 @@ fn fields_expand_wildcards(
 
                 // Modify the supported types for certain functions.
                 match name.as_str() {
-                    "count" | "first" | "last" | "distinct" | "elapsed" | "mode" | "sample" => {
+                    "count" | "count_hll" | "sum_hll" | "first" | "last" | "distinct"
+                    | "elapsed" | "mode" | "sample" => {
                         supported_types
                             .extend([Some(VarRefDataType::String), Some(VarRefDataType::Boolean)]);
                     }
@@ -947,6 +934,13 @@ struct FieldChecker {
     /// `true` if the projection contains an invocation of the `TOP` or `BOTTOM` function.
     has_top_bottom: bool,
 
+    /// `true` if the projection contains an invocation of the `SAMPLE` function.
+    has_sample: bool,
+
+    /// `true` if the projection contains an invocation of the `FIRST`, `LAST`, `MAX`
+    /// or `MIN` function.
+    has_row_selector: bool,
+
     /// `true` when one or more projections do not contain an aggregate expression.
     has_non_aggregate_fields: bool,
 
@@ -1020,9 +1014,11 @@ impl FieldChecker {
         if self.has_non_aggregate_fields {
             if self.window_aggregate_count() > 0 {
                 return error::query("mixing aggregate and non-aggregate columns is not supported");
-            } else if self.selector_count > 1 {
+            } else if self.selector_count > 1 && !self.has_row_selector {
+                // The tags and fields are taken from the row selected by the first FIRST,
+                // LAST, MAX or MIN function, so at least one must be present.
                 return error::query(
-                    "mixing multiple selector functions with tags or fields is not supported",
+                    "mixing multiple selector functions with tags or fields requires a FIRST, LAST, MAX or MIN function",
                 );
             }
         }
@@ -1035,6 +1031,13 @@ impl FieldChecker {
 
         let projection_type = if self.has_top_bottom {
             ProjectionType::TopBottomSelector
+        } else if self.has_sample && self.selector_count == 1 && self.aggregate_count == 0 {
+            // Like TOP and BOTTOM, SAMPLE selects multiple rows from each
+            // group and window, so is not planned as an aggregate when
+            // grouping by time.
+            ProjectionType::Selector {
+                has_fields: self.has_non_aggregate_fields,
+            }
         } else if self.has_group_by_time {
             if self.window_count > 0 {
                 if self.window_count == self.aggregate_count + self.selector_count {
@@ -1076,6 +1079,16 @@ impl FieldChecker {
     }
 }
 
+/// Returns `true` if `e` evaluates to a constant, which is either a
+/// literal or a call to `now()`.
+fn is_constant(e: &Expr) -> bool {
+    match e {
+        Expr::Literal(_) => true,
+        Expr::Call(c) => is_now_function(&c.name) && c.args.is_empty(),
+        _ => false,
+    }
+}
+
 impl FieldChecker {
     fn check_expr(&mut self, e: &Expr) -> Result<()> {
         match e {
@@ -1086,12 +1099,19 @@ impl FieldChecker {
                 Ok(())
             }
             Expr::Call(c) if is_scalar_math_function(&c.name) => self.check_math_function(c),
+            // now() evaluates to a constant, and is therefore treated as a literal
+            Expr::Call(c) if is_now_function(&c.name) => {
+                check_exp_args!("now", 0, c.args);
+                error::query("field must contain at least one variable")
+            }
             Expr::Call(c) => self.check_aggregate_function(c),
             Expr::Binary(b) => match (&*b.lhs, &*b.rhs) {
-                (Expr::Literal(_), Expr::Literal(_)) => {
+                (lhs, rhs) if is_constant(lhs) && is_constant(rhs) => {
                     error::query("cannot perform a binary expression on two literals")
                 }
-                (Expr::Literal(_), other) | (other, Expr::Literal(_)) => self.check_expr(other),
+                (constant, other) | (other, constant) if is_constant(constant) => {
+                    self.check_expr(other)
+                }
                 (lhs, rhs) => {
                     self.check_expr(lhs)?;
                     self.check_expr(rhs)
@@ -1166,9 +1186,11 @@ impl FieldChecker {
             "elapsed" => self.check_elapsed(name, &c.args),
             "integral" => self.check_integral(name, &c.args),
             "count_hll" => self.check_count_hll(&c.args),
+            "approx_percentile" => self.check_approx_percentile(&c.args),
             "holt_winters" | "holt_winters_with_fit" => self.check_holt_winters(name, &c.args),
             "max" | "min" | "first" | "last" => {
                 self.inc_selector_count();
+                self.has_row_selector = true;
                 check_exp_args!(name, 1, c.args);
                 self.check_symbol(name, &c.args[0])
             }
@@ -1212,6 +1234,7 @@ impl FieldChecker {
 
     fn check_sample(&mut self, args: &[Expr]) -> Result<()> {
         self.inc_selector_count();
+        self.has_sample = true;
 
         check_exp_args!("sample", 2, args);
         let v = lit_integer!("sample", args, 1);
@@ -1480,19 +1503,33 @@ impl FieldChecker {
         self.check_symbol(name, &args[0])
     }
 
-    fn check_count_hll(&mut self, _args: &[Expr]) -> Result<()> {
+    fn check_count_hll(&mut self, args: &[Expr]) -> Result<()> {
         self.inc_aggregate_count();
-        // The count hyperloglog function is not documented for versions 1.8 or the latest 2.7.
-        // If anyone is using it, we'd like to know, so we'll explicitly return a not implemented
-        // message.
-        //
-        // See: https://docs.influxdata.com/influxdb/v2.7/query-data/influxql/functions/
-        // See: https://docs.influxdata.com/influxdb/v1.8/query_language/functions
-        error::not_implemented("count_hll")
+        check_exp_args!("count_hll", 1, args);
+        self.check_symbol("count_hll", &args[0])
     }
 
-    fn check_holt_winters(&mut self, name: &str, args: &[Expr]) -> Result<()> {
+    fn check_approx_percentile(&mut self, args: &[Expr]) -> Result<()> {
         self.inc_aggregate_count();
+
+        check_exp_args!("approx_percentile", 2, args);
+        match &args[1] {
+            Expr::Literal(Literal::Integer(v)) if (0..=100).contains(v) => {}
+            Expr::Literal(Literal::Float(v)) if (0.0..=100.0).contains(v) => {}
+            Expr::Literal(Literal::Integer(_) | Literal::Float(_)) => {
+                return error::query("approx_percentile() percentile must be between 0 and 100")
+            }
+            got => {
+                return error::query(format!(
+                    "expected number for approx_percentile(), got {got:?}"
+                ))
+            }
+        }
+        self.check_symbol("approx_percentile", &args[0])
+    }
+
+    fn check_holt_winters(&mut self, name: &str, args: &[Expr]) -> Result<()> {
+        self.inc_window_count();
         check_exp_args!(name, 3, args);
 
         let v = lit_integer!(name, args, 1);
@@ -1623,10 +1660,11 @@ struct SelectStatementInfo {
 ///
 /// For selector queries, which are those that use selector functions like `last` or `max`:
 ///
-/// * Projecting **multiple** selector functions, such as `last` or `first` will not be
-/// combined with non-aggregate columns
 /// * Projecting a **single** selector function, such as `last` or `first` may be combined
 /// with non-aggregate columns
+/// * Projecting **multiple** selector functions may be combined with non-aggregate columns
+/// if one of them is `first`, `last`, `max` or `min`, which selects the row of the
+/// non-aggregate columns
 ///
 /// Finally, the `top` and `bottom` function have the following additional restrictions:
 ///
@@ -1747,10 +1785,24 @@ mod test {
         .unwrap();
         assert_matches!(info.projection_type, ProjectionType::Aggregate);
 
+        let info = select_statement_info(&parse_select(
+            "SELECT sample(foo, 2), bar FROM cpu GROUP BY TIME(10s)",
+        ))
+        .unwrap();
+        assert_matches!(
+            info.projection_type,
+            ProjectionType::Selector { has_fields: true }
+        );
+
         let info =
             select_statement_info(&parse_select("SELECT last(foo), first(foo) FROM cpu")).unwrap();
         assert_matches!(info.projection_type, ProjectionType::Aggregate);
 
+        let info =
+            select_statement_info(&parse_select("SELECT last(foo), first(foo), bar FROM cpu"))
+                .unwrap();
+        assert_matches!(info.projection_type, ProjectionType::Aggregate);
+
         let info = select_statement_info(&parse_select("SELECT count(foo) FROM cpu")).unwrap();
         assert_matches!(info.projection_type, ProjectionType::Aggregate);
 
@@ -1766,6 +1818,12 @@ mod test {
         .unwrap();
         assert_matches!(info.projection_type, ProjectionType::WindowAggregateMixed);
 
+        let info = select_statement_info(&parse_select(
+            "SELECT holt_winters(mean(foo), 10, 4) FROM cpu GROUP BY TIME(10s)",
+        ))
+        .unwrap();
+        assert_matches!(info.projection_type, ProjectionType::WindowAggregate);
+
         let info = select_statement_info(&parse_select("SELECT top(foo, 3) FROM cpu")).unwrap();
         assert_matches!(info.projection_type, ProjectionType::TopBottomSelector);
     }
@@ -1960,10 +2018,21 @@ mod test {
 
         // count_hll
         let sel = parse_select("SELECT count_hll(foo) FROM cpu");
-        assert_error!(
-            select_statement_info(&sel),
-            DataFusionError::NotImplemented(_)
-        );
+        select_statement_info(&sel).unwrap();
+        let sel = parse_select("SELECT count_hll(sum_hll) FROM (SELECT sum_hll(foo) FROM cpu)");
+        select_statement_info(&sel).unwrap();
+        let sel = parse_select("SELECT count_hll(foo, 2) FROM cpu");
+        assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s == "invalid number of arguments for count_hll, expected 1, got 2");
+
+        // approx_percentile
+        let sel = parse_select("SELECT approx_percentile(foo, 95) FROM cpu");
+        select_statement_info(&sel).unwrap();
+        let sel = parse_select("SELECT approx_percentile(foo, 99.9) FROM cpu");
+        select_statement_info(&sel).unwrap();
+        let sel = parse_select("SELECT approx_percentile(foo, 101) FROM cpu");
+        assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s == "approx_percentile() percentile must be between 0 and 100");
+        let sel = parse_select("SELECT approx_percentile(foo, bar) FROM cpu");
+        assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s.starts_with("expected number for approx_percentile()"));
 
         // holt_winters, holt_winters_with_fit
         let sel = parse_select("SELECT holt_winters(mean(foo), 2, 3) FROM cpu GROUP BY time(30s)");
@@ -2023,6 +2092,12 @@ mod test {
         // can't project literals
         let sel = parse_select("SELECT foo, 1 FROM cpu");
         assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s == "field must contain at least one variable");
+        let sel = parse_select("SELECT foo, now() FROM cpu");
+        assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s == "field must contain at least one variable");
+        let sel = parse_select("SELECT now() - now() FROM cpu");
+        assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s == "cannot perform a binary expression on two literals");
+        let sel = parse_select("SELECT foo - now(1) FROM cpu");
+        assert_error!(select_statement_info(&sel), DataFusionError::Plan(ref s) if s == "invalid number of arguments for now, expected 0, got 1");
 
         // aggregate functions require a field reference
         let sel = parse_select("SELECT sum(1) FROM cpu");
@@ -2383,6 +2458,14 @@ mod test {
                 "SELECT time::timestamp AS time, host::tag AS host, usage_idle::float AS usage_idle, usage_system::float AS usage_system, usage_user::float AS usage_user FROM cpu GROUP BY cpu::tag, host::tag, region::tag"
             );
 
+            // Preserves SLIMIT and SOFFSET
+            let stmt = parse_select("SELECT usage_idle FROM cpu GROUP BY host SLIMIT 2 SOFFSET 1");
+            let stmt = rewrite_select_statement(&namespace, &stmt).unwrap();
+            assert_eq!(
+                stmt.to_string(),
+                "SELECT time::timestamp AS time, usage_idle::float AS usage_idle FROM cpu GROUP BY host::tag SLIMIT 2 SOFFSET 1"
+            );
+
             //
             // TIME
             //
@@ -2444,25 +2527,18 @@ mod test {
                 "Error during planning: unsupported binary expression: contains a wildcard or regular expression"
             );
 
-            let stmt = parse_select("SELECT COUNT(*::tag) FROM cpu");
+            let stmt = parse_select("SELECT percentile(usage_idle, 50), percentile(usage_idle, 90), usage_system FROM cpu");
             let err = rewrite_select_statement(&namespace, &stmt).unwrap_err();
             assert_eq!(
                 err.to_string(),
-                "Error during planning: unable to use tag as wildcard in count()"
-            );
-
-            let stmt = parse_select("SELECT usage_idle FROM cpu SLIMIT 1");
-            let err = rewrite_select_statement(&namespace, &stmt).unwrap_err();
-            assert_eq!(
-                err.to_string(),
-                "This feature is not implemented: SLIMIT or SOFFSET"
+                "Error during planning: mixing multiple selector functions with tags or fields requires a FIRST, LAST, MAX or MIN function"
             );
 
-            let stmt = parse_select("SELECT usage_idle FROM cpu SOFFSET 1");
+            let stmt = parse_select("SELECT COUNT(*::tag) FROM cpu");
             let err = rewrite_select_statement(&namespace, &stmt).unwrap_err();
             assert_eq!(
                 err.to_string(),
-                "This feature is not implemented: SLIMIT or SOFFSET"
+                "Error during planning: unable to use tag as wildcard in count()"
             );
         }
 
@@ -7,7 +7,7 @@
 
 use crate::plan::util::find_exprs_in_exprs;
 use crate::{error, NUMERICS};
-use arrow::datatypes::{DataType, TimeUnit};
+use arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
 use datafusion::logical_expr::{
     Expr, ReturnTypeFunction, ScalarFunctionImplementation, ScalarUDF, Signature, TypeSignature,
     Volatility,
@@ -22,6 +22,17 @@ pub(super) enum WindowFunction {
     Derivative,
     NonNegativeDerivative,
     CumulativeSum,
+    Elapsed,
+    HoltWinters,
+    HoltWintersWithFit,
+    ExponentialMovingAverage,
+    DoubleExponentialMovingAverage,
+    TripleExponentialMovingAverage,
+    TripleExponentialDerivative,
+    RelativeStrengthIndex,
+    ChandeMomentumOscillator,
+    KaufmansEfficiencyRatio,
+    KaufmansAdaptiveMovingAverage,
 }
 
 impl WindowFunction {
@@ -34,6 +45,21 @@ impl WindowFunction {
             DERIVATIVE_UDF_NAME => Some(Self::Derivative),
             NON_NEGATIVE_DERIVATIVE_UDF_NAME => Some(Self::NonNegativeDerivative),
             CUMULATIVE_SUM_UDF_NAME => Some(Self::CumulativeSum),
+            ELAPSED_UDF_NAME => Some(Self::Elapsed),
+            HOLT_WINTERS_UDF_NAME => Some(Self::HoltWinters),
+            HOLT_WINTERS_WITH_FIT_UDF_NAME => Some(Self::HoltWintersWithFit),
+            EXPONENTIAL_MOVING_AVERAGE_UDF_NAME => Some(Self::ExponentialMovingAverage),
+            DOUBLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME => {
+                Some(Self::DoubleExponentialMovingAverage)
+            }
+            TRIPLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME => {
+                Some(Self::TripleExponentialMovingAverage)
+            }
+            TRIPLE_EXPONENTIAL_DERIVATIVE_UDF_NAME => Some(Self::TripleExponentialDerivative),
+            RELATIVE_STRENGTH_INDEX_UDF_NAME => Some(Self::RelativeStrengthIndex),
+            CHANDE_MOMENTUM_OSCILLATOR_UDF_NAME => Some(Self::ChandeMomentumOscillator),
+            KAUFMANS_EFFICIENCY_RATIO_UDF_NAME => Some(Self::KaufmansEfficiencyRatio),
+            KAUFMANS_ADAPTIVE_MOVING_AVERAGE_UDF_NAME => Some(Self::KaufmansAdaptiveMovingAverage),
             _ => None,
         }
     }
@@ -209,6 +235,170 @@ static CUMULATIVE_SUM: Lazy<Arc<ScalarUDF>> = Lazy::new(|| {
     ))
 });
 
+const ELAPSED_UDF_NAME: &str = "elapsed";
+
+/// Create an expression to represent the `ELAPSED` function.
+pub(crate) fn elapsed(args: Vec<Expr>) -> Expr {
+    ELAPSED.call(args)
+}
+
+/// Definition of the `ELAPSED` function.
+static ELAPSED: Lazy<Arc<ScalarUDF>> = Lazy::new(|| {
+    let return_type_fn: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Int64)));
+    Arc::new(ScalarUDF::new(
+        ELAPSED_UDF_NAME,
+        &Signature::one_of(
+            NUMERICS
+                .iter()
+                .chain(&[DataType::Utf8, DataType::Boolean])
+                .flat_map(|dt| {
+                    vec![
+                        TypeSignature::Exact(vec![dt.clone()]),
+                        TypeSignature::Exact(vec![
+                            dt.clone(),
+                            DataType::Interval(IntervalUnit::MonthDayNano),
+                        ]),
+                    ]
+                })
+                .collect(),
+            Volatility::Immutable,
+        ),
+        &return_type_fn,
+        &stand_in_impl(ELAPSED_UDF_NAME),
+    ))
+});
+
+const HOLT_WINTERS_UDF_NAME: &str = "holt_winters";
+
+/// Create an expression to represent the `HOLT_WINTERS` function.
+pub(crate) fn holt_winters(args: Vec<Expr>) -> Expr {
+    HOLT_WINTERS.call(args)
+}
+
+/// Definition of the `HOLT_WINTERS` function.
+static HOLT_WINTERS: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| holt_winters_stand_in(HOLT_WINTERS_UDF_NAME));
+
+const HOLT_WINTERS_WITH_FIT_UDF_NAME: &str = "holt_winters_with_fit";
+
+/// Create an expression to represent the `HOLT_WINTERS_WITH_FIT` function.
+pub(crate) fn holt_winters_with_fit(args: Vec<Expr>) -> Expr {
+    HOLT_WINTERS_WITH_FIT.call(args)
+}
+
+/// Definition of the `HOLT_WINTERS_WITH_FIT` function.
+static HOLT_WINTERS_WITH_FIT: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| holt_winters_stand_in(HOLT_WINTERS_WITH_FIT_UDF_NAME));
+
+/// Create the definition of either of the `HOLT_WINTERS` functions, which take the
+/// value, the number of values to forecast and the length of a season.
+fn holt_winters_stand_in(name: &'static str) -> Arc<ScalarUDF> {
+    let return_type_fn: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Float64)));
+    Arc::new(ScalarUDF::new(
+        name,
+        &Signature::one_of(
+            NUMERICS
+                .iter()
+                .map(|dt| TypeSignature::Exact(vec![dt.clone(), DataType::Int64, DataType::Int64]))
+                .collect(),
+            Volatility::Immutable,
+        ),
+        &return_type_fn,
+        &stand_in_impl(name),
+    ))
+}
+
+const EXPONENTIAL_MOVING_AVERAGE_UDF_NAME: &str = "exponential_moving_average";
+
+/// Definition of the `EXPONENTIAL_MOVING_AVERAGE` function.
+static EXPONENTIAL_MOVING_AVERAGE: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(EXPONENTIAL_MOVING_AVERAGE_UDF_NAME, true));
+
+const DOUBLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME: &str = "double_exponential_moving_average";
+
+/// Definition of the `DOUBLE_EXPONENTIAL_MOVING_AVERAGE` function.
+static DOUBLE_EXPONENTIAL_MOVING_AVERAGE: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(DOUBLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME, true));
+
+const TRIPLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME: &str = "triple_exponential_moving_average";
+
+/// Definition of the `TRIPLE_EXPONENTIAL_MOVING_AVERAGE` function.
+static TRIPLE_EXPONENTIAL_MOVING_AVERAGE: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(TRIPLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME, true));
+
+const TRIPLE_EXPONENTIAL_DERIVATIVE_UDF_NAME: &str = "triple_exponential_derivative";
+
+/// Definition of the `TRIPLE_EXPONENTIAL_DERIVATIVE` function.
+static TRIPLE_EXPONENTIAL_DERIVATIVE: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(TRIPLE_EXPONENTIAL_DERIVATIVE_UDF_NAME, true));
+
+const RELATIVE_STRENGTH_INDEX_UDF_NAME: &str = "relative_strength_index";
+
+/// Definition of the `RELATIVE_STRENGTH_INDEX` function.
+static RELATIVE_STRENGTH_INDEX: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(RELATIVE_STRENGTH_INDEX_UDF_NAME, true));
+
+const CHANDE_MOMENTUM_OSCILLATOR_UDF_NAME: &str = "chande_momentum_oscillator";
+
+/// Definition of the `CHANDE_MOMENTUM_OSCILLATOR` function.
+static CHANDE_MOMENTUM_OSCILLATOR: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(CHANDE_MOMENTUM_OSCILLATOR_UDF_NAME, true));
+
+const KAUFMANS_EFFICIENCY_RATIO_UDF_NAME: &str = "kaufmans_efficiency_ratio";
+
+/// Definition of the `KAUFMANS_EFFICIENCY_RATIO` function.
+static KAUFMANS_EFFICIENCY_RATIO: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(KAUFMANS_EFFICIENCY_RATIO_UDF_NAME, false));
+
+const KAUFMANS_ADAPTIVE_MOVING_AVERAGE_UDF_NAME: &str = "kaufmans_adaptive_moving_average";
+
+/// Definition of the `KAUFMANS_ADAPTIVE_MOVING_AVERAGE` function.
+static KAUFMANS_ADAPTIVE_MOVING_AVERAGE: Lazy<Arc<ScalarUDF>> =
+    Lazy::new(|| technical_analysis_stand_in(KAUFMANS_ADAPTIVE_MOVING_AVERAGE_UDF_NAME, false));
+
+/// Create an expression to represent one of the technical analysis functions,
+/// such as `EXPONENTIAL_MOVING_AVERAGE`.
+///
+/// Returns `None` if `name` is not a technical analysis function.
+pub(crate) fn technical_analysis(name: &str, args: Vec<Expr>) -> Option<Expr> {
+    let udf = match name {
+        EXPONENTIAL_MOVING_AVERAGE_UDF_NAME => &EXPONENTIAL_MOVING_AVERAGE,
+        DOUBLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME => &DOUBLE_EXPONENTIAL_MOVING_AVERAGE,
+        TRIPLE_EXPONENTIAL_MOVING_AVERAGE_UDF_NAME => &TRIPLE_EXPONENTIAL_MOVING_AVERAGE,
+        TRIPLE_EXPONENTIAL_DERIVATIVE_UDF_NAME => &TRIPLE_EXPONENTIAL_DERIVATIVE,
+        RELATIVE_STRENGTH_INDEX_UDF_NAME => &RELATIVE_STRENGTH_INDEX,
+        CHANDE_MOMENTUM_OSCILLATOR_UDF_NAME => &CHANDE_MOMENTUM_OSCILLATOR,
+        KAUFMANS_EFFICIENCY_RATIO_UDF_NAME => &KAUFMANS_EFFICIENCY_RATIO,
+        KAUFMANS_ADAPTIVE_MOVING_AVERAGE_UDF_NAME => &KAUFMANS_ADAPTIVE_MOVING_AVERAGE,
+        _ => return None,
+    };
+    Some(udf.call(args))
+}
+
+/// Create the definition of a technical analysis function, which takes the value,
+/// the period, the hold period and, if `with_warmup` is `true`, the warmup type.
+fn technical_analysis_stand_in(name: &'static str, with_warmup: bool) -> Arc<ScalarUDF> {
+    let return_type_fn: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Float64)));
+    Arc::new(ScalarUDF::new(
+        name,
+        &Signature::one_of(
+            NUMERICS
+                .iter()
+                .map(|dt| {
+                    let mut args = vec![dt.clone(), DataType::Int64, DataType::Int64];
+                    if with_warmup {
+                        args.push(DataType::Utf8);
+                    }
+                    TypeSignature::Exact(args)
+                })
+                .collect(),
+            Volatility::Immutable,
+        ),
+        &return_type_fn,
+        &stand_in_impl(name),
+    ))
+}
+
 /// Returns an implementation that always returns an error.
 fn stand_in_impl(name: &'static str) -> ScalarFunctionImplementation {
     Arc::new(move |_| error::internal(format!("{name} should not exist in the final logical plan")))
@@ -4,14 +4,18 @@ use datafusion::logical_expr::{
     PartitionEvaluatorFactory, ReturnTypeFunction, WindowFunction, WindowUDF,
 };
 use once_cell::sync::Lazy;
+use query_functions::selectors::{selector_sample, selector_sample_with_seed};
+use query_functions::window_functions::{
+    cumulative_sum, derivative, difference, moving_average, non_negative,
+};
 use std::sync::Arc;
+use technical_analysis::Indicator;
 
-mod cumulative_sum;
-mod derivative;
-mod difference;
-mod moving_average;
-mod non_negative;
+mod elapsed;
+mod holt_winters;
+mod integral;
 mod percent_row_number;
+mod technical_analysis;
 
 /// Definition of the `CUMULATIVE_SUM` user-defined window function.
 pub(crate) static CUMULATIVE_SUM: Lazy<WindowFunction> = Lazy::new(|| {
@@ -55,6 +59,63 @@ pub(crate) static DIFFERENCE: Lazy<WindowFunction> = Lazy::new(|| {
     )))
 });
 
+/// Definition of the `ELAPSED` user-defined window function.
+pub(crate) static ELAPSED: Lazy<WindowFunction> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(elapsed::return_type);
+    let partition_evaluator_factory: PartitionEvaluatorFactory =
+        Arc::new(elapsed::partition_evaluator_factory);
+
+    WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
+        elapsed::NAME,
+        &elapsed::SIGNATURE,
+        &return_type,
+        &partition_evaluator_factory,
+    )))
+});
+
+/// Definition of the `HOLT_WINTERS` user-defined window function.
+pub(crate) static HOLT_WINTERS: Lazy<WindowFunction> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(holt_winters::return_type);
+    let partition_evaluator_factory: PartitionEvaluatorFactory =
+        Arc::new(holt_winters::partition_evaluator_factory);
+
+    WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
+        holt_winters::NAME,
+        &holt_winters::SIGNATURE,
+        &return_type,
+        &partition_evaluator_factory,
+    )))
+});
+
+/// Definition of the `HOLT_WINTERS_WITH_FIT` user-defined window function.
+pub(crate) static HOLT_WINTERS_WITH_FIT: Lazy<WindowFunction> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(holt_winters::return_type);
+    let partition_evaluator_factory: PartitionEvaluatorFactory =
+        Arc::new(holt_winters::with_fit_partition_evaluator_factory);
+
+    WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
+        holt_winters::WITH_FIT_NAME,
+        &holt_winters::SIGNATURE,
+        &return_type,
+        &partition_evaluator_factory,
+    )))
+});
+
+/// Definition of the `INTEGRAL` user-defined window function, which calculates
+/// the area under the curve for each `GROUP BY TIME` window.
+pub(crate) static INTEGRAL: Lazy<WindowFunction> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(integral::return_type);
+    let partition_evaluator_factory: PartitionEvaluatorFactory =
+        Arc::new(integral::partition_evaluator_factory);
+
+    WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
+        integral::NAME,
+        &integral::SIGNATURE,
+        &return_type,
+        &partition_evaluator_factory,
+    )))
+});
+
 /// Definition of the `MOVING_AVERAGE` user-defined window function.
 pub(crate) static MOVING_AVERAGE: Lazy<WindowFunction> = Lazy::new(|| {
     let return_type: ReturnTypeFunction = Arc::new(moving_average::return_type);
@@ -69,8 +130,6 @@ pub(crate) static MOVING_AVERAGE: Lazy<WindowFunction> = Lazy::new(|| {
     )))
 });
 
-const NON_NEGATIVE_DERIVATIVE_NAME: &str = "non_negative_derivative";
-
 /// Definition of the `NON_NEGATIVE_DERIVATIVE` user-defined window function.
 pub(crate) static NON_NEGATIVE_DERIVATIVE: Lazy<WindowFunction> = Lazy::new(|| {
     let return_type: ReturnTypeFunction = Arc::new(derivative::return_type);
@@ -81,15 +140,13 @@ pub(crate) static NON_NEGATIVE_DERIVATIVE: Lazy<WindowFunction> = Lazy::new(|| {
     });
 
     WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
-        NON_NEGATIVE_DERIVATIVE_NAME,
+        derivative::NON_NEGATIVE_NAME,
         &derivative::SIGNATURE,
         &return_type,
         &partition_evaluator_factory,
     )))
 });
 
-const NON_NEGATIVE_DIFFERENCE_NAME: &str = "non_negative_difference";
-
 /// Definition of the `NON_NEGATIVE_DIFFERENCE` user-defined window function.
 pub(crate) static NON_NEGATIVE_DIFFERENCE: Lazy<WindowFunction> = Lazy::new(|| {
     let return_type: ReturnTypeFunction = Arc::new(difference::return_type);
@@ -100,7 +157,7 @@ pub(crate) static NON_NEGATIVE_DIFFERENCE: Lazy<WindowFunction> = Lazy::new(|| {
     });
 
     WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
-        NON_NEGATIVE_DIFFERENCE_NAME,
+        difference::NON_NEGATIVE_NAME,
         &difference::SIGNATURE,
         &return_type,
         &partition_evaluator_factory,
@@ -120,3 +177,60 @@ pub(crate) static PERCENT_ROW_NUMBER: Lazy<WindowFunction> = Lazy::new(|| {
         &partition_evaluator_factory,
     )))
 });
+
+/// Definition of the `SAMPLE` user-defined window function.
+pub(crate) static SAMPLE: Lazy<WindowFunction> =
+    Lazy::new(|| WindowFunction::WindowUDF(Arc::new(selector_sample())));
+
+/// Definition of the `SAMPLE` user-defined window function, with the
+/// rows selected by a random number generator seeded with `seed`.
+pub(crate) fn sample_with_seed(seed: u64) -> WindowFunction {
+    WindowFunction::WindowUDF(Arc::new(selector_sample_with_seed(seed)))
+}
+
+/// Definition of the `CHANDE_MOMENTUM_OSCILLATOR` user-defined window function.
+pub(crate) static CHANDE_MOMENTUM_OSCILLATOR: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::ChandeMomentumOscillator));
+
+/// Definition of the `DOUBLE_EXPONENTIAL_MOVING_AVERAGE` user-defined window function.
+pub(crate) static DOUBLE_EXPONENTIAL_MOVING_AVERAGE: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::DoubleExponentialMovingAverage));
+
+/// Definition of the `EXPONENTIAL_MOVING_AVERAGE` user-defined window function.
+pub(crate) static EXPONENTIAL_MOVING_AVERAGE: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::ExponentialMovingAverage));
+
+/// Definition of the `KAUFMANS_ADAPTIVE_MOVING_AVERAGE` user-defined window function.
+pub(crate) static KAUFMANS_ADAPTIVE_MOVING_AVERAGE: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::KaufmansAdaptiveMovingAverage));
+
+/// Definition of the `KAUFMANS_EFFICIENCY_RATIO` user-defined window function.
+pub(crate) static KAUFMANS_EFFICIENCY_RATIO: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::KaufmansEfficiencyRatio));
+
+/// Definition of the `RELATIVE_STRENGTH_INDEX` user-defined window function.
+pub(crate) static RELATIVE_STRENGTH_INDEX: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::RelativeStrengthIndex));
+
+/// Definition of the `TRIPLE_EXPONENTIAL_DERIVATIVE` user-defined window function.
+pub(crate) static TRIPLE_EXPONENTIAL_DERIVATIVE: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::TripleExponentialDerivative));
+
+/// Definition of the `TRIPLE_EXPONENTIAL_MOVING_AVERAGE` user-defined window function.
+pub(crate) static TRIPLE_EXPONENTIAL_MOVING_AVERAGE: Lazy<WindowFunction> =
+    Lazy::new(|| technical_analysis_window_function(Indicator::TripleExponentialMovingAverage));
+
+/// Create the definition of the user-defined window function for a
+/// technical analysis `indicator`.
+fn technical_analysis_window_function(indicator: Indicator) -> WindowFunction {
+    let return_type: ReturnTypeFunction = Arc::new(technical_analysis::return_type);
+    let partition_evaluator_factory: PartitionEvaluatorFactory =
+        Arc::new(move || technical_analysis::partition_evaluator_factory(indicator));
+
+    WindowFunction::WindowUDF(Arc::new(WindowUDF::new(
+        indicator.name(),
+        indicator.signature(),
+        &return_type,
+        &partition_evaluator_factory,
+    )))
+}
@@ -0,0 +1,84 @@
+use crate::{error, NUMERICS};
+use arrow::array::{Array, ArrayRef, Int64Array, TimestampNanosecondArray};
+use arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
+use datafusion::common::{downcast_value, DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::{PartitionEvaluator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+/// The name of the elapsed window function.
+pub(super) const NAME: &str = "elapsed";
+
+/// Valid signatures for the elapsed window function.
+pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .chain(&[DataType::Utf8, DataType::Boolean])
+            .map(|dt| {
+                TypeSignature::Exact(vec![
+                    dt.clone(),
+                    DataType::Interval(IntervalUnit::MonthDayNano),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                ])
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Calculate the return type given the function signature.
+pub(super) fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(DataType::Int64))
+}
+
+/// Create a new partition_evaluator_factory.
+pub(super) fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(ElapsedPartitionEvaluator {}))
+}
+
+/// PartitionEvaluator which returns the time elapsed between
+/// consecutive non-null input values, in the provided units.
+#[derive(Debug)]
+struct ElapsedPartitionEvaluator {}
+
+impl PartitionEvaluator for ElapsedPartitionEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], _num_rows: usize) -> Result<Arc<dyn Array>> {
+        assert_eq!(values.len(), 3);
+
+        let array = Arc::clone(&values[0]);
+        let times = downcast_value!(values[2], TimestampNanosecondArray);
+
+        // The second element of the values array is the second argument to
+        // the 'elapsed' function. This specifies the unit duration for the
+        // elapsed time.
+        //
+        // INVARIANT:
+        // The planner guarantees that the second argument is always a duration
+        // literal.
+        let unit = match ScalarValue::try_from_array(&values[1], 0)? {
+            ScalarValue::IntervalMonthDayNano(Some(unit)) if unit > 0 => unit as i64,
+            v => return error::internal(format!("invalid value ({v}) for ELAPSED unit argument")),
+        };
+
+        let mut last_time: Option<i64> = None;
+        let elapsed: Int64Array = (0..array.len())
+            .map(|idx| {
+                if array.is_null(idx) || times.is_null(idx) {
+                    return None;
+                }
+                let t = times.value(idx);
+                last_time.replace(t).map(|last| (t - last) / unit)
+            })
+            .collect();
+        Ok(Arc::new(elapsed))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,756 @@
+use crate::{error, NUMERICS};
+use arrow::array::{Array, ArrayRef, Float64Array, Int64Array, TimestampNanosecondArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
+use datafusion::common::{downcast_value, DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::{PartitionEvaluator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The name of the holt_winters window function.
+pub(super) const NAME: &str = "holt_winters";
+
+/// The name of the holt_winters_with_fit window function.
+pub(super) const WITH_FIT_NAME: &str = "holt_winters_with_fit";
+
+/// Valid signatures for the holt_winters and holt_winters_with_fit window functions.
+pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .map(|dt| {
+                TypeSignature::Exact(vec![
+                    dt.clone(),
+                    DataType::Int64,
+                    DataType::Int64,
+                    DataType::Interval(IntervalUnit::MonthDayNano),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                ])
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Calculate the return type given the function signature.
+pub(super) fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(DataType::Float64))
+}
+
+/// Create a new partition_evaluator_factory for holt_winters.
+pub(super) fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(HoltWintersPartitionEvaluator {
+        include_fit_data: false,
+    }))
+}
+
+/// Create a new partition_evaluator_factory for holt_winters_with_fit.
+pub(super) fn with_fit_partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(HoltWintersPartitionEvaluator {
+        include_fit_data: true,
+    }))
+}
+
+/// PartitionEvaluator which fits a Holt-Winters model to the input data and returns
+/// the forecasted values, and optionally the fitted values, in the rows with matching times.
+#[derive(Debug)]
+struct HoltWintersPartitionEvaluator {
+    include_fit_data: bool,
+}
+
+impl PartitionEvaluator for HoltWintersPartitionEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], num_rows: usize) -> Result<Arc<dyn Array>> {
+        assert_eq!(values.len(), 5, "HOLT_WINTERS expects five arguments");
+
+        // The second and third elements of the values array are the number of values to
+        // forecast and the length of a season. The fourth is the interval of the
+        // `GROUP BY TIME` clause.
+        //
+        // INVARIANT:
+        // The planner and rewriter guarantee that these arguments are always constants.
+        //
+        // See: FieldChecker::check_holt_winters
+        let h = downcast_value!(&values[1], Int64Array).value(0);
+        let m = downcast_value!(&values[2], Int64Array).value(0);
+        let interval = match ScalarValue::try_from_array(&values[3], 0)? {
+            ScalarValue::IntervalMonthDayNano(Some(v)) => v as i64,
+            v => return error::internal(format!("unexpected interval for holt_winters ({v})")),
+        };
+
+        let array = cast(&values[0], &DataType::Float64)?;
+        let array = downcast_value!(&array, Float64Array);
+        let times = downcast_value!(&values[4], TimestampNanosecondArray);
+
+        // INVARIANT:
+        // The planner extends the gap-filled time range by `h` intervals, so the last `h`
+        // intervals of the partition are past the end of the time range of the query. They
+        // provide the rows for the forecasted values, and are not input to the model.
+        let Some(max_time) = times.iter().flatten().max() else {
+            return Ok(Arc::new(Float64Array::from(vec![None; num_rows])));
+        };
+        let cutoff = max_time - h * interval;
+
+        let mut points = times
+            .iter()
+            .zip(array.iter())
+            .filter_map(|(t, v)| match (t, v) {
+                (Some(t), Some(v)) if t <= cutoff => Some((t, v)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        points.sort_by_key(|&(t, _)| t);
+
+        let forecast = HoltWinters::new(h as usize, m as usize, self.include_fit_data, interval)
+            .forecast(&points)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        Ok(Arc::new(
+            times
+                .iter()
+                .map(|t| t.and_then(|t| forecast.get(&t).copied()))
+                .collect::<Float64Array>(),
+        ))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Arbitrary weight, in the range `[0, 1]`, for the initial guesses of a non-seasonal model.
+const WEIGHT: f64 = 0.5;
+/// The lower bound of the grid of initial guesses for alpha, beta, gamma and phi.
+const GUESS_LOWER: f64 = 0.3;
+/// The upper bound of the grid of initial guesses for alpha, beta, gamma and phi.
+const GUESS_UPPER: f64 = 1.0;
+/// The step between initial guesses. The grid is N⁴, so N is kept small.
+const GUESS_STEP: f64 = 0.4;
+/// Epsilon value for the minimisation process.
+const EPSILON: f64 = 1.0e-4;
+
+/// Fits a seasonal triple-exponential smoothing model to a series of points and forecasts
+/// future values.
+///
+/// This is a port of the `FloatHoltWintersReducer` from InfluxDB 1.x, including its
+/// parameter fitting, so that both produce the same output for the same input.
+///
+/// See: <https://github.com/influxdata/influxdb/blob/1.8/query/functions.go>
+#[derive(Debug)]
+struct HoltWinters {
+    /// The number of values to forecast.
+    h: usize,
+    /// The length of a season, in intervals.
+    m: usize,
+    /// `true` if `m` describes a season.
+    seasonal: bool,
+    /// `true` if the fitted values for the input points are included in the output.
+    include_fit_data: bool,
+    /// The interval between points, in nanoseconds.
+    interval: i64,
+}
+
+impl HoltWinters {
+    fn new(h: usize, m: usize, include_fit_data: bool, interval: i64) -> Self {
+        Self {
+            h,
+            m,
+            seasonal: m >= 2,
+            include_fit_data,
+            interval,
+        }
+    }
+
+    /// Fit the model to `points`, which are `(time, value)` pairs sorted by time, and return
+    /// the forecasted points.
+    fn forecast(&self, points: &[(i64, f64)]) -> Vec<(i64, f64)> {
+        let l = points.len();
+        if l < 2 || self.seasonal && l < self.m || self.h == 0 || self.interval <= 0 {
+            return vec![];
+        }
+
+        let start = self.round_time(points[0].0);
+        let stop = self.round_time(points[l - 1].0);
+        if (stop - start) / self.interval <= 0 {
+            return vec![];
+        }
+
+        // Place each value in its interval, using NaN for intervals that have no value.
+        let mut y = Vec::with_capacity(((stop - start) / self.interval) as usize + 1);
+        y.push(points[0].1);
+        let mut t = start;
+        for &(time, value) in &points[1..] {
+            let rounded = self.round_time(time);
+            if rounded <= t {
+                // Drop values that occur for the same interval
+                continue;
+            }
+            t += self.interval;
+            while rounded != t {
+                y.push(f64::NAN);
+                t += self.interval;
+            }
+            y.push(value);
+        }
+
+        let model = Model {
+            y: &y,
+            seasonal: self.seasonal,
+        };
+        let m = self.m;
+        let value_at = |i: usize| y.get(i).copied().unwrap_or(f64::NAN);
+
+        // Starting guesses, skipping any missing values.
+        let l0 = if self.seasonal {
+            (0..m)
+                .map(value_at)
+                .filter(|v| !v.is_nan())
+                .fold(0.0, |acc, v| acc + (1.0 / m as f64) * v)
+        } else {
+            WEIGHT * y[0]
+        };
+
+        let b0 = if self.seasonal {
+            (0..m)
+                .take_while(|i| m + i < y.len())
+                .filter(|&i| !y[i].is_nan() && !y[m + i].is_nan())
+                .fold(0.0, |acc, i| acc + 1.0 / (m * m) as f64 * (y[m + i] - y[i]))
+        } else if !y[1].is_nan() {
+            WEIGHT * (y[1] - y[0])
+        } else {
+            0.0
+        };
+
+        let s = if self.seasonal {
+            (0..m)
+                .map(|i| match value_at(i) {
+                    v if v.is_nan() => 0.0,
+                    v => v / l0,
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let mut parameters = vec![0.0; 6];
+        parameters[4] = l0;
+        parameters[5] = b0;
+        parameters.extend(s);
+
+        // Determine the best fit for alpha, beta, gamma and phi, starting from a grid of guesses.
+        let mut min_sse = f64::INFINITY;
+        let mut best_params: Option<Vec<f64>> = None;
+        let mut alpha = GUESS_LOWER;
+        while alpha < GUESS_UPPER {
+            let mut beta = GUESS_LOWER;
+            while beta < GUESS_UPPER {
+                let mut gamma = GUESS_LOWER;
+                while gamma < GUESS_UPPER {
+                    let mut phi = GUESS_LOWER;
+                    while phi < GUESS_UPPER {
+                        parameters[0] = alpha;
+                        parameters[1] = beta;
+                        parameters[2] = gamma;
+                        parameters[3] = phi;
+                        let (sse, params) =
+                            nelder_mead(|params| model.sse(params), &parameters, EPSILON, 1.0);
+                        if sse < min_sse || best_params.is_none() {
+                            min_sse = sse;
+                            best_params = Some(params);
+                        }
+                        phi += GUESS_STEP;
+                    }
+                    gamma += GUESS_STEP;
+                }
+                beta += GUESS_STEP;
+            }
+            alpha += GUESS_STEP;
+        }
+        let mut best_params = best_params.expect("at least one guess");
+
+        let forecasted = model.forecast(self.h, &mut best_params);
+        if self.include_fit_data {
+            let start = points[0].0;
+            forecasted
+                .into_iter()
+                .enumerate()
+                .filter(|(_, v)| !v.is_nan())
+                .map(|(i, v)| (start + self.interval * i as i64, v))
+                .collect()
+        } else {
+            let stop = points[l - 1].0;
+            forecasted[y.len()..]
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| !v.is_nan())
+                .map(|(i, &v)| (stop + self.interval * (i as i64 + 1), v))
+                .collect()
+        }
+    }
+
+    /// Round `t` to the nearest multiple of the interval.
+    fn round_time(&self, t: i64) -> i64 {
+        let remainder = t % self.interval;
+        if remainder > self.interval / 2 {
+            (t / self.interval + 1) * self.interval
+        } else {
+            (t / self.interval) * self.interval
+        }
+    }
+}
+
+/// The observed values the model is fitted to.
+struct Model<'a> {
+    y: &'a [f64],
+    seasonal: bool,
+}
+
+impl<'a> Model<'a> {
+    /// Compute the fitted values followed by `h` forecasted values for the model `params`,
+    /// which are `[alpha, beta, gamma, phi, l0, b0, s0..sm]`.
+    ///
+    /// As in InfluxDB 1.x, `params` is constrained and its seasonal values are updated in place.
+    fn forecast(&self, h: usize, params: &mut [f64]) -> Vec<f64> {
+        constrain(params);
+        let (params, seasonals) = params.split_at_mut(6);
+        let (alpha, beta, gamma, phi) = (params[0], params[1], params[2], params[3]);
+        let mut phi_h = phi;
+        let mut y_t = self.y[0];
+        let mut l_t = params[4];
+        let mut b_t = params[5];
+
+        // seasonals is a ring buffer of past s_t values
+        let m = seasonals.len();
+        // Season index offset
+        let mut so = 0;
+        if self.seasonal {
+            if m == 1 {
+                seasonals[0] = 1.0;
+            }
+            so = m - 1;
+        }
+
+        let l = self.y.len();
+        let mut forecasted = vec![0.0; l + h];
+        forecasted[0] = y_t;
+        let (mut s_tm, mut s_tmh) = (1.0, 1.0);
+        for t in 1..l + h {
+            if self.seasonal {
+                let hm = t % m;
+                s_tm = seasonals[(t + so - m) % m];
+                s_tmh = seasonals[(t + so + hm - m) % m];
+            }
+
+            // Using the recursive relations compute the next values
+            let l_tp = l_t;
+            let b_tp = b_t;
+            l_t = alpha * (y_t / s_tm) + (1.0 - alpha) * (l_tp + phi * b_tp);
+            b_t = beta * (l_t - l_tp) + (1.0 - beta) * phi * b_tp;
+            let s_t = gamma * (y_t / (l_tp + phi * b_tp)) + (1.0 - gamma) * s_tm;
+            y_t = (l_t + phi_h * b_t) * s_tmh;
+
+            phi_h += phi.powf(t as f64);
+
+            if self.seasonal {
+                seasonals[(t + so) % m] = s_t;
+                so += 1;
+            }
+
+            forecasted[t] = y_t;
+        }
+
+        forecasted
+    }
+
+    /// Compute the sum of squared errors of the model `params` against the observed values.
+    fn sse(&self, params: &mut [f64]) -> f64 {
+        let forecasted = self.forecast(0, params);
+        let mut sse = 0.0;
+        for (&y, f) in self.y.iter().zip(forecasted) {
+            // Skip missing values since we cannot use them to compute an error.
+            if y.is_nan() {
+                continue;
+            }
+            // Penalise forecasted NaNs
+            if f.is_nan() {
+                return f64::INFINITY;
+            }
+            let diff = f - y;
+            sse += diff * diff;
+        }
+        sse
+    }
+}
+
+/// Constrain alpha, beta, gamma and phi to the range `[0, 1]`.
+fn constrain(params: &mut [f64]) {
+    for p in &mut params[..4] {
+        *p = p.clamp(0.0, 1.0);
+    }
+}
+
+/// The maximum number of iterations of the Nelder-Mead simplex method.
+const NM_MAX_ITERATIONS: usize = 1000;
+/// Reflection coefficient.
+const NM_ALPHA: f64 = 1.0;
+/// Contraction coefficient.
+const NM_BETA: f64 = 0.5;
+/// Expansion coefficient.
+const NM_GAMMA: f64 = 2.0;
+
+/// Minimise `objective` using the Nelder-Mead simplex method, starting from the point `start`,
+/// and return the minimum along with the parameters that produced it.
+///
+/// This is a port of the `neldermead` package of InfluxDB 1.x. As with the original, the
+/// objective function may modify the parameters it is evaluated with.
+fn nelder_mead(
+    objective: impl Fn(&mut [f64]) -> f64,
+    start: &[f64],
+    epsilon: f64,
+    scale: f64,
+) -> (f64, Vec<f64>) {
+    let n = start.len();
+    let nf = n as f64;
+
+    // vertices of the simplex
+    let mut v = vec![vec![0.0; n]; n + 1];
+    // value of the function at each vertex
+    let mut f = vec![0.0; n + 1];
+    // reflection, expansion, contraction and centroid coordinates
+    let mut vr = vec![0.0; n];
+    let mut ve = vec![0.0; n];
+    let mut vc = vec![0.0; n];
+    let mut vm = vec![0.0; n];
+
+    // create the initial simplex
+    let pn = scale * ((nf + 1.0).sqrt() - 1.0 + nf) / (nf * 2_f64.sqrt());
+    let qn = scale * ((nf + 1.0).sqrt() - 1.0) / (nf * 2_f64.sqrt());
+
+    v[0].copy_from_slice(start);
+    for (i, vertex) in v.iter_mut().enumerate().skip(1) {
+        for (j, x) in vertex.iter_mut().enumerate() {
+            *x = if i - 1 == j { pn } else { qn } + start[j];
+        }
+    }
+
+    for j in 0..=n {
+        f[j] = objective(&mut v[j]);
+    }
+
+    for _ in 0..NM_MAX_ITERATIONS {
+        // find the indexes of the largest and smallest values
+        let mut vg = 0;
+        let mut vs = 0;
+        for i in 0..=n {
+            if f[i] > f[vg] {
+                vg = i;
+            }
+            if f[i] < f[vs] {
+                vs = i;
+            }
+        }
+        // find the index of the second largest value
+        let mut vh = vs;
+        for i in 0..=n {
+            if f[i] > f[vh] && f[i] < f[vg] {
+                vh = i;
+            }
+        }
+
+        // calculate the centroid
+        for i in 0..n {
+            let cent: f64 = (0..=n).filter(|&m| m != vg).map(|m| v[m][i]).sum();
+            vm[i] = cent / nf;
+        }
+
+        // reflect vg to new vertex vr
+        for i in 0..n {
+            vr[i] = vm[i] + NM_ALPHA * (vm[i] - v[vg][i]);
+        }
+        let fr = objective(&mut vr);
+
+        if fr < f[vh] && fr >= f[vs] {
+            v[vg].copy_from_slice(&vr);
+            f[vg] = fr;
+        }
+
+        // investigate a step further in this direction
+        if fr < f[vs] {
+            for i in 0..n {
+                ve[i] = vm[i] + NM_GAMMA * (vr[i] - vm[i]);
+            }
+            let fe = objective(&mut ve);
+
+            if fe < fr {
+                v[vg].copy_from_slice(&ve);
+                f[vg] = fe;
+            } else {
+                v[vg].copy_from_slice(&vr);
+                f[vg] = fr;
+            }
+        }
+
+        // check to see if a contraction is necessary
+        if fr >= f[vh] {
+            if fr < f[vg] && fr >= f[vh] {
+                // perform outside contraction
+                for i in 0..n {
+                    vc[i] = vm[i] + NM_BETA * (vr[i] - vm[i]);
+                }
+            } else {
+                // perform inside contraction
+                for i in 0..n {
+                    vc[i] = vm[i] - NM_BETA * (vm[i] - v[vg][i]);
+                }
+            }
+            let fc = objective(&mut vc);
+
+            if fc < f[vg] {
+                v[vg].copy_from_slice(&vc);
+                f[vg] = fc;
+            } else {
+                // the contraction was not successful, so halve the distance from vs to all the
+                // vertices of the simplex and continue.
+                let best = v[vs].clone();
+                for (row, vertex) in v.iter_mut().enumerate() {
+                    if row != vs {
+                        for (x, b) in vertex.iter_mut().zip(&best) {
+                            *x = b + (*x - b) / 2.0;
+                        }
+                    }
+                }
+                f[vg] = objective(&mut v[vg]);
+                f[vh] = objective(&mut v[vh]);
+            }
+        }
+
+        // test for convergence
+        let favg = f.iter().sum::<f64>() / (nf + 1.0);
+        let s = f
+            .iter()
+            .map(|fi| (fi - favg).powi(2) / nf)
+            .sum::<f64>()
+            .sqrt();
+        if s < epsilon {
+            break;
+        }
+    }
+
+    // find the index of the smallest value
+    let mut vs = 0;
+    for i in 0..=n {
+        if f[i] < f[vs] {
+            vs = i;
+        }
+    }
+
+    let parameters = v[vs].clone();
+    let min = objective(&mut v[vs]);
+
+    (min, parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quarterly visitor nights of international tourists to Australia, from
+    /// <https://otexts.com/fpp2/holt-winters.html>.
+    const AUSTOURISTS: [f64; 48] = [
+        30.052513, 19.148496, 25.317692, 27.591437, 32.076456, 23.487961, 28.47594, 35.123753,
+        36.838485, 25.007017, 30.72223, 28.693759, 36.640986, 23.824609, 29.311683, 31.770309,
+        35.177877, 19.775244, 29.60175, 34.538842, 41.273599, 26.655862, 28.279859, 35.191153,
+        41.727458, 24.04185, 32.328103, 37.328708, 46.213153, 29.346326, 36.48291, 42.977719,
+        48.901525, 31.180221, 37.717881, 40.420211, 51.206863, 31.887228, 40.978263, 43.772491,
+        55.558567, 33.850915, 42.076383, 45.642292, 59.76678, 35.191877, 44.319737, 47.913736,
+    ];
+
+    /// Population of the United States in millions, from the decennial census of 1790 to 1970.
+    const USPOPULATION: [f64; 19] = [
+        3.93, 5.31, 7.24, 9.64, 12.90, 17.10, 23.20, 31.40, 39.80, 50.20, 62.90, 76.00, 92.00,
+        105.70, 122.80, 131.70, 151.30, 179.30, 203.20,
+    ];
+
+    /// Convert `values` to points at times `1..`.
+    fn points(values: &[f64]) -> Vec<(i64, f64)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (i as i64 + 1, v))
+            .collect()
+    }
+
+    fn assert_points(got: &[(i64, f64)], want: &[(i64, f64)]) {
+        assert_eq!(got.len(), want.len(), "got {got:?}, want {want:?}");
+        for (&(got_time, got_value), &(want_time, want_value)) in got.iter().zip(want) {
+            assert_eq!(got_time, want_time, "got {got:?}, want {want:?}");
+            assert!(
+                (got_value - want_value).abs() < 1e-5,
+                "at time {got_time}: got {got_value}, want {want_value}"
+            );
+        }
+    }
+
+    // The expected values in the following tests are the output of the `FloatHoltWintersReducer`
+    // of InfluxDB 1.x for the same input.
+
+    #[test]
+    fn forecast_trend() {
+        let got = HoltWinters::new(10, 0, false, 1).forecast(&points(&USPOPULATION));
+        assert_points(
+            &got,
+            &[
+                (20, 226.4167216525905),
+                (21, 253.73052878285205),
+                (22, 283.32649700397553),
+                (23, 315.37474308085984),
+                (24, 350.06311454009256),
+                (25, 387.59901328556873),
+                (26, 428.21144141893404),
+                (27, 472.1532969569147),
+                (28, 519.7039509590035),
+                (29, 571.1721419458248),
+            ],
+        );
+    }
+
+    #[test]
+    fn forecast_trend_with_fit() {
+        let got = HoltWinters::new(10, 0, true, 1).forecast(&points(&USPOPULATION));
+        assert_points(
+            &got,
+            &[
+                (1, 3.93),
+                (2, 4.957405463559748),
+                (3, 7.012210102535647),
+                (4, 10.099589257439924),
+                (5, 14.229926188104242),
+                (6, 19.418878968703797),
+                (7, 25.68749172281409),
+                (8, 33.062351305731305),
+                (9, 41.575791076125206),
+                (10, 51.26614395589263),
+                (11, 62.178047564264595),
+                (12, 74.36280483872488),
+                (13, 87.87880423073163),
+                (14, 102.79200429905801),
+                (15, 119.17648832929542),
+                (16, 137.11509549747296),
+                (17, 156.70013608313175),
+                (18, 178.03419933863566),
+                (19, 201.23106385518594),
+                (20, 226.4167216525905),
+                (21, 253.73052878285205),
+                (22, 283.32649700397553),
+                (23, 315.37474308085984),
+                (24, 350.06311454009256),
+                (25, 387.59901328556873),
+                (26, 428.21144141893404),
+                (27, 472.1532969569147),
+                (28, 519.7039509590035),
+                (29, 571.1721419458248),
+            ],
+        );
+    }
+
+    #[test]
+    fn forecast_with_missing_values() {
+        // The value for time 9 is missing
+        let points = points(&USPOPULATION)
+            .into_iter()
+            .filter(|&(t, _)| t != 9)
+            .collect::<Vec<_>>();
+        let got = HoltWinters::new(10, 0, true, 1).forecast(&points);
+        assert_points(
+            &got,
+            &[
+                (1, 3.93),
+                (2, 4.617120400849855),
+                (3, 6.813272192884149),
+                (4, 10.019363397829453),
+                (5, 14.246635234112372),
+                (6, 19.51170992324891),
+                (7, 25.836714065311938),
+                (8, 33.249450412890894),
+                (9, 41.78361992342149),
+                (10, 51.47909658934975),
+                (11, 62.38225820123027),
+                (12, 74.54637690093963),
+                (13, 88.03207414194274),
+                (14, 102.90784550325938),
+                (15, 119.25066171700938),
+                (16, 137.1466532812919),
+                (17, 156.69188715766325),
+                (18, 177.9932453148075),
+                (19, 201.16941629892924),
+                (20, 226.3520126117738),
+                (21, 253.68682848739743),
+                (22, 283.33525471144674),
+                (23, 315.4758694592092),
+                (24, 350.30622678420883),
+                (25, 388.0448674174148),
+                (26, 428.9335799957439),
+                (27, 473.2399447940413),
+                (28, 521.260196564272),
+                (29, 573.3224482787483),
+            ],
+        );
+    }
+
+    /// The forecast for [`AUSTOURISTS`] with a season of four quarters.
+    const AUSTOURISTS_FORECAST: [(i64, f64); 10] = [
+        (49, 51.85064132137853),
+        (50, 43.26055282315273),
+        (51, 41.827258044814464),
+        (52, 54.3990354591749),
+        (53, 54.62334472770803),
+        (54, 45.57155693625209),
+        (55, 44.06051240252263),
+        (56, 57.30029870759433),
+        (57, 57.53591513519172),
+        (58, 47.999008139396096),
+    ];
+
+    #[test]
+    fn forecast_seasonal() {
+        let got = HoltWinters::new(10, 4, false, 1).forecast(&points(&AUSTOURISTS));
+        assert_points(&got, &AUSTOURISTS_FORECAST);
+    }
+
+    #[test]
+    fn forecast_seasonal_with_fit() {
+        let got = HoltWinters::new(10, 4, true, 1).forecast(&points(&AUSTOURISTS));
+        assert_eq!(got.len(), AUSTOURISTS.len() + 10);
+        assert_points(
+            &got[..8],
+            &[
+                (1, 30.052513),
+                (2, 25.493348660152726),
+                (3, 23.580555112434418),
+                (4, 29.778552485158475),
+                (5, 29.47875512677692),
+                (6, 24.441967670788195),
+                (7, 23.528570704444896),
+                (8, 30.52496555532255),
+            ],
+        );
+        assert_points(&got[AUSTOURISTS.len()..], &AUSTOURISTS_FORECAST);
+    }
+
+    #[test]
+    fn forecast_insufficient_points() {
+        let model = HoltWinters::new(3, 4, false, 10);
+        assert!(model.forecast(&[]).is_empty());
+        assert!(model.forecast(&[(0, 1.0)]).is_empty());
+        // Fewer points than the length of a season
+        assert!(model.forecast(&[(0, 1.0), (10, 2.0), (20, 3.0)]).is_empty());
+        // All points are in the same interval
+        let model = HoltWinters::new(3, 0, false, 10);
+        assert!(model.forecast(&[(0, 1.0), (1, 2.0)]).is_empty());
+    }
+}
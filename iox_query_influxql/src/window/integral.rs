@@ -0,0 +1,316 @@
+use crate::{error, NUMERICS};
+use arrow::array::{Array, ArrayRef, Float64Array, TimestampNanosecondArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
+use datafusion::common::{downcast_value, DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::{PartitionEvaluator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The name of the integral window function.
+pub(super) const NAME: &str = "integral";
+
+/// Valid signatures for the integral window function.
+pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .map(|dt| {
+                TypeSignature::Exact(vec![
+                    dt.clone(),
+                    DataType::Interval(IntervalUnit::MonthDayNano),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                    DataType::Interval(IntervalUnit::MonthDayNano),
+                ])
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Calculate the return type given the function signature.
+pub(super) fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(DataType::Float64))
+}
+
+/// Create a new partition_evaluator_factory.
+pub(super) fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(IntegralPartitionEvaluator {}))
+}
+
+/// PartitionEvaluator which calculates the area under the curve of a series
+/// for each `GROUP BY TIME` window, and returns it in every row of the window.
+///
+/// The arguments are the value, the unit of the result, the time, the start
+/// of the window of the row and the `GROUP BY TIME` interval. Rows must be
+/// ordered by time.
+#[derive(Debug)]
+struct IntegralPartitionEvaluator {}
+
+impl PartitionEvaluator for IntegralPartitionEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], _num_rows: usize) -> Result<Arc<dyn Array>> {
+        assert_eq!(values.len(), 5, "INTEGRAL expects five arguments");
+
+        // INVARIANT:
+        // The planner guarantees that the unit and interval arguments are
+        // always positive duration literals.
+        let unit = match ScalarValue::try_from_array(&values[1], 0)? {
+            ScalarValue::IntervalMonthDayNano(Some(v)) if v > 0 => v as f64,
+            v => return error::internal(format!("invalid value ({v}) for INTEGRAL unit argument")),
+        };
+        let interval = match ScalarValue::try_from_array(&values[4], 0)? {
+            ScalarValue::IntervalMonthDayNano(Some(v)) if v > 0 => v as i64,
+            v => return error::internal(format!("invalid interval ({v}) for INTEGRAL")),
+        };
+
+        let array = cast(&values[0], &DataType::Float64)?;
+        let array = downcast_value!(&array, Float64Array);
+        let times = downcast_value!(&values[2], TimestampNanosecondArray);
+        let windows = downcast_value!(&values[3], TimestampNanosecondArray);
+
+        let points = times
+            .iter()
+            .zip(windows.iter())
+            .zip(array.iter())
+            .filter_map(|((t, w), v)| match (t, w, v) {
+                (Some(t), Some(w), Some(v)) => Some(Point {
+                    time: t,
+                    value: v,
+                    window: w,
+                }),
+                _ => None,
+            });
+        let areas = integral(points, unit, interval);
+
+        Ok(Arc::new(
+            windows
+                .iter()
+                .map(|w| w.and_then(|w| areas.get(&w).copied()))
+                .collect::<Float64Array>(),
+        ))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// A point of the series, along with the start time of its window.
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    time: i64,
+    value: f64,
+    window: i64,
+}
+
+/// Calculate the area under the curve described by `points`, which must be
+/// sorted by time, for each window of `interval` nanoseconds, in multiples of
+/// `unit` nanoseconds. The result maps the start of each window to its area.
+///
+/// This is a port of the `FloatIntegralReducer` from InfluxDB 1.x, so the area
+/// of a window is interpolated up to its end when the series has a point in a
+/// later window, and the area from the end of the previous window with a point
+/// is attributed to the next window with a point. A window with a single point
+/// at its start, and no later points, has no area and is omitted.
+///
+/// See: <https://github.com/influxdata/influxdb/blob/1.8/query/functions.go>
+fn integral(
+    points: impl IntoIterator<Item = Point>,
+    unit: f64,
+    interval: i64,
+) -> HashMap<i64, f64> {
+    let mut areas = HashMap::new();
+    let mut points = points.into_iter();
+    let Some(first) = points.next() else {
+        return areas;
+    };
+
+    let mut prev = (first.time, first.value);
+    let (mut start, mut end) = (first.window, first.window + interval);
+    let mut sum = 0.0;
+    for p in points {
+        // Points sharing a timestamp do not contribute any area between them.
+        if p.time == prev.0 {
+            prev = (p.time, p.value);
+            continue;
+        }
+
+        if p.time >= end {
+            // Interpolate the area up to the end of the window
+            if prev.0 != end {
+                let value = linear(end, prev, (p.time, p.value));
+                let elapsed = (end - prev.0) as f64 / unit;
+                sum += 0.5 * (value + prev.1) * elapsed;
+                prev = (end, value);
+            }
+
+            areas.insert(start, sum);
+            (start, end) = (p.window, p.window + interval);
+            sum = 0.0;
+        }
+
+        let elapsed = (p.time - prev.0) as f64 / unit;
+        sum += 0.5 * (p.value + prev.1) * elapsed;
+        prev = (p.time, p.value);
+    }
+
+    // If the last point is at the start of its window, there is no area
+    // within the window.
+    if prev.0 != start {
+        areas.insert(start, sum);
+    }
+    areas
+}
+
+/// Returns the value at `time` of the line through the points `prev` and `next`.
+fn linear(time: i64, prev: (i64, f64), next: (i64, f64)) -> f64 {
+    let m = (next.1 - prev.1) / (next.0 - prev.0) as f64;
+    let x = (time - prev.0) as f64;
+    m * x + prev.1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SECOND: i64 = 1_000_000_000;
+
+    /// Returns the points for `(time, value)` pairs, with windows of `interval` nanoseconds.
+    fn points(points: &[(i64, f64)], interval: i64) -> Vec<Point> {
+        points
+            .iter()
+            .map(|&(time, value)| Point {
+                time,
+                value,
+                window: time - time.rem_euclid(interval),
+            })
+            .collect()
+    }
+
+    fn sorted(areas: HashMap<i64, f64>) -> Vec<(i64, f64)> {
+        let mut areas = areas.into_iter().collect::<Vec<_>>();
+        areas.sort_by_key(|&(t, _)| t);
+        areas
+    }
+
+    // The points and expected values of `group_by_time`, `group_by_time_interpolated`
+    // and `duplicate_times` are those of the INTEGRAL tests of InfluxDB 1.x.
+
+    #[test]
+    fn group_by_time() {
+        // SELECT integral(value) FROM cpu WHERE time > 0s AND time < 60s GROUP BY time(20s)
+        let interval = 20 * SECOND;
+        let points = points(
+            &[
+                (10 * SECOND, 20.0),
+                (15 * SECOND, 10.0),
+                (20 * SECOND, 0.0),
+                (30 * SECOND, -10.0),
+            ],
+            interval,
+        );
+        assert_eq!(
+            sorted(integral(points, SECOND as f64, interval)),
+            vec![(0, 100.0), (20 * SECOND, -50.0)]
+        );
+    }
+
+    #[test]
+    fn group_by_time_interpolated() {
+        // The area up to the end of the first window is interpolated from the
+        // points at 15s and 25s.
+        let interval = 20 * SECOND;
+        let points = points(
+            &[
+                (10 * SECOND, 20.0),
+                (15 * SECOND, 10.0),
+                (25 * SECOND, 0.0),
+                (30 * SECOND, -10.0),
+            ],
+            interval,
+        );
+        assert_eq!(
+            sorted(integral(points, SECOND as f64, interval)),
+            vec![(0, 112.5), (20 * SECOND, -12.5)]
+        );
+    }
+
+    #[test]
+    fn group_by_time_with_unit() {
+        let interval = 20 * SECOND;
+        let points = points(
+            &[
+                (10 * SECOND, 20.0),
+                (15 * SECOND, 10.0),
+                (25 * SECOND, 0.0),
+                (30 * SECOND, -10.0),
+            ],
+            interval,
+        );
+        assert_eq!(
+            sorted(integral(points, (10 * SECOND) as f64, interval)),
+            vec![(0, 11.25), (20 * SECOND, -1.25)]
+        );
+    }
+
+    #[test]
+    fn group_by_time_empty_windows() {
+        // The area between the end of the first window and the point at 45s
+        // belongs to the window of that point, and the empty window at 20s
+        // has no area.
+        let interval = 20 * SECOND;
+        let points = points(
+            &[
+                (10 * SECOND, 10.0),
+                (15 * SECOND, 10.0),
+                (45 * SECOND, 10.0),
+            ],
+            interval,
+        );
+        assert_eq!(
+            sorted(integral(points, SECOND as f64, interval)),
+            vec![(0, 100.0), (40 * SECOND, 250.0)]
+        );
+    }
+
+    #[test]
+    fn group_by_time_last_point_at_window_start() {
+        let interval = 20 * SECOND;
+        let points = points(&[(10 * SECOND, 20.0), (20 * SECOND, 0.0)], interval);
+        assert_eq!(
+            sorted(integral(points, SECOND as f64, interval)),
+            vec![(0, 100.0)]
+        );
+    }
+
+    #[test]
+    fn duplicate_times() {
+        // The first of the points at 5s closes the first trapezoid and the
+        // last opens the second.
+        let interval = 20 * SECOND;
+        let points = points(
+            &[
+                (0, 20.0),
+                (5 * SECOND, 10.0),
+                (5 * SECOND, 30.0),
+                (10 * SECOND, 40.0),
+            ],
+            interval,
+        );
+        assert_eq!(
+            sorted(integral(points, SECOND as f64, interval)),
+            vec![(0, 250.0)]
+        );
+    }
+
+    #[test]
+    fn no_points() {
+        assert!(integral(vec![], SECOND as f64, SECOND).is_empty());
+    }
+}
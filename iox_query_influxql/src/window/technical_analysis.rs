@@ -0,0 +1,1293 @@
+//! Technical analysis window functions, such as `EXPONENTIAL_MOVING_AVERAGE`
+//! and `RELATIVE_STRENGTH_INDEX`.
+//!
+//! The algorithms are ports of the implementations used by InfluxDB 1.x,
+//! which can be found in
+//! <https://github.com/influxdata/influxdb/tree/1.8/query/internal/gota>.
+
+use crate::{error, NUMERICS};
+use arrow::array::{Array, ArrayRef, Float64Array, Int64Array, StringArray};
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+use datafusion::common::{downcast_value, DataFusionError, Result};
+use datafusion::logical_expr::{PartitionEvaluator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+/// Valid signatures for the technical analysis window functions that accept
+/// a warmup type.
+///
+/// The arguments are the input value, the period, the hold period and the
+/// warmup type.
+static WARMUP_SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .map(|dt| {
+                TypeSignature::Exact(vec![
+                    dt.clone(),
+                    DataType::Int64,
+                    DataType::Int64,
+                    DataType::Utf8,
+                ])
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Valid signatures for the Kaufman's window functions.
+///
+/// The arguments are the input value, the period and the hold period.
+static KAUFMANS_SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .map(|dt| TypeSignature::Exact(vec![dt.clone(), DataType::Int64, DataType::Int64]))
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// The technical analysis indicators implemented by this module.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Indicator {
+    ExponentialMovingAverage,
+    DoubleExponentialMovingAverage,
+    TripleExponentialMovingAverage,
+    TripleExponentialDerivative,
+    RelativeStrengthIndex,
+    ChandeMomentumOscillator,
+    KaufmansEfficiencyRatio,
+    KaufmansAdaptiveMovingAverage,
+}
+
+impl Indicator {
+    /// The name of the window function.
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            Self::ExponentialMovingAverage => "exponential_moving_average",
+            Self::DoubleExponentialMovingAverage => "double_exponential_moving_average",
+            Self::TripleExponentialMovingAverage => "triple_exponential_moving_average",
+            Self::TripleExponentialDerivative => "triple_exponential_derivative",
+            Self::RelativeStrengthIndex => "relative_strength_index",
+            Self::ChandeMomentumOscillator => "chande_momentum_oscillator",
+            Self::KaufmansEfficiencyRatio => "kaufmans_efficiency_ratio",
+            Self::KaufmansAdaptiveMovingAverage => "kaufmans_adaptive_moving_average",
+        }
+    }
+
+    /// Valid signatures for the window function.
+    pub(super) fn signature(&self) -> &'static Signature {
+        match self {
+            Self::KaufmansEfficiencyRatio | Self::KaufmansAdaptiveMovingAverage => {
+                &KAUFMANS_SIGNATURE
+            }
+            _ => &WARMUP_SIGNATURE,
+        }
+    }
+
+    /// Create the algorithm for the indicator.
+    fn algorithm(&self, period: usize, warmup: Option<&str>) -> Result<Box<dyn Algorithm>> {
+        let warmup = match warmup {
+            None | Some("exponential") => Some(WarmupType::Exponential),
+            Some("simple") => Some(WarmupType::Simple),
+            Some("none") => None,
+            Some(v) => return error::internal(format!("invalid warmup type '{v}'")),
+        };
+
+        Ok(match (self, warmup) {
+            (Self::ChandeMomentumOscillator, None) => Box::new(Cmo::new(period)),
+            (Self::ChandeMomentumOscillator, Some(wt)) => Box::new(Cmos::new(period, wt)),
+            (Self::KaufmansEfficiencyRatio, _) => Box::new(Ker::new(period)),
+            (Self::KaufmansAdaptiveMovingAverage, _) => Box::new(Kama::new(period)),
+            (_, None) => {
+                return error::internal(format!("warmup type 'none' is not valid for {self:?}"))
+            }
+            (Self::ExponentialMovingAverage, Some(wt)) => Box::new(Ema::new(period, wt)),
+            (Self::DoubleExponentialMovingAverage, Some(wt)) => Box::new(Dema::new(period, wt)),
+            (Self::TripleExponentialMovingAverage, Some(wt)) => Box::new(Tema::new(period, wt)),
+            (Self::TripleExponentialDerivative, Some(wt)) => Box::new(Trix::new(period, wt)),
+            (Self::RelativeStrengthIndex, Some(wt)) => Box::new(Rsi::new(period, wt)),
+        })
+    }
+}
+
+/// Calculate the return type given the function signature.
+pub(super) fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(DataType::Float64))
+}
+
+/// Create a new partition_evaluator_factory for the indicator.
+pub(super) fn partition_evaluator_factory(
+    indicator: Indicator,
+) -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(TechnicalAnalysisPartitionEvaluator { indicator }))
+}
+
+/// PartitionEvaluator which feeds each non-null input value to the
+/// algorithm of a technical analysis indicator.
+#[derive(Debug)]
+struct TechnicalAnalysisPartitionEvaluator {
+    indicator: Indicator,
+}
+
+impl PartitionEvaluator for TechnicalAnalysisPartitionEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], _num_rows: usize) -> Result<Arc<dyn Array>> {
+        assert!(values.len() == 3 || values.len() == 4);
+
+        // INVARIANT:
+        // The planner and rewriter guarantee that the period, hold period and
+        // warmup type arguments are always constants.
+        //
+        // See: FieldChecker::check_exponential_moving_average
+        let period = downcast_value!(&values[1], Int64Array).value(0);
+        let hold_period = downcast_value!(&values[2], Int64Array).value(0);
+        let warmup = match values.get(3) {
+            Some(array) => Some(downcast_value!(array, StringArray).value(0)),
+            None => None,
+        };
+
+        let Ok(period) = usize::try_from(period) else {
+            return error::internal(format!("invalid period {period}"));
+        };
+        let mut algorithm = self.indicator.algorithm(period, warmup)?;
+
+        // Values are not produced until the number of input values
+        // exceeds the hold period, which defaults to the number of
+        // values required to warm up the algorithm.
+        let hold_period = match hold_period {
+            -1 => algorithm.warm_count(),
+            v => usize::try_from(v)
+                .or_else(|_| error::internal(format!("invalid hold period {v}")))?,
+        };
+
+        let array = cast(&values[0], &DataType::Float64)?;
+        let array = downcast_value!(array, Float64Array);
+        let mut count = 0;
+        let output: Float64Array = array
+            .iter()
+            .map(|v| {
+                v.and_then(|v| {
+                    let v = algorithm.add(v);
+                    count += 1;
+                    (count > hold_period).then_some(v)
+                })
+            })
+            .collect();
+        Ok(Arc::new(output))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// An algorithm that computes an output value for each input value.
+trait Algorithm: std::fmt::Debug + Send {
+    /// Add a new input value and return the computed output value.
+    fn add(&mut self, v: f64) -> f64;
+
+    /// The number of input values required before the algorithm
+    /// produces accurate results.
+    fn warm_count(&self) -> usize;
+}
+
+/// How an exponential moving average is calculated before it has
+/// received enough input values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WarmupType {
+    /// Use an exponential moving average, with the alpha value
+    /// scaled to prevent excessive weighting of the initial values.
+    Exponential,
+    /// Use a simple average of the values received so far.
+    Simple,
+}
+
+/// Exponential moving average.
+#[derive(Debug, Clone)]
+struct Ema {
+    period: usize,
+    last: f64,
+    count: usize,
+    alpha: f64,
+    warmup: WarmupType,
+}
+
+impl Ema {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        Self {
+            period,
+            last: 0.0,
+            count: 0,
+            alpha: 2.0 / (period + 1) as f64,
+            warmup,
+        }
+    }
+
+    fn warmed(&self) -> bool {
+        self.count == self.period
+    }
+
+    /// Returns `true` if the output of this average should be used as
+    /// the input to the next average of a multi-stage average.
+    fn feeds_next_stage(&self) -> bool {
+        self.warmed() || self.warmup == WarmupType::Exponential
+    }
+}
+
+impl Algorithm for Ema {
+    fn add(&mut self, v: f64) -> f64 {
+        let avg = if self.count == 0 {
+            v
+        } else if self.warmed() {
+            (v - self.last) * self.alpha + self.last
+        } else {
+            match self.warmup {
+                WarmupType::Simple => (self.last * self.count as f64 + v) / (self.count + 1) as f64,
+                WarmupType::Exponential => {
+                    let alpha = 2.0 / (self.count + 2) as f64;
+                    (v - self.last) * alpha + self.last
+                }
+            }
+        };
+
+        self.last = avg;
+        if self.count < self.period {
+            self.count += 1;
+        }
+        avg
+    }
+
+    fn warm_count(&self) -> usize {
+        self.period.saturating_sub(1)
+    }
+}
+
+/// Double exponential moving average.
+#[derive(Debug)]
+struct Dema {
+    ema1: Ema,
+    ema2: Ema,
+}
+
+impl Dema {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        Self {
+            ema1: Ema::new(period, warmup),
+            ema2: Ema::new(period, warmup),
+        }
+    }
+}
+
+impl Algorithm for Dema {
+    fn add(&mut self, v: f64) -> f64 {
+        let avg1 = self.ema1.add(v);
+        let avg2 = if self.ema1.feeds_next_stage() {
+            self.ema2.add(avg1)
+        } else {
+            avg1
+        };
+        2.0 * avg1 - avg2
+    }
+
+    fn warm_count(&self) -> usize {
+        match self.ema1.warmup {
+            WarmupType::Exponential => self.ema1.warm_count(),
+            WarmupType::Simple => self.ema1.warm_count() + self.ema2.warm_count(),
+        }
+    }
+}
+
+/// Triple exponential moving average.
+#[derive(Debug)]
+struct Tema {
+    ema1: Ema,
+    ema2: Ema,
+    ema3: Ema,
+}
+
+impl Tema {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        Self {
+            ema1: Ema::new(period, warmup),
+            ema2: Ema::new(period, warmup),
+            ema3: Ema::new(period, warmup),
+        }
+    }
+}
+
+impl Algorithm for Tema {
+    fn add(&mut self, v: f64) -> f64 {
+        let avg1 = self.ema1.add(v);
+        let (avg2, avg3) = if self.ema1.feeds_next_stage() {
+            let avg2 = self.ema2.add(avg1);
+            let avg3 = if self.ema2.feeds_next_stage() {
+                self.ema3.add(avg2)
+            } else {
+                avg2
+            };
+            (avg2, avg3)
+        } else {
+            (avg1, avg1)
+        };
+        3.0 * avg1 - 3.0 * avg2 + avg3
+    }
+
+    fn warm_count(&self) -> usize {
+        match self.ema1.warmup {
+            WarmupType::Exponential => self.ema1.warm_count(),
+            WarmupType::Simple => {
+                self.ema1.warm_count() + self.ema2.warm_count() + self.ema3.warm_count()
+            }
+        }
+    }
+}
+
+/// Triple exponential derivative (TRIX), which is the percentage rate
+/// of change of a triple exponential moving average.
+#[derive(Debug)]
+struct Trix {
+    ema1: Ema,
+    ema2: Ema,
+    ema3: Ema,
+    last: f64,
+}
+
+impl Trix {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        Self {
+            ema1: Ema::new(period, warmup),
+            ema2: Ema::new(period, warmup),
+            ema3: Ema::new(period, warmup),
+            last: 0.0,
+        }
+    }
+}
+
+impl Algorithm for Trix {
+    fn add(&mut self, v: f64) -> f64 {
+        let mut cur = self.ema1.add(v);
+        if self.ema1.feeds_next_stage() {
+            cur = self.ema2.add(cur);
+            if self.ema2.feeds_next_stage() {
+                cur = self.ema3.add(cur);
+            }
+        }
+
+        let rate = ((cur / self.last) - 1.0) * 100.0;
+        self.last = cur;
+        rate
+    }
+
+    fn warm_count(&self) -> usize {
+        match self.ema1.warmup {
+            WarmupType::Exponential => self.ema1.warm_count() + 1,
+            WarmupType::Simple => self.ema1.warm_count() * 3 + 1,
+        }
+    }
+}
+
+/// Tracks the exponential moving averages of the upward and downward
+/// movements of the input values.
+#[derive(Debug)]
+struct UpDownEma {
+    up: Ema,
+    down: Ema,
+    last: f64,
+}
+
+impl UpDownEma {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        let mut ema = Ema::new(period + 1, warmup);
+        ema.alpha = 1.0 / period as f64;
+        Self {
+            up: ema.clone(),
+            down: ema,
+            last: 0.0,
+        }
+    }
+
+    /// Add a new input value and return the averages of the upward and
+    /// downward movements.
+    fn add(&mut self, v: f64) -> (f64, f64) {
+        let (up, down) = if v > self.last {
+            (v - self.last, 0.0)
+        } else if v < self.last {
+            (0.0, self.last - v)
+        } else {
+            (0.0, 0.0)
+        };
+        self.last = v;
+        (self.up.add(up), self.down.add(down))
+    }
+
+    fn warm_count(&self) -> usize {
+        self.up.warm_count()
+    }
+}
+
+/// Relative strength index.
+#[derive(Debug)]
+struct Rsi(UpDownEma);
+
+impl Rsi {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        Self(UpDownEma::new(period, warmup))
+    }
+}
+
+impl Algorithm for Rsi {
+    fn add(&mut self, v: f64) -> f64 {
+        let (up, down) = self.0.add(v);
+        100.0 - (100.0 / (1.0 + up / down))
+    }
+
+    fn warm_count(&self) -> usize {
+        self.0.warm_count()
+    }
+}
+
+/// Chande momentum oscillator, smoothed using exponential moving
+/// averages, which is the version used by TA-Lib.
+#[derive(Debug)]
+struct Cmos(UpDownEma);
+
+impl Cmos {
+    fn new(period: usize, warmup: WarmupType) -> Self {
+        Self(UpDownEma::new(period, warmup))
+    }
+}
+
+impl Algorithm for Cmos {
+    fn add(&mut self, v: f64) -> f64 {
+        let (up, down) = self.0.add(v);
+        100.0 * ((up - down) / (up + down))
+    }
+
+    fn warm_count(&self) -> usize {
+        self.0.warm_count()
+    }
+}
+
+/// A price and its difference from the previous price.
+#[derive(Debug, Default, Clone, Copy)]
+struct PriceDiff {
+    price: f64,
+    diff: f64,
+}
+
+/// Chande momentum oscillator, calculated using the sums of the
+/// upward and downward movements over the period.
+#[derive(Debug)]
+struct Cmo {
+    points: Vec<PriceDiff>,
+    sum_up: f64,
+    sum_down: f64,
+    count: usize,
+    /// Index of the newest point.
+    idx: usize,
+}
+
+impl Cmo {
+    fn new(period: usize) -> Self {
+        Self {
+            points: vec![PriceDiff::default(); period],
+            sum_up: 0.0,
+            sum_down: 0.0,
+            count: 0,
+            idx: 0,
+        }
+    }
+}
+
+impl Algorithm for Cmo {
+    fn add(&mut self, v: f64) -> f64 {
+        let idx_oldest = (self.idx + 1) % self.points.len();
+
+        let mut diff = 0.0;
+        if self.count != 0 {
+            diff = v - self.points[self.idx].price;
+            if diff > 0.0 {
+                self.sum_up += diff;
+            } else if diff < 0.0 {
+                self.sum_down -= diff;
+            }
+        }
+
+        let mut out = 0.0;
+        if self.sum_up != 0.0 || self.sum_down != 0.0 {
+            out = 100.0 * ((self.sum_up - self.sum_down) / (self.sum_up + self.sum_down));
+        }
+
+        let oldest = self.points[idx_oldest];
+        if oldest.diff > 0.0 {
+            self.sum_up -= oldest.diff;
+        } else if oldest.diff < 0.0 {
+            self.sum_down += oldest.diff;
+        }
+
+        self.points[idx_oldest] = PriceDiff { price: v, diff };
+        self.idx = idx_oldest;
+
+        if self.count < self.points.len() + 2 {
+            self.count += 1;
+        }
+
+        out
+    }
+
+    fn warm_count(&self) -> usize {
+        self.points.len()
+    }
+}
+
+/// Kaufman's efficiency ratio.
+#[derive(Debug)]
+struct Ker {
+    points: Vec<PriceDiff>,
+    noise: f64,
+    count: usize,
+    /// Index of the newest point.
+    idx: usize,
+}
+
+impl Ker {
+    fn new(period: usize) -> Self {
+        Self {
+            points: vec![PriceDiff::default(); period],
+            noise: 0.0,
+            count: 0,
+            idx: 0,
+        }
+    }
+
+    fn warmed(&self) -> bool {
+        self.count == self.points.len() + 1
+    }
+}
+
+impl Algorithm for Ker {
+    fn add(&mut self, v: f64) -> f64 {
+        let idx_oldest = (self.idx + 1) % self.points.len();
+
+        let signal = (v - self.points[idx_oldest].price).abs();
+
+        let point = PriceDiff {
+            price: v,
+            diff: (v - self.points[self.idx].price).abs(),
+        };
+        self.noise -= self.points[idx_oldest].diff;
+        self.noise += point.diff;
+
+        self.idx = idx_oldest;
+        self.points[self.idx] = point;
+
+        if !self.warmed() {
+            self.count += 1;
+        }
+
+        if signal == 0.0 || self.noise == 0.0 {
+            0.0
+        } else {
+            signal / self.noise
+        }
+    }
+
+    fn warm_count(&self) -> usize {
+        self.points.len()
+    }
+}
+
+/// Kaufman's adaptive moving average.
+#[derive(Debug)]
+struct Kama {
+    ker: Ker,
+    last: f64,
+}
+
+impl Kama {
+    fn new(period: usize) -> Self {
+        Self {
+            ker: Ker::new(period),
+            last: 0.0,
+        }
+    }
+}
+
+impl Algorithm for Kama {
+    fn add(&mut self, v: f64) -> f64 {
+        if !self.ker.warmed() {
+            // initialize with the last value
+            self.last = self.ker.points[self.ker.idx].price;
+        }
+
+        const FAST: f64 = 2.0 / (2.0 + 1.0);
+        const SLOW: f64 = 2.0 / (30.0 + 1.0);
+
+        let er = self.ker.add(v);
+        let sc = (er * (FAST - SLOW) + SLOW).powi(2);
+
+        self.last += sc * (v - self.last);
+        self.last
+    }
+
+    fn warm_count(&self) -> usize {
+        self.ker.warm_count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Feed `values` to `algorithm`, returning the outputs after the
+    /// algorithm has warmed up, as is done by the partition evaluator
+    /// with the default hold period.
+    fn run(mut algorithm: Box<dyn Algorithm>, values: &[f64]) -> Vec<f64> {
+        let hold = algorithm.warm_count();
+        values
+            .iter()
+            .map(|v| algorithm.add(*v))
+            .skip(hold)
+            .collect()
+    }
+
+    fn assert_approx_eq(got: &[f64], want: &[f64]) {
+        assert_eq!(got.len(), want.len(), "got {got:?}, want {want:?}");
+        for (g, w) in got.iter().zip(want) {
+            assert!((g - w).abs() < 1e-9, "got {got:?}, want {want:?}");
+        }
+    }
+
+    const VALUES: &[f64] = &[1.0, 2.0, 3.0, 2.0, 4.0];
+
+    #[test]
+    fn test_ema() {
+        let got = run(
+            Box::new(Ema::new(2, WarmupType::Exponential)),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        assert_approx_eq(&got, &[5.0 / 3.0, 23.0 / 9.0, 95.0 / 27.0]);
+
+        let got = run(Box::new(Ema::new(3, WarmupType::Simple)), VALUES);
+        assert_approx_eq(&got, &[2.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_dema() {
+        // ema1: 1, 2, 4
+        // ema2:    2, 3
+        let got = run(Box::new(Dema::new(2, WarmupType::Simple)), &[1.0, 3.0, 5.0]);
+        assert_approx_eq(&got, &[2.0 * 4.0 - 3.0]);
+    }
+
+    #[test]
+    fn test_tema() {
+        // A constant input produces a constant output.
+        let got = run(
+            Box::new(Tema::new(3, WarmupType::Exponential)),
+            &[5.0, 5.0, 5.0, 5.0],
+        );
+        assert_approx_eq(&got, &[5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_trix() {
+        // A constant input has no rate of change.
+        let got = run(
+            Box::new(Trix::new(2, WarmupType::Exponential)),
+            &[5.0, 5.0, 5.0, 5.0],
+        );
+        assert_approx_eq(&got, &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_rsi() {
+        // Only upward movement
+        let got = run(
+            Box::new(Rsi::new(2, WarmupType::Exponential)),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        assert_approx_eq(&got, &[100.0, 100.0]);
+
+        // up:   1, 1, 1, 0.75, 0.75 + (2 - 0.75) / 3
+        // down: 0, 0, 0, 0.25, 0.25 - 0.25 / 3
+        let got = run(Box::new(Rsi::new(3, WarmupType::Simple)), VALUES);
+        assert_approx_eq(&got, &[75.0, 87.5]);
+    }
+
+    #[test]
+    fn test_cmo() {
+        // As with InfluxDB 1.x, the sums include the movements of the
+        // last period + 1 values.
+        let got = run(Box::new(Cmo::new(2)), VALUES);
+        assert_approx_eq(&got, &[100.0, 100.0 / 3.0, 50.0]);
+    }
+
+    #[test]
+    fn test_ker() {
+        let got = run(Box::new(Ker::new(2)), VALUES);
+        // |v[n] - v[n-2]| / (|v[n] - v[n-1]| + |v[n-1] - v[n-2]|)
+        assert_approx_eq(&got, &[2.0 / 2.0, 0.0 / 2.0, 1.0 / 3.0]);
+    }
+
+    #[test]
+    fn test_kama() {
+        // An efficiency ratio of 1 uses the fast smoothing constant.
+        let got = run(Box::new(Kama::new(2)), &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(got.len(), 2);
+        assert!(got[0] > 1.0 && got[0] < 3.0);
+        assert!(got[1] > got[0] && got[1] < 4.0);
+    }
+
+    /// The input for the golden tests, including missing values.
+    const SERIES: &[Option<f64>] = &[
+        Some(20.0),
+        Some(22.0),
+        Some(24.0),
+        Some(23.0),
+        None,
+        Some(25.0),
+        Some(27.0),
+        Some(26.0),
+        Some(24.0),
+        None,
+        Some(22.0),
+        Some(21.0),
+        Some(23.0),
+        Some(25.0),
+        Some(28.0),
+        Some(27.0),
+        Some(26.0),
+        Some(28.0),
+        Some(30.0),
+        Some(29.0),
+    ];
+
+    /// Evaluate the indicator over [`SERIES`] with a period of 4, as the partition
+    /// evaluator does for each series of a query.
+    fn evaluate(indicator: Indicator, hold_period: i64, warmup: Option<&str>) -> Vec<Option<f64>> {
+        let mut args: Vec<ArrayRef> = vec![
+            Arc::new(Float64Array::from(SERIES.to_vec())),
+            Arc::new(Int64Array::from(vec![4])),
+            Arc::new(Int64Array::from(vec![hold_period])),
+        ];
+        if let Some(warmup) = warmup {
+            args.push(Arc::new(StringArray::from(vec![warmup])));
+        }
+        let output = partition_evaluator_factory(indicator)
+            .unwrap()
+            .evaluate_all(&args, SERIES.len())
+            .unwrap();
+        output
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap()
+            .iter()
+            .collect()
+    }
+
+    fn assert_outputs(got: &[Option<f64>], want: &[Option<f64>]) {
+        assert_eq!(got.len(), want.len(), "got {got:?}, want {want:?}");
+        for (g, w) in got.iter().zip(want) {
+            match (g, w) {
+                (Some(g), Some(w)) => assert!((g - w).abs() < 1e-9, "got {got:?}, want {want:?}"),
+                _ => assert_eq!(g, w, "got {got:?}, want {want:?}"),
+            }
+        }
+    }
+
+    // The expected values of the following tests were computed by a separate implementation
+    // of the `gota` package used by InfluxDB 1.x, fed the non-null values of SERIES, with the
+    // hold period applied as by the reducers of InfluxDB 1.x. The averages with the simple
+    // warmup also agree with TA-Lib.
+
+    #[test]
+    fn golden_ema_exponential() {
+        assert_outputs(
+            &evaluate(Indicator::ExponentialMovingAverage, -1, Some("exponential")),
+            &[
+                None,
+                None,
+                None,
+                Some(22.799999999999997),
+                None,
+                Some(23.68),
+                Some(25.008),
+                Some(25.404799999999998),
+                Some(24.842879999999997),
+                None,
+                Some(23.705727999999997),
+                Some(22.623436799999997),
+                Some(22.774062079999997),
+                Some(23.664437248),
+                Some(25.3986623488),
+                Some(26.03919740928),
+                Some(26.023518445568),
+                Some(26.8141110673408),
+                Some(28.08846664040448),
+                Some(28.453079984242688),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_ema_simple() {
+        assert_outputs(
+            &evaluate(Indicator::ExponentialMovingAverage, -1, Some("simple")),
+            &[
+                None,
+                None,
+                None,
+                Some(22.25),
+                None,
+                Some(23.35),
+                Some(24.810000000000002),
+                Some(25.286),
+                Some(24.7716),
+                None,
+                Some(23.662959999999998),
+                Some(22.597776),
+                Some(22.7586656),
+                Some(23.65519936),
+                Some(25.393119616),
+                Some(26.0358717696),
+                Some(26.02152306176),
+                Some(26.812913837056),
+                Some(28.0877483022336),
+                Some(28.45264898134016),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_ema_simple_no_hold() {
+        assert_outputs(
+            &evaluate(Indicator::ExponentialMovingAverage, 0, Some("simple")),
+            &[
+                Some(20.0),
+                Some(21.0),
+                Some(22.0),
+                Some(22.25),
+                None,
+                Some(23.35),
+                Some(24.810000000000002),
+                Some(25.286),
+                Some(24.7716),
+                None,
+                Some(23.662959999999998),
+                Some(22.597776),
+                Some(22.7586656),
+                Some(23.65519936),
+                Some(25.393119616),
+                Some(26.0358717696),
+                Some(26.02152306176),
+                Some(26.812913837056),
+                Some(28.0877483022336),
+                Some(28.45264898134016),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_dema_exponential() {
+        assert_outputs(
+            &evaluate(
+                Indicator::DoubleExponentialMovingAverage,
+                -1,
+                Some("exponential"),
+            ),
+            &[
+                None,
+                None,
+                None,
+                Some(23.413333333333327),
+                None,
+                Some(24.576),
+                Some(26.342399999999998),
+                Some(26.443519999999996),
+                Some(25.128959999999996),
+                None,
+                Some(23.195084799999997),
+                Some(21.667676159999996),
+                Some(22.290980863999994),
+                Some(23.9088136192),
+                Some(26.585823232),
+                Some(27.135814975488),
+                Some(26.672081607065603),
+                Some(27.67760453730304),
+                Some(29.37117606622003),
+                Some(29.441473646034943),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_dema_simple() {
+        assert_outputs(
+            &evaluate(
+                Indicator::DoubleExponentialMovingAverage,
+                -1,
+                Some("simple"),
+            ),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(26.648000000000003),
+                Some(25.28016),
+                None,
+                Some(23.302911999999996),
+                Some(21.7426368),
+                Some(22.34211584),
+                Some(23.943189760000003),
+                Some(26.6086660096),
+                Some(27.15085089792),
+                Some(26.681901314048),
+                Some(27.683975253606402),
+                Some(29.3752858312704),
+                Some(29.44411190622618),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_tema_exponential() {
+        assert_outputs(
+            &evaluate(
+                Indicator::TripleExponentialMovingAverage,
+                -1,
+                Some("exponential"),
+            ),
+            &[
+                None,
+                None,
+                None,
+                Some(23.425777777777768),
+                None,
+                Some(24.753066666666655),
+                Some(26.711679999999994),
+                Some(26.487679999999994),
+                Some(24.703871999999993),
+                None,
+                Some(22.46199808),
+                Some(20.960753664000002),
+                Some(22.150435020799993),
+                Some(24.2609606656),
+                Some(27.362782167039992),
+                Some(27.547664346316786),
+                Some(26.65035858673664),
+                Some(27.79352891018445),
+                Some(29.69226026346087),
+                Some(29.45753470596546),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_tema_simple() {
+        assert_outputs(
+            &evaluate(
+                Indicator::TripleExponentialMovingAverage,
+                -1,
+                Some("simple"),
+            ),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(21.350323200000012),
+                Some(22.369881344),
+                Some(24.382573158400007),
+                Some(27.428829644799993),
+                Some(27.582608719872017),
+                Some(26.66819548159999),
+                Some(27.802161652695037),
+                Some(29.696083338215427),
+                Some(29.458945647902723),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_trix_exponential() {
+        assert_outputs(
+            &evaluate(
+                Indicator::TripleExponentialDerivative,
+                -1,
+                Some("exponential"),
+            ),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(2.2203920276725464),
+                Some(2.9159818234554935),
+                Some(2.919790316216675),
+                Some(2.028588641218021),
+                None,
+                Some(0.6218987192287528),
+                Some(-0.6913919980607419),
+                Some(-0.9583539289043874),
+                Some(-0.30444108078041543),
+                Some(1.1623169132080502),
+                Some(1.9180134677869054),
+                Some(1.842117751761596),
+                Some(2.0173490120076965),
+                Some(2.5436743722050403),
+                Some(2.5081969668212523),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_trix_simple() {
+        assert_outputs(
+            &evaluate(Indicator::TripleExponentialDerivative, -1, Some("simple")),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(-1.2385573939654115),
+                Some(-0.4273102086857161),
+                Some(1.1207660475152315),
+                Some(1.9152183294532055),
+                Some(1.8540947420831566),
+                Some(2.0331163920414896),
+                Some(2.558632443662834),
+                Some(2.520319969440421),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_rsi_exponential() {
+        assert_outputs(
+            &evaluate(Indicator::RelativeStrengthIndex, -1, Some("exponential")),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(90.9090909090909),
+                Some(92.5925925925926),
+                Some(82.41758241758242),
+                Some(63.73937677053824),
+                None,
+                Some(48.94851341551849),
+                Some(42.39062172911869),
+                Some(57.554881497095266),
+                Some(68.58168533482171),
+                Some(79.32433447697278),
+                Some(68.85997083681904),
+                Some(58.55979436198144),
+                Some(70.37623771853573),
+                Some(78.53650050657558),
+                Some(66.3515505976423),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_rsi_simple() {
+        assert_outputs(
+            &evaluate(Indicator::RelativeStrengthIndex, -1, Some("simple")),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(96.29629629629629),
+                Some(96.7032967032967),
+                Some(90.10238907849829),
+                Some(76.22714148219441),
+                None,
+                Some(63.24194836305563),
+                Some(56.79228746713409),
+                Some(66.03052638172593),
+                Some(73.56625631637954),
+                Some(81.6899823303659),
+                Some(71.8729634347021),
+                Some(61.947055759898504),
+                Some(72.18913115835414),
+                Some(79.53384275460922),
+                Some(67.62717635448206),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_cmo_none() {
+        assert_outputs(
+            &evaluate(Indicator::ChandeMomentumOscillator, -1, Some("none")),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(71.42857142857143),
+                Some(77.77777777777779),
+                Some(50.0),
+                Some(0.0),
+                None,
+                Some(-11.11111111111111),
+                Some(-50.0),
+                Some(-50.0),
+                Some(-11.11111111111111),
+                Some(40.0),
+                Some(55.55555555555556),
+                Some(55.55555555555556),
+                Some(55.55555555555556),
+                Some(55.55555555555556),
+                Some(14.285714285714285),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_cmo_exponential() {
+        assert_outputs(
+            &evaluate(Indicator::ChandeMomentumOscillator, -1, Some("exponential")),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(81.81818181818183),
+                Some(85.18518518518518),
+                Some(64.83516483516483),
+                Some(27.47875354107649),
+                None,
+                Some(-2.1029731689630204),
+                Some(-15.218756541762621),
+                Some(15.109762994190524),
+                Some(37.16337066964342),
+                Some(58.648668953945545),
+                Some(37.71994167363808),
+                Some(17.119588723962874),
+                Some(40.75247543707147),
+                Some(57.07300101315115),
+                Some(32.7031011952846),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_ker() {
+        assert_outputs(
+            &evaluate(Indicator::KaufmansEfficiencyRatio, -1, None),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(0.7142857142857143),
+                Some(0.7142857142857143),
+                Some(0.3333333333333333),
+                Some(0.14285714285714285),
+                None,
+                Some(0.42857142857142855),
+                Some(1.0),
+                Some(0.42857142857142855),
+                Some(0.14285714285714285),
+                Some(0.75),
+                Some(0.75),
+                Some(0.42857142857142855),
+                Some(0.42857142857142855),
+                Some(0.3333333333333333),
+                Some(0.3333333333333333),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_kama() {
+        assert_outputs(
+            &evaluate(Indicator::KaufmansAdaptiveMovingAverage, -1, None),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(23.48930512197942),
+                Some(24.34820561474061),
+                Some(24.46440692322432),
+                Some(24.453882728872145),
+                None,
+                Some(24.19853593086256),
+                Some(22.776964406034754),
+                Some(22.80017310467838),
+                Some(22.850024656474314),
+                Some(24.221922354645567),
+                Some(24.961972174844043),
+                Some(25.069987557274423),
+                Some(25.37487959085669),
+                Some(25.700250017244695),
+                Some(25.93238258113221),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_kama_hold() {
+        assert_outputs(
+            &evaluate(Indicator::KaufmansAdaptiveMovingAverage, 8, None),
+            &[
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(24.19853593086256),
+                Some(22.776964406034754),
+                Some(22.80017310467838),
+                Some(22.850024656474314),
+                Some(24.221922354645567),
+                Some(24.961972174844043),
+                Some(25.069987557274423),
+                Some(25.37487959085669),
+                Some(25.700250017244695),
+                Some(25.93238258113221),
+            ],
+        );
+    }
+}
@@ -283,6 +283,11 @@ impl ParquetStorage {
         Ok((parquet_meta, file_size))
     }
 
+    /// The URL of this store in the object store registry of the DataFusion runtime.
+    pub fn object_store_url(&self) -> ObjectStoreUrl {
+        ObjectStoreUrl::parse(format!("iox://{}/", self.id)).expect("valid object store URL")
+    }
+
     /// Inputs for [`ParquetExec`].
     ///
     /// See [`ParquetExecInput`] for more information.
@@ -290,8 +295,7 @@ impl ParquetStorage {
     /// [`ParquetExec`]: datafusion::datasource::physical_plan::ParquetExec
     pub fn parquet_exec_input(&self, path: &ParquetFilePath, file_size: usize) -> ParquetExecInput {
         ParquetExecInput {
-            object_store_url: ObjectStoreUrl::parse(format!("iox://{}/", self.id))
-                .expect("valid object store URL"),
+            object_store_url: self.object_store_url(),
             object_meta: ObjectMeta {
                 location: path.object_store_path(),
                 // we don't care about the "last modified" field
@@ -0,0 +1,457 @@
+//! Approximate aggregate functions, which summarise their input in
+//! fixed size sketches that can be merged across partitions.
+//!
+//! * `sum_hll(value)` returns a [HyperLogLog] sketch of the distinct
+//!   values, encoded as a string. String values that are themselves
+//!   encoded sketches are merged into the result.
+//! * `count_hll(value)` estimates the number of distinct values, merging
+//!   encoded sketches like `sum_hll`.
+//! * `approx_percentile(value, n)` estimates the `n`th percentile of the
+//!   values, where `n` is between 0 and 100, using a [t-digest].
+//!
+//! [HyperLogLog]: hll::HyperLogLog
+//! [t-digest]: tdigest::TDigest
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    compute::cast,
+    datatypes::{DataType, Field, Float64Type, Int64Type, UInt64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        Accumulator, AccumulatorFactoryFunction, AggregateUDF, ReturnTypeFunction, Signature,
+        StateTypeFunction, TypeSignature, Volatility,
+    },
+    prelude::SessionContext,
+    scalar::ScalarValue,
+};
+use once_cell::sync::Lazy;
+
+use self::{
+    hll::HyperLogLog,
+    tdigest::{Centroid, TDigest},
+};
+
+pub mod hll;
+pub mod tdigest;
+
+/// The name of the `sum_hll` aggregate function.
+pub const SUM_HLL_NAME: &str = "sum_hll";
+
+/// The name of the `count_hll` aggregate function.
+pub const COUNT_HLL_NAME: &str = "count_hll";
+
+/// The name of the `approx_percentile` aggregate function.
+pub const APPROX_PERCENTILE_NAME: &str = "approx_percentile";
+
+/// The numeric types accepted by `approx_percentile`.
+static NUMERICS: &[DataType] = &[DataType::Int64, DataType::UInt64, DataType::Float64];
+
+/// The types accepted by the HyperLogLog functions.
+static HLL_SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::uniform(
+        1,
+        vec![
+            DataType::Int64,
+            DataType::UInt64,
+            DataType::Float64,
+            DataType::Boolean,
+            DataType::Utf8,
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+        ],
+        Volatility::Immutable,
+    )
+});
+
+/// Definition of the `sum_hll` user-defined aggregate function.
+pub static SUM_HLL: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Utf8)));
+    let accumulator: AccumulatorFactoryFunction =
+        Arc::new(|_| Ok(Box::new(HllAccumulator::new(HllOutput::Sketch))));
+    let state_type: StateTypeFunction = Arc::new(|_| Ok(Arc::new(vec![DataType::Binary])));
+
+    Arc::new(AggregateUDF::new(
+        SUM_HLL_NAME,
+        &HLL_SIGNATURE,
+        &return_type,
+        &accumulator,
+        &state_type,
+    ))
+});
+
+/// Definition of the `count_hll` user-defined aggregate function.
+pub static COUNT_HLL: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
+    let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::UInt64)));
+    let accumulator: AccumulatorFactoryFunction =
+        Arc::new(|_| Ok(Box::new(HllAccumulator::new(HllOutput::Count))));
+    let state_type: StateTypeFunction = Arc::new(|_| Ok(Arc::new(vec![DataType::Binary])));
+
+    Arc::new(AggregateUDF::new(
+        COUNT_HLL_NAME,
+        &HLL_SIGNATURE,
+        &return_type,
+        &accumulator,
+        &state_type,
+    ))
+});
+
+/// Definition of the `approx_percentile` user-defined aggregate function.
+pub static APPROX_PERCENTILE: Lazy<Arc<AggregateUDF>> = Lazy::new(|| {
+    let signature = Signature::one_of(
+        NUMERICS
+            .iter()
+            .flat_map(|dt| {
+                [
+                    TypeSignature::Exact(vec![dt.clone(), DataType::Int64]),
+                    TypeSignature::Exact(vec![dt.clone(), DataType::Float64]),
+                ]
+            })
+            .collect(),
+        Volatility::Immutable,
+    );
+    let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Float64)));
+    let accumulator: AccumulatorFactoryFunction =
+        Arc::new(|_| Ok(Box::new(ApproxPercentileAccumulator::new())));
+    let state_type: StateTypeFunction = Arc::new(|_| {
+        let list = DataType::List(Arc::new(Field::new("item", DataType::Float64, true)));
+        Ok(Arc::new(vec![
+            list.clone(),
+            list,
+            DataType::Float64,
+            DataType::Float64,
+            DataType::Float64,
+        ]))
+    });
+
+    Arc::new(AggregateUDF::new(
+        APPROX_PERCENTILE_NAME,
+        &signature,
+        &return_type,
+        &accumulator,
+        &state_type,
+    ))
+});
+
+/// registers approximate aggregate functions so they can be invoked via SQL
+pub fn register_approx_aggregates(ctx: &SessionContext) {
+    for udaf in [&SUM_HLL, &COUNT_HLL, &APPROX_PERCENTILE] {
+        ctx.register_udaf(AggregateUDF::clone(udaf));
+    }
+}
+
+/// The value returned by a [`HllAccumulator`].
+#[derive(Debug, Clone, Copy)]
+enum HllOutput {
+    /// The encoded sketch.
+    Sketch,
+    /// The estimated number of distinct values.
+    Count,
+}
+
+#[derive(Debug)]
+struct HllAccumulator {
+    hll: HyperLogLog,
+    output: HllOutput,
+}
+
+impl HllAccumulator {
+    fn new(output: HllOutput) -> Self {
+        Self {
+            hll: HyperLogLog::new(),
+            output,
+        }
+    }
+}
+
+impl Accumulator for HllAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        assert_eq!(values.len(), 1);
+
+        let array = match values[0].data_type() {
+            DataType::Dictionary(_, _) => cast(&values[0], &DataType::Utf8)?,
+            _ => Arc::clone(&values[0]),
+        };
+        match array.data_type() {
+            DataType::Int64 => array
+                .as_primitive::<Int64Type>()
+                .iter()
+                .flatten()
+                .for_each(|v| self.hll.insert(&v.to_le_bytes())),
+            DataType::UInt64 => array
+                .as_primitive::<UInt64Type>()
+                .iter()
+                .flatten()
+                .for_each(|v| self.hll.insert(&v.to_le_bytes())),
+            DataType::Float64 => array
+                .as_primitive::<Float64Type>()
+                .iter()
+                .flatten()
+                .for_each(|v| self.hll.insert(&v.to_le_bytes())),
+            DataType::Boolean => array
+                .as_boolean()
+                .iter()
+                .flatten()
+                .for_each(|v| self.hll.insert(&[u8::from(v)])),
+            DataType::Utf8 => {
+                for s in array.as_string::<i32>().iter().flatten() {
+                    // Strings that aren't valid sketches are counted as
+                    // plain values.
+                    match HyperLogLog::try_from_string_encoding(s) {
+                        Some(sketch) => self.hll.merge(&sketch),
+                        None => self.hll.insert(s.as_bytes()),
+                    }
+                }
+            }
+            dt => {
+                return Err(DataFusionError::Internal(format!(
+                    "unsupported data type for HyperLogLog ({dt})"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(match self.output {
+            HllOutput::Sketch => ScalarValue::Utf8(Some(self.hll.to_string_encoding())),
+            HllOutput::Count => ScalarValue::UInt64(Some(self.hll.count())),
+        })
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) - std::mem::size_of_val(&self.hll) + self.hll.size()
+    }
+
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(self.hll.to_bytes()))])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        assert_eq!(states.len(), 1);
+
+        for bytes in states[0].as_binary::<i32>().iter().flatten() {
+            self.hll.merge(&HyperLogLog::try_from_bytes(bytes)?);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ApproxPercentileAccumulator {
+    digest: TDigest,
+    percentile: Option<f64>,
+}
+
+impl ApproxPercentileAccumulator {
+    fn new() -> Self {
+        Self {
+            digest: TDigest::new(),
+            percentile: None,
+        }
+    }
+
+    fn set_percentile(&mut self, array: &ArrayRef) -> Result<()> {
+        if self.percentile.is_some() {
+            return Ok(());
+        }
+        let array = cast(array, &DataType::Float64)?;
+        if let Some(percentile) = array.as_primitive::<Float64Type>().iter().flatten().next() {
+            if !(0.0..=100.0).contains(&percentile) {
+                return Err(DataFusionError::Execution(format!(
+                    "{APPROX_PERCENTILE_NAME} percentile must be between 0 and 100, got {percentile}"
+                )));
+            }
+            self.percentile = Some(percentile);
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for ApproxPercentileAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        assert_eq!(values.len(), 2);
+
+        self.set_percentile(&values[1])?;
+        let array = cast(&values[0], &DataType::Float64)?;
+        for v in array.as_primitive::<Float64Type>().iter().flatten() {
+            self.digest.insert(v);
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Float64(
+            self.percentile
+                .and_then(|p| self.digest.quantile(p / 100.0)),
+        ))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) - std::mem::size_of_val(&self.digest) + self.digest.size()
+    }
+
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        let (means, weights): (Vec<_>, Vec<_>) = self
+            .digest
+            .centroids()
+            .into_iter()
+            .map(|c| {
+                (
+                    ScalarValue::Float64(Some(c.mean)),
+                    ScalarValue::Float64(Some(c.weight)),
+                )
+            })
+            .unzip();
+        Ok(vec![
+            ScalarValue::new_list(Some(means), DataType::Float64),
+            ScalarValue::new_list(Some(weights), DataType::Float64),
+            ScalarValue::Float64(Some(self.digest.min())),
+            ScalarValue::Float64(Some(self.digest.max())),
+            ScalarValue::Float64(self.percentile),
+        ])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        assert_eq!(states.len(), 5);
+
+        self.set_percentile(&states[4])?;
+
+        let means = states[0].as_list::<i32>();
+        let weights = states[1].as_list::<i32>();
+        let mins = states[2].as_primitive::<Float64Type>();
+        let maxs = states[3].as_primitive::<Float64Type>();
+        for idx in 0..means.len() {
+            if means.is_null(idx) {
+                continue;
+            }
+            let row_means = means.value(idx);
+            let row_weights = weights.value(idx);
+            let centroids = row_means
+                .as_primitive::<Float64Type>()
+                .values()
+                .iter()
+                .zip(row_weights.as_primitive::<Float64Type>().values().iter())
+                .map(|(&mean, &weight)| Centroid { mean, weight })
+                .collect();
+            self.digest.merge(&TDigest::from_parts(
+                centroids,
+                mins.value(idx),
+                maxs.value(idx),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow::{
+        array::{Float64Array, Int64Array, StringArray},
+        record_batch::RecordBatch,
+    };
+    use datafusion::assert_batches_eq;
+    use datafusion_util::context_with_table;
+
+    use super::*;
+
+    async fn run_sql(sql: &str) -> Vec<RecordBatch> {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "host",
+                Arc::new(StringArray::from_iter_values((0..1000).map(|v| {
+                    if v % 2 == 0 {
+                        "a"
+                    } else {
+                        "b"
+                    }
+                }))) as ArrayRef,
+            ),
+            (
+                "id",
+                Arc::new(Int64Array::from_iter_values((0..1000).map(|v| v % 10))) as ArrayRef,
+            ),
+            (
+                "value",
+                Arc::new(Float64Array::from_iter_values((0..1000).map(f64::from))) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let ctx = context_with_table(batch);
+        register_approx_aggregates(&ctx);
+        ctx.sql(sql).await.unwrap().collect().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_count_hll() {
+        let result =
+            run_sql("SELECT host, count_hll(id) AS ids FROM t GROUP BY host ORDER BY host").await;
+
+        let expected = vec![
+            "+------+-----+",
+            "| host | ids |",
+            "+------+-----+",
+            "| a    | 5   |",
+            "| b    | 5   |",
+            "+------+-----+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+
+    #[tokio::test]
+    async fn test_count_hll_of_sum_hll() {
+        let result = run_sql(
+            "SELECT count_hll(ids) AS ids \
+             FROM (SELECT host, sum_hll(id) AS ids FROM t GROUP BY host)",
+        )
+        .await;
+
+        let expected = vec![
+            "+-----+", //
+            "| ids |", "+-----+", "| 10  |", "+-----+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+
+    #[tokio::test]
+    async fn test_count_hll_invalid_sketch() {
+        // strings with the sketch prefix that don't decode are counted
+        // as plain values
+        let result = run_sql(
+            "SELECT count_hll(column1) AS n \
+             FROM (VALUES ('HLL_foo'), ('HLL_foo'), ('HLL_bar'), ('foo'))",
+        )
+        .await;
+
+        let expected = vec![
+            "+---+", //
+            "| n |", "+---+", "| 3 |", "+---+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+
+    #[tokio::test]
+    async fn test_approx_percentile() {
+        let result = run_sql(
+            "SELECT host, approx_percentile(value, 50) AS p50, approx_percentile(value, 100) AS p100 \
+             FROM t GROUP BY host ORDER BY host",
+        )
+        .await;
+
+        let expected = vec![
+            "+------+-------+-------+",
+            "| host | p50   | p100  |",
+            "+------+-------+-------+",
+            "| a    | 499.0 | 998.0 |",
+            "| b    | 500.0 | 999.0 |",
+            "+------+-------+-------+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+}
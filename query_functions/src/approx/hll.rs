@@ -0,0 +1,186 @@
+//! A [HyperLogLog] sketch for estimating the number of distinct values.
+//!
+//! [HyperLogLog]: https://algo.inria.fr/flajolet/Publications/FlFuGaMe07.pdf
+use std::hash::Hasher;
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use datafusion::error::{DataFusionError, Result};
+use siphasher::sip::SipHasher13;
+
+/// The number of bits of the hash used to select a register.
+///
+/// This gives 4096 registers, and a standard error of approximately
+/// 1.6% for the estimated count.
+const PRECISION: u32 = 12;
+
+/// The number of registers in a sketch.
+const NUM_REGISTERS: usize = 1 << PRECISION;
+
+/// The version of the serialized sketch format.
+const VERSION: u8 = 1;
+
+/// The prefix of sketches encoded as strings.
+pub const STRING_PREFIX: &str = "HLL_";
+
+/// A HyperLogLog sketch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    /// Create a new, empty sketch.
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; NUM_REGISTERS],
+        }
+    }
+
+    /// Add a value, represented by its bytes, to the sketch.
+    pub fn insert(&mut self, bytes: &[u8]) {
+        // The hash must be stable, as serialized sketches may be merged
+        // by a different process.
+        let mut hasher = SipHasher13::new();
+        hasher.write(bytes);
+        self.insert_hash(hasher.finish());
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        let idx = (hash >> (64 - PRECISION)) as usize;
+        // Set a sentinel bit so the rank can't exceed the number of
+        // remaining bits.
+        let w = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = w.leading_zeros() as u8 + 1;
+        if rank > self.registers[idx] {
+            self.registers[idx] = rank;
+        }
+    }
+
+    /// Merge the values counted by `other` into this sketch.
+    pub fn merge(&mut self, other: &Self) {
+        for (r, o) in self.registers.iter_mut().zip(&other.registers) {
+            *r = (*r).max(*o);
+        }
+    }
+
+    /// Estimate the number of distinct values added to the sketch.
+    pub fn count(&self) -> u64 {
+        let m = NUM_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 2f64.powi(-i32::from(r)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+
+        // Use linear counting for small cardinalities, where the raw
+        // estimate is biased.
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    /// The size of the sketch in bytes.
+    pub fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.registers.capacity()
+    }
+
+    /// Serialize the sketch.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 + NUM_REGISTERS);
+        bytes.push(VERSION);
+        bytes.push(PRECISION as u8);
+        bytes.extend_from_slice(&self.registers);
+        bytes
+    }
+
+    /// Deserialize a sketch serialized by [`Self::to_bytes`].
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [VERSION, precision, registers @ ..]
+                if u32::from(*precision) == PRECISION && registers.len() == NUM_REGISTERS =>
+            {
+                Ok(Self {
+                    registers: registers.to_vec(),
+                })
+            }
+            _ => Err(DataFusionError::Execution(
+                "invalid HyperLogLog sketch".to_string(),
+            )),
+        }
+    }
+
+    /// Encode the sketch as a string, as returned by `sum_hll`.
+    pub fn to_string_encoding(&self) -> String {
+        format!("{STRING_PREFIX}{}", BASE64_STANDARD.encode(self.to_bytes()))
+    }
+
+    /// Decode a sketch encoded by [`Self::to_string_encoding`]. Returns
+    /// `None` if `s` is not a valid encoded sketch.
+    pub fn try_from_string_encoding(s: &str) -> Option<Self> {
+        let encoded = s.strip_prefix(STRING_PREFIX)?;
+        let bytes = BASE64_STANDARD.decode(encoded).ok()?;
+        Self::try_from_bytes(&bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch(values: impl IntoIterator<Item = u64>) -> HyperLogLog {
+        let mut hll = HyperLogLog::new();
+        for v in values {
+            hll.insert(&v.to_le_bytes());
+        }
+        hll
+    }
+
+    /// Assert `got` is within 5% of `want`.
+    fn assert_approx(got: u64, want: u64) {
+        let error = (got as f64 - want as f64).abs() / want as f64;
+        assert!(error < 0.05, "got {got}, want {want}");
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(HyperLogLog::new().count(), 0);
+        assert_eq!(sketch([1, 2, 3, 2, 1]).count(), 3);
+        assert_approx(sketch(0..1_000).count(), 1_000);
+        assert_approx(sketch(0..100_000).count(), 100_000);
+        // duplicates are not counted
+        assert_approx(sketch((0..100_000).map(|v| v % 5_000)).count(), 5_000);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut hll = sketch(0..60_000);
+        hll.merge(&sketch(40_000..100_000));
+        assert_approx(hll.count(), 100_000);
+        assert_eq!(hll, sketch(0..100_000));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let hll = sketch(0..1_000);
+        assert_eq!(HyperLogLog::try_from_bytes(&hll.to_bytes()).unwrap(), hll);
+
+        let s = hll.to_string_encoding();
+        assert!(s.starts_with(STRING_PREFIX));
+        assert_eq!(HyperLogLog::try_from_string_encoding(&s).unwrap(), hll);
+
+        assert!(HyperLogLog::try_from_string_encoding("foo").is_none());
+        assert!(HyperLogLog::try_from_string_encoding("HLL_foo").is_none());
+        HyperLogLog::try_from_bytes(&[VERSION, 4, 0]).unwrap_err();
+    }
+}
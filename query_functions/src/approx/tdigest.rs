@@ -0,0 +1,275 @@
+//! A merging [t-digest] for estimating quantiles.
+//!
+//! [t-digest]: https://arxiv.org/abs/1902.04023
+use std::f64::consts::PI;
+
+/// The compression parameter, which bounds the number of centroids in
+/// a digest to approximately this value.
+const COMPRESSION: f64 = 100.0;
+
+/// The number of values buffered before they are merged into the
+/// centroids.
+const BUFFER_SIZE: usize = 500;
+
+/// A cluster of values, represented by their mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Centroid {
+    /// The mean of the values in the centroid.
+    pub mean: f64,
+    /// The number of values in the centroid.
+    pub weight: f64,
+}
+
+/// A t-digest.
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    /// The centroids, sorted by mean.
+    centroids: Vec<Centroid>,
+    /// Values that have not yet been merged into the centroids.
+    buffer: Vec<f64>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TDigest {
+    /// Create a new, empty digest.
+    pub fn new() -> Self {
+        Self {
+            centroids: vec![],
+            buffer: vec![],
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Create a digest from the centroids, minimum and maximum of another
+    /// digest.
+    pub fn from_parts(centroids: Vec<Centroid>, min: f64, max: f64) -> Self {
+        let mut digest = Self {
+            centroids,
+            buffer: vec![],
+            min,
+            max,
+        };
+        digest.centroids.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        digest
+    }
+
+    /// Add a value to the digest. NaN values are ignored.
+    pub fn insert(&mut self, v: f64) {
+        if v.is_nan() {
+            return;
+        }
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+        self.buffer.push(v);
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.compress();
+        }
+    }
+
+    /// Merge the values summarised by `other` into this digest.
+    pub fn merge(&mut self, other: &Self) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.centroids.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.compress();
+    }
+
+    /// Returns `true` if no values have been added to the digest.
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty() && self.buffer.is_empty()
+    }
+
+    /// The smallest value added to the digest.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// The largest value added to the digest.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// The size of the digest in bytes.
+    pub fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self.centroids.capacity() * std::mem::size_of::<Centroid>()
+            + self.buffer.capacity() * std::mem::size_of::<f64>()
+    }
+
+    /// Returns the centroids of the digest, after merging any buffered
+    /// values.
+    pub fn centroids(&self) -> Vec<Centroid> {
+        if self.buffer.is_empty() {
+            self.centroids.clone()
+        } else {
+            let mut digest = self.clone();
+            digest.compress();
+            digest.centroids
+        }
+    }
+
+    /// Merge the buffered values into the centroids, and merge adjacent
+    /// centroids while they are within the size limit of the scale
+    /// function.
+    fn compress(&mut self) {
+        let mut centroids = std::mem::take(&mut self.centroids);
+        centroids.extend(
+            self.buffer
+                .drain(..)
+                .map(|mean| Centroid { mean, weight: 1.0 }),
+        );
+        if centroids.is_empty() {
+            return;
+        }
+        centroids.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(COMPRESSION as usize);
+        let mut weight_so_far = 0.0;
+        let mut limit = total * k_inverse(k(0.0) + 1.0);
+        let mut iter = centroids.into_iter();
+        let mut current = iter.next().expect("centroids is not empty");
+        for c in iter {
+            if weight_so_far + current.weight + c.weight <= limit {
+                let weight = current.weight + c.weight;
+                current.mean += (c.mean - current.mean) * c.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                limit = total * k_inverse(k(weight_so_far / total) + 1.0);
+                merged.push(current);
+                current = c;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    /// Estimate the value at quantile `q`, between 0 and 1. Returns `None`
+    /// if the digest is empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let centroids = self.centroids();
+        let first = centroids.first()?;
+        let last = centroids.last()?;
+        if q <= 0.0 {
+            return Some(self.min);
+        }
+        if q >= 1.0 {
+            return Some(self.max);
+        }
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let index = q * total;
+
+        // Interpolate between the minimum and the centre of the first
+        // centroid.
+        if index < first.weight / 2.0 {
+            return Some(interpolate(
+                self.min,
+                first.mean,
+                index / (first.weight / 2.0),
+            ));
+        }
+
+        // Interpolate between the centres of adjacent centroids.
+        let mut weight_so_far = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let step = (pair[0].weight + pair[1].weight) / 2.0;
+            if index < weight_so_far + step {
+                return Some(interpolate(
+                    pair[0].mean,
+                    pair[1].mean,
+                    (index - weight_so_far) / step,
+                ));
+            }
+            weight_so_far += step;
+        }
+
+        // Interpolate between the centre of the last centroid and the
+        // maximum.
+        let remaining = (total - weight_so_far).max(f64::MIN_POSITIVE);
+        Some(interpolate(
+            last.mean,
+            self.max,
+            ((index - weight_so_far) / remaining).min(1.0),
+        ))
+    }
+}
+
+/// The k1 scale function, which maps a quantile to an index that limits
+/// the size of the centroids, so that centroids near the tails are small.
+fn k(q: f64) -> f64 {
+    COMPRESSION / (2.0 * PI) * (2.0 * q - 1.0).asin()
+}
+
+fn k_inverse(k: f64) -> f64 {
+    if k >= COMPRESSION / 4.0 {
+        return 1.0;
+    }
+    ((k * 2.0 * PI / COMPRESSION).sin() + 1.0) / 2.0
+}
+
+fn interpolate(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(values: impl IntoIterator<Item = f64>) -> TDigest {
+        let mut digest = TDigest::new();
+        for v in values {
+            digest.insert(v);
+        }
+        digest
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(TDigest::new().quantile(0.5), None);
+        assert!(TDigest::new().is_empty());
+    }
+
+    #[test]
+    fn test_single_value() {
+        let d = digest([3.0]);
+        assert_eq!(d.quantile(0.0), Some(3.0));
+        assert_eq!(d.quantile(0.5), Some(3.0));
+        assert_eq!(d.quantile(1.0), Some(3.0));
+    }
+
+    #[test]
+    fn test_quantile() {
+        let d = digest((0..100_000).map(|v| v as f64));
+        assert!(d.centroids().len() <= 2 * COMPRESSION as usize);
+        assert_eq!(d.quantile(0.0), Some(0.0));
+        assert_eq!(d.quantile(1.0), Some(99_999.0));
+        for q in [0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99] {
+            let got = d.quantile(q).unwrap();
+            let want = q * 100_000.0;
+            assert!((got - want).abs() < 500.0, "q={q}, got {got}, want {want}");
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut d = digest((0..50_000).map(|v| v as f64));
+        d.merge(&digest((50_000..100_000).rev().map(|v| v as f64)));
+        let d = TDigest::from_parts(d.centroids(), d.min(), d.max());
+        for q in [0.01, 0.5, 0.99] {
+            let got = d.quantile(q).unwrap();
+            let want = q * 100_000.0;
+            assert!((got - want).abs() < 500.0, "q={q}, got {got}, want {want}");
+        }
+    }
+}
@@ -0,0 +1,649 @@
+//! Scalar function `DATE_BIN_WALLCLOCK`, which bins timestamps on the wall clock of a timezone.
+//!
+//! `DATE_BIN` bins timestamps on fixed nanosecond strides from the origin, which is not what
+//! users expect when the buckets are calendar units in a timezone with daylight saving time:
+//! a day in `Europe/Berlin` is 23 or 25 hours long twice a year, and months have no fixed
+//! length at all. `DATE_BIN_WALLCLOCK` takes the same arguments as `DATE_BIN`, plus an
+//! optional IANA timezone:
+//!
+//! ```sql
+//! SELECT
+//!   DATE_BIN_WALLCLOCK(INTERVAL '1 day', time, '1970-01-01T00:00:00Z', 'Europe/Berlin') AS day,
+//!   AVG(temp)
+//! FROM temps
+//! GROUP BY day
+//! ```
+//!
+//! The bins are computed on the wall clock of the timezone (UTC if it is omitted):
+//!
+//! * The origin is interpreted as a wall clock time, so the origin above is midnight
+//!   in Berlin, not midnight in UTC.
+//! * Strides may be given in months (so also quarters and years), in which case each
+//!   bin starts on the same day of the month and time of day as the origin. Months
+//!   may not be mixed with days or smaller units.
+//! * Strides of a day or more are lengths of wall clock time. Bins that start at a wall
+//!   clock time skipped by a timezone transition start at the first instant after the
+//!   transition, and bins that start at a wall clock time that occurs twice start at the
+//!   earlier of the two.
+//! * Strides shorter than a day are fixed lengths of time from the origin, so that bins
+//!   don't grow or shrink when the offset of the timezone changes: the hour that is
+//!   repeated when daylight saving time ends is two hourly bins, each starting at the
+//!   same wall clock time but with a different offset.
+//!
+//! The returned timestamps are always in UTC.
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, TimestampNanosecondArray},
+    datatypes::{
+        DataType, IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit, TimeUnit,
+        TimestampNanosecondType,
+    },
+};
+use chrono::{Datelike, Duration, LocalResult, Months, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        ReturnTypeFunction, ScalarFunctionImplementation, ScalarUDF, Signature, TypeSignature,
+        Volatility,
+    },
+    physical_plan::ColumnarValue,
+    prelude::{lit, Expr},
+    scalar::ScalarValue,
+};
+use once_cell::sync::Lazy;
+
+/// The name of the date_bin_wallclock UDF given to DataFusion.
+pub const DATE_BIN_WALLCLOCK_UDF_NAME: &str = "date_bin_wallclock";
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+const NANOS_PER_DAY: i64 = 86_400 * NANOS_PER_SEC;
+
+/// Implementation of date_bin_wallclock.
+pub(crate) static DATE_BIN_WALLCLOCK: Lazy<Arc<ScalarUDF>> = Lazy::new(|| {
+    let return_type_fn: ReturnTypeFunction =
+        Arc::new(|_| Ok(Arc::new(DataType::Timestamp(TimeUnit::Nanosecond, None))));
+    let fun: ScalarFunctionImplementation = Arc::new(date_bin_wallclock_impl);
+    Arc::new(ScalarUDF::new(
+        DATE_BIN_WALLCLOCK_UDF_NAME,
+        &Signature::one_of(signatures(), Volatility::Immutable),
+        &return_type_fn,
+        &fun,
+    ))
+});
+
+/// The signatures of `DATE_BIN_WALLCLOCK`, which are those of `DATE_BIN` with nanosecond
+/// timestamps, with an optional trailing timezone argument.
+fn signatures() -> Vec<TypeSignature> {
+    let time = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    [
+        IntervalUnit::MonthDayNano,
+        IntervalUnit::DayTime,
+        IntervalUnit::YearMonth,
+    ]
+    .into_iter()
+    .flat_map(|unit| {
+        let stride = DataType::Interval(unit);
+        [
+            TypeSignature::Exact(vec![stride.clone(), time.clone()]),
+            TypeSignature::Exact(vec![stride.clone(), time.clone(), time.clone()]),
+            TypeSignature::Exact(vec![stride, time.clone(), time.clone(), DataType::Utf8]),
+        ]
+    })
+    .collect()
+}
+
+/// Return an [`Expr`] that invokes `DATE_BIN_WALLCLOCK`, binning `source` by `stride` from
+/// the wall clock time `origin` in `tz`.
+pub fn date_bin_wallclock(stride: Expr, source: Expr, origin: Expr, tz: Tz) -> Expr {
+    DATE_BIN_WALLCLOCK.call(vec![stride, source, origin, lit(tz.name())])
+}
+
+/// Parse the name of an IANA timezone, such as `Europe/Berlin`.
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.parse::<Tz>()
+        .map_err(|_| DataFusionError::Plan(format!("invalid timezone: '{name}'")))
+}
+
+fn date_bin_wallclock_impl(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    if !(2..=4).contains(&args.len()) {
+        return Err(DataFusionError::Execution(format!(
+            "{DATE_BIN_WALLCLOCK_UDF_NAME} expects 2 to 4 arguments, got {}",
+            args.len()
+        )));
+    }
+
+    let ColumnarValue::Scalar(stride) = &args[0] else {
+        return Err(DataFusionError::NotImplemented(format!(
+            "{DATE_BIN_WALLCLOCK_UDF_NAME} only supports a scalar stride"
+        )));
+    };
+    let origin = match args.get(2) {
+        None => None,
+        Some(ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(Some(v), _))) => Some(*v),
+        Some(_) => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "{DATE_BIN_WALLCLOCK_UDF_NAME} only supports a scalar nanosecond timestamp origin"
+            )))
+        }
+    };
+    let tz = match args.get(3) {
+        None => None,
+        Some(ColumnarValue::Scalar(ScalarValue::Utf8(Some(name)))) => Some(parse_timezone(name)?),
+        Some(_) => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "{DATE_BIN_WALLCLOCK_UDF_NAME} only supports a scalar string timezone"
+            )))
+        }
+    };
+    let bins = WallclockBins::try_new(stride, origin, tz)?;
+
+    match &args[1] {
+        ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(v, _)) => Ok(ColumnarValue::Scalar(
+            ScalarValue::TimestampNanosecond(v.map(|v| bins.bin(v)), None),
+        )),
+        ColumnarValue::Array(array) => {
+            let array = array
+                .as_any()
+                .downcast_ref::<TimestampNanosecondArray>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{DATE_BIN_WALLCLOCK_UDF_NAME} expects a nanosecond timestamp source, got {}",
+                        array.data_type()
+                    ))
+                })?;
+            let binned = array.unary::<_, TimestampNanosecondType>(|v| bins.bin(v));
+            Ok(ColumnarValue::Array(Arc::new(binned)))
+        }
+        ColumnarValue::Scalar(v) => Err(DataFusionError::Execution(format!(
+            "{DATE_BIN_WALLCLOCK_UDF_NAME} expects a nanosecond timestamp source, got {}",
+            v.get_datatype()
+        ))),
+    }
+}
+
+/// The length of a bin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stride {
+    /// A number of calendar months.
+    Months(i64),
+    /// A number of nanoseconds on the wall clock, of at least a day.
+    Wallclock(i64),
+    /// A number of nanoseconds less than a day, which are binned in UTC.
+    Fixed(i64),
+}
+
+/// Bins of a fixed length or a number of calendar months, aligned to an origin on the wall
+/// clock of a timezone.
+///
+/// Bins are identified by the UTC timestamp, in nanoseconds, at which they start.
+/// Computations that would produce a bin outside the range of a nanosecond timestamp
+/// saturate to [`i64::MIN`] or [`i64::MAX`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallclockBins {
+    stride: Stride,
+    /// The wall clock time at which a bin starts.
+    origin: NaiveDateTime,
+    /// `origin` in nanoseconds, as if it were a UTC time.
+    origin_nanos: i64,
+    /// The UTC timestamp of `origin`, from which bins of a [`Stride::Fixed`] are aligned.
+    origin_utc: i64,
+    /// The timezone of the wall clock, or UTC if `None`.
+    tz: Option<Tz>,
+}
+
+impl WallclockBins {
+    /// Create bins of length `stride`, which must be a scalar interval, starting at the wall
+    /// clock time `origin` in `tz`. `origin` is specified in nanoseconds as if it were a UTC
+    /// time and defaults to the Unix epoch.
+    pub fn try_new(stride: &ScalarValue, origin: Option<i64>, tz: Option<Tz>) -> Result<Self> {
+        let (months, days, nanos) = match stride {
+            ScalarValue::IntervalMonthDayNano(Some(v)) => IntervalMonthDayNanoType::to_parts(*v),
+            ScalarValue::IntervalDayTime(Some(v)) => {
+                let (days, millis) = IntervalDayTimeType::to_parts(*v);
+                (0, days, millis as i64 * 1_000_000)
+            }
+            ScalarValue::IntervalYearMonth(Some(v)) => (*v, 0, 0),
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "stride must be a scalar interval, got {stride:?}"
+                )))
+            }
+        };
+
+        let stride = if months != 0 {
+            if days != 0 || nanos != 0 {
+                return Err(DataFusionError::NotImplemented(
+                    "a stride that mixes months with days or time is not supported".to_string(),
+                ));
+            }
+            Stride::Months(months as i64)
+        } else {
+            let nanos = (days as i64)
+                .checked_mul(NANOS_PER_DAY)
+                .and_then(|v| v.checked_add(nanos))
+                .ok_or_else(|| DataFusionError::Execution("stride is too large".to_string()))?;
+            if nanos < NANOS_PER_DAY {
+                Stride::Fixed(nanos)
+            } else {
+                Stride::Wallclock(nanos)
+            }
+        };
+        if matches!(stride, Stride::Months(v) | Stride::Wallclock(v) | Stride::Fixed(v) if v <= 0) {
+            return Err(DataFusionError::Execution(
+                "stride must be greater than zero".to_string(),
+            ));
+        }
+
+        let origin_nanos = origin.unwrap_or_default();
+        let mut bins = Self {
+            stride,
+            origin: naive_from_nanos(origin_nanos),
+            origin_nanos,
+            origin_utc: origin_nanos,
+            tz,
+        };
+        bins.origin_utc = bins
+            .to_utc_nanos(bins.origin)
+            .ok_or_else(|| DataFusionError::Execution("origin is out of range".to_string()))?;
+        Ok(bins)
+    }
+
+    /// Returns the bin that contains the timestamp `ts`.
+    pub fn bin(&self, ts: i64) -> i64 {
+        self.start(self.index(ts))
+    }
+
+    /// Returns the bin `n` bins after `bin`.
+    pub fn nth(&self, bin: i64, n: i64) -> i64 {
+        self.start(self.index(bin).saturating_add(n))
+    }
+
+    /// Returns the number of bins from `first` to `last`, inclusive.
+    pub fn count(&self, first: i64, last: i64) -> usize {
+        if last >= first {
+            (self.index(last) - self.index(first) + 1) as usize
+        } else {
+            0
+        }
+    }
+
+    /// Returns the index, relative to the origin, of the bin that contains `ts`.
+    fn index(&self, ts: i64) -> i64 {
+        match self.stride {
+            Stride::Months(months) => {
+                let local = self.to_local(ts);
+                let diff = (local.year() as i64 - self.origin.year() as i64) * 12
+                    + (local.month0() as i64 - self.origin.month0() as i64);
+                let index = diff.div_euclid(months);
+                // The bin found so far starts in the same month as `ts`, but may start
+                // after it if the origin is later in the month.
+                match self.start_local(index) {
+                    Some(start) if start > local => index - 1,
+                    _ => index,
+                }
+            }
+            Stride::Wallclock(nanos) => {
+                let local = ts as i128 + self.utc_offset_secs(ts) as i128 * NANOS_PER_SEC as i128;
+                (local - self.origin_nanos as i128).div_euclid(nanos as i128) as i64
+            }
+            Stride::Fixed(nanos) => {
+                (ts as i128 - self.origin_utc as i128).div_euclid(nanos as i128) as i64
+            }
+        }
+    }
+
+    /// Returns the UTC timestamp at which the bin with `index` starts.
+    fn start(&self, index: i64) -> i64 {
+        let saturated = if index < 0 { i64::MIN } else { i64::MAX };
+        match self.stride {
+            Stride::Fixed(nanos) => self.start_utc(index, nanos),
+            Stride::Months(_) | Stride::Wallclock(_) => self
+                .start_local(index)
+                .and_then(|local| self.to_utc_nanos(local)),
+        }
+        .unwrap_or(saturated)
+    }
+
+    /// Returns the UTC timestamp at which the bin with `index` of a [`Stride::Fixed`] of
+    /// `nanos` starts.
+    fn start_utc(&self, index: i64, nanos: i64) -> Option<i64> {
+        index.checked_mul(nanos)?.checked_add(self.origin_utc)
+    }
+
+    /// Returns the wall clock time at which the bin with `index` starts.
+    fn start_local(&self, index: i64) -> Option<NaiveDateTime> {
+        match self.stride {
+            Stride::Months(months) => {
+                let months = index.checked_mul(months)?;
+                let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+                if months >= 0 {
+                    self.origin.checked_add_months(delta)
+                } else {
+                    self.origin.checked_sub_months(delta)
+                }
+            }
+            Stride::Wallclock(nanos) => {
+                let local = index
+                    .checked_mul(nanos)
+                    .and_then(|v| v.checked_add(self.origin_nanos))?;
+                Some(naive_from_nanos(local))
+            }
+            Stride::Fixed(nanos) => Some(self.to_local(self.start_utc(index, nanos)?)),
+        }
+    }
+
+    /// Returns the offset of the wall clock from UTC at `ts`, in seconds.
+    fn utc_offset_secs(&self, ts: i64) -> i32 {
+        match self.tz {
+            Some(tz) => tz
+                .offset_from_utc_datetime(&naive_from_nanos(ts))
+                .fix()
+                .local_minus_utc(),
+            None => 0,
+        }
+    }
+
+    /// Returns the wall clock time at `ts`.
+    fn to_local(&self, ts: i64) -> NaiveDateTime {
+        let utc = naive_from_nanos(ts);
+        match self.tz {
+            Some(tz) => tz.from_utc_datetime(&utc).naive_local(),
+            None => utc,
+        }
+    }
+
+    /// Returns the UTC timestamp of the wall clock time `local`.
+    fn to_utc_nanos(&self, local: NaiveDateTime) -> Option<i64> {
+        let Some(tz) = self.tz else {
+            return local.timestamp_nanos_opt();
+        };
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(v) => v.timestamp_nanos_opt(),
+            LocalResult::Ambiguous(a, b) => a.min(b).timestamp_nanos_opt(),
+            LocalResult::None => {
+                // The wall clock skips over `local`, such as when daylight saving time
+                // starts, so use the offset from before the transition, which moves
+                // `local` past it by the length of the gap.
+                let before = tz
+                    .offset_from_utc_datetime(&local.checked_sub_signed(Duration::days(1))?)
+                    .fix();
+                local
+                    .checked_sub_signed(Duration::seconds(before.local_minus_utc() as i64))?
+                    .timestamp_nanos_opt()
+            }
+        }
+    }
+}
+
+fn naive_from_nanos(ts: i64) -> NaiveDateTime {
+    NaiveDateTime::from_timestamp_opt(
+        ts.div_euclid(NANOS_PER_SEC),
+        ts.rem_euclid(NANOS_PER_SEC) as u32,
+    )
+    .expect("nanosecond timestamps are within the range of NaiveDateTime")
+}
+
+#[cfg(test)]
+mod test {
+    use arrow::array::{ArrayRef, TimestampNanosecondArray};
+    use arrow::record_batch::RecordBatch;
+    use chrono::{DateTime, Utc};
+    use datafusion::assert_batches_eq;
+    use datafusion::error::Result;
+    use datafusion::prelude::{col, lit_timestamp_nano};
+    use datafusion_util::context_with_table;
+
+    use super::*;
+
+    fn ts(s: &str) -> i64 {
+        s.parse::<DateTime<Utc>>()
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap()
+    }
+
+    fn bins(stride: ScalarValue, origin: &str, tz: Option<&str>) -> WallclockBins {
+        WallclockBins::try_new(
+            &stride,
+            Some(ts(origin)),
+            tz.map(|tz| parse_timezone(tz).unwrap()),
+        )
+        .unwrap()
+    }
+
+    fn days(n: i32) -> ScalarValue {
+        ScalarValue::new_interval_mdn(0, n, 0)
+    }
+
+    fn hours(n: i64) -> ScalarValue {
+        ScalarValue::new_interval_mdn(0, 0, n * 3_600 * NANOS_PER_SEC)
+    }
+
+    fn months(n: i32) -> ScalarValue {
+        ScalarValue::new_interval_mdn(n, 0, 0)
+    }
+
+    #[test]
+    fn fixed_stride_utc() {
+        let bins = bins(hours(6), "1970-01-01T00:00:00Z", None);
+        assert_eq!(
+            bins.bin(ts("2023-03-26T13:14:15Z")),
+            ts("2023-03-26T12:00:00Z")
+        );
+        assert_eq!(
+            bins.bin(ts("1969-12-31T20:00:00Z")),
+            ts("1969-12-31T18:00:00Z")
+        );
+        assert_eq!(
+            bins.nth(ts("2023-03-26T12:00:00Z"), 3),
+            ts("2023-03-27T06:00:00Z")
+        );
+        assert_eq!(
+            bins.count(ts("2023-03-26T00:00:00Z"), ts("2023-03-27T00:00:00Z")),
+            5
+        );
+    }
+
+    #[test]
+    fn daily_bins_across_dst_start() {
+        let bins = bins(days(1), "1970-01-01T00:00:00Z", Some("Europe/Berlin"));
+
+        // Midnight on 26 March 2023 in Berlin is still CET (+01:00)
+        let day = ts("2023-03-25T23:00:00Z");
+        assert_eq!(bins.bin(ts("2023-03-26T12:00:00Z")), day);
+        assert_eq!(bins.bin(day), day);
+        // The day is only 23 hours long, and the next starts at midnight CEST (+02:00)
+        let next_day = ts("2023-03-26T22:00:00Z");
+        assert_eq!(bins.bin(ts("2023-03-26T21:59:59Z")), day);
+        assert_eq!(bins.bin(next_day), next_day);
+        assert_eq!(bins.nth(day, 1), next_day);
+        assert_eq!(bins.nth(next_day, -1), day);
+        assert_eq!(bins.count(day, ts("2023-03-27T22:00:00Z")), 3);
+    }
+
+    #[test]
+    fn daily_bins_across_dst_end() {
+        let bins = bins(days(1), "1970-01-01T00:00:00Z", Some("Europe/Berlin"));
+
+        // 29 October 2023 starts in CEST (+02:00) and ends in CET (+01:00), so is 25 hours long
+        let day = ts("2023-10-28T22:00:00Z");
+        let next_day = ts("2023-10-29T23:00:00Z");
+        assert_eq!(bins.bin(ts("2023-10-29T22:59:59Z")), day);
+        assert_eq!(bins.bin(ts("2023-10-29T23:00:00Z")), next_day);
+        assert_eq!(bins.nth(day, 1), next_day);
+        assert_eq!(bins.count(day, next_day), 2);
+    }
+
+    #[test]
+    fn hourly_bins_across_dst_transitions() {
+        let bins = bins(hours(1), "1970-01-01T00:00:00Z", Some("Europe/Berlin"));
+
+        // 02:00 to 03:00 does not exist on 26 March 2023, so the bin after
+        // 01:00 CET starts at 03:00 CEST
+        assert_eq!(
+            bins.nth(ts("2023-03-26T00:00:00Z"), 1),
+            ts("2023-03-26T01:00:00Z")
+        );
+        assert_eq!(
+            bins.bin(ts("2023-03-26T01:30:00Z")),
+            ts("2023-03-26T01:00:00Z")
+        );
+
+        // 02:00 to 03:00 happens twice on 29 October 2023, first in CEST (+02:00) and
+        // then in CET (+01:00), and each is a separate bin
+        assert_eq!(
+            bins.bin(ts("2023-10-29T00:30:00Z")),
+            ts("2023-10-29T00:00:00Z")
+        );
+        assert_eq!(
+            bins.bin(ts("2023-10-29T01:30:00Z")),
+            ts("2023-10-29T01:00:00Z")
+        );
+        assert_eq!(
+            bins.nth(ts("2023-10-29T00:00:00Z"), 1),
+            ts("2023-10-29T01:00:00Z")
+        );
+        assert_eq!(
+            bins.count(ts("2023-10-28T23:00:00Z"), ts("2023-10-29T02:00:00Z")),
+            4
+        );
+    }
+
+    #[test]
+    fn hourly_bins_are_aligned_to_the_origin_on_the_wall_clock() {
+        // India is 5:30 ahead of UTC, so bins start on the hour in India
+        let bins = bins(hours(1), "1970-01-01T00:00:00Z", Some("Asia/Kolkata"));
+        assert_eq!(
+            bins.bin(ts("2023-01-01T10:00:00Z")),
+            ts("2023-01-01T09:30:00Z")
+        );
+        assert_eq!(
+            bins.nth(ts("2023-01-01T09:30:00Z"), 2),
+            ts("2023-01-01T11:30:00Z")
+        );
+    }
+
+    #[test]
+    fn calendar_bins() {
+        let month = bins(months(1), "1970-01-01T00:00:00Z", None);
+        assert_eq!(
+            month.bin(ts("2024-02-29T23:59:59Z")),
+            ts("2024-02-01T00:00:00Z")
+        );
+        assert_eq!(
+            month.nth(ts("2024-02-01T00:00:00Z"), 11),
+            ts("2025-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            month.count(ts("2023-11-01T00:00:00Z"), ts("2024-02-01T00:00:00Z")),
+            4
+        );
+
+        let quarter = bins(months(3), "1970-01-01T00:00:00Z", None);
+        assert_eq!(
+            quarter.bin(ts("2023-05-10T00:00:00Z")),
+            ts("2023-04-01T00:00:00Z")
+        );
+        assert_eq!(
+            quarter.bin(ts("1969-12-31T00:00:00Z")),
+            ts("1969-10-01T00:00:00Z")
+        );
+
+        let year = bins(
+            ScalarValue::IntervalYearMonth(Some(12)),
+            "1970-01-01T00:00:00Z",
+            None,
+        );
+        assert_eq!(
+            year.bin(ts("2023-12-31T23:59:59Z")),
+            ts("2023-01-01T00:00:00Z")
+        );
+
+        // Bins start on the day and time of the origin
+        let month = bins(months(1), "1970-01-15T12:00:00Z", None);
+        assert_eq!(
+            month.bin(ts("2023-03-15T11:59:59Z")),
+            ts("2023-02-15T12:00:00Z")
+        );
+        assert_eq!(
+            month.bin(ts("2023-03-15T12:00:00Z")),
+            ts("2023-03-15T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn calendar_bins_in_timezone() {
+        let month = bins(months(1), "1970-01-01T00:00:00Z", Some("Europe/Berlin"));
+        // 00:30 on 1 April in Berlin is in CEST (+02:00), and the month started in CEST
+        assert_eq!(
+            month.bin(ts("2023-03-31T22:30:00Z")),
+            ts("2023-03-31T22:00:00Z")
+        );
+        // March started in CET (+01:00)
+        assert_eq!(
+            month.bin(ts("2023-03-31T21:30:00Z")),
+            ts("2023-02-28T23:00:00Z")
+        );
+        assert_eq!(
+            month.nth(ts("2023-02-28T23:00:00Z"), 1),
+            ts("2023-03-31T22:00:00Z")
+        );
+    }
+
+    #[test]
+    fn invalid_stride() {
+        let err = WallclockBins::try_new(&ScalarValue::new_interval_mdn(1, 1, 0), None, None)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "This feature is not implemented: a stride that mixes months with days or time is not supported"
+        );
+
+        let err = WallclockBins::try_new(&days(0), None, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Execution error: stride must be greater than zero"
+        );
+
+        let err = parse_timezone("Mars/Olympus_Mons").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: invalid timezone: 'Mars/Olympus_Mons'"
+        );
+    }
+
+    #[tokio::test]
+    async fn date_bin_wallclock_udf() -> Result<()> {
+        let times: TimestampNanosecondArray = vec![
+            Some(ts("2023-03-25T22:59:59Z")),
+            Some(ts("2023-03-26T12:00:00Z")),
+            None,
+            Some(ts("2023-03-26T22:00:00Z")),
+        ]
+        .into();
+        let batch = RecordBatch::try_from_iter(vec![("time", Arc::new(times) as ArrayRef)])?;
+        let ctx = context_with_table(batch);
+        let df = ctx.table("t").await?.select(vec![date_bin_wallclock(
+            lit(days(1)),
+            col("time"),
+            lit_timestamp_nano(0),
+            parse_timezone("Europe/Berlin")?,
+        )
+        .alias("day")])?;
+        let res = df.collect().await?;
+        let expected = [
+            "+----------------------+",
+            "| day                  |",
+            "+----------------------+",
+            "| 2023-03-24T23:00:00Z |",
+            "| 2023-03-25T23:00:00Z |",
+            "|                      |",
+            "| 2023-03-26T22:00:00Z |",
+            "+----------------------+",
+        ];
+        assert_batches_eq!(expected, &res);
+        Ok(())
+    }
+}
@@ -18,6 +18,15 @@
 //! in that they don't have normal implementations, but instead
 //! are transformed by logical optimizer rule `HandleGapFill` to
 //! produce a plan that fills gaps.
+//!
+//! `DATE_BIN_GAPFILL` also accepts the name of an IANA timezone after the
+//! origin, and strides of months, quarters or years. In either case timestamps
+//! are binned, and gaps are filled, on the wall clock of the timezone (or UTC)
+//! as described for [`date_bin_wallclock`](crate::date_bin_wallclock):
+//!
+//! ```sql
+//! DATE_BIN_GAPFILL(INTERVAL '1 day', time, '1970-01-01T00:00:00Z', 'Europe/Berlin') AS day
+//! ```
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Field, TimeUnit};
@@ -35,13 +44,30 @@ use schema::InfluxFieldType;
 pub const DATE_BIN_GAPFILL_UDF_NAME: &str = "date_bin_gapfill";
 
 /// (Non-)Implementation of date_bin_gapfill.
-/// This function takes arguments identical to `date_bin()` but
+/// This function takes arguments identical to `date_bin()`, plus
+/// an optional timezone like `date_bin_wallclock()`, but
 /// works in conjunction with the logical optimizer rule
 /// `HandleGapFill` to fill gaps in time series data.
 pub(crate) static DATE_BIN_GAPFILL: Lazy<Arc<ScalarUDF>> = Lazy::new(|| {
     // DATE_BIN_GAPFILL should have the same signature as DATE_BIN,
     // so that just adding _GAPFILL can turn a query into a gap-filling query.
     let mut signatures = BuiltinScalarFunction::DateBin.signature();
+    // Binning on the wall clock of a timezone takes the timezone name
+    // as an additional argument after the origin.
+    if let TypeSignature::OneOf(type_signatures) = &mut signatures.type_signature {
+        let with_timezone: Vec<_> = type_signatures
+            .iter()
+            .filter_map(|sig| match sig {
+                TypeSignature::Exact(args) if args.len() == 3 => {
+                    let mut args = args.clone();
+                    args.push(DataType::Utf8);
+                    Some(TypeSignature::Exact(args))
+                }
+                _ => None,
+            })
+            .collect();
+        type_signatures.extend(with_timezone);
+    }
     // We don't want this to be optimized away before we can give a helpful error message
     signatures.volatility = Volatility::Volatile;
 
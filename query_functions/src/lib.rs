@@ -23,8 +23,12 @@ use datafusion::{
 use group_by::WindowDuration;
 use window::EncodedWindowDuration;
 
+pub mod approx;
+
 pub mod coalesce_struct;
 
+pub mod date_bin_wallclock;
+
 /// Grouping by structs
 pub mod group_by;
 
@@ -37,6 +41,8 @@ pub mod selectors;
 /// window_bounds expressions
 mod window;
 
+pub mod window_functions;
+
 pub mod gapfill;
 
 /// Function registry
@@ -7,7 +7,7 @@ use datafusion::{
 };
 use once_cell::sync::Lazy;
 
-use crate::{gapfill, regex, window};
+use crate::{approx, date_bin_wallclock, gapfill, regex, window, window_functions};
 
 static REGISTRY: Lazy<IOxFunctionRegistry> = Lazy::new(IOxFunctionRegistry::new);
 
@@ -24,6 +24,7 @@ impl IOxFunctionRegistry {
 impl FunctionRegistry for IOxFunctionRegistry {
     fn udfs(&self) -> HashSet<String> {
         [
+            date_bin_wallclock::DATE_BIN_WALLCLOCK_UDF_NAME,
             gapfill::DATE_BIN_GAPFILL_UDF_NAME,
             gapfill::LOCF_UDF_NAME,
             gapfill::INTERPOLATE_UDF_NAME,
@@ -38,6 +39,9 @@ impl FunctionRegistry for IOxFunctionRegistry {
 
     fn udf(&self, name: &str) -> DataFusionResult<Arc<ScalarUDF>> {
         match name {
+            date_bin_wallclock::DATE_BIN_WALLCLOCK_UDF_NAME => {
+                Ok(date_bin_wallclock::DATE_BIN_WALLCLOCK.clone())
+            }
             gapfill::DATE_BIN_GAPFILL_UDF_NAME => Ok(gapfill::DATE_BIN_GAPFILL.clone()),
             gapfill::LOCF_UDF_NAME => Ok(gapfill::LOCF.clone()),
             gapfill::INTERPOLATE_UDF_NAME => Ok(gapfill::INTERPOLATE.clone()),
@@ -51,15 +55,33 @@ impl FunctionRegistry for IOxFunctionRegistry {
     }
 
     fn udaf(&self, name: &str) -> DataFusionResult<Arc<AggregateUDF>> {
-        Err(DataFusionError::Plan(format!(
-            "IOx FunctionRegistry does not contain user defined aggregate function '{name}'"
-        )))
+        match name {
+            approx::APPROX_PERCENTILE_NAME => Ok(approx::APPROX_PERCENTILE.clone()),
+            approx::COUNT_HLL_NAME => Ok(approx::COUNT_HLL.clone()),
+            approx::SUM_HLL_NAME => Ok(approx::SUM_HLL.clone()),
+            _ => Err(DataFusionError::Plan(format!(
+                "IOx FunctionRegistry does not contain user defined aggregate function '{name}'"
+            ))),
+        }
     }
 
     fn udwf(&self, name: &str) -> DataFusionResult<Arc<WindowUDF>> {
-        Err(DataFusionError::Plan(format!(
-            "IOx FunctionRegistry does not contain user defined window function '{name}'"
-        )))
+        use window_functions::{
+            cumulative_sum, derivative, difference, moving_average, CUMULATIVE_SUM, DERIVATIVE,
+            DIFFERENCE, MOVING_AVERAGE, NON_NEGATIVE_DERIVATIVE, NON_NEGATIVE_DIFFERENCE,
+        };
+
+        match name {
+            cumulative_sum::NAME => Ok(CUMULATIVE_SUM.clone()),
+            derivative::NAME => Ok(DERIVATIVE.clone()),
+            derivative::NON_NEGATIVE_NAME => Ok(NON_NEGATIVE_DERIVATIVE.clone()),
+            difference::NAME => Ok(DIFFERENCE.clone()),
+            difference::NON_NEGATIVE_NAME => Ok(NON_NEGATIVE_DIFFERENCE.clone()),
+            moving_average::NAME => Ok(MOVING_AVERAGE.clone()),
+            _ => Err(DataFusionError::Plan(format!(
+                "IOx FunctionRegistry does not contain user defined window function '{name}'"
+            ))),
+        }
     }
 }
 
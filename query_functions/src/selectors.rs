@@ -100,7 +100,7 @@ use std::{fmt::Debug, sync::Arc};
 use arrow::datatypes::DataType;
 use datafusion::{
     error::Result as DataFusionResult,
-    logical_expr::{AccumulatorFactoryFunction, Signature, Volatility},
+    logical_expr::{AccumulatorFactoryFunction, Signature, Volatility, WindowUDF},
     physical_plan::{udaf::AggregateUDF, Accumulator},
     prelude::SessionContext,
 };
@@ -108,6 +108,8 @@ use datafusion::{
 mod internal;
 use internal::{Comparison, Selector, Target};
 
+mod sample;
+
 mod type_handling;
 use type_handling::AggType;
 
@@ -117,6 +119,7 @@ pub fn register_selector_aggregates(ctx: &SessionContext) {
     ctx.register_udaf(selector_last());
     ctx.register_udaf(selector_min());
     ctx.register_udaf(selector_max());
+    ctx.register_udwf(selector_sample());
 }
 
 /// Returns a DataFusion user defined aggregate function for computing
@@ -191,6 +194,31 @@ pub fn selector_max() -> AggregateUDF {
     make_uda("selector_max", FactoryBuilder::new(SelectorType::Max))
 }
 
+/// Returns a DataFusion user defined window function for computing
+/// the sample(n) selector function, returning whether each row of the
+/// partition is one of the `n` rows chosen, uniformly at random, by
+/// reservoir sampling:
+///
+/// ```sql
+/// SELECT time, value FROM (
+///   SELECT time, value,
+///     selector_sample(2) OVER (PARTITION BY host ORDER BY time) AS sampled
+///   FROM cpu WHERE value IS NOT NULL
+/// ) WHERE sampled
+/// ```
+///
+/// If the partition has `n` or fewer rows, every row is selected.
+pub fn selector_sample() -> WindowUDF {
+    sample::make_udwf(None)
+}
+
+/// Returns the [`selector_sample`] window function, with the random
+/// number generator seeded with `seed`, so that the rows chosen are
+/// deterministic.
+pub fn selector_sample_with_seed(seed: u64) -> WindowUDF {
+    sample::make_udwf(Some(seed))
+}
+
 #[derive(Debug, Clone, Copy)]
 enum SelectorType {
     First,
@@ -0,0 +1,193 @@
+//! The sample selector, which selects a random subset of the rows in
+//! a partition using [reservoir sampling].
+//!
+//! [reservoir sampling]: https://en.wikipedia.org/wiki/Reservoir_sampling
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, BooleanArray},
+    datatypes::DataType,
+};
+use datafusion::{
+    common::{DataFusionError, Result, ScalarValue},
+    logical_expr::{
+        PartitionEvaluator, PartitionEvaluatorFactory, ReturnTypeFunction, Signature,
+        TypeSignature, Volatility, WindowUDF,
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The name of the sample selector window function.
+pub(super) const NAME: &str = "selector_sample";
+
+/// Create the sample selector window function. If `seed` is provided
+/// every partition is sampled with a random number generator seeded
+/// with it, so that the selected rows are deterministic.
+pub(super) fn make_udwf(seed: Option<u64>) -> WindowUDF {
+    // The only argument is the number of rows to select.
+    let signature = Signature::one_of(
+        vec![TypeSignature::Exact(vec![DataType::Int64])],
+        Volatility::Volatile,
+    );
+    let return_type: ReturnTypeFunction = Arc::new(|_| Ok(Arc::new(DataType::Boolean)));
+    let partition_evaluator_factory: PartitionEvaluatorFactory = Arc::new(move || {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Box::new(SamplePartitionEvaluator { rng }))
+    });
+
+    WindowUDF::new(NAME, &signature, &return_type, &partition_evaluator_factory)
+}
+
+/// PartitionEvaluator which returns `true` for at most `n` rows of the
+/// partition, chosen uniformly at random, and `false` for the rest.
+///
+/// Every row of the partition is a candidate, so any rows that should
+/// not be selected must be filtered out before evaluating the window
+/// function.
+#[derive(Debug)]
+struct SamplePartitionEvaluator {
+    rng: StdRng,
+}
+
+impl PartitionEvaluator for SamplePartitionEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], num_rows: usize) -> Result<Arc<dyn Array>> {
+        if num_rows == 0 {
+            return Ok(Arc::new(BooleanArray::from(Vec::<bool>::new())));
+        }
+
+        // INVARIANT:
+        // The planner guarantees that the number of rows is a literal.
+        let n = match ScalarValue::try_from_array(&values[0], 0)? {
+            ScalarValue::Int64(Some(n)) if n > 0 => n as usize,
+            v => {
+                return Err(DataFusionError::Execution(format!(
+                    "sample size must be a positive integer, got {v}"
+                )))
+            }
+        };
+
+        Ok(Arc::new(BooleanArray::from(sample(
+            &mut self.rng,
+            n,
+            num_rows,
+        ))))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Select `n` of `num_rows` rows using Algorithm R, returning whether
+/// each row was selected.
+fn sample(rng: &mut impl Rng, n: usize, num_rows: usize) -> Vec<bool> {
+    let mut reservoir: Vec<usize> = (0..n.min(num_rows)).collect();
+    for row in n..num_rows {
+        let idx = rng.gen_range(0..=row);
+        if idx < n {
+            reservoir[idx] = row;
+        }
+    }
+
+    let mut selected = vec![false; num_rows];
+    for row in reservoir {
+        selected[row] = true;
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Int64Array, StringArray},
+        record_batch::RecordBatch,
+    };
+    use datafusion::assert_batches_eq;
+    use datafusion_util::context_with_table;
+
+    use super::*;
+
+    fn count(selected: &[bool]) -> usize {
+        selected.iter().filter(|&&s| s).count()
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(sample(&mut rng, 3, 2), vec![true, true]);
+        assert_eq!(sample(&mut rng, 3, 3), vec![true, true, true]);
+        assert_eq!(count(&sample(&mut rng, 3, 100)), 3);
+        assert_eq!(count(&sample(&mut rng, 1, 100)), 1);
+    }
+
+    #[test]
+    fn test_sample_seeded() {
+        let a = sample(&mut StdRng::seed_from_u64(1), 10, 1_000);
+        let b = sample(&mut StdRng::seed_from_u64(1), 10, 1_000);
+        assert_eq!(a, b);
+        assert_eq!(count(&a), 10);
+    }
+
+    #[test]
+    fn test_sample_distribution() {
+        // Every row should be selected with roughly equal probability.
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = [0_usize; 10];
+        for _ in 0..10_000 {
+            for (row, selected) in sample(&mut rng, 2, 10).into_iter().enumerate() {
+                counts[row] += usize::from(selected);
+            }
+        }
+        for c in counts {
+            assert!((1_700..2_300).contains(&c), "counts: {counts:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sql() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "host",
+                Arc::new(StringArray::from(vec!["a", "a", "a", "a", "a", "b"])) as ArrayRef,
+            ),
+            (
+                "value",
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4, 5, 6])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let ctx = context_with_table(batch);
+        ctx.register_udwf(make_udwf(Some(42)));
+
+        let sql = "SELECT host, count(*) AS n FROM ( \
+                     SELECT host, selector_sample(2) OVER (PARTITION BY host ORDER BY value) AS s \
+                     FROM t \
+                   ) WHERE s GROUP BY host ORDER BY host";
+        let result = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+
+        let expected = vec![
+            "+------+---+",
+            "| host | n |",
+            "+------+---+",
+            "| a    | 2 |",
+            "| b    | 1 |",
+            "+------+---+",
+        ];
+        assert_batches_eq!(&expected, &result);
+
+        // The same rows are selected by every run with the same seed.
+        let sql = "SELECT value FROM ( \
+                     SELECT value, selector_sample(2) OVER (ORDER BY value) AS s FROM t \
+                   ) WHERE s ORDER BY value";
+        let first = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let second = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        assert_eq!(first, second);
+    }
+}
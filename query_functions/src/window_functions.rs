@@ -0,0 +1,281 @@
+//! User defined window functions for analysing time series.
+//!
+//! The implementations are shared by InfluxQL, which plans the functions
+//! with its own argument order, and SQL, which calls the functions
+//! registered by [`register_window_functions`]:
+//!
+//! ```sql
+//! SELECT
+//!   derivative(usage, time, INTERVAL '1 minute') OVER (PARTITION BY host ORDER BY time),
+//!   non_negative_difference(usage) OVER (PARTITION BY host ORDER BY time),
+//!   moving_average(usage, 3) OVER (PARTITION BY host ORDER BY time)
+//! FROM cpu
+//! ```
+use std::sync::Arc;
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{PartitionEvaluatorFactory, ReturnTypeFunction, Signature, WindowUDF},
+    prelude::{Expr, SessionContext},
+};
+use once_cell::sync::Lazy;
+
+pub mod cumulative_sum;
+pub mod derivative;
+pub mod difference;
+pub mod moving_average;
+pub mod non_negative;
+
+/// The numeric types accepted by the window functions.
+static NUMERICS: &[DataType] = &[DataType::Int64, DataType::UInt64, DataType::Float64];
+
+/// The `cumulative_sum(value)` window function.
+pub(crate) static CUMULATIVE_SUM: Lazy<Arc<WindowUDF>> = Lazy::new(|| {
+    sql_window_udf(
+        cumulative_sum::NAME,
+        &cumulative_sum::SIGNATURE,
+        Arc::new(cumulative_sum::return_type),
+        Arc::new(cumulative_sum::partition_evaluator_factory),
+    )
+});
+
+/// The `derivative(value, time[, unit])` window function.
+pub(crate) static DERIVATIVE: Lazy<Arc<WindowUDF>> = Lazy::new(|| {
+    sql_window_udf(
+        derivative::NAME,
+        &derivative::SQL_SIGNATURE,
+        Arc::new(derivative::return_type),
+        Arc::new(derivative::sql_partition_evaluator_factory),
+    )
+});
+
+/// The `difference(value)` window function.
+pub(crate) static DIFFERENCE: Lazy<Arc<WindowUDF>> = Lazy::new(|| {
+    sql_window_udf(
+        difference::NAME,
+        &difference::SIGNATURE,
+        Arc::new(difference::return_type),
+        Arc::new(difference::partition_evaluator_factory),
+    )
+});
+
+/// The `moving_average(value, n)` window function.
+pub(crate) static MOVING_AVERAGE: Lazy<Arc<WindowUDF>> = Lazy::new(|| {
+    sql_window_udf(
+        moving_average::NAME,
+        &moving_average::SIGNATURE,
+        Arc::new(moving_average::return_type),
+        Arc::new(moving_average::partition_evaluator_factory),
+    )
+});
+
+/// The `non_negative_derivative(value, time[, unit])` window function.
+pub(crate) static NON_NEGATIVE_DERIVATIVE: Lazy<Arc<WindowUDF>> = Lazy::new(|| {
+    sql_window_udf(
+        derivative::NON_NEGATIVE_NAME,
+        &derivative::SQL_SIGNATURE,
+        Arc::new(derivative::return_type),
+        Arc::new(|| {
+            Ok(non_negative::wrapper(
+                derivative::sql_partition_evaluator_factory()?,
+            ))
+        }),
+    )
+});
+
+/// The `non_negative_difference(value)` window function.
+pub(crate) static NON_NEGATIVE_DIFFERENCE: Lazy<Arc<WindowUDF>> = Lazy::new(|| {
+    sql_window_udf(
+        difference::NON_NEGATIVE_NAME,
+        &difference::SIGNATURE,
+        Arc::new(difference::return_type),
+        Arc::new(|| {
+            Ok(non_negative::wrapper(
+                difference::partition_evaluator_factory()?,
+            ))
+        }),
+    )
+});
+
+fn sql_window_udf(
+    name: &str,
+    signature: &Signature,
+    return_type: ReturnTypeFunction,
+    partition_evaluator_factory: PartitionEvaluatorFactory,
+) -> Arc<WindowUDF> {
+    Arc::new(WindowUDF::new(
+        name,
+        signature,
+        &return_type,
+        &partition_evaluator_factory,
+    ))
+}
+
+/// registers window functions so they can be invoked via SQL
+pub fn register_window_functions(ctx: &SessionContext) {
+    for udwf in [
+        &CUMULATIVE_SUM,
+        &DERIVATIVE,
+        &DIFFERENCE,
+        &MOVING_AVERAGE,
+        &NON_NEGATIVE_DERIVATIVE,
+        &NON_NEGATIVE_DIFFERENCE,
+    ] {
+        ctx.register_udwf(WindowUDF::clone(udwf));
+    }
+}
+
+/// Checks that the arguments of a call to one of the functions registered
+/// by [`register_window_functions`] that must be constant are literals.
+///
+/// The `unit` of `derivative` and `non_negative_derivative` and the window
+/// size `n` of `moving_average` are read from the first row of each
+/// partition, so any other expression is rejected when the query is planned.
+pub fn check_constant_arguments(fun: &WindowUDF, args: &[Expr]) -> Result<()> {
+    let (idx, arg_name) = if (fun.name == derivative::NAME
+        || fun.name == derivative::NON_NEGATIVE_NAME)
+        && fun.signature == *derivative::SQL_SIGNATURE
+    {
+        (2, "unit")
+    } else if fun.name == moving_average::NAME {
+        (1, "window size")
+    } else {
+        return Ok(());
+    };
+
+    match args.get(idx) {
+        None | Some(Expr::Literal(_)) => Ok(()),
+        Some(arg) => Err(DataFusionError::Plan(format!(
+            "{} {arg_name} must be a constant, got {arg}",
+            fun.name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow::{
+        array::{ArrayRef, Float64Array, StringArray, TimestampNanosecondArray},
+        record_batch::RecordBatch,
+    };
+    use datafusion::assert_batches_eq;
+    use datafusion_util::context_with_table;
+
+    use super::*;
+
+    async fn run_sql(sql: &str) -> Vec<RecordBatch> {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "host",
+                Arc::new(StringArray::from(vec!["a", "a", "a", "a", "b", "b"])) as ArrayRef,
+            ),
+            (
+                "time",
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    0,
+                    60_000_000_000,
+                    120_000_000_000,
+                    180_000_000_000,
+                    0,
+                    60_000_000_000,
+                ])) as ArrayRef,
+            ),
+            (
+                "usage",
+                Arc::new(Float64Array::from(vec![
+                    Some(1.0),
+                    Some(4.0),
+                    None,
+                    Some(7.0),
+                    Some(10.0),
+                    Some(7.0),
+                ])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let ctx = context_with_table(batch);
+        register_window_functions(&ctx);
+        ctx.sql(sql).await.unwrap().collect().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_derivative() {
+        let result = run_sql(
+            "SELECT host, time, \
+               derivative(usage, time) OVER (PARTITION BY host ORDER BY time) AS d, \
+               derivative(usage, time, INTERVAL '1 minute') OVER (PARTITION BY host ORDER BY time) AS d_m, \
+               non_negative_derivative(usage, time, INTERVAL '1 minute') OVER (PARTITION BY host ORDER BY time) AS nnd \
+             FROM t ORDER BY host, time",
+        )
+        .await;
+
+        let expected = vec![
+            "+------+---------------------+-------+------+-----+",
+            "| host | time                | d     | d_m  | nnd |",
+            "+------+---------------------+-------+------+-----+",
+            "| a    | 1970-01-01T00:00:00 |       |      |     |",
+            "| a    | 1970-01-01T00:01:00 | 0.05  | 3.0  | 3.0 |",
+            "| a    | 1970-01-01T00:02:00 |       |      |     |",
+            "| a    | 1970-01-01T00:03:00 | 0.025 | 1.5  | 1.5 |",
+            "| b    | 1970-01-01T00:00:00 |       |      |     |",
+            "| b    | 1970-01-01T00:01:00 | -0.05 | -3.0 |     |",
+            "+------+---------------------+-------+------+-----+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+
+    #[tokio::test]
+    async fn test_difference() {
+        let result = run_sql(
+            "SELECT host, time, \
+               difference(usage) OVER (PARTITION BY host ORDER BY time) AS d, \
+               non_negative_difference(usage) OVER (PARTITION BY host ORDER BY time) AS nnd, \
+               cumulative_sum(usage) OVER (PARTITION BY host ORDER BY time) AS sum \
+             FROM t ORDER BY host, time",
+        )
+        .await;
+
+        let expected = vec![
+            "+------+---------------------+------+-----+------+",
+            "| host | time                | d    | nnd | sum  |",
+            "+------+---------------------+------+-----+------+",
+            "| a    | 1970-01-01T00:00:00 |      |     | 1.0  |",
+            "| a    | 1970-01-01T00:01:00 | 3.0  | 3.0 | 5.0  |",
+            "| a    | 1970-01-01T00:02:00 |      |     |      |",
+            "| a    | 1970-01-01T00:03:00 | 3.0  | 3.0 | 12.0 |",
+            "| b    | 1970-01-01T00:00:00 |      |     | 10.0 |",
+            "| b    | 1970-01-01T00:01:00 | -3.0 |     | 17.0 |",
+            "+------+---------------------+------+-----+------+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+
+    #[tokio::test]
+    async fn test_moving_average() {
+        let result = run_sql(
+            "SELECT host, time, \
+               moving_average(usage, 2) OVER (PARTITION BY host ORDER BY time) AS avg \
+             FROM t ORDER BY host, time",
+        )
+        .await;
+
+        let expected = vec![
+            "+------+---------------------+-----+",
+            "| host | time                | avg |",
+            "+------+---------------------+-----+",
+            "| a    | 1970-01-01T00:00:00 |     |",
+            "| a    | 1970-01-01T00:01:00 | 2.5 |",
+            "| a    | 1970-01-01T00:02:00 |     |",
+            "| a    | 1970-01-01T00:03:00 | 5.5 |",
+            "| b    | 1970-01-01T00:00:00 |     |",
+            "| b    | 1970-01-01T00:01:00 | 8.5 |",
+            "+------+---------------------+-----+",
+        ];
+
+        assert_batches_eq!(&expected, &result);
+    }
+}
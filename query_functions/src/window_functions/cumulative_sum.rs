@@ -1,4 +1,5 @@
-use crate::NUMERICS;
+//! The cumulative sum window function.
+use super::NUMERICS;
 use arrow::array::{Array, ArrayRef};
 use arrow::datatypes::DataType;
 use datafusion::common::{Result, ScalarValue};
@@ -7,10 +8,10 @@ use once_cell::sync::Lazy;
 use std::sync::Arc;
 
 /// The name of the cumulative_sum window function.
-pub(super) const NAME: &str = "cumumlative_sum";
+pub const NAME: &str = "cumulative_sum";
 
 /// Valid signatures for the cumulative_sum window function.
-pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+pub static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
     Signature::one_of(
         NUMERICS
             .iter()
@@ -21,12 +22,12 @@ pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
 });
 
 /// Calculate the return type given the function signature.
-pub(super) fn return_type(sig: &[DataType]) -> Result<Arc<DataType>> {
+pub fn return_type(sig: &[DataType]) -> Result<Arc<DataType>> {
     Ok(Arc::new(sig[0].clone()))
 }
 
 /// Create a new partition_evaluator_factory.
-pub(super) fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+pub fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
     Ok(Box::new(CumulativeSumPartitionEvaluator {}))
 }
 
@@ -0,0 +1,237 @@
+//! The derivative window function.
+use super::NUMERICS;
+use arrow::array::{Array, ArrayRef};
+use arrow::datatypes::{
+    DataType, IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit, TimeUnit,
+};
+use datafusion::common::{DataFusionError, Result, ScalarValue};
+use datafusion::logical_expr::{PartitionEvaluator, Signature, TypeSignature, Volatility};
+use once_cell::sync::Lazy;
+
+use std::sync::Arc;
+
+/// The name of the derivative window function.
+pub const NAME: &str = "derivative";
+
+/// The name of the non-negative derivative window function.
+pub const NON_NEGATIVE_NAME: &str = "non_negative_derivative";
+
+/// The unit of the derivative when none is specified, one second.
+const DEFAULT_UNIT_NANOS: i64 = 1_000_000_000;
+
+/// Valid signatures for the derivative window function, as planned
+/// by InfluxQL.
+///
+/// The arguments are the value, the unit and the time.
+pub static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .map(|dt| {
+                TypeSignature::Exact(vec![
+                    dt.clone(),
+                    DataType::Duration(TimeUnit::Nanosecond),
+                    DataType::Timestamp(TimeUnit::Nanosecond, None),
+                ])
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Valid signatures for the derivative window function when called
+/// from SQL.
+///
+/// The arguments are the value, the time and an optional unit
+/// interval, which defaults to one second.
+pub static SQL_SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+    let time = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    Signature::one_of(
+        NUMERICS
+            .iter()
+            .flat_map(|dt| {
+                [
+                    TypeSignature::Exact(vec![dt.clone(), time.clone()]),
+                    TypeSignature::Exact(vec![
+                        dt.clone(),
+                        time.clone(),
+                        DataType::Interval(IntervalUnit::MonthDayNano),
+                    ]),
+                    TypeSignature::Exact(vec![
+                        dt.clone(),
+                        time.clone(),
+                        DataType::Interval(IntervalUnit::DayTime),
+                    ]),
+                ]
+            })
+            .collect(),
+        Volatility::Immutable,
+    )
+});
+
+/// Calculate the return type given the function signature.
+pub fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+    Ok(Arc::new(DataType::Float64))
+}
+
+/// Create a new partition_evaluator_factory for the InfluxQL argument
+/// order, see [`SIGNATURE`].
+pub fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(DerivativePartitionEvaluator {
+        time_idx: 2,
+        unit_idx: Some(1),
+    }))
+}
+
+/// Create a new partition_evaluator_factory for the SQL argument
+/// order, see [`SQL_SIGNATURE`].
+pub fn sql_partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+    Ok(Box::new(DerivativePartitionEvaluator {
+        time_idx: 1,
+        unit_idx: Some(2),
+    }))
+}
+
+/// PartitionEvaluator which returns the derivative between input values,
+/// in the provided units.
+#[derive(Debug)]
+struct DerivativePartitionEvaluator {
+    /// The index of the argument containing the times of the values.
+    time_idx: usize,
+    /// The index of the argument containing the unit duration, if
+    /// it was provided.
+    unit_idx: Option<usize>,
+}
+
+impl PartitionEvaluator for DerivativePartitionEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], _num_rows: usize) -> Result<Arc<dyn Array>> {
+        let array = Arc::clone(&values[0]);
+        let times = Arc::clone(&values[self.time_idx]);
+
+        // The unit argument specifies the unit duration for the derivation
+        // to use.
+        //
+        // INVARIANT:
+        // The planner guarantees that the unit argument is always a literal.
+        let unit = match self.unit_idx.and_then(|idx| values.get(idx)) {
+            Some(unit) => unit_nanos(&ScalarValue::try_from_array(unit, 0)?)?,
+            None => DEFAULT_UNIT_NANOS,
+        } as f64;
+
+        let mut idx: usize = 0;
+        let mut last: ScalarValue = array.data_type().try_into()?;
+        let mut last_time: ScalarValue = times.data_type().try_into()?;
+        let mut derivative: Vec<ScalarValue> = vec![];
+
+        while idx < array.len() {
+            last = ScalarValue::try_from_array(&array, idx)?;
+            last_time = ScalarValue::try_from_array(&times, idx)?;
+            derivative.push(ScalarValue::Float64(None));
+            idx += 1;
+            if !last.is_null() {
+                break;
+            }
+        }
+        while idx < array.len() {
+            let v = ScalarValue::try_from_array(&array, idx)?;
+            let t = ScalarValue::try_from_array(&times, idx)?;
+            if v.is_null() {
+                derivative.push(ScalarValue::Float64(None));
+            } else {
+                derivative.push(ScalarValue::Float64(Some(
+                    delta(&v, &last)? / (delta_time(&t, &last_time)? / unit),
+                )));
+                last = v.clone();
+                last_time = t.clone();
+            }
+            idx += 1;
+        }
+        Ok(Arc::new(ScalarValue::iter_to_array(derivative)?))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+fn delta(curr: &ScalarValue, prev: &ScalarValue) -> Result<f64> {
+    match (curr, prev) {
+        (ScalarValue::Float64(Some(curr)), ScalarValue::Float64(Some(prev))) => Ok(*curr - *prev),
+        (ScalarValue::Int64(Some(curr)), ScalarValue::Int64(Some(prev))) => {
+            Ok(*curr as f64 - *prev as f64)
+        }
+        (ScalarValue::UInt64(Some(curr)), ScalarValue::UInt64(Some(prev))) => {
+            Ok(*curr as f64 - *prev as f64)
+        }
+        _ => Err(DataFusionError::Internal(
+            "derivative attempted on unsupported values".to_string(),
+        )),
+    }
+}
+
+fn delta_time(curr: &ScalarValue, prev: &ScalarValue) -> Result<f64> {
+    if let (
+        ScalarValue::TimestampNanosecond(Some(curr), _),
+        ScalarValue::TimestampNanosecond(Some(prev), _),
+    ) = (curr, prev)
+    {
+        Ok(*curr as f64 - *prev as f64)
+    } else {
+        Err(DataFusionError::Internal(
+            "derivative attempted on unsupported values".to_string(),
+        ))
+    }
+}
+
+/// Returns the number of nanoseconds in the unit duration `unit`.
+fn unit_nanos(unit: &ScalarValue) -> Result<i64> {
+    let nanos = match unit {
+        ScalarValue::IntervalMonthDayNano(Some(v)) => {
+            let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(*v);
+            (months == 0).then(|| i64::from(days) * 86_400_000_000_000 + nanos)
+        }
+        ScalarValue::IntervalDayTime(Some(v)) => {
+            let (days, millis) = IntervalDayTimeType::to_parts(*v);
+            Some(i64::from(days) * 86_400_000_000_000 + i64::from(millis) * 1_000_000)
+        }
+        ScalarValue::DurationNanosecond(Some(v)) => Some(*v),
+        _ => None,
+    };
+    match nanos {
+        Some(nanos) if nanos > 0 => Ok(nanos),
+        _ => Err(DataFusionError::Execution(format!(
+            "derivative unit must be a positive duration, got {unit}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_nanos() {
+        assert_eq!(
+            unit_nanos(&ScalarValue::new_interval_mdn(0, 0, 1_000_000_000)).unwrap(),
+            1_000_000_000
+        );
+        assert_eq!(
+            unit_nanos(&ScalarValue::new_interval_mdn(0, 1, 0)).unwrap(),
+            86_400_000_000_000
+        );
+        assert_eq!(
+            unit_nanos(&ScalarValue::new_interval_dt(0, 60_000)).unwrap(),
+            60_000_000_000
+        );
+        assert_eq!(
+            unit_nanos(&ScalarValue::DurationNanosecond(Some(10))).unwrap(),
+            10
+        );
+        unit_nanos(&ScalarValue::new_interval_mdn(1, 0, 0)).unwrap_err();
+        unit_nanos(&ScalarValue::new_interval_mdn(0, 0, 0)).unwrap_err();
+    }
+}
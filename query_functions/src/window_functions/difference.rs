@@ -1,4 +1,5 @@
-use crate::NUMERICS;
+//! The difference window function.
+use super::NUMERICS;
 use arrow::array::{Array, ArrayRef};
 use arrow::compute::kernels::numeric::sub_wrapping;
 use arrow::compute::shift;
@@ -9,10 +10,13 @@ use once_cell::sync::Lazy;
 use std::sync::Arc;
 
 /// The name of the difference window function.
-pub(super) const NAME: &str = "difference";
+pub const NAME: &str = "difference";
+
+/// The name of the non-negative difference window function.
+pub const NON_NEGATIVE_NAME: &str = "non_negative_difference";
 
 /// Valid signatures for the difference window function.
-pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+pub static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
     Signature::one_of(
         NUMERICS
             .iter()
@@ -23,12 +27,12 @@ pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
 });
 
 /// Calculate the return type given the function signature.
-pub(super) fn return_type(sig: &[DataType]) -> Result<Arc<DataType>> {
+pub fn return_type(sig: &[DataType]) -> Result<Arc<DataType>> {
     Ok(Arc::new(sig[0].clone()))
 }
 
 /// Create a new partition_evaluator_factory.
-pub(super) fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+pub fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
     Ok(Box::new(DifferencePartitionEvaluator {}))
 }
 
@@ -1,4 +1,5 @@
-use crate::{error, NUMERICS};
+//! The moving average window function.
+use super::NUMERICS;
 use arrow::array::{Array, ArrayRef, Int64Array};
 use arrow::datatypes::DataType;
 use datafusion::common::{downcast_value, DataFusionError, Result, ScalarValue};
@@ -8,10 +9,10 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 
 /// The name of the moving average window function.
-pub(super) const NAME: &str = "moving_average";
+pub const NAME: &str = "moving_average";
 
 /// Valid signatures for the moving average window function.
-pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
+pub static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
     Signature::one_of(
         NUMERICS
             .iter()
@@ -22,12 +23,12 @@ pub(super) static SIGNATURE: Lazy<Signature> = Lazy::new(|| {
 });
 
 /// Calculate the return type given the function signature.
-pub(super) fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
+pub fn return_type(_: &[DataType]) -> Result<Arc<DataType>> {
     Ok(Arc::new(DataType::Float64))
 }
 
 /// Create a new partition_evaluator_factory.
-pub(super) fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
+pub fn partition_evaluator_factory() -> Result<Box<dyn PartitionEvaluator>> {
     Ok(Box::new(AvgNPartitionEvaluator {}))
 }
 
@@ -49,6 +50,11 @@ impl PartitionEvaluator for AvgNPartitionEvaluator {
         // See: FieldChecker::check_moving_average
         let n_values = downcast_value!(&values[1], Int64Array);
         let n = n_values.value(0);
+        if n < 1 {
+            return Err(DataFusionError::Execution(format!(
+                "moving_average window size must be greater than 0, got {n}"
+            )));
+        }
 
         let array = &values[0];
         let mut deq: VecDeque<f64> = VecDeque::new();
@@ -59,10 +65,10 @@ impl PartitionEvaluator for AvgNPartitionEvaluator {
                 ScalarValue::Int64(o) => o.map(|v| v as f64),
                 ScalarValue::UInt64(o) => o.map(|v| v as f64),
                 _ => {
-                    return error::internal(format!(
+                    return Err(DataFusionError::Internal(format!(
                         "unsupported data type for moving_average ({})",
                         array.data_type()
-                    ));
+                    )));
                 }
             };
             match value {
@@ -1,3 +1,4 @@
+//! Filter for window functions that only return non-negative values.
 use arrow::array::Array;
 use arrow::compute::kernels::cmp::lt;
 use arrow::compute::nullif;
@@ -8,9 +9,7 @@ use std::ops::Range;
 use std::sync::Arc;
 
 /// Wrap a PartitionEvaluator in a non-negative filter.
-pub(super) fn wrapper(
-    partition_evaluator: Box<dyn PartitionEvaluator>,
-) -> Box<dyn PartitionEvaluator> {
+pub fn wrapper(partition_evaluator: Box<dyn PartitionEvaluator>) -> Box<dyn PartitionEvaluator> {
     Box::new(NonNegative {
         partition_evaluator,
     })
@@ -42,6 +42,9 @@ pub const TIME_COLUMN_NAME: &str = "time";
 pub const INFLUXQL_MEASUREMENT_COLUMN_NAME: &str = "iox::measurement";
 /// The key identifying the schema-level metadata.
 pub const INFLUXQL_METADATA_KEY: &str = "iox::influxql::group_key::metadata";
+/// The key identifying the schema-level metadata describing the target of an InfluxQL
+/// `SELECT ... INTO` query.
+pub const INFLUXQL_INTO_METADATA_KEY: &str = "iox::influxql::into::metadata";
 
 /// The Timezone to use for InfluxDB timezone (should be a constant)
 #[allow(non_snake_case)]
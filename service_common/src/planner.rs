@@ -3,7 +3,9 @@ use std::sync::Arc;
 
 use bytes::Bytes;
 use datafusion::{
-    arrow::datatypes::SchemaRef, error::DataFusionError, physical_plan::ExecutionPlan,
+    arrow::{datatypes::SchemaRef, record_batch::RecordBatch},
+    error::DataFusionError,
+    physical_plan::ExecutionPlan,
 };
 use flightsql::{FlightSQLCommand, FlightSQLPlanner};
 use iox_query::{
@@ -16,6 +18,7 @@ use iox_query_influxrpc::InfluxRpcPlanner;
 
 pub use datafusion::error::{DataFusionError as Error, Result};
 use iox_query_influxql::frontend::planner::InfluxQLQueryPlanner;
+pub use iox_query_influxql::plan::StatementParams;
 use predicate::rpc_predicate::InfluxRpcPredicate;
 
 /// Query planner that plans queries on a separate threadpool.
@@ -50,17 +53,19 @@ impl Planner {
     }
 
     /// Plan an InfluxQL query against the data in `database`, and return a
-    /// DataFusion physical execution plan.
+    /// DataFusion physical execution plan. Any bind parameters referenced by
+    /// the query are replaced with the values in `params`.
     pub async fn influxql(
         &self,
         query: impl Into<String> + Send,
+        params: StatementParams,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         let planner = InfluxQLQueryPlanner::new();
         let query = query.into();
         let ctx = self.ctx.child_ctx("planner influxql");
 
         self.ctx
-            .run(async move { planner.query(&query, &ctx).await })
+            .run(async move { planner.query(&query, &params, &ctx).await })
             .await
     }
 
@@ -110,6 +115,30 @@ impl Planner {
             .await
     }
 
+    /// Handles a `DoPut` FlightSQL message, as described on
+    /// [`FlightSQLPlanner::do_put`], on a separate threadpool
+    pub async fn flight_sql_do_put<N>(
+        &self,
+        namespace_name: impl Into<String> + Send,
+        namespace: Arc<N>,
+        cmd: FlightSQLCommand,
+        data: Vec<RecordBatch>,
+    ) -> Result<Bytes>
+    where
+        N: QueryNamespace + 'static,
+    {
+        let namespace_name = namespace_name.into();
+        let ctx = self.ctx.child_ctx("planner flight_sql_do_put");
+
+        self.ctx
+            .run(async move {
+                FlightSQLPlanner::do_put(namespace_name, namespace, cmd, data, &ctx)
+                    .await
+                    .map_err(DataFusionError::from)
+            })
+            .await
+    }
+
     /// Returns the [`SchemaRef`] to be included in the response to a
     /// `GetFlightInfo` FlightSQL message as described on
     /// [`FlightSQLPlanner::get_schema`], on a separate threadpool.
@@ -23,9 +23,11 @@ use workspace_hack as _;
 mod keep_alive;
 mod request;
 
-use arrow::error::ArrowError;
+use arrow::{error::ArrowError, record_batch::RecordBatch};
 use arrow_flight::{
+    decode::FlightRecordBatchStream,
     encode::FlightDataEncoderBuilder,
+    error::FlightError,
     flight_descriptor::DescriptorType,
     flight_service_server::{FlightService as Flight, FlightServiceServer as FlightServer},
     Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
@@ -34,14 +36,21 @@ use arrow_flight::{
 use authz::{extract_token, Authorizer};
 use data_types::NamespaceNameError;
 use datafusion::{error::DataFusionError, physical_plan::ExecutionPlan};
-use flightsql::FlightSQLCommand;
+use flightsql::{
+    CommandStatementIngest, DoPutUpdateResult, FlightSQLCommand, TableDefinitionOptions,
+};
 use futures::{ready, Stream, StreamExt, TryStreamExt};
 use generated_types::influxdata::iox::querier::v1 as proto;
 use iox_query::{exec::IOxSessionContext, QueryCompletedToken, QueryNamespace};
 use observability_deps::tracing::{debug, info, warn};
 use prost::Message;
 use request::{IoxGetRequest, RunQuery};
-use service_common::{datafusion_error_to_tonic_code, planner::Planner, QueryNamespaceProvider};
+use schema::INFLUXQL_INTO_METADATA_KEY;
+use service_common::{
+    datafusion_error_to_tonic_code,
+    planner::{Planner, StatementParams},
+    QueryNamespaceProvider,
+};
 use snafu::{OptionExt, ResultExt, Snafu};
 use std::{
     fmt::Debug,
@@ -85,6 +94,27 @@ pub enum Error {
     #[snafu(display("Invalid handshake. No payload provided"))]
     InvalidHandshake {},
 
+    #[snafu(display("Invalid DoPut request. No FlightDescriptor provided"))]
+    MissingFlightDescriptor {},
+
+    #[snafu(display("Invalid DoPut path {:?}. Expected [database, table] or [table]", path))]
+    InvalidIngestPath { path: Vec<String> },
+
+    #[snafu(display("Writing data with DoPut is not supported by this server"))]
+    IngestNotSupported,
+
+    #[snafu(display("Table '{}' not found in database '{}'", table, namespace_name))]
+    TableNotFound {
+        namespace_name: String,
+        table: String,
+    },
+
+    #[snafu(display("Table '{}' already exists in database '{}'", table, namespace_name))]
+    TableAlreadyExists {
+        namespace_name: String,
+        table: String,
+    },
+
     #[snafu(display("Database '{}' not found", namespace_name))]
     DatabaseNotFound { namespace_name: String },
 
@@ -130,6 +160,15 @@ pub enum Error {
         source: service_common::planner::Error,
     },
 
+    #[snafu(display(
+        "SELECT ... INTO writes data and is not supported by DoGet, \
+    use the InfluxQL HTTP query API with POST instead"
+    ))]
+    SelectIntoNotSupported {
+        namespace_name: String,
+        query: String,
+    },
+
     #[snafu(display("Error while planning Flight SQL : {}", source))]
     FlightSQL { source: flightsql::Error },
 
@@ -163,9 +202,15 @@ impl From<Error> for tonic::Status {
             Error::DatabaseNotFound { .. }
             | Error::InvalidTicket { .. }
             | Error::InvalidHandshake { .. }
+            | Error::MissingFlightDescriptor { .. }
+            | Error::InvalidIngestPath { .. }
+            | Error::IngestNotSupported
+            | Error::TableNotFound { .. }
+            | Error::TableAlreadyExists { .. }
             | Error::Unauthenticated { .. }
             | Error::PermissionDenied { .. }
             | Error::InvalidDatabaseName { .. }
+            | Error::SelectIntoNotSupported { .. }
             | Error::Query { .. } => info!(e=%err, %namespace, %query, msg),
             Error::Optimize { .. }
             | Error::EncodeSchema { .. }
@@ -192,20 +237,27 @@ impl Error {
         let msg = self.to_string();
 
         let code = match self {
-            Self::DatabaseNotFound { .. } => tonic::Code::NotFound,
+            Self::DatabaseNotFound { .. } | Self::TableNotFound { .. } => tonic::Code::NotFound,
+            Self::TableAlreadyExists { .. } => tonic::Code::AlreadyExists,
             Self::InvalidTicket { .. }
             | Self::InvalidHandshake { .. }
+            | Self::MissingFlightDescriptor { .. }
+            | Self::InvalidIngestPath { .. }
             | Self::Deserialization { .. }
             | Self::TooManyFlightSQLDatabases { .. }
             | Self::NoFlightSQLDatabase
             | Self::InvalidDatabaseHeader { .. }
-            | Self::InvalidDatabaseName { .. } => tonic::Code::InvalidArgument,
+            | Self::InvalidDatabaseName { .. }
+            | Self::SelectIntoNotSupported { .. } => tonic::Code::InvalidArgument,
             Self::Planning { source, .. } | Self::Query { source, .. } => {
                 datafusion_error_to_tonic_code(&source)
             }
-            Self::UnsupportedMessageType { .. } => tonic::Code::Unimplemented,
+            Self::UnsupportedMessageType { .. } | Self::IngestNotSupported => {
+                tonic::Code::Unimplemented
+            }
             Self::FlightSQL { source } => match source {
                 flightsql::Error::InvalidHandle { .. }
+                | flightsql::Error::InvalidParameters { .. }
                 | flightsql::Error::Decode { .. }
                 | flightsql::Error::Protocol { .. }
                 | flightsql::Error::UnsupportedMessageType { .. } => tonic::Code::InvalidArgument,
@@ -234,6 +286,9 @@ impl Error {
             Error::InvalidTicket { .. }
             | Error::InternalCreatingTicket { .. }
             | Error::InvalidHandshake {}
+            | Error::MissingFlightDescriptor {}
+            | Error::InvalidIngestPath { .. }
+            | Error::IngestNotSupported
             | Error::TooManyFlightSQLDatabases { .. }
             | Error::NoFlightSQLDatabase
             | Error::InvalidDatabaseHeader { .. }
@@ -247,8 +302,11 @@ impl Error {
             | Error::PermissionDenied
             | Error::Authz { .. } => "<unknown>",
             Error::DatabaseNotFound { namespace_name } => namespace_name,
+            Error::TableNotFound { namespace_name, .. } => namespace_name,
+            Error::TableAlreadyExists { namespace_name, .. } => namespace_name,
             Error::Query { namespace_name, .. } => namespace_name,
             Error::Planning { namespace_name, .. } => namespace_name,
+            Error::SelectIntoNotSupported { namespace_name, .. } => namespace_name,
         }
     }
 
@@ -258,6 +316,11 @@ impl Error {
             Error::InvalidTicket { .. }
             | Error::InternalCreatingTicket { .. }
             | Error::InvalidHandshake {}
+            | Error::MissingFlightDescriptor {}
+            | Error::InvalidIngestPath { .. }
+            | Error::IngestNotSupported
+            | Error::TableNotFound { .. }
+            | Error::TableAlreadyExists { .. }
             | Error::TooManyFlightSQLDatabases { .. }
             | Error::NoFlightSQLDatabase
             | Error::InvalidDatabaseHeader { .. }
@@ -273,6 +336,7 @@ impl Error {
             | Error::DatabaseNotFound { .. } => "NONE",
             Error::Query { query, .. } => query,
             Error::Planning { query, .. } => query,
+            Error::SelectIntoNotSupported { query, .. } => query,
         }
     }
 
@@ -302,6 +366,23 @@ impl From<authz::Error> for Error {
 
 type TonicStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send + 'static>>;
 
+/// Writes the record batches sent with `DoPut` ingest requests. See
+/// [`FlightService`] for the requests that are supported.
+#[tonic::async_trait]
+pub trait BatchWriter: Debug + Send + Sync + 'static {
+    /// Returns true if `table` exists in the database `namespace_name`.
+    async fn table_exists(&self, namespace_name: &str, table: &str) -> bool;
+
+    /// Writes `batch` into `table` of the database `namespace_name`,
+    /// creating the table or adding any new columns as needed.
+    async fn write_batch(
+        &self,
+        namespace_name: &str,
+        table: &str,
+        batch: RecordBatch,
+    ) -> Result<(), tonic::Status>;
+}
+
 /// Concrete implementation of the IOx client protocol, implemented as
 /// a gRPC [Arrow Flight] Service API
 ///
@@ -411,7 +492,23 @@ type TonicStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send
 ///       ┃                                                  ┃
 /// ```
 ///
-/// ## FlightSQL Prepared Statement (no bind parameters like $1, etc)
+/// # Ingest
+///
+/// If the server is created with a [`BatchWriter`], record batches can
+/// be written with the `DoPut` method, using a [`FlightDescriptor`]
+/// that either
+///
+/// 1. Has the path `[database, table]`, or `[table]` with the database
+/// in the same header as for FlightSQL requests, or
+///
+/// 2. Contains a FlightSQL `CommandStatementIngest` naming the table,
+/// with the database in the header.
+///
+/// Each batch is written as it arrives, and acknowledged with a
+/// [`PutResult`] whose `app_metadata` contains a `DoPutUpdateResult`
+/// with the number of rows written.
+///
+/// ## FlightSQL Prepared Statement
 ///
 /// To run a prepared query, via FlightSQL, the client undertakes a
 /// few more steps:
@@ -422,7 +519,15 @@ type TonicStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send
 /// 2. Call `DoAction` method with the the request
 ///
 /// 3. Receive a `ActionCreatePreparedStatementResponse`, which contains
-/// a prepared statement "handle".
+/// a prepared statement "handle", and the schema of any bind
+/// parameters, such as `$1`, in the query.
+///
+/// If the query has bind parameters, the client calls the `DoPut`
+/// method with a [`FlightDescriptor`] containing the handle in a
+/// `CommandPreparedStatementQuery`, and a single row of parameter
+/// values. IOx returns a `DoPutPreparedStatementResult` with a new
+/// handle that includes the values, which the client uses in the
+/// following steps.
 ///
 /// 4. Encode the handle in a `CommandPreparedStatementQuery`
 /// FlightSQL structure in a [`FlightDescriptor`] and call the
@@ -470,16 +575,22 @@ where
 {
     server: Arc<S>,
     authz: Option<Arc<dyn Authorizer>>,
+    writer: Option<Arc<dyn BatchWriter>>,
 }
 
 pub fn make_server<S>(
     server: Arc<S>,
     authz: Option<Arc<dyn Authorizer>>,
+    writer: Option<Arc<dyn BatchWriter>>,
 ) -> FlightServer<impl Flight>
 where
     S: QueryNamespaceProvider,
 {
-    FlightServer::new(FlightService { server, authz })
+    FlightServer::new(FlightService {
+        server,
+        authz,
+        writer,
+    })
 }
 
 impl<S> FlightService<S>
@@ -532,12 +643,26 @@ where
                     Box::new(sql_query.clone()),
                 );
                 let plan = Planner::new(&ctx)
-                    .influxql(sql_query)
+                    .influxql(sql_query, StatementParams::default())
                     .await
                     .context(PlanningSnafu {
                         namespace_name: &namespace_name,
                         query: query.to_string(),
                     })?;
+                // The results of a `SELECT ... INTO` statement must be written rather than
+                // returned, which a read-only `DoGet` request can not do.
+                if plan
+                    .schema()
+                    .metadata()
+                    .contains_key(INFLUXQL_INTO_METADATA_KEY)
+                {
+                    return Err(SelectIntoNotSupportedSnafu {
+                        namespace_name: &namespace_name,
+                        query: query.to_string(),
+                    }
+                    .build()
+                    .into());
+                }
                 (token, plan)
             }
             RunQuery::FlightSQL(msg) => {
@@ -584,6 +709,50 @@ where
 
         Ok(Response::new(Box::pin(output) as TonicStream<FlightData>))
     }
+
+    /// Implementation of `DoPut` for ingest requests, which writes each
+    /// record batch into `table` as it arrives
+    async fn run_do_put_ingest(
+        &self,
+        namespace_name: String,
+        table: String,
+        options: Option<TableDefinitionOptions>,
+        authz_token: Option<Vec<u8>>,
+        batches: FlightRecordBatchStream,
+    ) -> Result<Response<TonicStream<PutResult>>, tonic::Status> {
+        let writer = Arc::clone(self.writer.as_ref().context(IngestNotSupportedSnafu)?);
+
+        let perms = vec![authz::Permission::ResourceAction(
+            authz::Resource::Database(namespace_name.clone()),
+            authz::Action::Write,
+        )];
+        self.authz
+            .permissions(authz_token, &perms)
+            .await
+            .map_err(Error::from)?;
+
+        if let Some(options) = options {
+            check_table_definition_options(writer.as_ref(), &namespace_name, &table, options)
+                .await?;
+        }
+
+        let output = batches.map_err(tonic::Status::from).and_then(move |batch| {
+            let writer = Arc::clone(&writer);
+            let namespace_name = namespace_name.clone();
+            let table = table.clone();
+            async move {
+                let record_count = batch.num_rows() as i64;
+                writer.write_batch(&namespace_name, &table, batch).await?;
+
+                let result = DoPutUpdateResult { record_count };
+                Ok(PutResult {
+                    app_metadata: result.encode_to_vec().into(),
+                })
+            }
+        });
+
+        Ok(Response::new(output.boxed()))
+    }
 }
 
 #[tonic::async_trait]
@@ -811,13 +980,110 @@ where
         Ok(tonic::Response::new(flight_info))
     }
 
+    /// Handles `DoPut` RPC requests. The [`FlightDescriptor`] of the
+    /// first message is treated as either the path of a table to write
+    /// to, or as containing a FlightSQL command, encoded as a binary
+    /// ProtoBuf message, and the messages are decoded as the record
+    /// batches for that command.
+    ///
+    /// see [`FlightService`] for more details.
     async fn do_put(
         &self,
-        _request: Request<Streaming<FlightData>>,
+        request: Request<Streaming<FlightData>>,
     ) -> Result<Response<Self::DoPutStream>, tonic::Status> {
-        info!("Handling flightsql do_put body");
+        let external_span_ctx: Option<RequestLogContext> = request.extensions().get().cloned();
+        let span_ctx: Option<SpanContext> = request.extensions().get().cloned();
+        let trace = external_span_ctx.format_jaeger();
+        let is_debug = has_debug_header(request.metadata());
+
+        let namespace_name = get_flightsql_namespace(request.metadata());
+        let authz_token = get_flight_authz(request.metadata());
+        let mut stream = request.into_inner();
+
+        // extract the descriptor from the first message
+        let first = stream
+            .message()
+            .await?
+            .context(MissingFlightDescriptorSnafu)?;
+        let flight_descriptor = first
+            .flight_descriptor
+            .clone()
+            .context(MissingFlightDescriptorSnafu)?;
+        let batches = FlightRecordBatchStream::new_from_flight_data(
+            futures::stream::once(async { Ok(first) })
+                .chain(stream)
+                .map_err(FlightError::Tonic),
+        );
+
+        if flight_descriptor.r#type() == DescriptorType::Path {
+            let (namespace_name, table) = match flight_descriptor.path.as_slice() {
+                [namespace_name, table] => (namespace_name.clone(), table.clone()),
+                [table] => (namespace_name?, table.clone()),
+                path => {
+                    return Err(InvalidIngestPathSnafu { path }.build().into());
+                }
+            };
+            info!(%namespace_name, %table, %trace, "DoPut ingest request");
+            return self
+                .run_do_put_ingest(namespace_name, table, None, authz_token, batches)
+                .await;
+        }
+
+        let namespace_name = namespace_name?;
+        let cmd = cmd_from_descriptor(flight_descriptor)?;
+
+        info!(%namespace_name, %cmd, %trace, "DoPut request");
+
+        if let FlightSQLCommand::CommandStatementIngest(CommandStatementIngest {
+            table,
+            table_definition_options,
+            ..
+        }) = cmd
+        {
+            return self
+                .run_do_put_ingest(
+                    namespace_name,
+                    table,
+                    table_definition_options,
+                    authz_token,
+                    batches,
+                )
+                .await;
+        }
+
+        let perms = flightsql_permissions(&namespace_name, &cmd);
+        self.authz
+            .permissions(authz_token, &perms)
+            .await
+            .map_err(Error::from)?;
 
-        Err(tonic::Status::unimplemented("Not yet implemented: do_put"))
+        let db = self
+            .server
+            .db(
+                &namespace_name,
+                span_ctx.child_span("get namespace"),
+                is_debug,
+            )
+            .await
+            .context(DatabaseNotFoundSnafu {
+                namespace_name: &namespace_name,
+            })?;
+
+        let data: Vec<_> = batches.try_collect().await?;
+
+        let ctx = db.new_query_context(span_ctx);
+        let app_metadata = Planner::new(&ctx)
+            .flight_sql_do_put(&namespace_name, db, cmd.clone(), data)
+            .await
+            .context(PlanningSnafu {
+                namespace_name: &namespace_name,
+                query: format!("{cmd:?}"),
+            })?;
+
+        let result = PutResult { app_metadata };
+        let stream = futures::stream::iter([Ok(result)]);
+
+        Ok(Response::new(stream.boxed()))
     }
 
     async fn do_action(
@@ -905,6 +1171,38 @@ fn cmd_from_descriptor(flight_descriptor: FlightDescriptor) -> Result<FlightSQLC
     }
 }
 
+/// Checks the [`TableDefinitionOptions`] of a FlightSQL ingest request
+/// against whether the table already exists. Replacing a table is not
+/// supported.
+async fn check_table_definition_options(
+    writer: &dyn BatchWriter,
+    namespace_name: &str,
+    table: &str,
+    options: TableDefinitionOptions,
+) -> Result<()> {
+    const IF_NOT_EXIST_FAIL: i32 = 2;
+    const IF_EXISTS_FAIL: i32 = 1;
+    const IF_EXISTS_REPLACE: i32 = 3;
+
+    let exists = writer.table_exists(namespace_name, table).await;
+    match (exists, options.if_not_exist, options.if_exists) {
+        (false, IF_NOT_EXIST_FAIL, _) => TableNotFoundSnafu {
+            namespace_name,
+            table,
+        }
+        .fail(),
+        (true, _, IF_EXISTS_FAIL) => TableAlreadyExistsSnafu {
+            namespace_name,
+            table,
+        }
+        .fail(),
+        (true, _, IF_EXISTS_REPLACE) => Err(Error::unsupported_message_type(
+            "CommandStatementIngest replacing an existing table",
+        )),
+        _ => Ok(()),
+    }
+}
+
 /// Figure out the database for this request by checking
 /// the "database=database_or_bucket_name" (preferred)
 /// or "bucket=database_or_bucket_name"
@@ -968,6 +1266,7 @@ fn flightsql_permissions(namespace_name: &str, cmd: &FlightSQLCommand) -> Vec<au
         FlightSQLCommand::CommandGetXdbcTypeInfo(_) => authz::Action::ReadSchema,
         FlightSQLCommand::ActionCreatePreparedStatementRequest(_) => authz::Action::Read,
         FlightSQLCommand::ActionClosePreparedStatementRequest(_) => authz::Action::Read,
+        FlightSQLCommand::CommandStatementIngest(_) => authz::Action::Write,
     };
     vec![authz::Permission::ResourceAction(resource, action)]
 }
@@ -1105,6 +1404,7 @@ mod tests {
         let service = FlightService {
             server: Arc::clone(&test_storage),
             authz: Option::<Arc<dyn Authorizer>>::None,
+            writer: None,
         };
         let ticket = Ticket {
             ticket: br#"{"namespace_name": "my_db", "sql_query": "SELECT 1;"}"#
@@ -1268,6 +1568,7 @@ mod tests {
         let svc = FlightService {
             server: Arc::clone(&test_storage),
             authz: Some(Arc::new(MockAuthorizer {})),
+            writer: None,
         };
 
         async fn assert_code(
@@ -1376,6 +1677,7 @@ mod tests {
         let svc = FlightService {
             server: Arc::clone(&test_storage),
             authz: Some(Arc::new(MockAuthorizer {})),
+            writer: None,
         };
 
         async fn assert_code(
@@ -1412,4 +1714,51 @@ mod tests {
         assert_code(&svc, tonic::Code::PermissionDenied, request("Bearer BAD")).await;
         assert_code(&svc, tonic::Code::Internal, request("Bearer UGLY")).await;
     }
+
+    #[tokio::test]
+    async fn ingest_table_definition_options() {
+        #[derive(Debug)]
+        struct MockWriter;
+
+        #[tonic::async_trait]
+        impl BatchWriter for MockWriter {
+            async fn table_exists(&self, _namespace_name: &str, table: &str) -> bool {
+                table == "cpu"
+            }
+
+            async fn write_batch(
+                &self,
+                _namespace_name: &str,
+                _table: &str,
+                _batch: RecordBatch,
+            ) -> Result<(), tonic::Status> {
+                Ok(())
+            }
+        }
+
+        async fn code(table: &str, if_not_exist: i32, if_exists: i32) -> tonic::Code {
+            let options = TableDefinitionOptions {
+                if_not_exist,
+                if_exists,
+            };
+            match check_table_definition_options(&MockWriter, "bananas", table, options).await {
+                Ok(()) => tonic::Code::Ok,
+                Err(e) => tonic::Status::from(e).code(),
+            }
+        }
+
+        // unspecified, create or append
+        for (if_not_exist, if_exists) in [(0, 0), (1, 2)] {
+            assert_eq!(code("cpu", if_not_exist, if_exists).await, tonic::Code::Ok);
+            assert_eq!(code("mem", if_not_exist, if_exists).await, tonic::Code::Ok);
+        }
+        // fail if the table does not exist
+        assert_eq!(code("cpu", 2, 0).await, tonic::Code::Ok);
+        assert_eq!(code("mem", 2, 0).await, tonic::Code::NotFound);
+        // fail if the table exists
+        assert_eq!(code("cpu", 0, 1).await, tonic::Code::AlreadyExists);
+        assert_eq!(code("mem", 0, 1).await, tonic::Code::Ok);
+        // replacing a table is not supported
+        assert_eq!(code("cpu", 0, 3).await, tonic::Code::Unimplemented);
+    }
 }